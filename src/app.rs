@@ -1,40 +1,257 @@
-use crate::export::{DiagramExporter, MermaidExporter, PlantUmlExporter};
-use crate::model::{ContainerType, Diagram, DiagramType, Element, ElementType, Position, Relationship};
-use crate::ui::canvas::Canvas;
+use crate::export::{adr, docs, embed};
+use crate::export::poster::{self, PageSize, PosterOptions};
+use crate::export::render;
+use crate::export::{DiagramExporter, ExportFormat, MermaidExporter, PlantUmlExporter, PlantUmlOptions, StructurizrExporter, SvgExporter};
+use crate::clipboard;
+use crate::history::History;
+use crate::model::{
+    Change, Diagram, DiagramEvent, MergeStrategy, DiagramType, Element, ElementType, IconPack, LineStyle, Position, Relationship, RelationshipGraph, Severity, Size, TextLabel, View,
+    Snippet, SystemKind, validation, COMPRESSED_FILE_EXTENSION, FILE_EXTENSION, SNIPPET_FILE_EXTENSION, YAML_FILE_EXTENSIONS,
+};
+use crate::preferences::{CatalogEntry, ElementTemplate, Preferences, Typography};
+use crate::style::{ElementKind, Stylesheet, STYLESHEET_FILE_EXTENSION};
+use crate::project::{self, Project};
+use crate::ui::api_suggestions::ApiSuggestions;
+use crate::ui::canvas::{Canvas, Comparison, Impact, RelationshipEnd};
+use crate::ui::comments::CommentsPanel;
+use crate::ui::dependency_matrix::DependencyMatrix;
+use crate::ui::element_table::ElementTable;
+use crate::ui::keymap::{self, Command};
+use crate::ui::metrics_panel::MetricsPanel;
+use crate::ui::relationship_list::RelationshipList;
 use eframe::egui;
+use std::borrow::Cow;
 use egui::{CentralPanel, Color32, Context, Id, SidePanel, TopBottomPanel};
 
+/// How far pasted elements are moved from where they were copied, so they don't hide the originals
+const PASTE_OFFSET: f32 = 20.0;
+
+/// Shown in the Edit menu; the platform delivers these shortcuts as copy and paste events
+const COPY_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::C);
+const PASTE_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::V);
+
+/// An open document other than the active one
+struct ParkedTab {
+    diagram: Diagram,
+    history: History,
+    file_path: Option<std::path::PathBuf>,
+    unsaved_changes: bool,
+    selected_element: Option<crate::model::ElementId>,
+    offset: egui::Vec2,
+    scale: f32,
+}
+
+/// What the central panel shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MainView {
+    Canvas,
+    DependencyMatrix,
+    Table,
+}
+
+/// A keyboard request to move the canvas selection
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Navigation {
+    /// Tab: next element in reading order
+    Next,
+    /// Shift+Tab: previous element in reading order
+    Previous,
+    /// Arrow key: nearest element in that direction
+    Toward(egui::Vec2),
+}
+
 /// Main application state
 pub struct C2DrawApp {
     diagram: Diagram,
+    history: History,
     canvas: Canvas,
     selected_element: Option<crate::model::ElementId>,
     file_path: Option<std::path::PathBuf>,
     show_export_window: bool,
     export_content: String,
-    export_title: String,
+    export_format: ExportFormat,
+    plantuml_options: PlantUmlOptions,
+    preview_png: Option<Vec<u8>>,
+    preview_texture: Option<egui::TextureHandle>,
+    preview_error: Option<String>,
+    preferences: Preferences,
+    show_preferences_window: bool,
+    show_diagram_properties: bool,
+    /// Revision note being written in the diagram properties window
+    revision_note: String,
+    show_poster_window: bool,
+    poster_options: PosterOptions,
+    show_dsl_editor: bool,
+    show_relationship_list: bool,
+    relationship_list: RelationshipList,
+    show_metrics_panel: bool,
+    metrics_panel: MetricsPanel,
+    /// Highlight what the selected element reaches through its relationships
+    show_impact: bool,
+    /// Outline elements and relationships on dependency cycles in red
+    show_cycles: bool,
+    /// Keyboard shortcut cheat-sheet, toggled with ?
+    show_shortcuts_window: bool,
+    /// Also highlight what reaches the selected element
+    impact_upstream: bool,
+    main_view: MainView,
+    /// Navigation keys taken from this frame's input, applied in `update`
+    navigation: Vec<Navigation>,
+    dependency_matrix: DependencyMatrix,
+    element_table: ElementTable,
+    comments_panel: CommentsPanel,
+    /// Focus and select the name field on the next properties panel render
+    focus_name_field: bool,
+    quick_add_input: String,
+    /// Create elements named in a quick-add command that do not exist yet
+    quick_add_create_missing: bool,
+    quick_add_error: Option<String>,
+    dsl_text: String,
+    dsl_error: Option<String>,
+    show_share_link_window: bool,
+    share_link_input: String,
+    share_link_error: Option<String>,
+    show_backups_window: bool,
+    backups: Vec<crate::backup::Backup>,
+    show_snippet_window: bool,
+    show_api_suggestions_window: bool,
+    api_suggestions: ApiSuggestions,
+    show_validation_window: bool,
+    /// Other version of the diagram whose differences are highlighted, and its file
+    compare_base: Option<(std::path::PathBuf, Diagram)>,
+    /// Elements ticked for the next snippet export
+    snippet_selection: std::collections::HashSet<crate::model::ElementId>,
+    show_group_window: bool,
+    show_views_window: bool,
+    show_stats_window: bool,
+    /// Elements ticked for the next group, and its name
+    group_selection: std::collections::HashSet<crate::model::ElementId>,
+    group_name: String,
+    /// Text label open in the label editor
+    editing_label: Option<uuid::Uuid>,
+    error_message: Option<String>,
+    /// Whether the diagram changed since it was last opened or saved
+    unsaved_changes: bool,
+    /// Window title last sent to the viewport
+    window_title: String,
+    /// Folder opened with File > Open Folder
+    project: Option<Project>,
+    /// Open documents in tab order; the active one is `None` here and lives
+    /// in the fields above
+    tabs: Vec<Option<ParkedTab>>,
+    active_tab: usize,
+    /// Tab with unsaved changes waiting for close confirmation
+    pending_close_tab: Option<usize>,
 }
 
 impl Default for C2DrawApp {
     fn default() -> Self {
         let mut app = Self {
             diagram: Diagram::default(),
+            history: History::new(&Diagram::default()),
             canvas: Canvas::new(),
             selected_element: None,
             file_path: None,
             show_export_window: false,
             export_content: String::new(),
-            export_title: String::new(),
+            export_format: ExportFormat::PlantUml,
+            plantuml_options: PlantUmlOptions::default(),
+            preview_png: None,
+            preview_texture: None,
+            preview_error: None,
+            preferences: Preferences::default(),
+            show_preferences_window: false,
+            show_diagram_properties: false,
+            revision_note: String::new(),
+            show_poster_window: false,
+            poster_options: PosterOptions::default(),
+            show_dsl_editor: false,
+            show_relationship_list: false,
+            relationship_list: RelationshipList::new(),
+            show_metrics_panel: false,
+            metrics_panel: MetricsPanel::new(),
+            show_impact: false,
+            show_cycles: false,
+            show_shortcuts_window: false,
+            impact_upstream: false,
+            main_view: MainView::Canvas,
+            navigation: Vec::new(),
+            dependency_matrix: DependencyMatrix::new(),
+            element_table: ElementTable::new(),
+            comments_panel: CommentsPanel::new(),
+            focus_name_field: false,
+            quick_add_input: String::new(),
+            quick_add_create_missing: true,
+            quick_add_error: None,
+            dsl_text: String::new(),
+            dsl_error: None,
+            show_share_link_window: false,
+            share_link_input: String::new(),
+            share_link_error: None,
+            show_backups_window: false,
+            backups: Vec::new(),
+            show_snippet_window: false,
+            show_api_suggestions_window: false,
+            api_suggestions: ApiSuggestions::new(),
+            show_validation_window: false,
+            compare_base: None,
+            snippet_selection: std::collections::HashSet::new(),
+            show_group_window: false,
+            show_views_window: false,
+            show_stats_window: false,
+            group_selection: std::collections::HashSet::new(),
+            group_name: String::new(),
+            editing_label: None,
+            error_message: None,
+            unsaved_changes: false,
+            window_title: String::new(),
+            project: None,
+            tabs: vec![None],
+            active_tab: 0,
+            pending_close_tab: None,
         };
         // Add some example elements
         app.add_example_elements();
+        app.diagram.take_events();
+        app.history = History::new(&app.diagram);
         app
     }
 }
 
 impl C2DrawApp {
     pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Self::default()
+        let mut app = Self {
+            preferences: Preferences::load(),
+            ..Self::default()
+        };
+        app.apply_stylesheet();
+        app
+    }
+
+    /// Load the stylesheet named in the preferences, or go back to the built-in style
+    fn apply_stylesheet(&mut self) {
+        let path = self.preferences.stylesheet_path.trim();
+        self.canvas.stylesheet = if path.is_empty() {
+            Stylesheet::default()
+        } else {
+            match Stylesheet::load_from_file(std::path::Path::new(path)) {
+                Ok(stylesheet) => stylesheet,
+                Err(e) => {
+                    self.error_message = Some(format!("Could not load stylesheet: {}", e));
+                    Stylesheet::default()
+                }
+            }
+        };
+    }
+
+    fn choose_stylesheet(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("C2Draw Stylesheet", &[STYLESHEET_FILE_EXTENSION])
+            .pick_file()
+        {
+            self.preferences.stylesheet_path = path.display().to_string();
+            self.apply_stylesheet();
+        }
     }
 
     fn add_example_elements(&mut self) {
@@ -62,17 +279,144 @@ impl C2DrawApp {
         ));
     }
 
-    fn new_diagram(&mut self) {
-        self.diagram = Diagram::default();
+    /// Replace the open diagram and start a fresh undo history
+    ///
+    /// A non-empty diagram without a file (imported or from a share link)
+    /// counts as unsaved.
+    fn load_diagram(&mut self, mut diagram: Diagram, file_path: Option<std::path::PathBuf>) {
+        diagram.take_events();
+        self.unsaved_changes = file_path.is_none() && !diagram.elements.is_empty();
+        self.history = History::new(&diagram);
+        self.diagram = diagram;
+        self.file_path = file_path;
         self.selected_element = None;
-        self.file_path = None;
+        self.compare_base = None;
+        self.canvas.cancel_relationship();
+        self.refresh_export();
+    }
+
+    fn new_diagram(&mut self) {
+        self.load_diagram(Diagram::default(), None);
+    }
+
+    fn undo(&mut self) {
+        if let Some(diagram) = self.history.undo() {
+            self.restore_snapshot(diagram);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(diagram) = self.history.redo() {
+            self.restore_snapshot(diagram);
+        }
+    }
+
+    fn restore_snapshot(&mut self, diagram: Diagram) {
+        self.diagram = diagram;
+        self.unsaved_changes = true;
+        if let Some(id) = self.selected_element
+            && self.diagram.get_element(id).is_none()
+        {
+            self.selected_element = None;
+        }
         self.canvas.cancel_relationship();
+        self.refresh_export();
+    }
+
+    fn handle_shortcuts(&mut self, ctx: &Context) {
+        // Text fields have their own undo
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        // The platform turns the copy and paste shortcuts into events, reading the clipboard for us
+        let (copy, pasted) = ctx.input(|i| {
+            let copy = i.events.iter().any(|e| matches!(e, egui::Event::Copy));
+            let pasted = i.events.iter().find_map(|e| match e {
+                egui::Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            });
+            (copy, pasted)
+        });
+        if copy {
+            self.copy_selection(ctx);
+        }
+        if let Some(text) = pasted {
+            self.paste(&text);
+        }
+        // Navigation keys are taken earlier, in raw_input_hook
+        let pressed = keymap::BINDINGS
+            .iter()
+            .filter(|b| !b.command.is_navigation())
+            .find(|b| ctx.input_mut(|i| i.consume_shortcut(&b.shortcut)));
+        match pressed.map(|b| b.command) {
+            Some(Command::Undo) => self.undo(),
+            Some(Command::Redo) => self.redo(),
+            Some(command @ (Command::BringToFront | Command::SendToBack)) => {
+                if let Some(id) = self.selected_element {
+                    self.restack(id, command);
+                }
+            }
+            Some(Command::ShowShortcuts) => self.show_shortcuts_window = !self.show_shortcuts_window,
+            _ => {}
+        }
+    }
+
+    /// Move the selection for the navigation keys taken by [`eframe::App::raw_input_hook`]
+    fn apply_navigation(&mut self) {
+        for navigation in std::mem::take(&mut self.navigation) {
+            let next = match (navigation, self.selected_element) {
+                (Navigation::Next, current) => self.canvas.cycle_selection(&self.diagram.elements, current, false),
+                (Navigation::Previous, current) => self.canvas.cycle_selection(&self.diagram.elements, current, true),
+                (Navigation::Toward(direction), Some(current)) => {
+                    self.canvas.neighbor(&self.diagram.elements, current, direction)
+                }
+                (Navigation::Toward(_), None) => self.canvas.cycle_selection(&self.diagram.elements, None, false),
+            };
+            if let Some(id) = next
+                && let Some(element) = self.diagram.get_element(id)
+            {
+                self.canvas.reveal(element);
+                self.selected_element = Some(id);
+            }
+        }
+    }
+
+    /// Back up the file at `path` (if any), then overwrite it with the diagram
+    ///
+    /// The diagram's modified time (and author and created time, if unset)
+    /// are stamped first.
+    fn write_diagram(&mut self, path: &std::path::Path) -> crate::Result<()> {
+        crate::backup::create_backup(path, self.preferences.backup_count)?;
+        self.diagram.mark_saved(&self.preferences.author, std::time::SystemTime::now());
+        self.diagram.assign_aliases();
+        self.diagram.save_to_file(path)?;
+        self.write_sidecars(path);
+        Ok(())
+    }
+
+    /// Rewrite the export files kept next to the diagram, as set in the preferences
+    ///
+    /// Failures are reported but do not undo the save.
+    fn write_sidecars(&mut self, path: &std::path::Path) {
+        let plantuml = PlantUmlExporter::with_options(self.plantuml_options.clone());
+        let mermaid = MermaidExporter::new();
+        let mut exporters: Vec<&dyn DiagramExporter> = Vec::new();
+        if self.preferences.sidecar_plantuml {
+            exporters.push(&plantuml);
+        }
+        if self.preferences.sidecar_mermaid {
+            exporters.push(&mermaid);
+        }
+        if let Err(e) = crate::export::write_sidecar_files(&self.diagram, path, &exporters) {
+            self.error_message = Some(format!("Saved, but could not write export files: {}", e));
+        }
     }
 
     fn save_diagram(&mut self) {
-        if let Some(path) = &self.file_path {
-            if let Ok(json) = self.diagram.to_json() {
-                let _ = std::fs::write(path, json);
+        if let Some(path) = self.file_path.clone() {
+            match self.write_diagram(&path) {
+                Ok(()) => self.unsaved_changes = false,
+                Err(e) => self.error_message = Some(e.to_string()),
             }
         } else {
             self.save_diagram_as();
@@ -80,230 +424,1484 @@ impl C2DrawApp {
     }
 
     fn save_diagram_as(&mut self) {
+        let dialog = rfd::FileDialog::new()
+            .add_filter("C2Draw Diagram", &[FILE_EXTENSION])
+            .add_filter("Compressed C2Draw Diagram", &[COMPRESSED_FILE_EXTENSION])
+            .add_filter("JSON", &["json"])
+            .add_filter("YAML", &YAML_FILE_EXTENSIONS);
+        #[cfg(feature = "ron")]
+        let dialog = dialog.add_filter("RON", &[crate::model::RON_FILE_EXTENSION]);
+        if let Some(path) = dialog.save_file() {
+            match self.write_diagram(&path) {
+                Ok(()) => {
+                    self.file_path = Some(path);
+                    self.unsaved_changes = false;
+                }
+                Err(e) => self.error_message = Some(e.to_string()),
+            }
+        }
+    }
+
+    fn open_diagram(&mut self) {
+        let dialog = rfd::FileDialog::new()
+            .add_filter("C2Draw Diagram", &[FILE_EXTENSION, COMPRESSED_FILE_EXTENSION])
+            .add_filter("JSON", &["json"])
+            .add_filter("YAML", &YAML_FILE_EXTENSIONS);
+        #[cfg(feature = "ron")]
+        let dialog = dialog.add_filter("RON", &[crate::model::RON_FILE_EXTENSION]);
+        if let Some(path) = dialog.pick_file() {
+            match Diagram::load_from_file(&path) {
+                Ok(diagram) => self.load_diagram(diagram, Some(path)),
+                Err(e) => self.error_message = Some(e.to_string()),
+            }
+        }
+    }
+
+    /// Pick another version of the diagram to highlight the differences from
+    fn compare_with_file(&mut self) {
         if let Some(path) = rfd::FileDialog::new()
-            .add_filter("C2Draw Diagram", &["c4d"])
+            .add_filter("C2Draw Diagram", &[FILE_EXTENSION, COMPRESSED_FILE_EXTENSION])
             .add_filter("JSON", &["json"])
-            .save_file()
+            .add_filter("YAML", &YAML_FILE_EXTENSIONS)
+            .pick_file()
         {
-            if let Ok(json) = self.diagram.to_json() {
-                let _ = std::fs::write(&path, json);
-                self.file_path = Some(path);
+            match Diagram::load_from_file(&path) {
+                Ok(base) => self.compare_base = Some((path, base)),
+                Err(e) => self.error_message = Some(e.to_string()),
             }
         }
     }
 
-    fn open_diagram(&mut self) {
+    /// Pick a diagram file and merge its content into this one
+    fn merge_file(&mut self, strategy: MergeStrategy) {
         if let Some(path) = rfd::FileDialog::new()
-            .add_filter("C2Draw Diagram", &["c4d"])
+            .add_filter("C2Draw Diagram", &[FILE_EXTENSION, COMPRESSED_FILE_EXTENSION])
             .add_filter("JSON", &["json"])
+            .add_filter("YAML", &YAML_FILE_EXTENSIONS)
             .pick_file()
         {
-            if let Ok(content) = std::fs::read_to_string(&path) {
-                if let Ok(diagram) = Diagram::from_json(&content) {
-                    self.diagram = diagram;
-                    self.selected_element = None;
-                    self.file_path = Some(path);
-                    self.canvas.cancel_relationship();
+            match Diagram::load_from_file(&path) {
+                Ok(other) => {
+                    let added = self.diagram.merge(other, strategy);
+                    self.selected_element = added.first().copied();
                 }
+                Err(e) => self.error_message = Some(e.to_string()),
             }
         }
     }
 
-    fn export_plantuml(&mut self) {
-        let exporter = PlantUmlExporter::new();
-        self.export_content = exporter.export(&self.diagram);
-        self.export_title = "C4-PlantUML Export".to_string();
-        self.show_export_window = true;
+    fn open_project(&mut self) {
+        if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+            match Project::open(dir) {
+                Ok(project) => self.project = Some(project),
+                Err(e) => self.error_message = Some(e.to_string()),
+            }
+        }
     }
 
-    fn export_mermaid(&mut self) {
-        let exporter = MermaidExporter::new();
-        self.export_content = exporter.export(&self.diagram);
-        self.export_title = "Mermaid Export".to_string();
-        self.show_export_window = true;
+    fn refresh_project(&mut self) {
+        if let Some(project) = &mut self.project
+            && let Err(e) = project.refresh()
+        {
+            self.error_message = Some(e.to_string());
+        }
     }
 
-    fn add_element(&mut self, element_type: ElementType) {
-        let index = self.diagram.elements.len();
-        let position = crate::ui::default_element_position(index);
-        let element = Element::new(element_type, position);
-        self.diagram.add_element(element);
+    /// Move the active document out of the editor
+    fn park_active(&mut self) -> ParkedTab {
+        self.canvas.cancel_relationship();
+        ParkedTab {
+            diagram: std::mem::take(&mut self.diagram),
+            history: std::mem::replace(&mut self.history, History::new(&Diagram::default())),
+            file_path: self.file_path.take(),
+            unsaved_changes: std::mem::take(&mut self.unsaved_changes),
+            selected_element: self.selected_element.take(),
+            offset: self.canvas.offset,
+            scale: self.canvas.scale,
+        }
     }
 
-    fn delete_selected(&mut self) {
-        if let Some(id) = self.selected_element {
-            self.diagram.remove_element(id);
-            self.selected_element = None;
-            self.canvas.cancel_relationship();
+    fn switch_tab(&mut self, index: usize) {
+        if index == self.active_tab {
+            return;
         }
+        let Some(tab) = self.tabs.get_mut(index).and_then(Option::take) else {
+            return;
+        };
+        self.tabs[self.active_tab] = Some(self.park_active());
+        self.active_tab = index;
+
+        self.diagram = tab.diagram;
+        self.history = tab.history;
+        self.file_path = tab.file_path;
+        self.unsaved_changes = tab.unsaved_changes;
+        self.selected_element = tab.selected_element;
+        self.canvas.offset = tab.offset;
+        self.canvas.scale = tab.scale;
+        self.refresh_export();
     }
 
-    fn start_relationship_mode(&mut self) {
-        if let Some(source_id) = self.selected_element {
-            // If an element is already selected, use it as the source
-            self.canvas.start_relationship(source_id);
+    /// Open a diagram file in a new tab, or switch to its tab if already open
+    ///
+    /// An untouched tab that was never saved is reused.
+    fn open_in_tab(&mut self, path: std::path::PathBuf) {
+        if self.file_path.as_ref() == Some(&path) {
+            return;
         }
-        // If no element selected, the user needs to select one on the canvas first
+        let open_tab = self
+            .tabs
+            .iter()
+            .position(|tab| tab.as_ref().is_some_and(|t| t.file_path.as_ref() == Some(&path)));
+        if let Some(index) = open_tab {
+            self.switch_tab(index);
+            return;
+        }
+
+        let diagram = match Diagram::load_from_file(&path) {
+            Ok(diagram) => diagram,
+            Err(e) => {
+                self.error_message = Some(e.to_string());
+                return;
+            }
+        };
+        self.open_new_tab(diagram, Some(path));
     }
 
-    fn cancel_relationship_mode(&mut self) {
-        self.canvas.cancel_relationship();
+    /// Show a diagram in a new tab; an untouched tab that was never saved is reused
+    fn open_new_tab(&mut self, diagram: Diagram, file_path: Option<std::path::PathBuf>) {
+        if self.file_path.is_some() || self.unsaved_changes {
+            self.tabs[self.active_tab] = Some(self.park_active());
+            self.tabs.push(None);
+            self.active_tab = self.tabs.len() - 1;
+            self.canvas.offset = egui::Vec2::ZERO;
+            self.canvas.scale = 1.0;
+        }
+        self.load_diagram(diagram, file_path);
     }
 
-    fn get_relationship_status_text(&self) -> Option<String> {
-        if self.canvas.is_in_relationship_mode() {
-            Some("Click another element to create relationship".to_string())
+    /// Open a new, unsaved container diagram scaffolded from a software system's neighbours
+    fn generate_container_diagram(&mut self, system_id: crate::model::ElementId) {
+        if let Some(scaffold) = crate::model::scaffold::container_diagram_for(&self.diagram, system_id) {
+            self.open_new_tab(scaffold, None);
+            self.canvas.request_fit();
+        }
+    }
+
+    /// Close a tab, asking first if it has unsaved changes
+    fn request_close_tab(&mut self, index: usize) {
+        let unsaved = match self.tabs.get(index) {
+            Some(Some(tab)) => tab.unsaved_changes,
+            Some(None) => self.unsaved_changes,
+            None => return,
+        };
+        if unsaved {
+            self.pending_close_tab = Some(index);
         } else {
-            None
+            self.close_tab(index);
         }
     }
 
-    fn render_sidebar(&mut self, ctx: &Context) {
-        SidePanel::left("sidebar")
-            .default_width(150.0)
-            .show(ctx, |ui| {
-                ui.heading("Elements");
-                ui.separator();
+    /// Close a tab without saving; closing the last tab leaves a new diagram
+    fn close_tab(&mut self, index: usize) {
+        if self.tabs.len() == 1 {
+            self.new_diagram();
+            return;
+        }
+        if index == self.active_tab {
+            self.switch_tab(if index + 1 < self.tabs.len() { index + 1 } else { index - 1 });
+        }
+        self.tabs.remove(index);
+        if self.active_tab > index {
+            self.active_tab -= 1;
+        }
+    }
 
-                ui.label("C1 - System Context");
-                if ui.button("➕ Person")
-                    .on_hover_text("Add an internal person/actor (e.g., Customer, Admin)")
-                    .clicked()
-                {
-                    self.add_element(ElementType::person("New Person", "Description"));
-                }
-                if ui.button("➕ External Person")
-                    .on_hover_text("Add an external person outside your organization (e.g., Public User)")
-                    .clicked()
-                {
-                    self.add_element(ElementType::external_person("External User", "Description"));
-                }
-                if ui.button("➕ System")
-                    .on_hover_text("Add an internal software system that you build/maintain")
-                    .clicked()
-                {
-                    self.add_element(ElementType::system("New System", "Description"));
-                }
-                if ui.button("➕ External System")
-                    .on_hover_text("Add an external system outside your control (e.g., Third-party API)")
-                    .clicked()
-                {
-                    self.add_element(ElementType::external_system("External System", "Description"));
-                }
+    fn open_backups_window(&mut self) {
+        let Some(path) = &self.file_path else {
+            return;
+        };
+        match crate::backup::list_backups(path) {
+            Ok(backups) => {
+                self.backups = backups;
+                self.show_backups_window = true;
+            }
+            Err(e) => self.error_message = Some(e.to_string()),
+        }
+    }
 
-                ui.separator();
-                ui.label("C2 - Container");
-                if ui.button("➕ Web App")
-                    .on_hover_text("Add a web application container (browser-based UI)")
-                    .clicked()
-                {
-                    self.add_element(ElementType::container(
-                        "Web Application",
-                        "Description",
-                        ContainerType::WebApplication,
-                        "React/Spring Boot",
-                    ));
-                }
-                if ui.button("➕ Database")
-                    .on_hover_text("Add a database container for data persistence")
-                    .clicked()
-                {
-                    self.add_element(ElementType::container(
-                        "Database",
-                        "Description",
-                        ContainerType::Database,
-                        "PostgreSQL",
-                    ));
-                }
-                if ui.button("➕ Queue")
-                    .on_hover_text("Add a message queue for async communication")
-                    .clicked()
-                {
-                    self.add_element(ElementType::container(
-                        "Message Queue",
-                        "Description",
-                        ContainerType::Queue,
-                        "RabbitMQ",
-                    ));
-                }
+    /// Load a backup in place of the open file, leaving it unsaved
+    fn restore_backup(&mut self, backup: &std::path::Path) {
+        match Diagram::load_from_file(backup) {
+            Ok(diagram) => {
+                let file_path = self.file_path.clone();
+                self.load_diagram(diagram, file_path);
+                self.unsaved_changes = true;
+                self.show_backups_window = false;
+            }
+            Err(e) => self.error_message = Some(e.to_string()),
+        }
+    }
 
-                ui.separator();
-                ui.label("Actions");
+    fn open_snippet_window(&mut self) {
+        self.snippet_selection = self.selected_element.into_iter().collect();
+        self.show_snippet_window = true;
+    }
 
-                // Relationship button with dynamic state
-                let rel_button = ui.button("🔗 Add Relationship");
-                let rel_tooltip = if self.canvas.is_in_relationship_mode() {
-                    "Click another element to complete the relationship"
-                } else {
-                    "Start creating a relationship. First select a source element, then click this button."
-                };
-                if rel_button.on_hover_text(rel_tooltip).clicked() {
-                    self.start_relationship_mode();
-                }
+    /// Make `view` the active view (None for the whole diagram), dropping a selection it hides
+    fn show_view(&mut self, view: Option<uuid::Uuid>) {
+        self.diagram.set_active_view(view);
+        self.canvas.view = self.diagram.active_view().cloned();
+        let selected = self.selected_element.and_then(|id| self.diagram.get_element(id));
+        if selected.is_some_and(|e| !self.canvas.is_shown(e)) {
+            self.selected_element = None;
+            self.canvas.cancel_relationship();
+        }
+        self.refresh_export();
+    }
 
-                // Cancel relationship mode button (only show when in relationship mode)
-                if self.canvas.is_in_relationship_mode() {
-                    if ui.button("❌ Cancel Relationship")
-                        .on_hover_text("Cancel the current relationship creation")
-                        .clicked()
-                    {
-                        self.cancel_relationship_mode();
-                    }
-                }
+    fn open_group_window(&mut self) {
+        self.group_selection = self.selected_element.into_iter().collect();
+        self.group_name = "Group".to_string();
+        self.show_group_window = true;
+    }
 
-                if ui.button("🗑️ Delete Selected")
-                    .on_hover_text("Delete the currently selected element and all its relationships")
-                    .clicked()
-                {
-                    self.delete_selected();
-                }
+    /// Put a new text label in the middle of the view and open it for editing
+    fn add_text_label(&mut self) {
+        let position = self
+            .canvas
+            .drop_position(&self.diagram.elements, Size::new(120.0, 30.0))
+            .unwrap_or(Position::new(0.0, 0.0));
+        self.editing_label = Some(self.diagram.add_label(TextLabel::new("Title", position)));
+    }
 
-                // Show relationship mode status
-                if let Some(status) = self.get_relationship_status_text() {
-                    ui.separator();
-                    ui.colored_label(Color32::from_rgb(0, 120, 215), status);
-                }
-            });
+    fn export_snippet(&mut self) {
+        let ids: Vec<_> = self.snippet_selection.iter().copied().collect();
+        let snippet = Snippet::from_selection(&self.diagram, &ids);
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("C2Draw Snippet", &[SNIPPET_FILE_EXTENSION])
+            .save_file()
+        {
+            match snippet.save_to_file(&path) {
+                Ok(()) => self.show_snippet_window = false,
+                Err(e) => self.error_message = Some(e.to_string()),
+            }
+        }
     }
 
-    fn render_properties_panel(&mut self, ctx: &Context) {
-        SidePanel::right("properties")
-            .default_width(200.0)
-            .show(ctx, |ui| {
-                ui.heading("Properties");
-                ui.separator();
+    /// Insert a snippet file to the right of the existing elements
+    fn import_snippet(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("C2Draw Snippet", &[SNIPPET_FILE_EXTENSION])
+            .pick_file()
+        else {
+            return;
+        };
+        match Snippet::load_from_file(&path) {
+            Ok(snippet) => self.insert_beside(&snippet),
+            Err(e) => self.error_message = Some(e.to_string()),
+        }
+    }
 
-                if let Some(id) = self.selected_element {
-                    if let Some(element) = self.diagram.get_element_mut(id) {
-                        ui.label("Type");
-                        ui.label(element.element_type.type_name());
-                        ui.separator();
+    /// Insert a snippet to the right of the existing elements and select its first element
+    fn insert_beside(&mut self, snippet: &Snippet) {
+        let at = self
+            .diagram
+            .elements
+            .values()
+            .map(|e| e.position.x + e.size.width)
+            .reduce(f32::max)
+            .map_or(Position::new(50.0, 50.0), |right| Position::new(right + 50.0, 50.0));
+        let inserted = self.diagram.insert_snippet(snippet, at);
+        self.selected_element = inserted.first().copied();
+    }
 
-                        ui.label("Name");
-                        let mut name = element.name().to_string();
-                        if ui.text_edit_singleline(&mut name).changed() {
-                            element.set_name(name);
-                        }
+    /// Bring an element to the front or send it to the back
+    fn restack(&mut self, id: crate::model::ElementId, command: Command) {
+        match command {
+            Command::BringToFront => self.diagram.bring_to_front(id),
+            Command::SendToBack => self.diagram.send_to_back(id),
+            _ => false,
+        };
+    }
 
-                        ui.label("Description");
-                        let mut desc = element.description().to_string();
-                        ui.text_edit_multiline(&mut desc);
-                        element.set_description(desc);
+    /// Put the selected element, and the elements nested in it, on the system clipboard
+    fn copy_selection(&mut self, ctx: &Context) {
+        let selected: Vec<_> = self.selected_element.into_iter().collect();
+        if let Some(text) = clipboard::encode_clipboard(&self.diagram, &selected) {
+            ctx.copy_text(text);
+        }
+    }
 
-                        ui.separator();
-                        if ui.button("Delete Element")
-                            .on_hover_text("Remove this element from the diagram")
-                            .clicked()
-                        {
-                            self.diagram.remove_element(id);
-                            self.selected_element = None;
-                            self.canvas.cancel_relationship();
-                        }
-                    }
-                } else {
-                    ui.label("No element selected");
-                }
-            });
+    /// Insert elements copied from this or another C2Draw window, offset from where they were copied
+    fn paste(&mut self, text: &str) {
+        let Some(snippet) = clipboard::decode_clipboard(text) else {
+            return;
+        };
+        let origin = snippet.origin();
+        let inserted = self
+            .diagram
+            .insert_snippet(&snippet, Position::new(origin.x + PASTE_OFFSET, origin.y + PASTE_OFFSET));
+        self.selected_element = inserted.first().copied();
+    }
+
+    /// React to changes queued on the diagram since the last frame
+    fn handle_diagram_events(&mut self, ctx: &Context) {
+        if let Some((id, command)) = self.canvas.take_stacking_request() {
+            self.restack(id, command);
+        }
+        for (id, from) in self.canvas.take_completed_moves() {
+            if let Some(to) = self.diagram.get_element(id).map(|e| e.position)
+                && from != to
+            {
+                self.diagram.emit(DiagramEvent::ElementMoved { id, from, to });
+            }
+        }
+        if let Some((rel_id, end, anchor)) = self.canvas.take_anchor_change()
+            && let Some(rel) = self.diagram.relationships.iter_mut().find(|r| r.id == rel_id)
+        {
+            match end {
+                RelationshipEnd::Source => rel.source_anchor = Some(anchor),
+                RelationshipEnd::Target => rel.target_anchor = Some(anchor),
+            }
+            self.diagram.emit(DiagramEvent::RelationshipUpdated(rel_id));
+        }
+        if let Some((rel_id, waypoints)) = self.canvas.take_waypoint_change()
+            && let Some(rel) = self.diagram.relationships.iter_mut().find(|r| r.id == rel_id)
+        {
+            rel.waypoints = waypoints;
+            self.diagram.emit(DiagramEvent::RelationshipUpdated(rel_id));
+        }
+
+        if let Some((id, position)) = self.canvas.take_text_label_move() {
+            self.diagram.move_label(id, position);
+        }
+        if let Some((rel_id, offset)) = self.canvas.take_label_change()
+            && let Some(rel) = self.diagram.relationships.iter_mut().find(|r| r.id == rel_id)
+            && rel.label_offset != offset
+        {
+            rel.label_offset = offset;
+            self.diagram.emit(DiagramEvent::RelationshipUpdated(rel_id));
+        }
+
+        let events = self.diagram.take_events();
+        self.history.record(&self.diagram, &events);
+        if events.iter().any(|e| matches!(e, DiagramEvent::ElementRemoved(id) if self.selected_element == Some(*id))) {
+            self.selected_element = None;
+        }
+        if !events.is_empty() {
+            self.unsaved_changes = true;
+            self.refresh_export();
+        }
+
+        let title = format!(
+            "C2Draw - {}{}",
+            self.diagram.name,
+            if self.unsaved_changes { " *" } else { "" }
+        );
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+
+        if let Some(id) = self.canvas.take_clicked_link() {
+            self.follow_link(ctx, id);
+        }
+    }
+
+    /// Open an element's link: a linked diagram in a tab, anything else in the browser
+    fn follow_link(&mut self, ctx: &Context, id: crate::model::ElementId) {
+        let Some(element) = self.diagram.get_element(id) else {
+            return;
+        };
+        let linked_diagram = self
+            .file_path
+            .as_deref()
+            .and_then(|current| project::drill_down_path(element, current));
+        match (linked_diagram, element.url.as_deref()) {
+            (Some(path), _) => self.open_in_tab(path),
+            (None, Some(url)) => ctx.open_url(egui::OpenUrl::new_tab(url.trim())),
+            (None, None) => {}
+        }
+    }
+
+    /// Write the diagram's SVG next to its file and copy a Markdown snippet embedding it
+    fn copy_embed_snippet(&mut self, ctx: &Context) {
+        let Some(path) = &self.file_path else {
+            return;
+        };
+        let image = path.with_extension("svg");
+        if let Err(e) = SvgExporter::with_stylesheet(self.canvas.stylesheet.clone()).export_to_file(&self.visible_diagram(), &image) {
+            self.error_message = Some(format!("SVG export failed: {}", e));
+            return;
+        }
+        let file_name = |p: &std::path::Path| p.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        ctx.copy_text(embed::embed_snippet(
+            &self.diagram.name,
+            self.export_format,
+            &self.export_content,
+            &file_name(&image),
+            &file_name(path),
+        ));
+    }
+
+    fn copy_share_link(&mut self, ctx: &Context) {
+        match crate::share::encode_share_link(&self.diagram) {
+            Ok(link) => ctx.copy_text(link),
+            Err(e) => {
+                self.share_link_error = Some(e.to_string());
+                self.show_share_link_window = true;
+            }
+        }
+    }
+
+    fn open_share_link(&mut self) {
+        match crate::share::decode_share_link(&self.share_link_input) {
+            Ok(diagram) => {
+                self.load_diagram(diagram, None);
+                self.share_link_input.clear();
+                self.share_link_error = None;
+                self.show_share_link_window = false;
+            }
+            Err(e) => self.share_link_error = Some(e.to_string()),
+        }
+    }
+
+    fn import_csv(&mut self) {
+        let Some(elements_path) = rfd::FileDialog::new()
+            .set_title("Import Elements CSV")
+            .add_filter("CSV", &["csv"])
+            .pick_file()
+        else {
+            return;
+        };
+        // The relationships file is optional; cancelling imports elements only
+        let relationships_path = rfd::FileDialog::new()
+            .set_title("Import Relationships CSV (optional)")
+            .add_filter("CSV", &["csv"])
+            .pick_file();
+
+        match crate::import::csv::import_csv_files(&elements_path, relationships_path.as_deref()) {
+            Ok(diagram) => self.load_diagram(diagram, None),
+            Err(e) => self.error_message = Some(format!("CSV import failed: {}", e)),
+        }
+    }
+
+    fn import_cargo_workspace(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Import Cargo Workspace")
+            .add_filter("Cargo manifest", &["toml"])
+            .pick_file()
+        else {
+            return;
+        };
+        match crate::import::cargo::import_cargo_workspace(&path) {
+            Ok(diagram) => self.load_diagram(diagram, None),
+            Err(e) => self.error_message = Some(format!("Cargo import failed: {}", e)),
+        }
+    }
+
+    fn import_compose(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Import docker-compose File")
+            .add_filter("Compose file", &["yml", "yaml"])
+            .pick_file()
+        else {
+            return;
+        };
+        match crate::import::compose::import_compose_file(&path) {
+            Ok(diagram) => self.load_diagram(diagram, None),
+            Err(e) => self.error_message = Some(format!("docker-compose import failed: {}", e)),
+        }
+    }
+
+    fn import_kubernetes(&mut self) {
+        let Some(dir) = rfd::FileDialog::new()
+            .set_title("Import Kubernetes Manifests")
+            .pick_folder()
+        else {
+            return;
+        };
+        match crate::import::kubernetes::import_manifest_dir(&dir) {
+            Ok(diagram) => self.load_diagram(diagram, None),
+            Err(e) => self.error_message = Some(format!("Kubernetes import failed: {}", e)),
+        }
+    }
+
+    /// Pick an OpenAPI document and show relationship suggestions for it
+    fn load_openapi_spec(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Open OpenAPI Document")
+            .add_filter("OpenAPI", &["json", "yaml", "yml"])
+            .pick_file()
+        else {
+            return;
+        };
+        let spec = std::fs::read_to_string(&path)
+            .map_err(|e| crate::C2DrawError::file(&path, e))
+            .and_then(|text| Ok(crate::import::openapi::parse_openapi(&text)?));
+        match spec {
+            Ok(spec) => {
+                self.api_suggestions.load(spec, &self.diagram);
+                self.show_api_suggestions_window = true;
+            }
+            Err(e) => self.error_message = Some(format!("OpenAPI import failed: {}", e)),
+        }
+    }
+
+    fn import_terraform(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Import Terraform State or Plan")
+            .add_filter("Terraform JSON", &["json", "tfstate"])
+            .pick_file()
+        else {
+            return;
+        };
+        match crate::import::terraform::import_terraform_file(&path) {
+            Ok(diagram) => self.load_diagram(diagram, None),
+            Err(e) => self.error_message = Some(format!("Terraform import failed: {}", e)),
+        }
+    }
+
+    /// The diagram as the canvas shows it, for "export visible" operations
+    fn visible_diagram(&self) -> Cow<'_, Diagram> {
+        if self.canvas.hide_external {
+            Cow::Owned(self.diagram.without_external())
+        } else {
+            Cow::Borrowed(&self.diagram)
+        }
+    }
+
+    /// Open the export window showing the diagram in `format`
+    fn show_export(&mut self, format: ExportFormat) {
+        self.export_format = format;
+        self.show_export_window = true;
+        self.refresh_export();
+    }
+
+    /// Regenerate the export window's text, if it is open
+    ///
+    /// Called whenever the diagram changes so the window stays live. A
+    /// rendered preview no longer matches new text and is dropped.
+    fn refresh_export(&mut self) {
+        if !self.show_export_window {
+            return;
+        }
+        self.diagram.assign_aliases();
+        let content = self.export_format.export(&self.visible_diagram(), &self.plantuml_options);
+        if content != self.export_content {
+            self.export_content = content;
+            self.clear_preview();
+        }
+    }
+
+    fn open_in_plantuml_server(&mut self, ctx: &Context) {
+        self.diagram.assign_aliases();
+        let exporter = PlantUmlExporter::with_options(self.plantuml_options.clone());
+        match exporter.server_url(&self.visible_diagram()) {
+            Ok(url) => ctx.open_url(egui::OpenUrl::new_tab(url)),
+            Err(e) => self.error_message = Some(format!("Could not build the PlantUML server link: {}", e)),
+        }
+    }
+
+    fn export_svg(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("SVG Image", &["svg"])
+            .save_file()
+            && let Err(e) = SvgExporter::with_stylesheet(self.canvas.stylesheet.clone()).export_to_file(&self.visible_diagram(), &path)
+        {
+            self.error_message = Some(format!("SVG export failed: {}", e));
+        }
+    }
+
+    fn export_structurizr(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Structurizr Workspace", &["json"])
+            .set_file_name("workspace.json")
+            .save_file()
+            && let Err(e) = StructurizrExporter::new().export_to_file(&self.visible_diagram(), &path)
+        {
+            self.error_message = Some(format!("Structurizr export failed: {}", e));
+        }
+    }
+
+    /// Write the diagram as SVG poster pages into a chosen folder
+    fn export_poster(&mut self) {
+        let stem = self
+            .file_path
+            .as_ref()
+            .and_then(|p| p.file_stem())
+            .map_or("poster".to_string(), |n| n.to_string_lossy().into_owned());
+        if let Some(out_dir) = rfd::FileDialog::new()
+            .set_title("Export Poster Pages To")
+            .pick_folder()
+        {
+            match poster::export_poster(&self.visible_diagram(), &self.poster_options, &out_dir, &stem) {
+                Ok(_) => self.show_poster_window = false,
+                Err(e) => self.error_message = Some(format!("Poster export failed: {}", e)),
+            }
+        }
+    }
+
+    /// Save a Markdown ADR stub that describes and links to the diagram
+    fn export_adr(&mut self) {
+        let mut dialog = rfd::FileDialog::new()
+            .set_title("Export ADR")
+            .add_filter("Markdown", &["md"])
+            .set_file_name("adr.md");
+        if let Some(dir) = self.file_path.as_ref().and_then(|p| p.parent()) {
+            dialog = dialog.set_directory(dir);
+        }
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        let link = self.file_path.as_ref().map(|diagram_path| {
+            adr::relative_path(path.parent().unwrap_or(std::path::Path::new("")), diagram_path)
+        });
+        let date = crate::backup::format_iso8601(std::time::SystemTime::now());
+        self.diagram.assign_aliases();
+        let contents = adr::render_adr(&self.diagram, link.as_deref(), &date[..10]);
+        if let Err(e) = std::fs::write(&path, contents) {
+            self.error_message = Some(format!("ADR export failed: {}", crate::C2DrawError::file(&path, e)));
+        }
+    }
+
+    /// Write a Markdown + SVG handbook for every diagram in the open file's folder
+    fn export_docs(&mut self) {
+        let current_name = self
+            .file_path
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map_or("diagram.c4d".to_string(), |n| n.to_string_lossy().into_owned());
+        let mut diagrams = match self.file_path.as_ref().and_then(|p| p.parent()) {
+            Some(dir) => match docs::load_workspace(dir) {
+                Ok(diagrams) => diagrams,
+                Err(e) => {
+                    self.error_message = Some(format!("Documentation export failed: {}", e));
+                    return;
+                }
+            },
+            None => Vec::new(),
+        };
+        // The open diagram may have unsaved changes
+        diagrams.retain(|d| d.file_name != current_name);
+        diagrams.push(docs::WorkspaceDiagram {
+            file_name: current_name,
+            diagram: self.diagram.clone(),
+        });
+        diagrams.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+
+        if let Some(out_dir) = rfd::FileDialog::new()
+            .set_title("Export Documentation To")
+            .pick_folder()
+            && let Err(e) = docs::export_docs(&diagrams, &out_dir)
+        {
+            self.error_message = Some(format!("Documentation export failed: {}", e));
+        }
+    }
+
+    fn clear_preview(&mut self) {
+        self.preview_png = None;
+        self.preview_texture = None;
+        self.preview_error = None;
+    }
+
+    fn render_preview(&mut self, ctx: &Context) {
+        self.clear_preview();
+        let result = render::render_png(self.export_format.render_source(), &self.export_content, &self.preferences)
+            .and_then(|png| render::decode_png(&png).map(|image| (png, image)));
+        match result {
+            Ok((png, image)) => {
+                self.preview_texture =
+                    Some(ctx.load_texture("export_preview", image, egui::TextureOptions::LINEAR));
+                self.preview_png = Some(png);
+            }
+            Err(e) => self.preview_error = Some(e.to_string()),
+        }
+    }
+
+    fn save_preview_png(&mut self) {
+        if let Some(png) = &self.preview_png
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter("PNG Image", &["png"])
+                .save_file()
+            && let Err(e) = std::fs::write(&path, png)
+        {
+            self.preview_error = Some(e.to_string());
+        }
+    }
+
+    /// Add an element with a unique name, selected and ready to rename
+    /// Add an element from the preferences' template for `kind`
+    fn add_element(&mut self, kind: ElementKind) {
+        let template = self.preferences.element_template(kind);
+        self.add_from_template(kind, &template);
+    }
+
+    /// Add an element filled in from a template, select it and focus its name
+    fn add_from_template(&mut self, kind: ElementKind, template: &ElementTemplate) {
+        let position = self
+            .canvas
+            .drop_position(&self.diagram.elements, template.size)
+            .unwrap_or_else(|| crate::ui::default_element_position(self.diagram.elements.len()));
+        let mut element = template.element(kind, position);
+        element.set_name(self.diagram.unique_name(element.name()));
+        self.selected_element = Some(element.id);
+        self.focus_name_field = true;
+        self.diagram.add_element(element);
+    }
+
+    fn delete_selected(&mut self) {
+        for id in std::mem::take(&mut self.canvas.marked) {
+            self.diagram.remove_element(id);
+        }
+        if let Some(id) = self.selected_element {
+            self.diagram.remove_element(id);
+            self.selected_element = None;
+            self.canvas.cancel_relationship();
+        }
+    }
+
+    fn start_relationship_mode(&mut self) {
+        if let Some(source_id) = self.selected_element {
+            // If an element is already selected, use it as the source
+            self.canvas.start_relationship(source_id);
+        }
+        // If no element selected, the user needs to select one on the canvas first
+    }
+
+    /// Add the relationship typed into the toolbar's quick-add field
+    fn quick_add_relationship(&mut self) {
+        let result = crate::quick_add::parse_quick_relationship(&self.quick_add_input).and_then(|command| {
+            crate::quick_add::apply_quick_relationship(&mut self.diagram, &command, self.quick_add_create_missing)
+        });
+        match result {
+            Ok(_) => {
+                self.quick_add_input.clear();
+                self.quick_add_error = None;
+            }
+            Err(e) => self.quick_add_error = Some(e.to_string()),
+        }
+    }
+
+    fn cancel_relationship_mode(&mut self) {
+        self.canvas.cancel_relationship();
+    }
+
+    fn get_relationship_status_text(&self) -> Option<String> {
+        if self.canvas.is_in_relationship_mode() {
+            Some("Click another element to create relationship".to_string())
+        } else {
+            None
+        }
+    }
+
+    fn render_toolbar(&mut self, ctx: &Context) {
+        TopBottomPanel::top("toolbar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("➖").on_hover_text("Zoom out").clicked() {
+                    self.canvas.zoom_out();
+                }
+                if ui
+                    .button(format!("{:.0}%", self.canvas.scale * 100.0))
+                    .on_hover_text("Reset zoom to 100%")
+                    .clicked()
+                {
+                    self.canvas.reset_zoom();
+                }
+                if ui.button("➕").on_hover_text("Zoom in").clicked() {
+                    self.canvas.zoom_in();
+                }
+                if ui.button("⛶ Fit").on_hover_text("Zoom to fit all elements").clicked() {
+                    self.canvas.request_fit();
+                }
+
+                ui.separator();
+                if ui
+                    .add_enabled(self.history.can_undo(), egui::Button::new("↶ Undo"))
+                    .on_hover_text(format!("Undo ({})", ctx.format_shortcut(&keymap::shortcut(Command::Undo))))
+                    .clicked()
+                {
+                    self.undo();
+                }
+                if ui
+                    .add_enabled(self.history.can_redo(), egui::Button::new("↷ Redo"))
+                    .on_hover_text(format!("Redo ({})", ctx.format_shortcut(&keymap::shortcut(Command::Redo))))
+                    .clicked()
+                {
+                    self.redo();
+                }
+
+                ui.separator();
+                ui.toggle_value(&mut self.canvas.snap_to_grid, "# Snap")
+                    .on_hover_text("Snap elements to the grid when dropped");
+
+                ui.separator();
+                let can_link = self.selected_element.is_some() && !self.canvas.is_in_relationship_mode();
+                if ui
+                    .add_enabled(can_link, egui::Button::new("🔗 Add Relationship"))
+                    .on_hover_text("Draw a relationship from the selected element")
+                    .clicked()
+                {
+                    self.start_relationship_mode();
+                }
+
+                ui.separator();
+                let quick_add = ui.add(
+                    egui::TextEdit::singleline(&mut self.quick_add_input)
+                        .desired_width(220.0)
+                        .hint_text("User -> Web App : logs in [HTTPS]"),
+                )
+                .on_hover_text("Type a relationship and press Enter");
+                if quick_add.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    self.quick_add_relationship();
+                    quick_add.request_focus();
+                }
+                if quick_add.changed() {
+                    self.quick_add_error = None;
+                }
+                ui.checkbox(&mut self.quick_add_create_missing, "Create missing")
+                    .on_hover_text("Add unknown names as new software systems");
+                if let Some(error) = &self.quick_add_error {
+                    ui.colored_label(Color32::from_rgb(200, 0, 0), error);
+                }
+
+                ui.separator();
+                ui.label("Export:");
+                if ui.button("PlantUML").clicked() {
+                    self.show_export(ExportFormat::PlantUml);
+                }
+                if ui.button("Mermaid").clicked() {
+                    self.show_export(ExportFormat::Mermaid);
+                }
+                if ui.button("Flowchart").clicked() {
+                    self.show_export(ExportFormat::MermaidFlowchart);
+                }
+            });
+        });
+    }
+
+    fn render_sidebar(&mut self, ctx: &Context) {
+        SidePanel::left("sidebar")
+            .default_width(150.0)
+            .show(ctx, |ui| {
+                ui.heading("Elements");
+                ui.separator();
+
+                ui.label("C1 - System Context");
+                if ui.button("➕ Person")
+                    .on_hover_text("Add an internal person/actor (e.g., Customer, Admin)")
+                    .clicked()
+                {
+                    self.add_element(ElementKind::Person);
+                }
+                if ui.button("➕ External Person")
+                    .on_hover_text("Add an external person outside your organization (e.g., Public User)")
+                    .clicked()
+                {
+                    self.add_element(ElementKind::ExternalPerson);
+                }
+                if ui.button("➕ System")
+                    .on_hover_text("Add an internal software system that you build/maintain")
+                    .clicked()
+                {
+                    self.add_element(ElementKind::System);
+                }
+                if ui.button("➕ External System")
+                    .on_hover_text("Add an external system outside your control (e.g., Third-party API)")
+                    .clicked()
+                {
+                    self.add_element(ElementKind::ExternalSystem);
+                }
+
+                ui.separator();
+                ui.label("C2 - Container");
+                if ui.button("➕ Web App")
+                    .on_hover_text("Add a web application container (browser-based UI)")
+                    .clicked()
+                {
+                    self.add_element(ElementKind::Container);
+                }
+                if ui.button("➕ Database")
+                    .on_hover_text("Add a database container for data persistence")
+                    .clicked()
+                {
+                    self.add_element(ElementKind::Database);
+                }
+                if ui.button("➕ Queue")
+                    .on_hover_text("Add a message queue for async communication")
+                    .clicked()
+                {
+                    self.add_element(ElementKind::Queue);
+                }
+                ui.menu_button("➕ Stack Preset", |ui| {
+                    for preset in crate::presets::PRESETS {
+                        if ui.button(preset.name).on_hover_text(preset.description).clicked() {
+                            self.insert_beside(&preset.snippet());
+                            ui.close();
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("Add a pre-connected set of containers for a common technology stack");
+
+                ui.separator();
+                ui.label("C3 - Component");
+                if ui.button("➕ Component")
+                    .on_hover_text("Add a component, a building block inside a container (e.g., a controller or service)")
+                    .clicked()
+                {
+                    self.add_element(ElementKind::Component);
+                }
+
+                ui.separator();
+                ui.label("Annotations");
+                if ui.button("➕ Note")
+                    .on_hover_text("Add a sticky note of free text; it has no C4 meaning")
+                    .clicked()
+                {
+                    self.add_element(ElementKind::Note);
+                }
+
+                ui.separator();
+                ui.label("Templates");
+                let mut chosen = None;
+                for (index, entry) in self.preferences.catalog.iter().enumerate() {
+                    let hover = match entry.template.technology.as_str() {
+                        "" => entry.kind.as_str().to_string(),
+                        technology => format!("{} [{}]", entry.kind.as_str(), technology),
+                    };
+                    if ui.button(format!("➕ {}", entry.label)).on_hover_text(hover).clicked() {
+                        chosen = Some(index);
+                    }
+                }
+                if self.preferences.catalog.is_empty() {
+                    ui.weak("Use \"Save as Template\" on an element to add one");
+                }
+                if let Some(entry) = chosen.map(|index| self.preferences.catalog[index].clone()) {
+                    self.add_from_template(entry.kind, &entry.template);
+                }
+
+                ui.separator();
+                ui.label("Actions");
+
+                // Relationship button with dynamic state
+                let rel_button = ui.button("🔗 Add Relationship");
+                let rel_tooltip = if self.canvas.is_in_relationship_mode() {
+                    "Click another element to complete the relationship"
+                } else {
+                    "Start creating a relationship. First select a source element, then click this button."
+                };
+                if rel_button.on_hover_text(rel_tooltip).clicked() {
+                    self.start_relationship_mode();
+                }
+
+                // Cancel relationship mode button (only show when in relationship mode)
+                if self.canvas.is_in_relationship_mode() {
+                    if ui.button("❌ Cancel Relationship")
+                        .on_hover_text("Cancel the current relationship creation")
+                        .clicked()
+                    {
+                        self.cancel_relationship_mode();
+                    }
+                }
+
+                if ui.button("🗑️ Delete Selected")
+                    .on_hover_text("Delete the currently selected elements and all their relationships")
+                    .clicked()
+                {
+                    self.delete_selected();
+                }
+
+                // Show relationship mode status
+                if let Some(status) = self.get_relationship_status_text() {
+                    ui.separator();
+                    ui.colored_label(Color32::from_rgb(0, 120, 215), status);
+                }
+            });
+    }
+
+    fn render_properties_panel(&mut self, ctx: &Context) {
+        let mut open_link = None;
+        let mut scaffold_from = None;
+        SidePanel::right("properties")
+            .default_width(200.0)
+            .show(ctx, |ui| {
+                ui.heading("Properties");
+                ui.separator();
+
+                if let Some(id) = self.selected_element {
+                    // Systems a container and containers a component can be placed in, by name
+                    let parents = |is_parent: fn(&ElementType) -> bool| {
+                        let mut options: Vec<(crate::model::ElementId, String)> = self
+                            .diagram
+                            .elements
+                            .values()
+                            .filter(|e| e.id != id && is_parent(&e.element_type))
+                            .map(|e| (e.id, e.name().to_string()))
+                            .collect();
+                        options.sort_by(|a, b| a.1.cmp(&b.1));
+                        options
+                    };
+                    let systems = parents(|t| matches!(t, ElementType::SoftwareSystem(_)));
+                    let containers = parents(|t| matches!(t, ElementType::Container(_)));
+                    if let Some(element) = self.diagram.get_element_mut(id) {
+                        ui.label("Type");
+                        ui.label(element.element_type.type_name());
+                        ui.separator();
+
+                        let is_note = element.is_note();
+                        ui.label(if is_note { "Text" } else { "Name" });
+                        let mut changed = false;
+                        let mut name = element.name().to_string();
+                        let rows = if is_note { 4 } else { 1 };
+                        let mut name_edit = egui::TextEdit::multiline(&mut name).desired_rows(rows).show(ui);
+                        if std::mem::take(&mut self.focus_name_field) {
+                            name_edit.response.request_focus();
+                            let all = egui::text::CCursorRange::two(
+                                egui::text::CCursor::new(0),
+                                egui::text::CCursor::new(element.name().chars().count()),
+                            );
+                            name_edit.state.cursor.set_char_range(Some(all));
+                            name_edit.state.store(ui.ctx(), name_edit.response.id);
+                        }
+                        if name_edit.response.changed() {
+                            element.set_name(name);
+                            changed = true;
+                        }
+
+                        if !is_note {
+                            ui.label("Description");
+                            let mut desc = element.description().to_string();
+                            if ui.text_edit_multiline(&mut desc).changed() {
+                                element.set_description(desc);
+                                changed = true;
+                            }
+                        }
+
+                        if let ElementType::SoftwareSystem(data) = &mut element.element_type {
+                            ui.label("Shown As");
+                            egui::ComboBox::from_id_salt(("system_kind", id))
+                                .selected_text(data.kind.as_str())
+                                .show_ui(ui, |ui| {
+                                    for kind in SystemKind::ALL {
+                                        changed |= ui.selectable_value(&mut data.kind, kind, kind.as_str()).changed();
+                                    }
+                                });
+                        }
+
+                        if let ElementType::Container(data) = &mut element.element_type {
+                            ui.label("Container Type");
+                            changed |= crate::ui::container_type_picker(
+                                ui,
+                                ("container_type", id),
+                                &mut data.container_type,
+                            );
+
+                            ui.label("Technology");
+                            changed |= ui.text_edit_singleline(&mut data.technology).changed();
+
+                            ui.label("Part of System");
+                            changed |= crate::ui::parent_picker(ui, ("container_system", id), &mut data.system, &systems);
+
+                            if !self.preferences.icon_packs.is_empty() || data.icon.is_some() {
+                                ui.label("Icon");
+                                changed |= crate::ui::cloud_icon_picker(
+                                    ui,
+                                    ("container_icon", id),
+                                    &mut data.icon,
+                                    &self.preferences.icon_packs,
+                                );
+                            }
+                        }
+
+                        if let ElementType::Component(data) = &mut element.element_type {
+                            ui.label("Technology");
+                            changed |= ui.text_edit_singleline(&mut data.technology).changed();
+
+                            ui.label("Part of Container");
+                            changed |= crate::ui::parent_picker(
+                                ui,
+                                ("component_container", id),
+                                &mut data.container,
+                                &containers,
+                            );
+                        }
+
+                        ui.label("Tags");
+                        changed |= crate::ui::tags_edit(ui, ("element_tags", id), &mut element.tags);
+
+                        ui.label("Style");
+                        let base = self.canvas.stylesheet.element_style(element);
+                        changed |= crate::ui::element_style_edit(ui, ("element_style", id), &mut element.style, &base);
+
+                        ui.label("Link");
+                        changed |= crate::ui::url_edit(ui, ("element_url", id), &mut element.url);
+
+                        if let (Some(project), Some(current)) = (&self.project, &self.file_path) {
+                            ui.label("Links to diagram");
+                            changed |= crate::ui::diagram_link_picker(
+                                ui,
+                                ("element_link", id),
+                                &mut element.url,
+                                project,
+                                current,
+                            );
+                            if let Some(target) = project::drill_down_path(element, current)
+                                && ui.button("Open Linked Diagram")
+                                    .on_hover_text(target.display().to_string())
+                                    .clicked()
+                            {
+                                open_link = Some(target);
+                            }
+                        }
+                        if changed {
+                            self.diagram.emit(DiagramEvent::ElementUpdated(id));
+                        }
+
+                        if let Some(element) = self.diagram.get_element(id)
+                            && ui.button("Save as Template")
+                                .on_hover_text("Add this element to the sidebar's template library")
+                                .clicked()
+                        {
+                            self.preferences.catalog.push(CatalogEntry::from_element(element));
+                            if let Err(e) = self.preferences.save() {
+                                self.error_message = Some(format!("Could not save preferences: {}", e));
+                            }
+                        }
+
+                        if let Some(group) = self.diagram.group_of(id) {
+                            let (group_id, mut name) = (group.id, group.name.clone());
+                            ui.horizontal(|ui| {
+                                ui.label("Group:");
+                                if ui.text_edit_singleline(&mut name).changed() {
+                                    self.diagram.rename_group(group_id, name);
+                                }
+                                if ui.button("Ungroup")
+                                    .on_hover_text("Let the elements of this group move on their own again")
+                                    .clicked()
+                                {
+                                    self.diagram.ungroup(group_id);
+                                }
+                            });
+                        }
+
+                        if let Some(element) = self.diagram.get_element(id)
+                            && matches!(element.element_type, ElementType::SoftwareSystem(_))
+                            && ui.button("Generate Container Diagram")
+                                .on_hover_text("Open a new container diagram with the people and systems this system talks to")
+                                .clicked()
+                        {
+                            scaffold_from = Some(id);
+                        }
+
+                        self.render_connection_anchors(ui, id);
+
+                        ui.separator();
+                        if ui.button("Delete Element")
+                            .on_hover_text("Remove this element from the diagram")
+                            .clicked()
+                        {
+                            self.diagram.remove_element(id);
+                            self.selected_element = None;
+                            self.canvas.cancel_relationship();
+                        }
+                    }
+                } else {
+                    ui.label("No element selected");
+                }
+            });
+
+        if let Some(path) = open_link {
+            self.open_in_tab(path);
+        }
+        if let Some(id) = scaffold_from {
+            self.generate_container_diagram(id);
+        }
+    }
+
+    /// Direction and anchor controls for this element's connected relationships
+    fn render_connection_anchors(&mut self, ui: &mut egui::Ui, id: crate::model::ElementId) {
+        let steps = self.diagram.step_numbers();
+        let elements = &self.diagram.elements;
+        let mut updated = Vec::new();
+        let mut reversed = None;
+        let connected = self
+            .diagram
+            .relationships
+            .iter_mut()
+            .filter(|r| r.source_id == id || r.target_id == id);
+
+        for (index, rel) in connected.enumerate() {
+            if index == 0 {
+                ui.separator();
+                ui.label("Connections");
+            }
+            let rel_id = rel.id;
+            let (arrow, other, anchor) = if rel.source_id == id {
+                ("→", rel.target_id, &mut rel.source_anchor)
+            } else {
+                ("←", rel.source_id, &mut rel.target_anchor)
+            };
+            let other_name = elements.get(&other).map_or("?", |e| e.name());
+            let mut note = rel.note.clone().unwrap_or_default();
+            ui.horizontal(|ui| {
+                ui.label(format!("{} {}", arrow, other_name));
+                if ui.small_button("⇄").on_hover_text("Reverse direction").clicked() {
+                    reversed = Some(rel_id);
+                }
+            });
+            let mut changed = false;
+            if let Some(&step) = steps.get(&rel_id) {
+                ui.horizontal(|ui| {
+                    ui.label("Step");
+                    let mut order = step;
+                    if ui
+                        .add(egui::DragValue::new(&mut order).range(1..=u32::MAX))
+                        .on_hover_text("Position of this interaction in the sequence")
+                        .changed()
+                    {
+                        rel.order = Some(order);
+                        changed = true;
+                    }
+                });
+            }
+            changed |= crate::ui::anchor_picker(ui, ("anchor", rel_id), anchor);
+            changed |= crate::ui::line_style_picker(ui, ("line_style", rel_id), &mut rel.line_style);
+            if !rel.waypoints.is_empty() {
+                ui.horizontal(|ui| {
+                    let bends = rel.waypoints.len();
+                    ui.label(format!("{} bend{}", bends, if bends == 1 { "" } else { "s" }));
+                    if ui.small_button("Straighten").on_hover_text("Remove all bends").clicked() {
+                        rel.waypoints.clear();
+                        changed = true;
+                    }
+                });
+            }
+            changed |= crate::ui::direction_picker(ui, ("direction", rel_id), &mut rel.direction);
+            let note_edit = egui::TextEdit::multiline(&mut note)
+                .id_salt(("note", rel_id))
+                .desired_rows(1)
+                .hint_text("Note");
+            if ui.add(note_edit).changed() {
+                rel.note = (!note.trim().is_empty()).then_some(note);
+                changed = true;
+            }
+            changed |= crate::ui::tags_edit(ui, ("rel_tags", rel_id), &mut rel.tags);
+            if changed {
+                updated.push(rel_id);
+            }
+        }
+
+        for rel_id in updated {
+            self.diagram.emit(DiagramEvent::RelationshipUpdated(rel_id));
+        }
+        if let Some(rel_id) = reversed {
+            self.diagram.reverse_relationship(rel_id);
+        }
+    }
+
+    fn render_relationship_list(&mut self, ctx: &Context) {
+        if !self.show_relationship_list {
+            return;
+        }
+
+        TopBottomPanel::bottom("relationship_list")
+            .resizable(true)
+            .default_height(180.0)
+            .show(ctx, |ui| {
+                ui.heading("Relationships");
+                self.relationship_list.render(ui, &mut self.diagram);
+            });
+    }
+
+    fn render_project_panel(&mut self, ctx: &Context) {
+        let Some(project) = &self.project else {
+            return;
+        };
+        let mut open = None;
+        let mut refresh = false;
+        let mut close = false;
+        SidePanel::left("project")
+            .default_width(180.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(project.name());
+                    if ui.small_button("⟳").on_hover_text("Rescan the folder").clicked() {
+                        refresh = true;
+                    }
+                    if ui.small_button("✖").on_hover_text("Close the folder").clicked() {
+                        close = true;
+                    }
+                });
+                ui.separator();
+
+                if project.files.is_empty() {
+                    ui.colored_label(Color32::from_gray(120), "No diagrams in this folder");
+                }
+                // Files grouped by folder, top-level files first
+                let mut folders: std::collections::BTreeMap<&std::path::Path, Vec<&std::path::PathBuf>> =
+                    std::collections::BTreeMap::new();
+                for file in &project.files {
+                    folders.entry(file.parent().unwrap_or(std::path::Path::new(""))).or_default().push(file);
+                }
+                egui::ScrollArea::vertical().id_salt("project_scroll").show(ui, |ui| {
+                    let mut file_list = |ui: &mut egui::Ui, files: &[&std::path::PathBuf]| {
+                        for file in files {
+                            let path = project.absolute(file);
+                            let name = file.file_name().unwrap_or_default().to_string_lossy();
+                            let is_open = self.file_path.as_ref() == Some(&path);
+                            if ui.selectable_label(is_open, name).clicked() {
+                                open = Some(path);
+                            }
+                        }
+                    };
+                    for (folder, files) in &folders {
+                        if folder.as_os_str().is_empty() {
+                            file_list(ui, files);
+                        } else {
+                            egui::CollapsingHeader::new(format!("🗀 {}", folder.display()))
+                                .default_open(true)
+                                .show(ui, |ui| file_list(ui, files));
+                        }
+                    }
+                });
+            });
+
+        if let Some(path) = open {
+            self.open_in_tab(path);
+        }
+        if refresh {
+            self.refresh_project();
+        }
+        if close {
+            self.project = None;
+        }
+    }
+
+    fn render_tab_bar(&mut self, ctx: &Context) {
+        if self.tabs.len() < 2 && self.project.is_none() {
+            return;
+        }
+        let mut switch = None;
+        let mut close = None;
+        TopBottomPanel::top("tab_bar").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for (index, tab) in self.tabs.iter().enumerate() {
+                    let (diagram, path, unsaved) = match tab {
+                        Some(tab) => (&tab.diagram, &tab.file_path, tab.unsaved_changes),
+                        None => (&self.diagram, &self.file_path, self.unsaved_changes),
+                    };
+                    let title = match path.as_deref().and_then(std::path::Path::file_stem) {
+                        Some(stem) => stem.to_string_lossy().into_owned(),
+                        None => diagram.name.clone(),
+                    };
+                    let label = format!("{}{}", title, if unsaved { " *" } else { "" });
+                    let response = ui.selectable_label(index == self.active_tab, label);
+                    let response = match path {
+                        Some(path) => response.on_hover_text(path.display().to_string()),
+                        None => response,
+                    };
+                    if response.clicked() {
+                        switch = Some(index);
+                    }
+                    if ui.small_button("✖").on_hover_text("Close tab").clicked() {
+                        close = Some(index);
+                    }
+                    ui.separator();
+                }
+            });
+        });
+
+        if let Some(index) = switch {
+            self.switch_tab(index);
+        }
+        if let Some(index) = close {
+            self.request_close_tab(index);
+        }
+    }
+
+    fn render_metrics_panel(&mut self, ctx: &Context) {
+        if !self.show_metrics_panel {
+            return;
+        }
+
+        SidePanel::right("metrics")
+            .default_width(240.0)
+            .show(ctx, |ui| {
+                ui.heading("Fan-in / Fan-out");
+                ui.label("Relationships into and out of each element");
+                ui.separator();
+                self.metrics_panel.render(ui, &self.diagram, &mut self.selected_element);
+            });
+    }
+
+    fn render_comments_panel(&mut self, ctx: &Context) {
+        if !self.canvas.show_comments {
+            return;
+        }
+
+        SidePanel::right("comments")
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.heading("Comments");
+                ui.separator();
+                self.comments_panel.render(
+                    ui,
+                    &mut self.diagram,
+                    self.selected_element,
+                    &self.preferences.author,
+                );
+            });
+    }
+
+    fn render_dsl_editor(&mut self, ctx: &Context) {
+        if !self.show_dsl_editor {
+            return;
+        }
+
+        SidePanel::right("dsl_editor")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.heading("Text");
+                ui.separator();
+
+                if let Some(error) = &self.dsl_error {
+                    ui.colored_label(Color32::from_rgb(200, 0, 0), error);
+                }
+
+                let response = egui::ScrollArea::vertical()
+                    .id_salt("dsl_editor_scroll")
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut self.dsl_text)
+                                .code_editor()
+                                .desired_width(f32::INFINITY)
+                                .desired_rows(30),
+                        )
+                    })
+                    .inner;
+
+                if response.changed() {
+                    // Text -> canvas: apply as soon as the text parses
+                    match crate::dsl::apply_dsl(&mut self.diagram, &self.dsl_text) {
+                        Ok(()) => {
+                            self.dsl_error = None;
+                            if let Some(id) = self.selected_element
+                                && self.diagram.get_element(id).is_none()
+                            {
+                                self.selected_element = None;
+                            }
+                        }
+                        Err(e) => self.dsl_error = Some(e.to_string()),
+                    }
+                } else if !response.has_focus() && self.dsl_error.is_none() {
+                    // Canvas -> text: keep the text in sync while not being edited
+                    let text = crate::dsl::to_dsl(&self.diagram);
+                    if text != self.dsl_text {
+                        self.dsl_text = text;
+                    }
+                }
+            });
     }
 
     fn render_menu_bar(&mut self, ctx: &Context) {
@@ -314,78 +1912,1355 @@ impl C2DrawApp {
                         self.new_diagram();
                         ui.close();
                     }
-                    if ui.button("Open...").clicked() {
-                        self.open_diagram();
-                        ui.close();
+                    if ui.button("Open...").clicked() {
+                        self.open_diagram();
+                        ui.close();
+                    }
+                    if ui.button("Open Folder...")
+                        .on_hover_text("Browse the diagrams in a folder and open them in tabs")
+                        .clicked()
+                    {
+                        self.open_project();
+                        ui.close();
+                    }
+                    ui.menu_button("Merge", |ui| {
+                        for strategy in MergeStrategy::ALL {
+                            let hover = match strategy {
+                                MergeStrategy::KeepBoth => "Add every element of another diagram, even ones with a name already used here",
+                                MergeStrategy::MatchByName => "Add another diagram, reusing elements here with the same type and name",
+                            };
+                            if ui.button(format!("{}...", strategy.as_str())).on_hover_text(hover).clicked() {
+                                self.merge_file(strategy);
+                                ui.close();
+                            }
+                        }
+                    });
+                    if ui.button("Compare with File...")
+                        .on_hover_text("Highlight what was added, removed, changed or moved since another version of this diagram")
+                        .clicked()
+                    {
+                        self.compare_with_file();
+                        ui.close();
+                    }
+                    ui.menu_button("Import", |ui| {
+                        if ui.button("CSV...")
+                            .on_hover_text("Create a diagram from an elements CSV and an optional relationships CSV")
+                            .clicked()
+                        {
+                            self.import_csv();
+                            ui.close();
+                        }
+                        if ui.button("Cargo Workspace...")
+                            .on_hover_text("Create a container diagram from the crates of a Cargo workspace")
+                            .clicked()
+                        {
+                            self.import_cargo_workspace();
+                            ui.close();
+                        }
+                        if ui.button("docker-compose...")
+                            .on_hover_text("Create a container diagram from the services of a docker-compose file")
+                            .clicked()
+                        {
+                            self.import_compose();
+                            ui.close();
+                        }
+                        if ui.button("Kubernetes Manifests...")
+                            .on_hover_text("Create a container diagram from a folder of Kubernetes YAML")
+                            .clicked()
+                        {
+                            self.import_kubernetes();
+                            ui.close();
+                        }
+                        if ui.button("Terraform...")
+                            .on_hover_text("Create a container diagram from a terraform.tfstate or `terraform show -json` output")
+                            .clicked()
+                        {
+                            self.import_terraform();
+                            ui.close();
+                        }
+                        if ui.button("OpenAPI...")
+                            .on_hover_text("Connect consumers to an API container using the operations of an OpenAPI document")
+                            .clicked()
+                        {
+                            self.load_openapi_spec();
+                            ui.close();
+                        }
+                        if ui.button("Snippet...")
+                            .on_hover_text("Insert elements and relationships from a snippet file")
+                            .clicked()
+                        {
+                            self.import_snippet();
+                            ui.close();
+                        }
+                    });
+                    ui.separator();
+                    if ui.button("Save").clicked() {
+                        self.save_diagram();
+                        ui.close();
+                    }
+                    if ui.button("Save As...").clicked() {
+                        self.save_diagram_as();
+                        ui.close();
+                    }
+                    if ui.button("Export Snippet...")
+                        .on_hover_text("Save some elements and their relationships for reuse in other diagrams")
+                        .clicked()
+                    {
+                        self.open_snippet_window();
+                        ui.close();
+                    }
+                    if ui.add_enabled(self.file_path.is_some(), egui::Button::new("Restore Backup..."))
+                        .on_hover_text("Replace the diagram with an earlier saved version")
+                        .clicked()
+                    {
+                        self.open_backups_window();
+                        ui.close();
+                    }
+                    ui.separator();
+                    if ui.button("Diagram Properties...")
+                        .on_hover_text("Name, description, type, author and version of this diagram")
+                        .clicked()
+                    {
+                        self.show_diagram_properties = true;
+                        ui.close();
+                    }
+                    if ui.button("Preferences...").clicked() {
+                        self.show_preferences_window = true;
+                        ui.close();
+                    }
+                    ui.separator();
+                    if ui.button("Copy Share Link")
+                        .on_hover_text("Copy a link containing the whole diagram, compressed into the URL")
+                        .clicked()
+                    {
+                        self.copy_share_link(ctx);
+                        ui.close();
+                    }
+                    if ui.button("Open Share Link...").clicked() {
+                        self.share_link_error = None;
+                        self.show_share_link_window = true;
+                        ui.close();
+                    }
+                    ui.separator();
+                    if ui.button("Exit").clicked() {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                    }
+                });
+
+                ui.menu_button("Edit", |ui| {
+                    if ui.add_enabled(self.history.can_undo(), egui::Button::new("Undo").shortcut_text(ctx.format_shortcut(&keymap::shortcut(Command::Undo)))).clicked() {
+                        self.undo();
+                        ui.close();
+                    }
+                    if ui.add_enabled(self.history.can_redo(), egui::Button::new("Redo").shortcut_text(ctx.format_shortcut(&keymap::shortcut(Command::Redo)))).clicked() {
+                        self.redo();
+                        ui.close();
+                    }
+                    ui.separator();
+                    if ui.add_enabled(self.selected_element.is_some(), egui::Button::new("Copy").shortcut_text(ctx.format_shortcut(&COPY_SHORTCUT)))
+                        .on_hover_text("Copy the selected element, with the elements inside it, to paste here or in another window")
+                        .clicked()
+                    {
+                        self.copy_selection(ctx);
+                        ui.close();
+                    }
+                    if ui.add(egui::Button::new("Paste").shortcut_text(ctx.format_shortcut(&PASTE_SHORTCUT))).clicked() {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::RequestPaste);
+                        ui.close();
+                    }
+                    if ui.button("Group Elements...")
+                        .on_hover_text("Tie elements together so dragging one moves them all")
+                        .clicked()
+                    {
+                        self.open_group_window();
+                        ui.close();
+                    }
+                    if ui.button("Add Text Label")
+                        .on_hover_text("Free text for titles and section headers; not part of the C4 exports")
+                        .clicked()
+                    {
+                        self.add_text_label();
+                        ui.close();
+                    }
+                    ui.separator();
+                    for (command, label) in [(Command::BringToFront, "Bring to Front"), (Command::SendToBack, "Send to Back")] {
+                        let button = egui::Button::new(label).shortcut_text(ctx.format_shortcut(&keymap::shortcut(command)));
+                        if ui.add_enabled(self.selected_element.is_some(), button).clicked()
+                            && let Some(id) = self.selected_element
+                        {
+                            self.restack(id, command);
+                            ui.close();
+                        }
+                    }
+                    ui.separator();
+                    if ui.button("Validate...")
+                        .on_hover_text("Check the diagram for dangling relationships, missing or duplicate names and misplaced elements")
+                        .clicked()
+                    {
+                        self.show_validation_window = true;
+                        ui.close();
+                    }
+                });
+                ui.menu_button("Export", |ui| {
+                    if ui.button("C4-PlantUML...")
+                        .on_hover_text("Export diagram to PlantUML format (requires PlantUML to render)")
+                        .clicked()
+                    {
+                        self.show_export(ExportFormat::PlantUml);
+                        ui.close();
+                    }
+                    if ui.button("Open in PlantUML Server")
+                        .on_hover_text("Render the diagram on plantuml.com in your web browser")
+                        .clicked()
+                    {
+                        self.open_in_plantuml_server(ctx);
+                        ui.close();
+                    }
+                    if ui.button("Mermaid...")
+                        .on_hover_text("Export diagram to Mermaid format (works in GitHub, Notion, etc.)")
+                        .clicked()
+                    {
+                        self.show_export(ExportFormat::Mermaid);
+                        ui.close();
+                    }
+                    if ui.button("Mermaid Flowchart...")
+                        .on_hover_text("Export diagram as a plain Mermaid flowchart (for renderers without C4 support)")
+                        .clicked()
+                    {
+                        self.show_export(ExportFormat::MermaidFlowchart);
+                        ui.close();
+                    }
+                    if ui.button("SVG Image...")
+                        .on_hover_text("Save the diagram as an SVG image; elements with a URL are clickable")
+                        .clicked()
+                    {
+                        self.export_svg();
+                        ui.close();
+                    }
+                    if ui.button("Structurizr Workspace...")
+                        .on_hover_text("Save a Structurizr workspace JSON file that keeps the canvas layout, for upload to Structurizr")
+                        .clicked()
+                    {
+                        self.export_structurizr();
+                        ui.close();
+                    }
+                    if ui.button("Poster Tiles...")
+                        .on_hover_text("Split the diagram across printable pages to assemble a large poster")
+                        .clicked()
+                    {
+                        self.show_poster_window = true;
+                        ui.close();
+                    }
+                    ui.separator();
+                    if ui.button("ADR...")
+                        .on_hover_text("Save an Architecture Decision Record template that links to and inventories this diagram")
+                        .clicked()
+                    {
+                        self.export_adr();
+                        ui.close();
+                    }
+                    if ui.button("Documentation Site...")
+                        .on_hover_text("Write every diagram in this folder as Markdown pages with SVG images and an index")
+                        .clicked()
+                    {
+                        self.export_docs();
+                        ui.close();
+                    }
+                });
+
+                ui.menu_button("View", |ui| {
+                    ui.label("Diagram Type");
+                    ui.radio_value(&mut self.diagram.diagram_type, DiagramType::SystemContext, "System Context (C1)")
+                        .on_hover_text("Show system-level view (people and systems)");
+                    ui.radio_value(&mut self.diagram.diagram_type, DiagramType::Container, "Container (C2)")
+                        .on_hover_text("Show container-level view (apps, databases, etc.)");
+                    ui.radio_value(&mut self.diagram.diagram_type, DiagramType::Component, "Component (C3)")
+                        .on_hover_text("Show the components inside a container");
+                    ui.radio_value(&mut self.diagram.diagram_type, DiagramType::Dynamic, "Dynamic")
+                        .on_hover_text("Number the relationships as the steps of one interaction");
+                    ui.separator();
+                    ui.radio_value(&mut self.main_view, MainView::Canvas, "Canvas")
+                        .on_hover_text("Edit the diagram visually");
+                    ui.radio_value(&mut self.main_view, MainView::DependencyMatrix, "Dependency Matrix")
+                        .on_hover_text("Show sources against targets to audit coupling");
+                    ui.radio_value(&mut self.main_view, MainView::Table, "Element Table")
+                        .on_hover_text("Edit all elements as rows of a spreadsheet");
+                    ui.separator();
+                    ui.checkbox(&mut self.show_dsl_editor, "Text Editor")
+                        .on_hover_text("Edit the diagram as text side by side with the canvas");
+                    ui.checkbox(&mut self.show_relationship_list, "Relationships")
+                        .on_hover_text("List, filter and edit all relationships");
+                    ui.checkbox(&mut self.show_metrics_panel, "Fan-in / Fan-out")
+                        .on_hover_text("Count each element's incoming and outgoing relationships");
+                    ui.checkbox(&mut self.canvas.show_comments, "Comments")
+                        .on_hover_text("Show review comments; right-click the canvas to comment on a point");
+                    ui.checkbox(&mut self.show_impact, "Impact Analysis")
+                        .on_hover_text("Highlight everything the selected element reaches through its relationships, coloured by distance");
+                    ui.add_enabled(self.show_impact, egui::Checkbox::new(&mut self.impact_upstream, "Include Upstream"))
+                        .on_hover_text("Also highlight, in blue, everything that reaches the selected element");
+                    ui.checkbox(&mut self.show_cycles, "Highlight Cycles")
+                        .on_hover_text("Draw elements and relationships that depend on each other in a circle in red");
+                    ui.separator();
+                    if ui.checkbox(&mut self.canvas.hide_external, "Hide External Elements")
+                        .on_hover_text("Leave external persons and systems off the canvas and exports without deleting them")
+                        .changed()
+                    {
+                        let selected = self.selected_element.and_then(|id| self.diagram.get_element(id));
+                        if selected.is_some_and(|e| !self.canvas.is_shown(e)) {
+                            self.selected_element = None;
+                            self.canvas.cancel_relationship();
+                        }
+                        self.refresh_export();
+                    }
+                    ui.menu_button("Active View", |ui| {
+                        let mut active = self.diagram.active_view;
+                        ui.radio_value(&mut active, None, "Whole Diagram");
+                        for view in &self.diagram.views {
+                            ui.radio_value(&mut active, Some(view.id), &view.name);
+                        }
+                        if active != self.diagram.active_view {
+                            self.show_view(active);
+                        }
+                        ui.separator();
+                        if ui.button("Manage Views...")
+                            .on_hover_text("Define views that show only elements with certain tags or types")
+                            .clicked()
+                        {
+                            self.show_views_window = true;
+                            ui.close();
+                        }
+                    });
+                });
+                ui.menu_button("Analyze", |ui| {
+                    if ui.button("Statistics...")
+                        .on_hover_text("Count elements by type, relationships and unconnected elements")
+                        .clicked()
+                    {
+                        self.show_stats_window = true;
+                        ui.close();
+                    }
+                });
+                ui.menu_button("Help", |ui| {
+                    let shortcut = ctx.format_shortcut(&keymap::shortcut(Command::ShowShortcuts));
+                    if ui.add(egui::Button::new("Keyboard Shortcuts").shortcut_text(shortcut)).clicked() {
+                        self.show_shortcuts_window = true;
+                        ui.close();
+                    }
+                });
+            });
+        });
+    }
+
+    fn render_export_window(&mut self, ctx: &Context) {
+        if self.show_export_window {
+            egui::Window::new("Export")
+                .id(Id::new("export_window"))
+                .collapsible(false)
+                .resizable(true)
+                .default_size([500.0, 400.0])
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        for format in ExportFormat::ALL {
+                            if ui.selectable_label(self.export_format == format, format.as_str()).clicked()
+                                && self.export_format != format
+                            {
+                                self.show_export(format);
+                            }
+                        }
+                    });
+                    ui.separator();
+
+                    if self.export_format == ExportFormat::PlantUml {
+                        let options = &mut self.plantuml_options;
+                        let mut changed = false;
+                        ui.horizontal_wrapped(|ui| {
+                            changed |= ui
+                                .checkbox(&mut options.include_notes, "Relationship notes")
+                                .on_hover_text("Emit relationship notes as PlantUML notes")
+                                .changed();
+                            changed |= ui
+                                .checkbox(&mut options.show_person_outline, "Person outline")
+                                .on_hover_text("Draw people as outlined figures (SHOW_PERSON_OUTLINE)")
+                                .changed();
+                            changed |= ui
+                                .checkbox(&mut options.hide_stereotype, "Hide stereotypes")
+                                .on_hover_text("Hide the <<stereotype>> labels (HIDE_STEREOTYPE)")
+                                .changed();
+                            changed |= ui
+                                .checkbox(&mut options.show_floating_legend, "Floating legend")
+                                .on_hover_text("Add a floating legend (SHOW_FLOATING_LEGEND)")
+                                .changed();
+                            changed |= ui
+                                .checkbox(&mut options.sketch_mode, "Sketch")
+                                .on_hover_text("Render in a hand-drawn style (LAYOUT_AS_SKETCH)")
+                                .changed();
+                        });
+                        if changed {
+                            self.refresh_export();
+                        }
+                        ui.separator();
+                    }
+
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        crate::ui::code_view(ui, self.export_format, &self.export_content);
+                    });
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Copy to Clipboard")
+                            .on_hover_text("Copy the export code to your clipboard")
+                            .clicked()
+                        {
+                            ctx.copy_text(self.export_content.clone());
+                        }
+                        if ui.add_enabled(self.file_path.is_some(), egui::Button::new("Copy Markdown Embed"))
+                            .on_hover_text("Save an SVG next to the diagram file and copy Markdown showing it, with this source collapsed below and a link to the diagram")
+                            .on_disabled_hover_text("Save the diagram first so the snippet can link to it")
+                            .clicked()
+                        {
+                            self.copy_embed_snippet(ctx);
+                        }
+                        let can_render = self.export_format.render_source().is_available(&self.preferences);
+                        if ui.add_enabled(can_render, egui::Button::new("Render Preview"))
+                            .on_hover_text("Render with the local tool configured in Preferences")
+                            .on_disabled_hover_text("Configure a local renderer in File → Preferences")
+                            .clicked()
+                        {
+                            self.render_preview(ctx);
+                        }
+                        if ui.add_enabled(self.preview_png.is_some(), egui::Button::new("Save PNG..."))
+                            .clicked()
+                        {
+                            self.save_preview_png();
+                        }
+                        if ui.button("Close").clicked() {
+                            self.show_export_window = false;
+                        }
+                    });
+
+                    if let Some(error) = &self.preview_error {
+                        ui.colored_label(Color32::from_rgb(200, 0, 0), error);
+                    }
+                    if let Some(texture) = &self.preview_texture {
+                        ui.separator();
+                        egui::ScrollArea::both()
+                            .id_salt("export_preview")
+                            .max_height(400.0)
+                            .show(ui, |ui| {
+                                ui.image((texture.id(), texture.size_vec2()));
+                            });
+                    }
+                });
+        }
+    }
+
+    fn render_diagram_properties_window(&mut self, ctx: &Context) {
+        if !self.show_diagram_properties {
+            return;
+        }
+        let mut open = true;
+        let mut metadata = self.diagram.metadata.clone();
+        let mut changed = false;
+        let mut add_note = false;
+        egui::Window::new("Diagram Properties")
+            .id(Id::new("diagram_properties_window"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("diagram_properties").num_columns(2).show(ui, |ui| {
+                    ui.label("Name");
+                    changed |= ui.text_edit_singleline(&mut self.diagram.name).changed();
+                    ui.end_row();
+
+                    ui.label("Description");
+                    changed |= ui.text_edit_multiline(&mut self.diagram.description).changed();
+                    ui.end_row();
+
+                    ui.label("Type");
+                    egui::ComboBox::from_id_salt("diagram_type")
+                        .selected_text(self.diagram.diagram_type.as_str())
+                        .show_ui(ui, |ui| {
+                            for diagram_type in DiagramType::ALL {
+                                changed |= ui
+                                    .selectable_value(&mut self.diagram.diagram_type, diagram_type, diagram_type.as_str())
+                                    .changed();
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Author");
+                    ui.text_edit_singleline(&mut metadata.author);
+                    ui.end_row();
+
+                    ui.label("Version");
+                    ui.add(egui::TextEdit::singleline(&mut metadata.version_label).hint_text("e.g. v2.1 or Draft"));
+                    ui.end_row();
+
+                    ui.label("Created");
+                    ui.label(metadata.created.as_deref().unwrap_or("Not saved yet"));
+                    ui.end_row();
+
+                    ui.label("Modified");
+                    ui.label(metadata.modified.as_deref().unwrap_or("Not saved yet"));
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.label("Revision notes");
+                egui::ScrollArea::vertical().max_height(120.0).show(ui, |ui| {
+                    if metadata.changelog.is_empty() {
+                        ui.weak("No notes yet");
+                    }
+                    for revision in metadata.changelog.iter().rev() {
+                        ui.label(revision.summary());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let response = ui.add(egui::TextEdit::singleline(&mut self.revision_note).hint_text("What changed?"));
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    let can_add = !self.revision_note.trim().is_empty();
+                    add_note = ui.add_enabled(can_add, egui::Button::new("Add Note")).clicked() || (submitted && can_add);
+                });
+            });
+        if changed {
+            self.diagram.emit(DiagramEvent::PropertiesChanged);
+        }
+        self.diagram.set_metadata(metadata);
+        if add_note {
+            let author = match self.diagram.metadata.author.trim() {
+                "" => self.preferences.author.clone(),
+                author => author.to_string(),
+            };
+            self.diagram.add_revision(&author, &self.revision_note, std::time::SystemTime::now());
+            self.revision_note.clear();
+        }
+        self.show_diagram_properties = open;
+    }
+
+    fn render_poster_window(&mut self, ctx: &Context) {
+        if !self.show_poster_window {
+            return;
+        }
+        let mut open = true;
+        let mut export = false;
+        egui::Window::new("Poster Tiles")
+            .id(Id::new("poster_window"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let options = &mut self.poster_options;
+                egui::Grid::new("poster_options").num_columns(2).show(ui, |ui| {
+                    ui.label("Page size");
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt("poster_page_size")
+                            .selected_text(options.page_size.as_str())
+                            .show_ui(ui, |ui| {
+                                for page_size in PageSize::ALL {
+                                    ui.selectable_value(&mut options.page_size, page_size, page_size.as_str());
+                                }
+                            });
+                        ui.checkbox(&mut options.landscape, "Landscape");
+                    });
+                    ui.end_row();
+
+                    ui.label("Scale");
+                    ui.add(egui::DragValue::new(&mut options.scale).range(0.1..=10.0).speed(0.05).suffix("×"))
+                        .on_hover_text("1× prints the diagram at its on-screen size");
+                    ui.end_row();
+
+                    ui.label("Overlap");
+                    ui.add(egui::DragValue::new(&mut options.overlap_mm).range(0.0..=50.0).suffix(" mm"))
+                        .on_hover_text("Strip repeated on neighbouring pages, marked with dashed lines for trimming");
+                    ui.end_row();
+                });
+
+                let (rows, columns) = poster::poster_grid(&self.visible_diagram(), &self.poster_options);
+                ui.label(format!("{} × {} pages ({} in total)", columns, rows, rows * columns));
+                ui.horizontal(|ui| {
+                    export = ui.button("Export...").on_hover_text("Write one SVG file per page into a folder").clicked();
+                    if ui.button("Cancel").clicked() {
+                        self.show_poster_window = false;
+                    }
+                });
+            });
+        self.show_poster_window &= open;
+        if export {
+            self.export_poster();
+        }
+    }
+
+    fn render_preferences_window(&mut self, ctx: &Context) {
+        if self.show_preferences_window {
+            egui::Window::new("Preferences")
+                .id(Id::new("preferences_window"))
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.heading("Offline Rendering");
+                    egui::Grid::new("render_preferences").num_columns(2).show(ui, |ui| {
+                        ui.label("Java executable");
+                        ui.text_edit_singleline(&mut self.preferences.java_path);
+                        ui.end_row();
+
+                        ui.label("plantuml.jar");
+                        ui.text_edit_singleline(&mut self.preferences.plantuml_jar)
+                            .on_hover_text("Path to a local plantuml.jar (leave empty to disable)");
+                        ui.end_row();
+
+                        ui.label("mmdc");
+                        ui.text_edit_singleline(&mut self.preferences.mmdc_path)
+                            .on_hover_text("Path to the Mermaid CLI (leave empty to disable)");
+                        ui.end_row();
+                    });
+
+                    ui.separator();
+                    ui.heading("Saving");
+                    ui.horizontal(|ui| {
+                        ui.label("Backups to keep");
+                        ui.add(egui::DragValue::new(&mut self.preferences.backup_count).range(0..=100))
+                            .on_hover_text("Earlier versions kept as name.c4d.bak-<timestamp> (0 disables backups)");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Also write");
+                        ui.checkbox(&mut self.preferences.sidecar_plantuml, "PlantUML (.puml)")
+                            .on_hover_text("Rewrite name.puml next to the diagram file on every save");
+                        ui.checkbox(&mut self.preferences.sidecar_mermaid, "Mermaid (.mmd)")
+                            .on_hover_text("Rewrite name.mmd next to the diagram file on every save");
+                    });
+
+                    ui.separator();
+                    ui.heading("Comments");
+                    ui.horizontal(|ui| {
+                        ui.label("Author name");
+                        ui.text_edit_singleline(&mut self.preferences.author);
+                    });
+
+                    ui.separator();
+                    ui.heading("Relationships");
+                    ui.horizontal(|ui| {
+                        ui.label("Line style");
+                        egui::ComboBox::from_id_salt("line_style_preference")
+                            .selected_text(self.preferences.line_style.as_str())
+                            .show_ui(ui, |ui| {
+                                for style in LineStyle::ALL {
+                                    ui.selectable_value(&mut self.preferences.line_style, style, style.as_str());
+                                }
+                            })
+                            .response
+                            .on_hover_text("Used for relationships that do not choose their own style");
+                    });
+
+                    ui.separator();
+                    ui.heading("Stylesheet");
+                    ui.horizontal(|ui| {
+                        let name = match self.preferences.stylesheet_path.trim() {
+                            "" => "Built-in".to_string(),
+                            path => {
+                                let file = std::path::Path::new(path)
+                                    .file_name()
+                                    .map_or_else(|| path.to_string(), |n| n.to_string_lossy().into_owned());
+                                match self.canvas.stylesheet.name.as_str() {
+                                    "" => file,
+                                    sheet => format!("{} ({})", sheet, file),
+                                }
+                            }
+                        };
+                        ui.label(name).on_hover_text(&self.preferences.stylesheet_path);
+                        if ui.button("Choose...")
+                            .on_hover_text("Apply a .c4style file's colours, shapes, fonts and tag rules to all diagrams")
+                            .clicked()
+                        {
+                            self.choose_stylesheet();
+                        }
+                        let has_stylesheet = !self.preferences.stylesheet_path.trim().is_empty();
+                        if ui.add_enabled(has_stylesheet, egui::Button::new("Reload")).clicked() {
+                            self.apply_stylesheet();
+                        }
+                        if ui.add_enabled(has_stylesheet, egui::Button::new("Clear")).clicked() {
+                            self.preferences.stylesheet_path.clear();
+                            self.apply_stylesheet();
+                        }
+                    });
+
+                    ui.separator();
+                    egui::CollapsingHeader::new("New Elements")
+                        .id_salt("element_template_preferences")
+                        .show(ui, |ui| {
+                            ui.label("Name, text and size of elements added from the sidebar");
+                            for kind in ElementKind::ALL {
+                                self.render_element_template(ui, kind);
+                            }
+                        });
+                    egui::CollapsingHeader::new("Template Library")
+                        .id_salt("catalog_preferences")
+                        .show(ui, |ui| self.render_catalog(ui));
+
+                    ui.separator();
+                    ui.heading("Icon Packs");
+                    ui.horizontal(|ui| {
+                        for pack in IconPack::ALL {
+                            let mut enabled = self.preferences.icon_packs.contains(&pack);
+                            if ui.checkbox(&mut enabled, pack.as_str())
+                                .on_hover_text("Offer this pack's icons for containers")
+                                .changed()
+                            {
+                                if enabled {
+                                    self.preferences.icon_packs.push(pack);
+                                    self.preferences.icon_packs.sort();
+                                } else {
+                                    self.preferences.icon_packs.retain(|&p| p != pack);
+                                }
+                            }
+                        }
+                    });
+
+                    ui.separator();
+                    ui.heading("Canvas Text");
+                    egui::Grid::new("typography_preferences").num_columns(2).show(ui, |ui| {
+                        let typography = &mut self.preferences.typography;
+                        for (label, size) in [
+                            ("Element names", &mut typography.name_size),
+                            ("Descriptions", &mut typography.description_size),
+                            ("Relationship labels", &mut typography.label_size),
+                        ] {
+                            ui.label(label);
+                            ui.add(
+                                egui::DragValue::new(size)
+                                    .range(Typography::SIZE_RANGE)
+                                    .speed(0.5)
+                                    .suffix(" pt"),
+                            );
+                            ui.end_row();
+                        }
+                    });
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("Save").clicked() {
+                            if let Err(e) = self.preferences.save() {
+                                self.error_message = Some(format!("Could not save preferences: {}", e));
+                            }
+                            self.show_preferences_window = false;
+                        }
+                        if ui.button("Close").clicked() {
+                            self.show_preferences_window = false;
+                        }
+                    });
+                });
+        }
+    }
+
+    /// Preference fields for one kind's new-element template
+    fn render_element_template(&mut self, ui: &mut egui::Ui, kind: ElementKind) {
+        let mut template = self.preferences.element_template(kind);
+        let has_technology = matches!(
+            kind,
+            ElementKind::Container | ElementKind::Database | ElementKind::Queue | ElementKind::Component
+        );
+        let mut changed = false;
+        let mut reset = false;
+        egui::CollapsingHeader::new(kind.as_str())
+            .id_salt(("element_template", kind))
+            .show(ui, |ui| {
+                egui::Grid::new(("element_template_grid", kind)).num_columns(2).show(ui, |ui| {
+                    ui.label("Name");
+                    changed |= ui.text_edit_singleline(&mut template.name).changed();
+                    ui.end_row();
+
+                    if kind != ElementKind::Note {
+                        ui.label("Description");
+                        changed |= ui.text_edit_singleline(&mut template.description).changed();
+                        ui.end_row();
+                    }
+
+                    if has_technology {
+                        ui.label("Technology");
+                        changed |= ui.text_edit_singleline(&mut template.technology).changed();
+                        ui.end_row();
                     }
-                    ui.separator();
-                    if ui.button("Save").clicked() {
-                        self.save_diagram();
-                        ui.close();
+
+                    ui.label("Size");
+                    ui.horizontal(|ui| {
+                        changed |= ui.add(egui::DragValue::new(&mut template.size.width).range(40.0..=1000.0)).changed();
+                        ui.label("×");
+                        changed |= ui.add(egui::DragValue::new(&mut template.size.height).range(30.0..=1000.0)).changed();
+                    });
+                    ui.end_row();
+                });
+                let customised = self.preferences.element_templates.contains_key(&kind);
+                reset = ui.add_enabled(customised, egui::Button::new("Reset")).clicked();
+            });
+        if reset {
+            self.preferences.element_templates.remove(&kind);
+        } else if changed {
+            self.preferences.element_templates.insert(kind, template);
+        }
+    }
+
+    /// Editable list of the sidebar's template library
+    fn render_catalog(&mut self, ui: &mut egui::Ui) {
+        ui.label("Pre-filled elements listed under Templates in the sidebar");
+        let mut removed = None;
+        egui::Grid::new("catalog_grid").num_columns(5).striped(true).show(ui, |ui| {
+            ui.strong("Label");
+            ui.strong("Type");
+            ui.strong("Name");
+            ui.strong("Technology");
+            ui.end_row();
+            for (index, entry) in self.preferences.catalog.iter_mut().enumerate() {
+                ui.text_edit_singleline(&mut entry.label);
+                let mut kind = entry.kind;
+                egui::ComboBox::from_id_salt(("catalog_kind", index))
+                    .selected_text(kind.as_str())
+                    .show_ui(ui, |ui| {
+                        for option in ElementKind::ALL {
+                            ui.selectable_value(&mut kind, option, option.as_str());
+                        }
+                    });
+                if kind != entry.kind {
+                    entry.kind = kind;
+                    entry.template.size = entry.template.element_type(kind).default_size();
+                }
+                ui.text_edit_singleline(&mut entry.template.name);
+                ui.text_edit_singleline(&mut entry.template.technology);
+                if ui.button("🗑").on_hover_text("Remove from the library").clicked() {
+                    removed = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(index) = removed {
+            self.preferences.catalog.remove(index);
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Add").clicked() {
+                self.preferences.catalog.push(CatalogEntry::new("New Template", ElementKind::Container, "Container", "", ""));
+            }
+            if ui.button("Restore Built-in").on_hover_text("Replace the library with the one C2Draw ships with").clicked() {
+                self.preferences.catalog = crate::preferences::builtin_catalog();
+            }
+        });
+    }
+
+    /// Cheat-sheet of every key binding, generated from the keymap
+    fn render_shortcuts_window(&mut self, ctx: &Context) {
+        if !self.show_shortcuts_window {
+            return;
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.show_shortcuts_window = false;
+            return;
+        }
+        egui::Window::new("Keyboard Shortcuts")
+            .id(Id::new("shortcuts_window"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .open(&mut self.show_shortcuts_window)
+            .show(ctx, |ui| {
+                egui::Grid::new("shortcuts_grid").num_columns(2).spacing([24.0, 4.0]).show(ui, |ui| {
+                    for (category, rows) in keymap::cheat_sheet() {
+                        ui.strong(category);
+                        ui.end_row();
+                        for row in rows {
+                            let keys: Vec<String> = row.shortcuts.iter().map(|s| ctx.format_shortcut(s)).collect();
+                            ui.monospace(keys.join(" / "));
+                            ui.label(row.description);
+                            ui.end_row();
+                        }
                     }
-                    if ui.button("Save As...").clicked() {
-                        self.save_diagram_as();
-                        ui.close();
+                    ui.strong("Mouse");
+                    ui.end_row();
+                    for (input, description) in keymap::GESTURES {
+                        ui.monospace(input);
+                        ui.label(description);
+                        ui.end_row();
                     }
-                    ui.separator();
-                    if ui.button("Exit").clicked() {
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                });
+                ui.separator();
+                ui.weak("Press ? or Esc to close");
+            });
+    }
+
+    fn render_error_window(&mut self, ctx: &Context) {
+        if let Some(message) = &self.error_message {
+            let mut open = true;
+            egui::Window::new("Error")
+                .id(Id::new("error_window"))
+                .collapsible(false)
+                .resizable(false)
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    ui.colored_label(Color32::from_rgb(200, 0, 0), message);
+                });
+            if !open {
+                self.error_message = None;
+            }
+        }
+    }
+
+    fn render_close_tab_window(&mut self, ctx: &Context) {
+        let Some(index) = self.pending_close_tab else {
+            return;
+        };
+        let mut close = false;
+        let mut cancel = false;
+        egui::Window::new("Unsaved Changes")
+            .id(Id::new("close_tab_window"))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("This diagram has unsaved changes. Close it anyway?");
+                ui.horizontal(|ui| {
+                    if ui.button("Discard Changes").clicked() {
+                        close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
                     }
                 });
+            });
+        if close || cancel {
+            self.pending_close_tab = None;
+        }
+        if close {
+            self.close_tab(index);
+        }
+    }
 
-                ui.menu_button("Export", |ui| {
-                    if ui.button("C4-PlantUML...")
-                        .on_hover_text("Export diagram to PlantUML format (requires PlantUML to render)")
-                        .clicked()
-                    {
-                        self.export_plantuml();
-                        ui.close();
+    fn render_backups_window(&mut self, ctx: &Context) {
+        if self.show_backups_window {
+            let mut restore = None;
+            egui::Window::new("Restore Backup")
+                .id(Id::new("backups_window"))
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    if self.backups.is_empty() {
+                        ui.label("No backups of this diagram yet.");
+                    } else {
+                        ui.label("Restored diagrams replace the open one until saved.");
+                        egui::Grid::new("backups").num_columns(2).show(ui, |ui| {
+                            for backup in &self.backups {
+                                ui.label(backup.display_time());
+                                if ui.button("Restore").clicked() {
+                                    restore = Some(backup.path.clone());
+                                }
+                                ui.end_row();
+                            }
+                        });
                     }
-                    if ui.button("Mermaid...")
-                        .on_hover_text("Export diagram to Mermaid format (works in GitHub, Notion, etc.)")
-                        .clicked()
-                    {
-                        self.export_mermaid();
-                        ui.close();
+
+                    if ui.button("Close").clicked() {
+                        self.show_backups_window = false;
                     }
                 });
+            if let Some(path) = restore {
+                self.restore_backup(&path);
+            }
+        }
+    }
 
-                ui.menu_button("View", |ui| {
-                    ui.label("Diagram Type");
-                    ui.radio_value(&mut self.diagram.diagram_type, DiagramType::SystemContext, "System Context (C1)")
-                        .on_hover_text("Show system-level view (people and systems)");
-                    ui.radio_value(&mut self.diagram.diagram_type, DiagramType::Container, "Container (C2)")
-                        .on_hover_text("Show container-level view (apps, databases, etc.)");
+    fn render_snippet_window(&mut self, ctx: &Context) {
+        if self.show_snippet_window {
+            egui::Window::new("Export Snippet")
+                .id(Id::new("snippet_window"))
+                .collapsible(false)
+                .resizable(true)
+                .show(ctx, |ui| {
+                    ui.label("Elements to include (relationships between them are kept):");
+                    let mut elements: Vec<&Element> = self.diagram.elements.values().collect();
+                    elements.sort_by(|a, b| a.name().cmp(b.name()));
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        for element in elements {
+                            let mut included = self.snippet_selection.contains(&element.id);
+                            if ui.checkbox(&mut included, element.name()).changed() {
+                                if included {
+                                    self.snippet_selection.insert(element.id);
+                                } else {
+                                    self.snippet_selection.remove(&element.id);
+                                }
+                            }
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(!self.snippet_selection.is_empty(), egui::Button::new("Save..."))
+                            .clicked()
+                        {
+                            self.export_snippet();
+                        }
+                        if ui.button("Close").clicked() {
+                            self.show_snippet_window = false;
+                        }
+                    });
+                });
+        }
+    }
+
+    fn render_views_window(&mut self, ctx: &Context) {
+        if !self.show_views_window {
+            return;
+        }
+        let mut open = true;
+        let mut show = None;
+        let mut remove = None;
+        let mut edited = Vec::new();
+        egui::Window::new("Views")
+            .id(Id::new("views_window"))
+            .collapsible(false)
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label("A view shows only the elements with the included tags and types, leaving out excluded tags.");
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for view in &self.diagram.views {
+                        let mut view = view.clone();
+                        let active = self.diagram.active_view == Some(view.id);
+                        ui.separator();
+                        let mut changed = false;
+                        ui.horizontal(|ui| {
+                            changed |= ui.text_edit_singleline(&mut view.name).changed();
+                            if active {
+                                ui.weak("(shown)");
+                            } else if ui.button("Show").clicked() {
+                                show = Some(Some(view.id));
+                            }
+                            if ui.button("Delete").clicked() {
+                                remove = Some(view.id);
+                            }
+                        });
+                        egui::Grid::new(("view_grid", view.id)).num_columns(2).show(ui, |ui| {
+                            ui.label("Include tags");
+                            changed |= crate::ui::tags_edit(ui, ("view_include", view.id), &mut view.include_tags);
+                            ui.end_row();
+                            ui.label("Exclude tags");
+                            changed |= crate::ui::tags_edit(ui, ("view_exclude", view.id), &mut view.exclude_tags);
+                            ui.end_row();
+                            ui.label("Types");
+                            ui.horizontal_wrapped(|ui| {
+                                for type_name in View::TYPE_NAMES {
+                                    let mut included = view.types.iter().any(|t| t == type_name);
+                                    if ui.checkbox(&mut included, type_name).changed() {
+                                        view.types.retain(|t| t != type_name);
+                                        if included {
+                                            view.types.push(type_name.to_string());
+                                        }
+                                        changed = true;
+                                    }
+                                }
+                            });
+                            ui.end_row();
+                        });
+                        if changed {
+                            edited.push(view);
+                        }
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Add View").clicked() {
+                        let name = format!("View {}", self.diagram.views.len() + 1);
+                        show = Some(Some(self.diagram.add_view(View::new(name))));
+                    }
+                    if self.diagram.active_view.is_some() && ui.button("Show Whole Diagram").clicked() {
+                        show = Some(None);
+                    }
                 });
             });
-        });
+        let refresh = !edited.is_empty() || remove.is_some();
+        for view in edited {
+            self.diagram.update_view(view);
+        }
+        if let Some(id) = remove {
+            self.diagram.remove_view(id);
+        }
+        match show {
+            Some(view) => self.show_view(view),
+            None if refresh => self.show_view(self.diagram.active_view),
+            None => {}
+        }
+        self.show_views_window = open;
     }
 
-    fn render_export_window(&mut self, ctx: &Context) {
-        if self.show_export_window {
-            egui::Window::new(&self.export_title)
-                .id(Id::new("export_window"))
+    fn render_group_window(&mut self, ctx: &Context) {
+        if self.show_group_window {
+            egui::Window::new("Group Elements")
+                .id(Id::new("group_window"))
                 .collapsible(false)
                 .resizable(true)
-                .default_size([500.0, 400.0])
                 .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().show(ui, |ui| {
-                        ui.add(
-                            egui::TextEdit::multiline(&mut self.export_content)
-                                .code_editor()
-                                .desired_rows(20),
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut self.group_name);
+                    });
+                    ui.label("Elements that move together (an element can be in one group):");
+                    let mut elements: Vec<&Element> = self.diagram.elements.values().collect();
+                    elements.sort_by(|a, b| a.name().cmp(b.name()));
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        for element in elements {
+                            let mut included = self.group_selection.contains(&element.id);
+                            let label = match self.diagram.group_of(element.id) {
+                                Some(group) => format!("{} (in {})", element.name(), group.name),
+                                None => element.name().to_string(),
+                            };
+                            if ui.checkbox(&mut included, label).changed() {
+                                if included {
+                                    self.group_selection.insert(element.id);
+                                } else {
+                                    self.group_selection.remove(&element.id);
+                                }
+                            }
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(self.group_selection.len() >= 2, egui::Button::new("Group")).clicked() {
+                            let mut ids: Vec<_> = self.group_selection.iter().copied().collect();
+                            ids.sort();
+                            self.diagram.group_elements(self.group_name.trim(), &ids);
+                            self.show_group_window = false;
+                        }
+                        if ui.button("Close").clicked() {
+                            self.show_group_window = false;
+                        }
+                    });
+                });
+        }
+    }
+
+    fn render_label_window(&mut self, ctx: &Context) {
+        let Some(mut label) = self.editing_label.and_then(|id| self.diagram.get_label(id)).cloned() else {
+            self.editing_label = None;
+            return;
+        };
+        let mut open = true;
+        let mut delete = false;
+        egui::Window::new("Text Label")
+            .id(Id::new("label_window"))
+            .collapsible(false)
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.add(egui::TextEdit::multiline(&mut label.text).desired_rows(2));
+                ui.add(egui::Slider::new(&mut label.font_size, TextLabel::FONT_SIZES).text("Font size"));
+                ui.label("Labels appear on the canvas and in SVG images; the C4 text exports leave them out.");
+                ui.horizontal(|ui| {
+                    delete = ui.button("Delete").clicked();
+                    if ui.button("Close").clicked() {
+                        self.editing_label = None;
+                    }
+                });
+            });
+        if delete {
+            self.diagram.remove_label(label.id);
+            self.editing_label = None;
+        } else {
+            self.diagram.update_label(label);
+        }
+        if !open {
+            self.editing_label = None;
+        }
+    }
+
+    fn render_stats_window(&mut self, ctx: &Context) {
+        if !self.show_stats_window {
+            return;
+        }
+        let stats = self.diagram.stats();
+        let mut open = true;
+        egui::Window::new("Statistics")
+            .id(Id::new("stats_window"))
+            .collapsible(false)
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("stats_grid").num_columns(2).striped(true).show(ui, |ui| {
+                    for (type_name, count) in &stats.by_type {
+                        ui.label(*type_name);
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+                    ui.strong("Elements");
+                    ui.strong(stats.elements().to_string());
+                    ui.end_row();
+                    ui.label("Internal");
+                    ui.label(stats.internal.to_string());
+                    ui.end_row();
+                    ui.label("External");
+                    ui.label(stats.external.to_string());
+                    ui.end_row();
+                    ui.strong("Relationships");
+                    ui.strong(stats.relationships.to_string());
+                    ui.end_row();
+                });
+                ui.separator();
+                if stats.unconnected.is_empty() {
+                    ui.label("Every element is connected.");
+                } else {
+                    ui.label(format!("Unconnected elements ({}), click to select:", stats.unconnected.len()));
+                    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                        for id in &stats.unconnected {
+                            if let Some(element) = self.diagram.get_element(*id)
+                                && ui.selectable_label(self.selected_element == Some(*id), element.name()).clicked()
+                            {
+                                self.selected_element = Some(*id);
+                            }
+                        }
+                    });
+                }
+            });
+        self.show_stats_window = open;
+    }
+
+    fn render_api_suggestions_window(&mut self, ctx: &Context) {
+        if self.show_api_suggestions_window {
+            let mut apply = false;
+            egui::Window::new("OpenAPI Suggestions")
+                .id(Id::new("api_suggestions_window"))
+                .collapsible(false)
+                .resizable(true)
+                .show(ctx, |ui| {
+                    apply = self.api_suggestions.render(ui, &self.diagram);
+                    ui.horizontal(|ui| {
+                        if ui.button("Load...").clicked() {
+                            self.load_openapi_spec();
+                        }
+                        if ui.button("Close").clicked() {
+                            self.show_api_suggestions_window = false;
+                        }
+                    });
+                });
+            if apply {
+                let canvas = &self.canvas;
+                self.selected_element = self.api_suggestions.apply(&mut self.diagram, |elements, size| {
+                    canvas
+                        .drop_position(elements, size)
+                        .unwrap_or_else(|| crate::ui::default_element_position(elements.len()))
+                });
+            }
+        }
+    }
+
+    /// Summary of the differences from the compared file
+    fn render_comparison_window(&mut self, ctx: &Context) {
+        let Some((path, base)) = &self.compare_base else {
+            return;
+        };
+        let diff = base.diff(&self.diagram);
+        let mut stop = false;
+        egui::Window::new("Comparison")
+            .id(Id::new("comparison_window"))
+            .collapsible(true)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::Vec2::new(-16.0, -16.0))
+            .show(ctx, |ui| {
+                ui.label(format!("Since {}", path.file_name().unwrap_or_default().to_string_lossy()));
+                ui.label(diff.summary());
+                for change in Change::ALL {
+                    let elements = diff.elements_with(change).len();
+                    let relationships = diff.relationships_with(change).len();
+                    if elements + relationships > 0 {
+                        ui.colored_label(
+                            Comparison::color(change),
+                            format!("{}: {} elements, {} relationships", change.as_str(), elements, relationships),
                         );
+                    }
+                }
+                stop = ui.button("Stop Comparing").clicked();
+            });
+        if stop {
+            self.compare_base = None;
+            self.canvas.comparison = None;
+        }
+    }
+
+    /// Issues found by the validation rules; clicking one selects its element
+    fn render_validation_window(&mut self, ctx: &Context) {
+        if !self.show_validation_window {
+            return;
+        }
+        let issues = validation::validate(&self.diagram);
+        let orphans = self.diagram.orphan_elements();
+        let mut focus = None;
+        let mut select_orphans = false;
+        egui::Window::new("Validation")
+            .id(Id::new("validation_window"))
+            .collapsible(false)
+            .resizable(true)
+            .open(&mut self.show_validation_window)
+            .show(ctx, |ui| {
+                if !orphans.is_empty() {
+                    ui.horizontal(|ui| {
+                        let noun = if orphans.len() == 1 { "element has" } else { "elements have" };
+                        ui.label(format!("{} {} no relationships", orphans.len(), noun));
+                        select_orphans = ui
+                            .button("Select All Orphans")
+                            .on_hover_text("Select them on the canvas, e.g. to delete them before publishing")
+                            .clicked();
+                    });
+                    ui.separator();
+                }
+                if issues.is_empty() {
+                    ui.label("No issues found");
+                    return;
+                }
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("validation_grid").num_columns(2).striped(true).show(ui, |ui| {
+                        for issue in &issues {
+                            let color = match issue.severity {
+                                Severity::Error => Color32::from_rgb(200, 0, 0),
+                                Severity::Warning => Color32::from_rgb(200, 120, 0),
+                                Severity::Info => Color32::GRAY,
+                            };
+                            ui.colored_label(color, issue.severity.as_str());
+                            let target = issue.focus(&self.diagram);
+                            let response = ui
+                                .add_enabled(target.is_some(), egui::Button::new(&issue.message).frame(false))
+                                .on_hover_text(format!("[{}] show the element", issue.rule));
+                            if response.clicked() {
+                                focus = target;
+                            }
+                            ui.end_row();
+                        }
                     });
+                });
+            });
+        if let Some(id) = focus
+            && let Some(element) = self.diagram.get_element(id)
+        {
+            self.main_view = MainView::Canvas;
+            self.canvas.reveal(element);
+            self.selected_element = Some(id);
+        }
+        if select_orphans {
+            self.main_view = MainView::Canvas;
+            self.selected_element = None;
+            self.canvas.marked = orphans;
+        }
+    }
+
+    fn render_share_link_window(&mut self, ctx: &Context) {
+        if self.show_share_link_window {
+            egui::Window::new("Open Share Link")
+                .id(Id::new("share_link_window"))
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label("Paste a C2Draw share link:");
+                    ui.text_edit_singleline(&mut self.share_link_input);
+
+                    if let Some(error) = &self.share_link_error {
+                        ui.colored_label(Color32::from_rgb(200, 0, 0), error);
+                    }
 
                     ui.horizontal(|ui| {
-                        if ui.button("Copy to Clipboard")
-                            .on_hover_text("Copy the export code to your clipboard")
-                            .clicked()
-                        {
-                            ctx.copy_text(self.export_content.clone());
+                        if ui.button("Open").clicked() {
+                            self.open_share_link();
                         }
                         if ui.button("Close").clicked() {
-                            self.show_export_window = false;
+                            self.show_share_link_window = false;
                         }
                     });
                 });
@@ -395,13 +3270,50 @@ impl C2DrawApp {
 
 impl eframe::App for C2DrawApp {
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.handle_shortcuts(ctx);
+        self.apply_navigation();
         self.render_menu_bar(ctx);
+        self.render_toolbar(ctx);
+        self.render_tab_bar(ctx);
+        self.render_project_panel(ctx);
         self.render_sidebar(ctx);
         self.render_properties_panel(ctx);
+        self.render_comments_panel(ctx);
+        self.render_metrics_panel(ctx);
+        self.render_dsl_editor(ctx);
+        self.render_relationship_list(ctx);
 
         CentralPanel::default()
             .frame(egui::Frame::central_panel(&ctx.style()).fill(Color32::from_gray(240)))
             .show(ctx, |ui| {
+                match self.main_view {
+                    MainView::Canvas => {}
+                    MainView::DependencyMatrix => {
+                        self.dependency_matrix.render(ui, &self.diagram, &mut self.selected_element);
+                        return;
+                    }
+                    MainView::Table => {
+                        self.element_table.render(ui, &mut self.diagram, &mut self.selected_element);
+                        return;
+                    }
+                }
+                self.canvas.typography = self.canvas.stylesheet.fonts.unwrap_or(self.preferences.typography);
+                self.canvas.line_style = self.preferences.line_style;
+                self.canvas.impact = match self.selected_element {
+                    Some(id) if self.show_impact => Impact::of(&self.diagram, id, self.impact_upstream),
+                    _ => Default::default(),
+                };
+                (self.canvas.cycle_elements, self.canvas.cycle_relationships) = if self.show_cycles {
+                    let elements = RelationshipGraph::new(&self.diagram).cycles().into_iter().flatten().collect();
+                    (elements, self.diagram.cyclic_relationships().into_iter().collect())
+                } else {
+                    Default::default()
+                };
+                self.canvas.view = self.diagram.active_view().cloned();
+                self.canvas.steps = self.diagram.step_numbers();
+                self.canvas.groups.clone_from(&self.diagram.groups);
+                self.canvas.comparison = self.compare_base.as_ref().map(|(_, base)| Comparison::new(base, &self.diagram));
+
                 // Render the canvas - it returns the target element ID if in relationship mode
                 let clicked_target = self.canvas.render(
                     ui,
@@ -409,6 +3321,18 @@ impl eframe::App for C2DrawApp {
                     &self.diagram.relationships,
                     &mut self.selected_element,
                 );
+                if let Some(id) = self.canvas.draw_text_labels(ui, &self.diagram.labels, self.editing_label) {
+                    self.editing_label = Some(id);
+                }
+                if self.canvas.show_comments {
+                    if let Some(position) = self.canvas.take_comment_request() {
+                        self.comments_panel.pending_position = Some(position);
+                    }
+                    let show_resolved = self.comments_panel.show_resolved;
+                    if let Some(id) = self.canvas.draw_comment_markers(ui, &self.diagram, show_resolved) {
+                        self.comments_panel.focused_thread = Some(id);
+                    }
+                }
 
                 // Handle relationship creation if a target was clicked
                 if let Some(target_id) = clicked_target {
@@ -425,5 +3349,47 @@ impl eframe::App for C2DrawApp {
             });
 
         self.render_export_window(ctx);
+        self.render_share_link_window(ctx);
+        self.render_backups_window(ctx);
+        self.render_close_tab_window(ctx);
+        self.render_snippet_window(ctx);
+        self.render_group_window(ctx);
+        self.render_views_window(ctx);
+        self.render_label_window(ctx);
+        self.render_stats_window(ctx);
+        self.render_api_suggestions_window(ctx);
+        self.render_validation_window(ctx);
+        self.render_comparison_window(ctx);
+        self.render_diagram_properties_window(ctx);
+        self.render_poster_window(ctx);
+        self.render_preferences_window(ctx);
+        self.render_shortcuts_window(ctx);
+        self.render_error_window(ctx);
+        self.handle_diagram_events(ctx);
+    }
+
+    /// Take Tab and the arrow keys for canvas navigation before egui moves widget focus with them
+    fn raw_input_hook(&mut self, ctx: &Context, raw_input: &mut egui::RawInput) {
+        if self.main_view != MainView::Canvas || ctx.memory(|m| m.focused().is_some()) {
+            return;
+        }
+        raw_input.events.retain(|event| {
+            let egui::Event::Key { key, pressed, modifiers, .. } = event else {
+                return true;
+            };
+            let navigation = match keymap::navigation_for(*key, *modifiers) {
+                Some(Command::PreviousElement) => Navigation::Previous,
+                Some(Command::NextElement) => Navigation::Next,
+                Some(Command::SelectLeft) => Navigation::Toward(egui::Vec2::LEFT),
+                Some(Command::SelectRight) => Navigation::Toward(egui::Vec2::RIGHT),
+                Some(Command::SelectUp) => Navigation::Toward(egui::Vec2::UP),
+                Some(Command::SelectDown) => Navigation::Toward(egui::Vec2::DOWN),
+                _ => return true,
+            };
+            if *pressed {
+                self.navigation.push(navigation);
+            }
+            false
+        });
     }
 }
@@ -0,0 +1,249 @@
+//! Timestamped backup copies of diagram files
+//!
+//! Before a save overwrites a file, the previous version is copied next to it
+//! as `name.c4d.bak-<timestamp>`. Only the newest backups are kept.
+
+use crate::C2DrawError;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Separator between the file name and the timestamp of a backup
+pub const BACKUP_SUFFIX: &str = ".bak-";
+
+/// A backup copy of a diagram file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// UTC time of the backup, formatted as `YYYYMMDD-HHMMSS-mmm`
+    pub timestamp: String,
+}
+
+impl Backup {
+    /// Timestamp formatted for display, e.g. `2026-10-15 14:23:01 UTC`
+    pub fn display_time(&self) -> String {
+        let t = &self.timestamp;
+        if t.len() < 15 || !t.is_ascii() {
+            return t.clone();
+        }
+        format!(
+            "{}-{}-{} {}:{}:{} UTC",
+            &t[0..4],
+            &t[4..6],
+            &t[6..8],
+            &t[9..11],
+            &t[11..13],
+            &t[13..15]
+        )
+    }
+}
+
+/// Copy `path` to a new timestamped backup and prune old ones
+///
+/// Keeps at most `keep` backups; `keep == 0` disables backups. Does nothing
+/// if `path` does not exist yet. Returns the path of the new backup.
+pub fn create_backup(path: &Path, keep: usize) -> crate::Result<Option<PathBuf>> {
+    if keep == 0 || !path.exists() {
+        return Ok(None);
+    }
+    let backup = backup_path(path, &format_timestamp(SystemTime::now()));
+    std::fs::copy(path, &backup).map_err(|e| C2DrawError::file(&backup, e))?;
+    prune_backups(path, keep)?;
+    Ok(Some(backup))
+}
+
+/// Backups of `path`, newest first
+pub fn list_backups(path: &Path) -> crate::Result<Vec<Backup>> {
+    let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Ok(Vec::new());
+    };
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let prefix = format!("{}{}", file_name.to_string_lossy(), BACKUP_SUFFIX);
+
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|e| C2DrawError::file(dir, e))? {
+        let entry = entry.map_err(|e| C2DrawError::file(dir, e))?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if let Some(timestamp) = name.strip_prefix(&prefix) {
+            backups.push(Backup {
+                path: entry.path(),
+                timestamp: timestamp.to_string(),
+            });
+        }
+    }
+    // Timestamps sort lexicographically in time order
+    backups.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(backups)
+}
+
+/// Delete all but the newest `keep` backups of `path`
+pub fn prune_backups(path: &Path, keep: usize) -> crate::Result<()> {
+    for old in list_backups(path)?.into_iter().skip(keep) {
+        std::fs::remove_file(&old.path).map_err(|e| C2DrawError::file(&old.path, e))?;
+    }
+    Ok(())
+}
+
+/// Path of the backup of `path` taken at `timestamp`
+pub fn backup_path(path: &Path, timestamp: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(BACKUP_SUFFIX);
+    name.push(timestamp);
+    path.with_file_name(name)
+}
+
+/// Format a time as a sortable UTC `YYYYMMDD-HHMMSS-mmm` string
+pub fn format_timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let seconds_of_day = secs % 86_400;
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}-{:03}",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Format a time as ISO 8601 in UTC, to the second (`2024-02-29T13:45:30Z`)
+pub fn format_iso8601(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let seconds_of_day = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60
+    )
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's days_from_civil inverse
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("c2draw-backup-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    mod timestamp_tests {
+        use super::*;
+
+        /// Verifies the epoch formats as the first millisecond of 1970
+        #[test]
+        fn format_timestamp_epoch() {
+            assert_eq!(format_timestamp(UNIX_EPOCH), "19700101-000000-000");
+        }
+
+        /// Verifies a known date, including a leap day and milliseconds
+        #[test]
+        fn format_timestamp_known_date() {
+            // 2024-02-29 13:45:30.250 UTC
+            let time = UNIX_EPOCH + Duration::from_millis(1_709_214_330_250);
+            assert_eq!(format_timestamp(time), "20240229-134530-250");
+        }
+
+        /// Verifies ISO 8601 formatting drops the milliseconds
+        #[test]
+        fn format_iso8601_known_date() {
+            let time = UNIX_EPOCH + Duration::from_millis(1_709_214_330_250);
+            assert_eq!(format_iso8601(time), "2024-02-29T13:45:30Z");
+        }
+
+        /// Verifies the display form is readable
+        #[test]
+        fn display_time_formats_timestamp() {
+            let backup = Backup {
+                path: PathBuf::new(),
+                timestamp: "20240229-134530-250".to_string(),
+            };
+            assert_eq!(backup.display_time(), "2024-02-29 13:45:30 UTC");
+        }
+    }
+
+    mod backup_tests {
+        use super::*;
+
+        /// Verifies the backup name extends the original file name
+        #[test]
+        fn backup_path_appends_suffix() {
+            let path = backup_path(Path::new("/tmp/arch.c4d"), "20240229-134530-250");
+            assert_eq!(path, Path::new("/tmp/arch.c4d.bak-20240229-134530-250"));
+        }
+
+        /// Verifies nothing is backed up for new files or when disabled
+        #[test]
+        fn create_backup_skips_missing_or_disabled() {
+            let dir = temp_dir();
+            let path = dir.join("arch.c4d");
+            assert_eq!(create_backup(&path, 5).unwrap(), None);
+
+            std::fs::write(&path, "{}").unwrap();
+            assert_eq!(create_backup(&path, 0).unwrap(), None);
+            assert!(list_backups(&path).unwrap().is_empty());
+
+            let _ = std::fs::remove_dir_all(dir);
+        }
+
+        /// Verifies the previous contents are copied to the backup
+        #[test]
+        fn create_backup_copies_file() {
+            let dir = temp_dir();
+            let path = dir.join("arch.c4d");
+            std::fs::write(&path, "old").unwrap();
+
+            let backup = create_backup(&path, 5).unwrap().expect("backup created");
+            assert_eq!(std::fs::read_to_string(&backup).unwrap(), "old");
+            assert_eq!(list_backups(&path).unwrap()[0].path, backup);
+
+            let _ = std::fs::remove_dir_all(dir);
+        }
+
+        /// Verifies backups are listed newest first and pruned to the limit
+        #[test]
+        fn prune_backups_keeps_newest() {
+            let dir = temp_dir();
+            let path = dir.join("arch.c4d");
+            for timestamp in ["20240101-000000-000", "20240301-000000-000", "20240201-000000-000"] {
+                std::fs::write(backup_path(&path, timestamp), timestamp).unwrap();
+            }
+            // Backups of other files are ignored
+            std::fs::write(backup_path(&dir.join("other.c4d"), "20240401-000000-000"), "").unwrap();
+
+            prune_backups(&path, 2).unwrap();
+            let remaining: Vec<String> = list_backups(&path)
+                .unwrap()
+                .into_iter()
+                .map(|b| b.timestamp)
+                .collect();
+            assert_eq!(remaining, vec!["20240301-000000-000", "20240201-000000-000"]);
+
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
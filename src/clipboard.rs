@@ -0,0 +1,125 @@
+//! Copy and paste through the system clipboard
+//!
+//! Copied elements go on the clipboard as JSON: a [`Snippet`] tagged with a
+//! `format` marker, so a paste of ordinary text is told apart from copied
+//! elements. Because the payload is plain text, elements can be copied from
+//! one running C2Draw instance and pasted into another. Pasting inserts the
+//! snippet with fresh IDs, like importing a snippet file.
+
+use crate::model::{Diagram, ElementId, Snippet};
+use serde::{Deserialize, Serialize};
+
+/// Value of the `format` field that marks a C2Draw clipboard payload
+pub const CLIPBOARD_FORMAT: &str = "c2draw/elements";
+
+#[derive(Serialize, Deserialize)]
+struct Payload {
+    format: String,
+    #[serde(flatten)]
+    snippet: Snippet,
+}
+
+/// The elements to copy for a selection: the selected elements and everything nested inside them
+pub fn copy_selection(diagram: &Diagram, selected: &[ElementId]) -> Vec<ElementId> {
+    let mut ids: Vec<ElementId> = Vec::new();
+    let mut pending: Vec<ElementId> = selected.iter().rev().copied().collect();
+    while let Some(id) = pending.pop() {
+        if ids.contains(&id) || diagram.get_element(id).is_none() {
+            continue;
+        }
+        ids.push(id);
+        let mut children: Vec<ElementId> = diagram.children_of(id).iter().map(|e| e.id).collect();
+        children.sort();
+        pending.extend(children.into_iter().rev());
+    }
+    ids
+}
+
+/// Clipboard text for the selected elements, their nested elements and the relationships among them
+///
+/// Returns `None` if none of the IDs are in the diagram.
+pub fn encode_clipboard(diagram: &Diagram, selected: &[ElementId]) -> Option<String> {
+    let snippet = Snippet::from_selection(diagram, &copy_selection(diagram, selected));
+    if snippet.is_empty() {
+        return None;
+    }
+    let payload = Payload {
+        format: CLIPBOARD_FORMAT.to_string(),
+        snippet,
+    };
+    serde_json::to_string(&payload).ok()
+}
+
+/// The copied elements in clipboard text, or `None` if it holds anything else
+pub fn decode_clipboard(text: &str) -> Option<Snippet> {
+    let payload: Payload = serde_json::from_str(text.trim()).ok()?;
+    (payload.format == CLIPBOARD_FORMAT && !payload.snippet.is_empty()).then_some(payload.snippet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContainerType, DiagramType, Element, ElementType, Position, Relationship};
+
+    /// Diagram with a customer using a shop that holds an API, returning the customer, shop and API
+    fn shop() -> (Diagram, [ElementId; 3]) {
+        let mut diagram = Diagram::new("Shop", "", DiagramType::Container);
+        let user = Element::new(ElementType::person("Customer", ""), Position::new(0.0, 0.0));
+        let shop = Element::new(ElementType::system("Shop", ""), Position::new(300.0, 0.0));
+        let mut api = Element::new(
+            ElementType::container("API", "", ContainerType::Microservice, "Rust"),
+            Position::new(320.0, 60.0),
+        );
+        if let ElementType::Container(data) = &mut api.element_type {
+            data.system = Some(shop.id);
+        }
+        let ids = [user.id, shop.id, api.id];
+        diagram.add_element(user);
+        diagram.add_element(shop);
+        diagram.add_element(api);
+        diagram.add_relationship(Relationship::new(ids[0], ids[2], "Calls"));
+        diagram.add_relationship(Relationship::new(ids[1], ids[0], "Emails"));
+        (diagram, ids)
+    }
+
+    mod clipboard_tests {
+        use super::*;
+
+        /// Verifies copying an element takes its nested elements along
+        #[test]
+        fn copy_includes_nested_elements() {
+            let (diagram, [user, shop, api]) = shop();
+            assert_eq!(copy_selection(&diagram, &[shop]), vec![shop, api]);
+            assert_eq!(copy_selection(&diagram, &[user, api]), vec![user, api]);
+            assert!(copy_selection(&diagram, &[ElementId::new_v4()]).is_empty());
+        }
+
+        /// Verifies a copied selection pastes back with fresh IDs and its internal relationships
+        #[test]
+        fn round_trip_remaps_ids() {
+            let (diagram, [user, shop, api]) = shop();
+            let text = encode_clipboard(&diagram, &[user, shop]).unwrap();
+            let snippet = decode_clipboard(&text).unwrap();
+            assert_eq!(snippet.elements.len(), 3);
+            assert_eq!(snippet.relationships.len(), 2);
+
+            let mut target = Diagram::new("Other", "", DiagramType::Container);
+            let inserted = target.insert_snippet(&snippet, Position::new(0.0, 0.0));
+            assert_eq!(target.elements.len(), 3);
+            assert!(inserted.iter().all(|id| ![user, shop, api].contains(id)));
+            assert_eq!(target.elements[&inserted[2]].parent(), Some(inserted[1]));
+            assert!(target.relationships.iter().all(|r| inserted.contains(&r.source_id)));
+        }
+
+        /// Verifies other clipboard text is not mistaken for copied elements
+        #[test]
+        fn ignores_other_text() {
+            let (diagram, _) = shop();
+            assert!(decode_clipboard("Customer").is_none());
+            assert!(decode_clipboard(&serde_json::to_string(&diagram).unwrap()).is_none());
+            let snippet = Snippet::from_selection(&diagram, &[]);
+            assert!(decode_clipboard(&snippet.to_json().unwrap()).is_none());
+            assert!(encode_clipboard(&diagram, &[]).is_none());
+        }
+    }
+}
@@ -0,0 +1,653 @@
+//! Textual diagram DSL
+//!
+//! The DSL is the element and relationship subset of C4-PlantUML, one macro
+//! per line:
+//!
+//! ```text
+//! title Internet Banking
+//! Person(customer, "Customer", "A bank customer")
+//! System_Ext(mail, "E-mail System", "Sends e-mails")
+//! ContainerDb(db, "Database", "Stores accounts", "PostgreSQL")
+//! Rel(customer, db, "Reads from", "JDBC")
+//! BiRel(customer, mail, "Exchanges e-mails")
+//! ```
+//!
+//! Relationships of a Dynamic diagram are numbered steps,
+//! `RelIndex(1, customer, db, "Reads from")`.
+//!
+//! [`to_dsl`] renders a diagram as text and [`apply_dsl`] updates a diagram
+//! from text. Existing elements are referenced by their `elem_<uuid>` alias so
+//! positions and sizes survive a round trip through the text editor; any other
+//! alias creates a new element.
+
+use crate::model::{
+    ContainerType, Diagram, DiagramEvent, DiagramType, Direction, Element, ElementId, ElementType, Relationship, SystemKind,
+};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// A parse error with the 1-based line it occurred on
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("line {line}: {message}")]
+pub struct DslError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// An element declaration parsed from the DSL
+#[derive(Debug, Clone)]
+pub struct DslElement {
+    pub alias: String,
+    pub element_type: ElementType,
+}
+
+/// A relationship declaration parsed from the DSL
+#[derive(Debug, Clone, PartialEq)]
+pub struct DslRelationship {
+    pub source: String,
+    pub target: String,
+    pub description: String,
+    pub technology: Option<String>,
+    /// `Rel_Back` or `BiRel` rather than `Rel`
+    pub direction: Direction,
+    /// Step number given to `RelIndex`
+    pub order: Option<u32>,
+}
+
+/// The result of parsing DSL text
+#[derive(Debug, Clone, Default)]
+pub struct DslDocument {
+    pub title: Option<String>,
+    pub elements: Vec<DslElement>,
+    pub relationships: Vec<DslRelationship>,
+}
+
+/// Alias used for an element in DSL text
+pub fn element_alias(id: ElementId) -> String {
+    format!("elem_{}", id.simple())
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+/// Render a diagram as DSL text
+pub fn to_dsl(diagram: &Diagram) -> String {
+    let mut output = format!("title {}\n\n", diagram.name.replace('\n', " "));
+
+    let mut elements: Vec<&Element> = diagram.elements.values().collect();
+    elements.sort_by(|a, b| a.name().cmp(b.name()).then(a.id.cmp(&b.id)));
+
+    for element in elements {
+        let alias = element_alias(element.id);
+        let name = quote(element.name());
+        let description = quote(element.description());
+        let line = match &element.element_type {
+            ElementType::Person(data) => {
+                let macro_name = if data.is_external { "Person_Ext" } else { "Person" };
+                format!("{}({}, {}, {})", macro_name, alias, name, description)
+            }
+            ElementType::SoftwareSystem(data) => {
+                format!("{}({}, {}, {})", data.macro_name(), alias, name, description)
+            }
+            ElementType::Container(data) => {
+                let macro_name = match data.container_type {
+                    ContainerType::Database => "ContainerDb",
+                    ContainerType::Queue => "ContainerQueue",
+                    _ => "Container",
+                };
+                format!(
+                    "{}({}, {}, {}, {})",
+                    macro_name,
+                    alias,
+                    name,
+                    description,
+                    quote(&data.technology)
+                )
+            }
+            ElementType::Component(data) => {
+                format!("Component({}, {}, {}, {})", alias, name, description, quote(&data.technology))
+            }
+            // Notes have no C4 macro; applying the text keeps them as they are
+            ElementType::Note(_) => continue,
+        };
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    if !diagram.relationships.is_empty() {
+        output.push('\n');
+    }
+    // A Dynamic diagram lists its relationships as numbered steps
+    let mut relationships: Vec<(Option<u32>, &Relationship)> = if diagram.diagram_type == DiagramType::Dynamic {
+        diagram.steps().into_iter().map(|(step, rel)| (Some(step), rel)).collect()
+    } else {
+        diagram.relationships.iter().map(|rel| (None, rel)).collect()
+    };
+    relationships.retain(|(_, rel)| !touches_note(diagram, rel));
+    for (step, rel) in relationships {
+        let source = match step {
+            Some(step) => format!("{}, {}", step, element_alias(rel.source_id)),
+            None => element_alias(rel.source_id),
+        };
+        let target = element_alias(rel.target_id);
+        let macro_name = match step {
+            Some(_) => "RelIndex",
+            None => rel.direction.macro_name(),
+        };
+        let line = match &rel.technology {
+            Some(tech) => format!(
+                "{}({}, {}, {}, {})",
+                macro_name,
+                source,
+                target,
+                quote(&rel.description),
+                quote(tech)
+            ),
+            None => format!("{}({}, {}, {})", macro_name, source, target, quote(&rel.description)),
+        };
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Split the argument list of a macro call, honouring quoted strings
+fn split_arguments(args: &str, line: usize) -> Result<Vec<String>, DslError> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut chars = args.chars();
+    let mut in_quotes = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => match chars.next() {
+                Some('n') => current.push('\n'),
+                Some(other) => current.push(other),
+                None => break,
+            },
+            ',' if !in_quotes => {
+                result.push(current.trim().to_string());
+                current.clear();
+            }
+            c if in_quotes || !c.is_whitespace() => current.push(c),
+            _ => {}
+        }
+    }
+
+    if in_quotes {
+        return Err(DslError {
+            line,
+            message: "unterminated string".to_string(),
+        });
+    }
+    if !current.trim().is_empty() || !result.is_empty() {
+        result.push(current.trim().to_string());
+    }
+    Ok(result)
+}
+
+/// Parse DSL text
+pub fn parse_dsl(text: &str) -> Result<DslDocument, DslError> {
+    let mut document = DslDocument::default();
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let trimmed = raw.trim();
+
+        if trimmed.is_empty()
+            || trimmed.starts_with('\'')
+            || trimmed.starts_with('!')
+            || trimmed.starts_with('@')
+        {
+            continue;
+        }
+        if let Some(title) = trimmed.strip_prefix("title ") {
+            document.title = Some(title.trim().to_string());
+            continue;
+        }
+
+        let error = |message: String| DslError { line, message };
+
+        let (macro_name, rest) = trimmed
+            .split_once('(')
+            .ok_or_else(|| error(format!("expected a macro call, found `{}`", trimmed)))?;
+        let args = rest
+            .trim_end()
+            .strip_suffix(')')
+            .ok_or_else(|| error("missing closing parenthesis".to_string()))?;
+        let args = split_arguments(args, line)?;
+        let arg = |i: usize| args.get(i).cloned().unwrap_or_default();
+
+        let macro_name = macro_name.trim();
+        if macro_name == "RelIndex" {
+            let order = args
+                .first()
+                .and_then(|index| index.trim().parse().ok())
+                .ok_or_else(|| error("RelIndex needs a step number".to_string()))?;
+            if args.len() < 3 {
+                return Err(error("RelIndex needs a source and a target".to_string()));
+            }
+            document.relationships.push(DslRelationship {
+                source: arg(1),
+                target: arg(2),
+                description: arg(3),
+                technology: args.get(4).cloned(),
+                direction: Direction::Forward,
+                order: Some(order),
+            });
+            continue;
+        }
+        let direction = Direction::ALL.into_iter().find(|d| d.macro_name() == macro_name);
+        if let Some(direction) = direction {
+            if args.len() < 2 {
+                return Err(error(format!("{} needs a source and a target", macro_name)));
+            }
+            document.relationships.push(DslRelationship {
+                source: arg(0),
+                target: arg(1),
+                description: arg(2),
+                technology: args.get(3).cloned(),
+                direction,
+                order: None,
+            });
+            continue;
+        }
+
+        let element_type = match macro_name {
+            "Person" => ElementType::person(arg(1), arg(2)),
+            "Person_Ext" => ElementType::external_person(arg(1), arg(2)),
+            "System" | "System_Ext" | "SystemDb" | "SystemDb_Ext" | "SystemQueue" | "SystemQueue_Ext" => {
+                let mut system = if macro_name.ends_with("_Ext") {
+                    ElementType::external_system(arg(1), arg(2))
+                } else {
+                    ElementType::system(arg(1), arg(2))
+                };
+                if let ElementType::SoftwareSystem(data) = &mut system {
+                    data.kind = match macro_name.trim_end_matches("_Ext") {
+                        "SystemDb" => SystemKind::Database,
+                        "SystemQueue" => SystemKind::Queue,
+                        _ => SystemKind::Default,
+                    };
+                }
+                system
+            }
+            "Container" => ElementType::container(arg(1), arg(2), ContainerType::Microservice, arg(3)),
+            "ContainerDb" => ElementType::container(arg(1), arg(2), ContainerType::Database, arg(3)),
+            "ContainerQueue" => ElementType::container(arg(1), arg(2), ContainerType::Queue, arg(3)),
+            "Component" => ElementType::component(arg(1), arg(2), arg(3)),
+            other => return Err(error(format!("unknown macro `{}`", other))),
+        };
+        if arg(0).is_empty() {
+            return Err(error(format!("{} needs an alias", macro_name)));
+        }
+        if document.elements.iter().any(|e| e.alias == arg(0)) {
+            return Err(error(format!("duplicate alias `{}`", arg(0))));
+        }
+        document.elements.push(DslElement {
+            alias: arg(0),
+            element_type,
+        });
+    }
+
+    for rel in &document.relationships {
+        for alias in [&rel.source, &rel.target] {
+            if !document.elements.iter().any(|e| &e.alias == alias) {
+                let line = text
+                    .lines()
+                    .position(|l| {
+                        let l = l.trim_start();
+                        (l.starts_with("Rel") || l.starts_with("BiRel")) && l.contains(alias.as_str())
+                    })
+                    .map_or(0, |i| i + 1);
+                return Err(DslError {
+                    line,
+                    message: format!("unknown element `{}`", alias),
+                });
+            }
+        }
+    }
+
+    Ok(document)
+}
+
+/// Whether either end of a relationship is a note, which the DSL leaves out
+fn touches_note(diagram: &Diagram, rel: &Relationship) -> bool {
+    [rel.source_id, rel.target_id]
+        .iter()
+        .any(|id| diagram.elements.get(id).is_some_and(Element::is_note))
+}
+
+/// Replace the content of a diagram with the content described by DSL text
+///
+/// Elements whose alias matches an existing element keep their id, position
+/// and size; elements missing from the text are removed, except notes, which
+/// the text does not describe and which stay with their relationships. On
+/// error the diagram is left untouched.
+pub fn apply_dsl(diagram: &mut Diagram, text: &str) -> Result<(), DslError> {
+    let document = parse_dsl(text)?;
+
+    let existing: HashMap<String, ElementId> = diagram
+        .elements
+        .keys()
+        .map(|id| (element_alias(*id), *id))
+        .collect();
+
+    let mut elements = HashMap::new();
+    let mut ids: HashMap<String, ElementId> = HashMap::new();
+
+    for parsed in document.elements {
+        let mut element_type = parsed.element_type;
+        let element = match existing.get(&parsed.alias).and_then(|id| diagram.elements.get(id)) {
+            Some(old) => {
+                // `Container(...)` does not say which non-Db/Queue kind it is
+                if let (ElementType::Container(new), ElementType::Container(old)) =
+                    (&mut element_type, &old.element_type)
+                    && matches!(new.container_type, ContainerType::Microservice)
+                    && !matches!(old.container_type, ContainerType::Database | ContainerType::Queue)
+                {
+                    new.container_type = old.container_type.clone();
+                }
+                // nor which system a container belongs to
+                if let (ElementType::Container(new), ElementType::Container(old)) =
+                    (&mut element_type, &old.element_type)
+                {
+                    new.system = old.system;
+                }
+                // nor which container a component belongs to
+                if let (ElementType::Component(new), ElementType::Component(old)) =
+                    (&mut element_type, &old.element_type)
+                {
+                    new.container = old.container;
+                }
+                Element {
+                    element_type,
+                    ..old.clone()
+                }
+            }
+            None => {
+                let position = crate::model::default_element_position(elements.len());
+                Element::new(element_type, position)
+            }
+        };
+        ids.insert(parsed.alias, element.id);
+        elements.insert(element.id, element);
+    }
+
+    for note in diagram.elements.values().filter(|e| e.is_note()) {
+        elements.entry(note.id).or_insert_with(|| note.clone());
+    }
+
+    let mut relationships = Vec::new();
+    for parsed in document.relationships {
+        let source_id = ids[&parsed.source];
+        let target_id = ids[&parsed.target];
+        let previous = diagram.relationships.iter().find(|r| {
+            r.source_id == source_id
+                && r.target_id == target_id
+                && r.description == parsed.description
+                && !relationships.iter().any(|n: &Relationship| n.id == r.id)
+        });
+        let mut rel = match previous {
+            Some(previous) => previous.clone(),
+            None => Relationship::new(source_id, target_id, parsed.description.clone()),
+        };
+        rel.description = parsed.description;
+        rel.technology = parsed.technology;
+        rel.direction = parsed.direction;
+        rel.order = parsed.order.or(rel.order);
+        relationships.push(rel);
+    }
+    relationships.extend(
+        diagram
+            .relationships
+            .iter()
+            .filter(|rel| touches_note(diagram, rel))
+            .filter(|rel| elements.contains_key(&rel.source_id) && elements.contains_key(&rel.target_id))
+            .cloned(),
+    );
+
+    if let Some(title) = document.title {
+        diagram.name = title;
+    }
+    diagram.elements = elements;
+    diagram.relationships = relationships;
+    diagram.remove_orphaned_comments();
+    diagram.emit(DiagramEvent::Replaced);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Position;
+
+    fn sample_diagram() -> (Diagram, ElementId, ElementId) {
+        let mut diagram = Diagram::new("Sample", "", DiagramType::Container);
+        let user = Element::new(ElementType::person("User", "A \"user\""), Position::new(10.0, 20.0));
+        let db = Element::new(
+            ElementType::container("Database", "Stores data", ContainerType::Database, "PostgreSQL"),
+            Position::new(300.0, 40.0),
+        );
+        let (user_id, db_id) = (user.id, db.id);
+        diagram.add_element(user);
+        diagram.add_element(db);
+        diagram.add_relationship(Relationship::with_technology(user_id, db_id, "reads", "SQL"));
+        (diagram, user_id, db_id)
+    }
+
+    mod to_dsl_tests {
+        use super::*;
+
+        /// Verifies to_dsl emits one macro per element and relationship
+        #[test]
+        fn to_dsl_emits_macros() {
+            let (diagram, user_id, db_id) = sample_diagram();
+            let text = to_dsl(&diagram);
+
+            assert!(text.starts_with("title Sample"));
+            assert!(text.contains(&format!("Person({}, \"User\", \"A \\\"user\\\"\")", element_alias(user_id))));
+            assert!(text.contains(&format!(
+                "ContainerDb({}, \"Database\", \"Stores data\", \"PostgreSQL\")",
+                element_alias(db_id)
+            )));
+            assert!(text.contains("\"reads\", \"SQL\")"));
+        }
+    }
+
+    mod parse_dsl_tests {
+        use super::*;
+
+        /// Verifies every supported macro is recognised
+        #[test]
+        fn parse_dsl_recognises_macros() {
+            let text = r#"
+                title Shop
+                ' a comment
+                Person(a, "A", "")
+                Person_Ext(b, "B", "")
+                System(c, "C", "")
+                System_Ext(d, "D", "")
+                Container(e, "E", "", "Rust")
+                ContainerDb(f, "F", "", "")
+                ContainerQueue(g, "G", "", "")
+                Rel(a, c, "uses")
+                Rel(c, f, "reads, writes", "SQL")
+                BiRel(c, d, "syncs")
+                Rel_Back(g, e, "notifies")
+            "#;
+            let document = parse_dsl(text).expect("Failed to parse");
+
+            assert_eq!(document.title.as_deref(), Some("Shop"));
+            assert_eq!(document.elements.len(), 7);
+            assert_eq!(document.relationships.len(), 4);
+            assert_eq!(document.relationships[1].description, "reads, writes");
+            assert_eq!(document.relationships[1].technology.as_deref(), Some("SQL"));
+            assert_eq!(document.relationships[1].direction, Direction::Forward);
+            assert_eq!(document.relationships[2].direction, Direction::BiDirectional);
+            assert_eq!(document.relationships[3].direction, Direction::Back);
+        }
+
+        /// Verifies database and queue systems round-trip through their macros
+        #[test]
+        fn system_kinds_round_trip() {
+            let document = parse_dsl("SystemDb_Ext(a, \"A\", \"\")\nSystemQueue(b, \"B\", \"\")").unwrap();
+            let kinds: Vec<(SystemKind, bool)> = document
+                .elements
+                .iter()
+                .map(|parsed| match &parsed.element_type {
+                    ElementType::SoftwareSystem(data) => (data.kind, data.is_external),
+                    other => panic!("expected a system, got {:?}", other),
+                })
+                .collect();
+            assert_eq!(kinds, vec![(SystemKind::Database, true), (SystemKind::Queue, false)]);
+
+            let mut diagram = Diagram::new("Sample", "", DiagramType::SystemContext);
+            apply_dsl(&mut diagram, "SystemDb_Ext(a, \"A\", \"\")").unwrap();
+            assert!(to_dsl(&diagram).contains("SystemDb_Ext("));
+        }
+
+        /// Verifies re-applying a component keeps the container it belongs to
+        #[test]
+        fn component_keeps_container() {
+            let (mut diagram, _, db_id) = sample_diagram();
+            let mut component = Element::new(ElementType::component("Repo", "", "JPA"), Position::new(0.0, 0.0));
+            if let ElementType::Component(data) = &mut component.element_type {
+                data.container = Some(db_id);
+            }
+            let id = component.id;
+            diagram.add_element(component);
+
+            let text = to_dsl(&diagram);
+            assert!(text.contains(&format!("Component({}, \"Repo\", \"\", \"JPA\")", element_alias(id))));
+            apply_dsl(&mut diagram, &text.replace("\"JPA\"", "\"Hibernate\"")).unwrap();
+            match &diagram.elements[&id].element_type {
+                ElementType::Component(data) => {
+                    assert_eq!(data.technology, "Hibernate");
+                    assert_eq!(data.container, Some(db_id));
+                }
+                other => panic!("expected a component, got {:?}", other),
+            }
+        }
+
+        /// Verifies Dynamic diagrams are written and read as numbered RelIndex steps
+        #[test]
+        fn dynamic_steps_round_trip() {
+            let (mut diagram, user_id, db_id) = sample_diagram();
+            diagram.diagram_type = DiagramType::Dynamic;
+            diagram.relationships[0].order = Some(2);
+            diagram.add_relationship(Relationship::new(db_id, user_id, "returns rows"));
+
+            let text = to_dsl(&diagram);
+            assert!(text.contains(&format!(
+                "RelIndex(2, {}, {}, \"reads\", \"SQL\")",
+                element_alias(user_id),
+                element_alias(db_id)
+            )));
+            assert!(text.contains(&format!("RelIndex(3, {}, ", element_alias(db_id))));
+
+            apply_dsl(&mut diagram, &text.replace("RelIndex(3,", "RelIndex(1,")).unwrap();
+            let steps: Vec<(u32, &str)> = diagram.steps().iter().map(|(n, r)| (*n, r.description.as_str())).collect();
+            assert_eq!(steps, vec![(1, "returns rows"), (2, "reads")]);
+            assert!(parse_dsl("RelIndex(a, b, c)").is_err());
+        }
+
+        /// Verifies errors carry the offending line number
+        #[test]
+        fn parse_dsl_reports_line_numbers() {
+            let err = parse_dsl("Person(a, \"A\", \"\")\nWidget(b)").unwrap_err();
+            assert_eq!(err.line, 2);
+            assert!(err.message.contains("Widget"));
+        }
+
+        /// Verifies relationships must reference declared elements
+        #[test]
+        fn parse_dsl_rejects_unknown_aliases() {
+            let err = parse_dsl("Person(a, \"A\", \"\")\nRel(a, b, \"uses\")").unwrap_err();
+            assert_eq!(err.line, 2);
+            assert!(err.message.contains("`b`"));
+        }
+
+        /// Verifies unterminated strings and duplicate aliases are rejected
+        #[test]
+        fn parse_dsl_rejects_malformed_input() {
+            assert!(parse_dsl("Person(a, \"A)").is_err());
+            assert!(parse_dsl("Person(a, \"A\", \"\")\nSystem(a, \"B\", \"\")").is_err());
+        }
+    }
+
+    mod apply_dsl_tests {
+        use super::*;
+
+        /// Verifies applying the generated text leaves the diagram unchanged
+        #[test]
+        fn apply_dsl_roundtrip_preserves_layout() {
+            let (mut diagram, user_id, db_id) = sample_diagram();
+            let rel_id = diagram.relationships[0].id;
+            let text = to_dsl(&diagram);
+
+            apply_dsl(&mut diagram, &text).expect("Failed to apply");
+
+            assert_eq!(diagram.elements.len(), 2);
+            assert_eq!(diagram.get_element(user_id).unwrap().position, Position::new(10.0, 20.0));
+            assert_eq!(diagram.get_element(user_id).unwrap().description(), "A \"user\"");
+            assert_eq!(diagram.get_element(db_id).unwrap().position, Position::new(300.0, 40.0));
+            assert_eq!(diagram.relationships.len(), 1);
+            assert_eq!(diagram.relationships[0].id, rel_id);
+            assert_eq!(diagram.take_events().last(), Some(&DiagramEvent::Replaced));
+        }
+
+        /// Verifies edits, additions and removals in the text reach the diagram
+        #[test]
+        fn apply_dsl_updates_diagram() {
+            let (mut diagram, user_id, db_id) = sample_diagram();
+            let text = format!(
+                "title Renamed\nPerson({}, \"Customer\", \"\")\nSystem(mail, \"Mail\", \"\")\nRel({}, mail, \"notifies\")\n",
+                element_alias(user_id),
+                element_alias(user_id)
+            );
+
+            apply_dsl(&mut diagram, &text).expect("Failed to apply");
+
+            assert_eq!(diagram.name, "Renamed");
+            assert_eq!(diagram.elements.len(), 2);
+            assert!(diagram.get_element(db_id).is_none());
+            assert_eq!(diagram.get_element(user_id).unwrap().name(), "Customer");
+            assert_eq!(diagram.relationships.len(), 1);
+            assert_eq!(diagram.relationships[0].source_id, user_id);
+        }
+
+        /// Verifies a parse error leaves the diagram untouched
+        #[test]
+        fn apply_dsl_error_leaves_diagram_unchanged() {
+            let (mut diagram, _, _) = sample_diagram();
+            assert!(apply_dsl(&mut diagram, "Nonsense").is_err());
+            assert_eq!(diagram.elements.len(), 2);
+            assert_eq!(diagram.relationships.len(), 1);
+        }
+
+        /// Verifies notes are left out of the text and survive applying it
+        #[test]
+        fn apply_dsl_keeps_notes() {
+            let (mut diagram, user_id, db_id) = sample_diagram();
+            let note = Element::new(ElementType::note("Ask about \"retention\""), Position::new(0.0, 200.0));
+            let note_id = note.id;
+            diagram.add_element(note);
+            diagram.add_relationship(Relationship::new(note_id, db_id, ""));
+            diagram.add_relationship(Relationship::new(note_id, user_id, ""));
+            let text = to_dsl(&diagram);
+            assert!(!text.contains("retention"));
+            assert!(!text.contains(&element_alias(note_id)));
+
+            let text = format!("{}\n", text.lines().filter(|l| !l.contains(&element_alias(user_id))).collect::<Vec<_>>().join("\n"));
+            apply_dsl(&mut diagram, &text).expect("Failed to apply");
+
+            assert_eq!(diagram.get_element(note_id).unwrap().name(), "Ask about \"retention\"");
+            assert!(diagram.get_element(user_id).is_none());
+            assert_eq!(diagram.relationships.len(), 1);
+            assert_eq!((diagram.relationships[0].source_id, diagram.relationships[0].target_id), (note_id, db_id));
+        }
+    }
+}
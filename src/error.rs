@@ -0,0 +1,99 @@
+//! Crate-wide error type
+
+use crate::dsl::DslError;
+use crate::export::render::RenderError;
+use crate::import::ImportError;
+use crate::model::SchemaError;
+use crate::quick_add::QuickAddError;
+use crate::share::ShareLinkError;
+use thiserror::Error;
+
+/// Errors returned by C2Draw's save, load, import and export paths
+#[derive(Debug, Error)]
+pub enum C2DrawError {
+    /// Reading or writing a file failed
+    #[error("{path}: {source}")]
+    File {
+        path: std::path::PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// Any other I/O failure
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// A diagram could not be serialized or deserialized
+    #[error("invalid diagram data: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A diagram could not be written as RON
+    #[cfg(feature = "ron")]
+    #[error("invalid diagram data: {0}")]
+    RonWrite(#[from] ron::Error),
+    /// A RON diagram file could not be read
+    #[cfg(feature = "ron")]
+    #[error("invalid diagram data: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    /// A diagram file does not match the file format schema
+    #[error("invalid diagram data: {0}")]
+    Schema(#[from] SchemaError),
+    #[error(transparent)]
+    Import(#[from] ImportError),
+    #[error(transparent)]
+    Render(#[from] RenderError),
+    #[error(transparent)]
+    ShareLink(#[from] ShareLinkError),
+    #[error(transparent)]
+    Dsl(#[from] DslError),
+    #[error(transparent)]
+    QuickAdd(#[from] QuickAddError),
+}
+
+impl C2DrawError {
+    /// Attach the path of the file being accessed to an I/O error
+    pub fn file(path: impl Into<std::path::PathBuf>, source: std::io::Error) -> Self {
+        C2DrawError::File {
+            path: path.into(),
+            source,
+        }
+    }
+}
+
+/// Result alias using [`C2DrawError`]
+pub type Result<T> = std::result::Result<T, C2DrawError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod display_tests {
+        use super::*;
+
+        /// Verifies file errors mention the path
+        #[test]
+        fn file_error_includes_path() {
+            let err = C2DrawError::file(
+                "/tmp/diagram.c4d",
+                std::io::Error::new(std::io::ErrorKind::NotFound, "not found"),
+            );
+            assert_eq!(err.to_string(), "/tmp/diagram.c4d: not found");
+        }
+
+        /// Verifies JSON errors are converted and described
+        #[test]
+        fn json_error_converts() {
+            let json_err = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+            let err: C2DrawError = json_err.into();
+            assert!(err.to_string().starts_with("invalid diagram data"));
+        }
+
+        /// Verifies module errors pass their message through unchanged
+        #[test]
+        fn module_errors_are_transparent() {
+            let import = ImportError::Parse {
+                line: 3,
+                message: "bad".to_string(),
+            };
+            let err: C2DrawError = import.clone().into();
+            assert_eq!(err.to_string(), import.to_string());
+        }
+    }
+}
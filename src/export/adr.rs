@@ -0,0 +1,144 @@
+//! Architecture Decision Record stub export
+//!
+//! Produces a Markdown ADR in the usual Status / Context / Decision /
+//! Consequences layout, pre-filled from a diagram: its name and description,
+//! a link to the diagram file, an inventory of its elements and the diagram
+//! itself as embedded Mermaid source, so the record shows what it affects.
+//! Sections the author still has to write hold HTML comment prompts, which
+//! Markdown renderers hide.
+
+use super::{DiagramExporter, MermaidExporter};
+use crate::model::{Diagram, Element, ElementType};
+use std::path::{Component, Path, PathBuf};
+
+/// Path of `to` relative to the directory `from_dir`, with `/` separators
+///
+/// Both paths should be absolute; if they share no prefix (e.g. different
+/// drives) `to` is returned unchanged.
+pub fn relative_path(from_dir: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from_dir.components().collect();
+    let target: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return to.to_string_lossy().replace('\\', "/");
+    }
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &target[common..] {
+        relative.push(component);
+    }
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+/// Text safe to place in a Markdown table cell
+fn cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+fn technology(element: &Element) -> &str {
+    match &element.element_type {
+        ElementType::Container(data) => &data.technology,
+        _ => "",
+    }
+}
+
+/// Render an ADR stub for `diagram`
+///
+/// `diagram_link` is where the diagram file is, relative to the ADR (see
+/// [`relative_path`]); None for a diagram that was never saved. `date` is
+/// written as given, normally `YYYY-MM-DD`.
+pub fn render_adr(diagram: &Diagram, diagram_link: Option<&str>, date: &str) -> String {
+    let mut output = format!("# ADR: {}\n\n", diagram.name);
+    output.push_str("- Status: Proposed\n");
+    output.push_str(&format!("- Date: {}\n", date));
+    if !diagram.metadata.author.is_empty() {
+        output.push_str(&format!("- Deciders: {}\n", diagram.metadata.author));
+    }
+    match diagram_link {
+        Some(link) => output.push_str(&format!("- Diagram: [{}]({})", diagram.name, link)),
+        None => output.push_str(&format!("- Diagram: {}", diagram.name)),
+    }
+    if !diagram.metadata.version_label.is_empty() {
+        output.push_str(&format!(" ({})", diagram.metadata.version_label));
+    }
+    output.push_str("\n\n## Context\n\n");
+    if !diagram.description.is_empty() {
+        output.push_str(&format!("{}\n\n", diagram.description));
+    }
+    output.push_str("<!-- What is the issue that motivates this decision? What forces are at play? -->\n");
+
+    let mut elements: Vec<&Element> = diagram.elements.values().collect();
+    elements.sort_by(|a, b| a.name().cmp(b.name()).then(a.id.cmp(&b.id)));
+    if !elements.is_empty() {
+        output.push_str("\n### Affected elements\n\n| Element | Type | Technology | Description |\n|---|---|---|---|\n");
+        for element in elements {
+            output.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                cell(element.name()),
+                element.element_type.type_name(),
+                cell(technology(element)),
+                cell(element.description())
+            ));
+        }
+    }
+
+    output.push_str("\n## Decision\n\n<!-- What change is proposed or has been agreed? -->\n");
+    output.push_str("\n## Consequences\n\n<!-- What becomes easier or harder because of this change? -->\n");
+    output.push_str("\n## Diagram\n\n```mermaid\n");
+    output.push_str(MermaidExporter::new().export(diagram).trim_end());
+    output.push_str("\n```\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContainerType, DiagramType, Position};
+
+    mod relative_path_tests {
+        use super::*;
+
+        /// Verifies links climb out of the ADR folder and back down to the diagram
+        #[test]
+        fn relative_path_between_folders() {
+            assert_eq!(relative_path(Path::new("/repo/docs/adr"), Path::new("/repo/arch/shop.c4d")), "../../arch/shop.c4d");
+            assert_eq!(relative_path(Path::new("/repo"), Path::new("/repo/shop.c4d")), "shop.c4d");
+        }
+    }
+
+    mod render_adr_tests {
+        use super::*;
+
+        /// Verifies the ADR holds the diagram link, element inventory and Mermaid source
+        #[test]
+        fn render_adr_fills_in_diagram() {
+            let mut diagram = Diagram::new("Shop", "Online shop | v2", DiagramType::Container);
+            diagram.metadata.author = "Sam".to_string();
+            diagram.add_element(Element::new(
+                ElementType::container("Orders DB", "Stores orders", ContainerType::Database, "PostgreSQL"),
+                Position::new(0.0, 0.0),
+            ));
+            diagram.add_element(Element::new(ElementType::person("Customer", "Buys things"), Position::new(0.0, 0.0)));
+
+            let adr = render_adr(&diagram, Some("../shop.c4d"), "2024-03-01");
+            assert!(adr.starts_with("# ADR: Shop\n\n- Status: Proposed\n- Date: 2024-03-01\n- Deciders: Sam\n"));
+            assert!(adr.contains("- Diagram: [Shop](../shop.c4d)\n"));
+            assert!(adr.contains("## Context\n\nOnline shop | v2\n"));
+            let customer = adr.find("| Customer |").unwrap();
+            let database = adr.find("| Orders DB | Container | PostgreSQL | Stores orders |").unwrap();
+            assert!(customer < database);
+            assert!(adr.contains("```mermaid\nC4Container"));
+            assert!(adr.ends_with("```\n"));
+        }
+
+        /// Verifies an unsaved diagram is named without a link
+        #[test]
+        fn render_adr_without_file() {
+            let adr = render_adr(&Diagram::new("Draft", "", DiagramType::SystemContext), None, "2024-03-01");
+            assert!(adr.contains("- Diagram: Draft\n"));
+            assert!(!adr.contains("### Affected elements"));
+        }
+    }
+}
@@ -0,0 +1,343 @@
+//! Documentation site export
+//!
+//! Writes a set of diagrams to a folder as a small Markdown handbook: an
+//! `index.md` grouped by C4 level and, per diagram, a Markdown page with an
+//! SVG image and element/relationship tables. Elements that drill down into
+//! another diagram link to its page, and the detail page links back.
+//!
+//! An element drills down into a diagram if its `url` names that diagram's
+//! file, or, failing that, if it is a software system with the same name as
+//! a container diagram, or a container with the same name as a component
+//! diagram.
+
+use super::{DiagramExporter, SvgExporter};
+use crate::model::{Diagram, DiagramType, Element, ElementType, COMPRESSED_FILE_EXTENSION, FILE_EXTENSION};
+use std::path::Path;
+
+/// A diagram and the file it was loaded from
+#[derive(Debug, Clone)]
+pub struct WorkspaceDiagram {
+    /// File name, e.g. `context.c4d`; used for page names and `url` links
+    pub file_name: String,
+    pub diagram: Diagram,
+}
+
+/// A generated documentation file
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocsFile {
+    pub name: String,
+    pub contents: String,
+}
+
+/// Load every diagram file in a directory, sorted by file name
+pub fn load_workspace(dir: &Path) -> crate::Result<Vec<WorkspaceDiagram>> {
+    let mut diagrams = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|e| crate::C2DrawError::file(dir, e))? {
+        let path = entry.map_err(|e| crate::C2DrawError::file(dir, e))?.path();
+        let is_diagram = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext == FILE_EXTENSION || ext == COMPRESSED_FILE_EXTENSION);
+        if !is_diagram || !path.is_file() {
+            continue;
+        }
+        diagrams.push(WorkspaceDiagram {
+            file_name: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+            diagram: Diagram::load_from_file(&path)?,
+        });
+    }
+    diagrams.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    Ok(diagrams)
+}
+
+/// Render the documentation site and write it into `out_dir`
+pub fn export_docs(diagrams: &[WorkspaceDiagram], out_dir: &Path) -> crate::Result<()> {
+    std::fs::create_dir_all(out_dir).map_err(|e| crate::C2DrawError::file(out_dir, e))?;
+    for file in render_docs(diagrams) {
+        let path = out_dir.join(&file.name);
+        std::fs::write(&path, file.contents).map_err(|e| crate::C2DrawError::file(&path, e))?;
+    }
+    Ok(())
+}
+
+/// Render the documentation site: `index.md` plus a page and image per diagram
+pub fn render_docs(diagrams: &[WorkspaceDiagram]) -> Vec<DocsFile> {
+    let slugs = page_slugs(diagrams);
+    let drill_downs: Vec<Vec<Option<usize>>> = diagrams
+        .iter()
+        .map(|d| {
+            sorted_elements(&d.diagram)
+                .into_iter()
+                .map(|e| drill_down_target(e, diagrams))
+                .collect()
+        })
+        .collect();
+
+    let mut files = vec![DocsFile {
+        name: "index.md".to_string(),
+        contents: render_index(diagrams, &slugs),
+    }];
+    for (index, workspace_diagram) in diagrams.iter().enumerate() {
+        let parents: Vec<usize> = (0..diagrams.len())
+            .filter(|&other| other != index && drill_downs[other].contains(&Some(index)))
+            .collect();
+        files.push(DocsFile {
+            name: format!("{}.md", slugs[index]),
+            contents: render_page(workspace_diagram, index, &slugs, &drill_downs[index], &parents, diagrams),
+        });
+        files.push(DocsFile {
+            name: format!("{}.svg", slugs[index]),
+            contents: SvgExporter::new().export(&workspace_diagram.diagram),
+        });
+    }
+    files
+}
+
+/// The diagram an element drills down into, if any
+fn drill_down_target(element: &Element, diagrams: &[WorkspaceDiagram]) -> Option<usize> {
+    if let Some(url) = element.url.as_deref() {
+        let file_name = url.trim().rsplit(['/', '\\']).next().unwrap_or("");
+        if let Some(index) = diagrams.iter().position(|d| d.file_name == file_name) {
+            return Some(index);
+        }
+    }
+    let level = match element.element_type {
+        ElementType::SoftwareSystem(_) => DiagramType::Container,
+        ElementType::Container(_) => DiagramType::Component,
+        _ => return None,
+    };
+    diagrams.iter().position(|d| {
+        d.diagram.diagram_type == level && d.diagram.name.trim().eq_ignore_ascii_case(element.name().trim())
+    })
+}
+
+/// Unique page names derived from the file names
+fn page_slugs(diagrams: &[WorkspaceDiagram]) -> Vec<String> {
+    let mut slugs: Vec<String> = Vec::with_capacity(diagrams.len());
+    for d in diagrams {
+        let stem = Path::new(&d.file_name)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase();
+        let mut base: String = stem
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        if base.is_empty() || base == "index" {
+            base = format!("diagram-{}", base);
+        }
+        let mut slug = base.clone();
+        let mut n = 2;
+        while slugs.contains(&slug) {
+            slug = format!("{}-{}", base, n);
+            n += 1;
+        }
+        slugs.push(slug);
+    }
+    slugs
+}
+
+fn sorted_elements(diagram: &Diagram) -> Vec<&Element> {
+    let mut elements: Vec<&Element> = diagram.elements.values().collect();
+    elements.sort_by(|a, b| a.name().cmp(b.name()).then(a.id.cmp(&b.id)));
+    elements
+}
+
+/// Text safe to place in a Markdown table cell
+fn cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+fn render_index(diagrams: &[WorkspaceDiagram], slugs: &[String]) -> String {
+    let mut output = String::from("# Architecture\n");
+    for diagram_type in DiagramType::ALL {
+        let entries: Vec<usize> = (0..diagrams.len())
+            .filter(|&i| diagrams[i].diagram.diagram_type == diagram_type)
+            .collect();
+        if entries.is_empty() {
+            continue;
+        }
+        output.push_str(&format!("\n## {}\n\n", diagram_type.as_str()));
+        for index in entries {
+            let diagram = &diagrams[index].diagram;
+            output.push_str(&format!("- [{}]({}.md)", diagram.name, slugs[index]));
+            if !diagram.description.is_empty() {
+                output.push_str(&format!(" — {}", diagram.description.replace('\n', " ")));
+            }
+            output.push('\n');
+        }
+    }
+    output
+}
+
+fn render_page(
+    workspace_diagram: &WorkspaceDiagram,
+    index: usize,
+    slugs: &[String],
+    drill_downs: &[Option<usize>],
+    parents: &[usize],
+    diagrams: &[WorkspaceDiagram],
+) -> String {
+    let diagram = &workspace_diagram.diagram;
+    let mut output = format!("# {}\n\n", diagram.name);
+    output.push_str(&format!("*{} diagram*", diagram.diagram_type.as_str()));
+    for &parent in parents {
+        output.push_str(&format!(
+            " · Part of [{}]({}.md)",
+            diagrams[parent].diagram.name, slugs[parent]
+        ));
+    }
+    output.push_str(" · [Index](index.md)\n\n");
+    if !diagram.description.is_empty() {
+        output.push_str(&format!("{}\n\n", diagram.description));
+    }
+    output.push_str(&format!("![{}]({}.svg)\n", diagram.name, slugs[index]));
+
+    let elements = sorted_elements(diagram);
+    if !elements.is_empty() {
+        output.push_str("\n## Elements\n\n| Name | Type | Description | Technology |\n|---|---|---|---|\n");
+        for (element, target) in elements.iter().zip(drill_downs) {
+            let name = match target {
+                Some(target) => format!("[{}]({}.md)", cell(element.name()), slugs[*target]),
+                None => cell(element.name()),
+            };
+            let technology = cell(element.element_type.technology().unwrap_or(""));
+            output.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                name,
+                element.element_type.type_name(),
+                cell(element.description()),
+                technology
+            ));
+        }
+    }
+
+    if !diagram.relationships.is_empty() {
+        output.push_str("\n## Relationships\n\n| Source | Destination | Description | Technology |\n|---|---|---|---|\n");
+        let name = |id| diagram.get_element(id).map_or("?".to_string(), |e| cell(e.name()));
+        for rel in &diagram.relationships {
+            output.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                name(rel.source_id),
+                name(rel.target_id),
+                cell(&rel.description),
+                cell(rel.technology.as_deref().unwrap_or(""))
+            ));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContainerType, Position, Relationship};
+
+    /// A context diagram with a "Banking" system and its container diagram
+    fn workspace() -> Vec<WorkspaceDiagram> {
+        let mut context = Diagram::new("Internet Banking", "Overview", DiagramType::SystemContext);
+        let user = Element::new(ElementType::person("Customer", "Has an account"), Position::new(0.0, 0.0));
+        let system = Element::new(ElementType::system("Banking", "Core | ledger"), Position::new(200.0, 0.0));
+        let (user_id, system_id) = (user.id, system.id);
+        context.add_element(user);
+        context.add_element(system);
+        context.add_relationship(Relationship::with_technology(user_id, system_id, "Uses", "HTTPS"));
+
+        let mut containers = Diagram::new("Banking", "", DiagramType::Container);
+        containers.add_element(Element::new(
+            ElementType::container("API", "Serves the app", ContainerType::Microservice, "Rust"),
+            Position::new(0.0, 0.0),
+        ));
+
+        vec![
+            WorkspaceDiagram {
+                file_name: "context.c4d".to_string(),
+                diagram: context,
+            },
+            WorkspaceDiagram {
+                file_name: "banking containers.c4dz".to_string(),
+                diagram: containers,
+            },
+        ]
+    }
+
+    fn file<'a>(files: &'a [DocsFile], name: &str) -> &'a str {
+        &files.iter().find(|f| f.name == name).expect(name).contents
+    }
+
+    mod render_docs_tests {
+        use super::*;
+
+        /// Verifies an index, page and image are produced per diagram
+        #[test]
+        fn render_docs_produces_all_files() {
+            let files = render_docs(&workspace());
+            let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+            assert_eq!(
+                names,
+                vec!["index.md", "context.md", "context.svg", "banking-containers.md", "banking-containers.svg"]
+            );
+            assert!(file(&files, "context.svg").starts_with("<svg"));
+        }
+
+        /// Verifies the index groups diagrams by level
+        #[test]
+        fn render_index_groups_by_level() {
+            let files = render_docs(&workspace());
+            let index = file(&files, "index.md");
+            assert!(index.contains(
+                "## System Context\n\n- [Internet Banking](context.md) — Overview\n\n## Container\n\n- [Banking](banking-containers.md)\n"
+            ));
+        }
+
+        /// Verifies systems link down to the matching container diagram and back
+        #[test]
+        fn render_page_links_between_levels() {
+            let files = render_docs(&workspace());
+            let context = file(&files, "context.md");
+            assert!(context.contains("| [Banking](banking-containers.md) | Software System | Core \\| ledger |  |"));
+            assert!(context.contains("| Customer | Banking | Uses | HTTPS |"));
+            assert!(context.contains("![Internet Banking](context.svg)"));
+
+            let containers = file(&files, "banking-containers.md");
+            assert!(containers.contains("Part of [Internet Banking](context.md)"));
+            assert!(containers.contains("| API | Container | Serves the app | Rust |"));
+        }
+
+        /// Verifies an element URL naming a diagram file takes precedence
+        #[test]
+        fn drill_down_prefers_url() {
+            let diagrams = workspace();
+            let mut person = Element::new(ElementType::person("Customer", ""), Position::new(0.0, 0.0));
+            assert_eq!(drill_down_target(&person, &diagrams), None);
+            person.url = Some("../docs/context.c4d".to_string());
+            assert_eq!(drill_down_target(&person, &diagrams), Some(0));
+        }
+
+        /// Verifies containers drill down into the component diagram of the same name
+        #[test]
+        fn container_drills_into_components() {
+            let mut diagrams = workspace();
+            diagrams.push(WorkspaceDiagram {
+                file_name: "api.c4d".to_string(),
+                diagram: Diagram::new("API", "", DiagramType::Component),
+            });
+            let api = diagrams[1].diagram.elements.values().next().unwrap().clone();
+            assert_eq!(drill_down_target(&api, &diagrams), Some(2));
+            assert!(file(&render_docs(&diagrams), "index.md").ends_with("## Component\n\n- [API](api.md)\n"));
+        }
+
+        /// Verifies page names are sanitized and unique
+        #[test]
+        fn page_slugs_are_unique() {
+            let diagram = Diagram::default();
+            let named = |name: &str| WorkspaceDiagram {
+                file_name: name.to_string(),
+                diagram: diagram.clone(),
+            };
+            let slugs = page_slugs(&[named("A b.c4d"), named("a-b.c4dz"), named("index.c4d")]);
+            assert_eq!(slugs, vec!["a-b", "a-b-2", "diagram-index"]);
+        }
+    }
+}
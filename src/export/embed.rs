@@ -0,0 +1,56 @@
+//! Markdown snippet for embedding a diagram in a repository's documentation
+//!
+//! The snippet shows the diagram's SVG image, keeps its text source in a
+//! collapsed `<details>` block so it can be reviewed in diffs, and links to
+//! the `.c4d` file to edit it. Paths are written as given, normally relative
+//! to the Markdown file the snippet is pasted into.
+
+use super::ExportFormat;
+
+/// Code fence language that renders or highlights `format`
+fn fence_language(format: ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::PlantUml => "plantuml",
+        ExportFormat::Mermaid | ExportFormat::MermaidFlowchart => "mermaid",
+    }
+}
+
+/// Render the snippet for a diagram called `name`
+///
+/// `source` is the diagram exported as `format`, `image` the path of its
+/// rendered image and `diagram_file` the path of the diagram file.
+pub fn embed_snippet(name: &str, format: ExportFormat, source: &str, image: &str, diagram_file: &str) -> String {
+    let file_name = diagram_file.rsplit('/').next().unwrap_or(diagram_file);
+    format!(
+        "![{name}]({image})\n\n<details>\n<summary>{format} source</summary>\n\n```{language}\n{source}\n```\n\n</details>\n\nEdit with C2Draw: [{file_name}]({diagram_file})\n",
+        format = format.as_str(),
+        language = fence_language(format),
+        source = source.trim_end(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod embed_snippet_tests {
+        use super::*;
+
+        /// Verifies the snippet holds the image, collapsed source and file link
+        #[test]
+        fn embed_snippet_layout() {
+            let snippet = embed_snippet("Shop", ExportFormat::Mermaid, "C4Context\n  title Shop\n", "docs/shop.svg", "docs/shop.c4d");
+            assert_eq!(
+                snippet,
+                "![Shop](docs/shop.svg)\n\n<details>\n<summary>Mermaid source</summary>\n\n```mermaid\nC4Context\n  title Shop\n```\n\n</details>\n\nEdit with C2Draw: [shop.c4d](docs/shop.c4d)\n"
+            );
+        }
+
+        /// Verifies PlantUML source is fenced as plantuml
+        #[test]
+        fn embed_snippet_plantuml_fence() {
+            let snippet = embed_snippet("Shop", ExportFormat::PlantUml, "@startuml\n@enduml", "shop.svg", "shop.c4d");
+            assert!(snippet.contains("<summary>C4-PlantUML source</summary>\n\n```plantuml\n@startuml"));
+        }
+    }
+}
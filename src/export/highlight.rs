@@ -0,0 +1,170 @@
+//! Syntax highlighting for exported diagram source
+//!
+//! A small line-based tokenizer, enough to colour C4-PlantUML and Mermaid
+//! output in the export window. It only has to understand what the exporters
+//! emit, not the full languages.
+
+use super::ExportFormat;
+use std::ops::Range;
+
+/// What a highlighted span of text is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Text,
+    Comment,
+    /// `@startuml`, `!include` and other preprocessor lines
+    Directive,
+    /// Macro and statement names (`Person`, `Rel`, `title`, `flowchart`)
+    Keyword,
+    String,
+    /// Arrows and brackets
+    Punctuation,
+}
+
+/// Statement keywords, highlighted even when not followed by `(`
+const KEYWORDS: &[&str] = &[
+    "title", "note", "end", "C4Context", "C4Container", "flowchart", "subgraph", "classDef", "class", "style",
+    "UpdateLayoutConfig",
+];
+
+/// Split `text` into consecutive spans covering all of it
+pub fn highlight(format: ExportFormat, text: &str) -> Vec<(Range<usize>, TokenKind)> {
+    let comment = match format {
+        ExportFormat::PlantUml => "'",
+        ExportFormat::Mermaid | ExportFormat::MermaidFlowchart => "%%",
+    };
+    let mut spans = Vec::new();
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        highlight_line(line, line_start, comment, &mut spans);
+        line_start += line.len();
+    }
+    spans
+}
+
+fn highlight_line(line: &str, offset: usize, comment: &str, spans: &mut Vec<(Range<usize>, TokenKind)>) {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let whole_line = if trimmed.starts_with(comment) {
+        Some(TokenKind::Comment)
+    } else if trimmed.starts_with('@') || trimmed.starts_with('!') {
+        Some(TokenKind::Directive)
+    } else {
+        None
+    };
+    if let Some(kind) = whole_line {
+        push(spans, offset..offset + indent, TokenKind::Text);
+        push(spans, offset + indent..offset + line.len(), kind);
+        return;
+    }
+
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let kind = match bytes[i] {
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += 1;
+                }
+                i = (i + 1).min(bytes.len());
+                TokenKind::String
+            }
+            b if b.is_ascii_alphabetic() || b == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                let word = &line[start..i];
+                if bytes.get(i) == Some(&b'(') || KEYWORDS.contains(&word) {
+                    TokenKind::Keyword
+                } else {
+                    TokenKind::Text
+                }
+            }
+            b'-' | b'.' | b'>' | b'<' | b'(' | b')' | b'[' | b']' | b'{' | b'}' | b',' => {
+                i += 1;
+                TokenKind::Punctuation
+            }
+            _ => {
+                // Any other run of characters, stopping at a UTF-8 boundary
+                i += 1;
+                while i < bytes.len() && !line.is_char_boundary(i) {
+                    i += 1;
+                }
+                TokenKind::Text
+            }
+        };
+        push(spans, offset + start..offset + i, kind);
+    }
+}
+
+/// Append a span, merging it into the previous one if they are the same kind
+fn push(spans: &mut Vec<(Range<usize>, TokenKind)>, range: Range<usize>, kind: TokenKind) {
+    if range.is_empty() {
+        return;
+    }
+    match spans.last_mut() {
+        Some((last, last_kind)) if *last_kind == kind && last.end == range.start => last.end = range.end,
+        _ => spans.push((range, kind)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(format: ExportFormat, text: &str) -> Vec<(&str, TokenKind)> {
+        highlight(format, text)
+            .into_iter()
+            .map(|(range, kind)| (&text[range], kind))
+            .filter(|(s, _)| !s.trim().is_empty())
+            .collect()
+    }
+
+    mod highlight_tests {
+        use super::*;
+
+        /// Verifies spans cover the whole text without gaps
+        #[test]
+        fn spans_cover_text() {
+            let text = "@startuml\nPerson(user, \"User ü\", \"x\")\n' note\n";
+            let spans = highlight(ExportFormat::PlantUml, text);
+            let mut end = 0;
+            for (range, _) in &spans {
+                assert_eq!(range.start, end);
+                end = range.end;
+            }
+            assert_eq!(end, text.len());
+        }
+
+        /// Verifies PlantUML directives, macros, strings and comments
+        #[test]
+        fn highlight_plantuml() {
+            let text = "!include C4.puml\nRel(a, b, \"uses\")\n' Author: Ana\n";
+            let tokens = tokens(ExportFormat::PlantUml, text);
+            assert_eq!(tokens[0], ("!include C4.puml\n", TokenKind::Directive));
+            assert_eq!(tokens[1], ("Rel", TokenKind::Keyword));
+            assert!(tokens.contains(&("\"uses\"", TokenKind::String)));
+            assert_eq!(tokens.last(), Some(&("' Author: Ana\n", TokenKind::Comment)));
+        }
+
+        /// Verifies Mermaid comments use %% and keywords stand alone
+        #[test]
+        fn highlight_mermaid() {
+            let text = "flowchart TD\n    %% Notes\n    a --> b\n";
+            let tokens = tokens(ExportFormat::MermaidFlowchart, text);
+            assert_eq!(tokens[0], ("flowchart", TokenKind::Keyword));
+            assert_eq!(tokens[1].1, TokenKind::Text);
+            assert_eq!(tokens[2], ("%% Notes\n", TokenKind::Comment));
+            assert!(tokens.contains(&("-->", TokenKind::Punctuation)));
+        }
+
+        /// Verifies an unterminated string runs to the end of the line
+        #[test]
+        fn unterminated_string() {
+            let tokens = tokens(ExportFormat::PlantUml, "title \"Open");
+            assert_eq!(tokens, vec![("title", TokenKind::Keyword), ("\"Open", TokenKind::String)]);
+        }
+    }
+}
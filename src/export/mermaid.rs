@@ -13,6 +13,8 @@ impl MermaidExporter {
         match diagram_type {
             DiagramType::SystemContext => "C4Context",
             DiagramType::Container => "C4Container",
+            DiagramType::Component => "C4Component",
+            DiagramType::Dynamic => "C4Dynamic",
         }
     }
 
@@ -20,10 +22,26 @@ impl MermaidExporter {
         s.replace('"', "\\\"").replace('\n', " ")
     }
 
+    /// `UpdateElementStyle` giving one element its own colours, if it has any
+    fn generate_element_style(&self, element: &crate::model::Element) -> Option<String> {
+        if element.is_note() {
+            return None;
+        }
+        let style = &element.style;
+        let colors: Vec<String> = [("bgColor", style.background), ("fontColor", style.text), ("borderColor", style.border)]
+            .into_iter()
+            .filter_map(|(parameter, color)| color.map(|color| format!("${}=\"{}\"", parameter, color.to_hex())))
+            .collect();
+        if colors.is_empty() {
+            return None;
+        }
+        Some(format!("    UpdateElementStyle({}, {})", element.export_alias(), colors.join(", ")))
+    }
+
     fn generate_element(&self, element: &crate::model::Element) -> String {
         let name = self.escape_string(element.name());
         let description = self.escape_string(element.description());
-        let id = format!("elem_{}", element.id.simple());
+        let id = element.export_alias();
 
         match &element.element_type {
             ElementType::Person(data) => {
@@ -39,51 +57,97 @@ impl MermaidExporter {
                     )
                 }
             }
-            ElementType::SoftwareSystem(data) => {
-                if data.is_external {
+            ElementType::SoftwareSystem(data) => format!(
+                "    {}({}, \"{}\", \"{}\")",
+                data.macro_name(), id, name, description
+            ),
+            ElementType::Container(data) => {
+                let technology = self.escape_string(&data.technology);
+                if technology.is_empty() {
                     format!(
-                        "    System_Ext({}, \"{}\", \"{}\")",
+                        "    Container({}, \"{}\", \"{}\")",
                         id, name, description
                     )
                 } else {
                     format!(
-                        "    System({}, \"{}\", \"{}\")",
-                        id, name, description
+                        "    Container({}, \"{}\", \"{}\", \"{}\")",
+                        id, name, description, technology
                     )
                 }
             }
-            ElementType::Container(data) => {
+            ElementType::Component(data) => {
                 let technology = self.escape_string(&data.technology);
                 if technology.is_empty() {
-                    format!(
-                        "    Container({}, \"{}\", \"{}\")",
-                        id, name, description
-                    )
+                    format!("    Component({}, \"{}\", \"{}\")", id, name, description)
                 } else {
                     format!(
-                        "    Container({}, \"{}\", \"{}\", \"{}\")",
+                        "    Component({}, \"{}\", \"{}\", \"{}\")",
                         id, name, description, technology
                     )
                 }
             }
+            // Mermaid C4 has no notes, so one is kept as a comment
+            ElementType::Note(_) => format!("    %% Note: {}", name),
         }
     }
 
-    fn generate_relationship(&self, rel: &crate::model::Relationship) -> String {
-        let source_id = format!("elem_{}", rel.source_id.simple());
-        let target_id = format!("elem_{}", rel.target_id.simple());
+    /// An element, or a boundary around its parts when it has any in the diagram
+    fn generate_element_tree(&self, diagram: &Diagram, element: &crate::model::Element, depth: usize) -> String {
+        let indent = "    ".repeat(depth);
+        let children = diagram.children_of(element.id);
+        if children.is_empty() {
+            return format!("{}{}\n", indent, self.generate_element(element));
+        }
+        let boundary = match element.element_type {
+            ElementType::SoftwareSystem(_) => "System_Boundary",
+            _ => "Container_Boundary",
+        };
+        let mut output = format!(
+            "{}    {}({}, \"{}\") {{\n",
+            indent,
+            boundary,
+            element.export_alias(),
+            self.escape_string(element.name())
+        );
+        for child in children {
+            output.push_str(&self.generate_element_tree(diagram, child, depth + 1));
+        }
+        output.push_str(&format!("{}    }}\n", indent));
+        output
+    }
+
+    fn generate_relationship(&self, diagram: &Diagram, rel: &crate::model::Relationship) -> String {
+        self.generate_relationship_at(diagram, rel, None)
+    }
+
+    /// A relationship, as `RelIndex` with its step number on Dynamic diagrams
+    fn generate_relationship_at(
+        &self,
+        diagram: &Diagram,
+        rel: &crate::model::Relationship,
+        step: Option<u32>,
+    ) -> String {
+        let source_id = match step {
+            Some(step) => format!("{}, {}", step, diagram.export_alias(rel.source_id)),
+            None => diagram.export_alias(rel.source_id),
+        };
+        let target_id = diagram.export_alias(rel.target_id);
         let description = self.escape_string(&rel.description);
+        let macro_name = match step {
+            Some(_) => "RelIndex",
+            None => rel.direction.macro_name(),
+        };
 
         if let Some(tech) = &rel.technology {
             let technology = self.escape_string(tech);
             format!(
-                "    BiRel({}, {}, \"{}\", \"{}\")",
-                source_id, target_id, description, technology
+                "    {}({}, {}, \"{}\", \"{}\")",
+                macro_name, source_id, target_id, description, technology
             )
         } else {
             format!(
-                "    BiRel({}, {}, \"{}\")",
-                source_id, target_id, description
+                "    {}({}, {}, \"{}\")",
+                macro_name, source_id, target_id, description
             )
         }
     }
@@ -97,6 +161,7 @@ impl Default for MermaidExporter {
 
 impl DiagramExporter for MermaidExporter {
     fn export(&self, diagram: &Diagram) -> String {
+        let diagram = &*diagram.in_active_view();
         let diagram_keyword = self.get_diagram_keyword(diagram.diagram_type);
         let mut output = String::new();
 
@@ -118,21 +183,47 @@ impl DiagramExporter for MermaidExporter {
                 self.escape_string(&diagram.description)
             ));
         }
+        for line in diagram.metadata.header_lines() {
+            output.push_str(&format!("    %% {}\n", line));
+        }
 
         output.push('\n');
 
-        // Elements
-        for element in diagram.elements.values() {
-            output.push_str(&self.generate_element(element));
-            output.push('\n');
+        // Elements; systems and containers with parts become boundaries around them
+        for element in diagram.top_level() {
+            output.push_str(&self.generate_element_tree(diagram, element, 0));
         }
 
         output.push('\n');
 
-        // Relationships
-        for rel in &diagram.relationships {
-            output.push_str(&self.generate_relationship(rel));
+        // Relationships, less any to a note; a Dynamic diagram lists them as numbered steps
+        let to_note = |rel: &crate::model::Relationship| {
+            [rel.source_id, rel.target_id]
+                .iter()
+                .any(|id| diagram.elements.get(id).is_some_and(|e| e.is_note()))
+        };
+        if diagram.diagram_type == DiagramType::Dynamic {
+            for (step, rel) in diagram.steps().into_iter().filter(|(_, rel)| !to_note(rel)) {
+                output.push_str(&self.generate_relationship_at(diagram, rel, Some(step)));
+                output.push('\n');
+            }
+        } else {
+            for rel in diagram.relationships.iter().filter(|rel| !to_note(rel)) {
+                output.push_str(&self.generate_relationship(diagram, rel));
+                output.push('\n');
+            }
+        }
+
+        // Colours chosen for single elements; Mermaid has no border styles
+        let mut styled: Vec<&crate::model::Element> = diagram.elements.values().collect();
+        styled.sort_by_key(|e| (e.name(), e.id));
+        let styles: Vec<String> = styled.into_iter().filter_map(|e| self.generate_element_style(e)).collect();
+        if !styles.is_empty() {
             output.push('\n');
+            for style in styles {
+                output.push_str(&style);
+                output.push('\n');
+            }
         }
 
         output
@@ -146,7 +237,8 @@ impl DiagramExporter for MermaidExporter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::{ContainerType, Diagram, DiagramType, Element, ElementId, ElementType, Position, Relationship};
+    use crate::model::{ContainerType, Diagram, DiagramType, Direction, Element, ElementId, ElementType, Position, Relationship, SystemKind};
+    use crate::style::{Color, ElementStyle};
 
     mod escape_string_tests {
         use super::*;
@@ -251,6 +343,23 @@ mod tests {
             assert!(result.contains("System_Ext("));
         }
 
+        /// Verifies database and queue systems use the SystemDb and SystemQueue macros
+        #[test]
+        fn generate_element_system_kinds() {
+            let exporter = MermaidExporter::new();
+            let mut element = Element::new(ElementType::external_system("Ledger", "Books"), Position::new(0.0, 0.0));
+            if let ElementType::SoftwareSystem(data) = &mut element.element_type {
+                data.kind = SystemKind::Database;
+            }
+            assert!(exporter.generate_element(&element).contains("SystemDb_Ext("));
+
+            if let ElementType::SoftwareSystem(data) = &mut element.element_type {
+                data.kind = SystemKind::Queue;
+                data.is_external = false;
+            }
+            assert!(exporter.generate_element(&element).contains("SystemQueue("));
+        }
+
         /// Verifies generate_element creates correct output for container
         #[test]
         fn generate_element_container() {
@@ -309,8 +418,8 @@ mod tests {
             let target_id = ElementId::new_v4();
             let rel = Relationship::new(source_id, target_id, "uses");
 
-            let result = exporter.generate_relationship(&rel);
-            assert!(result.contains("BiRel("));
+            let result = exporter.generate_relationship(&Diagram::default(), &rel);
+            assert!(result.starts_with("    Rel("));
             assert!(result.contains("uses"));
             assert!(!result.contains("\", \""));
         }
@@ -323,12 +432,26 @@ mod tests {
             let target_id = ElementId::new_v4();
             let rel = Relationship::with_technology(source_id, target_id, "uses", "HTTPS");
 
-            let result = exporter.generate_relationship(&rel);
-            assert!(result.contains("BiRel("));
+            let result = exporter.generate_relationship(&Diagram::default(), &rel);
+            assert!(result.starts_with("    Rel("));
             assert!(result.contains("uses"));
             assert!(result.contains("HTTPS"));
         }
 
+        /// Verifies each direction uses its own macro
+        #[test]
+        fn generate_relationship_direction() {
+            let exporter = MermaidExporter::new();
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "syncs");
+            assert!(exporter.generate_relationship(&Diagram::default(), &rel).starts_with("    Rel("));
+
+            rel.direction = Direction::BiDirectional;
+            assert!(exporter.generate_relationship(&Diagram::default(), &rel).starts_with("    BiRel("));
+
+            rel.direction = Direction::Back;
+            assert!(exporter.generate_relationship(&Diagram::default(), &rel).starts_with("    Rel_Back("));
+        }
+
         /// Verifies generate_relationship uses proper indentation
         #[test]
         fn generate_relationship_uses_proper_indentation() {
@@ -337,7 +460,7 @@ mod tests {
             let target_id = ElementId::new_v4();
             let rel = Relationship::new(source_id, target_id, "uses");
 
-            let result = exporter.generate_relationship(&rel);
+            let result = exporter.generate_relationship(&Diagram::default(), &rel);
             assert!(result.starts_with("    ")); // 4 spaces indent
         }
     }
@@ -345,6 +468,34 @@ mod tests {
     mod export_tests {
         use super::*;
 
+        /// Verifies assigned aliases replace UUIDs in elements, boundaries and relationships
+        #[test]
+        fn export_uses_aliases() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::Container);
+            let user = Element::new(ElementType::person("Customer", ""), Position::new(0.0, 0.0));
+            let shop = Element::new(ElementType::system("Web Shop", ""), Position::new(300.0, 0.0));
+            let mut api = Element::new(
+                ElementType::container("API", "", ContainerType::Microservice, ""),
+                Position::new(300.0, 100.0),
+            );
+            if let ElementType::Container(data) = &mut api.element_type {
+                data.system = Some(shop.id);
+            }
+            let orders = Relationship::new(user.id, api.id, "Orders");
+            diagram.add_element(user);
+            diagram.add_element(shop);
+            diagram.add_element(api);
+            diagram.add_relationship(orders);
+            diagram.assign_aliases();
+
+            let result = MermaidExporter::new().export(&diagram);
+            assert!(result.contains("System_Boundary(web_shop, \"Web Shop\")"));
+            assert!(result.contains("Container(api, \"API\""));
+            assert!(result.contains("Rel(customer, api, \"Orders\")"));
+            assert!(!result.contains("elem_"));
+            assert_eq!(result, MermaidExporter::new().export(&diagram.clone()));
+        }
+
         /// Verifies export produces valid Mermaid output
         #[test]
         fn export_produces_valid_mermaid() {
@@ -377,6 +528,114 @@ mod tests {
             assert!(!result.contains("C4Context"));
         }
 
+        /// Verifies Component diagrams use the C4Component keyword and declare components
+        #[test]
+        fn export_component_diagram() {
+            let exporter = MermaidExporter::new();
+            let mut diagram = Diagram::new("Test", "", DiagramType::Component);
+            diagram.add_element(Element::new(
+                ElementType::component("Checkout", "Takes payment", ""),
+                Position::new(0.0, 0.0),
+            ));
+
+            let result = exporter.export(&diagram);
+            assert!(result.starts_with("C4Component"));
+            assert!(result.contains("\"Checkout\", \"Takes payment\")"));
+        }
+
+        /// Verifies an element's own colours are applied with UpdateElementStyle
+        #[test]
+        fn export_element_style() {
+            let exporter = MermaidExporter::new();
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let mut element = Element::new(ElementType::system("Legacy", ""), Position::new(0.0, 0.0));
+            element.style = ElementStyle {
+                background: Some(Color::rgb(255, 0, 0)),
+                text: Some(Color::gray(255)),
+                ..ElementStyle::default()
+            };
+            let id = element.id;
+            diagram.add_element(element);
+            diagram.add_element(Element::new(ElementType::person("User", ""), Position::new(0.0, 0.0)));
+
+            let result = exporter.export(&diagram);
+            assert!(result.ends_with(&format!(
+                "\n    UpdateElementStyle(elem_{}, $bgColor=\"#ff0000\", $fontColor=\"#ffffff\")\n",
+                id.simple()
+            )));
+            assert_eq!(result.matches("UpdateElementStyle").count(), 1);
+        }
+
+        /// Verifies parts are nested in boundaries of the system and container they belong to
+        #[test]
+        fn export_groups_parts_in_boundaries() {
+            let exporter = MermaidExporter::new();
+            let mut diagram = Diagram::new("Shop", "", DiagramType::Component);
+            let shop = Element::new(ElementType::system("Shop", ""), Position::new(0.0, 0.0));
+            let mut api = ElementType::container("API", "", ContainerType::Microservice, "");
+            if let ElementType::Container(data) = &mut api {
+                data.system = Some(shop.id);
+            }
+            let api = Element::new(api, Position::new(0.0, 0.0));
+            let mut checkout = ElementType::component("Checkout", "", "");
+            if let ElementType::Component(data) = &mut checkout {
+                data.container = Some(api.id);
+            }
+            let (shop_id, api_id) = (shop.id, api.id);
+            diagram.add_element(shop);
+            diagram.add_element(api);
+            diagram.add_element(Element::new(checkout, Position::new(0.0, 0.0)));
+
+            let result = exporter.export(&diagram);
+            assert!(result.contains(&format!("    System_Boundary(elem_{}, \"Shop\") {{\n", shop_id.simple())));
+            assert!(result.contains(&format!("        Container_Boundary(elem_{}, \"API\") {{\n", api_id.simple())));
+            assert!(result.contains("            Component(elem_"));
+            assert!(result.contains("        }\n    }\n"));
+            assert!(!result.contains("System(elem_"));
+        }
+
+        /// Verifies notes are kept as comments, without their relationships or colours
+        #[test]
+        fn export_note_as_comment() {
+            let exporter = MermaidExporter::new();
+            let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+            let mut note = Element::new(ElementType::note("Legacy\nto be replaced"), Position::new(0.0, 0.0));
+            note.style.background = Some(Color::rgb(255, 255, 0));
+            let shop = Element::new(ElementType::system("Shop", ""), Position::new(0.0, 0.0));
+            let (note_id, shop_id) = (note.id, shop.id);
+            diagram.add_element(note);
+            diagram.add_element(shop);
+            diagram.add_relationship(Relationship::new(note_id, shop_id, ""));
+
+            let result = exporter.export(&diagram);
+            assert!(result.contains("    %% Note: Legacy to be replaced\n"));
+            assert!(!result.contains(&format!("elem_{}", note_id.simple())));
+            assert!(!result.contains("Rel("));
+        }
+
+        /// Verifies Dynamic diagrams list relationships as numbered steps in order
+        #[test]
+        fn export_dynamic_diagram() {
+            let exporter = MermaidExporter::new();
+            let mut diagram = Diagram::new("Sign in", "", DiagramType::Dynamic);
+            let user = Element::new(ElementType::person("User", ""), Position::new(0.0, 0.0));
+            let app = Element::new(ElementType::system("App", ""), Position::new(200.0, 0.0));
+            let (user_id, app_id) = (user.id, app.id);
+            diagram.add_element(user);
+            diagram.add_element(app);
+            diagram.add_relationship(Relationship::new(user_id, app_id, "Submits credentials"));
+            diagram.add_relationship(Relationship::new(app_id, user_id, "Returns a session"));
+            diagram.relationships.swap(0, 1);
+
+            let result = exporter.export(&diagram);
+            assert!(result.starts_with("C4Dynamic"));
+            let first = result
+                .find(&format!("RelIndex(1, elem_{}, elem_{}, \"Submits credentials\")", user_id.simple(), app_id.simple()))
+                .unwrap();
+            let second = result.find("RelIndex(2, ").unwrap();
+            assert!(first < second);
+        }
+
         /// Verifies export handles empty diagrams
         #[test]
         fn export_handles_empty_diagram() {
@@ -409,7 +668,8 @@ mod tests {
             diagram.add_relationship(Relationship::new(source_id, target_id, "uses"));
 
             let result = exporter.export(&diagram);
-            assert!(result.contains("BiRel("));
+            assert!(result.contains("    Rel("));
+            assert!(!result.contains("BiRel("));
             assert!(result.contains("uses"));
         }
 
@@ -438,6 +698,16 @@ mod tests {
             let result = exporter.export(&diagram);
             assert!(result.contains("%% A description"));
         }
+
+        /// Verifies diagram metadata is written as comments in the header
+        #[test]
+        fn export_includes_metadata_comments() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            diagram.metadata.modified = Some("2024-02-29T13:45:30Z".to_string());
+
+            let result = MermaidExporter::new().export(&diagram);
+            assert!(result.contains("    title Test\n    %% Modified: 2024-02-29T13:45:30Z\n"));
+        }
     }
 
 }
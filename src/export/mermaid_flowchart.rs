@@ -0,0 +1,432 @@
+use crate::model::{ContainerType, Diagram, Direction, Element, ElementType, Relationship, SystemKind};
+use super::DiagramExporter;
+
+/// Exports diagrams to a plain Mermaid `flowchart` for renderers that do not
+/// support the experimental C4 syntax
+pub struct MermaidFlowchartExporter;
+
+/// Subgraphs emitted by the flowchart exporter, in output order
+const GROUPS: [(&str, &str); 5] = [
+    ("people", "People"),
+    ("systems", "Software Systems"),
+    ("containers", "Containers"),
+    ("components", "Components"),
+    ("notes", "Notes"),
+];
+
+/// Class definitions approximating the C4-PlantUML palette
+const CLASS_DEFS: [(&str, &str); 9] = [
+    ("person", "fill:#08427b,stroke:#073b6f,color:#fff"),
+    ("external_person", "fill:#686868,stroke:#4d4d4d,color:#fff"),
+    ("system", "fill:#1168bd,stroke:#0b4884,color:#fff"),
+    ("external_system", "fill:#999999,stroke:#8a8a8a,color:#fff"),
+    ("container", "fill:#438dd5,stroke:#3c7fc0,color:#fff"),
+    ("database", "fill:#438dd5,stroke:#3c7fc0,color:#fff"),
+    ("queue", "fill:#438dd5,stroke:#3c7fc0,color:#fff"),
+    ("component", "fill:#85bbf0,stroke:#78a8d8,color:#000"),
+    ("note", "fill:#fff59d,stroke:#c8b900,color:#000"),
+];
+
+impl MermaidFlowchartExporter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn escape_string(&self, s: &str) -> String {
+        s.replace('"', "#quot;").replace('\n', "<br/>")
+    }
+
+    /// Subgraph an element belongs to
+    fn group_for(&self, element: &Element) -> &'static str {
+        match &element.element_type {
+            ElementType::Person(_) => "people",
+            ElementType::SoftwareSystem(_) => "systems",
+            ElementType::Container(_) => "containers",
+            ElementType::Component(_) => "components",
+            ElementType::Note(_) => "notes",
+        }
+    }
+
+    /// Style class applied to an element
+    fn class_for(&self, element: &Element) -> &'static str {
+        match &element.element_type {
+            ElementType::Person(data) => {
+                if data.is_external {
+                    "external_person"
+                } else {
+                    "person"
+                }
+            }
+            ElementType::SoftwareSystem(data) => {
+                if data.is_external {
+                    "external_system"
+                } else {
+                    "system"
+                }
+            }
+            ElementType::Container(data) => match data.container_type {
+                ContainerType::Database => "database",
+                ContainerType::Queue => "queue",
+                _ => "container",
+            },
+            ElementType::Component(_) => "component",
+            ElementType::Note(_) => "note",
+        }
+    }
+
+    fn generate_element(&self, element: &Element) -> String {
+        let id = element.export_alias();
+        let name = self.escape_string(element.name());
+        let description = self.escape_string(element.description());
+
+        let stereotype = match &element.element_type {
+            ElementType::Container(data) if !data.technology.is_empty() => {
+                format!("[Container: {}]", self.escape_string(&data.technology))
+            }
+            ElementType::Component(data) if !data.technology.is_empty() => {
+                format!("[Component: {}]", self.escape_string(&data.technology))
+            }
+            other => format!("[{}]", other.type_name()),
+        };
+
+        let label = if element.is_note() {
+            name
+        } else if description.is_empty() {
+            format!("<b>{}</b><br/>{}", name, stereotype)
+        } else {
+            format!("<b>{}</b><br/>{}<br/>{}", name, stereotype, description)
+        };
+
+        // Databases get the cylinder shape, everything else a rounded box
+        let shape = match &element.element_type {
+            ElementType::Container(data) if matches!(data.container_type, ContainerType::Database) => {
+                format!("[(\"{}\")]", label)
+            }
+            ElementType::SoftwareSystem(data) if data.kind == SystemKind::Database => {
+                format!("[(\"{}\")]", label)
+            }
+            ElementType::Person(_) => format!("([\"{}\"])", label),
+            ElementType::Note(_) => format!("[\"{}\"]", label),
+            _ => format!("(\"{}\")", label),
+        };
+
+        format!("        {}{}:::{}", id, shape, self.class_for(element))
+    }
+
+    fn generate_relationship(&self, diagram: &Diagram, rel: &Relationship) -> String {
+        let source_id = diagram.export_alias(rel.source_id);
+        let target_id = diagram.export_alias(rel.target_id);
+        let description = self.escape_string(&rel.description);
+
+        let label = match &rel.technology {
+            Some(tech) if !tech.is_empty() => {
+                format!("{}<br/>[{}]", description, self.escape_string(tech))
+            }
+            _ => description,
+        };
+
+        // Flowcharts have no left-pointing arrow, so a back arrow is drawn from the target
+        let (source_id, arrow, target_id) = match rel.direction {
+            Direction::Forward => (source_id, "-->", target_id),
+            Direction::Back => (target_id, "-->", source_id),
+            Direction::BiDirectional => (source_id, "<-->", target_id),
+        };
+        if label.is_empty() {
+            format!("    {} {} {}", source_id, arrow, target_id)
+        } else {
+            format!("    {} {}|\"{}\"| {}", source_id, arrow, label, target_id)
+        }
+    }
+}
+
+impl Default for MermaidFlowchartExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiagramExporter for MermaidFlowchartExporter {
+    fn export(&self, diagram: &Diagram) -> String {
+        let diagram = &*diagram.in_active_view();
+        let mut output = String::new();
+
+        // Header
+        output.push_str("flowchart TD\n");
+
+        // Title (flowcharts have no title keyword, so use a comment)
+        if !diagram.name.is_empty() {
+            output.push_str(&format!(
+                "    %% {}\n",
+                diagram.name.replace('\n', " ")
+            ));
+        }
+
+        // Description
+        if !diagram.description.is_empty() {
+            output.push_str(&format!(
+                "    %% {}\n",
+                diagram.description.replace('\n', " ")
+            ));
+        }
+        for line in diagram.metadata.header_lines() {
+            output.push_str(&format!("    %% {}\n", line));
+        }
+
+        output.push('\n');
+
+        // Class definitions
+        for (class, style) in CLASS_DEFS {
+            output.push_str(&format!("    classDef {} {}\n", class, style));
+        }
+
+        output.push('\n');
+
+        // Elements, grouped into one subgraph per element kind
+        for (group, title) in GROUPS {
+            let members: Vec<&Element> = diagram
+                .elements
+                .values()
+                .filter(|e| self.group_for(e) == group)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+
+            output.push_str(&format!("    subgraph {}[\"{}\"]\n", group, title));
+            for element in members {
+                output.push_str(&self.generate_element(element));
+                output.push('\n');
+            }
+            output.push_str("    end\n");
+        }
+
+        output.push('\n');
+
+        // Relationships
+        for rel in &diagram.relationships {
+            output.push_str(&self.generate_relationship(diagram, rel));
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "mmd"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContainerType, Diagram, DiagramType, Element, ElementId, ElementType, Position, Relationship};
+
+    mod escape_string_tests {
+        use super::*;
+
+        /// Verifies escape_string replaces double quotes with the Mermaid entity
+        #[test]
+        fn escape_string_escapes_quotes() {
+            let exporter = MermaidFlowchartExporter::new();
+            let result = exporter.escape_string(r#"This has "quotes" in it"#);
+            assert_eq!(result, "This has #quot;quotes#quot; in it");
+        }
+
+        /// Verifies escape_string turns newlines into line breaks
+        #[test]
+        fn escape_string_replaces_newlines() {
+            let exporter = MermaidFlowchartExporter::new();
+            let result = exporter.escape_string("Line1\nLine2");
+            assert_eq!(result, "Line1<br/>Line2");
+        }
+    }
+
+    mod generate_element_tests {
+        use super::*;
+
+        /// Verifies generate_element emits a stadium node with the person class
+        #[test]
+        fn generate_element_person() {
+            let exporter = MermaidFlowchartExporter::new();
+            let element = Element::new(
+                ElementType::person("User", "A user"),
+                Position::new(0.0, 0.0),
+            );
+            let id = format!("elem_{}", element.id.simple());
+
+            let result = exporter.generate_element(&element);
+            assert!(result.contains(&format!("{}([", id)));
+            assert!(result.contains("<b>User</b>"));
+            assert!(result.contains("[Person]"));
+            assert!(result.contains("A user"));
+            assert!(result.ends_with(":::person"));
+        }
+
+        /// Verifies generate_element uses the external classes for external elements
+        #[test]
+        fn generate_element_external_classes() {
+            let exporter = MermaidFlowchartExporter::new();
+            let person = Element::new(
+                ElementType::external_person("Partner", ""),
+                Position::new(0.0, 0.0),
+            );
+            let system = Element::new(
+                ElementType::external_system("Payments", ""),
+                Position::new(0.0, 0.0),
+            );
+
+            assert!(exporter.generate_element(&person).ends_with(":::external_person"));
+            assert!(exporter.generate_element(&system).ends_with(":::external_system"));
+        }
+
+        /// Verifies generate_element includes container technology in the stereotype
+        #[test]
+        fn generate_element_container_technology() {
+            let exporter = MermaidFlowchartExporter::new();
+            let element = Element::new(
+                ElementType::container("WebApp", "A web app", ContainerType::WebApplication, "React"),
+                Position::new(0.0, 0.0),
+            );
+
+            let result = exporter.generate_element(&element);
+            assert!(result.contains("[Container: React]"));
+            assert!(result.ends_with(":::container"));
+        }
+
+        /// Verifies generate_element draws databases as cylinders
+        #[test]
+        fn generate_element_database_cylinder() {
+            let exporter = MermaidFlowchartExporter::new();
+            let element = Element::new(
+                ElementType::container("DB", "Stores data", ContainerType::Database, "PostgreSQL"),
+                Position::new(0.0, 0.0),
+            );
+
+            let result = exporter.generate_element(&element);
+            assert!(result.contains("[(\""));
+            assert!(result.contains("\")]"));
+            assert!(result.ends_with(":::database"));
+        }
+    }
+
+    mod generate_relationship_tests {
+        use super::*;
+
+        /// Verifies generate_relationship emits a labelled directed edge
+        #[test]
+        fn generate_relationship_without_technology() {
+            let exporter = MermaidFlowchartExporter::new();
+            let rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "uses");
+
+            let result = exporter.generate_relationship(&Diagram::default(), &rel);
+            assert!(result.contains("-->|\"uses\"|"));
+        }
+
+        /// Verifies generate_relationship appends the technology to the label
+        #[test]
+        fn generate_relationship_with_technology() {
+            let exporter = MermaidFlowchartExporter::new();
+            let rel = Relationship::with_technology(ElementId::new_v4(), ElementId::new_v4(), "uses", "HTTPS");
+
+            let result = exporter.generate_relationship(&Diagram::default(), &rel);
+            assert!(result.contains("uses<br/>[HTTPS]"));
+        }
+
+        /// Verifies generate_relationship omits the label when there is no text
+        #[test]
+        fn generate_relationship_empty_description() {
+            let exporter = MermaidFlowchartExporter::new();
+            let rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "");
+
+            let result = exporter.generate_relationship(&Diagram::default(), &rel);
+            assert!(result.contains(" --> "));
+            assert!(!result.contains('|'));
+        }
+
+        /// Verifies bidirectional relationships get arrowheads at both ends
+        #[test]
+        fn generate_relationship_bidirectional() {
+            let exporter = MermaidFlowchartExporter::new();
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "syncs");
+            rel.direction = Direction::BiDirectional;
+
+            let result = exporter.generate_relationship(&Diagram::default(), &rel);
+            assert!(result.contains("<-->|\"syncs\"|"));
+        }
+
+        /// Verifies back relationships are drawn from the target
+        #[test]
+        fn generate_relationship_back() {
+            let exporter = MermaidFlowchartExporter::new();
+            let (source, target) = (ElementId::new_v4(), ElementId::new_v4());
+            let mut rel = Relationship::new(source, target, "notifies");
+            rel.direction = Direction::Back;
+
+            let result = exporter.generate_relationship(&Diagram::default(), &rel);
+            assert!(result.starts_with(&format!("    elem_{} -->", target.simple())));
+            assert!(result.ends_with(&format!("elem_{}", source.simple())));
+        }
+    }
+
+    mod export_tests {
+        use super::*;
+
+        /// Verifies export produces a flowchart with class definitions and subgraphs
+        #[test]
+        fn export_produces_flowchart() {
+            let exporter = MermaidFlowchartExporter::new();
+            let mut diagram = Diagram::new("Test Diagram", "Test Description", DiagramType::SystemContext);
+
+            let source = Element::new(
+                ElementType::person("User", "A user"),
+                Position::new(0.0, 0.0),
+            );
+            let target = Element::new(
+                ElementType::system("System", "A system"),
+                Position::new(100.0, 0.0),
+            );
+            let source_id = source.id;
+            let target_id = target.id;
+            diagram.add_element(source);
+            diagram.add_element(target);
+            diagram.add_relationship(Relationship::new(source_id, target_id, "uses"));
+
+            let result = exporter.export(&diagram);
+            assert!(result.starts_with("flowchart TD"));
+            assert!(result.contains("%% Test Diagram"));
+            assert!(result.contains("%% Test Description"));
+            assert!(result.contains("classDef person"));
+            assert!(result.contains("subgraph people[\"People\"]"));
+            assert!(result.contains("subgraph systems[\"Software Systems\"]"));
+            assert!(!result.contains("subgraph containers"));
+            assert!(result.contains("-->|\"uses\"|"));
+        }
+
+        /// Verifies export handles empty diagrams
+        #[test]
+        fn export_handles_empty_diagram() {
+            let exporter = MermaidFlowchartExporter::new();
+            let diagram = Diagram::new("Empty", "", DiagramType::Container);
+
+            let result = exporter.export(&diagram);
+            assert!(result.starts_with("flowchart TD"));
+            assert!(!result.contains("subgraph"));
+        }
+
+        /// Verifies export does not use any C4 syntax
+        #[test]
+        fn export_avoids_c4_syntax() {
+            let exporter = MermaidFlowchartExporter::new();
+            let mut diagram = Diagram::new("Test", "", DiagramType::Container);
+            diagram.add_element(Element::new(
+                ElementType::container("Queue", "Events", ContainerType::Queue, "Kafka"),
+                Position::new(0.0, 0.0),
+            ));
+
+            let result = exporter.export(&diagram);
+            assert!(!result.contains("C4Container"));
+            assert!(!result.contains("ContainerQueue("));
+            assert!(result.contains(":::queue"));
+        }
+    }
+}
@@ -1,10 +1,60 @@
+pub mod adr;
+pub mod docs;
+pub mod embed;
+pub mod highlight;
 pub mod mermaid;
+pub mod mermaid_flowchart;
 pub mod plantuml;
+pub mod poster;
+pub mod render;
+pub mod structurizr;
+pub mod svg;
 
 pub use mermaid::MermaidExporter;
-pub use plantuml::PlantUmlExporter;
+pub use mermaid_flowchart::MermaidFlowchartExporter;
+pub use plantuml::{PlantUmlExporter, PlantUmlOptions};
+pub use structurizr::StructurizrExporter;
+pub use svg::SvgExporter;
 
 use crate::model::Diagram;
+use render::RenderSource;
+
+/// Text formats shown in the export window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    PlantUml,
+    Mermaid,
+    MermaidFlowchart,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::PlantUml, ExportFormat::Mermaid, ExportFormat::MermaidFlowchart];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::PlantUml => "C4-PlantUML",
+            ExportFormat::Mermaid => "Mermaid",
+            ExportFormat::MermaidFlowchart => "Mermaid Flowchart",
+        }
+    }
+
+    /// Tool that can render this format to an image
+    pub fn render_source(&self) -> RenderSource {
+        match self {
+            ExportFormat::PlantUml => RenderSource::PlantUml,
+            ExportFormat::Mermaid | ExportFormat::MermaidFlowchart => RenderSource::Mermaid,
+        }
+    }
+
+    /// Export `diagram` in this format
+    pub fn export(&self, diagram: &Diagram, plantuml_options: &PlantUmlOptions) -> String {
+        match self {
+            ExportFormat::PlantUml => PlantUmlExporter::with_options(plantuml_options.clone()).export(diagram),
+            ExportFormat::Mermaid => MermaidExporter::new().export(diagram),
+            ExportFormat::MermaidFlowchart => MermaidFlowchartExporter::new().export(diagram),
+        }
+    }
+}
 
 /// Trait for diagram exporters
 pub trait DiagramExporter {
@@ -13,6 +63,29 @@ pub trait DiagramExporter {
 
     /// Get the file extension for this format
     fn file_extension(&self) -> &'static str;
+
+    /// Export a diagram and write the result to a file
+    fn export_to_file(&self, diagram: &Diagram, path: &std::path::Path) -> crate::Result<()> {
+        std::fs::write(path, self.export(diagram)).map_err(|e| crate::C2DrawError::file(path, e))
+    }
+}
+
+/// Write the diagram next to its file in each exporter's format
+///
+/// `arch.c4d` gets `arch.puml`, `arch.mmd` and so on, overwriting earlier
+/// copies. Returns the files written.
+pub fn write_sidecar_files(
+    diagram: &Diagram,
+    diagram_path: &std::path::Path,
+    exporters: &[&dyn DiagramExporter],
+) -> crate::Result<Vec<std::path::PathBuf>> {
+    let mut written = Vec::new();
+    for exporter in exporters {
+        let path = diagram_path.with_extension(exporter.file_extension());
+        exporter.export_to_file(diagram, &path)?;
+        written.push(path);
+    }
+    Ok(written)
 }
 
 #[cfg(test)]
@@ -83,6 +156,41 @@ mod tests {
             assert_eq!(exporter.file_extension(), "mmd");
         }
 
+        /// Verifies MermaidFlowchartExporter implements the trait correctly
+        #[test]
+        fn mermaid_flowchart_exporter_implements_trait() {
+            let exporter = MermaidFlowchartExporter::new();
+            let diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+
+            let output = exporter.export(&diagram);
+            assert!(!output.is_empty());
+            assert_eq!(exporter.file_extension(), "mmd");
+        }
+
+        /// Verifies export_to_file writes the export output to disk
+        #[test]
+        fn export_to_file_writes_output() {
+            let exporter = TestExporter::new();
+            let diagram = Diagram::new("On Disk", "", DiagramType::SystemContext);
+            let path = std::env::temp_dir().join(format!("c2draw-{}.test", uuid::Uuid::new_v4()));
+
+            exporter.export_to_file(&diagram, &path).expect("Failed to export");
+            let written = std::fs::read_to_string(&path).unwrap();
+            let _ = std::fs::remove_file(&path);
+            assert_eq!(written, "Test export of: On Disk");
+        }
+
+        /// Verifies export_to_file reports the path on failure
+        #[test]
+        fn export_to_file_reports_path_on_failure() {
+            let exporter = TestExporter::new();
+            let diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let path = std::path::Path::new("/nonexistent-c2draw-dir/out.test");
+
+            let err = exporter.export_to_file(&diagram, path).unwrap_err();
+            assert!(err.to_string().contains("/nonexistent-c2draw-dir/out.test"));
+        }
+
         /// Verifies export produces non-empty output for diagrams with elements
         #[test]
         fn export_produces_output_with_elements() {
@@ -99,4 +207,50 @@ mod tests {
         }
 
     }
+
+    mod sidecar_tests {
+        use super::*;
+
+        /// Verifies side-car files are named after the diagram file
+        #[test]
+        fn write_sidecar_files_next_to_diagram() {
+            let dir = std::env::temp_dir().join(format!("c2draw-sidecar-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let diagram = Diagram::new("Sidecar", "", DiagramType::SystemContext);
+            let plantuml = PlantUmlExporter::new();
+            let mermaid = MermaidExporter::new();
+
+            let written = write_sidecar_files(&diagram, &dir.join("arch.c4dz"), &[&plantuml, &mermaid]).unwrap();
+            let puml = std::fs::read_to_string(dir.join("arch.puml")).unwrap();
+            let _ = std::fs::remove_dir_all(&dir);
+
+            assert_eq!(written, vec![dir.join("arch.puml"), dir.join("arch.mmd")]);
+            assert_eq!(puml, plantuml.export(&diagram));
+        }
+
+        /// Verifies nothing is written without exporters
+        #[test]
+        fn write_sidecar_files_without_exporters() {
+            let diagram = Diagram::new("Sidecar", "", DiagramType::SystemContext);
+            let written = write_sidecar_files(&diagram, std::path::Path::new("/nonexistent/arch.c4d"), &[]).unwrap();
+            assert!(written.is_empty());
+        }
+    }
+    mod export_format_tests {
+        use super::*;
+
+        /// Verifies each format matches its exporter and renderer
+        #[test]
+        fn export_format_uses_matching_exporter() {
+            let diagram = Diagram::new("Formats", "", DiagramType::SystemContext);
+            let options = PlantUmlOptions::default();
+            assert_eq!(ExportFormat::PlantUml.export(&diagram, &options), PlantUmlExporter::new().export(&diagram));
+            assert_eq!(ExportFormat::Mermaid.export(&diagram, &options), MermaidExporter::new().export(&diagram));
+            assert_eq!(
+                ExportFormat::MermaidFlowchart.export(&diagram, &options),
+                MermaidFlowchartExporter::new().export(&diagram)
+            );
+            assert_eq!(ExportFormat::MermaidFlowchart.render_source(), RenderSource::Mermaid);
+        }
+    }
 }
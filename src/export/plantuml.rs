@@ -1,18 +1,67 @@
-use crate::model::{ContainerType, Diagram, DiagramType, ElementType};
+use crate::model::elements::ContainerData;
+use crate::model::{ContainerType, Diagram, DiagramType, Element, ElementType};
+use crate::style::BorderStyle;
 use super::DiagramExporter;
+use std::collections::BTreeSet;
+
+/// Base URL of the public PlantUML server's diagram renderer
+pub const PLANTUML_SERVER_URL: &str = "https://www.plantuml.com/plantuml/uml/";
+
+/// Alphabet used by PlantUML's text encoding (a reordered base64)
+const PLANTUML_ALPHABET: &[u8; 64] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz-_";
+
+/// Encode PlantUML source using the PlantUML text-encoding scheme
+/// (raw deflate followed by PlantUML's base64 variant)
+pub fn encode_plantuml_text(source: &str) -> std::io::Result<String> {
+    let compressed = crate::share::deflate(source.as_bytes())?;
+    let mut out = String::with_capacity(compressed.len().div_ceil(3) * 4);
+    for chunk in compressed.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            let index = (n >> (18 - 6 * i)) & 0x3f;
+            out.push(PLANTUML_ALPHABET[index as usize] as char);
+        }
+    }
+    Ok(out)
+}
+
+/// Optional output for the C4-PlantUML exporter
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlantUmlOptions {
+    /// Emit relationship notes as `note on link` blocks
+    pub include_notes: bool,
+    /// Draw people as outlined figures (`SHOW_PERSON_OUTLINE()`)
+    pub show_person_outline: bool,
+    /// Hide the `<<stereotype>>` labels on elements (`HIDE_STEREOTYPE()`)
+    pub hide_stereotype: bool,
+    /// Add a floating legend (`SHOW_FLOATING_LEGEND()`)
+    pub show_floating_legend: bool,
+    /// Render in a hand-drawn style (`LAYOUT_AS_SKETCH()`)
+    pub sketch_mode: bool,
+}
 
 /// Exports diagrams to C4-PlantUML format
-pub struct PlantUmlExporter;
+pub struct PlantUmlExporter {
+    options: PlantUmlOptions,
+}
 
 impl PlantUmlExporter {
     pub fn new() -> Self {
-        Self
+        Self::with_options(PlantUmlOptions::default())
+    }
+
+    pub fn with_options(options: PlantUmlOptions) -> Self {
+        Self { options }
     }
 
     fn get_include(&self, diagram_type: DiagramType) -> &'static str {
         match diagram_type {
             DiagramType::SystemContext => "C4_Context.puml",
             DiagramType::Container => "C4_Container.puml",
+            DiagramType::Component => "C4_Component.puml",
+            DiagramType::Dynamic => "C4_Dynamic.puml",
         }
     }
 
@@ -20,38 +69,150 @@ impl PlantUmlExporter {
         s.replace('"', "\\\"").replace('\n', " ")
     }
 
+    /// Display directives emitted after the `!include` line
+    fn header_directives(&self) -> Vec<&'static str> {
+        let mut directives = Vec::new();
+        if self.options.show_person_outline {
+            directives.push("SHOW_PERSON_OUTLINE()");
+        }
+        if self.options.hide_stereotype {
+            directives.push("HIDE_STEREOTYPE()");
+        }
+        if self.options.sketch_mode {
+            directives.push("LAYOUT_AS_SKETCH()");
+        }
+        directives
+    }
+
+    /// Trailing `, $tags="a+b"` argument, empty if there are no tags
+    fn tags_argument(&self, tags: &[String]) -> String {
+        let tags: Vec<String> = tags
+            .iter()
+            .map(|tag| self.escape_string(tag.trim()))
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        if tags.is_empty() {
+            String::new()
+        } else {
+            format!(", $tags=\"{}\"", tags.join("+"))
+        }
+    }
+
+    /// Trailing `, $link="..."` argument, empty if the element has no link
+    fn link_argument(&self, element: &Element) -> String {
+        match element.url.as_deref().map(str::trim) {
+            Some(url) if !url.is_empty() => format!(", $link=\"{}\"", self.escape_string(url)),
+            _ => String::new(),
+        }
+    }
+
+    /// Standard library files defining the sprites of all container icons
+    ///
+    /// Grouped by pack, each pack's common file before its sprite files.
+    fn sprite_includes(&self, diagram: &Diagram) -> Vec<&'static str> {
+        let mut includes = BTreeSet::new();
+        for element in diagram.elements.values() {
+            if let ElementType::Container(ContainerData { icon: Some(icon), .. }) = &element.element_type {
+                for (position, include) in icon.includes().into_iter().enumerate() {
+                    includes.insert((icon.pack, position.min(1), include));
+                }
+            }
+        }
+        includes.into_iter().map(|(_, _, include)| include).collect()
+    }
+
+    /// `AddElementTag`/`AddRelTag` definitions for every tag in use
+    fn generate_tag_definitions(&self, diagram: &Diagram) -> String {
+        let collect = |tags: &mut BTreeSet<String>, source: &[String]| {
+            tags.extend(
+                source
+                    .iter()
+                    .map(|tag| self.escape_string(tag.trim()))
+                    .filter(|tag| !tag.is_empty()),
+            );
+        };
+        let mut element_tags = BTreeSet::new();
+        for element in diagram.elements.values() {
+            collect(&mut element_tags, &element.tags);
+        }
+        let mut rel_tags = BTreeSet::new();
+        for rel in &diagram.relationships {
+            collect(&mut rel_tags, &rel.tags);
+        }
+
+        let mut output = String::new();
+        for tag in element_tags {
+            output.push_str(&format!("AddElementTag(\"{}\")\n", tag));
+        }
+        let mut styled: Vec<&Element> = diagram
+            .elements
+            .values()
+            .filter(|e| !e.style.is_empty() && !e.is_note())
+            .collect();
+        styled.sort_by_key(|e| (e.name(), e.id));
+        for element in styled {
+            output.push_str(&self.generate_style_tag(element));
+            output.push('\n');
+        }
+        for tag in rel_tags {
+            output.push_str(&format!("AddRelTag(\"{}\")\n", tag));
+        }
+        output
+    }
+
+    /// Tag carrying an element's own style, if it has one
+    fn style_tag(&self, element: &Element) -> Option<String> {
+        (!element.style.is_empty()).then(|| format!("style_{}", element.id.simple()))
+    }
+
+    /// `AddElementTag` giving one element its own colours and border
+    fn generate_style_tag(&self, element: &Element) -> String {
+        let style = &element.style;
+        let mut output = format!("AddElementTag(\"{}\"", self.style_tag(element).unwrap_or_default());
+        for (parameter, color) in [("bgColor", style.background), ("fontColor", style.text), ("borderColor", style.border)] {
+            if let Some(color) = color {
+                output.push_str(&format!(", ${}=\"{}\"", parameter, color.to_hex()));
+            }
+        }
+        if let Some(border_style) = style.border_style {
+            let line = match border_style {
+                BorderStyle::Solid => "SolidLine()",
+                BorderStyle::Dashed => "DashedLine()",
+                BorderStyle::Dotted => "DottedLine()",
+            };
+            output.push_str(&format!(", $borderStyle={}", line));
+        }
+        output.push_str(&format!(", $legendText=\"{}\")", self.escape_string(element.name())));
+        output
+    }
+
     fn generate_element(&self, element: &crate::model::Element) -> String {
         let name = self.escape_string(element.name());
         let description = self.escape_string(element.description());
-        let id = format!("elem_{}", element.id.simple());
+        let id = element.export_alias();
+        let mut tags = element.tags.clone();
+        tags.extend(self.style_tag(element));
+        // Named arguments trailing every macro
+        let tags = self.tags_argument(&tags) + &self.link_argument(element);
 
         match &element.element_type {
             ElementType::Person(data) => {
                 if data.is_external {
                     format!(
-                        "Person_Ext({}, \"{}\", \"{}\")",
-                        id, name, description
-                    )
-                } else {
-                    format!(
-                        "Person({}, \"{}\", \"{}\")",
-                        id, name, description
-                    )
-                }
-            }
-            ElementType::SoftwareSystem(data) => {
-                if data.is_external {
-                    format!(
-                        "System_Ext({}, \"{}\", \"{}\")",
-                        id, name, description
+                        "Person_Ext({}, \"{}\", \"{}\"{})",
+                        id, name, description, tags
                     )
                 } else {
                     format!(
-                        "System({}, \"{}\", \"{}\")",
-                        id, name, description
+                        "Person({}, \"{}\", \"{}\"{})",
+                        id, name, description, tags
                     )
                 }
             }
+            ElementType::SoftwareSystem(data) => format!(
+                "{}({}, \"{}\", \"{}\"{})",
+                data.macro_name(), id, name, description, tags
+            ),
             ElementType::Container(data) => {
                 let container_type = match &data.container_type {
                     ContainerType::Database => "ContainerDb",
@@ -59,38 +220,133 @@ impl PlantUmlExporter {
                     _ => "Container",
                 };
                 let technology = self.escape_string(&data.technology);
+                let sprite = data
+                    .icon
+                    .as_ref()
+                    .map(|icon| format!(", $sprite=\"{}\"", icon.sprite))
+                    .unwrap_or_default();
                 if technology.is_empty() {
                     format!(
-                        "{}({}, \"{}\", \"{}\")",
-                        container_type, id, name, description
+                        "{}({}, \"{}\", \"{}\"{}{})",
+                        container_type, id, name, description, sprite, tags
                     )
                 } else {
                     format!(
-                        "{}({}, \"{}\", \"{}\", \"{}\")",
-                        container_type, id, name, description, technology
+                        "{}({}, \"{}\", \"{}\", \"{}\"{}{})",
+                        container_type, id, name, description, technology, sprite, tags
                     )
                 }
             }
+            ElementType::Component(data) => {
+                let technology = self.escape_string(&data.technology);
+                if technology.is_empty() {
+                    format!("Component({}, \"{}\", \"{}\"{})", id, name, description, tags)
+                } else {
+                    format!(
+                        "Component({}, \"{}\", \"{}\", \"{}\"{})",
+                        id, name, description, technology, tags
+                    )
+                }
+            }
+            // A plain PlantUML note, which relationships can still point at
+            ElementType::Note(data) => {
+                let color = element
+                    .style
+                    .background
+                    .map(|color| format!(" {}", color.to_hex()))
+                    .unwrap_or_default();
+                format!("note as {}{}\n{}\nend note", id, color, data.text.trim_end())
+            }
+        }
+    }
+
+    /// An element, or a boundary around its parts when it has any in the diagram
+    fn generate_element_tree(&self, diagram: &Diagram, element: &crate::model::Element, depth: usize) -> String {
+        let indent = "    ".repeat(depth);
+        let children = diagram.children_of(element.id);
+        if children.is_empty() {
+            return format!("{}{}\n", indent, self.generate_element(element));
+        }
+        let boundary = match element.element_type {
+            ElementType::SoftwareSystem(_) => "System_Boundary",
+            _ => "Container_Boundary",
+        };
+        let mut output = format!(
+            "{}{}({}, \"{}\") {{\n",
+            indent,
+            boundary,
+            element.export_alias(),
+            self.escape_string(element.name())
+        );
+        for child in children {
+            output.push_str(&self.generate_element_tree(diagram, child, depth + 1));
         }
+        output.push_str(&format!("{}}}\n", indent));
+        output
+    }
+
+    fn generate_relationship(&self, diagram: &Diagram, rel: &crate::model::Relationship) -> String {
+        self.generate_relationship_at(diagram, rel, None)
     }
 
-    fn generate_relationship(&self, rel: &crate::model::Relationship) -> String {
-        let source_id = format!("elem_{}", rel.source_id.simple());
-        let target_id = format!("elem_{}", rel.target_id.simple());
+    /// A relationship, as `RelIndex` with its step number on Dynamic diagrams
+    fn generate_relationship_at(
+        &self,
+        diagram: &Diagram,
+        rel: &crate::model::Relationship,
+        step: Option<u32>,
+    ) -> String {
+        let source_id = match step {
+            Some(step) => format!("{}, {}", step, diagram.export_alias(rel.source_id)),
+            None => diagram.export_alias(rel.source_id),
+        };
+        let target_id = diagram.export_alias(rel.target_id);
         let description = self.escape_string(&rel.description);
+        let tags = self.tags_argument(&rel.tags);
+        let macro_name = match step {
+            Some(_) => "RelIndex",
+            None => rel.direction.macro_name(),
+        };
 
-        if let Some(tech) = &rel.technology {
+        let rel_line = if let Some(tech) = &rel.technology {
             let technology = self.escape_string(tech);
             format!(
-                "Rel({}, {}, \"{}\", \"{}\")",
-                source_id, target_id, description, technology
+                "{}({}, {}, \"{}\", \"{}\"{})",
+                macro_name, source_id, target_id, description, technology, tags
             )
         } else {
             format!(
-                "Rel({}, {}, \"{}\")",
-                source_id, target_id, description
+                "{}({}, {}, \"{}\"{})",
+                macro_name, source_id, target_id, description, tags
             )
+        };
+
+        match rel.note.as_deref().map(str::trim) {
+            Some(note) if self.options.include_notes && !note.is_empty() => {
+                format!("{}\n{}", rel_line, self.generate_note(note))
+            }
+            _ => rel_line,
+        }
+    }
+
+    /// A `note on link` block attached to the preceding relationship
+    fn generate_note(&self, note: &str) -> String {
+        let mut out = String::from("note on link\n");
+        for line in note.lines() {
+            out.push_str("  ");
+            out.push_str(line);
+            out.push('\n');
         }
+        out.push_str("end note");
+        out
+    }
+}
+
+impl PlantUmlExporter {
+    /// URL that renders the diagram on the public PlantUML server
+    pub fn server_url(&self, diagram: &Diagram) -> std::io::Result<String> {
+        let encoded = encode_plantuml_text(&self.export(diagram))?;
+        Ok(format!("{}{}", PLANTUML_SERVER_URL, encoded))
     }
 }
 
@@ -102,16 +358,46 @@ impl Default for PlantUmlExporter {
 
 impl DiagramExporter for PlantUmlExporter {
     fn export(&self, diagram: &Diagram) -> String {
+        let diagram = &*diagram.in_active_view();
         let include = self.get_include(diagram.diagram_type);
         let mut output = String::new();
 
         // Header
         output.push_str("@startuml\n");
+        for line in diagram.metadata.header_lines() {
+            output.push_str(&format!("' {}\n", line));
+        }
         output.push_str(&format!(
             "!include https://raw.githubusercontent.com/plantuml-stdlib/C4-PlantUML/master/{}\n\n",
             include
         ));
 
+        // Sprite libraries for cloud icons
+        let sprite_includes = self.sprite_includes(diagram);
+        if !sprite_includes.is_empty() {
+            for include in sprite_includes {
+                output.push_str(&format!("!include <{}>\n", include));
+            }
+            output.push('\n');
+        }
+
+        // Display options
+        let directives = self.header_directives();
+        if !directives.is_empty() {
+            for directive in directives {
+                output.push_str(directive);
+                output.push('\n');
+            }
+            output.push('\n');
+        }
+
+        // Tag definitions
+        let tag_definitions = self.generate_tag_definitions(diagram);
+        if !tag_definitions.is_empty() {
+            output.push_str(&tag_definitions);
+            output.push('\n');
+        }
+
         // Title
         output.push_str(&format!("title {}\n\n", self.escape_string(&diagram.name)));
 
@@ -123,21 +409,30 @@ impl DiagramExporter for PlantUmlExporter {
             ));
         }
 
-        // Elements
-        for element in diagram.elements.values() {
-            output.push_str(&self.generate_element(element));
-            output.push('\n');
+        // Elements; systems and containers with parts become boundaries around them
+        for element in diagram.top_level() {
+            output.push_str(&self.generate_element_tree(diagram, element, 0));
         }
 
         output.push('\n');
 
-        // Relationships
-        for rel in &diagram.relationships {
-            output.push_str(&self.generate_relationship(rel));
-            output.push('\n');
+        // Relationships; a Dynamic diagram lists them as numbered steps
+        if diagram.diagram_type == DiagramType::Dynamic {
+            for (step, rel) in diagram.steps() {
+                output.push_str(&self.generate_relationship_at(diagram, rel, Some(step)));
+                output.push('\n');
+            }
+        } else {
+            for rel in &diagram.relationships {
+                output.push_str(&self.generate_relationship(diagram, rel));
+                output.push('\n');
+            }
         }
 
-        // Footer
+        // Footer; the legend only lists what was declared before it
+        if self.options.show_floating_legend {
+            output.push_str("\nSHOW_FLOATING_LEGEND()\n");
+        }
         output.push_str("\n@enduml\n");
 
         output
@@ -151,7 +446,8 @@ impl DiagramExporter for PlantUmlExporter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::{ContainerType, Diagram, DiagramType, Element, ElementId, ElementType, Position, Relationship};
+    use crate::model::{ContainerType, Diagram, DiagramType, Direction, Element, ElementId, ElementType, Position, Relationship, SystemKind};
+    use crate::style::{Color, ElementStyle};
 
     mod escape_string_tests {
         use super::*;
@@ -256,6 +552,23 @@ mod tests {
             assert!(result.contains("System_Ext"));
         }
 
+        /// Verifies database and queue systems use the SystemDb and SystemQueue macros
+        #[test]
+        fn generate_element_system_kinds() {
+            let exporter = PlantUmlExporter::new();
+            let mut element = Element::new(ElementType::external_system("Ledger", "Books"), Position::new(0.0, 0.0));
+            if let ElementType::SoftwareSystem(data) = &mut element.element_type {
+                data.kind = SystemKind::Database;
+            }
+            assert!(exporter.generate_element(&element).contains("SystemDb_Ext("));
+
+            if let ElementType::SoftwareSystem(data) = &mut element.element_type {
+                data.kind = SystemKind::Queue;
+                data.is_external = false;
+            }
+            assert!(exporter.generate_element(&element).contains("SystemQueue("));
+        }
+
         /// Verifies generate_element creates correct output for container
         #[test]
         fn generate_element_container() {
@@ -325,7 +638,7 @@ mod tests {
             let target_id = ElementId::new_v4();
             let rel = Relationship::new(source_id, target_id, "uses");
 
-            let result = exporter.generate_relationship(&rel);
+            let result = exporter.generate_relationship(&Diagram::default(), &rel);
             assert!(result.contains("Rel("));
             assert!(result.contains("uses"));
             assert!(!result.contains("\", \""));
@@ -339,16 +652,356 @@ mod tests {
             let target_id = ElementId::new_v4();
             let rel = Relationship::with_technology(source_id, target_id, "uses", "HTTPS");
 
-            let result = exporter.generate_relationship(&rel);
+            let result = exporter.generate_relationship(&Diagram::default(), &rel);
             assert!(result.contains("Rel("));
             assert!(result.contains("uses"));
             assert!(result.contains("HTTPS"));
         }
+
+        /// Verifies each direction uses its own macro
+        #[test]
+        fn generate_relationship_direction() {
+            let exporter = PlantUmlExporter::new();
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "syncs");
+            assert!(exporter.generate_relationship(&Diagram::default(), &rel).starts_with("Rel("));
+
+            rel.direction = Direction::BiDirectional;
+            assert!(exporter.generate_relationship(&Diagram::default(), &rel).starts_with("BiRel("));
+
+            rel.direction = Direction::Back;
+            assert!(exporter.generate_relationship(&Diagram::default(), &rel).starts_with("Rel_Back("));
+        }
+
+        /// Verifies notes are only emitted when enabled in the options
+        #[test]
+        fn generate_relationship_note_follows_options() {
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "uses");
+            rel.note = Some("mTLS\n200 req/s".to_string());
+
+            let plain = PlantUmlExporter::new().generate_relationship(&Diagram::default(), &rel);
+            assert!(!plain.contains("note"));
+
+            let exporter = PlantUmlExporter::with_options(PlantUmlOptions {
+                include_notes: true,
+                ..Default::default()
+            });
+            let result = exporter.generate_relationship(&Diagram::default(), &rel);
+            assert!(result.ends_with("note on link\n  mTLS\n  200 req/s\nend note"));
+        }
+
+        /// Verifies blank notes are skipped even when notes are enabled
+        #[test]
+        fn generate_relationship_skips_blank_note() {
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "uses");
+            rel.note = Some("   ".to_string());
+            let exporter = PlantUmlExporter::with_options(PlantUmlOptions {
+                include_notes: true,
+                ..Default::default()
+            });
+            assert!(!exporter.generate_relationship(&Diagram::default(), &rel).contains("note"));
+        }
+    }
+
+    mod tags_tests {
+        use super::*;
+
+        /// Verifies elements pass their tags as a `$tags` argument
+        #[test]
+        fn generate_element_includes_tags() {
+            let exporter = PlantUmlExporter::new();
+            let mut element = Element::new(
+                ElementType::container("API", "", ContainerType::Microservice, "Rust"),
+                Position::new(0.0, 0.0),
+            );
+            element.tags = vec!["legacy".to_string(), " v2 ".to_string(), "".to_string()];
+
+            let result = exporter.generate_element(&element);
+            assert!(result.ends_with("\"Rust\", $tags=\"legacy+v2\")"));
+        }
+
+        /// Verifies untagged output is unchanged
+        #[test]
+        fn generate_element_without_tags() {
+            let exporter = PlantUmlExporter::new();
+            let element = Element::new(ElementType::system("S", "d"), Position::new(0.0, 0.0));
+            assert!(!exporter.generate_element(&element).contains("$tags"));
+        }
+
+        /// Verifies relationships pass their tags as a `$tags` argument
+        #[test]
+        fn generate_relationship_includes_tags() {
+            let exporter = PlantUmlExporter::new();
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "uses");
+            rel.tags = vec!["async".to_string()];
+            assert!(exporter.generate_relationship(&Diagram::default(), &rel).ends_with("\"uses\", $tags=\"async\")"));
+        }
+
+        /// Verifies element links are passed as `$link`
+        #[test]
+        fn export_element_link() {
+            let exporter = PlantUmlExporter::new();
+            let mut element = Element::new(ElementType::system("Shop", ""), Position::new(0.0, 0.0));
+            element.url = Some(" https://wiki.example.com/shop?a=\"b\" ".to_string());
+            element.tags = vec!["legacy".to_string()];
+            assert_eq!(
+                exporter.generate_element(&element),
+                format!(
+                    "System(elem_{}, \"Shop\", \"\", $tags=\"legacy\", $link=\"https://wiki.example.com/shop?a=\\\"b\\\"\")",
+                    element.id.simple()
+                )
+            );
+
+            element.url = Some(" ".to_string());
+            assert!(!exporter.generate_element(&element).contains("$link"));
+        }
+
+        /// Verifies each tag in use is defined once, by kind
+        #[test]
+        fn export_defines_tags() {
+            let exporter = PlantUmlExporter::new();
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let mut a = Element::new(ElementType::system("A", ""), Position::new(0.0, 0.0));
+            a.tags = vec!["legacy".to_string()];
+            let mut b = Element::new(ElementType::system("B", ""), Position::new(0.0, 0.0));
+            b.tags = vec!["legacy".to_string(), "core".to_string()];
+            let mut rel = Relationship::new(a.id, b.id, "uses");
+            rel.tags = vec!["async".to_string()];
+            diagram.add_element(a);
+            diagram.add_element(b);
+            diagram.add_relationship(rel);
+
+            let result = exporter.export(&diagram);
+            assert!(result.contains(
+                "AddElementTag(\"core\")\nAddElementTag(\"legacy\")\nAddRelTag(\"async\")\n\ntitle"
+            ));
+            assert_eq!(result.matches("AddElementTag(\"legacy\")").count(), 1);
+        }
+    }
+
+    mod header_directives_tests {
+        use super::*;
+
+        /// Verifies no directives are emitted by default
+        #[test]
+        fn header_directives_empty_by_default() {
+            assert!(PlantUmlExporter::new().header_directives().is_empty());
+        }
+
+        /// Verifies each enabled option adds its directive
+        #[test]
+        fn header_directives_follow_options() {
+            let exporter = PlantUmlExporter::with_options(PlantUmlOptions {
+                show_person_outline: true,
+                hide_stereotype: true,
+                show_floating_legend: true,
+                sketch_mode: true,
+                ..Default::default()
+            });
+            assert_eq!(
+                exporter.header_directives(),
+                vec!["SHOW_PERSON_OUTLINE()", "HIDE_STEREOTYPE()", "LAYOUT_AS_SKETCH()"]
+            );
+        }
+
+        /// Verifies the floating legend is emitted after the relationships
+        #[test]
+        fn export_places_legend_in_footer() {
+            let exporter = PlantUmlExporter::with_options(PlantUmlOptions {
+                show_floating_legend: true,
+                ..Default::default()
+            });
+            let diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let result = exporter.export(&diagram);
+            assert!(result.ends_with("\nSHOW_FLOATING_LEGEND()\n\n@enduml\n"));
+            assert_eq!(result.matches("SHOW_FLOATING_LEGEND()").count(), 1);
+        }
+
+        /// Verifies directives are placed between the include and the title
+        #[test]
+        fn export_places_directives_after_include() {
+            let exporter = PlantUmlExporter::with_options(PlantUmlOptions {
+                hide_stereotype: true,
+                ..Default::default()
+            });
+            let diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let result = exporter.export(&diagram);
+            assert!(result.contains("C4_Context.puml\n\nHIDE_STEREOTYPE()\n\ntitle Test"));
+        }
+    }
+
+    mod sprite_tests {
+        use super::*;
+        use crate::model::{CloudIcon, IconPack};
+
+        fn with_icon(name: &str, technology: &str, icon: CloudIcon) -> Element {
+            let mut element = Element::new(
+                ElementType::container(name, "", ContainerType::Microservice, technology),
+                Position::new(0.0, 0.0),
+            );
+            if let ElementType::Container(data) = &mut element.element_type {
+                data.icon = Some(icon);
+            }
+            element
+        }
+
+        /// Verifies a container icon is exported as a sprite argument
+        #[test]
+        fn generate_element_includes_sprite() {
+            let exporter = PlantUmlExporter::new();
+            let element = with_icon("Handler", "Python", CloudIcon::new(IconPack::Aws, "Lambda"));
+            let result = exporter.generate_element(&element);
+            assert!(result.ends_with(", \"Python\", $sprite=\"Lambda\")"));
+
+            let element = with_icon("Handler", "", CloudIcon::new(IconPack::Aws, "Lambda"));
+            assert!(exporter.generate_element(&element).ends_with("\"\", $sprite=\"Lambda\")"));
+        }
+
+        /// Verifies the sprite libraries are included once each, common files first
+        #[test]
+        fn export_includes_sprite_libraries() {
+            let exporter = PlantUmlExporter::new();
+            let mut diagram = Diagram::new("Test", "", DiagramType::Container);
+            diagram.add_element(with_icon("A", "", CloudIcon::new(IconPack::Gcp, "Cloud_Run")));
+            diagram.add_element(with_icon("B", "", CloudIcon::new(IconPack::Gcp, "Cloud_Run")));
+            diagram.add_element(with_icon("C", "", CloudIcon::new(IconPack::Aws, "RDS")));
+
+            let result = exporter.export(&diagram);
+            assert!(result.contains(
+                "C4_Container.puml\n\n!include <awslib/AWSCommon>\n!include <awslib/Database/RDS>\n\
+                 !include <gcp/GCPCommon>\n!include <gcp/Compute/Cloud_Run>\n\ntitle"
+            ));
+        }
+
+        /// Verifies no sprite libraries are included without icons
+        #[test]
+        fn export_without_icons_has_no_sprite_includes() {
+            let diagram = Diagram::new("Test", "", DiagramType::Container);
+            assert!(!PlantUmlExporter::new().export(&diagram).contains("!include <"));
+        }
+    }
+
+    mod server_url_tests {
+        use super::*;
+
+        fn decode(encoded: &str) -> String {
+            let mut bytes = Vec::new();
+            for chunk in encoded.as_bytes().chunks(4) {
+                let mut n = 0u32;
+                for &c in chunk {
+                    let value = PLANTUML_ALPHABET.iter().position(|&a| a == c).unwrap() as u32;
+                    n = (n << 6) | value;
+                }
+                bytes.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8, n as u8]);
+            }
+            let mut decoder = flate2::read::DeflateDecoder::new(&bytes[..]);
+            let mut out = String::new();
+            std::io::Read::read_to_string(&mut decoder, &mut out).unwrap();
+            out
+        }
+
+        /// Verifies the encoded text only uses the PlantUML alphabet
+        #[test]
+        fn encode_plantuml_text_uses_plantuml_alphabet() {
+            let encoded = encode_plantuml_text("@startuml\nBob -> Alice : hello\n@enduml").unwrap();
+            assert!(!encoded.is_empty());
+            assert_eq!(encoded.len() % 4, 0);
+            assert!(encoded.bytes().all(|c| PLANTUML_ALPHABET.contains(&c)));
+        }
+
+        /// Verifies the encoding can be reversed to the original source
+        #[test]
+        fn encode_plantuml_text_roundtrip() {
+            let source = "@startuml\nBob -> Alice : hello\n@enduml\n";
+            let encoded = encode_plantuml_text(source).unwrap();
+            assert_eq!(decode(&encoded), source);
+        }
+
+        /// Verifies server_url points at the PlantUML server and encodes the export
+        #[test]
+        fn server_url_encodes_export() {
+            let exporter = PlantUmlExporter::new();
+            let diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+
+            let url = exporter.server_url(&diagram).unwrap();
+            assert!(url.starts_with(PLANTUML_SERVER_URL));
+            assert_eq!(decode(&url[PLANTUML_SERVER_URL.len()..]), exporter.export(&diagram));
+        }
     }
 
     mod export_tests {
         use super::*;
 
+        /// Verifies relationships between the same elements are exported in the diagram's order
+        #[test]
+        fn export_keeps_parallel_relationship_order() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+            let user = Element::new(ElementType::person("Customer", ""), Position::new(0.0, 0.0));
+            let shop = Element::new(ElementType::system("Shop", ""), Position::new(300.0, 0.0));
+            let (user_id, shop_id) = (user.id, shop.id);
+            diagram.add_element(user);
+            diagram.add_element(shop);
+            for (source, target, description) in [(user_id, shop_id, "Orders"), (shop_id, user_id, "Emails"), (user_id, shop_id, "Pays")] {
+                diagram.add_relationship(Relationship::new(source, target, description));
+            }
+            diagram.assign_aliases();
+
+            let result = PlantUmlExporter::new().export(&diagram);
+            let orders = result.find("Rel(customer, shop, \"Orders\")").unwrap();
+            let emails = result.find("Rel(shop, customer, \"Emails\")").unwrap();
+            let pays = result.find("Rel(customer, shop, \"Pays\")").unwrap();
+            assert!(orders < emails && emails < pays);
+        }
+
+        /// Verifies assigned aliases replace UUIDs in elements, boundaries and relationships
+        #[test]
+        fn export_uses_aliases() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::Container);
+            let user = Element::new(ElementType::person("Customer", ""), Position::new(0.0, 0.0));
+            let shop = Element::new(ElementType::system("Web Shop", ""), Position::new(300.0, 0.0));
+            let mut api = Element::new(
+                ElementType::container("API", "", ContainerType::Microservice, ""),
+                Position::new(300.0, 100.0),
+            );
+            if let ElementType::Container(data) = &mut api.element_type {
+                data.system = Some(shop.id);
+            }
+            let orders = Relationship::new(user.id, api.id, "Orders");
+            diagram.add_element(user);
+            diagram.add_element(shop);
+            diagram.add_element(api);
+            diagram.add_relationship(orders);
+            diagram.assign_aliases();
+
+            let result = PlantUmlExporter::new().export(&diagram);
+            assert!(result.contains("System_Boundary(web_shop, \"Web Shop\")"));
+            assert!(result.contains("Container(api, \"API\""));
+            assert!(result.contains("Rel(customer, api, \"Orders\")"));
+            assert!(!result.contains("elem_"));
+            assert_eq!(result, PlantUmlExporter::new().export(&diagram.clone()));
+        }
+
+        /// Verifies only the elements and relationships of the active view are exported
+        #[test]
+        fn export_respects_active_view() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+            let user = Element::new(ElementType::person("Customer", ""), Position::new(0.0, 0.0));
+            let mut shop = Element::new(ElementType::system("Shop", ""), Position::new(300.0, 0.0));
+            shop.tags = vec!["core".to_string()];
+            let uses = Relationship::new(user.id, shop.id, "Uses");
+            diagram.add_element(user);
+            diagram.add_element(shop);
+            diagram.add_relationship(uses);
+            let mut view = crate::model::View::new("Core");
+            view.include_tags = vec!["core".to_string()];
+            let view = diagram.add_view(view);
+            diagram.set_active_view(Some(view));
+
+            let result = PlantUmlExporter::new().export(&diagram);
+            assert!(result.contains("\"Shop\""));
+            assert!(!result.contains("Customer"));
+            assert!(!result.contains("Rel("));
+        }
+
         /// Verifies export produces valid PlantUML output
         #[test]
         fn export_produces_valid_plantuml() {
@@ -384,6 +1037,119 @@ mod tests {
             assert!(!result.contains("C4_Context.puml"));
         }
 
+        /// Verifies Component diagrams include the component library and declare components
+        #[test]
+        fn export_component_diagram() {
+            let exporter = PlantUmlExporter::new();
+            let mut diagram = Diagram::new("Test", "", DiagramType::Component);
+            let element = Element::new(
+                ElementType::component("Checkout", "Takes payment", "Spring Bean"),
+                Position::new(0.0, 0.0),
+            );
+            let id = element.id;
+            diagram.add_element(element);
+
+            let result = exporter.export(&diagram);
+            assert!(result.contains("C4_Component.puml"));
+            assert!(result.contains(&format!(
+                "Component(elem_{}, \"Checkout\", \"Takes payment\", \"Spring Bean\")",
+                id.simple()
+            )));
+        }
+
+        /// Verifies an element's own style is defined as a tag and applied to it
+        #[test]
+        fn export_element_style() {
+            let exporter = PlantUmlExporter::new();
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let mut element = Element::new(ElementType::system("Legacy", ""), Position::new(0.0, 0.0));
+            element.tags = vec!["old".to_string()];
+            element.style = ElementStyle {
+                background: Some(Color::rgb(255, 0, 0)),
+                border_style: Some(BorderStyle::Dashed),
+                ..ElementStyle::default()
+            };
+            let tag = format!("style_{}", element.id.simple());
+            diagram.add_element(element);
+
+            let result = exporter.export(&diagram);
+            assert!(result.contains(&format!(
+                "AddElementTag(\"{}\", $bgColor=\"#ff0000\", $borderStyle=DashedLine(), $legendText=\"Legacy\")\n",
+                tag
+            )));
+            assert!(result.contains(&format!("$tags=\"old+{}\")", tag)));
+        }
+
+        /// Verifies parts are nested in boundaries of the system and container they belong to
+        #[test]
+        fn export_groups_parts_in_boundaries() {
+            let exporter = PlantUmlExporter::new();
+            let mut diagram = Diagram::new("Shop", "", DiagramType::Component);
+            let shop = Element::new(ElementType::system("Shop", ""), Position::new(0.0, 0.0));
+            let mut api = ElementType::container("API", "", ContainerType::Microservice, "");
+            if let ElementType::Container(data) = &mut api {
+                data.system = Some(shop.id);
+            }
+            let api = Element::new(api, Position::new(0.0, 0.0));
+            let mut checkout = ElementType::component("Checkout", "", "");
+            if let ElementType::Component(data) = &mut checkout {
+                data.container = Some(api.id);
+            }
+            let (shop_id, api_id) = (shop.id, api.id);
+            diagram.add_element(shop);
+            diagram.add_element(api);
+            diagram.add_element(Element::new(checkout, Position::new(0.0, 0.0)));
+
+            let result = exporter.export(&diagram);
+            assert!(result.contains(&format!("System_Boundary(elem_{}, \"Shop\") {{\n", shop_id.simple())));
+            assert!(result.contains(&format!("    Container_Boundary(elem_{}, \"API\") {{\n", api_id.simple())));
+            assert!(result.contains("        Component(elem_"));
+            assert!(result.contains("    }\n}\n"));
+            assert!(!result.contains("System(elem_"));
+        }
+
+        /// Verifies notes become PlantUML notes that relationships can point at
+        #[test]
+        fn export_note() {
+            let exporter = PlantUmlExporter::new();
+            let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+            let mut note = Element::new(ElementType::note("Legacy\nto be replaced"), Position::new(0.0, 0.0));
+            note.style.background = Some(Color::rgb(255, 255, 0));
+            let shop = Element::new(ElementType::system("Shop", ""), Position::new(0.0, 0.0));
+            let (note_id, shop_id) = (note.id, shop.id);
+            diagram.add_element(note);
+            diagram.add_element(shop);
+            diagram.add_relationship(Relationship::new(note_id, shop_id, ""));
+
+            let result = exporter.export(&diagram);
+            assert!(result.contains(&format!("note as elem_{} #ffff00\nLegacy\nto be replaced\nend note\n", note_id.simple())));
+            assert!(result.contains(&format!("Rel(elem_{}, elem_{}, \"\")", note_id.simple(), shop_id.simple())));
+            assert!(!result.contains("AddElementTag"));
+        }
+
+        /// Verifies Dynamic diagrams list relationships as numbered steps in order
+        #[test]
+        fn export_dynamic_diagram() {
+            let exporter = PlantUmlExporter::new();
+            let mut diagram = Diagram::new("Sign in", "", DiagramType::Dynamic);
+            let user = Element::new(ElementType::person("User", ""), Position::new(0.0, 0.0));
+            let app = Element::new(ElementType::system("App", ""), Position::new(200.0, 0.0));
+            let (user_id, app_id) = (user.id, app.id);
+            diagram.add_element(user);
+            diagram.add_element(app);
+            diagram.add_relationship(Relationship::new(user_id, app_id, "Submits credentials"));
+            diagram.add_relationship(Relationship::new(app_id, user_id, "Returns a session"));
+            diagram.relationships.swap(0, 1);
+
+            let result = exporter.export(&diagram);
+            assert!(result.contains("C4_Dynamic.puml"));
+            let first = result
+                .find(&format!("RelIndex(1, elem_{}, elem_{}, \"Submits credentials\")", user_id.simple(), app_id.simple()))
+                .unwrap();
+            let second = result.find("RelIndex(2, ").unwrap();
+            assert!(first < second);
+        }
+
         /// Verifies export handles empty diagrams
         #[test]
         fn export_handles_empty_diagram() {
@@ -420,6 +1186,17 @@ mod tests {
             assert!(result.contains("Rel("));
             assert!(result.contains("uses"));
         }
+
+        /// Verifies diagram metadata is written as comments after @startuml
+        #[test]
+        fn export_includes_metadata_comments() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            diagram.metadata.author = "Ana".to_string();
+            diagram.metadata.version_label = "v2".to_string();
+
+            let result = PlantUmlExporter::new().export(&diagram);
+            assert!(result.starts_with("@startuml\n' Author: Ana\n' Version: v2\n!include"));
+        }
     }
 
 }
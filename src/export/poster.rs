@@ -0,0 +1,281 @@
+//! Tiled poster export
+//!
+//! Splits the SVG image of a large diagram across printable pages. Each page
+//! is a standalone SVG sized in millimetres that shows one tile of the full
+//! image. Neighbouring tiles overlap by a margin, and dashed marks show where
+//! to trim or glue the pages. Each page also has a small row/column label so
+//! the pages can be put back together on a wall.
+
+use super::SvgExporter;
+use crate::model::Diagram;
+use std::path::{Path, PathBuf};
+
+/// SVG user units (CSS pixels) per millimetre
+const UNITS_PER_MM: f32 = 96.0 / 25.4;
+
+/// Paper sizes offered for poster export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PageSize {
+    #[default]
+    A4,
+    A3,
+    Letter,
+}
+
+impl PageSize {
+    pub const ALL: [PageSize; 3] = [PageSize::A4, PageSize::A3, PageSize::Letter];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PageSize::A4 => "A4",
+            PageSize::A3 => "A3",
+            PageSize::Letter => "Letter",
+        }
+    }
+
+    /// Portrait width and height in millimetres
+    pub fn size_mm(&self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => (210.0, 297.0),
+            PageSize::A3 => (297.0, 420.0),
+            PageSize::Letter => (215.9, 279.4),
+        }
+    }
+}
+
+/// How to split a diagram into pages
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PosterOptions {
+    pub page_size: PageSize,
+    pub landscape: bool,
+    /// Print scale; 1.0 prints one canvas unit as one CSS pixel (1/96 inch)
+    pub scale: f32,
+    /// Width of the strip shared by neighbouring pages, in millimetres
+    pub overlap_mm: f32,
+}
+
+impl Default for PosterOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PageSize::A4,
+            landscape: false,
+            scale: 1.0,
+            overlap_mm: 10.0,
+        }
+    }
+}
+
+impl PosterOptions {
+    /// Page width and height in millimetres, after orientation
+    pub fn page_mm(&self) -> (f32, f32) {
+        let (width, height) = self.page_size.size_mm();
+        if self.landscape { (height, width) } else { (width, height) }
+    }
+
+    /// Area of the diagram image covered by one page, in image units
+    fn tile_size(&self) -> (f32, f32) {
+        let (width, height) = self.page_mm();
+        let units = UNITS_PER_MM / self.scale.max(0.01);
+        (width * units, height * units)
+    }
+
+    /// Overlap between neighbouring pages in image units, at most half a page
+    fn overlap(&self) -> f32 {
+        let (width, height) = self.tile_size();
+        (self.overlap_mm.max(0.0) * UNITS_PER_MM / self.scale.max(0.01)).min(width.min(height) * 0.5)
+    }
+
+    /// Number of rows and columns of pages for an image of this size
+    pub fn grid(&self, width: f32, height: f32) -> (usize, usize) {
+        let (tile_width, tile_height) = self.tile_size();
+        let overlap = self.overlap();
+        let count = |length: f32, tile: f32| {
+            if length <= tile {
+                1
+            } else {
+                ((length - overlap) / (tile - overlap)).ceil() as usize
+            }
+        };
+        (count(height, tile_height), count(width, tile_width))
+    }
+}
+
+/// One printable page of a poster
+#[derive(Debug, Clone, PartialEq)]
+pub struct PosterPage {
+    /// Zero-based position in the grid
+    pub row: usize,
+    pub column: usize,
+    pub name: String,
+    pub contents: String,
+}
+
+/// Number of rows and columns `diagram` needs with these options
+pub fn poster_grid(diagram: &Diagram, options: &PosterOptions) -> (usize, usize) {
+    let (_, width, height) = SvgExporter::new().export_body(diagram);
+    options.grid(width, height)
+}
+
+/// Split the diagram's SVG image into pages, row by row
+///
+/// Pages are named `<stem>-r<row>-c<column>.svg`, counting from 1.
+pub fn render_poster(diagram: &Diagram, options: &PosterOptions, stem: &str) -> Vec<PosterPage> {
+    let (body, width, height) = SvgExporter::new().export_body(diagram);
+    let (rows, columns) = options.grid(width, height);
+    let (page_width, page_height) = options.page_mm();
+    let (tile_width, tile_height) = options.tile_size();
+    let overlap = options.overlap();
+    let stroke = 0.5 * UNITS_PER_MM / options.scale.max(0.01);
+
+    let mut pages = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            let x = column as f32 * (tile_width - overlap);
+            let y = row as f32 * (tile_height - overlap);
+
+            let mut marks = String::new();
+            let mut mark = |x1: f32, y1: f32, x2: f32, y2: f32| {
+                marks.push_str(&format!(
+                    "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#c00000\" stroke-width=\"{}\" stroke-dasharray=\"{} {}\"/>\n",
+                    x1, y1, x2, y2, stroke, stroke * 6.0, stroke * 4.0
+                ));
+            };
+            // Marks run through the middle of each overlap strip
+            let (top, bottom) = (y, y + tile_height);
+            let (left, right) = (x, x + tile_width);
+            if column > 0 {
+                mark(left + overlap * 0.5, top, left + overlap * 0.5, bottom);
+            }
+            if column + 1 < columns {
+                mark(right - overlap * 0.5, top, right - overlap * 0.5, bottom);
+            }
+            if row > 0 {
+                mark(left, top + overlap * 0.5, right, top + overlap * 0.5);
+            }
+            if row + 1 < rows {
+                mark(left, bottom - overlap * 0.5, right, bottom - overlap * 0.5);
+            }
+
+            let label = format!(
+                "  <text x=\"{}\" y=\"{}\" text-anchor=\"end\" font-size=\"{}\" fill=\"#c00000\">Row {} / {}, Column {} / {}</text>\n",
+                right - overlap,
+                bottom - overlap,
+                stroke * 6.0,
+                row + 1,
+                rows,
+                column + 1,
+                columns
+            );
+            pages.push(PosterPage {
+                row,
+                column,
+                name: format!("{}-r{}-c{}.svg", stem, row + 1, column + 1),
+                contents: format!(
+                    "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}mm\" height=\"{}mm\" viewBox=\"{} {} {} {}\" font-family=\"sans-serif\">\n{}{}{}</svg>\n",
+                    page_width, page_height, x, y, tile_width, tile_height, body, marks, label
+                ),
+            });
+        }
+    }
+    pages
+}
+
+/// Render the poster and write its pages into `out_dir`, returning their paths
+pub fn export_poster(diagram: &Diagram, options: &PosterOptions, out_dir: &Path, stem: &str) -> crate::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(out_dir).map_err(|e| crate::C2DrawError::file(out_dir, e))?;
+    let mut written = Vec::new();
+    for page in render_poster(diagram, options, stem) {
+        let path = out_dir.join(&page.name);
+        std::fs::write(&path, page.contents).map_err(|e| crate::C2DrawError::file(&path, e))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, Element, ElementType, Position};
+
+    fn wide_diagram() -> Diagram {
+        let mut diagram = Diagram::new("Wide", "", DiagramType::SystemContext);
+        diagram.add_element(Element::new(ElementType::system("Left", ""), Position::new(0.0, 0.0)));
+        diagram.add_element(Element::new(ElementType::system("Right", ""), Position::new(1500.0, 0.0)));
+        diagram
+    }
+
+    mod grid_tests {
+        use super::*;
+
+        /// Verifies a small image fits on a single page
+        #[test]
+        fn small_image_is_one_page() {
+            assert_eq!(PosterOptions::default().grid(100.0, 100.0), (1, 1));
+        }
+
+        /// Verifies the overlap is accounted for when counting pages
+        #[test]
+        fn grid_counts_overlapping_pages() {
+            let options = PosterOptions {
+                overlap_mm: 0.0,
+                ..PosterOptions::default()
+            };
+            let (tile_width, _) = options.tile_size();
+            assert_eq!(options.grid(tile_width * 2.0, 10.0), (1, 2));
+
+            let options = PosterOptions::default();
+            assert_eq!(options.grid(tile_width * 2.0, 10.0), (1, 3));
+        }
+
+        /// Verifies landscape swaps the page dimensions and scale enlarges the poster
+        #[test]
+        fn landscape_and_scale() {
+            let options = PosterOptions {
+                landscape: true,
+                ..PosterOptions::default()
+            };
+            assert_eq!(options.page_mm(), (297.0, 210.0));
+
+            let diagram = wide_diagram();
+            let (_, columns) = poster_grid(&diagram, &PosterOptions::default());
+            let (_, scaled) = poster_grid(&diagram, &PosterOptions { scale: 2.0, ..PosterOptions::default() });
+            assert!(scaled > columns);
+        }
+    }
+
+    mod render_poster_tests {
+        use super::*;
+
+        /// Verifies each page shows its own tile of the full image with marks and a label
+        #[test]
+        fn pages_cover_the_image() {
+            let pages = render_poster(&wide_diagram(), &PosterOptions::default(), "wide");
+            assert!(pages.len() > 1);
+            assert_eq!(pages[0].name, "wide-r1-c1.svg");
+            assert!(pages[0].contents.contains("width=\"210mm\" height=\"297mm\" viewBox=\"0 0 "));
+            assert!(pages[1].contents.contains("stroke-dasharray"));
+            assert!(pages[1].contents.contains(&format!("Column 2 / {}", pages.len())));
+            assert!(pages.iter().all(|p| p.contents.contains("Left") && p.contents.contains("Right")));
+        }
+
+        /// Verifies a single page has no overlap marks
+        #[test]
+        fn single_page_has_no_marks() {
+            let diagram = Diagram::new("Empty", "", DiagramType::SystemContext);
+            let pages = render_poster(&diagram, &PosterOptions::default(), "empty");
+            assert_eq!(pages.len(), 1);
+            assert!(!pages[0].contents.contains("stroke-dasharray"));
+        }
+
+        /// Verifies pages are written to the output folder
+        #[test]
+        fn export_poster_writes_pages() {
+            let dir = std::env::temp_dir().join(format!("c2draw-poster-{}", uuid::Uuid::new_v4()));
+            let written = export_poster(&wide_diagram(), &PosterOptions::default(), &dir, "wide").unwrap();
+            let all_exist = written.iter().all(|p| p.is_file());
+            let _ = std::fs::remove_dir_all(&dir);
+            assert!(all_exist);
+            assert_eq!(written[0], dir.join("wide-r1-c1.svg"));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Offline rendering through locally installed PlantUML / Mermaid CLI tools
+
+use crate::preferences::Preferences;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use thiserror::Error;
+
+/// Source language of a diagram to be rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderSource {
+    PlantUml,
+    Mermaid,
+}
+
+impl RenderSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RenderSource::PlantUml => "PlantUML",
+            RenderSource::Mermaid => "Mermaid",
+        }
+    }
+
+    /// Whether a local renderer for this source is configured
+    pub fn is_available(&self, preferences: &Preferences) -> bool {
+        match self {
+            RenderSource::PlantUml => preferences.has_plantuml(),
+            RenderSource::Mermaid => preferences.has_mmdc(),
+        }
+    }
+}
+
+/// Errors that can occur while rendering through an external tool
+#[derive(Debug, Error)]
+pub enum RenderError {
+    /// No local renderer has been configured in the preferences
+    #[error("no local {} renderer configured (see File → Preferences)", .0.as_str())]
+    NotConfigured(RenderSource),
+    /// The renderer could not be started or its files could not be accessed
+    #[error("could not run renderer: {0}")]
+    Io(#[from] std::io::Error),
+    /// The renderer ran but reported a failure
+    #[error("renderer failed: {0}")]
+    Failed(String),
+}
+
+/// Render diagram source to PNG bytes using the configured local tool
+pub fn render_png(
+    source: RenderSource,
+    text: &str,
+    preferences: &Preferences,
+) -> Result<Vec<u8>, RenderError> {
+    if !source.is_available(preferences) {
+        return Err(RenderError::NotConfigured(source));
+    }
+    match source {
+        RenderSource::PlantUml => render_plantuml_png(text, preferences),
+        RenderSource::Mermaid => render_mermaid_png(text, preferences),
+    }
+}
+
+/// Run `java -jar plantuml.jar -tpng -pipe`, feeding the source on stdin
+fn render_plantuml_png(text: &str, preferences: &Preferences) -> Result<Vec<u8>, RenderError> {
+    let mut child = Command::new(preferences.java_path.trim())
+        .arg("-jar")
+        .arg(preferences.plantuml_jar.trim())
+        .args(["-tpng", "-pipe", "-charset", "UTF-8"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(RenderError::Failed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// Run `mmdc -i <input> -o <output>` through temporary files
+fn render_mermaid_png(text: &str, preferences: &Preferences) -> Result<Vec<u8>, RenderError> {
+    let stem = format!("c2draw-{}", uuid::Uuid::new_v4().simple());
+    let input = std::env::temp_dir().join(format!("{}.mmd", stem));
+    let output_path = std::env::temp_dir().join(format!("{}.png", stem));
+
+    std::fs::write(&input, text)?;
+    let result = Command::new(preferences.mmdc_path.trim())
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output_path)
+        .output();
+    let _ = std::fs::remove_file(&input);
+
+    let output = result?;
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&output_path);
+        return Err(RenderError::Failed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    let png = std::fs::read(&output_path)?;
+    let _ = std::fs::remove_file(&output_path);
+    Ok(png)
+}
+
+/// Decode PNG bytes into an egui image for previewing
+#[cfg(feature = "gui")]
+pub fn decode_png(bytes: &[u8]) -> Result<egui::ColorImage, RenderError> {
+    let image = image::load_from_memory_with_format(bytes, image::ImageFormat::Png)
+        .map_err(|e| RenderError::Failed(e.to_string()))?
+        .to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Ok(egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod render_source_tests {
+        use super::*;
+
+        /// Verifies availability follows the configured tool paths
+        #[test]
+        fn is_available_follows_preferences() {
+            let mut prefs = Preferences::default();
+            assert!(!RenderSource::PlantUml.is_available(&prefs));
+            assert!(!RenderSource::Mermaid.is_available(&prefs));
+
+            prefs.plantuml_jar = "/opt/plantuml.jar".to_string();
+            prefs.mmdc_path = "mmdc".to_string();
+            assert!(RenderSource::PlantUml.is_available(&prefs));
+            assert!(RenderSource::Mermaid.is_available(&prefs));
+        }
+    }
+
+    mod render_png_tests {
+        use super::*;
+
+        /// Verifies rendering without a configured tool fails fast
+        #[test]
+        fn render_png_requires_configuration() {
+            let prefs = Preferences::default();
+            let result = render_png(RenderSource::Mermaid, "flowchart TD", &prefs);
+            assert!(matches!(result, Err(RenderError::NotConfigured(RenderSource::Mermaid))));
+        }
+
+        /// Verifies a missing executable is reported as an IO error
+        #[test]
+        fn render_png_reports_missing_executable() {
+            let prefs = Preferences {
+                mmdc_path: "/nonexistent/c2draw-test-mmdc".to_string(),
+                ..Default::default()
+            };
+            let result = render_png(RenderSource::Mermaid, "flowchart TD", &prefs);
+            assert!(matches!(result, Err(RenderError::Io(_))));
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    mod decode_png_tests {
+        use super::*;
+
+        /// Verifies non-PNG data is rejected
+        #[test]
+        fn decode_png_rejects_invalid_data() {
+            assert!(decode_png(b"not a png").is_err());
+        }
+    }
+}
@@ -0,0 +1,549 @@
+//! Structurizr workspace JSON
+//!
+//! The workspace holds the model (people and software systems, with their
+//! containers and components nested inside and relationships listed on their
+//! source) and one view laid out as on the canvas, so it can be uploaded to
+//! Structurizr with the layout intact. Notes have no Structurizr equivalent
+//! and are left out.
+//!
+//! Structurizr needs every container inside a software system and every
+//! component inside a container. Containers without a system are put in one
+//! named after the diagram, and components without a container in a
+//! container of that name inside it.
+
+use crate::model::{ContainerType, Diagram, DiagramType, Direction, Element, ElementId, ElementType, Position};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use uuid::Uuid;
+use super::DiagramExporter;
+
+/// Structurizr draws boxes at 450 by 300 by default, about three times the canvas size
+const SCALE: f32 = 3.0;
+
+/// Space left above and to the left of the layout
+const MARGIN: f32 = 50.0;
+
+/// Exports diagrams to a Structurizr workspace with view layout
+pub struct StructurizrExporter;
+
+impl StructurizrExporter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// The workspace as a JSON value
+    pub fn workspace(&self, diagram: &Diagram) -> Value {
+        let diagram = &*diagram.in_active_view();
+        let model = Model::new(diagram);
+        let mut views = Map::new();
+        let (kind, view) = model.view();
+        views.insert(kind.to_string(), json!([view]));
+        views.insert(
+            "configuration".to_string(),
+            json!({
+                "styles": {
+                    "elements": [
+                        { "tag": "Person", "shape": "Person" },
+                        { "tag": "Database", "shape": "Cylinder" },
+                        { "tag": "Queue", "shape": "Pipe" },
+                    ]
+                }
+            }),
+        );
+        json!({
+            "name": diagram.name,
+            "description": diagram.description,
+            "model": model.model(),
+            "views": views,
+        })
+    }
+}
+
+impl Default for StructurizrExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiagramExporter for StructurizrExporter {
+    fn export(&self, diagram: &Diagram) -> String {
+        let mut output = serde_json::to_string_pretty(&self.workspace(diagram)).unwrap_or_default();
+        output.push('\n');
+        output
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "json"
+    }
+}
+
+/// The diagram's C4 elements with their Structurizr IDs and parents
+struct Model<'a> {
+    diagram: &'a Diagram,
+    /// Exported elements sorted by name, which is also the order of their IDs
+    elements: Vec<&'a Element>,
+    ids: HashMap<ElementId, String>,
+    relationship_ids: HashMap<Uuid, String>,
+    /// Software system for containers that are not in one, if any are
+    implicit_system: Option<String>,
+    /// Container for components that are not in one, if any are
+    implicit_container: Option<String>,
+}
+
+impl<'a> Model<'a> {
+    fn new(diagram: &'a Diagram) -> Self {
+        let elements = diagram.find_elements(|e| !e.is_note());
+        let ids: HashMap<ElementId, String> =
+            elements.iter().enumerate().map(|(i, e)| (e.id, (i + 1).to_string())).collect();
+        let mut next = elements.len() + 1;
+        let mut model = Self {
+            diagram,
+            elements,
+            ids,
+            relationship_ids: HashMap::new(),
+            implicit_system: None,
+            implicit_container: None,
+        };
+        let mut take_id = || {
+            next += 1;
+            (next - 1).to_string()
+        };
+        let loose_components = model
+            .elements
+            .iter()
+            .any(|e| matches!(e.element_type, ElementType::Component(_)) && model.parent_id(e).is_none());
+        if loose_components {
+            model.implicit_container = Some(take_id());
+        }
+        let loose_containers = model
+            .elements
+            .iter()
+            .any(|e| matches!(e.element_type, ElementType::Container(_)) && model.parent_id(e).is_none());
+        if loose_containers || loose_components {
+            model.implicit_system = Some(take_id());
+        }
+        for rel in &diagram.relationships {
+            if model.ids.contains_key(&rel.source_id) && model.ids.contains_key(&rel.target_id) {
+                let id = take_id();
+                model.relationship_ids.insert(rel.id, id);
+            }
+        }
+        model
+    }
+
+    /// ID of the exported system or container an element is part of, if it has one
+    fn parent_id(&self, element: &Element) -> Option<&str> {
+        let parent = self.diagram.get_element(element.parent()?)?;
+        let fits = match element.element_type {
+            ElementType::Container(_) => matches!(parent.element_type, ElementType::SoftwareSystem(_)),
+            ElementType::Component(_) => matches!(parent.element_type, ElementType::Container(_)),
+            _ => false,
+        };
+        self.ids.get(&parent.id).filter(|_| fits).map(String::as_str)
+    }
+
+    /// ID of the system or container an element is placed in, including the implicit ones
+    fn placed_in(&self, element: &Element) -> Option<&str> {
+        self.parent_id(element).or(match element.element_type {
+            ElementType::Container(_) => self.implicit_system.as_deref(),
+            ElementType::Component(_) => self.implicit_container.as_deref(),
+            _ => None,
+        })
+    }
+
+    /// Relationships leaving an element, as Structurizr stores them on their source
+    fn relationships_from(&self, id: &str) -> Vec<Value> {
+        self.diagram
+            .relationships
+            .iter()
+            .filter_map(|rel| {
+                let rel_id = self.relationship_ids.get(&rel.id)?;
+                // Structurizr relationships have one direction; both-way ones keep source to target
+                let (source, target) = match rel.direction {
+                    Direction::Back => (rel.target_id, rel.source_id),
+                    Direction::Forward | Direction::BiDirectional => (rel.source_id, rel.target_id),
+                };
+                (self.ids[&source] == id).then(|| {
+                    let mut value = json!({
+                        "id": rel_id,
+                        "sourceId": self.ids[&source],
+                        "destinationId": self.ids[&target],
+                        "description": rel.description,
+                        "tags": tags("Relationship", &rel.tags),
+                    });
+                    if let Some(technology) = rel.technology.as_deref().filter(|t| !t.is_empty()) {
+                        value["technology"] = json!(technology);
+                    }
+                    value
+                })
+            })
+            .collect()
+    }
+
+    /// An element and everything placed in it
+    fn element(&self, element: &Element) -> Value {
+        let id = &self.ids[&element.id];
+        let mut value = json!({
+            "id": id,
+            "name": element.name(),
+            "description": element.description(),
+        });
+        let base = match &element.element_type {
+            ElementType::Person(_) => "Element,Person",
+            ElementType::SoftwareSystem(_) => "Element,Software System",
+            ElementType::Container(data) => {
+                value["technology"] = json!(data.technology);
+                match data.container_type {
+                    ContainerType::Database => "Element,Container,Database",
+                    ContainerType::Queue => "Element,Container,Queue",
+                    _ => "Element,Container",
+                }
+            }
+            ElementType::Component(data) => {
+                value["technology"] = json!(data.technology);
+                "Element,Component"
+            }
+            ElementType::Note(_) => "Element",
+        };
+        value["tags"] = json!(tags(base, &element.tags));
+        if matches!(element.element_type, ElementType::Person(_) | ElementType::SoftwareSystem(_)) {
+            value["location"] = json!(if element.is_external() { "External" } else { "Internal" });
+        }
+        if let Some(url) = element.url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+            value["url"] = json!(url);
+        }
+        let parts = match element.element_type {
+            ElementType::SoftwareSystem(_) => "containers",
+            _ => "components",
+        };
+        self.add_parts(&mut value, id, parts);
+        value
+    }
+
+    /// Containers or components placed in `id` under `key`, and relationships leaving it
+    fn add_parts(&self, value: &mut Value, id: &str, key: &str) {
+        let mut parts: Vec<Value> = self
+            .elements
+            .iter()
+            .filter(|e| self.placed_in(e) == Some(id))
+            .map(|e| self.element(e))
+            .collect();
+        if self.implicit_system.as_deref() == Some(id)
+            && let Some(container) = &self.implicit_container
+        {
+            parts.push(self.implicit(container, "Element,Container"));
+        }
+        if !parts.is_empty() {
+            value[key] = json!(parts);
+        }
+        let relationships = self.relationships_from(id);
+        if !relationships.is_empty() {
+            value["relationships"] = json!(relationships);
+        }
+    }
+
+    /// The system or container holding elements that are not in one
+    fn implicit(&self, id: &str, tags: &str) -> Value {
+        let mut value = json!({
+            "id": id,
+            "name": if self.diagram.name.is_empty() { "Unnamed" } else { &self.diagram.name },
+            "description": "",
+            "tags": tags,
+        });
+        let parts = if tags == "Element,Software System" {
+            value["location"] = json!("Internal");
+            "containers"
+        } else {
+            "components"
+        };
+        self.add_parts(&mut value, id, parts);
+        value
+    }
+
+    fn model(&self) -> Value {
+        let of_type = |wanted: fn(&ElementType) -> bool| -> Vec<Value> {
+            self.elements.iter().filter(|e| wanted(&e.element_type)).map(|e| self.element(e)).collect()
+        };
+        let people = of_type(|t| matches!(t, ElementType::Person(_)));
+        let mut systems = of_type(|t| matches!(t, ElementType::SoftwareSystem(_)));
+        if let Some(id) = &self.implicit_system {
+            systems.push(self.implicit(id, "Element,Software System"));
+        }
+        json!({ "people": people, "softwareSystems": systems })
+    }
+
+    /// First system or container, by name, with something placed in it
+    fn first_holder(&self, holder: fn(&ElementType) -> bool, implicit: Option<&'a str>) -> Option<&str> {
+        self.elements
+            .iter()
+            .filter(|e| holder(&e.element_type))
+            .map(|e| self.ids[&e.id].as_str())
+            .chain(implicit)
+            .find(|id| self.elements.iter().any(|e| self.placed_in(e) == Some(id)))
+    }
+
+    /// The kind of view, its scope, and the deepest level of element it shows
+    ///
+    /// Levels run from 1 (people and systems) to 3 (components). A view that
+    /// needs a scope the diagram lacks falls back to the next level up.
+    fn scope(&self) -> (&'static str, Option<(&'static str, String)>, u8) {
+        let is_container = |t: &ElementType| matches!(t, ElementType::Container(_));
+        let is_system = |t: &ElementType| matches!(t, ElementType::SoftwareSystem(_));
+        let container = self.first_holder(is_container, self.implicit_container.as_deref()).map(str::to_string);
+        let system = self.first_holder(is_system, self.implicit_system.as_deref()).map(str::to_string);
+        let internal_system = self
+            .elements
+            .iter()
+            .find(|e| is_system(&e.element_type) && !e.is_external())
+            .map(|e| self.ids[&e.id].clone());
+        match self.diagram.diagram_type {
+            DiagramType::Dynamic => match (container, system) {
+                (Some(id), _) => ("dynamicViews", Some(("elementId", id)), 3),
+                (None, Some(id)) => ("dynamicViews", Some(("elementId", id)), 2),
+                (None, None) => ("dynamicViews", None, 1),
+            },
+            DiagramType::Component if container.is_some() => {
+                ("componentViews", container.map(|id| ("containerId", id)), 3)
+            }
+            DiagramType::Component | DiagramType::Container if system.is_some() => {
+                ("containerViews", system.map(|id| ("softwareSystemId", id)), 2)
+            }
+            _ => match internal_system {
+                Some(id) => ("systemContextViews", Some(("softwareSystemId", id)), 1),
+                None => ("systemLandscapeViews", None, 1),
+            },
+        }
+    }
+
+    /// The view's kind and the view, with element positions from the canvas
+    fn view(&self) -> (&'static str, Value) {
+        let (kind, scope, level) = self.scope();
+        let scope_id = scope.as_ref().map(|(_, id)| id.as_str());
+        let shown: Vec<&Element> = self
+            .elements
+            .iter()
+            .copied()
+            .filter(|e| {
+                let element_level = match e.element_type {
+                    ElementType::Container(_) => 2,
+                    ElementType::Component(_) => 3,
+                    _ => 1,
+                };
+                element_level <= level && Some(self.ids[&e.id].as_str()) != scope_id
+            })
+            .collect();
+
+        let min_x = shown.iter().map(|e| e.position.x).fold(f32::INFINITY, f32::min);
+        let min_y = shown.iter().map(|e| e.position.y).fold(f32::INFINITY, f32::min);
+        let place = |p: Position| {
+            (
+                ((p.x - min_x) * SCALE + MARGIN).round() as i64,
+                ((p.y - min_y) * SCALE + MARGIN).round() as i64,
+            )
+        };
+        let (mut width, mut height) = (0, 0);
+        let elements: Vec<Value> = shown
+            .iter()
+            .map(|e| {
+                let (x, y) = place(e.position);
+                let (right, bottom) = place(Position::new(e.position.x + e.size.width, e.position.y + e.size.height));
+                width = width.max(right);
+                height = height.max(bottom);
+                json!({ "id": self.ids[&e.id], "x": x, "y": y })
+            })
+            .collect();
+
+        let in_view = |id: ElementId| shown.iter().any(|e| e.id == id);
+        let steps: Vec<(Option<u32>, &crate::model::Relationship)> = if kind == "dynamicViews" {
+            self.diagram.steps().into_iter().map(|(step, rel)| (Some(step), rel)).collect()
+        } else {
+            self.diagram.relationships.iter().map(|rel| (None, rel)).collect()
+        };
+        let relationships: Vec<Value> = steps
+            .into_iter()
+            .filter(|(_, rel)| in_view(rel.source_id) && in_view(rel.target_id))
+            .filter_map(|(step, rel)| {
+                let mut value = json!({ "id": self.relationship_ids.get(&rel.id)? });
+                if !rel.waypoints.is_empty() {
+                    let vertices: Vec<Value> = rel
+                        .waypoints
+                        .iter()
+                        .map(|p| {
+                            let (x, y) = place(*p);
+                            json!({ "x": x, "y": y })
+                        })
+                        .collect();
+                    value["vertices"] = json!(vertices);
+                }
+                if let Some(step) = step {
+                    value["order"] = json!(step.to_string());
+                    value["description"] = json!(rel.description);
+                }
+                Some(value)
+            })
+            .collect();
+
+        let mut view = json!({
+            "key": kind.trim_end_matches("Views"),
+            "title": self.diagram.name,
+            "description": self.diagram.description,
+            "elements": elements,
+            "relationships": relationships,
+            "dimensions": { "width": width + MARGIN as i64, "height": height + MARGIN as i64 },
+        });
+        if let Some((field, id)) = scope {
+            view[field] = json!(id);
+        }
+        (kind, view)
+    }
+}
+
+/// Structurizr's comma-separated tags: the built-in ones, then the element's own
+fn tags(base: &str, own: &[String]) -> String {
+    std::iter::once(base)
+        .chain(own.iter().map(|t| t.trim()).filter(|t| !t.is_empty()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Relationship;
+
+    /// Container diagram with a customer, a shop system holding an API and a
+    /// database, and a loose container
+    fn shop() -> (Diagram, [ElementId; 4]) {
+        let mut diagram = Diagram::new("Shop", "Online shop", DiagramType::Container);
+        let user = Element::new(ElementType::external_person("Customer", "Buys things"), Position::new(0.0, 0.0));
+        let system = Element::new(ElementType::system("Shop", ""), Position::new(200.0, 0.0));
+        let mut api = Element::new(
+            ElementType::container("API", "", ContainerType::Microservice, "Rust"),
+            Position::new(100.0, 200.0),
+        );
+        let mut db = Element::new(
+            ElementType::container("Orders", "", ContainerType::Database, "PostgreSQL"),
+            Position::new(100.0, 400.0),
+        );
+        if let (ElementType::Container(api_data), ElementType::Container(db_data)) =
+            (&mut api.element_type, &mut db.element_type)
+        {
+            api_data.system = Some(system.id);
+            db_data.system = Some(system.id);
+        }
+        let ids = [user.id, system.id, api.id, db.id];
+        for element in [user, system, api, db] {
+            diagram.add_element(element);
+        }
+        let mut reads = Relationship::with_technology(ids[2], ids[3], "Reads", "SQL");
+        reads.waypoints = vec![Position::new(150.0, 350.0)];
+        diagram.add_relationship(Relationship::new(ids[0], ids[2], "Uses"));
+        diagram.add_relationship(reads);
+        (diagram, ids)
+    }
+
+    mod model_tests {
+        use super::*;
+
+        /// Verifies containers nest in their system and relationships sit on their source
+        #[test]
+        fn model_nests_containers_and_relationships() {
+            let (diagram, _) = shop();
+            let workspace = StructurizrExporter::new().workspace(&diagram);
+            let model = &workspace["model"];
+
+            let customer = &model["people"][0];
+            assert_eq!(customer["name"], "Customer");
+            assert_eq!(customer["location"], "External");
+            assert_eq!(customer["tags"], "Element,Person");
+            assert_eq!(customer["relationships"][0]["description"], "Uses");
+
+            let shop = &model["softwareSystems"][0];
+            assert_eq!(shop["name"], "Shop");
+            let containers = shop["containers"].as_array().unwrap();
+            assert_eq!(containers.len(), 2);
+            let api = &containers[0];
+            assert_eq!(api["technology"], "Rust");
+            assert_eq!(customer["relationships"][0]["destinationId"], api["id"]);
+            assert_eq!(api["relationships"][0]["technology"], "SQL");
+            assert_eq!(containers[1]["tags"], "Element,Container,Database");
+        }
+
+        /// Verifies notes are left out and loose containers get a system named after the diagram
+        #[test]
+        fn model_places_loose_containers() {
+            let mut diagram = Diagram::new("Payments", "", DiagramType::Container);
+            diagram.add_element(Element::new(
+                ElementType::container("Worker", "", ContainerType::Queue, ""),
+                Position::new(0.0, 0.0),
+            ));
+            diagram.add_element(Element::new(ElementType::note("Draft"), Position::new(0.0, 0.0)));
+            let workspace = StructurizrExporter::new().workspace(&diagram);
+
+            let systems = workspace["model"]["softwareSystems"].as_array().unwrap();
+            assert_eq!(systems.len(), 1);
+            assert_eq!(systems[0]["name"], "Payments");
+            assert_eq!(systems[0]["containers"][0]["name"], "Worker");
+            assert!(!workspace.to_string().contains("Draft"));
+            assert_eq!(workspace["views"]["containerViews"][0]["softwareSystemId"], systems[0]["id"]);
+        }
+    }
+
+    mod view_tests {
+        use super::*;
+
+        /// Verifies the view is scoped to the system and laid out from canvas positions
+        #[test]
+        fn container_view_keeps_layout() {
+            let (diagram, _) = shop();
+            let workspace = StructurizrExporter::new().workspace(&diagram);
+            let view = &workspace["views"]["containerViews"][0];
+            let shop = &workspace["model"]["softwareSystems"][0];
+            assert_eq!(view["softwareSystemId"], shop["id"]);
+
+            let elements = view["elements"].as_array().unwrap();
+            assert_eq!(elements.len(), 3, "the system in scope is not an element of its view");
+            let customer = &workspace["model"]["people"][0];
+            let api = &shop["containers"][0];
+            let at = |id: &Value| elements.iter().find(|e| e["id"] == *id).map(|e| (e["x"].clone(), e["y"].clone()));
+            assert_eq!(at(&customer["id"]), Some((json!(50), json!(50))));
+            assert_eq!(at(&api["id"]), Some((json!(350), json!(650))));
+
+            let routed = view["relationships"].as_array().unwrap().iter().find(|r| r.get("vertices").is_some()).unwrap();
+            assert_eq!(routed["vertices"], json!([{ "x": 500, "y": 1100 }]));
+        }
+
+        /// Verifies context views show people and systems, and fall back to a landscape without a system
+        #[test]
+        fn context_view_shows_people_and_systems() {
+            let (mut diagram, _) = shop();
+            diagram.diagram_type = DiagramType::SystemContext;
+            let workspace = StructurizrExporter::new().workspace(&diagram);
+            let view = &workspace["views"]["systemContextViews"][0];
+            assert_eq!(view["elements"].as_array().unwrap().len(), 1);
+            assert_eq!(view["key"], "systemContext");
+
+            diagram.elements.retain(|_, e| matches!(e.element_type, ElementType::Person(_)));
+            let workspace = StructurizrExporter::new().workspace(&diagram);
+            assert!(workspace["views"]["systemLandscapeViews"][0]["elements"].is_array());
+        }
+
+        /// Verifies dynamic views number their relationships in step order
+        #[test]
+        fn dynamic_view_orders_steps() {
+            let (mut diagram, _) = shop();
+            diagram.diagram_type = DiagramType::Dynamic;
+            diagram.relationships[0].order = Some(2);
+            diagram.relationships[1].order = Some(1);
+            let workspace = StructurizrExporter::new().workspace(&diagram);
+            let view = &workspace["views"]["dynamicViews"][0];
+            let steps: Vec<(&str, &str)> = view["relationships"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|r| (r["order"].as_str().unwrap(), r["description"].as_str().unwrap()))
+                .collect();
+            assert_eq!(steps, vec![("1", "Reads"), ("2", "Uses")]);
+        }
+    }
+}
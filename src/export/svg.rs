@@ -0,0 +1,659 @@
+use crate::model::{bezier_midpoint, draw_order, curve_control_points, loop_curve, polyline_midpoint, Diagram, Direction, Element, ElementType, LineStyle, Position, Relationship, Side, Size, TextLabel};
+use crate::style::{BorderStyle, Color, Shape, Stylesheet, DEFAULT_LINE_COLOR};
+use super::DiagramExporter;
+
+/// Space left around the diagram's bounding box
+const MARGIN: f32 = 20.0;
+
+/// Exports diagrams to a standalone SVG image laid out as on the canvas
+///
+/// Elements are drawn in the shapes and colours a stylesheet gives them, the
+/// built-in one unless another is set. Elements with a URL are wrapped in
+/// `<a href>` so the image is clickable when embedded in a browser or wiki.
+pub struct SvgExporter {
+    stylesheet: Stylesheet,
+}
+
+impl SvgExporter {
+    pub fn new() -> Self {
+        Self::with_stylesheet(Stylesheet::default())
+    }
+
+    /// Draw elements and lines as `stylesheet` styles them on the canvas
+    pub fn with_stylesheet(stylesheet: Stylesheet) -> Self {
+        Self { stylesheet }
+    }
+
+    fn escape_string(&self, s: &str) -> String {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&apos;")
+    }
+
+    fn generate_element(&self, element: &Element, origin: Position) -> String {
+        let x = element.position.x - origin.x;
+        let y = element.position.y - origin.y;
+        let style = self.stylesheet.element_style(element);
+        let stroke = format!(
+            "stroke=\"{}\" stroke-width=\"2\"{}",
+            style.border.to_hex(),
+            match style.border_style {
+                BorderStyle::Solid => "",
+                BorderStyle::Dashed => " stroke-dasharray=\"8 4\"",
+                BorderStyle::Dotted => " stroke-dasharray=\"2 3\"",
+            }
+        );
+        let mut shape = String::from("  <g>\n");
+        shape.push_str(&outline(style.shape, Position::new(x, y), element.size, &style.background.to_hex(), &stroke));
+
+        // Notes have a folded corner, as on the canvas
+        if element.is_note() {
+            let right = x + element.size.width;
+            shape.push_str(&format!(
+                "    <polygon points=\"{},{} {},{} {},{}\" fill=\"{}\" fill-opacity=\"0.5\"/>\n",
+                right - 14.0, y, right, y + 14.0, right - 14.0, y + 14.0, style.border.to_hex()
+            ));
+        }
+
+        let text = style.text.to_hex();
+        let name_style = if element.is_note() {
+            format!("font-size=\"12\" fill=\"{}\"", text)
+        } else {
+            format!("font-weight=\"bold\" font-size=\"13\" fill=\"{}\"", text)
+        };
+        let mut lines = vec![(element.name().to_string(), name_style)];
+        if let ElementType::Container(data) = &element.element_type
+            && !data.technology.is_empty()
+        {
+            lines.push((format!("[{}]", data.technology), format!("font-size=\"10\" fill=\"{}\"", text)));
+        }
+        if !element.description().is_empty() {
+            let description = format!("font-size=\"10\" fill=\"{}\"", style.description.to_hex());
+            lines.push((element.description().to_string(), description));
+        }
+        let center_x = x + element.size.width * 0.5;
+        // Text starts below the top cap of a cylinder
+        let cap = if style.shape == Shape::Cylinder { style.shape.cap_radius(element.size).height } else { 0.0 };
+        let mut line_y = y + 24.0 + cap;
+        for (text, style) in lines {
+            for line in text.lines() {
+                shape.push_str(&format!(
+                    "    <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" {}>{}</text>\n",
+                    center_x, line_y, style, self.escape_string(line)
+                ));
+                line_y += 16.0;
+            }
+        }
+        shape.push_str("  </g>\n");
+
+        match &element.url {
+            Some(url) if !url.trim().is_empty() => {
+                let indented: String = shape.lines().map(|line| format!("  {}\n", line)).collect();
+                format!(
+                    "  <a href=\"{}\" target=\"_blank\">\n{}  </a>\n",
+                    self.escape_string(url.trim()),
+                    indented
+                )
+            }
+            _ => shape,
+        }
+    }
+
+    fn generate_relationship(&self, diagram: &Diagram, rel: &Relationship, origin: Position) -> Option<String> {
+        let source = diagram.get_element(rel.source_id)?;
+        let target = diagram.get_element(rel.target_id)?;
+
+        // Unpinned ends point at the nearest bend, or else the other element
+        let start = match rel.source_anchor {
+            Some(anchor) => anchor.point(source.position, source.size),
+            None => self.element_edge_point(source, rel.waypoints.first().copied().unwrap_or(center(target))),
+        };
+        let end = match rel.target_anchor {
+            Some(anchor) => anchor.point(target.position, target.size),
+            None => self.element_edge_point(target, rel.waypoints.last().copied().unwrap_or(center(source))),
+        };
+        let (x1, y1) = (start.x - origin.x, start.y - origin.y);
+        let (x2, y2) = (end.x - origin.x, end.y - origin.y);
+        let color = self.stylesheet.line_color(rel);
+        let arrow = marker_id(color);
+        let markers = match rel.direction {
+            Direction::Forward => format!("marker-end=\"url(#{})\"", arrow),
+            Direction::Back => format!("marker-start=\"url(#{})\"", arrow),
+            Direction::BiDirectional => format!("marker-start=\"url(#{0})\" marker-end=\"url(#{0})\"", arrow),
+        };
+        let stroke = format!("stroke=\"{}\" stroke-width=\"2\" {}", color.to_hex(), markers);
+
+        let (mut output, label_x, label_y) = if !rel.waypoints.is_empty() {
+            let route: Vec<Position> = std::iter::once(start)
+                .chain(rel.waypoints.iter().copied())
+                .chain(std::iter::once(end))
+                .map(|p| Position::new(p.x - origin.x, p.y - origin.y))
+                .collect();
+            let points: Vec<String> = route.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+            let polyline = format!(
+                "  <polyline points=\"{}\" fill=\"none\" {}/>\n",
+                points.join(" "),
+                stroke
+            );
+            let middle = polyline_midpoint(&route);
+            (polyline, middle.x, middle.y)
+        } else if rel.is_self_loop() {
+            let curve = loop_curve(source.position, source.size, rel.source_anchor, rel.target_anchor);
+            bezier_path(curve.map(|p| Position::new(p.x - origin.x, p.y - origin.y)), &stroke)
+        } else if rel.line_style == Some(LineStyle::Curved) {
+            let source_side = rel
+                .source_anchor
+                .map_or_else(|| Side::nearest(source.position, source.size, start), |a| a.side);
+            let target_side = rel
+                .target_anchor
+                .map_or_else(|| Side::nearest(target.position, target.size, end), |a| a.side);
+            let [c1, c2] = curve_control_points(start, source_side, end, target_side);
+            bezier_path([start, c1, c2, end].map(|p| Position::new(p.x - origin.x, p.y - origin.y)), &stroke)
+        } else {
+            let line = format!(
+                "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {}/>\n",
+                x1, y1, x2, y2, stroke
+            );
+            (line, (x1 + x2) * 0.5, (y1 + y2) * 0.5)
+        };
+        let label = rel.label_position(Position::new(label_x, label_y));
+        let (label_x, label_y) = (label.x, label.y);
+        if !rel.description.is_empty() {
+            output.push_str(&format!(
+                "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"10\" fill=\"#3c3c3c\">{}</text>\n",
+                label_x,
+                label_y,
+                self.escape_string(&rel.description)
+            ));
+        }
+        Some(output)
+    }
+
+    /// Where a line towards `target` meets the element's drawn outline
+    fn element_edge_point(&self, element: &Element, target: Position) -> Position {
+        let on_box = edge_point(element.position, element.size, target);
+        match self.stylesheet.element_style(element).shape {
+            Shape::Rectangle | Shape::Rounded => on_box,
+            shape => shape.exit_point(element.position, element.size, target).unwrap_or(on_box),
+        }
+    }
+
+    /// One `<text>` per line, with the first line's top at the label's position
+    fn generate_label(&self, label: &TextLabel, origin: Position) -> String {
+        let x = label.position.x - origin.x;
+        let mut y = label.position.y - origin.y + label.font_size;
+        let mut output = String::new();
+        for line in label.text.lines() {
+            output.push_str(&format!(
+                "  <text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{}\">{}</text>\n",
+                x, y, label.font_size, LABEL_COLOR.to_hex(), self.escape_string(line)
+            ));
+            y += label.font_size * LINE_HEIGHT;
+        }
+        output
+    }
+}
+
+/// Colour of free text labels, as on the canvas
+const LABEL_COLOR: Color = Color::gray(40);
+
+/// Distance between the baselines of a label's lines, relative to its font size
+const LINE_HEIGHT: f32 = 1.2;
+
+/// Rough box taken up by a label, as its top-left and bottom-right corners
+///
+/// There is no font to measure here, so characters are taken to be 0.6 of
+/// the font size wide; enough to keep labels inside the image.
+fn label_bounds(label: &TextLabel) -> [Position; 2] {
+    let lines = label.text.lines().count().max(1) as f32;
+    let widest = label.text.lines().map(|l| l.chars().count()).max().unwrap_or(0) as f32;
+    let size = Size::new(widest * label.font_size * 0.6, lines * label.font_size * LINE_HEIGHT);
+    [label.position, Position::new(label.position.x + size.width, label.position.y + size.height)]
+}
+
+/// ID of the arrowhead marker in a line colour; `arrow` for the default one
+fn marker_id(color: Color) -> String {
+    if color == DEFAULT_LINE_COLOR {
+        "arrow".to_string()
+    } else {
+        format!("arrow-{}", color.to_hex().trim_start_matches('#'))
+    }
+}
+
+/// An element's shape filling the box at `position`
+///
+/// Cylinders and pipes are a filled outline plus the rim of their near end,
+/// drawn with `stroke` but no fill.
+fn outline(shape: Shape, position: Position, size: Size, fill: &str, stroke: &str) -> String {
+    let (x, y, w, h) = (position.x, position.y, size.width, size.height);
+    let radius = shape.cap_radius(size);
+    let (rx, ry) = (radius.width, radius.height);
+    match shape {
+        Shape::Rectangle => format!(
+            "    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" {}/>\n",
+            x, y, w, h, fill, stroke
+        ),
+        Shape::Rounded => format!(
+            "    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"4\" fill=\"{}\" {}/>\n",
+            x, y, w, h, fill, stroke
+        ),
+        Shape::Ellipse => format!(
+            "    <ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" fill=\"{}\" {}/>\n",
+            x + w * 0.5, y + h * 0.5, w * 0.5, h * 0.5, fill, stroke
+        ),
+        Shape::Cylinder => format!(
+            "    <path d=\"M {x} {top} A {rx} {ry} 0 0 1 {right} {top} V {bottom} A {rx} {ry} 0 0 1 {x} {bottom} Z\" fill=\"{fill}\" {stroke}/>\n    <path d=\"M {x} {top} A {rx} {ry} 0 0 0 {right} {top}\" fill=\"none\" {stroke}/>\n",
+            top = y + ry,
+            bottom = y + h - ry,
+            right = x + w,
+        ),
+        Shape::Pipe => format!(
+            "    <path d=\"M {left} {y} H {right} A {rx} {ry} 0 0 1 {right} {bottom} H {left} A {rx} {ry} 0 0 1 {left} {y} Z\" fill=\"{fill}\" {stroke}/>\n    <path d=\"M {right} {y} A {rx} {ry} 0 0 0 {right} {bottom}\" fill=\"none\" {stroke}/>\n",
+            left = x + rx,
+            right = x + w - rx,
+            bottom = y + h,
+        ),
+    }
+}
+
+/// A cubic bezier `<path>` and the point halfway along it, for the label
+fn bezier_path(curve: [Position; 4], stroke: &str) -> (String, f32, f32) {
+    let [start, c1, c2, end] = curve;
+    let path = format!(
+        "  <path d=\"M {} {} C {} {}, {} {}, {} {}\" fill=\"none\" {}/>\n",
+        start.x, start.y, c1.x, c1.y, c2.x, c2.y, end.x, end.y, stroke
+    );
+    let middle = bezier_midpoint(curve);
+    (path, middle.x, middle.y)
+}
+
+impl Default for SvgExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn center(element: &Element) -> Position {
+    Position::new(
+        element.position.x + element.size.width * 0.5,
+        element.position.y + element.size.height * 0.5,
+    )
+}
+
+/// Point where the line from the element's centre towards `target` leaves its box
+fn edge_point(position: Position, size: Size, target: Position) -> Position {
+    let cx = position.x + size.width * 0.5;
+    let cy = position.y + size.height * 0.5;
+    let (dx, dy) = (target.x - cx, target.y - cy);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length < 0.001 {
+        return Position::new(cx, cy);
+    }
+    let (dx, dy) = (dx / length, dy / length);
+    let tx = if dx.abs() > 0.001 { size.width * 0.5 / dx.abs() } else { f32::INFINITY };
+    let ty = if dy.abs() > 0.001 { size.height * 0.5 / dy.abs() } else { f32::INFINITY };
+    let distance = tx.min(ty);
+    Position::new(cx + dx * distance, cy + dy * distance)
+}
+
+impl SvgExporter {
+    /// Everything inside the root `<svg>` element, and the image's width and height
+    pub(crate) fn export_body(&self, diagram: &Diagram) -> (String, f32, f32) {
+        let diagram = &*diagram.in_active_view();
+        let elements = draw_order(diagram.elements.values());
+
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+        if let Some(first) = elements.first() {
+            (min_x, min_y) = (first.position.x, first.position.y);
+            (max_x, max_y) = (min_x, min_y);
+        }
+        for element in &elements {
+            min_x = min_x.min(element.position.x);
+            min_y = min_y.min(element.position.y);
+            max_x = max_x.max(element.position.x + element.size.width);
+            max_y = max_y.max(element.position.y + element.size.height);
+        }
+        // Loops stay inside their control points, so those are enough to fit them in
+        let loops = diagram
+            .relationships
+            .iter()
+            .filter(|r| r.is_self_loop() && r.waypoints.is_empty())
+            .filter_map(|r| {
+                let element = diagram.get_element(r.source_id)?;
+                Some(loop_curve(element.position, element.size, r.source_anchor, r.target_anchor))
+            })
+            .collect::<Vec<_>>();
+        let loop_points = loops.iter().flatten();
+        let labels: Vec<[Position; 2]> = diagram.labels.iter().filter(|l| !l.text.trim().is_empty()).map(label_bounds).collect();
+        let label_points = labels.iter().flatten();
+        for point in diagram.relationships.iter().flat_map(|r| &r.waypoints).chain(loop_points).chain(label_points) {
+            min_x = min_x.min(point.x);
+            min_y = min_y.min(point.y);
+            max_x = max_x.max(point.x);
+            max_y = max_y.max(point.y);
+        }
+        let origin = Position::new(min_x - MARGIN, min_y - MARGIN);
+        let width = max_x - min_x + MARGIN * 2.0;
+        let height = max_y - min_y + MARGIN * 2.0;
+
+        let mut output = String::new();
+        if !diagram.name.is_empty() {
+            output.push_str(&format!("  <title>{}</title>\n", self.escape_string(&diagram.name)));
+        }
+        let metadata = diagram.metadata.header_lines();
+        if !metadata.is_empty() {
+            output.push_str(&format!("  <desc>{}</desc>\n", self.escape_string(&metadata.join("; "))));
+        }
+        // One arrowhead per line colour, as markers can't take the colour of their line everywhere
+        let mut colors = vec![DEFAULT_LINE_COLOR];
+        for rel in &diagram.relationships {
+            let color = self.stylesheet.line_color(rel);
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+        output.push_str("  <defs>\n");
+        for color in colors {
+            output.push_str(&format!(
+                "    <marker id=\"{}\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\">\n      <path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"{}\"/>\n    </marker>\n",
+                marker_id(color),
+                color.to_hex()
+            ));
+        }
+        output.push_str("  </defs>\n");
+
+        for element in &elements {
+            output.push_str(&self.generate_element(element, origin));
+        }
+        for rel in &diagram.relationships {
+            if let Some(line) = self.generate_relationship(diagram, rel, origin) {
+                output.push_str(&line);
+            }
+        }
+        for label in &diagram.labels {
+            output.push_str(&self.generate_label(label, origin));
+        }
+
+        (output, width, height)
+    }
+}
+
+impl DiagramExporter for SvgExporter {
+    fn export(&self, diagram: &Diagram) -> String {
+        let (body, width, height) = self.export_body(diagram);
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"sans-serif\">\n{2}</svg>\n",
+            width, height, body
+        )
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "svg"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, ElementId};
+
+    fn element_at(name: &str, x: f32, y: f32) -> Element {
+        Element::new(ElementType::system(name, ""), Position::new(x, y))
+    }
+
+    mod escape_string_tests {
+        use super::*;
+
+        /// Verifies escape_string escapes XML special characters
+        #[test]
+        fn escape_string_escapes_xml() {
+            let exporter = SvgExporter::new();
+            assert_eq!(
+                exporter.escape_string(r#"<a & "b">"#),
+                "&lt;a &amp; &quot;b&quot;&gt;"
+            );
+        }
+    }
+
+    mod generate_element_tests {
+        use super::*;
+
+        /// Verifies elements without a URL are not wrapped in a link
+        #[test]
+        fn generate_element_without_url() {
+            let exporter = SvgExporter::new();
+            let element = element_at("Billing", 0.0, 0.0);
+            let svg = exporter.generate_element(&element, Position::new(0.0, 0.0));
+            assert!(svg.contains(">Billing</text>"));
+            assert!(!svg.contains("<a "));
+        }
+
+        /// Verifies elements with a URL are wrapped in an escaped href
+        #[test]
+        fn generate_element_wraps_url_in_link() {
+            let exporter = SvgExporter::new();
+            let mut element = element_at("Billing", 0.0, 0.0);
+            element.url = Some("https://example.com/?a=1&b=2".to_string());
+            let svg = exporter.generate_element(&element, Position::new(0.0, 0.0));
+            assert!(svg.starts_with("  <a href=\"https://example.com/?a=1&amp;b=2\" target=\"_blank\">\n"));
+            assert!(svg.ends_with("  </a>\n"));
+            assert!(svg.contains("<rect"));
+        }
+
+        /// Verifies positions are relative to the export origin
+        #[test]
+        fn generate_element_offsets_by_origin() {
+            let exporter = SvgExporter::new();
+            let element = element_at("Billing", 120.0, 50.0);
+            let svg = exporter.generate_element(&element, Position::new(100.0, 30.0));
+            assert!(svg.contains("<rect x=\"20\" y=\"20\""));
+        }
+    }
+
+    mod shape_tests {
+        use super::*;
+        use crate::model::ContainerType;
+        use crate::style::{ElementKind, ElementStyle, TagRule};
+
+        /// Verifies databases are cylinders and queues pipes, each with the rim of their near end
+        #[test]
+        fn containers_are_drawn_in_their_shapes() {
+            let exporter = SvgExporter::new();
+            let db = Element::new(
+                ElementType::container("Orders", "", ContainerType::Database, "PostgreSQL"),
+                Position::new(0.0, 0.0),
+            );
+            let svg = exporter.generate_element(&db, Position::new(0.0, 0.0));
+            assert!(svg.contains("<path d=\"M 0 12 A 80 12 0 0 1 160 12 V 88 A 80 12 0 0 1 0 88 Z\" fill=\"#c8ffc8\""));
+            assert!(svg.contains("<path d=\"M 0 12 A 80 12 0 0 0 160 12\" fill=\"none\""));
+            assert!(svg.contains("y=\"36\""), "text starts below the top cap");
+
+            let mut queue = Element::new(
+                ElementType::container("Events", "", ContainerType::Queue, "Kafka"),
+                Position::new(0.0, 0.0),
+            );
+            queue.size = Size::new(200.0, 100.0);
+            let svg = exporter.generate_element(&queue, Position::new(0.0, 0.0));
+            assert!(svg.contains("<path d=\"M 16 0 H 184 A 16 50 0 0 1 184 100 H 16 A 16 50 0 0 1 16 0 Z\""), "{}", svg);
+            assert!(svg.contains("<path d=\"M 184 0 A 16 50 0 0 0 184 100\" fill=\"none\""));
+        }
+
+        /// Verifies lines to a database stop at its curved top rather than the corner of its box
+        #[test]
+        fn lines_meet_curved_outlines() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::Container);
+            let api = element_at("API", 0.0, 0.0);
+            let db = Element::new(
+                ElementType::container("Orders", "", ContainerType::Database, ""),
+                Position::new(200.0, 200.0),
+            );
+            let (api_id, db_id) = (api.id, db.id);
+            diagram.add_element(api);
+            diagram.add_element(db);
+            diagram.add_relationship(Relationship::new(api_id, db_id, "Reads"));
+            let exporter = SvgExporter::new();
+            let end = exporter.element_edge_point(diagram.get_element(db_id).unwrap(), center(diagram.get_element(api_id).unwrap()));
+            let on_box = edge_point(Position::new(200.0, 200.0), Size::new(160.0, 100.0), Position::new(80.0, 50.0));
+            assert!(end.x > on_box.x && end.y > on_box.y, "{:?} vs {:?}", end, on_box);
+        }
+
+        /// Verifies stylesheet colours, shapes, borders and line colours are used
+        #[test]
+        fn stylesheet_is_applied() {
+            let mut stylesheet = Stylesheet::default();
+            stylesheet.elements.insert(
+                ElementKind::System,
+                ElementStyle {
+                    background: Color::from_hex("#112233"),
+                    shape: Some(Shape::Ellipse),
+                    border_style: Some(BorderStyle::Dashed),
+                    ..ElementStyle::default()
+                },
+            );
+            stylesheet.tag_rules.push(TagRule {
+                tag: "async".to_string(),
+                element: ElementStyle::default(),
+                line: Color::from_hex("#ff0000"),
+            });
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let a = element_at("A", 0.0, 0.0);
+            let b = element_at("B", 300.0, 0.0);
+            let (a_id, b_id) = (a.id, b.id);
+            diagram.add_element(a);
+            diagram.add_element(b);
+            let mut rel = Relationship::new(a_id, b_id, "Notifies");
+            rel.tags = vec!["async".to_string()];
+            diagram.add_relationship(rel);
+
+            let svg = SvgExporter::with_stylesheet(stylesheet).export(&diagram);
+            assert!(svg.contains("<ellipse cx=\"100\" cy=\"70\" rx=\"80\" ry=\"50\" fill=\"#112233\" stroke=\"#969696\" stroke-width=\"2\" stroke-dasharray=\"8 4\"/>"));
+            assert!(svg.contains("<marker id=\"arrow-ff0000\""));
+            assert!(svg.contains("stroke=\"#ff0000\" stroke-width=\"2\" marker-end=\"url(#arrow-ff0000)\""));
+            // the line leaves the ellipse at its side, not the box
+            assert!(svg.contains("<line x1=\"180\" y1=\"70\" x2=\"320\" y2=\"70\""));
+        }
+    }
+
+    mod export_tests {
+        use super::*;
+
+        /// Verifies the document is sized to the elements plus a margin
+        #[test]
+        fn export_sizes_to_bounding_box() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            diagram.add_element(element_at("A", 100.0, 100.0));
+            diagram.add_element(element_at("B", 400.0, 300.0));
+            let svg = SvgExporter::new().export(&diagram);
+            assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"500\" height=\"340\""));
+            assert!(svg.contains("<title>Test</title>"));
+            assert!(svg.trim_end().ends_with("</svg>"));
+        }
+
+        /// Verifies relationships become arrowed lines with labels
+        #[test]
+        fn export_includes_relationships() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let a = element_at("A", 0.0, 0.0);
+            let b = element_at("B", 300.0, 0.0);
+            let (a_id, b_id): (ElementId, ElementId) = (a.id, b.id);
+            diagram.add_element(a);
+            diagram.add_element(b);
+            diagram.add_relationship(Relationship::new(a_id, b_id, "Calls"));
+            let svg = SvgExporter::new().export(&diagram);
+            assert!(svg.contains("<line x1=\"180\" y1=\"70\" x2=\"320\" y2=\"70\""));
+            assert!(svg.contains(">Calls</text>"));
+        }
+
+        /// Verifies a relationship to the same element is drawn as a loop inside the image
+        #[test]
+        fn export_self_loop() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let a = element_at("A", 0.0, 0.0);
+            let a_id = a.id;
+            diagram.add_element(a);
+            diagram.add_relationship(Relationship::new(a_id, a_id, "Runs nightly job"));
+            let svg = SvgExporter::new().export(&diagram);
+            assert!(svg.contains("<path d=\"M 148 70 C 148 20, 230 90, 180 90\""));
+            assert!(svg.contains("height=\"190\""));
+            assert!(!svg.contains("NaN"));
+            assert!(svg.contains(">Runs nightly job</text>"));
+        }
+
+        /// Verifies curved relationships become bezier paths
+        #[test]
+        fn export_curved_relationship() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let a = element_at("A", 0.0, 0.0);
+            let b = element_at("B", 300.0, 0.0);
+            let (a_id, b_id): (ElementId, ElementId) = (a.id, b.id);
+            diagram.add_element(a);
+            diagram.add_element(b);
+            let mut rel = Relationship::new(a_id, b_id, "Calls");
+            rel.line_style = Some(LineStyle::Curved);
+            diagram.add_relationship(rel);
+            let svg = SvgExporter::new().export(&diagram);
+            assert!(svg.contains("<path d=\"M 180 70 C 236 70, 264 70, 320 70\""));
+            assert!(svg.contains("<text x=\"250\" y=\"70\""));
+        }
+
+        /// Verifies routed relationships become polylines through their bends
+        #[test]
+        fn export_routed_relationship() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let a = element_at("A", 0.0, 0.0);
+            let b = element_at("B", 300.0, 0.0);
+            let (a_id, b_id): (ElementId, ElementId) = (a.id, b.id);
+            diagram.add_element(a);
+            diagram.add_element(b);
+            let mut rel = Relationship::new(a_id, b_id, "Calls");
+            rel.waypoints = vec![Position::new(80.0, 300.0), Position::new(380.0, 300.0)];
+            diagram.add_relationship(rel);
+            let svg = SvgExporter::new().export(&diagram);
+            assert!(svg.contains("<polyline points=\"100,120 100,320 400,320 400,120\""));
+            assert!(svg.contains("<text x=\"250\" y=\"320\""));
+        }
+
+        /// Verifies bidirectional relationships get an arrowhead at the start too
+        #[test]
+        fn export_bidirectional_relationship() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let a = element_at("A", 0.0, 0.0);
+            let b = element_at("B", 300.0, 0.0);
+            let (a_id, b_id): (ElementId, ElementId) = (a.id, b.id);
+            diagram.add_element(a);
+            diagram.add_element(b);
+            let mut rel = Relationship::new(a_id, b_id, "Syncs");
+            rel.direction = Direction::BiDirectional;
+            diagram.add_relationship(rel);
+            let svg = SvgExporter::new().export(&diagram);
+            assert!(svg.contains("marker-start=\"url(#arrow)\" marker-end=\"url(#arrow)\""));
+
+            diagram.relationships[0].direction = Direction::Back;
+            let svg = SvgExporter::new().export(&diagram);
+            assert!(svg.contains("marker-start=\"url(#arrow)\"/>"));
+        }
+
+        /// Verifies free text labels are drawn and fit inside the image
+        #[test]
+        fn export_text_labels() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            diagram.add_element(element_at("A", 0.0, 0.0));
+            let mut label = TextLabel::new("Payments\n<area>", Position::new(0.0, 200.0));
+            label.font_size = 10.0;
+            diagram.add_label(label);
+            let svg = SvgExporter::new().export(&diagram);
+            assert!(svg.contains("<text x=\"20\" y=\"230\" font-size=\"10\" fill=\"#282828\">Payments</text>"));
+            assert!(svg.contains("<text x=\"20\" y=\"242\" font-size=\"10\" fill=\"#282828\">&lt;area&gt;</text>"));
+            assert!(svg.contains("height=\"264\""));
+        }
+
+        /// Verifies an empty diagram still produces a valid document
+        #[test]
+        fn export_empty_diagram() {
+            let svg = SvgExporter::new().export(&Diagram::default());
+            assert!(svg.contains("width=\"40\" height=\"40\""));
+        }
+    }
+}
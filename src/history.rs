@@ -0,0 +1,187 @@
+//! Undo/redo history
+//!
+//! The history keeps whole-diagram snapshots. After each batch of
+//! [`DiagramEvent`]s the state *before* the batch is pushed onto the undo
+//! stack, so every user action — whichever code path made it — becomes one
+//! undo step without each mutation having to know how to invert itself.
+
+use crate::model::{Diagram, DiagramEvent};
+
+/// Maximum number of undo steps kept by default
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Undo and redo stacks of diagram snapshots
+#[derive(Debug, Clone)]
+pub struct History {
+    undo: Vec<Diagram>,
+    redo: Vec<Diagram>,
+    /// The diagram as of the last recorded change
+    current: Diagram,
+    /// Edit that the last undo step can absorb, so typing a name is one step
+    coalesce_key: Option<DiagramEvent>,
+    limit: usize,
+}
+
+impl History {
+    /// Start an empty history at the given diagram state
+    pub fn new(diagram: &Diagram) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            current: diagram.clone(),
+            coalesce_key: None,
+            limit: DEFAULT_HISTORY_LIMIT,
+        }
+    }
+
+    pub fn with_limit(diagram: &Diagram, limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            ..Self::new(diagram)
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Record that `diagram` changed by `events` since the last call
+    ///
+    /// Consecutive batches that only update the same element or relationship,
+    /// or only the diagram's properties, are merged into one undo step.
+    pub fn record(&mut self, diagram: &Diagram, events: &[DiagramEvent]) {
+        if events.is_empty() {
+            return;
+        }
+
+        let key = match events {
+            [event @ (DiagramEvent::ElementUpdated(_)
+            | DiagramEvent::RelationshipUpdated(_)
+            | DiagramEvent::GroupsChanged(_)
+            | DiagramEvent::LabelsChanged(_)
+            | DiagramEvent::PropertiesChanged)] => {
+                Some(event.clone())
+            }
+            _ => None,
+        };
+        let previous = std::mem::replace(&mut self.current, diagram.clone());
+        if key.is_none() || key != self.coalesce_key || self.undo.is_empty() {
+            self.undo.push(previous);
+            if self.undo.len() > self.limit {
+                self.undo.remove(0);
+            }
+        }
+        self.coalesce_key = key;
+        self.redo.clear();
+    }
+
+    /// Step back, returning the diagram to restore
+    pub fn undo(&mut self) -> Option<Diagram> {
+        let previous = self.undo.pop()?;
+        self.redo.push(std::mem::replace(&mut self.current, previous.clone()));
+        self.coalesce_key = None;
+        Some(previous)
+    }
+
+    /// Step forward again, returning the diagram to restore
+    pub fn redo(&mut self) -> Option<Diagram> {
+        let next = self.redo.pop()?;
+        self.undo.push(std::mem::replace(&mut self.current, next.clone()));
+        self.coalesce_key = None;
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Element, ElementType, Position};
+
+    fn add_person(diagram: &mut Diagram, name: &str) -> crate::model::ElementId {
+        let element = Element::new(ElementType::person(name, ""), Position::new(0.0, 0.0));
+        let id = element.id;
+        diagram.add_element(element);
+        id
+    }
+
+    /// Record the diagram's pending events, as the app does once per frame
+    fn commit(history: &mut History, diagram: &mut Diagram) {
+        let events = diagram.take_events();
+        history.record(diagram, &events);
+    }
+
+    mod undo_redo_tests {
+        use super::*;
+
+        /// Verifies undo and redo walk back and forth through recorded changes
+        #[test]
+        fn undo_redo_restores_snapshots() {
+            let mut diagram = Diagram::default();
+            let mut history = History::new(&diagram);
+            assert!(!history.can_undo());
+
+            add_person(&mut diagram, "A");
+            commit(&mut history, &mut diagram);
+            add_person(&mut diagram, "B");
+            commit(&mut history, &mut diagram);
+
+            assert_eq!(history.undo().unwrap().elements.len(), 1);
+            assert_eq!(history.undo().unwrap().elements.len(), 0);
+            assert!(history.undo().is_none());
+            assert_eq!(history.redo().unwrap().elements.len(), 1);
+            assert!(history.can_redo());
+        }
+
+        /// Verifies a new change clears the redo stack
+        #[test]
+        fn record_clears_redo() {
+            let mut diagram = Diagram::default();
+            let mut history = History::new(&diagram);
+            add_person(&mut diagram, "A");
+            commit(&mut history, &mut diagram);
+            diagram = history.undo().unwrap();
+
+            add_person(&mut diagram, "B");
+            commit(&mut history, &mut diagram);
+
+            assert!(!history.can_redo());
+        }
+
+        /// Verifies repeated edits of one element become a single undo step
+        #[test]
+        fn record_coalesces_updates_of_same_element() {
+            let mut diagram = Diagram::default();
+            let id = add_person(&mut diagram, "A");
+            diagram.take_events();
+            let mut history = History::new(&diagram);
+
+            for name in ["Al", "Ali", "Alice"] {
+                diagram.update_element(id, |e| e.set_name(name.to_string()));
+                commit(&mut history, &mut diagram);
+            }
+
+            let restored = history.undo().unwrap();
+            assert_eq!(restored.get_element(id).unwrap().name(), "A");
+            assert!(!history.can_undo());
+        }
+
+        /// Verifies the oldest steps are dropped beyond the limit
+        #[test]
+        fn record_respects_limit() {
+            let mut diagram = Diagram::default();
+            let mut history = History::with_limit(&diagram, 2);
+            for name in ["A", "B", "C"] {
+                add_person(&mut diagram, name);
+                commit(&mut history, &mut diagram);
+            }
+
+            assert!(history.undo().is_some());
+            assert!(history.undo().is_some());
+            assert!(history.undo().is_none());
+        }
+    }
+}
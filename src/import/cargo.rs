@@ -0,0 +1,374 @@
+//! Import of a Cargo workspace as a container diagram
+//!
+//! Every crate becomes a container: its library as one, and each binary as
+//! another that uses the library. Dependencies between workspace crates
+//! become relationships; dependencies on crates from a registry are left out.
+//! Workspace members are read from `[workspace] members`, where a trailing
+//! `*` matches every directory holding a `Cargo.toml`.
+
+use super::{ImportError, auto_layout};
+use crate::model::{ContainerType, Diagram, DiagramType, Element, ElementId, ElementType, Position, Relationship};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use toml_edit::{Document, Item, TableLike};
+
+/// The parts of a `Cargo.toml` the importer uses
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    /// `[package] name`, None for a virtual workspace manifest
+    pub name: Option<String>,
+    pub description: String,
+    /// `[workspace] members` patterns
+    pub members: Vec<String>,
+    /// Names of `[dependencies]`, as used by the crate (after `package = ...` renames)
+    pub dependencies: Vec<String>,
+    /// Whether a `[lib]` section is present
+    pub has_lib_section: bool,
+    /// Names of `[[bin]]` targets
+    pub bins: Vec<String>,
+}
+
+/// Line (1-based) of a byte offset
+fn line_of(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+/// Parse a `Cargo.toml`
+pub fn parse_manifest(text: &str) -> Result<Manifest, ImportError> {
+    let document = Document::parse(text).map_err(|e| ImportError::Parse {
+        line: e.span().map_or(1, |span| line_of(text, span.start)),
+        message: e.message().to_string(),
+    })?;
+    let root = document.as_table();
+    let table = |key: &str| root.get(key).and_then(Item::as_table_like);
+    let string = |table: &dyn TableLike, key: &str| table.get(key).and_then(Item::as_str).map(str::to_string);
+
+    let mut manifest = Manifest::default();
+    if let Some(package) = table("package") {
+        manifest.name = string(package, "name");
+        manifest.description = string(package, "description").unwrap_or_default();
+    }
+    if let Some(members) = table("workspace")
+        .and_then(|workspace| workspace.get("members"))
+        .and_then(Item::as_array)
+    {
+        manifest.members = members.iter().filter_map(|m| m.as_str()).map(str::to_string).collect();
+    }
+    if let Some(dependencies) = table("dependencies") {
+        manifest.dependencies = dependencies
+            .iter()
+            .map(|(key, value)| {
+                value
+                    .as_table_like()
+                    .and_then(|dependency| string(dependency, "package"))
+                    .unwrap_or_else(|| key.to_string())
+            })
+            .collect();
+    }
+    manifest.has_lib_section = table("lib").is_some();
+    if let Some(bins) = root.get("bin").and_then(Item::as_array_of_tables) {
+        manifest.bins = bins.iter().filter_map(|bin| string(bin, "name")).collect();
+    }
+    Ok(manifest)
+}
+
+/// A crate of the workspace with the targets found on disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceCrate {
+    pub name: String,
+    pub description: String,
+    pub dependencies: Vec<String>,
+    pub has_lib: bool,
+    pub bins: Vec<String>,
+}
+
+impl WorkspaceCrate {
+    /// Combine a package manifest with the targets present in `dir`
+    ///
+    /// A crate with neither a library nor a binary found is taken to be a
+    /// library.
+    fn from_manifest(manifest: Manifest, dir: &Path) -> Option<Self> {
+        let name = manifest.name?;
+        let src = dir.join("src");
+        let mut bins = manifest.bins;
+        if src.join("main.rs").is_file() && !bins.contains(&name) {
+            bins.insert(0, name.clone());
+        }
+        if let Ok(entries) = std::fs::read_dir(src.join("bin")) {
+            let mut found: Vec<String> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+                .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+                .filter(|bin| !bins.contains(bin))
+                .collect();
+            found.sort();
+            bins.extend(found);
+        }
+        let has_lib = manifest.has_lib_section || src.join("lib.rs").is_file() || bins.is_empty();
+        Some(Self {
+            name,
+            description: manifest.description,
+            dependencies: manifest.dependencies,
+            has_lib,
+            bins,
+        })
+    }
+}
+
+/// Build a container diagram from workspace crates
+///
+/// Dependency names are matched against crate names with `-` and `_`
+/// treated alike, as Cargo does.
+pub fn workspace_diagram(name: &str, crates: &[WorkspaceCrate]) -> Diagram {
+    let normalize = |name: &str| name.replace('-', "_");
+    let mut diagram = Diagram::new(name, "Generated from the Cargo workspace", DiagramType::Container);
+    // Library container of each crate, by normalized name
+    let mut libraries: HashMap<String, ElementId> = HashMap::new();
+    // Every container of each crate, in the order crates were given
+    let mut containers: BTreeMap<usize, Vec<ElementId>> = BTreeMap::new();
+
+    let mut add = |diagram: &mut Diagram, index: usize, element_type: ElementType| {
+        let element = Element::new(element_type, Position::new(0.0, 0.0));
+        let id = element.id;
+        diagram.add_element(element);
+        containers.entry(index).or_default().push(id);
+        id
+    };
+    for (index, krate) in crates.iter().enumerate() {
+        let library = krate.has_lib.then(|| {
+            add(
+                &mut diagram,
+                index,
+                ElementType::container(
+                    &krate.name,
+                    &krate.description,
+                    ContainerType::Other("Library".to_string()),
+                    "Rust library",
+                ),
+            )
+        });
+        if let Some(library) = library {
+            libraries.insert(normalize(&krate.name), library);
+        }
+        for bin in &krate.bins {
+            let bin_name = if library.is_some() && *bin == krate.name {
+                format!("{} (binary)", bin)
+            } else {
+                bin.clone()
+            };
+            let id = add(
+                &mut diagram,
+                index,
+                ElementType::container(bin_name, &krate.description, ContainerType::Microservice, "Rust binary"),
+            );
+            if let Some(library) = library {
+                diagram.add_relationship(Relationship::new(id, library, "Uses"));
+            }
+        }
+    }
+
+    for (index, krate) in crates.iter().enumerate() {
+        for dependency in &krate.dependencies {
+            let Some(&target) = libraries.get(&normalize(dependency)) else {
+                continue;
+            };
+            let own_library = libraries.get(&normalize(&krate.name)).copied();
+            // A crate's binaries reach its dependencies through its library
+            let sources: Vec<ElementId> = match own_library {
+                Some(library) => vec![library],
+                None => containers[&index].clone(),
+            };
+            for source in sources.into_iter().filter(|&source| source != target) {
+                diagram.add_relationship(Relationship::with_technology(source, target, "Depends on", "Cargo"));
+            }
+        }
+    }
+
+    auto_layout(&mut diagram);
+    diagram.take_events();
+    diagram
+}
+
+/// Directories matched by a workspace member pattern, relative to `root`
+///
+/// Only a trailing `*` is supported as a wildcard.
+fn member_dirs(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let Some(parent) = pattern.strip_suffix('*') else {
+        return vec![root.join(pattern)];
+    };
+    let Ok(entries) = std::fs::read_dir(root.join(parent)) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.join("Cargo.toml").is_file())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Read a workspace (or single crate) from its root `Cargo.toml`
+pub fn import_cargo_workspace(manifest_path: &Path) -> crate::Result<Diagram> {
+    let read = |path: &Path| -> crate::Result<Manifest> {
+        let text = std::fs::read_to_string(path).map_err(|e| crate::C2DrawError::file(path, e))?;
+        parse_manifest(&text).map_err(|e| match e {
+            ImportError::Parse { line, message } => ImportError::Parse {
+                line,
+                message: format!("{}: {}", path.display(), message),
+            },
+            other => other,
+        })
+        .map_err(Into::into)
+    };
+    let root_dir = manifest_path.parent().unwrap_or(Path::new(""));
+    let root = read(manifest_path)?;
+    let name = root
+        .name
+        .clone()
+        .or_else(|| root_dir.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "Workspace".to_string());
+
+    let members = root.members.clone();
+    let mut crates: Vec<WorkspaceCrate> = WorkspaceCrate::from_manifest(root, root_dir).into_iter().collect();
+    for dir in members.iter().flat_map(|pattern| member_dirs(root_dir, pattern)) {
+        let manifest = read(&dir.join("Cargo.toml"))?;
+        if let Some(krate) = WorkspaceCrate::from_manifest(manifest, &dir)
+            && !crates.iter().any(|c| c.name == krate.name)
+        {
+            crates.push(krate);
+        }
+    }
+    Ok(workspace_diagram(&name, &crates))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn krate(name: &str, dependencies: &[&str], has_lib: bool, bins: &[&str]) -> WorkspaceCrate {
+        WorkspaceCrate {
+            name: name.to_string(),
+            description: String::new(),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            has_lib,
+            bins: bins.iter().map(|b| b.to_string()).collect(),
+        }
+    }
+
+    fn find<'a>(diagram: &'a Diagram, name: &str) -> &'a Element {
+        diagram.elements.values().find(|e| e.name() == name).unwrap()
+    }
+
+    mod parse_manifest_tests {
+        use super::*;
+
+        /// Verifies package, workspace, dependency and target sections are read
+        #[test]
+        fn parse_manifest_reads_sections() {
+            let manifest = parse_manifest(
+                r#"
+[package]
+name = "server"
+description = "HTTP front end"
+
+[workspace]
+members = ["crates/*", "tools/gen"]
+
+[dependencies]
+serde = "1"
+core = { path = "../core" }
+db = { package = "storage", path = "../storage" }
+
+[dev-dependencies]
+tempfile = "3"
+
+[[bin]]
+name = "server-admin"
+path = "src/admin.rs"
+"#,
+            )
+            .unwrap();
+            assert_eq!(manifest.name.as_deref(), Some("server"));
+            assert_eq!(manifest.description, "HTTP front end");
+            assert_eq!(manifest.members, vec!["crates/*", "tools/gen"]);
+            assert_eq!(manifest.dependencies, vec!["serde", "core", "storage"]);
+            assert!(!manifest.has_lib_section);
+            assert_eq!(manifest.bins, vec!["server-admin"]);
+        }
+
+        /// Verifies invalid TOML reports the line of the error
+        #[test]
+        fn parse_manifest_reports_line() {
+            let error = parse_manifest("[package]\nname = \"a\"\nversion = \n").unwrap_err();
+            assert!(matches!(error, ImportError::Parse { line: 3, .. }), "{:?}", error);
+        }
+    }
+
+    mod workspace_diagram_tests {
+        use super::*;
+
+        /// Verifies crates become containers connected by their workspace dependencies
+        #[test]
+        fn workspace_diagram_connects_crates() {
+            let diagram = workspace_diagram(
+                "shop",
+                &[
+                    krate("shop-core", &["serde"], true, &[]),
+                    krate("shop-server", &["shop_core", "tokio"], true, &["shop-server"]),
+                    krate("migrate", &["shop-core"], false, &["migrate"]),
+                ],
+            );
+            assert_eq!(diagram.diagram_type, DiagramType::Container);
+            assert_eq!(diagram.elements.len(), 4);
+            assert_eq!(diagram.relationships.len(), 3);
+
+            let core = find(&diagram, "shop-core").id;
+            let server = find(&diagram, "shop-server").id;
+            let server_bin = find(&diagram, "shop-server (binary)").id;
+            let migrate = find(&diagram, "migrate");
+            assert!(matches!(&migrate.element_type, ElementType::Container(data) if data.technology == "Rust binary"));
+
+            let connected = |source, target| {
+                diagram.relationships.iter().any(|r| r.source_id == source && r.target_id == target)
+            };
+            assert!(connected(server, core));
+            assert!(connected(server_bin, server));
+            assert!(connected(migrate.id, core));
+            assert!(!connected(server_bin, core));
+        }
+    }
+
+    mod import_cargo_workspace_tests {
+        use super::*;
+
+        /// Verifies a workspace on disk is imported with its member crates and binaries
+        #[test]
+        fn import_reads_members_from_disk() {
+            let root = std::env::temp_dir().join(format!("c2draw-cargo-{}", uuid::Uuid::new_v4()));
+            let write = |path: &str, text: &str| {
+                let path = root.join(path);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, text).unwrap();
+            };
+            write("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n");
+            write("crates/api/Cargo.toml", "[package]\nname = \"api\"\n\n[dependencies]\nmodel = { path = \"../model\" }\n");
+            write("crates/api/src/main.rs", "fn main() {}\n");
+            write("crates/api/src/bin/worker.rs", "fn main() {}\n");
+            write("crates/model/Cargo.toml", "[package]\nname = \"model\"\n");
+            write("crates/model/src/lib.rs", "");
+            write("crates/notes.txt", "not a crate");
+
+            let diagram = import_cargo_workspace(&root.join("Cargo.toml"));
+            std::fs::remove_dir_all(&root).unwrap();
+            let diagram = diagram.unwrap();
+
+            let mut names: Vec<&str> = diagram.elements.values().map(|e| e.name()).collect();
+            names.sort();
+            assert_eq!(names, vec!["api", "model", "worker"]);
+            assert_eq!(diagram.relationships.len(), 2);
+            assert!(diagram.name.starts_with("c2draw-cargo-"));
+        }
+    }
+}
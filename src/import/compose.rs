@@ -0,0 +1,191 @@
+//! Import of a docker-compose file as a container diagram
+//!
+//! Each service becomes a container whose type is guessed from its image
+//! (see [`container_type_for_image`]) and whose technology is the image, or
+//! "Docker build" for services built from source. `depends_on` entries become
+//! "Depends on" relationships. Services attached to the same user-defined
+//! network without a dependency between them are connected through that
+//! network, pointing at the data store or broker when one side is one; two
+//! data stores sharing a network are left unconnected.
+
+use super::{ImportError, auto_layout, container_type_for_image, yaml::{self, Yaml}};
+use crate::model::{ContainerType, Diagram, DiagramType, Element, ElementId, ElementType, Position, Relationship};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Build a container diagram from the text of a compose file
+pub fn parse_compose(name: &str, text: &str) -> Result<Diagram, ImportError> {
+    let document = yaml::parse(text)?.into_iter().next().unwrap_or(Yaml::Null);
+    let services = document.get("services").map(Yaml::as_map).unwrap_or_default();
+    if services.is_empty() {
+        return Err(ImportError::Parse {
+            line: 1,
+            message: "no services found".to_string(),
+        });
+    }
+
+    let mut diagram = Diagram::new(name, "Generated from docker-compose", DiagramType::Container);
+    let mut ids: HashMap<&str, ElementId> = HashMap::new();
+    let mut types: HashMap<ElementId, ContainerType> = HashMap::new();
+    for (service, definition) in services {
+        let image = definition.get("image").and_then(Yaml::as_str);
+        let container_type = image.map_or(ContainerType::Microservice, container_type_for_image);
+        let technology = image.unwrap_or(if definition.get("build").is_some() { "Docker build" } else { "" });
+        let element = Element::new(
+            ElementType::container(service, "", container_type.clone(), technology),
+            Position::new(0.0, 0.0),
+        );
+        ids.insert(service, element.id);
+        types.insert(element.id, container_type);
+        diagram.add_element(element);
+    }
+
+    let is_store = |id: &ElementId| matches!(types[id], ContainerType::Database | ContainerType::Queue);
+    let mut connected: Vec<(ElementId, ElementId)> = Vec::new();
+    for (service, definition) in services {
+        let source = ids[service.as_str()];
+        for dependency in definition.get("depends_on").map(Yaml::names).unwrap_or_default() {
+            let Some(&target) = ids.get(dependency) else {
+                return Err(ImportError::UnknownElement {
+                    line: definition_line(text, service),
+                    name: dependency.to_string(),
+                });
+            };
+            diagram.add_relationship(Relationship::new(source, target, "Depends on"));
+            connected.push((source, target));
+        }
+    }
+
+    // Services per network, in file order
+    let mut networks: Vec<(&str, Vec<ElementId>)> = Vec::new();
+    for (service, definition) in services {
+        for network in definition.get("networks").map(Yaml::names).unwrap_or_default() {
+            match networks.iter_mut().find(|(n, _)| *n == network) {
+                Some((_, members)) => members.push(ids[service.as_str()]),
+                None => networks.push((network, vec![ids[service.as_str()]])),
+            }
+        }
+    }
+    for (network, members) in &networks {
+        for (i, &a) in members.iter().enumerate() {
+            for &b in &members[i + 1..] {
+                if (is_store(&a) && is_store(&b)) || connected.iter().any(|&pair| pair == (a, b) || pair == (b, a)) {
+                    continue;
+                }
+                let (source, target) = if is_store(&a) && !is_store(&b) { (b, a) } else { (a, b) };
+                diagram.add_relationship(Relationship::with_technology(source, target, "Connects to", format!("{} network", network)));
+                connected.push((source, target));
+            }
+        }
+    }
+
+    auto_layout(&mut diagram);
+    diagram.take_events();
+    Ok(diagram)
+}
+
+/// Line (1-based) where a service's definition starts, or 1 if it cannot be found
+fn definition_line(text: &str, service: &str) -> usize {
+    let header = format!("{}:", service);
+    text.lines().position(|line| line.trim() == header).map_or(1, |i| i + 1)
+}
+
+/// Read a compose file, naming the diagram after its directory as Compose
+/// names the project
+pub fn import_compose_file(path: &Path) -> crate::Result<Diagram> {
+    let text = std::fs::read_to_string(path).map_err(|e| crate::C2DrawError::file(path, e))?;
+    let name = path
+        .parent()
+        .and_then(Path::file_name)
+        .map_or_else(|| "Compose".to_string(), |n| n.to_string_lossy().into_owned());
+    Ok(parse_compose(&name, &text)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPOSE: &str = r#"
+services:
+  web:
+    build: ./web
+    ports: ["80:80"]
+    depends_on:
+      - api
+    networks: [front]
+  api:
+    image: registry.example.com/shop/api:2.1
+    depends_on:
+      db:
+        condition: service_healthy
+    networks:
+      - front
+      - back
+  db:
+    image: postgres:16
+    networks: [back]
+  events:
+    image: bitnami/kafka
+    networks: [back]
+"#;
+
+    fn find<'a>(diagram: &'a Diagram, name: &str) -> &'a Element {
+        diagram.elements.values().find(|e| e.name() == name).unwrap()
+    }
+
+    fn container_type(element: &Element) -> &ContainerType {
+        match &element.element_type {
+            ElementType::Container(data) => &data.container_type,
+            _ => panic!("not a container"),
+        }
+    }
+
+    mod parse_compose_tests {
+        use super::*;
+
+        /// Verifies services become containers typed from their images
+        #[test]
+        fn services_become_containers() {
+            let diagram = parse_compose("shop", COMPOSE).unwrap();
+            assert_eq!(diagram.name, "shop");
+            assert_eq!(diagram.diagram_type, DiagramType::Container);
+            assert_eq!(diagram.elements.len(), 4);
+            assert_eq!(container_type(find(&diagram, "db")), &ContainerType::Database);
+            assert_eq!(container_type(find(&diagram, "events")), &ContainerType::Queue);
+            assert_eq!(container_type(find(&diagram, "api")), &ContainerType::Microservice);
+            assert!(matches!(&find(&diagram, "web").element_type, ElementType::Container(data) if data.technology == "Docker build"));
+        }
+
+        /// Verifies depends_on in both forms and shared networks become relationships
+        #[test]
+        fn dependencies_and_networks_become_relationships() {
+            let diagram = parse_compose("shop", COMPOSE).unwrap();
+            let id = |name| find(&diagram, name).id;
+            let relationship = |source, target| {
+                diagram
+                    .relationships
+                    .iter()
+                    .find(|r| r.source_id == id(source) && r.target_id == id(target))
+            };
+            assert_eq!(relationship("web", "api").unwrap().description, "Depends on");
+            assert_eq!(relationship("api", "db").unwrap().description, "Depends on");
+            // api and db share "back" but are already connected
+            assert_eq!(diagram.relationships.len(), 3);
+            assert_eq!(relationship("api", "events").unwrap().technology.as_deref(), Some("back network"));
+            assert!(relationship("db", "events").is_none());
+        }
+
+        /// Verifies a dependency on an undefined service is reported
+        #[test]
+        fn unknown_dependency_is_error() {
+            let error = parse_compose("x", "services:\n  api:\n    depends_on: [db]\n").unwrap_err();
+            assert!(matches!(error, ImportError::UnknownElement { line: 2, name } if name == "db"));
+        }
+
+        /// Verifies a file without services is rejected
+        #[test]
+        fn missing_services_is_error() {
+            assert!(parse_compose("x", "version: '3'\n").is_err());
+        }
+    }
+}
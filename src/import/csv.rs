@@ -0,0 +1,305 @@
+//! Bulk import of elements and relationships from CSV
+//!
+//! Elements CSV columns: `name`, `type`, `technology`, `description`.
+//! Relationships CSV columns: `source`, `target`, `description`, `technology`,
+//! where source and target are element names. Column order is taken from the
+//! header row; unknown columns are ignored.
+
+use super::{ImportError, auto_layout};
+use crate::model::{ContainerType, Diagram, DiagramType, Element, ElementId, ElementType, Position, Relationship};
+use std::collections::HashMap;
+
+/// A parsed CSV row together with its 1-based line number
+type Row = (usize, Vec<String>);
+
+/// Parse CSV text (RFC 4180: quoted fields, doubled quotes, embedded newlines)
+fn parse_csv(text: &str) -> Result<Vec<Row>, ImportError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|f| !f.trim().is_empty()) {
+                    rows.push((row_line, std::mem::take(&mut row)));
+                } else {
+                    row.clear();
+                }
+                line += 1;
+                row_line = line;
+            }
+            '\n' => {
+                field.push(c);
+                line += 1;
+            }
+            c => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(ImportError::Parse {
+            line: row_line,
+            message: "unterminated quoted field".to_string(),
+        });
+    }
+    row.push(field);
+    if row.iter().any(|f| !f.trim().is_empty()) {
+        rows.push((row_line, row));
+    }
+    Ok(rows)
+}
+
+/// Map header names to column indices
+fn header_columns(header: &[String]) -> HashMap<String, usize> {
+    header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.trim().to_lowercase(), i))
+        .collect()
+}
+
+fn column<'a>(columns: &HashMap<String, usize>, row: &'a [String], name: &str) -> &'a str {
+    columns
+        .get(name)
+        .and_then(|&i| row.get(i))
+        .map_or("", |s| s.trim())
+}
+
+/// Element type for a `type` cell; unrecognised values become custom containers
+fn element_type_for(kind: &str, name: &str, description: &str, technology: &str) -> ElementType {
+    let normalized = kind.trim().to_lowercase().replace(['-', '_'], " ");
+    let container = |container_type| ElementType::container(name, description, container_type, technology);
+
+    match normalized.as_str() {
+        "person" | "user" | "actor" => ElementType::person(name, description),
+        "external person" | "person ext" => ElementType::external_person(name, description),
+        "system" | "software system" => ElementType::system(name, description),
+        "external system" | "system ext" => ElementType::external_system(name, description),
+        "container" | "microservice" | "service" => container(ContainerType::Microservice),
+        "web app" | "webapp" | "web application" => container(ContainerType::WebApplication),
+        "mobile app" | "mobile" => container(ContainerType::MobileApp),
+        "database" | "db" => container(ContainerType::Database),
+        "queue" | "message queue" => container(ContainerType::Queue),
+        "component" => ElementType::component(name, description, technology),
+        _ => container(ContainerType::Other(kind.trim().to_string())),
+    }
+}
+
+/// Build a diagram from an elements CSV and an optional relationships CSV
+///
+/// The diagram is a Component diagram if any component was imported, a
+/// Container diagram if any container was, and a System Context diagram
+/// otherwise. Elements are arranged with
+/// [`auto_layout`].
+pub fn import_csv(elements_csv: &str, relationships_csv: Option<&str>) -> Result<Diagram, ImportError> {
+    let mut diagram = Diagram::new("Imported Diagram", "", DiagramType::SystemContext);
+    let mut by_name: HashMap<String, ElementId> = HashMap::new();
+
+    let mut rows = parse_csv(elements_csv)?.into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Ok(diagram);
+    };
+    let columns = header_columns(&header);
+    if !columns.contains_key("name") {
+        return Err(ImportError::Parse {
+            line: 1,
+            message: "elements CSV needs a \"name\" column".to_string(),
+        });
+    }
+
+    for (line, row) in rows {
+        let name = column(&columns, &row, "name");
+        if name.is_empty() {
+            return Err(ImportError::Parse {
+                line,
+                message: "element name is empty".to_string(),
+            });
+        }
+        let element_type = element_type_for(
+            column(&columns, &row, "type"),
+            name,
+            column(&columns, &row, "description"),
+            column(&columns, &row, "technology"),
+        );
+        match element_type {
+            ElementType::Component(_) => diagram.diagram_type = DiagramType::Component,
+            ElementType::Container(_) if diagram.diagram_type == DiagramType::SystemContext => {
+                diagram.diagram_type = DiagramType::Container;
+            }
+            _ => {}
+        }
+        let element = Element::new(element_type, Position::new(0.0, 0.0));
+        by_name.insert(name.to_lowercase(), element.id);
+        diagram.add_element(element);
+    }
+
+    if let Some(relationships_csv) = relationships_csv {
+        let mut rows = parse_csv(relationships_csv)?.into_iter();
+        if let Some((_, header)) = rows.next() {
+            let columns = header_columns(&header);
+            for (line, row) in rows {
+                let lookup = |key: &str| {
+                    let name = column(&columns, &row, key);
+                    by_name.get(&name.to_lowercase()).copied().ok_or_else(|| {
+                        ImportError::UnknownElement {
+                            line,
+                            name: name.to_string(),
+                        }
+                    })
+                };
+                let source_id = lookup("source")?;
+                let target_id = lookup("target")?;
+                let description = column(&columns, &row, "description");
+                let technology = column(&columns, &row, "technology");
+
+                let rel = if technology.is_empty() {
+                    Relationship::new(source_id, target_id, description)
+                } else {
+                    Relationship::with_technology(source_id, target_id, description, technology)
+                };
+                diagram.add_relationship(rel);
+            }
+        }
+    }
+
+    auto_layout(&mut diagram);
+    Ok(diagram)
+}
+
+/// Read the CSV files from disk and import them with [`import_csv`]
+pub fn import_csv_files(
+    elements_path: &std::path::Path,
+    relationships_path: Option<&std::path::Path>,
+) -> crate::Result<Diagram> {
+    let read = |path: &std::path::Path| {
+        std::fs::read_to_string(path).map_err(|e| crate::C2DrawError::file(path, e))
+    };
+    let elements = read(elements_path)?;
+    let relationships = relationships_path.map(read).transpose()?;
+    Ok(import_csv(&elements, relationships.as_deref())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod parse_csv_tests {
+        use super::*;
+
+        /// Verifies plain rows are split into fields
+        #[test]
+        fn parse_csv_splits_fields() {
+            let rows = parse_csv("a,b,c\n1,2,3\n").unwrap();
+            assert_eq!(rows.len(), 2);
+            assert_eq!(rows[1], (2, vec!["1".to_string(), "2".to_string(), "3".to_string()]));
+        }
+
+        /// Verifies quoted fields with commas, quotes and newlines
+        #[test]
+        fn parse_csv_handles_quoting() {
+            let rows = parse_csv("\"a, b\",\"say \"\"hi\"\"\",\"multi\nline\"\r\nnext,row,here").unwrap();
+            assert_eq!(rows[0].1, vec!["a, b", "say \"hi\"", "multi\nline"]);
+            assert_eq!(rows[1].0, 3);
+        }
+
+        /// Verifies blank lines are skipped and unterminated quotes rejected
+        #[test]
+        fn parse_csv_blank_lines_and_errors() {
+            assert_eq!(parse_csv("a\n\n\nb\n").unwrap().len(), 2);
+            assert!(parse_csv("\"open").is_err());
+        }
+    }
+
+    mod import_csv_tests {
+        use super::*;
+
+        const ELEMENTS: &str = "name,type,technology,description\n\
+            Customer,person,,Buys things\n\
+            Shop,web app,React,Storefront\n\
+            Orders DB,database,PostgreSQL,Stores orders\n\
+            Payments,external system,,Card processing\n\
+            Cache,Redis Cache,Redis,Hot data\n";
+
+        const RELATIONSHIPS: &str = "source,target,description,technology\n\
+            Customer,Shop,browses,HTTPS\n\
+            shop,orders db,writes,\n";
+
+        /// Verifies element types, technologies and diagram type are derived
+        #[test]
+        fn import_csv_creates_elements() {
+            let diagram = import_csv(ELEMENTS, None).expect("Failed to import");
+
+            assert_eq!(diagram.elements.len(), 5);
+            assert_eq!(diagram.diagram_type, DiagramType::Container);
+
+            let find = |name: &str| diagram.elements.values().find(|e| e.name() == name).unwrap();
+            assert!(matches!(find("Customer").element_type, ElementType::Person(_)));
+            assert!(find("Payments").is_external());
+            match &find("Orders DB").element_type {
+                ElementType::Container(data) => {
+                    assert!(matches!(data.container_type, ContainerType::Database));
+                    assert_eq!(data.technology, "PostgreSQL");
+                }
+                _ => panic!("Expected Container variant"),
+            }
+            match &find("Cache").element_type {
+                ElementType::Container(data) => assert_eq!(data.container_type.as_str(), "Redis Cache"),
+                _ => panic!("Expected Container variant"),
+            }
+        }
+
+        /// Verifies relationships resolve element names case-insensitively
+        #[test]
+        fn import_csv_creates_relationships() {
+            let diagram = import_csv(ELEMENTS, Some(RELATIONSHIPS)).expect("Failed to import");
+
+            assert_eq!(diagram.relationships.len(), 2);
+            assert_eq!(diagram.relationships[0].technology.as_deref(), Some("HTTPS"));
+            assert!(diagram.relationships[1].technology.is_none());
+        }
+
+        /// Verifies unknown relationship endpoints are reported with their line
+        #[test]
+        fn import_csv_reports_unknown_elements() {
+            let rels = "source,target,description\nCustomer,Nowhere,goes\n";
+            let err = import_csv(ELEMENTS, Some(rels)).unwrap_err();
+            assert_eq!(
+                err,
+                ImportError::UnknownElement {
+                    line: 2,
+                    name: "Nowhere".to_string()
+                }
+            );
+        }
+
+        /// Verifies a missing name column is rejected
+        #[test]
+        fn import_csv_requires_name_column() {
+            assert!(import_csv("type,description\nperson,x\n", None).is_err());
+        }
+
+        /// Verifies a people-and-systems import yields a System Context diagram
+        #[test]
+        fn import_csv_system_context() {
+            let diagram = import_csv("name,type\nUser,person\nCRM,system\n", None).unwrap();
+            assert_eq!(diagram.diagram_type, DiagramType::SystemContext);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! Import of Kubernetes manifests as a container diagram
+//!
+//! Deployments, StatefulSets and DaemonSets become containers, typed from
+//! their first container's image. A Service is resolved to the workloads its
+//! selector matches; a workload whose environment mentions a Service's host
+//! name (`DB_HOST: postgres`, `http://orders.shop.svc:8080`) gets a
+//! relationship to those workloads. Each Ingress becomes a container routing
+//! to the workloads behind its backend Services.
+
+use super::{ImportError, auto_layout, container_type_for_image, yaml::{self, Yaml}};
+use crate::model::{ContainerType, Diagram, DiagramType, Element, ElementId, ElementType, Position, Relationship};
+use std::path::Path;
+
+/// Kinds of workload that become containers
+const WORKLOAD_KINDS: [&str; 3] = ["Deployment", "StatefulSet", "DaemonSet"];
+
+/// A workload and what relationships are inferred from
+struct Workload<'a> {
+    id: ElementId,
+    labels: Vec<(&'a str, &'a str)>,
+    /// Environment variable values of all its containers
+    env: Vec<&'a str>,
+}
+
+/// A Service with the workloads its selector matches
+struct Service<'a> {
+    name: &'a str,
+    targets: Vec<ElementId>,
+}
+
+fn metadata_name(document: &Yaml) -> &str {
+    document.get("metadata").and_then(|m| m.get("name")).and_then(Yaml::as_str).unwrap_or("")
+}
+
+fn path<'a>(document: &'a Yaml, keys: &[&str]) -> Option<&'a Yaml> {
+    keys.iter().try_fold(document, |node, key| node.get(key))
+}
+
+fn string_pairs(node: Option<&Yaml>) -> Vec<(&str, &str)> {
+    node.map(Yaml::as_map)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|(k, v)| Some((k.as_str(), v.as_str()?)))
+        .collect()
+}
+
+/// Whether `value` refers to the host `service`, alone or in a URL or
+/// `host:port`, possibly qualified with a namespace (`service.namespace.svc`)
+fn mentions_host(value: &str, service: &str) -> bool {
+    value
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '.'))
+        .any(|host| host == service || host.strip_prefix(service).is_some_and(|rest| rest.starts_with('.')))
+}
+
+/// Flatten manifests, expanding `kind: List` into its items
+fn manifests(documents: &[Yaml]) -> Vec<&Yaml> {
+    documents
+        .iter()
+        .flat_map(|document| match document.get("kind").and_then(Yaml::as_str) {
+            Some("List") => document.get("items").map(Yaml::as_list).unwrap_or_default().iter().collect(),
+            _ => vec![document],
+        })
+        .collect()
+}
+
+/// Build a container diagram from parsed manifests
+pub fn manifests_diagram(name: &str, documents: &[Yaml]) -> Diagram {
+    let documents = manifests(documents);
+    let of_kind = |kinds: &[&str]| -> Vec<&Yaml> {
+        documents
+            .iter()
+            .copied()
+            .filter(|d| d.get("kind").and_then(Yaml::as_str).is_some_and(|k| kinds.contains(&k)))
+            .collect()
+    };
+    let mut diagram = Diagram::new(name, "Generated from Kubernetes manifests", DiagramType::Container);
+
+    let mut workloads = Vec::new();
+    for document in of_kind(&WORKLOAD_KINDS) {
+        let pod = path(document, &["spec", "template"]);
+        let containers = pod.and_then(|p| path(p, &["spec", "containers"])).map(Yaml::as_list).unwrap_or_default();
+        let image = containers.first().and_then(|c| c.get("image")).and_then(Yaml::as_str).unwrap_or("");
+        let description = path(document, &["metadata", "annotations", "description"]).and_then(Yaml::as_str).unwrap_or("");
+        let element = Element::new(
+            ElementType::container(metadata_name(document), description, container_type_for_image(image), image),
+            Position::new(0.0, 0.0),
+        );
+        workloads.push(Workload {
+            id: element.id,
+            labels: string_pairs(pod.and_then(|p| path(p, &["metadata", "labels"]))),
+            env: containers
+                .iter()
+                .flat_map(|c| c.get("env").map(Yaml::as_list).unwrap_or_default())
+                .filter_map(|var| var.get("value").and_then(Yaml::as_str))
+                .collect(),
+        });
+        diagram.add_element(element);
+    }
+
+    let services: Vec<Service> = of_kind(&["Service"])
+        .into_iter()
+        .map(|document| {
+            let selector = string_pairs(path(document, &["spec", "selector"]));
+            let targets = workloads
+                .iter()
+                .filter(|w| !selector.is_empty() && selector.iter().all(|pair| w.labels.contains(pair)))
+                .map(|w| w.id)
+                .collect();
+            Service { name: metadata_name(document), targets }
+        })
+        .collect();
+
+    for workload in &workloads {
+        for service in &services {
+            if !workload.env.iter().any(|value| mentions_host(value, service.name)) {
+                continue;
+            }
+            for &target in service.targets.iter().filter(|&&t| t != workload.id) {
+                diagram.add_relationship(Relationship::with_technology(workload.id, target, "Uses", format!("{} Service", service.name)));
+            }
+        }
+    }
+
+    for document in of_kind(&["Ingress"]) {
+        let ingress = Element::new(
+            ElementType::container(metadata_name(document), "", ContainerType::Other("Ingress".to_string()), "Kubernetes Ingress"),
+            Position::new(0.0, 0.0),
+        );
+        let ingress_id = ingress.id;
+        diagram.add_element(ingress);
+        let rules = path(document, &["spec", "rules"]).map(Yaml::as_list).unwrap_or_default();
+        for rule in rules {
+            let host = rule.get("host").and_then(Yaml::as_str).unwrap_or("");
+            for route in path(rule, &["http", "paths"]).map(Yaml::as_list).unwrap_or_default() {
+                let backend = path(route, &["backend", "service", "name"])
+                    .or_else(|| path(route, &["backend", "serviceName"]))
+                    .and_then(Yaml::as_str);
+                let Some(service) = services.iter().find(|s| Some(s.name) == backend) else {
+                    continue;
+                };
+                let route = format!("Routes {}{}", host, route.get("path").and_then(Yaml::as_str).unwrap_or("/"));
+                for &target in &service.targets {
+                    diagram.add_relationship(Relationship::with_technology(ingress_id, target, route.clone(), "HTTP"));
+                }
+            }
+        }
+    }
+
+    auto_layout(&mut diagram);
+    diagram.take_events();
+    diagram
+}
+
+/// Read every `.yaml` / `.yml` file under a directory, recursively
+pub fn import_manifest_dir(dir: &Path) -> crate::Result<Diagram> {
+    fn collect(dir: &Path, files: &mut Vec<std::path::PathBuf>) -> crate::Result<()> {
+        let entries = std::fs::read_dir(dir).map_err(|e| crate::C2DrawError::file(dir, e))?;
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            if path.is_dir() {
+                collect(&path, files)?;
+            } else if path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml") {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    collect(dir, &mut files)?;
+    files.sort();
+    let mut documents = Vec::new();
+    for file in &files {
+        let text = std::fs::read_to_string(file).map_err(|e| crate::C2DrawError::file(file, e))?;
+        documents.extend(yaml::parse(&text).map_err(|e| match e {
+            ImportError::Parse { line, message } => ImportError::Parse {
+                line,
+                message: format!("{}: {}", file.display(), message),
+            },
+            other => other,
+        })?);
+    }
+    let name = dir.file_name().map_or_else(|| "Cluster".to_string(), |n| n.to_string_lossy().into_owned());
+    Ok(manifests_diagram(&name, &documents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFESTS: &str = r#"
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: orders
+spec:
+  template:
+    metadata:
+      labels: {app: orders, tier: api}
+    spec:
+      containers:
+        - name: orders
+          image: shop/orders:1.4
+          env:
+            - name: DATABASE_URL
+              value: postgres://orders-db.shop.svc:5432/orders
+---
+apiVersion: apps/v1
+kind: StatefulSet
+metadata:
+  name: orders-db
+spec:
+  template:
+    metadata:
+      labels:
+        app: orders-db
+    spec:
+      containers:
+        - image: postgres:16
+---
+kind: Service
+metadata:
+  name: orders
+spec:
+  selector:
+    app: orders
+---
+kind: Service
+metadata:
+  name: orders-db
+spec:
+  selector:
+    app: orders-db
+---
+apiVersion: networking.k8s.io/v1
+kind: Ingress
+metadata:
+  name: public
+spec:
+  rules:
+    - host: shop.example.com
+      http:
+        paths:
+          - path: /orders
+            backend:
+              service:
+                name: orders
+                port: {number: 80}
+"#;
+
+    fn find<'a>(diagram: &'a Diagram, name: &str) -> &'a Element {
+        diagram.elements.values().find(|e| e.name() == name).unwrap()
+    }
+
+    mod manifests_diagram_tests {
+        use super::*;
+
+        /// Verifies workloads and ingresses become containers and services resolve to relationships
+        #[test]
+        fn manifests_become_containers_and_relationships() {
+            let diagram = manifests_diagram("shop", &yaml::parse(MANIFESTS).unwrap());
+            assert_eq!(diagram.elements.len(), 3);
+            let orders = find(&diagram, "orders");
+            let database = find(&diagram, "orders-db");
+            let ingress = find(&diagram, "public");
+            assert!(matches!(&database.element_type, ElementType::Container(data) if data.container_type == ContainerType::Database));
+            assert!(matches!(&orders.element_type, ElementType::Container(data) if data.technology == "shop/orders:1.4"));
+
+            assert_eq!(diagram.relationships.len(), 2);
+            let uses = diagram.relationships.iter().find(|r| r.source_id == orders.id).unwrap();
+            assert_eq!(uses.target_id, database.id);
+            assert_eq!(uses.technology.as_deref(), Some("orders-db Service"));
+            let route = diagram.relationships.iter().find(|r| r.source_id == ingress.id).unwrap();
+            assert_eq!(route.target_id, orders.id);
+            assert_eq!(route.description, "Routes shop.example.com/orders");
+        }
+
+        /// Verifies host names only match whole host labels
+        #[test]
+        fn mentions_host_matches_whole_names() {
+            assert!(mentions_host("orders", "orders"));
+            assert!(mentions_host("http://orders.shop.svc.cluster.local:80", "orders"));
+            assert!(mentions_host("redis:6379", "redis"));
+            assert!(!mentions_host("orders-db:5432", "orders"));
+            assert!(!mentions_host("preorders", "orders"));
+        }
+    }
+
+    mod import_manifest_dir_tests {
+        use super::*;
+
+        /// Verifies manifests are gathered from nested directories and `kind: List` is expanded
+        #[test]
+        fn import_reads_nested_files() {
+            let root = std::env::temp_dir().join(format!("c2draw-k8s-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(root.join("db")).unwrap();
+            std::fs::write(root.join("app.yaml"), MANIFESTS.split("---\nkind: Service").next().unwrap()).unwrap();
+            std::fs::write(
+                root.join("db/list.yml"),
+                "kind: List\nitems:\n  - kind: Deployment\n    metadata:\n      name: cache\n",
+            )
+            .unwrap();
+            std::fs::write(root.join("README.md"), "not a manifest").unwrap();
+
+            let diagram = import_manifest_dir(&root);
+            std::fs::remove_dir_all(&root).unwrap();
+            let diagram = diagram.unwrap();
+            let mut names: Vec<&str> = diagram.elements.values().map(|e| e.name()).collect();
+            names.sort();
+            assert_eq!(names, vec!["cache", "orders", "orders-db"]);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+pub mod cargo;
+pub mod compose;
+pub mod csv;
+pub mod kubernetes;
+pub mod openapi;
+pub mod terraform;
+pub mod yaml;
+
+use crate::model::{ContainerType, Diagram, ElementId, ElementType, Position};
+use thiserror::Error;
+
+/// Errors produced while importing external data into a diagram
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ImportError {
+    /// The input could not be parsed (1-based line number)
+    #[error("line {line}: {message}")]
+    Parse { line: usize, message: String },
+    /// A relationship refers to an element that does not exist
+    #[error("line {line}: unknown element \"{name}\"")]
+    UnknownElement { line: usize, name: String },
+}
+
+/// Image names of common data stores and message brokers
+const DATABASE_IMAGES: [&str; 18] = [
+    "postgres", "postgis", "mysql", "mariadb", "mongo", "redis", "valkey", "memcached", "cassandra", "scylla",
+    "couchdb", "elasticsearch", "opensearch", "influxdb", "neo4j", "cockroach", "mssql", "clickhouse",
+];
+const QUEUE_IMAGES: [&str; 8] = ["rabbitmq", "kafka", "redpanda", "nats", "activemq", "artemis", "pulsar", "mosquitto"];
+
+/// Container type guessed from a container image reference
+///
+/// Matches the image's repository name, without registry, tag or digest,
+/// against well-known database and broker images: `postgres:16`,
+/// `bitnami/kafka` and `docker.io/library/redis@sha256:...` are all
+/// recognised. Anything else is a microservice.
+pub fn container_type_for_image(image: &str) -> ContainerType {
+    let name = image.rsplit('/').next().unwrap_or(image);
+    let name = name.split(['@', ':']).next().unwrap_or(name).to_lowercase();
+    if DATABASE_IMAGES.iter().any(|db| name.contains(db)) {
+        ContainerType::Database
+    } else if QUEUE_IMAGES.iter().any(|queue| name.contains(queue)) {
+        ContainerType::Queue
+    } else {
+        ContainerType::Microservice
+    }
+}
+
+/// Horizontal distance between auto-laid-out elements
+const LAYOUT_COLUMN_SPACING: f32 = 220.0;
+/// Vertical distance between auto-laid-out rows
+const LAYOUT_ROW_SPACING: f32 = 170.0;
+/// Maximum number of elements per row before wrapping
+const LAYOUT_MAX_COLUMNS: usize = 5;
+
+/// Layer an element is placed in by [`auto_layout`]: people at the top,
+/// internal systems and services in the middle, data stores and external
+/// systems at the bottom
+fn layout_layer(element_type: &ElementType) -> usize {
+    match element_type {
+        ElementType::Person(_) => 0,
+        ElementType::SoftwareSystem(data) if data.is_external => 2,
+        ElementType::SoftwareSystem(_) => 1,
+        ElementType::Container(data) => match data.container_type {
+            ContainerType::Database | ContainerType::Queue => 2,
+            _ => 1,
+        },
+        ElementType::Component(_) | ElementType::Note(_) => 1,
+    }
+}
+
+/// Arrange all elements of a diagram in layered rows
+///
+/// Elements keep their relative order within a layer (sorted by name so the
+/// result is deterministic) and wrap onto extra rows when a layer is wide.
+pub fn auto_layout(diagram: &mut Diagram) {
+    let mut layers: [Vec<(String, ElementId)>; 3] = Default::default();
+    for element in diagram.elements.values() {
+        layers[layout_layer(&element.element_type)].push((element.name().to_string(), element.id));
+    }
+
+    let mut row = 0;
+    for layer in &mut layers {
+        if layer.is_empty() {
+            continue;
+        }
+        layer.sort();
+        for chunk in layer.chunks(LAYOUT_MAX_COLUMNS) {
+            for (col, (_, id)) in chunk.iter().enumerate() {
+                if let Some(element) = diagram.elements.get_mut(id) {
+                    element.position = Position::new(
+                        50.0 + col as f32 * LAYOUT_COLUMN_SPACING,
+                        50.0 + row as f32 * LAYOUT_ROW_SPACING,
+                    );
+                }
+            }
+            row += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, Element};
+
+    mod container_type_for_image_tests {
+        use super::*;
+
+        /// Verifies databases and brokers are recognised regardless of registry and tag
+        #[test]
+        fn container_type_from_image_name() {
+            assert_eq!(container_type_for_image("postgres:16-alpine"), ContainerType::Database);
+            assert_eq!(container_type_for_image("docker.io/library/redis@sha256:abc"), ContainerType::Database);
+            assert_eq!(container_type_for_image("bitnami/kafka:3.7"), ContainerType::Queue);
+            assert_eq!(container_type_for_image("localhost:5000/shop/api:1.2"), ContainerType::Microservice);
+        }
+    }
+
+    mod auto_layout_tests {
+        use super::*;
+
+        /// Verifies people, internal and external elements land on separate rows
+        #[test]
+        fn auto_layout_places_layers_on_rows() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::Container);
+            let user = Element::new(ElementType::person("User", ""), Position::new(0.0, 0.0));
+            let api = Element::new(
+                ElementType::container("API", "", ContainerType::Microservice, ""),
+                Position::new(0.0, 0.0),
+            );
+            let db = Element::new(
+                ElementType::container("DB", "", ContainerType::Database, ""),
+                Position::new(0.0, 0.0),
+            );
+            let (user_id, api_id, db_id) = (user.id, api.id, db.id);
+            diagram.add_element(user);
+            diagram.add_element(api);
+            diagram.add_element(db);
+
+            auto_layout(&mut diagram);
+
+            let y = |id| diagram.get_element(id).unwrap().position.y;
+            assert!(y(user_id) < y(api_id));
+            assert!(y(api_id) < y(db_id));
+        }
+
+        /// Verifies wide layers wrap onto additional rows
+        #[test]
+        fn auto_layout_wraps_wide_layers() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            for i in 0..(LAYOUT_MAX_COLUMNS + 1) {
+                diagram.add_element(Element::new(
+                    ElementType::system(format!("System {}", i), ""),
+                    Position::new(0.0, 0.0),
+                ));
+            }
+
+            auto_layout(&mut diagram);
+
+            let rows: std::collections::HashSet<i32> = diagram
+                .elements
+                .values()
+                .map(|e| e.position.y as i32)
+                .collect();
+            assert_eq!(rows.len(), 2);
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! Relationship suggestions from an OpenAPI document
+//!
+//! An OpenAPI (or Swagger 2.0) document, in JSON or YAML, describes one API:
+//! it is drawn as a single container named after `info.title`, and its
+//! operations, grouped by their first tag, become suggested relationships
+//! from the containers that consume the API. Each suggestion's description
+//! is built from the summaries of its operations.
+
+use super::{ImportError, yaml::{self, Yaml}};
+use crate::model::{ContainerType, Diagram, ElementId, ElementType, Relationship};
+
+/// HTTP methods that name operations under a path
+const METHODS: [&str; 8] = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+/// Operation names listed in a suggestion before the rest are counted
+const MAX_LISTED_OPERATIONS: usize = 3;
+
+/// One operation of the API
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation {
+    /// Upper-case HTTP method
+    pub method: String,
+    pub path: String,
+    /// `summary`, else `operationId`; empty if neither is given
+    pub summary: String,
+    pub tags: Vec<String>,
+}
+
+impl Operation {
+    /// The summary, or the method and path if there is none
+    pub fn label(&self) -> String {
+        if self.summary.is_empty() {
+            format!("{} {}", self.method, self.path)
+        } else {
+            self.summary.clone()
+        }
+    }
+}
+
+/// A suggested relationship from a consumer to the API
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    /// Tag the operations share; empty for untagged operations
+    pub tag: String,
+    pub description: String,
+    pub operations: usize,
+}
+
+/// The parts of an OpenAPI document used for suggestions
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiSpec {
+    pub title: String,
+    pub description: String,
+    pub version: String,
+    /// Whether every server URL is `https`
+    pub https: bool,
+    pub operations: Vec<Operation>,
+}
+
+fn from_json(value: serde_json::Value) -> Yaml {
+    use serde_json::Value;
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Scalar(b.to_string()),
+        Value::Number(n) => Yaml::Scalar(n.to_string()),
+        Value::String(s) => Yaml::Scalar(s),
+        Value::Array(items) => Yaml::List(items.into_iter().map(from_json).collect()),
+        Value::Object(map) => Yaml::Map(map.into_iter().map(|(k, v)| (k, from_json(v))).collect()),
+    }
+}
+
+/// Parse an OpenAPI document, as JSON if it starts with `{` and as YAML otherwise
+pub fn parse_openapi(text: &str) -> Result<ApiSpec, ImportError> {
+    let document = if text.trim_start().starts_with('{') {
+        from_json(serde_json::from_str(text).map_err(|e| ImportError::Parse {
+            line: e.line(),
+            message: e.to_string(),
+        })?)
+    } else {
+        yaml::parse(text)?.into_iter().next().unwrap_or(Yaml::Null)
+    };
+    if document.get("openapi").is_none() && document.get("swagger").is_none() {
+        return Err(ImportError::Parse {
+            line: 1,
+            message: "not an OpenAPI document: no \"openapi\" or \"swagger\" version".to_string(),
+        });
+    }
+
+    let info = |key| document.get("info").and_then(|i| i.get(key)).and_then(Yaml::as_str).unwrap_or("").to_string();
+    let servers: Vec<&str> = document
+        .get("servers")
+        .map(Yaml::as_list)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|server| server.get("url").and_then(Yaml::as_str))
+        .collect();
+    // Swagger 2.0 lists schemes instead of servers
+    let schemes = document.get("schemes").map(Yaml::names).unwrap_or_default();
+    let https = if servers.is_empty() {
+        !schemes.is_empty() && schemes.iter().all(|s| *s == "https")
+    } else {
+        servers.iter().all(|url| url.starts_with("https://"))
+    };
+
+    let mut operations = Vec::new();
+    for (path, item) in document.get("paths").map(Yaml::as_map).unwrap_or_default() {
+        for (method, operation) in item.as_map() {
+            if !METHODS.contains(&method.as_str()) {
+                continue;
+            }
+            let text = |key| operation.get(key).and_then(Yaml::as_str).map(str::trim);
+            operations.push(Operation {
+                method: method.to_uppercase(),
+                path: path.clone(),
+                summary: text("summary").or(text("operationId")).unwrap_or("").to_string(),
+                tags: operation.get("tags").map(Yaml::names).unwrap_or_default().into_iter().map(str::to_string).collect(),
+            });
+        }
+    }
+
+    Ok(ApiSpec {
+        title: info("title"),
+        description: info("description"),
+        version: info("version"),
+        https,
+        operations,
+    })
+}
+
+impl ApiSpec {
+    /// A container for the API
+    pub fn container(&self) -> ElementType {
+        let name = if self.title.is_empty() { "API" } else { &self.title };
+        let description = self.description.lines().next().unwrap_or("");
+        ElementType::container(name, description, ContainerType::Microservice, "REST API")
+    }
+
+    /// A container already in the diagram named like the API, ignoring case
+    pub fn find_container(&self, diagram: &Diagram) -> Option<ElementId> {
+        let mut matches: Vec<_> = diagram
+            .elements
+            .values()
+            .filter(|e| matches!(e.element_type, ElementType::Container(_)) && e.name().eq_ignore_ascii_case(self.title.trim()))
+            .map(|e| e.id)
+            .collect();
+        matches.sort();
+        matches.first().copied()
+    }
+
+    /// Technology of relationships to the API
+    pub fn technology(&self) -> &'static str {
+        if self.https { "REST/HTTPS" } else { "REST/HTTP" }
+    }
+
+    /// One suggestion per tag, in the order tags first appear, with untagged
+    /// operations last
+    pub fn suggestions(&self) -> Vec<Suggestion> {
+        let mut groups: Vec<(&str, Vec<&Operation>)> = Vec::new();
+        for operation in &self.operations {
+            let tag = operation.tags.first().map_or("", String::as_str);
+            match groups.iter_mut().find(|(t, _)| *t == tag) {
+                Some((_, operations)) => operations.push(operation),
+                None => groups.push((tag, vec![operation])),
+            }
+        }
+        groups.sort_by_key(|(tag, _)| tag.is_empty());
+        groups
+            .into_iter()
+            .map(|(tag, operations)| {
+                let mut labels: Vec<String> = operations.iter().take(MAX_LISTED_OPERATIONS).map(|o| o.label()).collect();
+                if operations.len() > MAX_LISTED_OPERATIONS {
+                    labels.push(format!("{} more", operations.len() - MAX_LISTED_OPERATIONS));
+                }
+                let description = match labels.split_last() {
+                    Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+                    _ => labels.concat(),
+                };
+                Suggestion {
+                    tag: tag.to_string(),
+                    description,
+                    operations: operations.len(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Add a relationship from each consumer to the API for each suggestion
+///
+/// Relationships the diagram already has with the same ends and description
+/// are skipped, as is the API itself among the consumers. Returns the number
+/// added.
+pub fn add_suggested_relationships(
+    diagram: &mut Diagram,
+    api: ElementId,
+    consumers: &[ElementId],
+    suggestions: &[&Suggestion],
+    technology: &str,
+) -> usize {
+    let mut added = 0;
+    for &consumer in consumers.iter().filter(|&&c| c != api) {
+        for suggestion in suggestions {
+            let exists = diagram
+                .relationships
+                .iter()
+                .any(|r| r.source_id == consumer && r.target_id == api && r.description == suggestion.description);
+            if !exists {
+                diagram.add_relationship(Relationship::with_technology(consumer, api, suggestion.description.clone(), technology));
+                added += 1;
+            }
+        }
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, Element, Position};
+
+    const SPEC: &str = r#"
+openapi: 3.0.3
+info:
+  title: Orders API
+  description: |
+    Places and tracks orders.
+    Second line.
+  version: "2.1"
+servers:
+  - url: https://api.example.com/v2
+paths:
+  /orders:
+    parameters: []
+    get:
+      summary: List orders
+      tags: [orders]
+    post:
+      summary: Place an order
+      tags: [orders]
+  /orders/{id}:
+    get:
+      operationId: getOrder
+      tags: [orders]
+    delete:
+      summary: Cancel an order
+      tags: [orders]
+  /health:
+    get: {}
+  /payments:
+    post:
+      summary: Pay for an order
+      tags: [payments, orders]
+"#;
+
+    mod parse_openapi_tests {
+        use super::*;
+
+        /// Verifies info, servers and operations are read from YAML
+        #[test]
+        fn parse_yaml_spec() {
+            let spec = parse_openapi(SPEC).unwrap();
+            assert_eq!(spec.title, "Orders API");
+            assert_eq!(spec.version, "2.1");
+            assert!(spec.https);
+            assert_eq!(spec.operations.len(), 6);
+            assert_eq!(spec.operations[2].summary, "getOrder");
+            assert_eq!(spec.operations[4].label(), "GET /health");
+            assert!(matches!(spec.container(), ElementType::Container(data) if data.description == "Places and tracks orders."));
+        }
+
+        /// Verifies JSON documents and Swagger 2.0 schemes are understood
+        #[test]
+        fn parse_json_swagger() {
+            let spec = parse_openapi(
+                r#"{"swagger": "2.0", "info": {"title": "Pets", "version": "1"}, "schemes": ["http"],
+                    "paths": {"/pets": {"get": {"summary": "List pets"}}}}"#,
+            )
+            .unwrap();
+            assert_eq!(spec.title, "Pets");
+            assert!(!spec.https);
+            assert_eq!(spec.technology(), "REST/HTTP");
+            assert_eq!(spec.operations[0].method, "GET");
+        }
+
+        /// Verifies other documents are rejected
+        #[test]
+        fn parse_rejects_non_openapi() {
+            assert!(parse_openapi("services:\n  web: {}\n").is_err());
+        }
+    }
+
+    mod suggestion_tests {
+        use super::*;
+
+        /// Verifies operations are grouped by first tag and summarised
+        #[test]
+        fn suggestions_group_by_tag() {
+            let suggestions = parse_openapi(SPEC).unwrap().suggestions();
+            assert_eq!(suggestions.len(), 3);
+            assert_eq!(suggestions[0].tag, "orders");
+            assert_eq!(suggestions[0].operations, 4);
+            assert_eq!(suggestions[0].description, "List orders, Place an order, getOrder and 1 more");
+            assert_eq!(suggestions[1].description, "Pay for an order");
+            assert_eq!(suggestions[2].tag, "");
+            assert_eq!(suggestions[2].description, "GET /health");
+        }
+
+        /// Verifies relationships are added once per consumer and suggestion
+        #[test]
+        fn add_suggested_relationships_skips_existing() {
+            let spec = parse_openapi(SPEC).unwrap();
+            let suggestions = spec.suggestions();
+            let mut diagram = Diagram::new("Shop", "", DiagramType::Container);
+            let web = Element::new(ElementType::container("Web", "", ContainerType::WebApplication, ""), Position::new(0.0, 0.0));
+            let api = Element::new(ElementType::container("orders api", "", ContainerType::Microservice, ""), Position::new(0.0, 0.0));
+            let (web_id, api_id) = (web.id, api.id);
+            diagram.add_element(web);
+            diagram.add_element(api);
+            let (web, api) = (web_id, api_id);
+            assert_eq!(spec.find_container(&diagram), Some(api));
+
+            let chosen = [&suggestions[0], &suggestions[1]];
+            assert_eq!(add_suggested_relationships(&mut diagram, api, &[web, api], &chosen, spec.technology()), 2);
+            assert_eq!(add_suggested_relationships(&mut diagram, api, &[web], &chosen, spec.technology()), 0);
+            assert_eq!(diagram.relationships.len(), 2);
+            assert_eq!(diagram.relationships[0].technology.as_deref(), Some("REST/HTTPS"));
+        }
+    }
+}
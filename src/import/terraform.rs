@@ -0,0 +1,437 @@
+//! Import of Terraform state or plan JSON as a container diagram
+//!
+//! Reads the output of `terraform show -json` (for a state or a saved plan)
+//! as well as raw `terraform.tfstate` files. Compute, database, queue and
+//! load-balancer resources of the AWS, Azure and Google providers become
+//! containers, one per instance, described by their resource address. Every
+//! other resource is left out, but dependencies are followed through it: a
+//! function depending on a security group that depends on a database is
+//! connected to the database.
+
+use super::{ImportError, auto_layout};
+use crate::model::{
+    CloudIcon, ContainerType, Diagram, DiagramType, Element, ElementId, ElementType, IconPack, Position, Relationship,
+};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// What a mapped resource is drawn as
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Compute,
+    Database,
+    Queue,
+    LoadBalancer,
+}
+
+impl Kind {
+    fn container_type(self) -> ContainerType {
+        match self {
+            Kind::Compute => ContainerType::Microservice,
+            Kind::Database => ContainerType::Database,
+            Kind::Queue => ContainerType::Queue,
+            Kind::LoadBalancer => ContainerType::Other("Load Balancer".to_string()),
+        }
+    }
+}
+
+struct ResourceKind {
+    resource_type: &'static str,
+    kind: Kind,
+    technology: &'static str,
+    icon: Option<(IconPack, &'static str)>,
+}
+
+const fn kind(
+    resource_type: &'static str,
+    kind: Kind,
+    technology: &'static str,
+    icon: Option<(IconPack, &'static str)>,
+) -> ResourceKind {
+    ResourceKind {
+        resource_type,
+        kind,
+        technology,
+        icon,
+    }
+}
+
+/// Resource types that become containers
+const RESOURCE_KINDS: &[ResourceKind] = &[
+    kind("aws_instance", Kind::Compute, "Amazon EC2", Some((IconPack::Aws, "EC2"))),
+    kind("aws_autoscaling_group", Kind::Compute, "EC2 Auto Scaling", Some((IconPack::Aws, "EC2"))),
+    kind("aws_ecs_service", Kind::Compute, "Amazon ECS", Some((IconPack::Aws, "ElasticContainerService"))),
+    kind("aws_eks_cluster", Kind::Compute, "Amazon EKS", Some((IconPack::Aws, "ElasticKubernetesService"))),
+    kind("aws_lambda_function", Kind::Compute, "AWS Lambda", Some((IconPack::Aws, "Lambda"))),
+    kind("aws_db_instance", Kind::Database, "Amazon RDS", Some((IconPack::Aws, "RDS"))),
+    kind("aws_rds_cluster", Kind::Database, "Amazon Aurora", Some((IconPack::Aws, "RDS"))),
+    kind("aws_dynamodb_table", Kind::Database, "Amazon DynamoDB", Some((IconPack::Aws, "DynamoDB"))),
+    kind("aws_elasticache_cluster", Kind::Database, "Amazon ElastiCache", None),
+    kind("aws_elasticache_replication_group", Kind::Database, "Amazon ElastiCache", None),
+    kind("aws_s3_bucket", Kind::Database, "Amazon S3", Some((IconPack::Aws, "SimpleStorageService"))),
+    kind("aws_sqs_queue", Kind::Queue, "Amazon SQS", Some((IconPack::Aws, "SimpleQueueService"))),
+    kind("aws_sns_topic", Kind::Queue, "Amazon SNS", Some((IconPack::Aws, "SimpleNotificationService"))),
+    kind("aws_kinesis_stream", Kind::Queue, "Amazon Kinesis", None),
+    kind("aws_msk_cluster", Kind::Queue, "Amazon MSK", None),
+    kind("aws_lb", Kind::LoadBalancer, "Elastic Load Balancing", None),
+    kind("aws_alb", Kind::LoadBalancer, "Elastic Load Balancing", None),
+    kind("aws_elb", Kind::LoadBalancer, "Classic Load Balancer", None),
+    kind("aws_api_gateway_rest_api", Kind::LoadBalancer, "Amazon API Gateway", Some((IconPack::Aws, "APIGateway"))),
+    kind("aws_apigatewayv2_api", Kind::LoadBalancer, "Amazon API Gateway", Some((IconPack::Aws, "APIGateway"))),
+    kind("aws_cloudfront_distribution", Kind::LoadBalancer, "Amazon CloudFront", Some((IconPack::Aws, "CloudFront"))),
+    kind("azurerm_linux_virtual_machine", Kind::Compute, "Azure Virtual Machine", None),
+    kind("azurerm_windows_virtual_machine", Kind::Compute, "Azure Virtual Machine", None),
+    kind("azurerm_linux_web_app", Kind::Compute, "Azure App Service", Some((IconPack::Azure, "AzureAppService"))),
+    kind("azurerm_windows_web_app", Kind::Compute, "Azure App Service", Some((IconPack::Azure, "AzureAppService"))),
+    kind("azurerm_linux_function_app", Kind::Compute, "Azure Functions", Some((IconPack::Azure, "AzureFunction"))),
+    kind("azurerm_windows_function_app", Kind::Compute, "Azure Functions", Some((IconPack::Azure, "AzureFunction"))),
+    kind("azurerm_kubernetes_cluster", Kind::Compute, "Azure Kubernetes Service", Some((IconPack::Azure, "AzureKubernetesService"))),
+    kind("azurerm_mssql_database", Kind::Database, "Azure SQL Database", Some((IconPack::Azure, "AzureSqlDatabase"))),
+    kind("azurerm_postgresql_flexible_server", Kind::Database, "Azure Database for PostgreSQL", None),
+    kind("azurerm_cosmosdb_account", Kind::Database, "Azure Cosmos DB", Some((IconPack::Azure, "AzureCosmosDb"))),
+    kind("azurerm_storage_account", Kind::Database, "Azure Storage", Some((IconPack::Azure, "AzureBlobStorage"))),
+    kind("azurerm_servicebus_queue", Kind::Queue, "Azure Service Bus", Some((IconPack::Azure, "AzureServiceBus"))),
+    kind("azurerm_servicebus_topic", Kind::Queue, "Azure Service Bus", Some((IconPack::Azure, "AzureServiceBus"))),
+    kind("azurerm_eventhub", Kind::Queue, "Azure Event Hubs", None),
+    kind("azurerm_lb", Kind::LoadBalancer, "Azure Load Balancer", None),
+    kind("azurerm_application_gateway", Kind::LoadBalancer, "Azure Application Gateway", None),
+    kind("google_compute_instance", Kind::Compute, "Compute Engine", None),
+    kind("google_cloud_run_service", Kind::Compute, "Cloud Run", Some((IconPack::Gcp, "Cloud_Run"))),
+    kind("google_cloud_run_v2_service", Kind::Compute, "Cloud Run", Some((IconPack::Gcp, "Cloud_Run"))),
+    kind("google_cloudfunctions_function", Kind::Compute, "Cloud Functions", Some((IconPack::Gcp, "Cloud_Functions"))),
+    kind("google_cloudfunctions2_function", Kind::Compute, "Cloud Functions", Some((IconPack::Gcp, "Cloud_Functions"))),
+    kind("google_container_cluster", Kind::Compute, "Google Kubernetes Engine", Some((IconPack::Gcp, "Kubernetes_Engine"))),
+    kind("google_sql_database_instance", Kind::Database, "Cloud SQL", Some((IconPack::Gcp, "Cloud_SQL"))),
+    kind("google_firestore_database", Kind::Database, "Firestore", Some((IconPack::Gcp, "Cloud_Firestore"))),
+    kind("google_storage_bucket", Kind::Database, "Cloud Storage", Some((IconPack::Gcp, "Cloud_Storage"))),
+    kind("google_pubsub_topic", Kind::Queue, "Pub/Sub", Some((IconPack::Gcp, "Cloud_PubSub"))),
+    kind("google_compute_forwarding_rule", Kind::LoadBalancer, "Cloud Load Balancing", None),
+    kind("google_compute_global_forwarding_rule", Kind::LoadBalancer, "Cloud Load Balancing", None),
+];
+
+/// A managed resource instance
+#[derive(Debug)]
+struct Resource<'a> {
+    /// Full address, e.g. `module.db.aws_db_instance.main[0]`
+    address: String,
+    resource_type: &'a str,
+    name: &'a str,
+    values: &'a Value,
+}
+
+/// Address without its instance key: `aws_instance.web[1]` becomes `aws_instance.web`
+fn base_address(address: &str) -> &str {
+    match address.strip_suffix(']').and_then(|a| a.rfind('[')) {
+        Some(bracket) => &address[..bracket],
+        None => address,
+    }
+}
+
+fn strings(value: Option<&Value>) -> impl Iterator<Item = &str> {
+    value.and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str)
+}
+
+/// Resources and dependencies from a `terraform show -json` module
+fn read_module<'a>(module: &'a Value, resources: &mut Vec<Resource<'a>>, depends: &mut HashMap<String, Vec<String>>) {
+    for resource in module.get("resources").and_then(Value::as_array).into_iter().flatten() {
+        if resource.get("mode").and_then(Value::as_str) != Some("managed") {
+            continue;
+        }
+        let address = resource.get("address").and_then(Value::as_str).unwrap_or_default();
+        depends
+            .entry(base_address(address).to_string())
+            .or_default()
+            .extend(strings(resource.get("depends_on")).map(str::to_string));
+        resources.push(Resource {
+            address: address.to_string(),
+            resource_type: resource.get("type").and_then(Value::as_str).unwrap_or_default(),
+            name: resource.get("name").and_then(Value::as_str).unwrap_or_default(),
+            values: resource.get("values").unwrap_or(&Value::Null),
+        });
+    }
+    for child in module.get("child_modules").and_then(Value::as_array).into_iter().flatten() {
+        read_module(child, resources, depends);
+    }
+}
+
+/// Resources and dependencies from a raw `terraform.tfstate`
+fn read_raw_state<'a>(state: &'a Value, resources: &mut Vec<Resource<'a>>, depends: &mut HashMap<String, Vec<String>>) {
+    for resource in state.get("resources").and_then(Value::as_array).into_iter().flatten() {
+        if resource.get("mode").and_then(Value::as_str) != Some("managed") {
+            continue;
+        }
+        let resource_type = resource.get("type").and_then(Value::as_str).unwrap_or_default();
+        let name = resource.get("name").and_then(Value::as_str).unwrap_or_default();
+        let base = match resource.get("module").and_then(Value::as_str) {
+            Some(module) => format!("{}.{}.{}", module, resource_type, name),
+            None => format!("{}.{}", resource_type, name),
+        };
+        for instance in resource.get("instances").and_then(Value::as_array).into_iter().flatten() {
+            let address = match instance.get("index_key") {
+                Some(Value::String(key)) => format!("{}[\"{}\"]", base, key),
+                Some(key @ Value::Number(_)) => format!("{}[{}]", base, key),
+                _ => base.clone(),
+            };
+            depends
+                .entry(base.clone())
+                .or_default()
+                .extend(strings(instance.get("dependencies")).map(str::to_string));
+            resources.push(Resource {
+                address,
+                resource_type,
+                name,
+                values: instance.get("attributes").unwrap_or(&Value::Null),
+            });
+        }
+    }
+}
+
+/// References between resources in a plan's configuration, keyed by the
+/// address of the referring resource
+fn read_configuration(module: &Value, prefix: &str, depends: &mut HashMap<String, Vec<String>>) {
+    fn references<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(map) => {
+                found.extend(strings(map.get("references")));
+                map.values().for_each(|v| references(v, found));
+            }
+            Value::Array(items) => items.iter().for_each(|v| references(v, found)),
+            _ => {}
+        }
+    }
+
+    for resource in module.get("resources").and_then(Value::as_array).into_iter().flatten() {
+        let address = resource.get("address").and_then(Value::as_str).unwrap_or_default();
+        let mut found = Vec::new();
+        references(resource.get("expressions").unwrap_or(&Value::Null), &mut found);
+        found.extend(strings(resource.get("depends_on")));
+        depends
+            .entry(format!("{}{}", prefix, address))
+            .or_default()
+            .extend(found.into_iter().map(|r| format!("{}{}", prefix, r)));
+    }
+    for (name, call) in module.get("module_calls").and_then(Value::as_object).into_iter().flatten() {
+        if let Some(child) = call.get("module") {
+            read_configuration(child, &format!("{}module.{}.", prefix, name), depends);
+        }
+    }
+}
+
+/// The resource a reference such as `aws_db_instance.main.address` points at
+///
+/// References name an attribute or a single instance of a resource; the
+/// longest prefix, up to a `.` or `[`, that is a known address wins.
+fn referenced<'a>(reference: &str, known: &HashSet<&'a str>) -> Option<&'a str> {
+    reference
+        .char_indices()
+        .filter(|&(_, c)| c == '.' || c == '[')
+        .map(|(i, _)| &reference[..i])
+        .chain(std::iter::once(reference))
+        .filter_map(|prefix| known.get(prefix).copied())
+        .next_back()
+}
+
+/// The name to show for a resource: its `name`, `identifier` or `Name` tag
+/// if it has one, otherwise its name in the configuration
+fn display_name<'a>(resource: &Resource<'a>) -> &'a str {
+    let values = resource.values;
+    ["name", "identifier", "function_name", "bucket"]
+        .iter()
+        .filter_map(|key| values.get(key))
+        .chain(values.get("tags").and_then(|tags| tags.get("Name")))
+        .filter_map(Value::as_str)
+        .find(|name| !name.is_empty())
+        .unwrap_or(resource.name)
+}
+
+/// Build a container diagram from Terraform JSON
+pub fn parse_terraform(name: &str, text: &str) -> Result<Diagram, ImportError> {
+    let root: Value = serde_json::from_str(text).map_err(|e| ImportError::Parse {
+        line: e.line(),
+        message: e.to_string(),
+    })?;
+
+    let mut resources = Vec::new();
+    let mut depends: HashMap<String, Vec<String>> = HashMap::new();
+    if let Some(module) = root.pointer("/values/root_module").or_else(|| root.pointer("/planned_values/root_module")) {
+        read_module(module, &mut resources, &mut depends);
+    } else {
+        read_raw_state(&root, &mut resources, &mut depends);
+    }
+    if let Some(module) = root.pointer("/configuration/root_module") {
+        read_configuration(module, "", &mut depends);
+    }
+    if resources.is_empty() {
+        return Err(ImportError::Parse {
+            line: 1,
+            message: "no managed resources found".to_string(),
+        });
+    }
+
+    let mut diagram = Diagram::new(name, "Generated from Terraform", DiagramType::Container);
+    // Elements of each mapped resource, by base address
+    let mut mapped: HashMap<&str, Vec<ElementId>> = HashMap::new();
+    for resource in &resources {
+        let Some(kind) = RESOURCE_KINDS.iter().find(|k| k.resource_type == resource.resource_type) else {
+            continue;
+        };
+        let mut element_type = ElementType::container(display_name(resource), &resource.address, kind.kind.container_type(), kind.technology);
+        if let (ElementType::Container(data), Some((pack, sprite))) = (&mut element_type, kind.icon) {
+            data.icon = Some(CloudIcon::new(pack, sprite));
+        }
+        let element = Element::new(element_type, Position::new(0.0, 0.0));
+        mapped.entry(base_address(&resource.address)).or_default().push(element.id);
+        diagram.add_element(element);
+    }
+
+    // Follow dependencies through unmapped resources to the mapped ones
+    let known: HashSet<&str> = depends.keys().map(String::as_str).chain(mapped.keys().copied()).collect();
+    let mut sources: Vec<&str> = mapped.keys().copied().collect();
+    sources.sort();
+    for source in sources {
+        let mut targets: Vec<&str> = Vec::new();
+        let mut visited: HashSet<&str> = HashSet::from([source]);
+        let mut stack = vec![source];
+        while let Some(address) = stack.pop() {
+            for dependency in depends.get(address).into_iter().flatten() {
+                let Some(dependency) = referenced(dependency, &known) else {
+                    continue;
+                };
+                if !visited.insert(dependency) {
+                    continue;
+                }
+                if mapped.contains_key(dependency) {
+                    targets.push(dependency);
+                } else {
+                    stack.push(dependency);
+                }
+            }
+        }
+        targets.sort();
+        for target in targets {
+            for &source_id in &mapped[source] {
+                for &target_id in &mapped[target] {
+                    diagram.add_relationship(Relationship::new(source_id, target_id, "Uses"));
+                }
+            }
+        }
+    }
+
+    auto_layout(&mut diagram);
+    diagram.take_events();
+    Ok(diagram)
+}
+
+/// Read a Terraform state or plan JSON file, naming the diagram after the file
+pub fn import_terraform_file(path: &Path) -> crate::Result<Diagram> {
+    let text = std::fs::read_to_string(path).map_err(|e| crate::C2DrawError::file(path, e))?;
+    let name = path
+        .file_stem()
+        .map_or_else(|| "Terraform".to_string(), |n| n.to_string_lossy().into_owned());
+    Ok(parse_terraform(&name, &text)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(diagram: &'a Diagram, name: &str) -> &'a Element {
+        diagram.elements.values().find(|e| e.name() == name).unwrap()
+    }
+
+    fn connected(diagram: &Diagram, source: &str, target: &str) -> bool {
+        let (source, target) = (find(diagram, source).id, find(diagram, target).id);
+        diagram.relationships.iter().any(|r| r.source_id == source && r.target_id == target)
+    }
+
+    mod parse_terraform_tests {
+        use super::*;
+
+        /// Verifies `terraform show -json` state maps resources and follows dependencies through unmapped ones
+        #[test]
+        fn show_json_state() {
+            let diagram = parse_terraform(
+                "prod",
+                r#"{
+                  "values": { "root_module": {
+                    "resources": [
+                      { "address": "aws_lambda_function.api", "mode": "managed", "type": "aws_lambda_function", "name": "api",
+                        "values": { "function_name": "orders-api" }, "depends_on": ["aws_iam_role.api"] },
+                      { "address": "aws_iam_role.api", "mode": "managed", "type": "aws_iam_role", "name": "api",
+                        "values": {}, "depends_on": ["aws_sqs_queue.jobs"] },
+                      { "address": "data.aws_region.current", "mode": "data", "type": "aws_region", "name": "current", "values": {} }
+                    ],
+                    "child_modules": [ { "resources": [
+                      { "address": "module.db.aws_db_instance.main", "mode": "managed", "type": "aws_db_instance", "name": "main",
+                        "values": { "identifier": "orders" } },
+                      { "address": "aws_sqs_queue.jobs", "mode": "managed", "type": "aws_sqs_queue", "name": "jobs",
+                        "values": { "name": "" }, "depends_on": ["module.db.aws_db_instance.main"] }
+                    ] } ]
+                  } }
+                }"#,
+            )
+            .unwrap();
+            assert_eq!(diagram.elements.len(), 3);
+            let api = find(&diagram, "orders-api");
+            assert_eq!(api.description(), "aws_lambda_function.api");
+            assert!(matches!(&api.element_type, ElementType::Container(data)
+                if data.technology == "AWS Lambda" && data.icon == Some(CloudIcon::new(IconPack::Aws, "Lambda"))));
+            assert!(matches!(&find(&diagram, "jobs").element_type, ElementType::Container(data)
+                if data.container_type == ContainerType::Queue));
+
+            assert_eq!(diagram.relationships.len(), 2);
+            assert!(connected(&diagram, "orders-api", "jobs"));
+            assert!(connected(&diagram, "jobs", "orders"));
+        }
+
+        /// Verifies a raw tfstate file with counted instances and instance dependencies
+        #[test]
+        fn raw_state() {
+            let diagram = parse_terraform(
+                "state",
+                r#"{ "version": 4, "resources": [
+                  { "mode": "managed", "type": "aws_instance", "name": "web", "instances": [
+                    { "index_key": 0, "attributes": { "tags": { "Name": "web-a" } }, "dependencies": ["aws_lb.front", "aws_db_instance.main"] },
+                    { "index_key": 1, "attributes": { "tags": { "Name": "web-b" } } }
+                  ] },
+                  { "mode": "managed", "type": "aws_db_instance", "name": "main", "instances": [ { "attributes": {} } ] },
+                  { "mode": "managed", "type": "aws_lb", "name": "front", "instances": [ { "attributes": {} } ] }
+                ] }"#,
+            )
+            .unwrap();
+            assert_eq!(diagram.elements.len(), 4);
+            assert_eq!(find(&diagram, "web-b").description(), "aws_instance.web[1]");
+            // Dependencies are recorded per resource, so every instance shares them
+            assert!(connected(&diagram, "web-a", "main"));
+            assert!(connected(&diagram, "web-b", "main"));
+            assert!(connected(&diagram, "web-b", "front"));
+        }
+
+        /// Verifies plan configuration references to resources or their attributes become relationships
+        #[test]
+        fn plan_references() {
+            let diagram = parse_terraform(
+                "plan",
+                r#"{
+                  "planned_values": { "root_module": { "resources": [
+                    { "address": "google_cloud_run_v2_service.app", "mode": "managed", "type": "google_cloud_run_v2_service", "name": "app", "values": {} },
+                    { "address": "google_pubsub_topic.events", "mode": "managed", "type": "google_pubsub_topic", "name": "events", "values": {} }
+                  ] } },
+                  "configuration": { "root_module": { "resources": [ { "address": "google_cloud_run_v2_service.app",
+                    "expressions": { "env": { "references": ["google_pubsub_topic.events.id", "google_pubsub_topic.events"] } } } ] } }
+                }"#,
+            )
+            .unwrap();
+            assert_eq!(diagram.relationships.len(), 1);
+            assert!(connected(&diagram, "app", "events"));
+        }
+
+        /// Verifies invalid JSON and JSON without resources are rejected
+        #[test]
+        fn invalid_input_is_error() {
+            assert!(matches!(parse_terraform("x", "{\n  \"values\": ]"), Err(ImportError::Parse { line: 2, .. })));
+            assert!(parse_terraform("x", "{}").is_err());
+        }
+    }
+}
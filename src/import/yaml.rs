@@ -0,0 +1,601 @@
+//! A YAML subset reader for configuration files
+//!
+//! Covers what docker-compose and Kubernetes manifests use in practice:
+//! block mappings and sequences, flow collections on a single line, plain,
+//! single- and double-quoted scalars, `|` / `>` block scalars, comments,
+//! `---` document separators, and anchors with `*alias` and `<<` merge keys.
+//! Tags are ignored and every scalar is kept as a string; multi-line flow
+//! collections and multi-line plain scalars are not supported.
+
+use super::ImportError;
+use std::collections::HashMap;
+
+/// A parsed YAML node
+#[derive(Debug, Clone, PartialEq)]
+pub enum Yaml {
+    Null,
+    Scalar(String),
+    List(Vec<Yaml>),
+    /// Entries in document order
+    Map(Vec<(String, Yaml)>),
+}
+
+impl Yaml {
+    /// Value of `key` if this is a mapping
+    pub fn get(&self, key: &str) -> Option<&Yaml> {
+        match self {
+            Yaml::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Yaml::Scalar(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Items of a sequence; empty for anything else
+    pub fn as_list(&self) -> &[Yaml] {
+        match self {
+            Yaml::List(items) => items,
+            _ => &[],
+        }
+    }
+
+    /// Entries of a mapping; empty for anything else
+    pub fn as_map(&self) -> &[(String, Yaml)] {
+        match self {
+            Yaml::Map(entries) => entries,
+            _ => &[],
+        }
+    }
+
+    /// Names listed either as a sequence of scalars or as the keys of a mapping
+    ///
+    /// Compose accepts both forms for `depends_on` and `networks`.
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            Yaml::List(items) => items.iter().filter_map(Yaml::as_str).collect(),
+            Yaml::Map(entries) => entries.iter().map(|(k, _)| k.as_str()).collect(),
+            Yaml::Scalar(s) => vec![s.as_str()],
+            Yaml::Null => Vec::new(),
+        }
+    }
+}
+
+/// A non-blank source line
+#[derive(Debug, Clone)]
+struct Line {
+    /// 1-based line number
+    number: usize,
+    indent: usize,
+    /// Content after the indent with any comment removed
+    content: String,
+    /// The whole line as written, for block scalars
+    raw: String,
+}
+
+fn error(line: usize, message: impl Into<String>) -> ImportError {
+    ImportError::Parse {
+        line,
+        message: message.into(),
+    }
+}
+
+/// Remove a trailing comment: `#` at the start or after whitespace, outside quotes
+fn strip_comment(content: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    let mut escaped = false;
+    for (i, c) in content.char_indices() {
+        match quote {
+            // a backslash escapes the next character in double quotes
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if (c == '"' || c == '\'') && matches!(previous, ' ' | ':' | '-' | '[' | '{' | ',') => quote = Some(c),
+            None if c == '#' && previous.is_whitespace() => return content[..i].trim_end(),
+            None => {}
+        }
+        previous = c;
+    }
+    content.trim_end()
+}
+
+/// Position of the `:` separating a mapping key from its value, if any
+fn key_separator(content: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut depth = 0usize;
+    let mut chars = content.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Some('"') if c == '\\' => {
+                chars.next();
+            }
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' if i == 0 => quote = Some(c),
+                '[' | '{' => depth += 1,
+                ']' | '}' => depth = depth.saturating_sub(1),
+                ':' if depth == 0 && chars.peek().is_none_or(|(_, next)| next.is_whitespace()) => return Some(i),
+                _ => {}
+            },
+        }
+    }
+    None
+}
+
+/// Whether a line is a sequence item (`-` followed by a space or nothing)
+fn is_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+/// Parse a YAML stream into its documents
+///
+/// Empty documents are left out.
+pub fn parse(text: &str) -> Result<Vec<Yaml>, ImportError> {
+    let mut documents = Vec::new();
+    let mut lines = Vec::new();
+    for (index, raw) in text.lines().enumerate() {
+        let number = index + 1;
+        if raw.starts_with("---") || raw.starts_with("...") {
+            documents.push(std::mem::take(&mut lines));
+            let rest = strip_comment(raw[3..].trim());
+            if !rest.is_empty() {
+                lines.push(Line { number, indent: 0, content: rest.to_string(), raw: raw.to_string() });
+            }
+            continue;
+        }
+        let trimmed = raw.trim_start_matches(' ');
+        if trimmed.starts_with('\t') {
+            return Err(error(number, "tabs are not allowed for indentation"));
+        }
+        let content = strip_comment(trimmed);
+        if content.is_empty() || raw.starts_with('%') {
+            // Keep blank lines inside block scalars
+            if content.is_empty() && !lines.is_empty() {
+                lines.push(Line { number, indent: usize::MAX, content: String::new(), raw: raw.to_string() });
+            }
+            continue;
+        }
+        lines.push(Line { number, indent: raw.len() - trimmed.len(), content: content.to_string(), raw: raw.to_string() });
+    }
+    documents.push(lines);
+
+    documents
+        .into_iter()
+        .filter_map(|mut lines| {
+            while lines.last().is_some_and(|l| l.indent == usize::MAX) {
+                lines.pop();
+            }
+            (!lines.is_empty()).then(|| {
+                let mut parser = Parser { lines, pos: 0, anchors: HashMap::new() };
+                parser.document()
+            })
+        })
+        .collect()
+}
+
+struct Parser {
+    lines: Vec<Line>,
+    pos: usize,
+    anchors: HashMap<String, Yaml>,
+}
+
+impl Parser {
+    /// The next line holding content, skipping blank lines kept for block scalars
+    fn peek(&mut self) -> Option<&Line> {
+        while self.lines.get(self.pos).is_some_and(|l| l.indent == usize::MAX) {
+            self.pos += 1;
+        }
+        self.lines.get(self.pos)
+    }
+
+    fn document(&mut self) -> Result<Yaml, ImportError> {
+        let indent = self.peek().map_or(0, |l| l.indent);
+        let value = self.block(indent)?;
+        if let Some(line) = self.peek() {
+            return Err(error(line.number, "unexpected indentation"));
+        }
+        Ok(value)
+    }
+
+    /// A block node whose lines start at `indent`
+    fn block(&mut self, indent: usize) -> Result<Yaml, ImportError> {
+        let Some(line) = self.peek() else {
+            return Ok(Yaml::Null);
+        };
+        if is_item(&line.content) {
+            self.sequence(indent)
+        } else if key_separator(&line.content).is_some() {
+            self.mapping(indent)
+        } else {
+            let line = line.clone();
+            self.pos += 1;
+            self.inline(&line.content, line.number)
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<Yaml, ImportError> {
+        let mut items = Vec::new();
+        while let Some(line) = self.peek() {
+            if line.indent != indent || !is_item(&line.content) {
+                break;
+            }
+            let line = line.clone();
+            let rest = line.content[1..].trim_start();
+            if rest.is_empty() {
+                self.pos += 1;
+                items.push(self.nested(indent)?);
+            } else if is_item(rest) || key_separator(rest).is_some() {
+                // The item's content starts on the dash line: reparse it as
+                // a line indented to where that content begins
+                let offset = line.content.len() - rest.len();
+                self.lines[self.pos].indent = indent + offset;
+                self.lines[self.pos].content = rest.to_string();
+                items.push(self.block(indent + offset)?);
+            } else {
+                self.pos += 1;
+                items.push(self.value(rest, indent, line.number)?);
+            }
+        }
+        Ok(Yaml::List(items))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<Yaml, ImportError> {
+        let mut entries: Vec<(String, Yaml)> = Vec::new();
+        while let Some(line) = self.peek() {
+            if line.indent != indent || is_item(&line.content) {
+                if line.indent > indent {
+                    return Err(error(line.number, "unexpected indentation"));
+                }
+                break;
+            }
+            let line = line.clone();
+            let Some(separator) = key_separator(&line.content) else {
+                return Err(error(line.number, format!("expected \"key: value\", found \"{}\"", line.content)));
+            };
+            self.pos += 1;
+            let key = unquote(line.content[..separator].trim(), line.number)?;
+            let rest = line.content[separator + 1..].trim();
+            let value = if rest.is_empty() {
+                // A sequence may sit at the same indent as its key
+                match self.peek() {
+                    Some(next) if next.indent == indent && is_item(&next.content) => self.sequence(indent)?,
+                    _ => self.nested(indent)?,
+                }
+            } else {
+                self.value(rest, indent, line.number)?
+            };
+            if key == "<<" {
+                for (k, v) in value.as_map() {
+                    if !entries.iter().any(|(existing, _)| existing == k) {
+                        entries.push((k.clone(), v.clone()));
+                    }
+                }
+            } else if let Some(entry) = entries.iter_mut().find(|(k, _)| *k == key) {
+                entry.1 = value;
+            } else {
+                entries.push((key, value));
+            }
+        }
+        Ok(Yaml::Map(entries))
+    }
+
+    /// The block nested under a key or dash at `indent`, or null if there is none
+    fn nested(&mut self, indent: usize) -> Result<Yaml, ImportError> {
+        match self.peek() {
+            Some(next) if next.indent > indent => {
+                let next_indent = next.indent;
+                self.block(next_indent)
+            }
+            _ => Ok(Yaml::Null),
+        }
+    }
+
+    /// The value written after a key or dash, with any anchor recorded
+    fn value(&mut self, text: &str, indent: usize, number: usize) -> Result<Yaml, ImportError> {
+        let (anchor, text) = match text.strip_prefix('&') {
+            Some(rest) => {
+                let (name, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+                (Some(name.to_string()), rest.trim())
+            }
+            None => (None, text),
+        };
+        let text = skip_tag(text);
+        let value = if text.is_empty() {
+            self.nested(indent)?
+        } else if let Some(name) = text.strip_prefix('*') {
+            self.anchors
+                .get(name)
+                .cloned()
+                .ok_or_else(|| error(number, format!("unknown alias \"{}\"", name)))?
+        } else if text.starts_with('|') || text.starts_with('>') {
+            self.block_scalar(text.starts_with('>'), indent)
+        } else {
+            self.inline(text, number)?
+        };
+        if let Some(anchor) = anchor {
+            self.anchors.insert(anchor, value.clone());
+        }
+        Ok(value)
+    }
+
+    /// The lines of a `|` (literal) or `>` (folded) block scalar
+    fn block_scalar(&mut self, folded: bool, indent: usize) -> Yaml {
+        let mut body: Vec<&str> = Vec::new();
+        let mut body_indent = None;
+        while let Some(line) = self.lines.get(self.pos) {
+            if line.indent != usize::MAX && line.indent <= indent {
+                break;
+            }
+            if line.indent != usize::MAX {
+                body_indent.get_or_insert(line.indent);
+            }
+            let strip = body_indent.unwrap_or(0).min(line.raw.len());
+            body.push(line.raw.get(strip..).unwrap_or(""));
+            self.pos += 1;
+        }
+        let separator = if folded { " " } else { "\n" };
+        let mut text = body.join(separator).trim_end_matches(['\n', ' ']).to_string();
+        text.push('\n');
+        Yaml::Scalar(text)
+    }
+
+    fn inline(&self, text: &str, number: usize) -> Result<Yaml, ImportError> {
+        let mut flow = Flow::new(text, number);
+        let value = flow.node()?;
+        flow.skip_spaces();
+        if flow.pos < text.len() {
+            return Err(error(number, format!("unexpected \"{}\"", &text[flow.pos..])));
+        }
+        Ok(value)
+    }
+}
+
+/// Drop a leading `!tag`
+fn skip_tag(text: &str) -> &str {
+    match text.strip_prefix('!') {
+        Some(rest) => rest.split_once(' ').map_or("", |(_, rest)| rest.trim_start()),
+        None => text,
+    }
+}
+
+/// A plain or quoted mapping key
+fn unquote(text: &str, number: usize) -> Result<String, ImportError> {
+    if text.starts_with('"') || text.starts_with('\'') {
+        Flow::new(text, number).quoted()
+    } else {
+        Ok(text.to_string())
+    }
+}
+
+fn plain(text: &str) -> Yaml {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => Yaml::Null,
+        _ => Yaml::Scalar(text.to_string()),
+    }
+}
+
+/// Parser for a single-line flow node: `[a, b]`, `{k: v}` or a scalar
+struct Flow<'a> {
+    text: &'a [u8],
+    pos: usize,
+    source: &'a str,
+    number: usize,
+    /// Number of enclosing brackets
+    depth: usize,
+}
+
+impl<'a> Flow<'a> {
+    fn new(source: &'a str, number: usize) -> Self {
+        Self { text: source.as_bytes(), pos: 0, source, number, depth: 0 }
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.text.get(self.pos) == Some(&b' ') {
+            self.pos += 1;
+        }
+    }
+
+    fn node(&mut self) -> Result<Yaml, ImportError> {
+        self.skip_spaces();
+        match self.text.get(self.pos) {
+            Some(b'[') => {
+                self.pos += 1;
+                self.depth += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_spaces();
+                    if self.text.get(self.pos) == Some(&b']') {
+                        self.pos += 1;
+                        self.depth -= 1;
+                        return Ok(Yaml::List(items));
+                    }
+                    items.push(self.node()?);
+                    self.end_of_entry(b']')?;
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                self.depth += 1;
+                let mut entries = Vec::new();
+                loop {
+                    self.skip_spaces();
+                    if self.text.get(self.pos) == Some(&b'}') {
+                        self.pos += 1;
+                        self.depth -= 1;
+                        return Ok(Yaml::Map(entries));
+                    }
+                    let key = match self.node()? {
+                        Yaml::Scalar(s) => s,
+                        _ => String::new(),
+                    };
+                    self.skip_spaces();
+                    let value = if self.text.get(self.pos) == Some(&b':') {
+                        self.pos += 1;
+                        self.node()?
+                    } else {
+                        Yaml::Null
+                    };
+                    entries.push((key, value));
+                    self.end_of_entry(b'}')?;
+                }
+            }
+            Some(b'"' | b'\'') => self.quoted().map(Yaml::Scalar),
+            _ => {
+                let start = self.pos;
+                while let Some(&c) = self.text.get(self.pos) {
+                    let ends_key = c == b':' && self.text.get(self.pos + 1).is_none_or(|n| *n == b' ' || *n == b',');
+                    if self.depth > 0 && (matches!(c, b',' | b']' | b'}') || ends_key) {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                Ok(plain(self.source[start..self.pos].trim()))
+            }
+        }
+    }
+
+    /// Consume the `,` after a flow entry, or leave the closing bracket
+    fn end_of_entry(&mut self, close: u8) -> Result<(), ImportError> {
+        self.skip_spaces();
+        match self.text.get(self.pos) {
+            Some(b',') => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(&c) if c == close => Ok(()),
+            _ => Err(error(self.number, format!("expected \",\" or \"{}\"", close as char))),
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, ImportError> {
+        let quote = self.source[self.pos..].chars().next().unwrap_or('"');
+        let mut chars = self.source[self.pos + 1..].char_indices();
+        let mut value = String::new();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                _ if c == quote => {
+                    if quote == '\'' && self.source[self.pos + 1 + i + 1..].starts_with('\'') {
+                        value.push('\'');
+                        chars.next();
+                        continue;
+                    }
+                    self.pos += 1 + i + 1;
+                    return Ok(value);
+                }
+                '\\' if quote == '"' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some(other) => value.push(other),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        Err(error(self.number, "unterminated quoted string"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn one(text: &str) -> Yaml {
+        let mut documents = parse(text).unwrap();
+        assert_eq!(documents.len(), 1);
+        documents.remove(0)
+    }
+
+    fn s(value: &str) -> Yaml {
+        Yaml::Scalar(value.to_string())
+    }
+
+    mod block_tests {
+        use super::*;
+
+        /// Verifies nested mappings and both sequence indent styles
+        #[test]
+        fn parse_nested_blocks() {
+            let doc = one("services:\n  web:\n    image: nginx # proxy\n    ports:\n    - \"80:80\"\n    depends_on:\n      - api\n  api:\n    build: .\n");
+            let web = doc.get("services").and_then(|s| s.get("web")).unwrap();
+            assert_eq!(web.get("image"), Some(&s("nginx")));
+            assert_eq!(web.get("ports"), Some(&Yaml::List(vec![s("80:80")])));
+            assert_eq!(web.get("depends_on").unwrap().names(), vec!["api"]);
+            assert_eq!(doc.get("services").unwrap().as_map().len(), 2);
+        }
+
+        /// Verifies mappings that start on a sequence item's dash line
+        #[test]
+        fn parse_mapping_in_sequence() {
+            let doc = one("containers:\n  - name: app\n    image: shop:1\n  - name: sidecar\n");
+            let containers = doc.get("containers").unwrap().as_list();
+            assert_eq!(containers.len(), 2);
+            assert_eq!(containers[0].get("image"), Some(&s("shop:1")));
+            assert_eq!(containers[1].get("name"), Some(&s("sidecar")));
+        }
+
+        /// Verifies block scalars keep their lines and do not swallow later keys
+        #[test]
+        fn parse_block_scalar() {
+            let doc = one("script: |\n  echo one\n\n  echo two\nnext: value\n");
+            assert_eq!(doc.get("script"), Some(&s("echo one\n\necho two\n")));
+            assert_eq!(doc.get("next"), Some(&s("value")));
+        }
+
+        /// Verifies anchors, aliases and merge keys
+        #[test]
+        fn parse_anchors_and_merges() {
+            let doc = one("base: &base\n  restart: always\n  image: app\nworker:\n  <<: *base\n  image: worker\n");
+            let worker = doc.get("worker").unwrap();
+            assert_eq!(worker.get("image"), Some(&s("worker")));
+            assert_eq!(worker.get("restart"), Some(&s("always")));
+        }
+
+        /// Verifies `---` separates documents and empty documents are dropped
+        #[test]
+        fn parse_multiple_documents() {
+            let documents = parse("---\nkind: Service\n---\n# nothing\n---\nkind: Deployment\n").unwrap();
+            assert_eq!(documents.len(), 2);
+            assert_eq!(documents[1].get("kind"), Some(&s("Deployment")));
+        }
+
+        /// Verifies badly indented lines report their line number
+        #[test]
+        fn parse_reports_bad_indentation() {
+            let error = parse("a:\n  b: 1\n    c: 2\n").unwrap_err();
+            assert!(matches!(error, ImportError::Parse { line: 3, .. }), "{:?}", error);
+        }
+    }
+
+    mod flow_tests {
+        use super::*;
+
+        /// Verifies flow sequences and mappings with quoted scalars
+        #[test]
+        fn parse_flow_collections() {
+            let doc = one("networks: [front, 'back end']\nlabels: {tier: \"web\", team: shop}\nempty: []\n");
+            assert_eq!(doc.get("networks").unwrap().names(), vec!["front", "back end"]);
+            assert_eq!(doc.get("labels").and_then(|l| l.get("tier")), Some(&s("web")));
+            assert_eq!(doc.get("empty"), Some(&Yaml::List(Vec::new())));
+        }
+
+        /// Verifies quoted scalars keep `#` and `:` and decode escapes
+        #[test]
+        fn parse_quoted_scalars() {
+            let doc = one("a: \"x # y: z\"\nb: 'it''s'\nc: \"line\\nbreak\"\nd: ~\nurl: http://host:80/path\ne: \"a \\\"b # c\\\"\"\n");
+            assert_eq!(doc.get("a"), Some(&s("x # y: z")));
+            assert_eq!(doc.get("b"), Some(&s("it's")));
+            assert_eq!(doc.get("c"), Some(&s("line\nbreak")));
+            assert_eq!(doc.get("d"), Some(&Yaml::Null));
+            assert_eq!(doc.get("url"), Some(&s("http://host:80/path")));
+            assert_eq!(doc.get("e"), Some(&s("a \"b # c\"")));
+        }
+    }
+}
@@ -4,7 +4,25 @@
 //! It supports System Context (C1) and Container (C2) diagrams with export
 //! to PlantUML and Mermaid formats.
 
+#[cfg(feature = "gui")]
 pub mod app;
+pub mod backup;
+pub mod clipboard;
+pub mod dsl;
+pub mod error;
 pub mod export;
+pub mod history;
+pub mod import;
+pub mod lint;
 pub mod model;
-pub mod ui;
\ No newline at end of file
+pub mod preferences;
+pub mod presets;
+pub mod project;
+pub mod quick_add;
+pub mod server;
+pub mod share;
+pub mod style;
+#[cfg(feature = "gui")]
+pub mod ui;
+
+pub use error::{C2DrawError, Result};
\ No newline at end of file
@@ -0,0 +1,158 @@
+//! Diagram linting for CI (`c2draw lint`)
+//!
+//! Loads each diagram file, runs the validation rules over it and prints one
+//! line per issue as `file: severity [rule] message`. The run fails when any
+//! issue is at least as severe as `--fail-on` (errors by default), or when a
+//! file cannot be loaded, so broken diagrams fail the pipeline.
+
+use crate::model::{validation, Diagram, Severity};
+use std::io::Write;
+use std::path::Path;
+
+const USAGE: &str = "usage: c2draw lint [--fail-on error|warning|info] <file.c4d>...";
+
+/// Issue counts over a lint run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LintSummary {
+    pub files: usize,
+    /// Files that could not be loaded
+    pub unreadable: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub infos: usize,
+}
+
+impl LintSummary {
+    /// Whether the run should fail at the `fail_on` threshold
+    pub fn failed(&self, fail_on: Severity) -> bool {
+        let failing = match fail_on {
+            Severity::Error => self.errors,
+            Severity::Warning => self.errors + self.warnings,
+            Severity::Info => self.errors + self.warnings + self.infos,
+        };
+        self.unreadable + failing > 0
+    }
+}
+
+/// Lint one diagram, writing its issues to `out` prefixed with `path`
+pub fn lint_diagram(path: &str, diagram: &Diagram, summary: &mut LintSummary, out: &mut impl Write) -> std::io::Result<()> {
+    summary.files += 1;
+    for issue in validation::validate(diagram) {
+        match issue.severity {
+            Severity::Error => summary.errors += 1,
+            Severity::Warning => summary.warnings += 1,
+            Severity::Info => summary.infos += 1,
+        }
+        writeln!(out, "{}: {} [{}] {}", path, issue.severity.as_str(), issue.rule, issue.message)?;
+    }
+    Ok(())
+}
+
+/// Run `c2draw lint` with the arguments after the subcommand
+///
+/// Returns whether the diagrams passed. Files that cannot be loaded are
+/// reported and fail the run; only bad arguments are returned as errors.
+pub fn run_cli(args: &[String], out: &mut impl Write) -> crate::Result<bool> {
+    let mut fail_on = Severity::Error;
+    let mut files = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fail-on" => {
+                let name = args.next().ok_or_else(|| usage_error("--fail-on requires a severity"))?;
+                fail_on = Severity::parse(name).ok_or_else(|| usage_error(&format!("unknown severity: {}", name)))?;
+            }
+            other if other.starts_with("--") => return Err(usage_error(&format!("unknown option: {}", other))),
+            other => files.push(other),
+        }
+    }
+    if files.is_empty() {
+        return Err(usage_error("missing diagram file"));
+    }
+
+    let mut summary = LintSummary::default();
+    for file in files {
+        match Diagram::load_from_file(Path::new(file)) {
+            Ok(diagram) => lint_diagram(file, &diagram, &mut summary, out)?,
+            Err(e) => {
+                summary.unreadable += 1;
+                writeln!(out, "{}: error [unreadable] {}", file, e)?;
+            }
+        }
+    }
+    writeln!(
+        out,
+        "{} files checked: {} errors, {} warnings, {} info{}",
+        summary.files + summary.unreadable,
+        summary.errors + summary.unreadable,
+        summary.warnings,
+        summary.infos,
+        if summary.failed(fail_on) { "" } else { " (passed)" }
+    )?;
+    Ok(!summary.failed(fail_on))
+}
+
+fn usage_error(message: &str) -> crate::C2DrawError {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{}\n{}", message, USAGE)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContainerType, DiagramType, Element, ElementType, Position};
+
+    /// Save a System Context diagram holding a container, which only warns
+    fn warning_diagram() -> String {
+        let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+        diagram.add_element(Element::new(
+            ElementType::container("API", "", ContainerType::Microservice, ""),
+            Position::new(0.0, 0.0),
+        ));
+        let path = std::env::temp_dir().join(format!("c2draw-lint-{}.c4d", uuid::Uuid::new_v4()));
+        diagram.save_to_file(&path).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn run(args: &[&str]) -> (crate::Result<bool>, String) {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut out = Vec::new();
+        let result = run_cli(&args, &mut out);
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    mod run_cli_tests {
+        use super::*;
+
+        /// Verifies warnings are reported but only fail the run when asked to
+        #[test]
+        fn fail_on_threshold() {
+            let file = warning_diagram();
+
+            let (passed, output) = run(&[&file]);
+            assert!(passed.unwrap());
+            assert!(output.contains(&format!("{}: warning [container-on-context] container \"API\"", file)));
+            assert!(output.ends_with("1 files checked: 0 errors, 1 warnings, 0 info (passed)\n"));
+
+            let (passed, _) = run(&["--fail-on", "warning", &file]);
+            assert!(!passed.unwrap());
+            std::fs::remove_file(&file).unwrap();
+        }
+
+        /// Verifies missing files are reported and fail the run
+        #[test]
+        fn unreadable_file_fails() {
+            let (passed, output) = run(&["/nonexistent/shop.c4d"]);
+            assert!(!passed.unwrap());
+            assert!(output.starts_with("/nonexistent/shop.c4d: error [unreadable] "));
+        }
+
+        /// Verifies bad arguments are usage errors
+        #[test]
+        fn rejects_bad_arguments() {
+            assert!(run(&[]).0.is_err());
+            assert!(run(&["--fail-on", "fatal", "shop.c4d"]).0.is_err());
+            assert!(run(&["--strict", "shop.c4d"]).0.is_err());
+        }
+    }
+}
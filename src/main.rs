@@ -1,6 +1,18 @@
+#[cfg(feature = "gui")]
 use c2draw::app::C2DrawApp;
 
+#[cfg(feature = "gui")]
 fn main() -> eframe::Result {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("serve") => {
+            serve(&args[1..]);
+            return Ok(());
+        }
+        Some("lint") => lint(&args[1..]),
+        _ => {}
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1024.0, 768.0])
@@ -14,3 +26,37 @@ fn main() -> eframe::Result {
         Box::new(|cc| Ok(Box::new(C2DrawApp::new(cc)))),
     )
 }
+
+/// Headless builds only provide the `serve` and `lint` subcommands
+#[cfg(not(feature = "gui"))]
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("serve") => serve(&args[1..]),
+        Some("lint") => lint(&args[1..]),
+        _ => {
+            eprintln!(
+                "c2draw was built without the \"gui\" feature\nusage: c2draw serve [--bind ADDRESS] <file.c4d>\n       c2draw lint [--fail-on error|warning|info] <file.c4d>..."
+            );
+            std::process::exit(2);
+        }
+    }
+}
+
+fn serve(args: &[String]) {
+    if let Err(e) = c2draw::server::run_cli(args) {
+        eprintln!("c2draw serve: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Exits 0 when the diagrams pass, 1 when they don't and 2 on bad arguments
+fn lint(args: &[String]) -> ! {
+    match c2draw::lint::run_cli(args, &mut std::io::stdout()) {
+        Ok(passed) => std::process::exit(if passed { 0 } else { 1 }),
+        Err(e) => {
+            eprintln!("c2draw lint: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
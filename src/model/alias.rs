@@ -0,0 +1,151 @@
+//! Export aliases
+//!
+//! PlantUML and Mermaid refer to elements by an identifier. An element's
+//! alias is derived from its name (`Payment API` becomes `payment_api`, with
+//! `_2`, `_3`, ... when names collide) and stored with the element, so it
+//! does not change when the element is renamed and re-exporting an unchanged
+//! diagram gives the same text. Elements without an alias are exported as
+//! `elem_<uuid>`.
+
+use super::{Diagram, Element, ElementId};
+use std::collections::HashSet;
+
+/// Lowercase ASCII letters, digits and single underscores taken from `name`
+///
+/// Never empty and never starts with a digit, so the result is usable as an
+/// identifier in both PlantUML and Mermaid.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    let slug = slug.trim_end_matches('_');
+    match slug.chars().next() {
+        None => "element".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("e_{}", slug),
+        Some(_) => slug.to_string(),
+    }
+}
+
+/// Whether `alias` could have come from [`slugify`]
+fn is_valid(alias: &str) -> bool {
+    alias.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+        && alias.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// The fallback alias for elements without one
+fn uuid_alias(id: ElementId) -> String {
+    format!("elem_{}", id.simple())
+}
+
+impl Element {
+    /// Identifier used for this element in exported text
+    pub fn export_alias(&self) -> String {
+        self.alias.clone().unwrap_or_else(|| uuid_alias(self.id))
+    }
+}
+
+impl Diagram {
+    /// Identifier used for the element in exported text, also for IDs not in the diagram
+    pub fn export_alias(&self, id: ElementId) -> String {
+        self.get_element(id).map_or_else(|| uuid_alias(id), Element::export_alias)
+    }
+
+    /// Give every element without a usable alias one derived from its name
+    ///
+    /// Existing aliases are kept; where two elements share one, the element
+    /// with the lower ID keeps it. Returns the number of aliases assigned.
+    pub fn assign_aliases(&mut self) -> usize {
+        let mut ids: Vec<ElementId> = self.elements.keys().copied().collect();
+        ids.sort();
+
+        let mut taken = HashSet::new();
+        let mut missing = Vec::new();
+        for id in ids {
+            match self.elements[&id].alias.as_deref() {
+                Some(alias) if is_valid(alias) && taken.insert(alias.to_string()) => {}
+                _ => missing.push(id),
+            }
+        }
+
+        for id in &missing {
+            let element = self.elements.get_mut(id).expect("IDs come from the map");
+            let base = slugify(element.name());
+            let alias = (1..)
+                .map(|n| if n == 1 { base.clone() } else { format!("{}_{}", base, n) })
+                .find(|alias| !taken.contains(alias))
+                .expect("unbounded range");
+            taken.insert(alias.clone());
+            element.alias = Some(alias);
+        }
+        missing.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, ElementType, Position};
+
+    fn add(diagram: &mut Diagram, name: &str) -> ElementId {
+        let element = Element::new(ElementType::system(name, ""), Position::new(0.0, 0.0));
+        let id = element.id;
+        diagram.add_element(element);
+        id
+    }
+
+    mod alias_tests {
+        use super::*;
+
+        /// Verifies names become lowercase identifiers
+        #[test]
+        fn slugify_names() {
+            assert_eq!(slugify("Payment API"), "payment_api");
+            assert_eq!(slugify("  Web/Mobile -- App! "), "web_mobile_app");
+            assert_eq!(slugify("3D Renderer"), "e_3d_renderer");
+            assert_eq!(slugify("Café"), "caf");
+            assert_eq!(slugify("???"), "element");
+        }
+
+        /// Verifies colliding names get numbered aliases and existing aliases survive renames
+        #[test]
+        fn assign_aliases_is_stable() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+            let first = add(&mut diagram, "Shop");
+            let second = add(&mut diagram, "shop");
+            assert_eq!(diagram.assign_aliases(), 2);
+            let (low, high) = (first.min(second), first.max(second));
+            assert_eq!(diagram.export_alias(low), "shop");
+            assert_eq!(diagram.export_alias(high), "shop_2");
+
+            diagram.get_element_mut(low).unwrap().set_name("Store".to_string());
+            assert_eq!(diagram.assign_aliases(), 0);
+            assert_eq!(diagram.export_alias(low), "shop");
+        }
+
+        /// Verifies duplicated and invalid aliases are replaced, and unknown IDs fall back to the UUID
+        #[test]
+        fn assign_aliases_repairs_duplicates() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+            let first = add(&mut diagram, "Billing");
+            let second = add(&mut diagram, "Billing");
+            let odd = add(&mut diagram, "Odd");
+            for id in [first, second] {
+                diagram.get_element_mut(id).unwrap().alias = Some("billing".to_string());
+            }
+            diagram.get_element_mut(odd).unwrap().alias = Some("not valid".to_string());
+
+            assert_eq!(diagram.assign_aliases(), 2);
+            assert_eq!(diagram.export_alias(first.min(second)), "billing");
+            assert_eq!(diagram.export_alias(first.max(second)), "billing_2");
+            assert_eq!(diagram.export_alias(odd), "odd");
+
+            let unknown = ElementId::new_v4();
+            assert_eq!(diagram.export_alias(unknown), format!("elem_{}", unknown.simple()));
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use super::{Diagram, DiagramEvent, ElementId, Position};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// What a comment thread is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CommentAnchor {
+    Element(ElementId),
+    /// A point on the canvas, in diagram coordinates
+    Position(Position),
+}
+
+/// A single review comment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    pub author: String,
+    pub text: String,
+}
+
+impl Comment {
+    pub fn new(author: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            author: author.into(),
+            text: text.into(),
+        }
+    }
+}
+
+/// A review discussion: an opening comment and its replies
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommentThread {
+    pub id: Uuid,
+    pub anchor: CommentAnchor,
+    pub comments: Vec<Comment>,
+    #[serde(default)]
+    pub resolved: bool,
+}
+
+impl CommentThread {
+    pub fn new(anchor: CommentAnchor, comment: Comment) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            anchor,
+            comments: vec![comment],
+            resolved: false,
+        }
+    }
+
+    /// Where the thread's marker is drawn: an element's top-right corner or
+    /// the anchored point. None if the element no longer exists.
+    pub fn marker_position(&self, diagram: &Diagram) -> Option<Position> {
+        match self.anchor {
+            CommentAnchor::Element(id) => diagram
+                .get_element(id)
+                .map(|e| Position::new(e.position.x + e.size.width, e.position.y)),
+            CommentAnchor::Position(position) => Some(position),
+        }
+    }
+}
+
+impl Diagram {
+    /// Start a new comment thread, returning its ID
+    pub fn add_comment_thread(&mut self, anchor: CommentAnchor, comment: Comment) -> Uuid {
+        let thread = CommentThread::new(anchor, comment);
+        let id = thread.id;
+        self.comments.push(thread);
+        self.emit(DiagramEvent::CommentsChanged(id));
+        id
+    }
+
+    pub fn get_comment_thread(&self, id: Uuid) -> Option<&CommentThread> {
+        self.comments.iter().find(|t| t.id == id)
+    }
+
+    /// Append a reply, returning false if the thread does not exist
+    pub fn reply_to_comment_thread(&mut self, id: Uuid, comment: Comment) -> bool {
+        let Some(thread) = self.comments.iter_mut().find(|t| t.id == id) else {
+            return false;
+        };
+        thread.comments.push(comment);
+        self.emit(DiagramEvent::CommentsChanged(id));
+        true
+    }
+
+    /// Mark a thread resolved or reopen it, returning false if it does not exist
+    pub fn set_comment_thread_resolved(&mut self, id: Uuid, resolved: bool) -> bool {
+        let Some(thread) = self.comments.iter_mut().find(|t| t.id == id) else {
+            return false;
+        };
+        if thread.resolved != resolved {
+            thread.resolved = resolved;
+            self.emit(DiagramEvent::CommentsChanged(id));
+        }
+        true
+    }
+
+    pub fn remove_comment_thread(&mut self, id: Uuid) {
+        let before = self.comments.len();
+        self.comments.retain(|t| t.id != id);
+        if self.comments.len() != before {
+            self.emit(DiagramEvent::CommentsChanged(id));
+        }
+    }
+
+    /// Drop threads attached to elements that no longer exist
+    pub(crate) fn remove_orphaned_comments(&mut self) {
+        let elements = &self.elements;
+        let mut removed = Vec::new();
+        self.comments.retain(|t| match t.anchor {
+            CommentAnchor::Element(id) if !elements.contains_key(&id) => {
+                removed.push(t.id);
+                false
+            }
+            _ => true,
+        });
+        for id in removed {
+            self.emit(DiagramEvent::CommentsChanged(id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, Element, ElementType};
+
+    fn diagram_with_element() -> (Diagram, ElementId) {
+        let mut diagram = Diagram::new("Review", "", DiagramType::SystemContext);
+        let element = Element::new(ElementType::system("Billing", ""), Position::new(100.0, 50.0));
+        let id = element.id;
+        diagram.add_element(element);
+        diagram.take_events();
+        (diagram, id)
+    }
+
+    mod thread_tests {
+        use super::*;
+
+        /// Verifies threads collect replies and can be resolved and reopened
+        #[test]
+        fn thread_lifecycle() {
+            let (mut diagram, element_id) = diagram_with_element();
+            let id = diagram.add_comment_thread(
+                CommentAnchor::Element(element_id),
+                Comment::new("ana", "Should this be async?"),
+            );
+            assert!(diagram.reply_to_comment_thread(id, Comment::new("bo", "Yes, via the queue")));
+            assert!(diagram.set_comment_thread_resolved(id, true));
+
+            let thread = diagram.get_comment_thread(id).unwrap();
+            assert_eq!(thread.comments.len(), 2);
+            assert!(thread.resolved);
+            assert_eq!(diagram.take_events(), vec![DiagramEvent::CommentsChanged(id); 3]);
+
+            assert!(diagram.set_comment_thread_resolved(id, false));
+            assert!(!diagram.get_comment_thread(id).unwrap().resolved);
+        }
+
+        /// Verifies operations on unknown threads are rejected
+        #[test]
+        fn unknown_thread_is_rejected() {
+            let (mut diagram, _) = diagram_with_element();
+            let unknown = Uuid::new_v4();
+            assert!(!diagram.reply_to_comment_thread(unknown, Comment::new("a", "b")));
+            assert!(!diagram.set_comment_thread_resolved(unknown, true));
+            diagram.remove_comment_thread(unknown);
+            assert!(diagram.take_events().is_empty());
+        }
+
+        /// Verifies markers sit at the element's top-right corner or the anchored point
+        #[test]
+        fn marker_position_follows_anchor() {
+            let (diagram, element_id) = diagram_with_element();
+            let on_element = CommentThread::new(CommentAnchor::Element(element_id), Comment::new("a", "b"));
+            assert_eq!(on_element.marker_position(&diagram), Some(Position::new(260.0, 50.0)));
+
+            let on_canvas = CommentThread::new(CommentAnchor::Position(Position::new(5.0, 6.0)), Comment::new("a", "b"));
+            assert_eq!(on_canvas.marker_position(&diagram), Some(Position::new(5.0, 6.0)));
+
+            let orphan = CommentThread::new(CommentAnchor::Element(Uuid::new_v4()), Comment::new("a", "b"));
+            assert_eq!(orphan.marker_position(&diagram), None);
+        }
+
+        /// Verifies removing an element removes the threads attached to it
+        #[test]
+        fn remove_element_removes_its_threads() {
+            let (mut diagram, element_id) = diagram_with_element();
+            diagram.add_comment_thread(CommentAnchor::Element(element_id), Comment::new("a", "on element"));
+            let kept = diagram.add_comment_thread(
+                CommentAnchor::Position(Position::new(0.0, 0.0)),
+                Comment::new("a", "on canvas"),
+            );
+
+            diagram.remove_element(element_id);
+            assert_eq!(diagram.comments.len(), 1);
+            assert_eq!(diagram.comments[0].id, kept);
+        }
+
+        /// Verifies comments are saved with the diagram and omitted when empty
+        #[test]
+        fn comments_roundtrip_through_json() {
+            let (mut diagram, element_id) = diagram_with_element();
+            assert!(!diagram.to_json().unwrap().contains("comments"));
+
+            diagram.add_comment_thread(CommentAnchor::Element(element_id), Comment::new("ana", "Why?"));
+            let restored = Diagram::from_json(&diagram.to_json().unwrap()).unwrap();
+            assert_eq!(restored.comments, diagram.comments);
+        }
+    }
+}
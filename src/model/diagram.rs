@@ -1,6 +1,17 @@
-use super::{Element, ElementId, Relationship, FILE_FORMAT_VERSION};
+use super::events::Subscribers;
+use super::{CommentThread, DiagramEvent, DiagramMetadata, Element, ElementGroup, ElementId, Position, Relationship, TextLabel, View, COMPRESSED_FILE_EXTENSION, FILE_FORMAT_VERSION, YAML_FILE_EXTENSIONS};
 use serde::{Deserialize, Serialize};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::OnceLock;
+use std::sync::mpsc::Receiver;
+use uuid::Uuid;
+
+/// First bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
 
 /// The complete diagram containing all elements and relationships
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +23,63 @@ pub struct Diagram {
     pub diagram_type: DiagramType,
     pub elements: HashMap<ElementId, Element>,
     pub relationships: Vec<Relationship>,
+    /// Review comment threads
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<CommentThread>,
+    /// Elements that move together on the canvas
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<ElementGroup>,
+    /// Free text on the canvas, left out of the C4 exports
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<TextLabel>,
+    /// Named filters showing part of the diagram
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub views: Vec<View>,
+    /// The view shown on the canvas and in exports; the whole diagram if None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_view: Option<Uuid>,
+    /// Author, timestamps and version label
+    #[serde(default, skip_serializing_if = "DiagramMetadata::is_empty")]
+    pub metadata: DiagramMetadata,
+    /// Changes not yet collected with [`Diagram::take_events`]
+    #[serde(skip)]
+    events: Vec<DiagramEvent>,
+    #[serde(skip)]
+    subscribers: Subscribers,
+    /// Relationships by element, built on first lookup and dropped by [`Diagram::emit`]
+    #[serde(skip)]
+    adjacency: OnceLock<Adjacency>,
+}
+
+/// Positions in [`Diagram::relationships`] of each element's relationships
+#[derive(Debug, Clone, Default)]
+struct Adjacency {
+    /// Number of relationships when built; a different count means the index is stale
+    len: usize,
+    outgoing: HashMap<ElementId, Vec<usize>>,
+    incoming: HashMap<ElementId, Vec<usize>>,
+}
+
+impl Adjacency {
+    fn build(relationships: &[Relationship]) -> Self {
+        let mut adjacency = Adjacency {
+            len: relationships.len(),
+            ..Default::default()
+        };
+        for (i, rel) in relationships.iter().enumerate() {
+            adjacency.outgoing.entry(rel.source_id).or_default().push(i);
+            adjacency.incoming.entry(rel.target_id).or_default().push(i);
+        }
+        adjacency
+    }
+
+    fn outgoing(&self, id: ElementId) -> &[usize] {
+        self.outgoing.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    fn incoming(&self, id: ElementId) -> &[usize] {
+        self.incoming.get(&id).map_or(&[], Vec::as_slice)
+    }
 }
 
 fn default_version() -> String {
@@ -33,19 +101,131 @@ impl Diagram {
             diagram_type,
             elements: HashMap::new(),
             relationships: Vec::new(),
+            comments: Vec::new(),
+            groups: Vec::new(),
+            labels: Vec::new(),
+            views: Vec::new(),
+            active_view: None,
+            metadata: DiagramMetadata::default(),
+            events: Vec::new(),
+            subscribers: Subscribers::default(),
+            adjacency: OnceLock::new(),
         }
     }
 
     /// Add an element to the diagram
     pub fn add_element(&mut self, element: Element) {
+        self.emit(DiagramEvent::ElementAdded(element.id));
         self.elements.insert(element.id, element);
     }
 
     /// Remove an element and all its relationships
+    ///
+    /// Removing a system or container also removes everything inside it.
     pub fn remove_element(&mut self, id: ElementId) {
-        self.elements.remove(&id);
-        self.relationships
-            .retain(|r| r.source_id != id && r.target_id != id);
+        if self.elements.remove(&id).is_none() {
+            return;
+        }
+        let children: Vec<ElementId> = self.children_of(id).iter().map(|e| e.id).collect();
+        for child in children {
+            self.remove_element(child);
+        }
+        let removed: Vec<Uuid> = self
+            .relationships
+            .iter()
+            .filter(|r| r.source_id == id || r.target_id == id)
+            .map(|r| r.id)
+            .collect();
+        self.relationships.retain(|r| r.source_id != id && r.target_id != id);
+        for rel in removed {
+            self.emit(DiagramEvent::RelationshipRemoved(rel));
+        }
+        self.emit(DiagramEvent::ElementRemoved(id));
+        self.remove_orphaned_comments();
+        self.remove_orphaned_group_members();
+    }
+
+    /// Draw the element above every other one
+    ///
+    /// Returns false if it is unknown or already alone on top.
+    pub fn bring_to_front(&mut self, id: ElementId) -> bool {
+        let top = self.elements.values().filter(|e| e.id != id).map(|e| e.z_index).max();
+        self.set_z_index(id, top.map_or(0, |z| z + 1), |z, new| z >= new)
+    }
+
+    /// Draw the element below every other one
+    ///
+    /// Returns false if it is unknown or already alone at the bottom.
+    pub fn send_to_back(&mut self, id: ElementId) -> bool {
+        let bottom = self.elements.values().filter(|e| e.id != id).map(|e| e.z_index).min();
+        self.set_z_index(id, bottom.map_or(0, |z| z - 1), |z, new| z <= new)
+    }
+
+    /// Move the element to `z_index` unless `already(current, z_index)` says it is there
+    fn set_z_index(&mut self, id: ElementId, z_index: i32, already: impl Fn(i32, i32) -> bool) -> bool {
+        let Some(element) = self.elements.get_mut(&id) else {
+            return false;
+        };
+        if already(element.z_index, z_index) {
+            return false;
+        }
+        element.z_index = z_index;
+        self.emit(DiagramEvent::ElementUpdated(id));
+        true
+    }
+
+    /// Elements that are part of `id`: a system's containers or a container's components
+    ///
+    /// Sorted by name so callers list them in a stable order.
+    pub fn children_of(&self, id: ElementId) -> Vec<&Element> {
+        let mut children: Vec<&Element> = self.elements.values().filter(|e| e.parent() == Some(id)).collect();
+        children.sort_by(|a, b| a.name().cmp(b.name()).then(a.id.cmp(&b.id)));
+        children
+    }
+
+    /// Elements that are not part of another element in this diagram
+    pub fn top_level(&self) -> impl Iterator<Item = &Element> {
+        self.elements
+            .values()
+            .filter(|e| e.parent().is_none_or(|parent| !self.elements.contains_key(&parent)))
+    }
+
+    /// Move an element, returning false if it does not exist
+    pub fn move_element(&mut self, id: ElementId, position: Position) -> bool {
+        let Some(element) = self.elements.get_mut(&id) else {
+            return false;
+        };
+        let from = element.position;
+        element.position = position;
+        if from != position {
+            self.emit(DiagramEvent::ElementMoved { id, from, to: position });
+        }
+        true
+    }
+
+    /// Edit an element in place and record an [`DiagramEvent::ElementUpdated`]
+    pub fn update_element<R>(&mut self, id: ElementId, edit: impl FnOnce(&mut Element) -> R) -> Option<R> {
+        let result = edit(self.elements.get_mut(&id)?);
+        self.emit(DiagramEvent::ElementUpdated(id));
+        Some(result)
+    }
+
+    /// A copy without external persons and systems or their relationships
+    ///
+    /// Used to export what the canvas shows while externals are hidden.
+    pub fn without_external(&self) -> Diagram {
+        let mut visible = self.clone();
+        let external: Vec<ElementId> = self
+            .elements
+            .values()
+            .filter(|e| e.is_external())
+            .map(|e| e.id)
+            .collect();
+        for id in external {
+            visible.remove_element(id);
+        }
+        visible.events.clear();
+        visible
     }
 
     /// Get an element by ID
@@ -58,43 +238,140 @@ impl Diagram {
         self.elements.get_mut(&id)
     }
 
+    /// `base`, or `base` with the lowest free number appended ("New Person 2")
+    pub fn unique_name(&self, base: &str) -> String {
+        let taken = |name: &str| self.elements.values().any(|e| e.name() == name);
+        if !taken(base) {
+            return base.to_string();
+        }
+        (2..)
+            .map(|n| format!("{} {}", base, n))
+            .find(|name| !taken(name))
+            .expect("unbounded range always yields a free name")
+    }
+
     /// Add a relationship between two elements
-    pub fn add_relationship(&mut self, relationship: Relationship) {
+    ///
+    /// On a Dynamic diagram an unnumbered relationship becomes the last step.
+    pub fn add_relationship(&mut self, mut relationship: Relationship) {
         // Only add if both elements exist
         if self.elements.contains_key(&relationship.source_id)
             && self.elements.contains_key(&relationship.target_id)
         {
+            if self.diagram_type == DiagramType::Dynamic && relationship.order.is_none() {
+                relationship.order = Some(self.steps().last().map_or(1, |(step, _)| step + 1));
+            }
+            self.emit(DiagramEvent::RelationshipAdded(relationship.id));
             self.relationships.push(relationship);
         }
     }
 
+    /// Relationships in step order, each with its step number
+    ///
+    /// Numbered relationships come first, ties in list order; unnumbered ones
+    /// follow and continue the numbering.
+    pub fn steps(&self) -> Vec<(u32, &Relationship)> {
+        let mut ordered: Vec<&Relationship> = self.relationships.iter().collect();
+        ordered.sort_by_key(|rel| (rel.order.is_none(), rel.order));
+        let mut next = ordered.iter().filter_map(|rel| rel.order).max().unwrap_or(0) + 1;
+        ordered
+            .into_iter()
+            .map(|rel| {
+                let step = rel.order.unwrap_or_else(|| {
+                    next += 1;
+                    next - 1
+                });
+                (step, rel)
+            })
+            .collect()
+    }
+
+    /// Step number of each relationship by ID; empty unless this is a Dynamic diagram
+    pub fn step_numbers(&self) -> HashMap<uuid::Uuid, u32> {
+        if self.diagram_type != DiagramType::Dynamic {
+            return HashMap::new();
+        }
+        self.steps().into_iter().map(|(step, rel)| (rel.id, step)).collect()
+    }
+
     /// Remove a relationship by ID
     pub fn remove_relationship(&mut self, id: uuid::Uuid) {
+        let before = self.relationships.len();
         self.relationships.retain(|r| r.id != id);
+        if self.relationships.len() != before {
+            self.emit(DiagramEvent::RelationshipRemoved(id));
+        }
+    }
+
+    /// Reverse a relationship's direction, returning false if it does not exist
+    pub fn reverse_relationship(&mut self, id: uuid::Uuid) -> bool {
+        let Some(rel) = self.relationships.iter_mut().find(|r| r.id == id) else {
+            return false;
+        };
+        rel.reverse();
+        self.emit(DiagramEvent::RelationshipUpdated(id));
+        true
+    }
+
+    /// Record a change made directly through the public fields
+    pub fn emit(&mut self, event: DiagramEvent) {
+        self.adjacency = OnceLock::new();
+        self.subscribers.send(&event);
+        self.events.push(event);
+    }
+
+    /// Receive every event from now on, as it happens
+    ///
+    /// Events still queue for [`Diagram::take_events`] as well. Dropping the
+    /// receiver ends the subscription; copies of the diagram start with none.
+    pub fn subscribe(&mut self) -> Receiver<DiagramEvent> {
+        self.subscribers.add()
+    }
+
+    /// Collect and clear the events queued since the last call
+    pub fn take_events(&mut self) -> Vec<DiagramEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Positions in `relationships` of those matching `keep`, from the
+    /// adjacency index when it is current and by a scan otherwise
+    fn indexed(
+        &self,
+        lookup: impl Fn(&Adjacency) -> Vec<usize>,
+        keep: impl Fn(&Relationship) -> bool,
+    ) -> Vec<&Relationship> {
+        let adjacency = self.adjacency.get_or_init(|| Adjacency::build(&self.relationships));
+        if adjacency.len == self.relationships.len() {
+            let found: Vec<&Relationship> = lookup(adjacency).into_iter().filter_map(|i| self.relationships.get(i)).collect();
+            // the field is public: only trust hits that still match
+            if found.iter().all(|r| keep(r)) {
+                return found;
+            }
+        }
+        self.relationships.iter().filter(|r| keep(r)).collect()
     }
 
     /// Get all relationships from a specific element
     pub fn relationships_from(&self, element_id: ElementId) -> Vec<&Relationship> {
-        self.relationships
-            .iter()
-            .filter(|r| r.source_id == element_id)
-            .collect()
+        self.indexed(|a| a.outgoing(element_id).to_vec(), |r| r.source_id == element_id)
     }
 
     /// Get all relationships to a specific element
     pub fn relationships_to(&self, element_id: ElementId) -> Vec<&Relationship> {
-        self.relationships
-            .iter()
-            .filter(|r| r.target_id == element_id)
-            .collect()
+        self.indexed(|a| a.incoming(element_id).to_vec(), |r| r.target_id == element_id)
     }
 
     /// Get all relationships connected to an element (both from and to)
     pub fn relationships_connected_to(&self, element_id: ElementId) -> Vec<&Relationship> {
-        self.relationships
-            .iter()
-            .filter(|r| r.source_id == element_id || r.target_id == element_id)
-            .collect()
+        self.indexed(
+            |a| {
+                let mut found = [a.outgoing(element_id), a.incoming(element_id)].concat();
+                found.sort_unstable();
+                found.dedup();
+                found
+            },
+            |r| r.source_id == element_id || r.target_id == element_id,
+        )
     }
 
     /// Save the diagram to a JSON string
@@ -102,10 +379,74 @@ impl Diagram {
         serde_json::to_string_pretty(self)
     }
 
-    /// Load a diagram from a JSON string
-    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(json)
+    /// Load a diagram from a JSON string, checked against the file schema
+    pub fn from_json(json: &str) -> crate::Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        super::schema::check(&value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Save the diagram as gzip-compressed JSON
+    pub fn to_compressed(&self) -> crate::Result<Vec<u8>> {
+        let json = serde_json::to_vec(self)?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&json)?;
+        Ok(encoder.finish()?)
+    }
+
+    /// Load a diagram from JSON or gzip-compressed JSON, detected by content
+    ///
+    /// The JSON is checked against the file schema first, so a malformed
+    /// diagram is reported by the field that is wrong.
+    pub fn from_bytes(bytes: &[u8]) -> crate::Result<Self> {
+        let value: serde_json::Value = if bytes.starts_with(&GZIP_MAGIC) {
+            let mut json = Vec::new();
+            GzDecoder::new(bytes).read_to_end(&mut json)?;
+            serde_json::from_slice(&json)?
+        } else {
+            serde_json::from_slice(bytes)?
+        };
+        super::schema::check(&value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Save the diagram to a file
+    ///
+    /// Files ending in `.c4dz` are gzip-compressed, `.yaml` and `.yml` files
+    /// are YAML, `.ron` files are RON (with the `ron` feature), and anything
+    /// else is JSON.
+    pub fn save_to_file(&self, path: &std::path::Path) -> crate::Result<()> {
+        #[cfg(feature = "ron")]
+        if has_extension(path, &[super::RON_FILE_EXTENSION]) {
+            return std::fs::write(path, self.to_ron()?).map_err(|e| crate::C2DrawError::file(path, e));
+        }
+        let bytes = if has_extension(path, &[COMPRESSED_FILE_EXTENSION]) {
+            self.to_compressed()?
+        } else if has_extension(path, &YAML_FILE_EXTENSIONS) {
+            self.to_yaml()?.into_bytes()
+        } else {
+            self.to_json()?.into_bytes()
+        };
+        std::fs::write(path, bytes).map_err(|e| crate::C2DrawError::file(path, e))
     }
+
+    /// Load a diagram from a file, compressed or not, or from YAML or RON by extension
+    pub fn load_from_file(path: &std::path::Path) -> crate::Result<Self> {
+        let bytes = std::fs::read(path).map_err(|e| crate::C2DrawError::file(path, e))?;
+        if has_extension(path, &YAML_FILE_EXTENSIONS) {
+            return Self::from_yaml(&String::from_utf8_lossy(&bytes));
+        }
+        #[cfg(feature = "ron")]
+        if has_extension(path, &[super::RON_FILE_EXTENSION]) {
+            return Self::from_ron(&String::from_utf8_lossy(&bytes));
+        }
+        Self::from_bytes(&bytes)
+    }
+}
+
+fn has_extension(path: &std::path::Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .is_some_and(|ext| extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)))
 }
 
 /// Type of C4 diagram
@@ -117,18 +458,37 @@ pub enum DiagramType {
     /// C2: Container diagram
     #[serde(rename = "Container")]
     Container,
+    /// C3: Component diagram
+    #[serde(rename = "Component")]
+    Component,
+    /// Numbered interactions between elements, e.g. one use case
+    #[serde(rename = "Dynamic")]
+    Dynamic,
 }
 
 impl DiagramType {
+    pub const ALL: [DiagramType; 4] = [
+        DiagramType::SystemContext,
+        DiagramType::Container,
+        DiagramType::Component,
+        DiagramType::Dynamic,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             DiagramType::SystemContext => "System Context",
             DiagramType::Container => "Container",
+            DiagramType::Component => "Component",
+            DiagramType::Dynamic => "Dynamic",
         }
     }
 
     pub fn supports_containers(&self) -> bool {
-        matches!(self, DiagramType::Container)
+        matches!(self, DiagramType::Container | DiagramType::Component | DiagramType::Dynamic)
+    }
+
+    pub fn supports_components(&self) -> bool {
+        matches!(self, DiagramType::Component | DiagramType::Dynamic)
     }
 }
 
@@ -137,6 +497,40 @@ mod tests {
     use super::*;
     use crate::model::{Element, ElementType, Position, Relationship};
 
+    mod z_order_tests {
+        use super::*;
+        use crate::model::draw_order;
+
+        /// Verifies raising and lowering change the draw order and report no-ops
+        #[test]
+        fn bring_to_front_and_send_to_back() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let ids: Vec<ElementId> = (0..3)
+                .map(|_| {
+                    let element = Element::new(ElementType::system("S", ""), Position::new(0.0, 0.0));
+                    let id = element.id;
+                    diagram.add_element(element);
+                    id
+                })
+                .collect();
+            let mut sorted = ids.clone();
+            sorted.sort();
+            let order = |diagram: &Diagram| draw_order(diagram.elements.values()).iter().map(|e| e.id).collect::<Vec<_>>();
+            assert_eq!(order(&diagram), sorted);
+            diagram.take_events();
+
+            assert!(diagram.bring_to_front(sorted[0]));
+            assert_eq!(order(&diagram), vec![sorted[1], sorted[2], sorted[0]]);
+            assert!(!diagram.bring_to_front(sorted[0]));
+            assert_eq!(diagram.take_events(), vec![DiagramEvent::ElementUpdated(sorted[0])]);
+
+            assert!(diagram.send_to_back(sorted[2]));
+            assert_eq!(order(&diagram), vec![sorted[2], sorted[1], sorted[0]]);
+            assert!(!diagram.send_to_back(sorted[2]));
+            assert!(!diagram.bring_to_front(ElementId::new_v4()));
+        }
+    }
+
     mod diagram_creation_tests {
         use super::*;
 
@@ -235,6 +629,67 @@ mod tests {
 
             assert!(diagram.elements.is_empty());
         }
+
+        /// Verifies removing a system removes its containers and their components
+        #[test]
+        fn remove_element_cascades_to_children() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::Component);
+            let system = Element::new(ElementType::system("Shop", ""), Position::new(0.0, 0.0));
+            let system_id = system.id;
+            let mut api = ElementType::container("API", "", crate::model::ContainerType::Microservice, "Rust");
+            let mut web = ElementType::container("Web", "", crate::model::ContainerType::WebApplication, "");
+            for container in [&mut api, &mut web] {
+                if let ElementType::Container(data) = container {
+                    data.system = Some(system_id);
+                }
+            }
+            let api = Element::new(api, Position::new(0.0, 0.0));
+            let web = Element::new(web, Position::new(0.0, 0.0));
+            let api_id = api.id;
+            let mut checkout = ElementType::component("Checkout", "", "");
+            if let ElementType::Component(data) = &mut checkout {
+                data.container = Some(api_id);
+            }
+            let checkout = Element::new(checkout, Position::new(0.0, 0.0));
+            let user = Element::new(ElementType::person("User", ""), Position::new(0.0, 0.0));
+            let (checkout_id, user_id) = (checkout.id, user.id);
+            for element in [system, web, api, checkout, user] {
+                diagram.add_element(element);
+            }
+            diagram.add_relationship(Relationship::new(user_id, checkout_id, "Pays"));
+
+            let children: Vec<&str> = diagram.children_of(system_id).iter().map(|e| e.name()).collect();
+            assert_eq!(children, vec!["API", "Web"]);
+            assert_eq!(diagram.children_of(api_id)[0].id, checkout_id);
+
+            diagram.remove_element(system_id);
+            assert_eq!(diagram.elements.len(), 1);
+            assert!(diagram.elements.contains_key(&user_id));
+            assert!(diagram.relationships.is_empty());
+        }
+
+        /// Verifies without_external drops external elements and their relationships only
+        #[test]
+        fn without_external_keeps_internal_structure() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let user = Element::new(ElementType::person("User", ""), Position::new(0.0, 0.0));
+            let system = Element::new(ElementType::system("Shop", ""), Position::new(0.0, 0.0));
+            let payments = Element::new(ElementType::external_system("Payments", ""), Position::new(0.0, 0.0));
+            let (user_id, system_id, payments_id) = (user.id, system.id, payments.id);
+            diagram.add_element(user);
+            diagram.add_element(system);
+            diagram.add_element(payments);
+            diagram.add_relationship(Relationship::new(user_id, system_id, "Buys from"));
+            diagram.add_relationship(Relationship::new(system_id, payments_id, "Charges via"));
+
+            let visible = diagram.without_external();
+            assert_eq!(visible.elements.len(), 2);
+            assert!(visible.get_element(payments_id).is_none());
+            assert_eq!(visible.relationships.len(), 1);
+            assert_eq!(visible.relationships[0].description, "Buys from");
+            assert_eq!(diagram.elements.len(), 3);
+            assert_eq!(diagram.relationships.len(), 2);
+        }
     }
 
     mod relationship_tests {
@@ -378,6 +833,35 @@ mod tests {
             let connected_to_target = diagram.relationships_connected_to(target_id);
             assert_eq!(connected_to_target.len(), 1);
         }
+
+        /// Verifies lookups stay correct as relationships change, including through the public field
+        #[test]
+        fn adjacency_index_follows_changes() {
+            let (mut diagram, source_id, target_id) = create_test_diagram_with_elements();
+            let ids = |rels: Vec<&Relationship>| rels.iter().map(|r| r.id).collect::<Vec<_>>();
+            let first = Relationship::new(source_id, target_id, "uses");
+            let first_id = first.id;
+            diagram.add_relationship(first);
+            let itself = Relationship::new(source_id, source_id, "calls");
+            let itself_id = itself.id;
+            diagram.add_relationship(itself);
+            assert_eq!(ids(diagram.relationships_connected_to(source_id)), vec![first_id, itself_id]);
+            assert_eq!(ids(diagram.relationships_to(source_id)), vec![itself_id]);
+
+            diagram.remove_relationship(first_id);
+            assert!(diagram.relationships_to(target_id).is_empty());
+
+            // edited without emitting: the count changed, so the stale index is not used
+            let back = Relationship::new(target_id, source_id, "replies");
+            let back_id = back.id;
+            diagram.relationships.push(back);
+            assert_eq!(ids(diagram.relationships_from(target_id)), vec![back_id]);
+            diagram.relationships[0].target_id = target_id;
+            assert_eq!(ids(diagram.relationships_to(source_id)), vec![back_id]);
+
+            diagram.remove_element(target_id);
+            assert_eq!(diagram.relationships_connected_to(source_id).len(), 0);
+        }
     }
 
     mod serialization_tests {
@@ -402,6 +886,101 @@ mod tests {
             assert_eq!(restored.elements.len(), diagram.elements.len());
         }
 
+        /// Verifies from_json reports malformed diagrams as schema errors naming the field
+        #[test]
+        fn from_json_reports_schema_errors() {
+            let json = Diagram::new("Test", "", DiagramType::Container).to_json().unwrap();
+            let broken = json.replace("\"Container\"", "\"Containr\"");
+            let err = Diagram::from_json(&broken).unwrap_err();
+            assert!(matches!(err, crate::C2DrawError::Schema(ref e) if e.path == "diagram_type"), "{}", err);
+            assert!(matches!(Diagram::from_json("{"), Err(crate::C2DrawError::Json(_))));
+        }
+
+        /// Verifies unique_name appends the lowest free number
+        #[test]
+        fn unique_name_skips_taken_names() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            assert_eq!(diagram.unique_name("New Person"), "New Person");
+
+            for name in ["New Person", "New Person 3"] {
+                diagram.add_element(Element::new(ElementType::person(name, ""), Position::new(0.0, 0.0)));
+            }
+            assert_eq!(diagram.unique_name("New Person"), "New Person 2");
+            assert_eq!(diagram.unique_name("Database"), "Database");
+        }
+
+        /// Verifies save_to_file and load_from_file roundtrip through disk
+        #[test]
+        fn file_roundtrip_preserves_data() {
+            let path = std::env::temp_dir().join(format!("c2draw-{}.c4d", uuid::Uuid::new_v4()));
+            let mut diagram = Diagram::new("On Disk", "", DiagramType::SystemContext);
+            diagram.add_element(Element::new(
+                ElementType::person("User", "A user"),
+                Position::new(0.0, 0.0),
+            ));
+
+            diagram.save_to_file(&path).expect("Failed to save");
+            let restored = Diagram::load_from_file(&path).expect("Failed to load");
+            let _ = std::fs::remove_file(&path);
+
+            assert_eq!(restored.name, "On Disk");
+            assert_eq!(restored.elements.len(), 1);
+        }
+
+        /// Verifies .c4dz files are compressed and load transparently
+        #[test]
+        fn compressed_file_roundtrip() {
+            let path = std::env::temp_dir().join(format!("c2draw-{}.c4dz", uuid::Uuid::new_v4()));
+            let mut diagram = Diagram::new("Compressed", "", DiagramType::Container);
+            diagram.add_element(Element::new(
+                ElementType::system("System", "A system"),
+                Position::new(0.0, 0.0),
+            ));
+
+            diagram.save_to_file(&path).expect("Failed to save");
+            let bytes = std::fs::read(&path).unwrap();
+            let restored = Diagram::load_from_file(&path).expect("Failed to load");
+            let _ = std::fs::remove_file(&path);
+
+            assert!(bytes.starts_with(&GZIP_MAGIC));
+            assert_eq!(restored.name, "Compressed");
+            assert_eq!(restored.elements.len(), 1);
+        }
+
+        /// Verifies format detection does not depend on the extension
+        #[test]
+        fn from_bytes_detects_format() {
+            let diagram = Diagram::new("Either", "", DiagramType::SystemContext);
+            let json = diagram.to_json().unwrap();
+            let compressed = diagram.to_compressed().unwrap();
+
+            assert_eq!(Diagram::from_bytes(json.as_bytes()).unwrap().name, "Either");
+            assert_eq!(Diagram::from_bytes(&compressed).unwrap().name, "Either");
+            assert!(compressed.len() < json.len());
+        }
+
+        /// Verifies load_from_file reports missing files and invalid content
+        #[test]
+        fn load_from_file_reports_errors() {
+            let path = std::env::temp_dir().join(format!("c2draw-{}.c4d", uuid::Uuid::new_v4()));
+            let missing = Diagram::load_from_file(&path).unwrap_err();
+            assert!(matches!(missing, crate::C2DrawError::File { .. }));
+
+            std::fs::write(&path, "not json").unwrap();
+            let invalid = Diagram::load_from_file(&path).unwrap_err();
+            let _ = std::fs::remove_file(&path);
+            assert!(matches!(invalid, crate::C2DrawError::Json(_)));
+        }
+
+        /// Verifies files that break the schema are reported by field
+        #[test]
+        fn from_bytes_checks_schema() {
+            let err = Diagram::from_bytes(br#"{"name": "Shop", "description": "", "diagram_type": "Deployment", "elements": {}, "relationships": []}"#)
+                .unwrap_err();
+            assert!(matches!(err, crate::C2DrawError::Schema(_)));
+            assert!(err.to_string().starts_with("invalid diagram data: diagram_type: expected one of"));
+        }
+
         /// Verifies JSON serialization includes version field
         #[test]
         fn json_includes_version() {
@@ -413,6 +992,199 @@ mod tests {
         }
     }
 
+    mod event_tests {
+        use super::*;
+
+        fn person(name: &str) -> Element {
+            Element::new(ElementType::person(name, ""), Position::new(0.0, 0.0))
+        }
+
+        /// Verifies adding elements and relationships queues events
+        #[test]
+        fn add_operations_emit_events() {
+            let mut diagram = Diagram::default();
+            let (a, b) = (person("A"), person("B"));
+            let (a_id, b_id) = (a.id, b.id);
+            diagram.add_element(a);
+            diagram.add_element(b);
+            let rel = Relationship::new(a_id, b_id, "uses");
+            let rel_id = rel.id;
+            diagram.add_relationship(rel);
+
+            assert_eq!(
+                diagram.take_events(),
+                vec![
+                    DiagramEvent::ElementAdded(a_id),
+                    DiagramEvent::ElementAdded(b_id),
+                    DiagramEvent::RelationshipAdded(rel_id),
+                ]
+            );
+            assert!(diagram.take_events().is_empty());
+        }
+
+        /// Verifies removing an element reports its cascaded relationships
+        #[test]
+        fn remove_element_emits_relationship_removals() {
+            let mut diagram = Diagram::default();
+            let (a, b) = (person("A"), person("B"));
+            let (a_id, b_id) = (a.id, b.id);
+            diagram.add_element(a);
+            diagram.add_element(b);
+            let rel = Relationship::new(a_id, b_id, "uses");
+            let rel_id = rel.id;
+            diagram.add_relationship(rel);
+            diagram.take_events();
+
+            diagram.remove_element(a_id);
+            diagram.remove_element(a_id);
+
+            assert_eq!(
+                diagram.take_events(),
+                vec![DiagramEvent::RelationshipRemoved(rel_id), DiagramEvent::ElementRemoved(a_id)]
+            );
+        }
+
+        /// Verifies move_element records the old and new positions
+        #[test]
+        fn move_element_emits_moved() {
+            let mut diagram = Diagram::default();
+            let a = person("A");
+            let id = a.id;
+            diagram.add_element(a);
+            diagram.take_events();
+
+            assert!(diagram.move_element(id, Position::new(5.0, 6.0)));
+            assert!(diagram.move_element(id, Position::new(5.0, 6.0)));
+            assert!(!diagram.move_element(uuid::Uuid::new_v4(), Position::new(0.0, 0.0)));
+
+            assert_eq!(
+                diagram.take_events(),
+                vec![DiagramEvent::ElementMoved {
+                    id,
+                    from: Position::new(0.0, 0.0),
+                    to: Position::new(5.0, 6.0),
+                }]
+            );
+        }
+
+        /// Verifies update_element applies the edit and emits an update
+        #[test]
+        fn update_element_emits_updated() {
+            let mut diagram = Diagram::default();
+            let a = person("A");
+            let id = a.id;
+            diagram.add_element(a);
+            diagram.take_events();
+
+            diagram.update_element(id, |e| e.set_name("Renamed".to_string()));
+
+            assert_eq!(diagram.get_element(id).unwrap().name(), "Renamed");
+            assert_eq!(diagram.take_events(), vec![DiagramEvent::ElementUpdated(id)]);
+            assert!(diagram.update_element(uuid::Uuid::new_v4(), |_| ()).is_none());
+        }
+
+        /// Verifies reverse_relationship flips the direction in place
+        #[test]
+        fn reverse_relationship_emits_updated() {
+            let mut diagram = Diagram::default();
+            let (a, b) = (person("A"), person("B"));
+            let (a_id, b_id) = (a.id, b.id);
+            diagram.add_element(a);
+            diagram.add_element(b);
+            let rel = Relationship::new(a_id, b_id, "uses");
+            let rel_id = rel.id;
+            diagram.add_relationship(rel);
+            diagram.take_events();
+
+            assert!(diagram.reverse_relationship(rel_id));
+            assert!(!diagram.reverse_relationship(uuid::Uuid::new_v4()));
+
+            assert_eq!(diagram.relationships_from(b_id)[0].id, rel_id);
+            assert_eq!(diagram.take_events(), vec![DiagramEvent::RelationshipUpdated(rel_id)]);
+        }
+
+        /// Verifies subscribers receive events as they happen, until they drop the receiver
+        #[test]
+        fn subscribers_receive_events() {
+            let mut diagram = Diagram::default();
+            let events = diagram.subscribe();
+            let a = person("A");
+            let id = a.id;
+            diagram.add_element(a);
+            diagram.remove_element(id);
+            assert_eq!(
+                events.try_iter().collect::<Vec<_>>(),
+                vec![DiagramEvent::ElementAdded(id), DiagramEvent::ElementRemoved(id)]
+            );
+            assert_eq!(diagram.take_events().len(), 2);
+
+            let mut copy = diagram.clone();
+            copy.add_element(person("B"));
+            assert!(events.try_recv().is_err());
+
+            drop(events);
+            diagram.add_element(person("C"));
+            assert!(diagram.subscribers.0.is_empty());
+        }
+
+        /// Verifies pending events are not serialized
+        #[test]
+        fn events_are_not_serialized() {
+            let mut diagram = Diagram::default();
+            diagram.add_element(person("A"));
+            let json = diagram.to_json().unwrap();
+            assert!(!json.contains("events"));
+            assert!(Diagram::from_json(&json).unwrap().take_events().is_empty());
+        }
+    }
+
+    mod steps_tests {
+        use super::*;
+
+        /// Verifies steps follow the order index and unnumbered relationships go last
+        #[test]
+        fn steps_in_order() {
+            let mut diagram = Diagram::new("Login", "", DiagramType::Container);
+            let ids: Vec<ElementId> = (0..3)
+                .map(|i| {
+                    let element = Element::new(ElementType::system(format!("S{}", i), ""), Position::new(0.0, 0.0));
+                    let id = element.id;
+                    diagram.add_element(element);
+                    id
+                })
+                .collect();
+            let mut second = Relationship::new(ids[1], ids[2], "second");
+            second.order = Some(2);
+            let mut first = Relationship::new(ids[0], ids[1], "first");
+            first.order = Some(1);
+            diagram.add_relationship(Relationship::new(ids[2], ids[0], "unnumbered"));
+            diagram.add_relationship(second);
+            diagram.add_relationship(first);
+
+            let steps: Vec<(u32, &str)> = diagram.steps().iter().map(|(n, r)| (*n, r.description.as_str())).collect();
+            assert_eq!(steps, vec![(1, "first"), (2, "second"), (3, "unnumbered")]);
+        }
+
+        /// Verifies relationships added to a Dynamic diagram become the next step
+        #[test]
+        fn dynamic_numbers_new_relationships() {
+            let mut diagram = Diagram::new("Login", "", DiagramType::Dynamic);
+            let a = Element::new(ElementType::person("User", ""), Position::new(0.0, 0.0));
+            let b = Element::new(ElementType::system("App", ""), Position::new(0.0, 0.0));
+            let (a_id, b_id) = (a.id, b.id);
+            diagram.add_element(a);
+            diagram.add_element(b);
+            diagram.add_relationship(Relationship::new(a_id, b_id, "signs in"));
+            diagram.add_relationship(Relationship::new(b_id, a_id, "returns a session"));
+            let orders: Vec<Option<u32>> = diagram.relationships.iter().map(|r| r.order).collect();
+            assert_eq!(orders, vec![Some(1), Some(2)]);
+            assert_eq!(diagram.step_numbers()[&diagram.relationships[1].id], 2);
+
+            diagram.diagram_type = DiagramType::Container;
+            assert!(diagram.step_numbers().is_empty());
+        }
+    }
+
     mod diagram_type_tests {
         use super::*;
 
@@ -421,6 +1193,7 @@ mod tests {
         fn diagram_type_as_str() {
             assert_eq!(DiagramType::SystemContext.as_str(), "System Context");
             assert_eq!(DiagramType::Container.as_str(), "Container");
+            assert_eq!(DiagramType::Component.as_str(), "Component");
         }
 
         /// Verifies supports_containers returns correct values
@@ -428,6 +1201,9 @@ mod tests {
         fn diagram_type_supports_containers() {
             assert!(!DiagramType::SystemContext.supports_containers());
             assert!(DiagramType::Container.supports_containers());
+            assert!(DiagramType::Component.supports_containers());
+            assert!(!DiagramType::Container.supports_components());
+            assert!(DiagramType::Component.supports_components());
         }
     }
 }
@@ -0,0 +1,224 @@
+//! Semantic differences between two versions of a diagram
+//!
+//! Elements and relationships are matched by ID, so a renamed element is a
+//! change rather than a removal and an addition. Layout is kept apart from
+//! content: an element that was only moved or resized, or a relationship that
+//! was only rerouted, is reported as [`Change::Moved`] so reviewers can tell
+//! architecture changes from tidying.
+
+use super::{Diagram, Element, ElementId, Relationship};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// How an element or relationship differs between the two versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Change {
+    Added,
+    Removed,
+    /// Its text, type, style or connections changed
+    Changed,
+    /// Only its position, size or route changed
+    Moved,
+}
+
+impl Change {
+    pub const ALL: [Change; 4] = [Change::Added, Change::Removed, Change::Changed, Change::Moved];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Change::Added => "added",
+            Change::Removed => "removed",
+            Change::Changed => "changed",
+            Change::Moved => "moved",
+        }
+    }
+}
+
+/// Everything that differs between two diagrams, by ID
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiagramDiff {
+    pub elements: BTreeMap<ElementId, Change>,
+    pub relationships: BTreeMap<Uuid, Change>,
+}
+
+impl DiagramDiff {
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty() && self.relationships.is_empty()
+    }
+
+    /// IDs of the elements with this kind of change
+    pub fn elements_with(&self, change: Change) -> Vec<ElementId> {
+        self.elements.iter().filter(|(_, c)| **c == change).map(|(id, _)| *id).collect()
+    }
+
+    /// IDs of the relationships with this kind of change
+    pub fn relationships_with(&self, change: Change) -> Vec<Uuid> {
+        self.relationships.iter().filter(|(_, c)| **c == change).map(|(id, _)| *id).collect()
+    }
+
+    /// One line counting the changes, e.g. `2 added, 1 changed`
+    pub fn summary(&self) -> String {
+        let counts: Vec<String> = Change::ALL
+            .into_iter()
+            .filter_map(|change| {
+                let count = self.elements.values().chain(self.relationships.values()).filter(|c| **c == change).count();
+                (count > 0).then(|| format!("{} {}", count, change.as_str()))
+            })
+            .collect();
+        if counts.is_empty() {
+            "no differences".to_string()
+        } else {
+            counts.join(", ")
+        }
+    }
+}
+
+impl Diagram {
+    /// The changes that turn this diagram into `other`
+    ///
+    /// Added means only in `other`, removed only in `self`.
+    pub fn diff(&self, other: &Diagram) -> DiagramDiff {
+        let mut diff = DiagramDiff::default();
+        for (id, element) in &self.elements {
+            let change = match other.elements.get(id) {
+                None => Some(Change::Removed),
+                Some(new) => compare(element_content(element), element_content(new), || {
+                    element.position == new.position && element.size == new.size
+                }),
+            };
+            diff.elements.extend(change.map(|c| (*id, c)));
+        }
+        for id in other.elements.keys().filter(|id| !self.elements.contains_key(id)) {
+            diff.elements.insert(*id, Change::Added);
+        }
+
+        let new_relationships: BTreeMap<Uuid, &Relationship> = other.relationships.iter().map(|r| (r.id, r)).collect();
+        for rel in &self.relationships {
+            let change = match new_relationships.get(&rel.id) {
+                None => Some(Change::Removed),
+                Some(new) => compare(relationship_content(rel), relationship_content(new), || {
+                    rel.waypoints == new.waypoints
+                        && rel.source_anchor == new.source_anchor
+                        && rel.target_anchor == new.target_anchor
+                }),
+            };
+            diff.relationships.extend(change.map(|c| (rel.id, c)));
+        }
+        for rel in &other.relationships {
+            if !self.relationships.iter().any(|r| r.id == rel.id) {
+                diff.relationships.insert(rel.id, Change::Added);
+            }
+        }
+        diff
+    }
+}
+
+/// Changed if the content differs, else moved unless the layout is the same
+fn compare(old: Value, new: Value, same_layout: impl FnOnce() -> bool) -> Option<Change> {
+    if old != new {
+        Some(Change::Changed)
+    } else if !same_layout() {
+        Some(Change::Moved)
+    } else {
+        None
+    }
+}
+
+/// An element's serialized fields other than its position and size
+fn element_content(element: &Element) -> Value {
+    let mut value = serde_json::to_value(element).unwrap_or_default();
+    if let Some(fields) = value.as_object_mut() {
+        fields.remove("position");
+        fields.remove("size");
+    }
+    value
+}
+
+/// Fields of a relationship that only affect how its line is routed
+const RELATIONSHIP_LAYOUT: [&str; 3] = ["waypoints", "source_anchor", "target_anchor"];
+
+/// A relationship's serialized fields other than its route
+fn relationship_content(rel: &Relationship) -> Value {
+    let mut value = serde_json::to_value(rel).unwrap_or_default();
+    if let Some(fields) = value.as_object_mut() {
+        for field in RELATIONSHIP_LAYOUT {
+            fields.remove(field);
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Anchor, DiagramType, ElementType, Position, Side, Size};
+
+    fn sample() -> (Diagram, ElementId, ElementId, Uuid) {
+        let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+        let user = Element::new(ElementType::person("Customer", ""), Position::new(0.0, 0.0));
+        let shop = Element::new(ElementType::system("Shop", ""), Position::new(300.0, 0.0));
+        let (user_id, shop_id) = (user.id, shop.id);
+        diagram.add_element(user);
+        diagram.add_element(shop);
+        let rel = Relationship::new(user_id, shop_id, "Buys from");
+        let rel_id = rel.id;
+        diagram.add_relationship(rel);
+        (diagram, user_id, shop_id, rel_id)
+    }
+
+    mod diff_tests {
+        use super::*;
+
+        /// Verifies a diagram has no differences from itself
+        #[test]
+        fn identical_diagrams() {
+            let (diagram, _, _, _) = sample();
+            let diff = diagram.diff(&diagram.clone());
+            assert!(diff.is_empty());
+            assert_eq!(diff.summary(), "no differences");
+        }
+
+        /// Verifies additions and removals are reported from the first diagram's point of view
+        #[test]
+        fn added_and_removed() {
+            let (old, user_id, shop_id, rel_id) = sample();
+            let mut new = old.clone();
+            new.remove_element(shop_id);
+            let mail = Element::new(ElementType::external_system("Mail", ""), Position::new(0.0, 300.0));
+            let mail_id = mail.id;
+            new.add_element(mail);
+            let notify = Relationship::new(user_id, mail_id, "Notifies");
+            let notify_id = notify.id;
+            new.add_relationship(notify);
+
+            let diff = old.diff(&new);
+            assert_eq!(diff.elements_with(Change::Removed), vec![shop_id]);
+            assert_eq!(diff.elements_with(Change::Added), vec![mail_id]);
+            assert_eq!(diff.relationships_with(Change::Removed), vec![rel_id]);
+            assert_eq!(diff.relationships_with(Change::Added), vec![notify_id]);
+            assert!(!diff.elements.contains_key(&user_id));
+            assert_eq!(diff.summary(), "2 added, 2 removed");
+        }
+
+        /// Verifies layout-only edits are told apart from content edits
+        #[test]
+        fn changed_and_moved() {
+            let (old, user_id, shop_id, rel_id) = sample();
+            let mut new = old.clone();
+            new.get_element_mut(user_id).unwrap().position = Position::new(50.0, 50.0);
+            new.get_element_mut(shop_id).unwrap().size = Size::new(300.0, 100.0);
+            new.get_element_mut(shop_id).unwrap().set_description("Sells things".to_string());
+            new.relationships[0].waypoints = vec![Position::new(150.0, 100.0)];
+            new.relationships[0].target_anchor = Some(Anchor::new(Side::West));
+
+            let diff = old.diff(&new);
+            assert_eq!(diff.elements.get(&user_id), Some(&Change::Moved));
+            assert_eq!(diff.elements.get(&shop_id), Some(&Change::Changed));
+            assert_eq!(diff.relationships.get(&rel_id), Some(&Change::Moved));
+
+            new.relationships[0].technology = Some("HTTPS".to_string());
+            assert_eq!(old.diff(&new).relationships.get(&rel_id), Some(&Change::Changed));
+        }
+    }
+}
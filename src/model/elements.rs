@@ -1,4 +1,5 @@
-use super::{ElementId, Position, Positioned, Size};
+use super::{CloudIcon, ElementId, Position, Positioned, Size};
+use crate::style::ElementStyle;
 use serde::{Deserialize, Serialize};
 
 /// A visual element on the diagram canvas
@@ -8,6 +9,21 @@ pub struct Element {
     pub element_type: ElementType,
     pub position: Position,
     pub size: Size,
+    /// Link to further documentation, made clickable in SVG exports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Free-form tags, carried into C4-PlantUML as `$tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Colours and border chosen for this element, over the stylesheet
+    #[serde(default, skip_serializing_if = "ElementStyle::is_empty")]
+    pub style: ElementStyle,
+    /// Stacking order where elements overlap; higher is drawn on top
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub z_index: i32,
+    /// Identifier in PlantUML and Mermaid exports, see [`Diagram::assign_aliases`](super::Diagram::assign_aliases)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
 }
 
 impl Element {
@@ -18,6 +34,11 @@ impl Element {
             element_type,
             position,
             size,
+            url: None,
+            tags: Vec::new(),
+            style: ElementStyle::default(),
+            z_index: 0,
+            alias: None,
         }
     }
 
@@ -26,6 +47,8 @@ impl Element {
             ElementType::Person(data) => &data.name,
             ElementType::SoftwareSystem(data) => &data.name,
             ElementType::Container(data) => &data.name,
+            ElementType::Component(data) => &data.name,
+            ElementType::Note(data) => &data.text,
         }
     }
 
@@ -34,6 +57,8 @@ impl Element {
             ElementType::Person(data) => &data.description,
             ElementType::SoftwareSystem(data) => &data.description,
             ElementType::Container(data) => &data.description,
+            ElementType::Component(data) => &data.description,
+            ElementType::Note(_) => "",
         }
     }
 
@@ -41,7 +66,21 @@ impl Element {
         match &self.element_type {
             ElementType::Person(data) => data.is_external,
             ElementType::SoftwareSystem(data) => data.is_external,
-            ElementType::Container(_) => false,
+            ElementType::Container(_) | ElementType::Component(_) | ElementType::Note(_) => false,
+        }
+    }
+
+    /// Whether this is a note rather than a C4 element
+    pub fn is_note(&self) -> bool {
+        matches!(self.element_type, ElementType::Note(_))
+    }
+
+    /// The element this one is part of: a container's system or a component's container
+    pub fn parent(&self) -> Option<ElementId> {
+        match &self.element_type {
+            ElementType::Container(data) => data.system,
+            ElementType::Component(data) => data.container,
+            ElementType::Person(_) | ElementType::SoftwareSystem(_) | ElementType::Note(_) => None,
         }
     }
 
@@ -50,6 +89,8 @@ impl Element {
             ElementType::Person(data) => data.name = name,
             ElementType::SoftwareSystem(data) => data.name = name,
             ElementType::Container(data) => data.name = name,
+            ElementType::Component(data) => data.name = name,
+            ElementType::Note(data) => data.text = name,
         }
     }
 
@@ -58,10 +99,23 @@ impl Element {
             ElementType::Person(data) => data.description = description,
             ElementType::SoftwareSystem(data) => data.description = description,
             ElementType::Container(data) => data.description = description,
+            ElementType::Component(data) => data.description = description,
+            ElementType::Note(_) => {}
         }
     }
 }
 
+fn is_zero(z_index: &i32) -> bool {
+    *z_index == 0
+}
+
+/// Elements back to front: by z-index, then by ID so equal ones always stack the same way
+pub fn draw_order<'a>(elements: impl IntoIterator<Item = &'a Element>) -> Vec<&'a Element> {
+    let mut order: Vec<&Element> = elements.into_iter().collect();
+    order.sort_by_key(|e| (e.z_index, e.id));
+    order
+}
+
 impl Positioned for Element {
     fn position(&self) -> Position {
         self.position
@@ -86,6 +140,9 @@ pub enum ElementType {
     Person(PersonData),
     SoftwareSystem(SystemData),
     Container(ContainerData),
+    Component(ComponentData),
+    /// Free text with no C4 meaning
+    Note(NoteData),
 }
 
 impl ElementType {
@@ -95,6 +152,8 @@ impl ElementType {
             ElementType::Person(_) => "Person",
             ElementType::SoftwareSystem(_) => "Software System",
             ElementType::Container(_) => "Container",
+            ElementType::Component(_) => "Component",
+            ElementType::Note(_) => "Note",
         }
     }
 
@@ -104,6 +163,25 @@ impl ElementType {
             ElementType::Person(_) => Size::new(120.0, 80.0),
             ElementType::SoftwareSystem(_) => Size::new(160.0, 100.0),
             ElementType::Container(_) => Size::new(160.0, 100.0),
+            ElementType::Component(_) => Size::new(160.0, 100.0),
+            ElementType::Note(_) => Size::new(160.0, 80.0),
+        }
+    }
+
+    /// Technology of a container or component
+    pub fn technology(&self) -> Option<&str> {
+        match self {
+            ElementType::Container(data) => Some(&data.technology),
+            ElementType::Component(data) => Some(&data.technology),
+            ElementType::Person(_) | ElementType::SoftwareSystem(_) | ElementType::Note(_) => None,
+        }
+    }
+
+    pub fn technology_mut(&mut self) -> Option<&mut String> {
+        match self {
+            ElementType::Container(data) => Some(&mut data.technology),
+            ElementType::Component(data) => Some(&mut data.technology),
+            ElementType::Person(_) | ElementType::SoftwareSystem(_) | ElementType::Note(_) => None,
         }
     }
 
@@ -131,6 +209,7 @@ impl ElementType {
             name: name.into(),
             description: description.into(),
             is_external: false,
+            kind: SystemKind::Default,
         })
     }
 
@@ -140,6 +219,7 @@ impl ElementType {
             name: name.into(),
             description: description.into(),
             is_external: true,
+            kind: SystemKind::Default,
         })
     }
 
@@ -155,8 +235,25 @@ impl ElementType {
             description: description.into(),
             container_type,
             technology: technology.into(),
+            icon: None,
+            system: None,
+        })
+    }
+
+    /// Create a new component element, not yet assigned to a container
+    pub fn component(name: impl Into<String>, description: impl Into<String>, technology: impl Into<String>) -> Self {
+        ElementType::Component(ComponentData {
+            name: name.into(),
+            description: description.into(),
+            technology: technology.into(),
+            container: None,
         })
     }
+
+    /// Create a new note
+    pub fn note(text: impl Into<String>) -> Self {
+        ElementType::Note(NoteData { text: text.into() })
+    }
 }
 
 /// C1: Person/Actor element
@@ -173,6 +270,48 @@ pub struct SystemData {
     pub name: String,
     pub description: String,
     pub is_external: bool,
+    /// Shown as a datastore or message broker rather than a plain system
+    #[serde(default, skip_serializing_if = "SystemKind::is_default")]
+    pub kind: SystemKind,
+}
+
+impl SystemData {
+    /// The C4-PlantUML macro declaring this system, e.g. `SystemDb_Ext`
+    pub fn macro_name(&self) -> &'static str {
+        match (self.kind, self.is_external) {
+            (SystemKind::Default, false) => "System",
+            (SystemKind::Default, true) => "System_Ext",
+            (SystemKind::Database, false) => "SystemDb",
+            (SystemKind::Database, true) => "SystemDb_Ext",
+            (SystemKind::Queue, false) => "SystemQueue",
+            (SystemKind::Queue, true) => "SystemQueue_Ext",
+        }
+    }
+}
+
+/// What a software system is drawn and exported as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SystemKind {
+    #[default]
+    Default,
+    Database,
+    Queue,
+}
+
+impl SystemKind {
+    pub const ALL: [SystemKind; 3] = [SystemKind::Default, SystemKind::Database, SystemKind::Queue];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SystemKind::Default => "System",
+            SystemKind::Database => "Database",
+            SystemKind::Queue => "Message Queue",
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == SystemKind::Default
+    }
 }
 
 /// C2: Container element
@@ -182,10 +321,33 @@ pub struct ContainerData {
     pub description: String,
     pub container_type: ContainerType,
     pub technology: String,
+    /// Provider icon from one of the bundled packs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<CloudIcon>,
+    /// Software system the container is part of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<ElementId>,
 }
 
-/// Types of containers
+/// C3: Component element
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentData {
+    pub name: String,
+    pub description: String,
+    pub technology: String,
+    /// Container the component is part of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ElementId>,
+}
+
+/// Sticky note annotating the diagram
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteData {
+    pub text: String,
+}
+
+/// Types of containers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ContainerType {
     WebApplication,
     MobileApp,
@@ -196,6 +358,15 @@ pub enum ContainerType {
 }
 
 impl ContainerType {
+    /// The predefined container types (everything except `Other`)
+    pub const STANDARD: [ContainerType; 5] = [
+        ContainerType::WebApplication,
+        ContainerType::MobileApp,
+        ContainerType::Database,
+        ContainerType::Microservice,
+        ContainerType::Queue,
+    ];
+
     pub fn as_str(&self) -> &str {
         match self {
             ContainerType::WebApplication => "Web Application",
@@ -483,6 +654,13 @@ mod tests {
             assert_eq!(ContainerType::Queue.as_str(), "Message Queue");
             assert_eq!(ContainerType::Other("Custom".to_string()).as_str(), "Custom");
         }
+
+        /// Verifies STANDARD lists every predefined type except Other
+        #[test]
+        fn container_type_standard_excludes_other() {
+            assert_eq!(ContainerType::STANDARD.len(), 5);
+            assert!(!ContainerType::STANDARD.iter().any(|t| matches!(t, ContainerType::Other(_))));
+        }
     }
 
     mod positioned_trait_tests {
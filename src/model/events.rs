@@ -0,0 +1,75 @@
+use super::{ElementId, Position};
+use std::sync::mpsc::{self, Receiver, Sender};
+use uuid::Uuid;
+
+/// A change made to a [`Diagram`](super::Diagram)
+///
+/// Mutating methods on `Diagram` queue these events; consumers (the UI, undo
+/// history, autosave, the API server) collect them with
+/// [`Diagram::take_events`](super::Diagram::take_events) instead of diffing
+/// the diagram themselves. Code embedding the model can also be sent each
+/// event as it happens with [`Diagram::subscribe`](super::Diagram::subscribe).
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagramEvent {
+    ElementAdded(ElementId),
+    ElementMoved {
+        id: ElementId,
+        from: Position,
+        to: Position,
+    },
+    /// An element's name, description or type-specific data changed
+    ElementUpdated(ElementId),
+    ElementRemoved(ElementId),
+    RelationshipAdded(Uuid),
+    /// A relationship's description, technology or anchors changed
+    RelationshipUpdated(Uuid),
+    RelationshipRemoved(Uuid),
+    /// A comment thread was added, replied to, resolved or removed
+    CommentsChanged(Uuid),
+    /// An element group was created, renamed or dissolved
+    GroupsChanged(Uuid),
+    /// A text label was added, edited, moved or removed
+    LabelsChanged(Uuid),
+    /// The diagram's name, description, type, metadata or views changed
+    PropertiesChanged,
+    /// The diagram's elements and relationships were replaced wholesale
+    Replaced,
+}
+
+impl DiagramEvent {
+    /// The element this event is about, if any
+    pub fn element_id(&self) -> Option<ElementId> {
+        match self {
+            DiagramEvent::ElementAdded(id)
+            | DiagramEvent::ElementMoved { id, .. }
+            | DiagramEvent::ElementUpdated(id)
+            | DiagramEvent::ElementRemoved(id) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+/// Channels a diagram sends its events to
+///
+/// A copy of a diagram is a different diagram, so cloning gives no subscribers.
+#[derive(Debug, Default)]
+pub(crate) struct Subscribers(pub(super) Vec<Sender<DiagramEvent>>);
+
+impl Clone for Subscribers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Subscribers {
+    pub(crate) fn add(&mut self) -> Receiver<DiagramEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.0.push(sender);
+        receiver
+    }
+
+    /// Send to every subscriber, forgetting those whose receiver was dropped
+    pub(crate) fn send(&mut self, event: &DiagramEvent) {
+        self.0.retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
@@ -0,0 +1,420 @@
+use super::{Diagram, ElementId};
+use std::collections::{HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+/// Number of relationships entering and leaving an element
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Degree {
+    pub fan_in: usize,
+    pub fan_out: usize,
+}
+
+/// Directed view of a diagram's relationships (source → target)
+///
+/// Built once from a [`Diagram`] and queried for cycles, reachability,
+/// orphans and fan-in/fan-out. Results are sorted by element ID so they are
+/// stable between calls.
+#[derive(Debug, Clone, Default)]
+pub struct RelationshipGraph {
+    nodes: Vec<ElementId>,
+    outgoing: HashMap<ElementId, Vec<ElementId>>,
+    incoming: HashMap<ElementId, Vec<ElementId>>,
+}
+
+impl RelationshipGraph {
+    pub fn new(diagram: &Diagram) -> Self {
+        let mut nodes: Vec<ElementId> = diagram.elements.keys().copied().collect();
+        nodes.sort();
+
+        let mut outgoing: HashMap<ElementId, Vec<ElementId>> = HashMap::new();
+        let mut incoming: HashMap<ElementId, Vec<ElementId>> = HashMap::new();
+        for rel in &diagram.relationships {
+            outgoing.entry(rel.source_id).or_default().push(rel.target_id);
+            incoming.entry(rel.target_id).or_default().push(rel.source_id);
+        }
+
+        Self {
+            nodes,
+            outgoing,
+            incoming,
+        }
+    }
+
+    fn successors(&self, id: ElementId) -> &[ElementId] {
+        self.outgoing.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    fn predecessors(&self, id: ElementId) -> &[ElementId] {
+        self.incoming.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Fan-in and fan-out of an element (parallel relationships count separately)
+    pub fn degree(&self, id: ElementId) -> Degree {
+        Degree {
+            fan_in: self.predecessors(id).len(),
+            fan_out: self.successors(id).len(),
+        }
+    }
+
+    /// Fan-in and fan-out of every element
+    pub fn degrees(&self) -> HashMap<ElementId, Degree> {
+        self.nodes.iter().map(|&id| (id, self.degree(id))).collect()
+    }
+
+    /// Elements with no relationships in either direction
+    pub fn orphans(&self) -> Vec<ElementId> {
+        self.nodes
+            .iter()
+            .copied()
+            .filter(|&id| self.degree(id) == Degree::default())
+            .collect()
+    }
+
+    /// Elements reachable by following relationships forward from `id`
+    /// (what `id` depends on), excluding `id` unless it lies on a cycle
+    pub fn reachable_from(&self, id: ElementId) -> Vec<ElementId> {
+        Self::walk(id, |n| self.successors(n))
+    }
+
+    /// Elements that can reach `id` (what is affected if `id` changes),
+    /// excluding `id` unless it lies on a cycle
+    pub fn impact_set(&self, id: ElementId) -> Vec<ElementId> {
+        Self::walk(id, |n| self.predecessors(n))
+    }
+
+    /// Fewest relationships from `id` to each element reachable from it,
+    /// following relationships forward (downstream)
+    pub fn downstream_distances(&self, id: ElementId) -> HashMap<ElementId, usize> {
+        Self::distances(id, |n| self.successors(n))
+    }
+
+    /// Fewest relationships from each element that can reach `id` (upstream)
+    pub fn upstream_distances(&self, id: ElementId) -> HashMap<ElementId, usize> {
+        Self::distances(id, |n| self.predecessors(n))
+    }
+
+    /// Breadth-first distances from `start`, which is left out
+    fn distances<'a>(start: ElementId, next: impl Fn(ElementId) -> &'a [ElementId]) -> HashMap<ElementId, usize> {
+        let mut distances = HashMap::new();
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((id, distance)) = queue.pop_front() {
+            for &neighbour in next(id) {
+                if neighbour != start && !distances.contains_key(&neighbour) {
+                    distances.insert(neighbour, distance + 1);
+                    queue.push_back((neighbour, distance + 1));
+                }
+            }
+        }
+        distances
+    }
+
+    fn walk<'a>(start: ElementId, next: impl Fn(ElementId) -> &'a [ElementId]) -> Vec<ElementId> {
+        let mut seen = HashSet::new();
+        let mut stack = next(start).to_vec();
+        while let Some(id) = stack.pop() {
+            if seen.insert(id) {
+                stack.extend_from_slice(next(id));
+            }
+        }
+        let mut result: Vec<ElementId> = seen.into_iter().collect();
+        result.sort();
+        result
+    }
+
+    /// Every strongly connected component: the groups of elements that can all
+    /// reach each other, each sorted, including single elements
+    pub fn strongly_connected_components(&self) -> Vec<Vec<ElementId>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: 0,
+            indices: HashMap::new(),
+            low_links: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: HashSet::new(),
+            components: Vec::new(),
+        };
+        for &id in &self.nodes {
+            if !tarjan.indices.contains_key(&id) {
+                tarjan.visit(id);
+            }
+        }
+
+        let mut components: Vec<Vec<ElementId>> = tarjan
+            .components
+            .into_iter()
+            .map(|mut c| {
+                c.sort();
+                c
+            })
+            .collect();
+        components.sort();
+        components
+    }
+
+    /// Groups of elements that depend on each other in a cycle
+    ///
+    /// Each group is a strongly connected component with more than one
+    /// element, or a single element with a relationship to itself.
+    pub fn cycles(&self) -> Vec<Vec<ElementId>> {
+        self.strongly_connected_components()
+            .into_iter()
+            .filter(|c| c.len() > 1 || self.successors(c[0]).contains(&c[0]))
+            .collect()
+    }
+
+    /// Whether any relationships form a cycle
+    pub fn has_cycle(&self) -> bool {
+        !self.cycles().is_empty()
+    }
+}
+
+impl Diagram {
+    /// Everything `id` depends on, directly or through other elements, sorted by ID
+    ///
+    /// Builds a [`RelationshipGraph`] per call; build one directly for many queries.
+    pub fn downstream_of(&self, id: ElementId) -> Vec<ElementId> {
+        RelationshipGraph::new(self).reachable_from(id)
+    }
+
+    /// Everything that depends on `id`, directly or through other elements, sorted by ID
+    ///
+    /// These are the elements impacted by a change to `id`.
+    pub fn upstream_of(&self, id: ElementId) -> Vec<ElementId> {
+        RelationshipGraph::new(self).impact_set(id)
+    }
+
+    /// Relationships that are part of a cycle: both ends are in the same cyclic group
+    pub fn cyclic_relationships(&self) -> Vec<Uuid> {
+        let mut group_of = HashMap::new();
+        for (index, cycle) in RelationshipGraph::new(self).cycles().into_iter().enumerate() {
+            group_of.extend(cycle.into_iter().map(|id| (id, index)));
+        }
+        self.relationships
+            .iter()
+            .filter(|r| group_of.get(&r.source_id).is_some_and(|g| group_of.get(&r.target_id) == Some(g)))
+            .map(|r| r.id)
+            .collect()
+    }
+
+    /// Elements that nothing uses and that use nothing, sorted by ID
+    ///
+    /// An element is an orphan when neither it nor anything nested inside it
+    /// has a relationship. Notes never take part in relationships and are
+    /// left out.
+    pub fn orphan_elements(&self) -> Vec<ElementId> {
+        let orphans = RelationshipGraph::new(self).orphans();
+        let mut used = HashSet::new();
+        for element in self.elements.values().filter(|e| !orphans.contains(&e.id)) {
+            let mut parent = element.parent();
+            while let Some(id) = parent.filter(|id| self.elements.contains_key(id)) {
+                if !used.insert(id) {
+                    break;
+                }
+                parent = self.elements[&id].parent();
+            }
+        }
+        orphans
+            .into_iter()
+            .filter(|id| !used.contains(id) && !self.elements[id].is_note())
+            .collect()
+    }
+}
+
+/// State for Tarjan's strongly connected components algorithm
+struct Tarjan<'a> {
+    graph: &'a RelationshipGraph,
+    index: usize,
+    indices: HashMap<ElementId, usize>,
+    low_links: HashMap<ElementId, usize>,
+    stack: Vec<ElementId>,
+    on_stack: HashSet<ElementId>,
+    components: Vec<Vec<ElementId>>,
+}
+
+impl Tarjan<'_> {
+    fn visit(&mut self, id: ElementId) {
+        self.indices.insert(id, self.index);
+        self.low_links.insert(id, self.index);
+        self.index += 1;
+        self.stack.push(id);
+        self.on_stack.insert(id);
+
+        for &next in self.graph.successors(id) {
+            if !self.indices.contains_key(&next) {
+                self.visit(next);
+                let low = self.low_links[&id].min(self.low_links[&next]);
+                self.low_links.insert(id, low);
+            } else if self.on_stack.contains(&next) {
+                let low = self.low_links[&id].min(self.indices[&next]);
+                self.low_links.insert(id, low);
+            }
+        }
+
+        if self.low_links[&id] == self.indices[&id] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(&member);
+                component.push(member);
+                if member == id {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContainerType, DiagramType, Element, ElementType, Position, Relationship};
+
+    /// Diagram with elements a..=e and the given edges between them by index
+    fn diagram_with_edges(edges: &[(usize, usize)]) -> (Diagram, Vec<ElementId>) {
+        let mut diagram = Diagram::new("Graph", "", DiagramType::SystemContext);
+        let ids: Vec<ElementId> = (0..5)
+            .map(|i| {
+                let element = Element::new(
+                    ElementType::system(format!("S{}", i), ""),
+                    Position::new(0.0, 0.0),
+                );
+                let id = element.id;
+                diagram.add_element(element);
+                id
+            })
+            .collect();
+        for &(from, to) in edges {
+            diagram.add_relationship(Relationship::new(ids[from], ids[to], "uses"));
+        }
+        (diagram, ids)
+    }
+
+    fn sorted(mut ids: Vec<ElementId>) -> Vec<ElementId> {
+        ids.sort();
+        ids
+    }
+
+    mod cycle_tests {
+        use super::*;
+
+        /// Verifies an acyclic graph reports no cycles
+        #[test]
+        fn cycles_empty_for_dag() {
+            let (diagram, _) = diagram_with_edges(&[(0, 1), (1, 2), (0, 2)]);
+            assert!(!RelationshipGraph::new(&diagram).has_cycle());
+        }
+
+        /// Verifies a cycle is reported as one group of its members
+        #[test]
+        fn cycles_finds_component() {
+            let (diagram, ids) = diagram_with_edges(&[(0, 1), (1, 2), (2, 0), (2, 3)]);
+            let cycles = RelationshipGraph::new(&diagram).cycles();
+            assert_eq!(cycles, vec![sorted(vec![ids[0], ids[1], ids[2]])]);
+        }
+
+        /// Verifies a self-relationship counts as a cycle
+        #[test]
+        fn cycles_includes_self_loops() {
+            let (diagram, ids) = diagram_with_edges(&[(4, 4)]);
+            assert_eq!(RelationshipGraph::new(&diagram).cycles(), vec![vec![ids[4]]]);
+        }
+
+        /// Verifies every element is in exactly one component
+        #[test]
+        fn components_cover_all_elements() {
+            let (diagram, ids) = diagram_with_edges(&[(0, 1), (1, 0), (2, 3)]);
+            let components = RelationshipGraph::new(&diagram).strongly_connected_components();
+            assert_eq!(components.len(), 4);
+            assert!(components.contains(&sorted(vec![ids[0], ids[1]])));
+            assert_eq!(components.concat().len(), 5);
+        }
+
+        /// Verifies only relationships inside a cycle are cyclic
+        #[test]
+        fn cyclic_relationships_stay_inside_cycles() {
+            let (diagram, _) = diagram_with_edges(&[(0, 1), (1, 0), (1, 2), (3, 3)]);
+            let rels = &diagram.relationships;
+            assert_eq!(diagram.cyclic_relationships(), vec![rels[0].id, rels[1].id, rels[3].id]);
+        }
+    }
+
+    mod reachability_tests {
+        use super::*;
+
+        /// Verifies reachable_from follows relationships transitively
+        #[test]
+        fn reachable_from_is_transitive() {
+            let (diagram, ids) = diagram_with_edges(&[(0, 1), (1, 2), (3, 0)]);
+            let graph = RelationshipGraph::new(&diagram);
+            assert_eq!(graph.reachable_from(ids[0]), sorted(vec![ids[1], ids[2]]));
+            assert!(graph.reachable_from(ids[2]).is_empty());
+        }
+
+        /// Verifies impact_set follows relationships backwards
+        #[test]
+        fn impact_set_follows_incoming() {
+            let (diagram, ids) = diagram_with_edges(&[(0, 1), (1, 2), (3, 0)]);
+            let graph = RelationshipGraph::new(&diagram);
+            assert_eq!(graph.impact_set(ids[2]), sorted(vec![ids[0], ids[1], ids[3]]));
+        }
+
+        /// Verifies the diagram queries go downstream and upstream from an element
+        #[test]
+        fn diagram_dependency_queries() {
+            let (diagram, ids) = diagram_with_edges(&[(0, 1), (1, 2), (3, 0)]);
+            assert_eq!(diagram.downstream_of(ids[3]), sorted(vec![ids[0], ids[1], ids[2]]));
+            assert_eq!(diagram.upstream_of(ids[1]), sorted(vec![ids[0], ids[3]]));
+            assert!(diagram.upstream_of(ids[4]).is_empty());
+        }
+
+        /// Verifies distances count the fewest hops in each direction
+        #[test]
+        fn distances_are_shortest_paths() {
+            let (diagram, ids) = diagram_with_edges(&[(0, 1), (1, 2), (0, 2), (2, 3), (3, 0)]);
+            let graph = RelationshipGraph::new(&diagram);
+            let downstream = graph.downstream_distances(ids[0]);
+            assert_eq!(downstream, HashMap::from([(ids[1], 1), (ids[2], 1), (ids[3], 2)]));
+            let upstream = graph.upstream_distances(ids[2]);
+            assert_eq!(upstream, HashMap::from([(ids[0], 1), (ids[1], 1), (ids[3], 2)]));
+        }
+    }
+
+    mod metrics_tests {
+        use super::*;
+
+        /// Verifies fan-in and fan-out counts
+        #[test]
+        fn degree_counts_relationships() {
+            let (diagram, ids) = diagram_with_edges(&[(0, 1), (0, 2), (1, 2)]);
+            let graph = RelationshipGraph::new(&diagram);
+            assert_eq!(graph.degree(ids[0]), Degree { fan_in: 0, fan_out: 2 });
+            assert_eq!(graph.degree(ids[2]), Degree { fan_in: 2, fan_out: 0 });
+            assert_eq!(graph.degrees().len(), 5);
+        }
+
+        /// Verifies orphans are elements without any relationships
+        #[test]
+        fn orphans_have_no_relationships() {
+            let (diagram, ids) = diagram_with_edges(&[(0, 1), (2, 2)]);
+            let graph = RelationshipGraph::new(&diagram);
+            assert_eq!(graph.orphans(), sorted(vec![ids[3], ids[4]]));
+        }
+
+        /// Verifies orphan elements skip notes and parents of connected elements
+        #[test]
+        fn orphan_elements_skip_notes_and_used_parents() {
+            let (mut diagram, ids) = diagram_with_edges(&[(0, 1)]);
+            let mut api = Element::new(ElementType::container("API", "", ContainerType::Microservice, ""), Position::new(0.0, 0.0));
+            if let ElementType::Container(data) = &mut api.element_type {
+                data.system = Some(ids[2]);
+            }
+            let api_id = api.id;
+            diagram.add_element(api);
+            diagram.add_element(Element::new(ElementType::note("TODO"), Position::new(0.0, 0.0)));
+            assert_eq!(diagram.orphan_elements(), sorted(vec![ids[2], ids[3], ids[4], api_id]));
+
+            diagram.add_relationship(Relationship::new(ids[0], api_id, "calls"));
+            assert_eq!(diagram.orphan_elements(), sorted(vec![ids[3], ids[4]]));
+        }
+    }
+}
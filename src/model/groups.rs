@@ -0,0 +1,171 @@
+//! Element groups
+//!
+//! A group ties elements together on the canvas so that dragging one of them
+//! moves them all. Groups are a drawing aid: unlike systems and containers
+//! they say nothing about the architecture, so the exporters ignore them. An
+//! element is in at most one group, and a group always has two or more
+//! members.
+
+use super::{Diagram, DiagramEvent, ElementId};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Elements that move together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElementGroup {
+    pub id: Uuid,
+    pub name: String,
+    pub members: Vec<ElementId>,
+}
+
+impl Diagram {
+    /// Group the given elements under `name`, taking them out of any other group
+    ///
+    /// Unknown and repeated IDs are ignored. Returns the new group's ID, or
+    /// None if fewer than two elements are left to group.
+    pub fn group_elements(&mut self, name: impl Into<String>, ids: &[ElementId]) -> Option<Uuid> {
+        let mut members: Vec<ElementId> = Vec::new();
+        for id in ids {
+            if self.elements.contains_key(id) && !members.contains(id) {
+                members.push(*id);
+            }
+        }
+        if members.len() < 2 {
+            return None;
+        }
+        for group in &mut self.groups {
+            group.members.retain(|id| !members.contains(id));
+        }
+        self.remove_small_groups();
+
+        let group = ElementGroup {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            members,
+        };
+        let id = group.id;
+        self.groups.push(group);
+        self.emit(DiagramEvent::GroupsChanged(id));
+        Some(id)
+    }
+
+    /// Dissolve a group, leaving its elements where they are
+    ///
+    /// Returns false if there is no such group.
+    pub fn ungroup(&mut self, id: Uuid) -> bool {
+        let before = self.groups.len();
+        self.groups.retain(|g| g.id != id);
+        let removed = self.groups.len() < before;
+        if removed {
+            self.emit(DiagramEvent::GroupsChanged(id));
+        }
+        removed
+    }
+
+    pub fn rename_group(&mut self, id: Uuid, name: impl Into<String>) {
+        if let Some(group) = self.groups.iter_mut().find(|g| g.id == id) {
+            group.name = name.into();
+            self.emit(DiagramEvent::GroupsChanged(id));
+        }
+    }
+
+    /// The group an element belongs to, if any
+    pub fn group_of(&self, element: ElementId) -> Option<&ElementGroup> {
+        self.groups.iter().find(|g| g.members.contains(&element))
+    }
+
+    /// Drop members that are no longer in the diagram, and groups left with fewer than two
+    pub(crate) fn remove_orphaned_group_members(&mut self) {
+        let elements = &self.elements;
+        for group in &mut self.groups {
+            group.members.retain(|id| elements.contains_key(id));
+        }
+        self.remove_small_groups();
+    }
+
+    fn remove_small_groups(&mut self) {
+        let mut removed = Vec::new();
+        self.groups.retain(|g| {
+            let keep = g.members.len() >= 2;
+            if !keep {
+                removed.push(g.id);
+            }
+            keep
+        });
+        for id in removed {
+            self.emit(DiagramEvent::GroupsChanged(id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, Element, ElementType, Position};
+
+    /// Diagram with `count` systems, returning their IDs
+    fn systems(count: usize) -> (Diagram, Vec<ElementId>) {
+        let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+        let ids = (0..count)
+            .map(|i| {
+                let element = Element::new(ElementType::system(format!("S{}", i), ""), Position::new(0.0, 0.0));
+                let id = element.id;
+                diagram.add_element(element);
+                id
+            })
+            .collect();
+        diagram.take_events();
+        (diagram, ids)
+    }
+
+    mod group_tests {
+        use super::*;
+
+        /// Verifies grouping needs two known elements and takes them from their old group
+        #[test]
+        fn group_elements_moves_members() {
+            let (mut diagram, ids) = systems(4);
+            assert_eq!(diagram.group_elements("One", &[ids[0], ids[0], ElementId::new_v4()]), None);
+
+            let first = diagram.group_elements("Payments", &[ids[0], ids[1], ids[2]]).unwrap();
+            assert_eq!(diagram.take_events(), vec![DiagramEvent::GroupsChanged(first)]);
+            let second = diagram.group_elements("Edge", &[ids[2], ids[3]]).unwrap();
+
+            assert_eq!(diagram.group_of(ids[0]).unwrap().members, vec![ids[0], ids[1]]);
+            assert_eq!(diagram.group_of(ids[2]).map(|g| g.id), Some(second));
+
+            diagram.group_elements("Core", &[ids[1], ids[3]]).unwrap();
+            assert_eq!(diagram.groups.len(), 1);
+            assert!(diagram.group_of(ids[0]).is_none());
+        }
+
+        /// Verifies ungrouping and removing members dissolve groups
+        #[test]
+        fn ungroup_and_remove() {
+            let (mut diagram, ids) = systems(3);
+            let group = diagram.group_elements("All", &ids).unwrap();
+            diagram.rename_group(group, "Everything");
+            assert_eq!(diagram.groups[0].name, "Everything");
+
+            diagram.remove_element(ids[0]);
+            assert_eq!(diagram.groups[0].members, vec![ids[1], ids[2]]);
+            diagram.remove_element(ids[1]);
+            assert!(diagram.groups.is_empty());
+
+            let group = diagram.group_elements("Pair", &[ids[2], ids[1]]);
+            assert!(group.is_none());
+            assert!(!diagram.ungroup(Uuid::new_v4()));
+        }
+
+        /// Verifies groups survive a save and load
+        #[test]
+        fn groups_roundtrip() {
+            let (mut diagram, ids) = systems(2);
+            let group = diagram.group_elements("Pair", &ids).unwrap();
+            let json = serde_json::to_string(&diagram).unwrap();
+            let restored: Diagram = serde_json::from_str(&json).unwrap();
+            assert_eq!(restored.groups, diagram.groups);
+            assert_eq!(restored.group_of(ids[1]).map(|g| g.id), Some(group));
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Cloud icon packs for containers
+//!
+//! Each pack maps to one of the sprite libraries bundled with the PlantUML
+//! standard library, so an icon chosen on the canvas exports as a `$sprite`
+//! argument together with the `!include` lines that define it.
+
+use serde::{Deserialize, Serialize};
+
+/// A bundled set of provider icons
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum IconPack {
+    Aws,
+    Azure,
+    Gcp,
+    Kubernetes,
+}
+
+impl IconPack {
+    pub const ALL: [IconPack; 4] = [IconPack::Aws, IconPack::Azure, IconPack::Gcp, IconPack::Kubernetes];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IconPack::Aws => "AWS",
+            IconPack::Azure => "Azure",
+            IconPack::Gcp => "Google Cloud",
+            IconPack::Kubernetes => "Kubernetes",
+        }
+    }
+
+    /// Short text shown on the canvas badge
+    pub fn badge(self) -> &'static str {
+        match self {
+            IconPack::Aws => "AWS",
+            IconPack::Azure => "AZ",
+            IconPack::Gcp => "GCP",
+            IconPack::Kubernetes => "K8s",
+        }
+    }
+
+    /// Brand colour used for the canvas badge
+    pub fn color(self) -> [u8; 3] {
+        match self {
+            IconPack::Aws => [255, 153, 0],
+            IconPack::Azure => [0, 120, 212],
+            IconPack::Gcp => [66, 133, 244],
+            IconPack::Kubernetes => [50, 108, 229],
+        }
+    }
+
+    /// Standard library file every sprite of this pack depends on
+    pub fn common_include(self) -> &'static str {
+        match self {
+            IconPack::Aws => "awslib/AWSCommon",
+            IconPack::Azure => "azure/AzureCommon",
+            IconPack::Gcp => "gcp/GCPCommon",
+            IconPack::Kubernetes => "kubernetes/k8s-sprites-unlabeled-25pct",
+        }
+    }
+
+    /// The icons offered for this pack
+    pub fn icons(self) -> impl Iterator<Item = &'static IconDef> {
+        ICONS.iter().filter(move |icon| icon.pack == self)
+    }
+}
+
+/// One icon of a pack
+#[derive(Debug)]
+pub struct IconDef {
+    pub pack: IconPack,
+    /// PlantUML sprite name
+    pub sprite: &'static str,
+    pub label: &'static str,
+    /// Standard library file defining the sprite, if not the pack's common include
+    pub include: Option<&'static str>,
+}
+
+const fn icon(pack: IconPack, sprite: &'static str, label: &'static str, include: Option<&'static str>) -> IconDef {
+    IconDef {
+        pack,
+        sprite,
+        label,
+        include,
+    }
+}
+
+/// Every bundled icon, grouped by pack
+pub const ICONS: &[IconDef] = &[
+    icon(IconPack::Aws, "Lambda", "Lambda", Some("awslib/Compute/Lambda")),
+    icon(IconPack::Aws, "EC2", "EC2", Some("awslib/Compute/EC2")),
+    icon(IconPack::Aws, "ElasticContainerService", "ECS", Some("awslib/Containers/ElasticContainerService")),
+    icon(IconPack::Aws, "ElasticKubernetesService", "EKS", Some("awslib/Containers/ElasticKubernetesService")),
+    icon(IconPack::Aws, "APIGateway", "API Gateway", Some("awslib/ApplicationIntegration/APIGateway")),
+    icon(IconPack::Aws, "SimpleQueueService", "SQS", Some("awslib/ApplicationIntegration/SimpleQueueService")),
+    icon(IconPack::Aws, "SimpleNotificationService", "SNS", Some("awslib/ApplicationIntegration/SimpleNotificationService")),
+    icon(IconPack::Aws, "RDS", "RDS", Some("awslib/Database/RDS")),
+    icon(IconPack::Aws, "DynamoDB", "DynamoDB", Some("awslib/Database/DynamoDB")),
+    icon(IconPack::Aws, "SimpleStorageService", "S3", Some("awslib/Storage/SimpleStorageService")),
+    icon(IconPack::Aws, "CloudFront", "CloudFront", Some("awslib/NetworkingContentDelivery/CloudFront")),
+    icon(IconPack::Azure, "AzureFunction", "Functions", Some("azure/Compute/AzureFunction")),
+    icon(IconPack::Azure, "AzureAppService", "App Service", Some("azure/Web/AzureAppService")),
+    icon(IconPack::Azure, "AzureKubernetesService", "AKS", Some("azure/Containers/AzureKubernetesService")),
+    icon(IconPack::Azure, "AzureServiceBus", "Service Bus", Some("azure/Integration/AzureServiceBus")),
+    icon(IconPack::Azure, "AzureSqlDatabase", "SQL Database", Some("azure/Databases/AzureSqlDatabase")),
+    icon(IconPack::Azure, "AzureCosmosDb", "Cosmos DB", Some("azure/Databases/AzureCosmosDb")),
+    icon(IconPack::Azure, "AzureBlobStorage", "Blob Storage", Some("azure/Storage/AzureBlobStorage")),
+    icon(IconPack::Gcp, "Cloud_Functions", "Cloud Functions", Some("gcp/Compute/Cloud_Functions")),
+    icon(IconPack::Gcp, "Cloud_Run", "Cloud Run", Some("gcp/Compute/Cloud_Run")),
+    icon(IconPack::Gcp, "Kubernetes_Engine", "GKE", Some("gcp/Compute/Kubernetes_Engine")),
+    icon(IconPack::Gcp, "Cloud_PubSub", "Pub/Sub", Some("gcp/Data_Analytics/Cloud_PubSub")),
+    icon(IconPack::Gcp, "Cloud_SQL", "Cloud SQL", Some("gcp/Databases/Cloud_SQL")),
+    icon(IconPack::Gcp, "Cloud_Firestore", "Firestore", Some("gcp/Databases/Cloud_Firestore")),
+    icon(IconPack::Gcp, "Cloud_Storage", "Cloud Storage", Some("gcp/Storage/Cloud_Storage")),
+    icon(IconPack::Kubernetes, "pod", "Pod", None),
+    icon(IconPack::Kubernetes, "deploy", "Deployment", None),
+    icon(IconPack::Kubernetes, "sts", "StatefulSet", None),
+    icon(IconPack::Kubernetes, "svc", "Service", None),
+    icon(IconPack::Kubernetes, "ing", "Ingress", None),
+    icon(IconPack::Kubernetes, "job", "Job", None),
+    icon(IconPack::Kubernetes, "pv", "Persistent Volume", None),
+];
+
+/// The icon chosen for a container
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CloudIcon {
+    pub pack: IconPack,
+    /// PlantUML sprite name
+    pub sprite: String,
+}
+
+impl CloudIcon {
+    pub fn new(pack: IconPack, sprite: impl Into<String>) -> Self {
+        Self {
+            pack,
+            sprite: sprite.into(),
+        }
+    }
+
+    /// The bundled definition of this icon, if it is still in the catalogue
+    pub fn def(&self) -> Option<&'static IconDef> {
+        self.pack.icons().find(|icon| icon.sprite == self.sprite)
+    }
+
+    /// Human-readable name, falling back to the sprite name
+    pub fn label(&self) -> &str {
+        self.def().map_or(self.sprite.as_str(), |icon| icon.label)
+    }
+
+    /// Standard library files to `!include` for this icon, common file first
+    pub fn includes(&self) -> Vec<&'static str> {
+        let mut includes = vec![self.pack.common_include()];
+        includes.extend(self.def().and_then(|icon| icon.include));
+        includes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod icon_catalogue_tests {
+        use super::*;
+
+        /// Verifies every pack offers icons and sprite names are unique within a pack
+        #[test]
+        fn packs_have_unique_sprites() {
+            for pack in IconPack::ALL {
+                let sprites: Vec<&str> = pack.icons().map(|icon| icon.sprite).collect();
+                assert!(!sprites.is_empty(), "{}", pack.as_str());
+                let unique: std::collections::HashSet<&&str> = sprites.iter().collect();
+                assert_eq!(unique.len(), sprites.len(), "{}", pack.as_str());
+            }
+        }
+
+        /// Verifies an icon lists its pack's common include before its own
+        #[test]
+        fn includes_start_with_common_file() {
+            let icon = CloudIcon::new(IconPack::Aws, "Lambda");
+            assert_eq!(icon.includes(), vec!["awslib/AWSCommon", "awslib/Compute/Lambda"]);
+            assert_eq!(icon.label(), "Lambda");
+
+            let pod = CloudIcon::new(IconPack::Kubernetes, "pod");
+            assert_eq!(pod.includes(), vec!["kubernetes/k8s-sprites-unlabeled-25pct"]);
+        }
+
+        /// Verifies an unknown sprite still exports with the common include
+        #[test]
+        fn unknown_sprite_falls_back_to_name() {
+            let icon = CloudIcon::new(IconPack::Azure, "AzureSomethingNew");
+            assert!(icon.def().is_none());
+            assert_eq!(icon.label(), "AzureSomethingNew");
+            assert_eq!(icon.includes(), vec!["azure/AzureCommon"]);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Free text labels
+//!
+//! Labels put titles and section headers on the canvas without wedging them
+//! into an element's description. Like groups they are a drawing aid rather
+//! than part of the C4 model, so the PlantUML and Mermaid exporters leave
+//! them out; the SVG image shows them as they are on the canvas.
+
+use super::{Diagram, DiagramEvent, Position};
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use uuid::Uuid;
+
+/// Text placed on the canvas, with its top-left corner at `position`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextLabel {
+    pub id: Uuid,
+    pub text: String,
+    pub position: Position,
+    /// Size in points at 100% zoom
+    #[serde(default = "default_font_size")]
+    pub font_size: f32,
+}
+
+fn default_font_size() -> f32 {
+    TextLabel::DEFAULT_FONT_SIZE
+}
+
+impl TextLabel {
+    pub const DEFAULT_FONT_SIZE: f32 = 20.0;
+    /// Sizes offered in the editor
+    pub const FONT_SIZES: RangeInclusive<f32> = 8.0..=72.0;
+
+    pub fn new(text: impl Into<String>, position: Position) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            text: text.into(),
+            position,
+            font_size: Self::DEFAULT_FONT_SIZE,
+        }
+    }
+}
+
+impl Diagram {
+    /// Add a label and return its ID
+    pub fn add_label(&mut self, label: TextLabel) -> Uuid {
+        let id = label.id;
+        self.labels.push(label);
+        self.emit(DiagramEvent::LabelsChanged(id));
+        id
+    }
+
+    pub fn get_label(&self, id: Uuid) -> Option<&TextLabel> {
+        self.labels.iter().find(|l| l.id == id)
+    }
+
+    /// Replace the label with the same ID, emitting an event if it changed
+    pub fn update_label(&mut self, label: TextLabel) {
+        if let Some(existing) = self.labels.iter_mut().find(|l| l.id == label.id)
+            && *existing != label
+        {
+            let id = label.id;
+            *existing = label;
+            self.emit(DiagramEvent::LabelsChanged(id));
+        }
+    }
+
+    pub fn move_label(&mut self, id: Uuid, position: Position) {
+        if let Some(label) = self.labels.iter_mut().find(|l| l.id == id)
+            && label.position != position
+        {
+            label.position = position;
+            self.emit(DiagramEvent::LabelsChanged(id));
+        }
+    }
+
+    /// Remove a label, returning false if there is no such label
+    pub fn remove_label(&mut self, id: Uuid) -> bool {
+        let before = self.labels.len();
+        self.labels.retain(|l| l.id != id);
+        let removed = self.labels.len() < before;
+        if removed {
+            self.emit(DiagramEvent::LabelsChanged(id));
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{DiagramExporter, MermaidExporter, PlantUmlExporter};
+    use crate::model::DiagramType;
+
+    mod label_tests {
+        use super::*;
+
+        /// Verifies adding, editing, moving and removing labels emit events only on change
+        #[test]
+        fn label_changes_emit_events() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::Container);
+            let id = diagram.add_label(TextLabel::new("Payments", Position::new(10.0, 20.0)));
+            let mut label = diagram.get_label(id).unwrap().clone();
+            label.font_size = 32.0;
+            diagram.update_label(label.clone());
+            diagram.update_label(label);
+            diagram.move_label(id, Position::new(10.0, 20.0));
+            diagram.move_label(id, Position::new(0.0, 0.0));
+            assert_eq!(diagram.take_events(), vec![DiagramEvent::LabelsChanged(id); 3]);
+
+            assert!(diagram.remove_label(id));
+            assert!(!diagram.remove_label(id));
+            assert!(diagram.labels.is_empty());
+        }
+
+        /// Verifies labels are saved but left out of the C4 exports
+        #[test]
+        fn labels_are_saved_but_not_exported() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::Container);
+            diagram.add_label(TextLabel::new("Payments area", Position::new(0.0, 0.0)));
+
+            let restored = Diagram::from_json(&diagram.to_json().unwrap()).unwrap();
+            assert_eq!(restored.labels, diagram.labels);
+            assert!(!PlantUmlExporter::new().export(&diagram).contains("Payments area"));
+            assert!(!MermaidExporter::new().export(&diagram).contains("Payments area"));
+        }
+    }
+}
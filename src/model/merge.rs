@@ -0,0 +1,203 @@
+//! Merging one diagram into another
+//!
+//! Elements keep their IDs and positions unless an ID is already taken in the
+//! target diagram, in which case they get a fresh one; relationships, parent
+//! links and comment threads follow the new IDs. With
+//! [`MergeStrategy::MatchByName`] an element that the target already has,
+//! under the same type and name, is not copied: whatever pointed at it points
+//! at the existing element instead.
+
+use super::{CommentAnchor, Diagram, Element, ElementId, ElementType, Relationship};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How elements of the merged diagram are matched to existing ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeStrategy {
+    /// Copy every element, even if one with the same name exists
+    #[default]
+    KeepBoth,
+    /// Reuse an existing element of the same type and name (ignoring case
+    /// and surrounding space); notes are always copied
+    MatchByName,
+}
+
+impl MergeStrategy {
+    pub const ALL: [MergeStrategy; 2] = [MergeStrategy::KeepBoth, MergeStrategy::MatchByName];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MergeStrategy::KeepBoth => "Keep Both",
+            MergeStrategy::MatchByName => "Match Elements by Name",
+        }
+    }
+}
+
+impl Diagram {
+    /// Copy the elements, relationships and comment threads of `other` into this diagram
+    ///
+    /// A relationship that already exists between the same elements with the
+    /// same description is not copied again. Returns the IDs of the elements
+    /// added, in name order.
+    pub fn merge(&mut self, other: Diagram, strategy: MergeStrategy) -> Vec<ElementId> {
+        let mut ids: HashMap<ElementId, ElementId> = HashMap::new();
+        let mut incoming: Vec<Element> = other.elements.into_values().collect();
+        incoming.sort_by(|a, b| a.name().cmp(b.name()).then(a.id.cmp(&b.id)));
+
+        let mut added = Vec::new();
+        for element in &incoming {
+            let matched = match strategy {
+                MergeStrategy::KeepBoth => None,
+                MergeStrategy::MatchByName => self.find_same(element),
+            };
+            let id = matched.unwrap_or_else(|| {
+                let id = if self.elements.contains_key(&element.id) { ElementId::new_v4() } else { element.id };
+                added.push(id);
+                id
+            });
+            ids.insert(element.id, id);
+        }
+
+        for mut element in incoming {
+            let id = ids[&element.id];
+            if !added.contains(&id) {
+                continue;
+            }
+            element.id = id;
+            match &mut element.element_type {
+                ElementType::Container(data) => data.system = data.system.map(|p| ids.get(&p).copied().unwrap_or(p)),
+                ElementType::Component(data) => {
+                    data.container = data.container.map(|p| ids.get(&p).copied().unwrap_or(p))
+                }
+                ElementType::Person(_) | ElementType::SoftwareSystem(_) | ElementType::Note(_) => {}
+            }
+            self.add_element(element);
+        }
+
+        for rel in other.relationships {
+            let (Some(&source_id), Some(&target_id)) = (ids.get(&rel.source_id), ids.get(&rel.target_id)) else {
+                continue;
+            };
+            let duplicate = self.relationships.iter().any(|r| {
+                r.source_id == source_id && r.target_id == target_id && r.description == rel.description
+            });
+            if duplicate {
+                continue;
+            }
+            let id = if self.relationships.iter().any(|r| r.id == rel.id) { Uuid::new_v4() } else { rel.id };
+            self.add_relationship(Relationship {
+                id,
+                source_id,
+                target_id,
+                ..rel
+            });
+        }
+
+        for thread in other.comments {
+            let anchor = match thread.anchor {
+                CommentAnchor::Element(id) => match ids.get(&id) {
+                    Some(&id) => CommentAnchor::Element(id),
+                    None => continue,
+                },
+                position => position,
+            };
+            let mut comments = thread.comments.into_iter();
+            let Some(first) = comments.next() else {
+                continue;
+            };
+            let id = self.add_comment_thread(anchor, first);
+            for reply in comments {
+                self.reply_to_comment_thread(id, reply);
+            }
+            if thread.resolved {
+                self.set_comment_thread_resolved(id, true);
+            }
+        }
+
+        added
+    }
+
+    /// An element of the same type and name as `element`, other than a note
+    fn find_same(&self, element: &Element) -> Option<ElementId> {
+        if element.is_note() {
+            return None;
+        }
+        let name = element.name().trim().to_lowercase();
+        self.elements
+            .values()
+            .filter(|e| e.element_type.type_name() == element.element_type.type_name())
+            .filter(|e| e.name().trim().to_lowercase() == name)
+            .map(|e| e.id)
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Comment, ContainerType, DiagramType, Position};
+
+    fn add(diagram: &mut Diagram, element_type: ElementType) -> ElementId {
+        let element = Element::new(element_type, Position::new(0.0, 0.0));
+        let id = element.id;
+        diagram.add_element(element);
+        id
+    }
+
+    mod merge_tests {
+        use super::*;
+
+        /// Verifies keeping both copies every element and remaps colliding IDs
+        #[test]
+        fn keep_both_remaps_colliding_ids() {
+            let mut target = Diagram::new("Shop", "", DiagramType::Container);
+            let user = add(&mut target, ElementType::person("Customer", ""));
+            let mut other = target.clone();
+            let shop = add(&mut other, ElementType::system("Shop", ""));
+            other.add_relationship(Relationship::new(user, shop, "Buys from"));
+            other.add_comment_thread(CommentAnchor::Element(user), Comment::new("Sam", "Who?"));
+            target.take_events();
+
+            let added = target.merge(other, MergeStrategy::KeepBoth);
+
+            assert_eq!(added.len(), 2);
+            assert_eq!(added[1], shop);
+            assert_ne!(added[0], user);
+            assert_eq!(target.elements.len(), 3);
+            assert_eq!(target.elements[&added[0]].name(), "Customer");
+            assert_eq!(target.relationships.len(), 1);
+            assert_eq!((target.relationships[0].source_id, target.relationships[0].target_id), (added[0], shop));
+            assert_eq!(target.comments[0].anchor, CommentAnchor::Element(added[0]));
+            assert!(!target.take_events().is_empty());
+        }
+
+        /// Verifies matching by name reuses existing elements and skips repeated relationships
+        #[test]
+        fn match_by_name_reuses_elements() {
+            let mut target = Diagram::new("Shop", "", DiagramType::Container);
+            let user = add(&mut target, ElementType::person("Customer", ""));
+            let shop = add(&mut target, ElementType::system("Shop", ""));
+            target.add_relationship(Relationship::new(user, shop, "Buys from"));
+
+            let mut other = Diagram::new("Shop", "", DiagramType::Container);
+            let other_user = add(&mut other, ElementType::person(" customer ", ""));
+            let other_shop = add(&mut other, ElementType::system("Shop", ""));
+            let mut api = ElementType::container("API", "", ContainerType::Microservice, "");
+            if let ElementType::Container(data) = &mut api {
+                data.system = Some(other_shop);
+            }
+            let api = add(&mut other, api);
+            add(&mut other, ElementType::container("Customer", "", ContainerType::WebApplication, ""));
+            other.add_relationship(Relationship::new(other_user, other_shop, "Buys from"));
+            other.add_relationship(Relationship::new(other_user, api, "Calls"));
+
+            let added = target.merge(other, MergeStrategy::MatchByName);
+
+            assert_eq!(added.len(), 2);
+            assert_eq!(target.elements.len(), 4);
+            assert_eq!(target.elements[&api].parent(), Some(shop));
+            assert_eq!(target.relationships.len(), 2);
+            assert_eq!(target.relationships[1].source_id, user);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use super::{Diagram, DiagramEvent};
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// Authorship and versioning details of a diagram
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiagramMetadata {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub author: String,
+    /// When the diagram was first saved (ISO 8601, UTC)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// When the diagram was last saved (ISO 8601, UTC)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    /// Free-form version shown to readers, e.g. "v2.1" or "Draft"
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub version_label: String,
+    /// Revision notes, oldest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changelog: Vec<Revision>,
+}
+
+/// A note on what changed in a revision of the diagram
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Revision {
+    /// When the note was added (ISO 8601, UTC)
+    pub date: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub author: String,
+    pub note: String,
+}
+
+impl Revision {
+    /// "date (author): note" on one line, with the date without its time
+    pub fn summary(&self) -> String {
+        let date = self.date.get(..10).unwrap_or(&self.date);
+        let note = self.note.trim().replace('\n', " ");
+        match self.author.trim() {
+            "" => format!("{}: {}", date, note),
+            author => format!("{} ({}): {}", date, author, note),
+        }
+    }
+}
+
+impl DiagramMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// "Key: value" lines for the fields that are set, for export headers
+    pub fn header_lines(&self) -> Vec<String> {
+        let fields = [
+            ("Author", Some(self.author.as_str())),
+            ("Version", Some(self.version_label.as_str())),
+            ("Created", self.created.as_deref()),
+            ("Modified", self.modified.as_deref()),
+        ];
+        let revisions = self.changelog.iter().map(|r| format!("Revision {}", r.summary()));
+        fields
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let value = value?.trim();
+                (!value.is_empty()).then(|| format!("{}: {}", key, value.replace('\n', " ")))
+            })
+            .chain(revisions)
+            .collect()
+    }
+}
+
+impl Diagram {
+    /// Replace the metadata, emitting an event if it changed
+    pub fn set_metadata(&mut self, metadata: DiagramMetadata) {
+        if self.metadata != metadata {
+            self.metadata = metadata;
+            self.emit(DiagramEvent::PropertiesChanged);
+        }
+    }
+
+    /// Add a revision note stamped with `now`
+    ///
+    /// Blank notes are ignored. Returns whether a note was added.
+    pub fn add_revision(&mut self, author: &str, note: &str, now: SystemTime) -> bool {
+        let note = note.trim();
+        if note.is_empty() {
+            return false;
+        }
+        self.metadata.changelog.push(Revision {
+            date: crate::backup::format_iso8601(now),
+            author: author.trim().to_string(),
+            note: note.to_string(),
+        });
+        self.emit(DiagramEvent::PropertiesChanged);
+        true
+    }
+
+    /// Stamp the diagram as saved at `now`
+    ///
+    /// Sets the modified time, the created time on first save, and the author
+    /// if none is set yet. No event is emitted: saving is not an edit.
+    pub fn mark_saved(&mut self, author: &str, now: SystemTime) {
+        let timestamp = crate::backup::format_iso8601(now);
+        if self.metadata.author.trim().is_empty() {
+            self.metadata.author = author.trim().to_string();
+        }
+        self.metadata.created.get_or_insert_with(|| timestamp.clone());
+        self.metadata.modified = Some(timestamp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::DiagramType;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    mod metadata_tests {
+        use super::*;
+
+        /// Verifies saving stamps created once, modified every time, and the author if unset
+        #[test]
+        fn mark_saved_updates_timestamps() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            diagram.mark_saved("ana", UNIX_EPOCH + Duration::from_secs(60));
+            diagram.mark_saved("bo", UNIX_EPOCH + Duration::from_secs(120));
+
+            assert_eq!(diagram.metadata.author, "ana");
+            assert_eq!(diagram.metadata.created.as_deref(), Some("1970-01-01T00:01:00Z"));
+            assert_eq!(diagram.metadata.modified.as_deref(), Some("1970-01-01T00:02:00Z"));
+            assert!(diagram.take_events().is_empty());
+        }
+
+        /// Verifies editing metadata emits an event only when something changed
+        #[test]
+        fn set_metadata_emits_on_change() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let metadata = DiagramMetadata {
+                version_label: "v2".to_string(),
+                ..DiagramMetadata::default()
+            };
+            diagram.set_metadata(metadata.clone());
+            diagram.set_metadata(metadata);
+            assert_eq!(diagram.take_events(), vec![DiagramEvent::PropertiesChanged]);
+        }
+
+        /// Verifies header lines skip empty fields
+        #[test]
+        fn header_lines_skip_empty_fields() {
+            let metadata = DiagramMetadata {
+                author: "Ana".to_string(),
+                modified: Some("2024-02-29T13:45:30Z".to_string()),
+                ..DiagramMetadata::default()
+            };
+            assert_eq!(
+                metadata.header_lines(),
+                vec!["Author: Ana".to_string(), "Modified: 2024-02-29T13:45:30Z".to_string()]
+            );
+            assert!(DiagramMetadata::default().header_lines().is_empty());
+        }
+
+        /// Verifies revision notes are stamped, skip blanks and end up in the header lines
+        #[test]
+        fn add_revision_appends_to_changelog() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            assert!(!diagram.add_revision("Ana", "  ", UNIX_EPOCH));
+            assert!(diagram.add_revision("Ana", "Split the API\ninto two", UNIX_EPOCH + Duration::from_secs(86_400)));
+            assert!(diagram.add_revision("", "Renamed queue", UNIX_EPOCH + Duration::from_secs(2 * 86_400)));
+            assert_eq!(diagram.take_events(), vec![DiagramEvent::PropertiesChanged; 2]);
+
+            assert_eq!(diagram.metadata.changelog[0].date, "1970-01-02T00:00:00Z");
+            assert_eq!(
+                diagram.metadata.header_lines(),
+                vec![
+                    "Revision 1970-01-02 (Ana): Split the API into two".to_string(),
+                    "Revision 1970-01-03: Renamed queue".to_string(),
+                ]
+            );
+            let restored = Diagram::from_json(&diagram.to_json().unwrap()).unwrap();
+            assert_eq!(restored.metadata.changelog, diagram.metadata.changelog);
+        }
+
+        /// Verifies metadata is saved with the diagram and omitted when empty
+        #[test]
+        fn metadata_roundtrip_through_json() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            assert!(!diagram.to_json().unwrap().contains("metadata"));
+
+            diagram.mark_saved("ana", UNIX_EPOCH);
+            let restored = Diagram::from_json(&diagram.to_json().unwrap()).unwrap();
+            assert_eq!(restored.metadata, diagram.metadata);
+        }
+    }
+}
@@ -1,10 +1,49 @@
+pub mod alias;
+pub mod comments;
 pub mod diagram;
+pub mod diff;
 pub mod elements;
+pub mod events;
+pub mod graph;
+pub mod groups;
+pub mod icons;
+pub mod labels;
+pub mod merge;
+pub mod metadata;
 pub mod relationship;
-
+#[cfg(feature = "ron")]
+pub mod ron;
+pub mod scaffold;
+pub mod search;
+pub mod schema;
+pub mod snippet;
+pub mod stats;
+pub mod validation;
+pub mod views;
+pub mod yaml;
+
+pub use comments::{Comment, CommentAnchor, CommentThread};
 pub use diagram::{Diagram, DiagramType};
-pub use elements::{ContainerType, Element, ElementType};
-pub use relationship::Relationship;
+pub use diff::{Change, DiagramDiff};
+pub use elements::{draw_order, ComponentData, ContainerType, Element, ElementType, NoteData, SystemKind};
+pub use events::DiagramEvent;
+pub use graph::{Degree, RelationshipGraph};
+pub use groups::ElementGroup;
+pub use icons::{CloudIcon, IconPack};
+pub use labels::TextLabel;
+pub use merge::MergeStrategy;
+pub use metadata::{DiagramMetadata, Revision};
+pub use schema::{SchemaError, FILE_SCHEMA};
+pub use relationship::{
+    bezier_midpoint, curve_control_points, loop_curve, parallel_offsets, polyline_midpoint, snap_angle, Anchor, Direction, LineStyle, Relationship, Side,
+};
+#[cfg(feature = "ron")]
+pub use self::ron::RON_FILE_EXTENSION;
+pub use snippet::{Snippet, SNIPPET_FILE_EXTENSION};
+pub use stats::DiagramStats;
+pub use validation::{Severity, Subject, ValidationIssue};
+pub use views::View;
+pub use yaml::YAML_FILE_EXTENSIONS;
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -12,6 +51,12 @@ use uuid::Uuid;
 /// Version of the diagram file format
 pub const FILE_FORMAT_VERSION: &str = "1.0";
 
+/// File extension of plain JSON diagram files
+pub const FILE_EXTENSION: &str = "c4d";
+
+/// File extension of gzip-compressed diagram files
+pub const COMPRESSED_FILE_EXTENSION: &str = "c4dz";
+
 /// Unique identifier for diagram elements
 pub type ElementId = Uuid;
 
@@ -27,10 +72,12 @@ impl Position {
         Self { x, y }
     }
 
+    #[cfg(feature = "gui")]
     pub fn to_pos2(&self) -> egui::Pos2 {
         egui::Pos2::new(self.x, self.y)
     }
 
+    #[cfg(feature = "gui")]
     pub fn from_pos2(pos: egui::Pos2) -> Self {
         Self::new(pos.x, pos.y)
     }
@@ -44,6 +91,7 @@ impl std::ops::Add for Position {
     }
 }
 
+#[cfg(feature = "gui")]
 impl std::ops::Add<egui::Vec2> for Position {
     type Output = Self;
 
@@ -52,6 +100,7 @@ impl std::ops::Add<egui::Vec2> for Position {
     }
 }
 
+#[cfg(feature = "gui")]
 impl std::ops::Sub for Position {
     type Output = egui::Vec2;
 
@@ -68,6 +117,13 @@ impl std::ops::Mul<f32> for Position {
     }
 }
 
+/// Get default position for new elements, filling a three-column grid
+pub fn default_element_position(index: usize) -> Position {
+    let col = index % 3;
+    let row = index / 3;
+    Position::new(50.0 + col as f32 * 200.0, 50.0 + row as f32 * 150.0)
+}
+
 /// Size of an element on the canvas (width, height)
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Size {
@@ -80,10 +136,12 @@ impl Size {
         Self { width, height }
     }
 
+    #[cfg(feature = "gui")]
     pub fn to_vec2(&self) -> egui::Vec2 {
         egui::Vec2::new(self.width, self.height)
     }
 
+    #[cfg(feature = "gui")]
     pub fn from_vec2(vec: egui::Vec2) -> Self {
         Self::new(vec.x, vec.y)
     }
@@ -152,6 +210,7 @@ mod tests {
 
         /// Verifies Position can be converted to egui::Pos2 and back
         #[test]
+        #[cfg(feature = "gui")]
         fn position_to_pos2_roundtrip() {
             let pos = Position::new(15.5, 25.5);
             let pos2 = pos.to_pos2();
@@ -175,6 +234,7 @@ mod tests {
 
         /// Verifies Position addition with egui::Vec2 works correctly
         #[test]
+        #[cfg(feature = "gui")]
         fn position_add_vec2() {
             let pos = Position::new(10.0, 20.0);
             let vec = egui::Vec2::new(5.0, 8.0);
@@ -185,6 +245,7 @@ mod tests {
 
         /// Verifies Position subtraction returns correct egui::Vec2
         #[test]
+        #[cfg(feature = "gui")]
         fn position_subtraction() {
             let pos1 = Position::new(10.0, 20.0);
             let pos2 = Position::new(3.0, 5.0);
@@ -217,6 +278,7 @@ mod tests {
 
         /// Verifies Size can be converted to egui::Vec2 and back
         #[test]
+        #[cfg(feature = "gui")]
         fn size_to_vec2_roundtrip() {
             let size = Size::new(150.5, 250.5);
             let vec2 = size.to_vec2();
@@ -1,5 +1,6 @@
-use super::ElementId;
-use serde::{Deserialize, Serialize};
+use super::{ElementId, Position, Size};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// A relationship/connection between two elements
@@ -10,6 +11,33 @@ pub struct Relationship {
     pub target_id: ElementId,
     pub description: String,
     pub technology: Option<String>,
+    /// Long-form note (protocol details, SLAs, auth flows)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Where the relationship leaves the source; nearest edge point if None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_anchor: Option<Anchor>,
+    /// Where the relationship meets the target; nearest edge point if None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_anchor: Option<Anchor>,
+    /// Free-form tags, carried into C4-PlantUML as `$tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// How the line is drawn; the canvas default if None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_style: Option<LineStyle>,
+    /// Which way the arrow points; older files say `"bidirectional": true`
+    #[serde(default, alias = "bidirectional", skip_serializing_if = "Direction::is_forward")]
+    pub direction: Direction,
+    /// Step number on a Dynamic diagram
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<u32>,
+    /// Bend points the line is routed through, from source to target
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waypoints: Vec<Position>,
+    /// How far the label is moved from the middle of the line; at the middle if None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label_offset: Option<Position>,
 }
 
 impl Relationship {
@@ -24,6 +52,15 @@ impl Relationship {
             target_id,
             description: description.into(),
             technology: None,
+            note: None,
+            source_anchor: None,
+            target_anchor: None,
+            tags: Vec::new(),
+            line_style: None,
+            direction: Direction::Forward,
+            order: None,
+            waypoints: Vec::new(),
+            label_offset: None,
         }
     }
 
@@ -39,6 +76,339 @@ impl Relationship {
             target_id,
             description: description.into(),
             technology: Some(technology.into()),
+            note: None,
+            source_anchor: None,
+            target_anchor: None,
+            tags: Vec::new(),
+            line_style: None,
+            direction: Direction::Forward,
+            order: None,
+            waypoints: Vec::new(),
+            label_offset: None,
+        }
+    }
+
+    /// Whether the relationship goes from an element back to itself
+    pub fn is_self_loop(&self) -> bool {
+        self.source_id == self.target_id
+    }
+
+    /// Where the label goes for a line whose middle is at `middle`
+    pub fn label_position(&self, middle: Position) -> Position {
+        match self.label_offset {
+            Some(offset) => Position::new(middle.x + offset.x, middle.y + offset.y),
+            None => middle,
+        }
+    }
+
+    /// Swap source and target (and their anchors and route), keeping the ID
+    pub fn reverse(&mut self) {
+        std::mem::swap(&mut self.source_id, &mut self.target_id);
+        std::mem::swap(&mut self.source_anchor, &mut self.target_anchor);
+        self.waypoints.reverse();
+    }
+}
+
+/// Which end of a relationship the arrowhead is drawn at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum Direction {
+    /// Towards the target
+    #[default]
+    Forward,
+    /// Towards the source, for a dependency drawn from the element that is used
+    Back,
+    /// At both ends, for a dependency going both ways
+    BiDirectional,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 3] = [Direction::Forward, Direction::Back, Direction::BiDirectional];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Forward => "Forward",
+            Direction::Back => "Back",
+            Direction::BiDirectional => "Both ways",
+        }
+    }
+
+    pub fn is_forward(&self) -> bool {
+        *self == Direction::Forward
+    }
+
+    /// Whether an arrowhead is drawn at the source end
+    pub fn arrow_at_source(&self) -> bool {
+        matches!(self, Direction::Back | Direction::BiDirectional)
+    }
+
+    /// Whether an arrowhead is drawn at the target end
+    pub fn arrow_at_target(&self) -> bool {
+        matches!(self, Direction::Forward | Direction::BiDirectional)
+    }
+
+    /// C4 macro declaring a relationship in this direction
+    pub fn macro_name(&self) -> &'static str {
+        match self {
+            Direction::Forward => "Rel",
+            Direction::Back => "Rel_Back",
+            Direction::BiDirectional => "BiRel",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Direction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        enum Named {
+            Forward,
+            Back,
+            BiDirectional,
+        }
+        /// A direction, or the `bidirectional` flag it replaced
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Flag(bool),
+            Named(Named),
+        }
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::Flag(true) | Stored::Named(Named::BiDirectional) => Direction::BiDirectional,
+            Stored::Flag(false) | Stored::Named(Named::Forward) => Direction::Forward,
+            Stored::Named(Named::Back) => Direction::Back,
+        })
+    }
+}
+
+/// How a relationship line is drawn between its ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LineStyle {
+    #[default]
+    Straight,
+    /// Cubic bezier leaving and entering each element square to its side
+    Curved,
+}
+
+impl LineStyle {
+    pub const ALL: [LineStyle; 2] = [LineStyle::Straight, LineStyle::Curved];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineStyle::Straight => "Straight",
+            LineStyle::Curved => "Curved",
+        }
+    }
+}
+
+/// Bezier control points for a curved line from `from` (on side `from_side`
+/// of its element) to `to` (on `to_side`)
+///
+/// Each control point sits out from its end along the side's outward normal,
+/// so lines converging on one element fan out instead of overlapping.
+pub fn curve_control_points(from: Position, from_side: Side, to: Position, to_side: Side) -> [Position; 2] {
+    let distance = ((to.x - from.x).powi(2) + (to.y - from.y).powi(2)).sqrt();
+    let reach = (distance * 0.4).clamp(20.0, 150.0);
+    let out = |point: Position, side: Side| {
+        let (nx, ny) = side.normal();
+        Position::new(point.x + nx * reach, point.y + ny * reach)
+    };
+    [out(from, from_side), out(to, to_side)]
+}
+
+/// Distance between the labels of relationships drawn side by side
+pub const PARALLEL_SPACING: f32 = 28.0;
+
+/// Sideways offsets that keep relationships between the same two elements apart
+///
+/// Relationships joining the same pair, in either direction, are spread
+/// evenly about the straight line between the elements, in the order they
+/// appear in `relationships`. Offsets are to the left of the line from the
+/// element with the lower ID to the other one, so they don't depend on which
+/// way each relationship points. Relationships with bends, loops and
+/// relationships that are alone between their elements are left out.
+pub fn parallel_offsets(relationships: &[Relationship]) -> HashMap<Uuid, f32> {
+    let mut pairs: HashMap<(ElementId, ElementId), Vec<Uuid>> = HashMap::new();
+    for rel in relationships.iter().filter(|r| r.waypoints.is_empty() && !r.is_self_loop()) {
+        let pair = (rel.source_id.min(rel.target_id), rel.source_id.max(rel.target_id));
+        pairs.entry(pair).or_default().push(rel.id);
+    }
+    pairs
+        .into_values()
+        .filter(|ids| ids.len() > 1)
+        .flat_map(|ids| {
+            let middle = (ids.len() - 1) as f32 * 0.5;
+            ids.into_iter().enumerate().map(move |(i, id)| (id, (i as f32 - middle) * PARALLEL_SPACING))
+        })
+        .collect()
+}
+
+/// How far a self-relationship's loop reaches out from its element
+const LOOP_REACH: f32 = 50.0;
+
+/// Bezier points (start, two controls, end) for a relationship from an element to itself
+///
+/// Unless its ends are pinned, the loop leaves through the top side and
+/// comes back through the right side, around the top-right corner.
+pub fn loop_curve(position: Position, size: Size, source: Option<Anchor>, target: Option<Anchor>) -> [Position; 4] {
+    let source = source.unwrap_or(Anchor::with_offset(Side::North, 0.8));
+    let target = target.unwrap_or(Anchor::with_offset(Side::East, 0.2));
+    let out = |anchor: Anchor| {
+        let point = anchor.point(position, size);
+        let (nx, ny) = anchor.side.normal();
+        [point, Position::new(point.x + nx * LOOP_REACH, point.y + ny * LOOP_REACH)]
+    };
+    let [start, c1] = out(source);
+    let [end, c2] = out(target);
+    [start, c1, c2, end]
+}
+
+/// Point halfway along a cubic bezier curve
+pub fn bezier_midpoint([p0, c1, c2, p3]: [Position; 4]) -> Position {
+    Position::new((p0.x + 3.0 * (c1.x + c2.x) + p3.x) / 8.0, (p0.y + 3.0 * (c1.y + c2.y) + p3.y) / 8.0)
+}
+
+/// Point halfway along a polyline, where its label goes
+pub fn polyline_midpoint(points: &[Position]) -> Position {
+    let length = |a: &Position, b: &Position| ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt();
+    let total: f32 = points.windows(2).map(|w| length(&w[0], &w[1])).sum();
+    let mut remaining = total * 0.5;
+    for w in points.windows(2) {
+        let segment = length(&w[0], &w[1]);
+        if remaining <= segment && segment > 0.0 {
+            let t = remaining / segment;
+            return Position::new(w[0].x + (w[1].x - w[0].x) * t, w[0].y + (w[1].y - w[0].y) * t);
+        }
+        remaining -= segment;
+    }
+    points.first().copied().unwrap_or(Position::new(0.0, 0.0))
+}
+
+/// `to` rotated about `from` onto the nearest multiple of 45 degrees
+///
+/// The distance between the points is kept. Used to keep hand-routed
+/// relationship segments horizontal, vertical or diagonal.
+pub fn snap_angle(from: Position, to: Position) -> Position {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length < f32::EPSILON {
+        return to;
+    }
+    let step = std::f32::consts::FRAC_PI_4;
+    let angle = (dy.atan2(dx) / step).round() * step;
+    Position::new(from.x + length * angle.cos(), from.y + length * angle.sin())
+}
+
+/// Side of an element's bounding box
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Side {
+    pub const ALL: [Side; 4] = [Side::North, Side::South, Side::East, Side::West];
+
+    /// Unit vector pointing out of the element through this side
+    pub fn normal(&self) -> (f32, f32) {
+        match self {
+            Side::North => (0.0, -1.0),
+            Side::South => (0.0, 1.0),
+            Side::East => (1.0, 0.0),
+            Side::West => (-1.0, 0.0),
+        }
+    }
+
+    /// The side nearest to `point`, relative to the box's proportions
+    pub fn nearest(position: Position, size: Size, point: Position) -> Side {
+        let dx = (point.x - (position.x + size.width * 0.5)) / size.width.max(1.0);
+        let dy = (point.y - (position.y + size.height * 0.5)) / size.height.max(1.0);
+        if dx.abs() >= dy.abs() {
+            if dx >= 0.0 { Side::East } else { Side::West }
+        } else if dy >= 0.0 {
+            Side::South
+        } else {
+            Side::North
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Side::North => "North",
+            Side::South => "South",
+            Side::East => "East",
+            Side::West => "West",
+        }
+    }
+}
+
+/// A fixed point on an element's border that a relationship end is pinned to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub side: Side,
+    /// Fraction along the side, left-to-right or top-to-bottom (0.5 = middle)
+    pub offset: f32,
+}
+
+impl Anchor {
+    /// Anchor at the middle of a side
+    pub fn new(side: Side) -> Self {
+        Self { side, offset: 0.5 }
+    }
+
+    pub fn with_offset(side: Side, offset: f32) -> Self {
+        Self {
+            side,
+            offset: offset.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Anchor on the nearest side at the point closest to `point`
+    pub fn nearest(position: Position, size: Size, point: Position) -> Self {
+        let side = Side::nearest(position, size, point);
+        let offset = match side {
+            Side::North | Side::South => (point.x - position.x) / size.width.max(1.0),
+            Side::East | Side::West => (point.y - position.y) / size.height.max(1.0),
+        };
+        Self::with_offset(side, offset)
+    }
+
+    /// Anchor where the line from `from` through `through` meets the box's border
+    ///
+    /// The first crossing is used, or the exit point if `from` lies inside the
+    /// box. Returns None if the line misses the box.
+    pub fn on_line(from: Position, through: Position, position: Position, size: Size) -> Option<Self> {
+        let (dx, dy) = (through.x - from.x, through.y - from.y);
+        let (mut t_min, mut t_max) = (f32::NEG_INFINITY, f32::INFINITY);
+        for (start, delta, low, high) in [
+            (from.x, dx, position.x, position.x + size.width),
+            (from.y, dy, position.y, position.y + size.height),
+        ] {
+            if delta.abs() < f32::EPSILON {
+                if start < low || start > high {
+                    return None;
+                }
+                continue;
+            }
+            let (t1, t2) = ((low - start) / delta, (high - start) / delta);
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+        }
+        if t_max < t_min.max(0.0) {
+            return None;
+        }
+        let t = if t_min >= 0.0 { t_min } else { t_max };
+        Some(Self::nearest(position, size, Position::new(from.x + dx * t, from.y + dy * t)))
+    }
+
+    /// The anchored point for an element at `position` with `size`
+    pub fn point(&self, position: Position, size: Size) -> Position {
+        let offset = self.offset.clamp(0.0, 1.0);
+        match self.side {
+            Side::North => Position::new(position.x + size.width * offset, position.y),
+            Side::South => Position::new(position.x + size.width * offset, position.y + size.height),
+            Side::West => Position::new(position.x, position.y + size.height * offset),
+            Side::East => Position::new(position.x + size.width, position.y + size.height * offset),
         }
     }
 }
@@ -169,5 +539,338 @@ mod tests {
             let restored: Relationship = serde_json::from_str(&json).expect("Failed to deserialize");
             assert_eq!(restored.technology, None);
         }
+
+        /// Verifies anchors are omitted when unset and roundtrip when pinned
+        #[test]
+        fn relationship_anchor_serialization() {
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "uses");
+            let json = serde_json::to_string(&rel).expect("Failed to serialize");
+            assert!(!json.contains("anchor"));
+
+            rel.target_anchor = Some(Anchor::with_offset(Side::West, 0.25));
+            let json = serde_json::to_string(&rel).expect("Failed to serialize");
+            let restored: Relationship = serde_json::from_str(&json).expect("Failed to deserialize");
+            assert_eq!(restored.source_anchor, None);
+            assert_eq!(restored.target_anchor, Some(Anchor::with_offset(Side::West, 0.25)));
+        }
+
+        /// Verifies the direction defaults to forward and is only saved otherwise
+        #[test]
+        fn relationship_direction_serialization() {
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "syncs");
+            assert_eq!(rel.direction, Direction::Forward);
+            assert!(!serde_json::to_string(&rel).unwrap().contains("direction"));
+
+            rel.direction = Direction::Back;
+            let json = serde_json::to_string(&rel).unwrap();
+            assert!(json.contains("\"direction\":\"Back\""));
+            let restored: Relationship = serde_json::from_str(&json).unwrap();
+            assert_eq!(restored.direction, Direction::Back);
+        }
+
+        /// Verifies files saved with the old bidirectional flag still load
+        #[test]
+        fn relationship_bidirectional_flag_loads() {
+            let rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "syncs");
+            let mut json: serde_json::Value = serde_json::to_value(&rel).unwrap();
+            json["bidirectional"] = serde_json::Value::Bool(true);
+            let restored: Relationship = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(restored.direction, Direction::BiDirectional);
+
+            json["bidirectional"] = serde_json::Value::Bool(false);
+            let restored: Relationship = serde_json::from_value(json).unwrap();
+            assert_eq!(restored.direction, Direction::Forward);
+        }
+    }
+
+    mod note_tests {
+        use super::*;
+
+        /// Verifies notes are omitted when unset and roundtrip when present
+        #[test]
+        fn relationship_note_serialization() {
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "uses");
+            let json = serde_json::to_string(&rel).expect("Failed to serialize");
+            assert!(!json.contains("note"));
+
+            rel.note = Some("OAuth2 client credentials\n99.9% SLA".to_string());
+            let json = serde_json::to_string(&rel).expect("Failed to serialize");
+            let restored: Relationship = serde_json::from_str(&json).expect("Failed to deserialize");
+            assert_eq!(restored.note, rel.note);
+        }
+    }
+
+    mod reverse_tests {
+        use super::*;
+
+        /// Verifies reverse swaps endpoints and anchors but keeps the ID
+        #[test]
+        fn reverse_swaps_endpoints() {
+            let (source_id, target_id) = (ElementId::new_v4(), ElementId::new_v4());
+            let mut rel = Relationship::new(source_id, target_id, "uses");
+            rel.source_anchor = Some(Anchor::new(Side::East));
+            rel.waypoints = vec![Position::new(1.0, 0.0), Position::new(2.0, 0.0)];
+            let id = rel.id;
+
+            rel.reverse();
+
+            assert_eq!(rel.id, id);
+            assert_eq!(rel.source_id, target_id);
+            assert_eq!(rel.target_id, source_id);
+            assert_eq!(rel.source_anchor, None);
+            assert_eq!(rel.target_anchor, Some(Anchor::new(Side::East)));
+            assert_eq!(rel.waypoints, vec![Position::new(2.0, 0.0), Position::new(1.0, 0.0)]);
+        }
+    }
+
+    mod waypoint_tests {
+        use super::*;
+
+        /// Verifies waypoints are omitted when unset and roundtrip in order
+        #[test]
+        fn waypoint_serialization() {
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "uses");
+            assert!(!serde_json::to_string(&rel).unwrap().contains("waypoints"));
+
+            rel.waypoints = vec![Position::new(10.0, 20.0), Position::new(30.0, 20.0)];
+            let json = serde_json::to_string(&rel).unwrap();
+            let restored: Relationship = serde_json::from_str(&json).unwrap();
+            assert_eq!(restored.waypoints, rel.waypoints);
+        }
+
+        /// Verifies the midpoint is measured along the whole route
+        #[test]
+        fn polyline_midpoint_follows_segments() {
+            let route = [Position::new(0.0, 0.0), Position::new(100.0, 0.0), Position::new(100.0, 300.0)];
+            assert_eq!(polyline_midpoint(&route), Position::new(100.0, 100.0));
+            assert_eq!(polyline_midpoint(&route[..2]), Position::new(50.0, 0.0));
+            assert_eq!(polyline_midpoint(&[Position::new(5.0, 5.0)]), Position::new(5.0, 5.0));
+        }
+    }
+
+    mod label_offset_tests {
+        use super::*;
+
+        /// Verifies the label offset moves the label from the middle and survives a roundtrip
+        #[test]
+        fn label_offset_roundtrip() {
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "uses");
+            let middle = Position::new(50.0, 20.0);
+            assert_eq!(rel.label_position(middle), middle);
+            assert!(!serde_json::to_string(&rel).unwrap().contains("label_offset"));
+
+            rel.label_offset = Some(Position::new(-10.0, 15.0));
+            assert_eq!(rel.label_position(middle), Position::new(40.0, 35.0));
+            let json = serde_json::to_string(&rel).unwrap();
+            let restored: Relationship = serde_json::from_str(&json).unwrap();
+            assert_eq!(restored.label_offset, rel.label_offset);
+        }
+    }
+
+    mod parallel_tests {
+        use super::*;
+
+        /// Verifies relationships sharing a pair, either way round, are spread about the middle in order
+        #[test]
+        fn parallel_offsets_spread_pairs() {
+            let (a, b, c) = (ElementId::new_v4(), ElementId::new_v4(), ElementId::new_v4());
+            let relationships = vec![
+                Relationship::new(a, b, "Reads"),
+                Relationship::new(b, a, "Notifies"),
+                Relationship::new(a, c, "Alone"),
+                Relationship::new(a, b, "Writes"),
+            ];
+            let offsets = parallel_offsets(&relationships);
+            assert_eq!(offsets.len(), 3);
+            assert_eq!(offsets[&relationships[0].id], -PARALLEL_SPACING);
+            assert_eq!(offsets[&relationships[1].id], 0.0);
+            assert_eq!(offsets[&relationships[3].id], PARALLEL_SPACING);
+        }
+
+        /// Verifies routed relationships and loops keep their own shape
+        #[test]
+        fn parallel_offsets_skip_routed_and_loops() {
+            let (a, b) = (ElementId::new_v4(), ElementId::new_v4());
+            let mut routed = Relationship::new(a, b, "Routed");
+            routed.waypoints = vec![Position::new(0.0, 0.0)];
+            let relationships = vec![
+                Relationship::new(a, b, "Plain"),
+                routed,
+                Relationship::new(a, a, "Loop"),
+                Relationship::new(a, a, "Loop"),
+            ];
+            assert!(parallel_offsets(&relationships).is_empty());
+        }
+    }
+
+    mod self_loop_tests {
+        use super::*;
+
+        /// Verifies the default loop goes around the top-right corner
+        #[test]
+        fn loop_curve_default_corner() {
+            let id = ElementId::new_v4();
+            assert!(Relationship::new(id, id, "Runs nightly job").is_self_loop());
+            assert!(!Relationship::new(id, ElementId::new_v4(), "uses").is_self_loop());
+
+            let curve = loop_curve(Position::new(0.0, 0.0), Size::new(100.0, 50.0), None, None);
+            assert_eq!(
+                curve,
+                [
+                    Position::new(80.0, 0.0),
+                    Position::new(80.0, -50.0),
+                    Position::new(150.0, 10.0),
+                    Position::new(100.0, 10.0),
+                ]
+            );
+            let middle = bezier_midpoint(curve);
+            assert!(middle.x > 100.0 && middle.y < 0.0);
+        }
+
+        /// Verifies pinned anchors move the loop's ends
+        #[test]
+        fn loop_curve_follows_anchors() {
+            let curve = loop_curve(
+                Position::new(0.0, 0.0),
+                Size::new(100.0, 50.0),
+                Some(Anchor::new(Side::West)),
+                Some(Anchor::new(Side::South)),
+            );
+            assert_eq!(curve[0], Position::new(0.0, 25.0));
+            assert_eq!(curve[1], Position::new(-50.0, 25.0));
+            assert_eq!(curve[3], Position::new(50.0, 50.0));
+        }
+    }
+
+    mod anchor_tests {
+        use super::*;
+
+        /// Verifies each side resolves to a point on the element border
+        #[test]
+        fn anchor_point_on_each_side() {
+            let position = Position::new(10.0, 20.0);
+            let size = Size::new(100.0, 50.0);
+
+            assert_eq!(Anchor::new(Side::North).point(position, size), Position::new(60.0, 20.0));
+            assert_eq!(Anchor::new(Side::South).point(position, size), Position::new(60.0, 70.0));
+            assert_eq!(Anchor::new(Side::West).point(position, size), Position::new(10.0, 45.0));
+            assert_eq!(Anchor::new(Side::East).point(position, size), Position::new(110.0, 45.0));
+        }
+
+        /// Verifies fractional offsets are applied along the side and clamped
+        #[test]
+        fn anchor_offset_is_clamped() {
+            let position = Position::new(0.0, 0.0);
+            let size = Size::new(100.0, 50.0);
+
+            assert_eq!(
+                Anchor::with_offset(Side::North, 0.25).point(position, size),
+                Position::new(25.0, 0.0)
+            );
+            assert_eq!(Anchor::with_offset(Side::East, 2.0).offset, 1.0);
+        }
+
+        /// Verifies the nearest anchor projects the point onto the nearest side
+        #[test]
+        fn nearest_anchor_projects_point() {
+            let position = Position::new(0.0, 0.0);
+            let size = Size::new(100.0, 50.0);
+
+            assert_eq!(Anchor::nearest(position, size, Position::new(25.0, -10.0)), Anchor::with_offset(Side::North, 0.25));
+            assert_eq!(Anchor::nearest(position, size, Position::new(130.0, 10.0)), Anchor::with_offset(Side::East, 0.2));
+        }
+
+        /// Verifies a line is anchored where it first crosses the border
+        #[test]
+        fn anchor_on_line_finds_crossing() {
+            let position = Position::new(100.0, 0.0);
+            let size = Size::new(100.0, 50.0);
+
+            let from = Position::new(0.0, 10.0);
+            let anchor = Anchor::on_line(from, Position::new(50.0, 10.0), position, size).unwrap();
+            assert_eq!(anchor, Anchor::with_offset(Side::West, 0.2));
+
+            // Diagonal from above-left enters through the top
+            let anchor = Anchor::on_line(Position::new(60.0, -50.0), Position::new(70.0, -40.0), position, size).unwrap();
+            assert_eq!(anchor, Anchor::with_offset(Side::North, 0.1));
+
+            assert!(Anchor::on_line(from, Position::new(0.0, 20.0), position, size).is_none());
+            assert!(Anchor::on_line(from, Position::new(-50.0, 10.0), position, size).is_none());
+        }
+    }
+
+    mod snap_angle_tests {
+        use super::*;
+
+        fn assert_close(a: Position, b: Position) {
+            assert!((a.x - b.x).abs() < 0.01 && (a.y - b.y).abs() < 0.01, "{:?} != {:?}", a, b);
+        }
+
+        /// Verifies points snap to horizontal, vertical and diagonal lines
+        #[test]
+        fn snap_angle_to_45_degrees() {
+            let from = Position::new(10.0, 10.0);
+            assert_close(snap_angle(from, Position::new(110.0, 15.0)), Position::new(110.12, 10.0));
+            assert_close(snap_angle(from, Position::new(12.0, -90.0)), Position::new(10.0, -90.02));
+            let diagonal = snap_angle(from, Position::new(60.0, 55.0));
+            assert!((diagonal.x - from.x - (diagonal.y - from.y)).abs() < 0.01);
+        }
+
+        /// Verifies a zero-length segment is left alone
+        #[test]
+        fn snap_angle_same_point() {
+            let point = Position::new(5.0, 5.0);
+            assert_eq!(snap_angle(point, point), point);
+        }
+    }
+
+    mod line_style_tests {
+        use super::*;
+
+        /// Verifies the nearest side accounts for the box's proportions
+        #[test]
+        fn nearest_side_of_point() {
+            let position = Position::new(0.0, 0.0);
+            let size = Size::new(200.0, 100.0);
+
+            assert_eq!(Side::nearest(position, size, Position::new(200.0, 50.0)), Side::East);
+            assert_eq!(Side::nearest(position, size, Position::new(0.0, 60.0)), Side::West);
+            assert_eq!(Side::nearest(position, size, Position::new(150.0, 100.0)), Side::South);
+            assert_eq!(Side::nearest(position, size, Position::new(20.0, -5.0)), Side::North);
+        }
+
+        /// Verifies control points extend out of each side, scaled to the line length
+        #[test]
+        fn control_points_follow_side_normals() {
+            let [c1, c2] = curve_control_points(
+                Position::new(0.0, 0.0),
+                Side::East,
+                Position::new(100.0, 100.0),
+                Side::North,
+            );
+            let reach = (100.0f32 * 2.0f32.sqrt()) * 0.4;
+            assert_eq!(c1, Position::new(reach, 0.0));
+            assert_eq!(c2, Position::new(100.0, 100.0 - reach));
+        }
+
+        /// Verifies the control point distance is clamped for very short and long lines
+        #[test]
+        fn control_point_reach_is_clamped() {
+            let [c1, _] = curve_control_points(Position::new(0.0, 0.0), Side::South, Position::new(5.0, 0.0), Side::South);
+            assert_eq!(c1, Position::new(0.0, 20.0));
+            let [c1, _] = curve_control_points(Position::new(0.0, 0.0), Side::West, Position::new(1000.0, 0.0), Side::East);
+            assert_eq!(c1, Position::new(-150.0, 0.0));
+        }
+
+        /// Verifies the style is omitted from JSON unless overridden
+        #[test]
+        fn line_style_roundtrip() {
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "uses");
+            assert!(!serde_json::to_string(&rel).unwrap().contains("line_style"));
+
+            rel.line_style = Some(LineStyle::Curved);
+            let restored: Relationship = serde_json::from_str(&serde_json::to_string(&rel).unwrap()).unwrap();
+            assert_eq!(restored.line_style, Some(LineStyle::Curved));
+        }
     }
 }
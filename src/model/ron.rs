@@ -0,0 +1,63 @@
+//! Diagrams as RON
+//!
+//! RON (Rusty Object Notation) writes enums the way Rust does, so a container
+//! type reads `Other("Lambda")` rather than `{"Other": "Lambda"}`. It needs the
+//! `ron` feature. RON files are not checked against the file schema, which
+//! describes JSON; the reader reports errors by line and column instead.
+
+use super::Diagram;
+use ::ron::ser::PrettyConfig;
+
+/// File extension of RON diagram files
+pub const RON_FILE_EXTENSION: &str = "ron";
+
+impl Diagram {
+    /// Save the diagram as a RON string
+    pub fn to_ron(&self) -> crate::Result<String> {
+        Ok(::ron::ser::to_string_pretty(self, PrettyConfig::default())?)
+    }
+
+    /// Load a diagram from a RON string
+    pub fn from_ron(text: &str) -> crate::Result<Self> {
+        Ok(::ron::from_str(text)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContainerType, DiagramType, Direction, Element, ElementType, Position, Relationship};
+
+    mod ron_tests {
+        use super::*;
+
+        /// Verifies a diagram survives a RON round trip with enums written the Rust way
+        #[test]
+        fn ron_roundtrip() {
+            let mut diagram = Diagram::new("Shop", "Online shop", DiagramType::Container);
+            let api = Element::new(
+                ElementType::container("API", "", ContainerType::Other("Lambda".to_string()), "Rust"),
+                Position::new(10.5, -20.0),
+            );
+            let user = Element::new(ElementType::person("Customer", ""), Position::new(0.0, 0.0));
+            let mut rel = Relationship::new(user.id, api.id, "Uses");
+            rel.direction = Direction::Back;
+            diagram.add_element(api);
+            diagram.add_element(user);
+            diagram.add_relationship(rel);
+
+            let text = diagram.to_ron().unwrap();
+            assert!(text.contains("Other(\"Lambda\")"), "{}", text);
+            let restored = Diagram::from_ron(&text).unwrap();
+            let json = |d: &Diagram| serde_json::to_value(d).unwrap();
+            assert_eq!(json(&restored), json(&diagram));
+        }
+
+        /// Verifies malformed RON is reported with its position
+        #[test]
+        fn from_ron_reports_position() {
+            let err = Diagram::from_ron("(name: \"Shop\",\n  oops)").unwrap_err();
+            assert!(err.to_string().contains("2:"), "{}", err);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Scaffolding a container diagram from a system context diagram
+//!
+//! Drilling into a software system starts from its surroundings: the people
+//! and systems it talks to. The scaffold copies those neighbours, keeping
+//! their IDs, and stands in a single placeholder container for the system
+//! itself so none of its relationships are lost. Splitting the placeholder
+//! into the real containers is left to the user.
+
+use super::{ContainerType, Diagram, DiagramType, Element, ElementId, ElementType, Position};
+
+/// Horizontal distance between neighbours in a row
+const COLUMN_SPACING: f32 = 220.0;
+/// Vertical distance between the rows of people, the placeholder and systems
+const ROW_SPACING: f32 = 200.0;
+/// Top-left corner of the layout
+const ORIGIN: Position = Position { x: 50.0, y: 50.0 };
+
+/// A new Container diagram for the software system `system_id`
+///
+/// People are laid out in a row above the placeholder container and other
+/// systems in a row below it, each row in the left-to-right order the
+/// neighbours had. Relationships between the system and its neighbours are
+/// attached to the placeholder; those among the neighbours are copied as they
+/// are. Returns None if `system_id` is not a software system.
+pub fn container_diagram_for(diagram: &Diagram, system_id: ElementId) -> Option<Diagram> {
+    let system = diagram.get_element(system_id)?;
+    let ElementType::SoftwareSystem(data) = &system.element_type else {
+        return None;
+    };
+
+    let mut neighbors: Vec<&Element> = diagram
+        .relationships_connected_to(system_id)
+        .iter()
+        .map(|r| if r.source_id == system_id { r.target_id } else { r.source_id })
+        .filter(|&id| id != system_id)
+        .filter_map(|id| diagram.get_element(id))
+        .filter(|e| !matches!(e.element_type, ElementType::Container(_)))
+        .collect();
+    neighbors.sort_by(|a, b| a.position.x.total_cmp(&b.position.x).then(a.id.cmp(&b.id)));
+    neighbors.dedup_by_key(|e| e.id);
+    let (people, systems): (Vec<&Element>, Vec<&Element>) =
+        neighbors.into_iter().partition(|e| matches!(e.element_type, ElementType::Person(_)));
+
+    let mut scaffold = Diagram::new(format!("{} - Containers", data.name), data.description.clone(), DiagramType::Container);
+    let columns = people.len().max(systems.len()).max(1);
+    let row = |elements: &[&Element], y: f32| -> Vec<Element> {
+        let indent = (columns - elements.len()) as f32 * COLUMN_SPACING * 0.5;
+        elements
+            .iter()
+            .enumerate()
+            .map(|(i, element)| {
+                let mut copy = (*element).clone();
+                copy.position = Position::new(ORIGIN.x + indent + i as f32 * COLUMN_SPACING, y);
+                copy
+            })
+            .collect()
+    };
+    let placeholder = Element::new(
+        ElementType::container(data.name.clone(), "Replace with the system's containers", ContainerType::Microservice, ""),
+        Position::new(ORIGIN.x + (columns - 1) as f32 * COLUMN_SPACING * 0.5, ORIGIN.y + ROW_SPACING),
+    );
+    let placeholder_id = placeholder.id;
+
+    for element in row(&people, ORIGIN.y)
+        .into_iter()
+        .chain(std::iter::once(placeholder))
+        .chain(row(&systems, ORIGIN.y + 2.0 * ROW_SPACING))
+    {
+        scaffold.add_element(element);
+    }
+
+    for rel in &diagram.relationships {
+        let mut copy = rel.clone();
+        for (end, anchor) in [
+            (&mut copy.source_id, &mut copy.source_anchor),
+            (&mut copy.target_id, &mut copy.target_anchor),
+        ] {
+            if *end == system_id {
+                *end = placeholder_id;
+            }
+            *anchor = None;
+        }
+        // Only relationships between copied elements are kept
+        scaffold.add_relationship(copy);
+    }
+    scaffold.take_events();
+    Some(scaffold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Relationship;
+
+    fn add(diagram: &mut Diagram, element_type: ElementType, x: f32) -> ElementId {
+        let element = Element::new(element_type, Position::new(x, 0.0));
+        let id = element.id;
+        diagram.add_element(element);
+        id
+    }
+
+    mod scaffold_tests {
+        use super::*;
+
+        /// Verifies the scaffold holds the system's neighbours and a placeholder for the system
+        #[test]
+        fn scaffold_copies_neighbors() {
+            let mut context = Diagram::new("Context", "", DiagramType::SystemContext);
+            let shop = add(&mut context, ElementType::system("Shop", "Sells things"), 0.0);
+            let customer = add(&mut context, ElementType::person("Customer", ""), 0.0);
+            let payments = add(&mut context, ElementType::external_system("Payments", ""), 300.0);
+            let mail = add(&mut context, ElementType::external_system("Mail", ""), 100.0);
+            let unrelated = add(&mut context, ElementType::system("Warehouse", ""), 0.0);
+            context.add_relationship(Relationship::new(customer, shop, "Buys from"));
+            context.add_relationship(Relationship::new(shop, payments, "Charges via"));
+            context.add_relationship(Relationship::new(shop, mail, "Sends email via"));
+            context.add_relationship(Relationship::new(payments, mail, "Sends receipts via"));
+            context.add_relationship(Relationship::new(unrelated, payments, "Refunds via"));
+
+            let scaffold = container_diagram_for(&context, shop).unwrap();
+            assert_eq!(scaffold.name, "Shop - Containers");
+            assert_eq!(scaffold.description, "Sells things");
+            assert_eq!(scaffold.diagram_type, DiagramType::Container);
+            assert_eq!(scaffold.elements.len(), 4);
+            assert!(scaffold.get_element(shop).is_none());
+            assert!(scaffold.get_element(unrelated).is_none());
+
+            let placeholder = scaffold
+                .elements
+                .values()
+                .find(|e| matches!(e.element_type, ElementType::Container(_)))
+                .unwrap();
+            assert_eq!(placeholder.name(), "Shop");
+            assert_eq!(scaffold.relationships_connected_to(placeholder.id).len(), 3);
+            assert_eq!(scaffold.relationships.len(), 4);
+
+            // Systems keep their left-to-right order below the placeholder
+            let (mail, payments) = (&scaffold.elements[&mail], &scaffold.elements[&payments]);
+            assert!(mail.position.x < payments.position.x);
+            assert!(mail.position.y > placeholder.position.y);
+            assert!(scaffold.elements[&customer].position.y < placeholder.position.y);
+        }
+
+        /// Verifies only software systems can be scaffolded
+        #[test]
+        fn scaffold_requires_system() {
+            let mut context = Diagram::new("Context", "", DiagramType::SystemContext);
+            let person = add(&mut context, ElementType::person("Customer", ""), 0.0);
+            assert!(container_diagram_for(&context, person).is_none());
+            assert!(container_diagram_for(&context, ElementId::new_v4()).is_none());
+        }
+    }
+}
@@ -0,0 +1,387 @@
+//! JSON Schema for the diagram file format
+//!
+//! The schema in `schema/c4d.schema.json` describes `.c4d` files for editors
+//! and external tools. Files are checked against it when loaded so a broken
+//! file is reported by where it goes wrong (`relationships[2].source_id`)
+//! rather than by line and column. Only the keywords the schema uses are
+//! supported: `type`, `enum`, `required`, `properties`,
+//! `additionalProperties`, `propertyNames`, `items`, `oneOf`, `$ref` and the
+//! `uuid` format.
+
+use serde_json::{Map, Value};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// The JSON Schema for `.c4d` files
+pub const FILE_SCHEMA: &str = include_str!("../../schema/c4d.schema.json");
+
+/// Where a file breaks the schema and how
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{path}: {message}")]
+pub struct SchemaError {
+    /// Location in the file, e.g. `relationships[2].source_id`
+    pub path: String,
+    pub message: String,
+}
+
+fn schema() -> &'static Value {
+    static SCHEMA: OnceLock<Value> = OnceLock::new();
+    SCHEMA.get_or_init(|| serde_json::from_str(FILE_SCHEMA).expect("bundled schema is valid JSON"))
+}
+
+/// Check a parsed diagram file against [`FILE_SCHEMA`]
+pub fn check(value: &Value) -> Result<(), SchemaError> {
+    Checker::default().check(schema(), value, "")
+}
+
+/// Walks a value alongside its schema, noting object keys the schema does
+/// not declare so tests can keep the schema in step with the model
+#[derive(Default)]
+struct Checker {
+    undeclared: Vec<String>,
+}
+
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn type_matches(name: &str, value: &Value) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => false,
+    }
+}
+
+fn child(path: &str, key: &str) -> String {
+    if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) }
+}
+
+fn error(path: &str, message: String) -> SchemaError {
+    SchemaError {
+        path: if path.is_empty() { "top level".to_string() } else { path.to_string() },
+        message,
+    }
+}
+
+/// Follow a `#/definitions/Name` reference
+fn resolve(schema: &Value) -> &Value {
+    match schema.get("$ref").and_then(Value::as_str).and_then(|r| r.strip_prefix("#/definitions/")) {
+        Some(name) => resolve(&self::schema()["definitions"][name]),
+        None => schema,
+    }
+}
+
+/// Restore the types of scalars read as text, as from YAML, where the schema
+/// says a number, integer or boolean goes
+///
+/// A `oneOf` value takes the first branch it then matches. Anything that
+/// cannot be converted is left for [`check`] to report.
+pub(crate) fn coerce(value: &mut Value) {
+    coerce_to(schema(), value);
+}
+
+fn coerce_to(schema: &Value, value: &mut Value) {
+    let schema = resolve(schema);
+    let names: Vec<&str> = match schema.get("type") {
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        Some(other) => other.as_str().into_iter().collect(),
+        None => Vec::new(),
+    };
+    if let Some(text) = value.as_str()
+        && !names.is_empty()
+        && !names.contains(&"string")
+    {
+        let converted = if names.contains(&"integer") || names.contains(&"number") {
+            text.parse::<i64>()
+                .map(Value::from)
+                .ok()
+                .or_else(|| names.contains(&"number").then(|| text.parse::<f64>().ok().map(Value::from)).flatten())
+        } else {
+            None
+        };
+        let converted = converted.or_else(|| {
+            (names.contains(&"boolean") && matches!(text, "true" | "false")).then(|| Value::Bool(text == "true"))
+        });
+        if let Some(converted) = converted {
+            *value = converted;
+        }
+    }
+    if let Some(branches) = schema.get("oneOf").and_then(Value::as_array) {
+        for branch in branches {
+            let mut candidate = value.clone();
+            coerce_to(branch, &mut candidate);
+            if Checker::default().check(branch, &candidate, "").is_ok() {
+                *value = candidate;
+                break;
+            }
+        }
+    }
+    match value {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, value) in map.iter_mut() {
+                if let Some(property) = properties.and_then(|p| p.get(key)).or_else(|| schema.get("additionalProperties")) {
+                    coerce_to(property, value);
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                items.iter_mut().for_each(|item| coerce_to(item_schema, item));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn required(schema: &Value) -> Vec<&str> {
+    schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str).collect()
+}
+
+impl Checker {
+    fn check(&mut self, schema: &Value, value: &Value, path: &str) -> Result<(), SchemaError> {
+        let schema = resolve(schema);
+        if let Some(types) = schema.get("type") {
+            let names: Vec<&str> = match types {
+                Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                other => other.as_str().into_iter().collect(),
+            };
+            if !names.iter().any(|name| type_matches(name, value)) {
+                let expected: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+                return Err(error(path, format!("expected {}, found {}", expected.join(" or "), describe(value))));
+            }
+        }
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+            && !allowed.contains(value)
+        {
+            let names: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            return Err(error(path, format!("expected one of {}, found {}", names.join(", "), value)));
+        }
+        if schema.get("format").and_then(Value::as_str) == Some("uuid")
+            && let Some(text) = value.as_str()
+            && uuid::Uuid::parse_str(text).is_err()
+        {
+            return Err(error(path, format!("\"{}\" is not a valid id", text)));
+        }
+        if let Some(branches) = schema.get("oneOf").and_then(Value::as_array) {
+            self.check_one_of(branches, value, path)?;
+        }
+        match value {
+            Value::Object(map) => self.check_object(schema, map, path),
+            Value::Array(items) => match schema.get("items") {
+                Some(item_schema) => items
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(i, item)| self.check(item_schema, item, &format!("{}[{}]", path, i))),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn check_object(&mut self, schema: &Value, map: &Map<String, Value>, path: &str) -> Result<(), SchemaError> {
+        if let Some(missing) = required(schema).into_iter().find(|key| !map.contains_key(*key)) {
+            return Err(error(path, format!("missing required field \"{}\"", missing)));
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, value) in map {
+            if let Some(names) = schema.get("propertyNames") {
+                self.check(names, &Value::String(key.clone()), &child(path, key))?;
+            }
+            match (properties.and_then(|p| p.get(key)), schema.get("additionalProperties")) {
+                (Some(property), _) => self.check(property, value, &child(path, key))?,
+                (None, Some(additional)) => self.check(additional, value, &child(path, key))?,
+                // keys of a oneOf value were checked by the branch it matched
+                (None, None) if schema.get("oneOf").is_none() => self.undeclared.push(child(path, key)),
+                (None, None) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// A branch must match; when none does, report the error of the branch
+    /// the value was evidently meant for
+    fn check_one_of(&mut self, branches: &[Value], value: &Value, path: &str) -> Result<(), SchemaError> {
+        let mut candidates = Vec::new();
+        for branch in branches {
+            let mut checker = Checker::default();
+            match checker.check(branch, value, path) {
+                Ok(()) => {
+                    self.undeclared.append(&mut checker.undeclared);
+                    return Ok(());
+                }
+                Err(e) => {
+                    let branch = resolve(branch);
+                    let shaped = branch.get("type").and_then(Value::as_str).is_none_or(|t| type_matches(t, value))
+                        && required(branch).iter().all(|key| value.get(key).is_some());
+                    if shaped {
+                        candidates.push(e);
+                    }
+                }
+            }
+        }
+        if candidates.len() == 1 {
+            return Err(candidates.remove(0));
+        }
+        let keys: Vec<&str> = branches.iter().flat_map(|b| required(resolve(b))).collect();
+        if value.is_object() && !keys.is_empty() {
+            Err(error(path, format!("expected an object with one of the keys {}", keys.join(", "))))
+        } else {
+            Err(error(path, format!("{} is not an allowed value here", describe(value))))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        Anchor, CloudIcon, Comment, CommentAnchor, CommentThread, ContainerType, Diagram, DiagramType, Direction, Element,
+        ElementType, IconPack, LineStyle, Position, Relationship, Side, SystemKind,
+    };
+    use crate::style::{BorderStyle, Color, ElementStyle, Shape};
+
+    /// A diagram with every optional field filled in
+    fn full_diagram() -> Diagram {
+        let mut diagram = Diagram::new("Shop", "Online shop", DiagramType::Container);
+        diagram.metadata.author = "Sam".to_string();
+        diagram.metadata.created = Some("2024-03-01T10:00:00Z".to_string());
+        diagram.metadata.version_label = "v2".to_string();
+        diagram.add_revision("Sam", "First draft", std::time::UNIX_EPOCH);
+        let mut user = Element::new(ElementType::person("Customer", ""), Position::new(0.0, 0.0));
+        user.url = Some("https://example.com".to_string());
+        user.tags = vec!["external".to_string()];
+        user.alias = Some("customer".to_string());
+        user.z_index = 2;
+        user.style = ElementStyle {
+            background: Some(Color::rgb(255, 0, 0)),
+            border: Some(Color::gray(0)),
+            text: Some(Color::gray(255)),
+            shape: Some(Shape::Ellipse),
+            border_style: Some(BorderStyle::Dotted),
+        };
+        let shop = Element::new(ElementType::system("Shop", ""), Position::new(0.0, 400.0));
+        let mut api = ElementType::container("API", "", ContainerType::Other("Lambda".to_string()), "Rust");
+        if let ElementType::Container(data) = &mut api {
+            data.icon = Some(CloudIcon::new(IconPack::Aws, "Lambda"));
+            data.system = Some(shop.id);
+        }
+        let api = Element::new(api, Position::new(200.0, 0.0));
+        let mut broker = ElementType::external_system("Broker", "");
+        if let ElementType::SoftwareSystem(data) = &mut broker {
+            data.kind = SystemKind::Queue;
+        }
+        let (user_id, api_id) = (user.id, api.id);
+        diagram.add_element(user);
+        diagram.add_element(shop);
+        diagram.add_element(api);
+        diagram.add_element(Element::new(broker, Position::new(400.0, 0.0)));
+        let mut checkout = ElementType::component("Checkout", "", "Spring Bean");
+        if let ElementType::Component(data) = &mut checkout {
+            data.container = Some(api_id);
+        }
+        diagram.add_element(Element::new(checkout, Position::new(200.0, 200.0)));
+        diagram.add_element(Element::new(ElementType::note("Pending review"), Position::new(400.0, 200.0)));
+        let mut rel = Relationship::with_technology(user_id, api_id, "Uses", "HTTPS");
+        rel.note = Some("rate limited".to_string());
+        rel.source_anchor = Some(Anchor::new(Side::East));
+        rel.tags = vec!["sync".to_string()];
+        rel.line_style = Some(LineStyle::Curved);
+        rel.direction = Direction::Back;
+        rel.order = Some(1);
+        rel.waypoints = vec![Position::new(100.0, 50.0)];
+        rel.label_offset = Some(Position::new(0.0, -20.0));
+        diagram.add_relationship(rel);
+        let mut thread = CommentThread::new(CommentAnchor::Element(api_id), Comment::new("Sam", "Split this?"));
+        thread.resolved = true;
+        diagram.comments.push(thread);
+        diagram
+            .comments
+            .push(CommentThread::new(CommentAnchor::Position(Position::new(1.0, 2.0)), Comment::new("Sam", "Gap")));
+        diagram.group_elements("Front", &[user_id, api_id]);
+        diagram.add_label(crate::model::TextLabel::new("Checkout flow", Position::new(0.0, -60.0)));
+        let mut view = crate::model::View::new("Sync");
+        view.include_tags = vec!["sync".to_string()];
+        view.exclude_tags = vec!["legacy".to_string()];
+        view.types = vec!["Container".to_string()];
+        let view = diagram.add_view(view);
+        diagram.set_active_view(Some(view));
+        diagram
+    }
+
+    fn value(diagram: &Diagram) -> Value {
+        serde_json::from_str(&diagram.to_json().unwrap()).unwrap()
+    }
+
+    mod check_tests {
+        use super::*;
+
+        /// Verifies saved diagrams pass and the schema declares every field they write
+        #[test]
+        fn schema_matches_model() {
+            let mut checker = Checker::default();
+            checker.check(schema(), &value(&full_diagram()), "").unwrap();
+            assert!(checker.undeclared.is_empty(), "not in schema: {:?}", checker.undeclared);
+        }
+
+        /// Verifies errors name the field that is wrong
+        #[test]
+        fn errors_point_at_field() {
+            let mut json = value(&full_diagram());
+            json["relationships"][0]["source_id"] = Value::from("nope");
+            let err = check(&json).unwrap_err();
+            assert_eq!(err.to_string(), "relationships[0].source_id: \"nope\" is not a valid id");
+
+            let mut json = value(&full_diagram());
+            json.as_object_mut().unwrap().remove("diagram_type");
+            assert_eq!(check(&json).unwrap_err().to_string(), "top level: missing required field \"diagram_type\"");
+        }
+
+        /// Verifies a oneOf failure is reported from the variant the value was meant to be
+        #[test]
+        fn one_of_reports_intended_variant() {
+            let diagram = full_diagram();
+            let id = diagram.elements.values().find(|e| e.name() == "API").unwrap().id;
+            let mut json = value(&diagram);
+            json["elements"][id.to_string()]["element_type"]["Container"]["container_type"] = Value::from("Databse");
+            let err = check(&json).unwrap_err();
+            assert_eq!(err.path, format!("elements.{}.element_type.Container.container_type", id));
+            assert!(err.message.starts_with("expected one of \"WebApplication\""));
+
+            let element_type = json["elements"][id.to_string()]["element_type"].as_object_mut().unwrap();
+            let data = element_type.remove("Container").unwrap();
+            element_type.insert("Persn".to_string(), data);
+            let err = check(&json).unwrap_err();
+            assert_eq!(err.message, "expected an object with one of the keys Person, SoftwareSystem, Container, Component, Note");
+        }
+
+        /// Verifies coercion restores every typed field of a diagram read back from YAML
+        #[test]
+        fn coerce_restores_yaml_types() {
+            let diagram = full_diagram();
+            let restored = Diagram::from_yaml(&diagram.to_yaml().unwrap()).unwrap();
+            assert_eq!(value(&restored), value(&diagram));
+        }
+
+        /// Verifies wrong types say what was expected and found
+        #[test]
+        fn type_mismatch_message() {
+            let mut json = value(&full_diagram());
+            json["relationships"] = Value::from("none");
+            assert_eq!(check(&json).unwrap_err().message, "expected array, found a string");
+        }
+    }
+}
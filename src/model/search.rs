@@ -0,0 +1,111 @@
+//! Finding elements
+//!
+//! Every finder returns matching elements sorted by name (ignoring case) and
+//! then by ID, so results are stable between calls and across saves. The
+//! convenience finders ignore case; use [`Diagram::find_elements`] with a
+//! closure for anything else.
+
+use super::{Diagram, Element};
+
+impl Diagram {
+    /// Elements for which `predicate` holds
+    pub fn find_elements(&self, predicate: impl Fn(&Element) -> bool) -> Vec<&Element> {
+        let mut found: Vec<&Element> = self.elements.values().filter(|e| predicate(e)).collect();
+        found.sort_by(|a, b| {
+            a.name()
+                .to_lowercase()
+                .cmp(&b.name().to_lowercase())
+                .then(a.id.cmp(&b.id))
+        });
+        found
+    }
+
+    /// Elements whose name contains `text`; an empty `text` matches everything
+    pub fn find_by_name(&self, text: &str) -> Vec<&Element> {
+        let text = text.trim().to_lowercase();
+        self.find_elements(|e| e.name().to_lowercase().contains(&text))
+    }
+
+    /// Elements of a type, named as [`ElementType::type_name`](super::ElementType::type_name)
+    /// gives it, e.g. "Container" or "Software System"
+    pub fn find_by_type(&self, type_name: &str) -> Vec<&Element> {
+        let type_name = type_name.trim();
+        self.find_elements(|e| e.element_type.type_name().eq_ignore_ascii_case(type_name))
+    }
+
+    /// Elements carrying `tag`
+    pub fn find_by_tag(&self, tag: &str) -> Vec<&Element> {
+        let tag = tag.trim();
+        self.find_elements(|e| e.tags.iter().any(|t| t.trim().eq_ignore_ascii_case(tag)))
+    }
+
+    /// Containers and components whose technology contains `text`
+    pub fn find_by_technology(&self, text: &str) -> Vec<&Element> {
+        let text = text.trim().to_lowercase();
+        self.find_elements(|e| {
+            e.element_type
+                .technology()
+                .is_some_and(|technology| technology.to_lowercase().contains(&text))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContainerType, DiagramType, ElementId, ElementType, Position};
+
+    /// Diagram with a shop system, two containers and a customer
+    fn shop() -> Diagram {
+        let mut diagram = Diagram::new("Shop", "", DiagramType::Container);
+        let mut api = Element::new(
+            ElementType::container("Shop API", "", ContainerType::Microservice, "Rust, Axum"),
+            Position::new(0.0, 0.0),
+        );
+        api.tags = vec!["Public".to_string()];
+        let mut user = Element::new(ElementType::person("customer", ""), Position::new(0.0, 0.0));
+        user.tags = vec!["public".to_string(), "external".to_string()];
+        for element in [
+            api,
+            user,
+            Element::new(ElementType::system("Shop", ""), Position::new(0.0, 0.0)),
+            Element::new(ElementType::container("Orders DB", "", ContainerType::Database, "PostgreSQL"), Position::new(0.0, 0.0)),
+        ] {
+            diagram.add_element(element);
+        }
+        diagram
+    }
+
+    fn names(elements: Vec<&Element>) -> Vec<&str> {
+        elements.into_iter().map(Element::name).collect()
+    }
+
+    mod search_tests {
+        use super::*;
+
+        /// Verifies results are sorted by name ignoring case, then by ID
+        #[test]
+        fn find_elements_is_sorted() {
+            let mut diagram = shop();
+            assert_eq!(names(diagram.find_elements(|_| true)), vec!["customer", "Orders DB", "Shop", "Shop API"]);
+
+            let twin = Element::new(ElementType::system("Shop", ""), Position::new(0.0, 0.0));
+            diagram.add_element(twin);
+            let shops: Vec<ElementId> = diagram.find_by_name("shop").iter().take(2).map(|e| e.id).collect();
+            assert!(shops[0] < shops[1]);
+        }
+
+        /// Verifies the finders by name, type, tag and technology ignore case
+        #[test]
+        fn convenience_finders() {
+            let diagram = shop();
+            assert_eq!(names(diagram.find_by_name("SHOP")), vec!["Shop", "Shop API"]);
+            assert_eq!(diagram.find_by_name("").len(), 4);
+            assert_eq!(names(diagram.find_by_type("container")), vec!["Orders DB", "Shop API"]);
+            assert_eq!(names(diagram.find_by_type("Software System")), vec!["Shop"]);
+            assert_eq!(names(diagram.find_by_tag("PUBLIC")), vec!["customer", "Shop API"]);
+            assert_eq!(names(diagram.find_by_technology("postgres")), vec!["Orders DB"]);
+            assert!(diagram.find_by_technology("Java").is_empty());
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use super::{Diagram, Element, ElementId, ElementType, Position, Relationship, FILE_FORMAT_VERSION};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// File extension of snippet files
+pub const SNIPPET_FILE_EXTENSION: &str = "c4snip";
+
+/// A reusable fragment of a diagram
+///
+/// Holds a set of elements and the relationships between them. Inserting a
+/// snippet gives every element and relationship a fresh ID, so the same
+/// snippet can be imported into many diagrams (or several times into one).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    #[serde(default = "default_version")]
+    pub version: String,
+    pub elements: Vec<Element>,
+    pub relationships: Vec<Relationship>,
+}
+
+fn default_version() -> String {
+    FILE_FORMAT_VERSION.to_string()
+}
+
+impl Snippet {
+    /// Copy the given elements and the relationships among them
+    ///
+    /// Relationships to elements outside the selection are left out. Unknown
+    /// IDs are ignored.
+    pub fn from_selection(diagram: &Diagram, ids: &[ElementId]) -> Self {
+        let elements: Vec<Element> = ids
+            .iter()
+            .filter_map(|id| diagram.get_element(*id))
+            .cloned()
+            .collect();
+        let relationships = diagram
+            .relationships
+            .iter()
+            .filter(|r| ids.contains(&r.source_id) && ids.contains(&r.target_id))
+            .cloned()
+            .collect();
+        Self {
+            version: FILE_FORMAT_VERSION.to_string(),
+            elements,
+            relationships,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Top-left corner of the snippet's elements
+    pub fn origin(&self) -> Position {
+        let mut elements = self.elements.iter();
+        let Some(first) = elements.next() else {
+            return Position::new(0.0, 0.0);
+        };
+        elements.fold(first.position, |origin, e| {
+            Position::new(origin.x.min(e.position.x), origin.y.min(e.position.y))
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Save the snippet to a file
+    pub fn save_to_file(&self, path: &std::path::Path) -> crate::Result<()> {
+        let json = self.to_json()?;
+        std::fs::write(path, json).map_err(|e| crate::C2DrawError::file(path, e))
+    }
+
+    /// Load a snippet from a file
+    pub fn load_from_file(path: &std::path::Path) -> crate::Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| crate::C2DrawError::file(path, e))?;
+        Ok(Self::from_json(&content)?)
+    }
+}
+
+impl Diagram {
+    /// Insert a copy of a snippet with fresh IDs, its top-left corner at `at`
+    ///
+    /// Parents inside the snippet follow the new IDs; parents outside it are
+    /// kept. Export aliases are dropped so they don't clash. Returns the IDs of the inserted elements in snippet order.
+    pub fn insert_snippet(&mut self, snippet: &Snippet, at: Position) -> Vec<ElementId> {
+        let origin = snippet.origin();
+        let ids: HashMap<ElementId, ElementId> =
+            snippet.elements.iter().map(|e| (e.id, ElementId::new_v4())).collect();
+        let mut inserted = Vec::with_capacity(snippet.elements.len());
+
+        for element in &snippet.elements {
+            let new_id = ids[&element.id];
+            inserted.push(new_id);
+            let mut element = Element {
+                id: new_id,
+                position: Position::new(
+                    at.x + element.position.x - origin.x,
+                    at.y + element.position.y - origin.y,
+                ),
+                alias: None,
+                ..element.clone()
+            };
+            match &mut element.element_type {
+                ElementType::Container(data) => data.system = data.system.map(|p| ids.get(&p).copied().unwrap_or(p)),
+                ElementType::Component(data) => {
+                    data.container = data.container.map(|p| ids.get(&p).copied().unwrap_or(p))
+                }
+                ElementType::Person(_) | ElementType::SoftwareSystem(_) | ElementType::Note(_) => {}
+            }
+            self.add_element(element);
+        }
+
+        for rel in &snippet.relationships {
+            let (Some(&source_id), Some(&target_id)) = (ids.get(&rel.source_id), ids.get(&rel.target_id)) else {
+                continue;
+            };
+            self.add_relationship(Relationship {
+                id: uuid::Uuid::new_v4(),
+                source_id,
+                target_id,
+                waypoints: rel
+                    .waypoints
+                    .iter()
+                    .map(|p| Position::new(at.x + p.x - origin.x, at.y + p.y - origin.y))
+                    .collect(),
+                ..rel.clone()
+            });
+        }
+
+        inserted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramEvent, DiagramType, ElementType};
+
+    /// Diagram with A -> B -> C, returning the element IDs
+    fn chain() -> (Diagram, [ElementId; 3]) {
+        let mut diagram = Diagram::new("Source", "", DiagramType::SystemContext);
+        let a = Element::new(ElementType::person("A", ""), Position::new(100.0, 200.0));
+        let b = Element::new(ElementType::system("B", ""), Position::new(300.0, 150.0));
+        let c = Element::new(ElementType::system("C", ""), Position::new(500.0, 200.0));
+        let ids = [a.id, b.id, c.id];
+        diagram.add_element(a);
+        diagram.add_element(b);
+        diagram.add_element(c);
+        diagram.add_relationship(Relationship::new(ids[0], ids[1], "Uses"));
+        diagram.add_relationship(Relationship::new(ids[1], ids[2], "Calls"));
+        diagram.take_events();
+        (diagram, ids)
+    }
+
+    mod from_selection_tests {
+        use super::*;
+
+        /// Verifies only relationships inside the selection are kept
+        #[test]
+        fn from_selection_keeps_internal_relationships() {
+            let (diagram, [a, b, _]) = chain();
+            let snippet = Snippet::from_selection(&diagram, &[a, b]);
+            assert_eq!(snippet.elements.len(), 2);
+            assert_eq!(snippet.relationships.len(), 1);
+            assert_eq!(snippet.relationships[0].description, "Uses");
+        }
+
+        /// Verifies unknown IDs are skipped
+        #[test]
+        fn from_selection_ignores_unknown_ids() {
+            let (diagram, _) = chain();
+            let snippet = Snippet::from_selection(&diagram, &[ElementId::new_v4()]);
+            assert!(snippet.is_empty());
+        }
+
+        /// Verifies the origin is the top-left of all elements
+        #[test]
+        fn origin_is_top_left() {
+            let (diagram, ids) = chain();
+            let snippet = Snippet::from_selection(&diagram, &ids);
+            assert_eq!(snippet.origin(), Position::new(100.0, 150.0));
+        }
+    }
+
+    mod insert_snippet_tests {
+        use super::*;
+
+        /// Verifies inserted elements and relationships get fresh, consistent IDs
+        #[test]
+        fn insert_snippet_remaps_ids() {
+            let (diagram, ids) = chain();
+            let snippet = Snippet::from_selection(&diagram, &ids);
+
+            let mut target = diagram.clone();
+            let inserted = target.insert_snippet(&snippet, Position::new(0.0, 0.0));
+
+            assert_eq!(inserted.len(), 3);
+            assert!(inserted.iter().all(|id| !ids.contains(id)));
+            assert_eq!(target.elements.len(), 6);
+            assert_eq!(target.relationships.len(), 4);
+            let copied = &target.relationships[2];
+            assert_eq!((copied.source_id, copied.target_id), (inserted[0], inserted[1]));
+            assert_ne!(copied.id, diagram.relationships[0].id);
+        }
+
+        /// Verifies the layout and relationship routes are kept relative to the insertion point
+        #[test]
+        fn insert_snippet_offsets_positions() {
+            let (mut diagram, ids) = chain();
+            diagram.relationships[0].waypoints = vec![Position::new(200.0, 300.0)];
+            let snippet = Snippet::from_selection(&diagram, &ids);
+
+            let mut target = Diagram::default();
+            let inserted = target.insert_snippet(&snippet, Position::new(10.0, 20.0));
+
+            assert_eq!(target.elements[&inserted[0]].position, Position::new(10.0, 70.0));
+            assert_eq!(target.elements[&inserted[1]].position, Position::new(210.0, 20.0));
+            assert_eq!(target.relationships[0].waypoints, vec![Position::new(110.0, 170.0)]);
+        }
+
+        /// Verifies a copied container moves into the copied system but keeps an outside parent otherwise
+        #[test]
+        fn insert_snippet_remaps_parents() {
+            let (mut diagram, [_, system, _]) = chain();
+            let mut api = Element::new(
+                ElementType::container("API", "", crate::model::ContainerType::Microservice, ""),
+                Position::new(300.0, 300.0),
+            );
+            if let ElementType::Container(data) = &mut api.element_type {
+                data.system = Some(system);
+            }
+            let api_id = api.id;
+            diagram.add_element(api);
+
+            let inserted = diagram.insert_snippet(&Snippet::from_selection(&diagram, &[system, api_id]), Position::new(0.0, 0.0));
+            assert_eq!(diagram.elements[&inserted[1]].parent(), Some(inserted[0]));
+
+            let inserted = diagram.insert_snippet(&Snippet::from_selection(&diagram, &[api_id]), Position::new(0.0, 0.0));
+            assert_eq!(diagram.elements[&inserted[0]].parent(), Some(system));
+        }
+
+        /// Verifies insertion is reported through diagram events
+        #[test]
+        fn insert_snippet_emits_events() {
+            let (diagram, ids) = chain();
+            let snippet = Snippet::from_selection(&diagram, &ids[..2]);
+
+            let mut target = Diagram::default();
+            target.insert_snippet(&snippet, Position::new(0.0, 0.0));
+            let events = target.take_events();
+            assert_eq!(events.iter().filter(|e| matches!(e, DiagramEvent::ElementAdded(_))).count(), 2);
+            assert_eq!(events.iter().filter(|e| matches!(e, DiagramEvent::RelationshipAdded(_))).count(), 1);
+        }
+
+        /// Verifies snippets survive a JSON roundtrip
+        #[test]
+        fn snippet_json_roundtrip() {
+            let (diagram, ids) = chain();
+            let snippet = Snippet::from_selection(&diagram, &ids);
+            let restored = Snippet::from_json(&snippet.to_json().unwrap()).unwrap();
+            assert_eq!(restored.elements.len(), 3);
+            assert_eq!(restored.relationships.len(), 2);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Diagram statistics
+//!
+//! A summary of what a diagram holds: how many elements of each type, how
+//! many relationships, how many elements are external, and which elements
+//! are not connected to anything.
+
+use super::{Diagram, ElementId, View};
+
+/// Counts summarising a diagram
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DiagramStats {
+    /// Elements of each type, named as [`ElementType::type_name`](super::ElementType::type_name)
+    /// gives them, in [`View::TYPE_NAMES`] order and including types with none
+    pub by_type: Vec<(&'static str, usize)>,
+    pub relationships: usize,
+    /// C4 elements that are not external; notes count as neither
+    pub internal: usize,
+    /// External people and systems
+    pub external: usize,
+    /// Elements with no relationships, as [`Diagram::orphan_elements`] finds them
+    pub unconnected: Vec<ElementId>,
+}
+
+impl DiagramStats {
+    /// Number of elements of every type, notes included
+    pub fn elements(&self) -> usize {
+        self.by_type.iter().map(|(_, count)| count).sum()
+    }
+}
+
+impl Diagram {
+    pub fn stats(&self) -> DiagramStats {
+        let by_type = View::TYPE_NAMES
+            .iter()
+            .map(|&name| (name, self.elements.values().filter(|e| e.element_type.type_name() == name).count()))
+            .collect();
+        let external = self.elements.values().filter(|e| e.is_external()).count();
+        let notes = self.elements.values().filter(|e| e.is_note()).count();
+        DiagramStats {
+            by_type,
+            relationships: self.relationships.len(),
+            internal: self.elements.len() - external - notes,
+            external,
+            unconnected: self.orphan_elements(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContainerType, DiagramType, Element, ElementType, Position, Relationship};
+
+    mod stats_tests {
+        use super::*;
+
+        /// Verifies the counts by type, internal and external, and the unconnected elements
+        #[test]
+        fn stats_counts_elements_and_relationships() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::Container);
+            let user = Element::new(ElementType::external_person("Customer", ""), Position::new(0.0, 0.0));
+            let api = Element::new(
+                ElementType::container("API", "", ContainerType::Microservice, "Rust"),
+                Position::new(0.0, 0.0),
+            );
+            let bank = Element::new(ElementType::external_system("Bank", ""), Position::new(0.0, 0.0));
+            let (user_id, api_id, bank_id) = (user.id, api.id, bank.id);
+            for element in [user, api, bank, Element::new(ElementType::note("Draft"), Position::new(0.0, 0.0))] {
+                diagram.add_element(element);
+            }
+            diagram.add_relationship(Relationship::new(user_id, api_id, "Uses"));
+
+            let stats = diagram.stats();
+            assert_eq!(
+                stats.by_type,
+                vec![("Person", 1), ("Software System", 1), ("Container", 1), ("Component", 0), ("Note", 1)]
+            );
+            assert_eq!(stats.elements(), 4);
+            assert_eq!(stats.relationships, 1);
+            assert_eq!((stats.internal, stats.external), (1, 2));
+            assert_eq!(stats.unconnected, vec![bank_id]);
+        }
+    }
+}
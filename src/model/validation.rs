@@ -0,0 +1,287 @@
+//! Consistency checks over a diagram
+//!
+//! [`validate`] runs every rule and returns the issues found, most severe
+//! first. Each issue names the rule that raised it, so tools can filter or
+//! report by rule, and the element or relationship it concerns, so editors
+//! can jump to it.
+
+use super::{Diagram, DiagramType, ElementId, ElementType};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How serious an issue is; ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Info, Severity::Warning, Severity::Error];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    /// Parse a severity name as written by [`Severity::as_str`], ignoring case
+    pub fn parse(name: &str) -> Option<Severity> {
+        Severity::ALL.into_iter().find(|s| s.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// What an issue is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Subject {
+    Diagram,
+    Element(ElementId),
+    Relationship(Uuid),
+}
+
+/// A problem found by a validation rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Short kebab-case name of the rule, e.g. `duplicate-name`
+    pub rule: &'static str,
+    pub message: String,
+    pub subject: Subject,
+}
+
+impl ValidationIssue {
+    fn new(severity: Severity, rule: &'static str, subject: Subject, message: String) -> Self {
+        Self {
+            severity,
+            rule,
+            message,
+            subject,
+        }
+    }
+
+    /// The element the issue concerns, if any
+    pub fn element(&self) -> Option<ElementId> {
+        match self.subject {
+            Subject::Element(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Element to show for the issue: its element, or the first end of its
+    /// relationship that is in the diagram
+    pub fn focus(&self, diagram: &Diagram) -> Option<ElementId> {
+        match self.subject {
+            Subject::Diagram => None,
+            Subject::Element(id) => diagram.get_element(id).map(|e| e.id),
+            Subject::Relationship(rel_id) => {
+                let rel = diagram.relationships.iter().find(|r| r.id == rel_id)?;
+                [rel.source_id, rel.target_id].into_iter().find(|id| diagram.get_element(*id).is_some())
+            }
+        }
+    }
+}
+
+/// Run every rule over `diagram`
+///
+/// Issues are sorted by severity, most severe first, then by message.
+pub fn validate(diagram: &Diagram) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    dangling_relationships(diagram, &mut issues);
+    empty_names(diagram, &mut issues);
+    containers_on_context(diagram, &mut issues);
+    components_off_component(diagram, &mut issues);
+    duplicate_names(diagram, &mut issues);
+    issues.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.message.cmp(&b.message)));
+    issues
+}
+
+/// Relationships whose source or target is not in the diagram
+fn dangling_relationships(diagram: &Diagram, issues: &mut Vec<ValidationIssue>) {
+    for rel in &diagram.relationships {
+        let missing: Vec<&str> = [("source", rel.source_id), ("target", rel.target_id)]
+            .into_iter()
+            .filter(|(_, id)| diagram.get_element(*id).is_none())
+            .map(|(end, _)| end)
+            .collect();
+        if !missing.is_empty() {
+            issues.push(ValidationIssue::new(
+                Severity::Error,
+                "dangling-relationship",
+                Subject::Relationship(rel.id),
+                format!("relationship \"{}\" has no {} element", rel.description, missing.join(" or ")),
+            ));
+        }
+    }
+}
+
+/// Elements without a name; notes may be blank
+fn empty_names(diagram: &Diagram, issues: &mut Vec<ValidationIssue>) {
+    for element in diagram.elements.values().filter(|e| !e.is_note()) {
+        if element.name().trim().is_empty() {
+            issues.push(ValidationIssue::new(
+                Severity::Error,
+                "empty-name",
+                Subject::Element(element.id),
+                format!("{} has no name", element.element_type.type_name()),
+            ));
+        }
+    }
+}
+
+/// Containers belong on container diagrams, not the system context
+fn containers_on_context(diagram: &Diagram, issues: &mut Vec<ValidationIssue>) {
+    if diagram.diagram_type != DiagramType::SystemContext {
+        return;
+    }
+    for element in diagram.elements.values() {
+        if matches!(element.element_type, ElementType::Container(_)) {
+            issues.push(ValidationIssue::new(
+                Severity::Warning,
+                "container-on-context",
+                Subject::Element(element.id),
+                format!("container \"{}\" is on a System Context diagram", element.name()),
+            ));
+        }
+    }
+}
+
+/// Components belong on component diagrams
+fn components_off_component(diagram: &Diagram, issues: &mut Vec<ValidationIssue>) {
+    if diagram.diagram_type.supports_components() {
+        return;
+    }
+    for element in diagram.elements.values() {
+        if matches!(element.element_type, ElementType::Component(_)) {
+            issues.push(ValidationIssue::new(
+                Severity::Warning,
+                "component-off-component",
+                Subject::Element(element.id),
+                format!("component \"{}\" is on a {} diagram", element.name(), diagram.diagram_type.as_str()),
+            ));
+        }
+    }
+}
+
+/// Names shared by more than one element, ignoring case and surrounding space;
+/// notes are free text and may repeat
+fn duplicate_names(diagram: &Diagram, issues: &mut Vec<ValidationIssue>) {
+    let mut by_name: HashMap<String, Vec<ElementId>> = HashMap::new();
+    for element in diagram.elements.values().filter(|e| !e.is_note()) {
+        let name = element.name().trim().to_lowercase();
+        if !name.is_empty() {
+            by_name.entry(name).or_default().push(element.id);
+        }
+    }
+    for ids in by_name.values_mut().filter(|ids| ids.len() > 1) {
+        ids.sort();
+        for &id in ids.iter() {
+            issues.push(ValidationIssue::new(
+                Severity::Warning,
+                "duplicate-name",
+                Subject::Element(id),
+                format!("{} elements are named \"{}\"", ids.len(), diagram.elements[&id].name().trim()),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContainerType, Element, Position, Relationship};
+
+    fn add(diagram: &mut Diagram, element_type: ElementType) -> ElementId {
+        let element = Element::new(element_type, Position::new(0.0, 0.0));
+        let id = element.id;
+        diagram.add_element(element);
+        id
+    }
+
+    fn rules(issues: &[ValidationIssue]) -> Vec<&str> {
+        issues.iter().map(|i| i.rule).collect()
+    }
+
+    mod validate_tests {
+        use super::*;
+
+        /// Verifies a consistent diagram has no issues
+        #[test]
+        fn clean_diagram_has_no_issues() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+            let user = add(&mut diagram, ElementType::person("Customer", ""));
+            let shop = add(&mut diagram, ElementType::system("Shop", ""));
+            diagram.add_relationship(Relationship::new(user, shop, "Buys from"));
+            assert!(validate(&diagram).is_empty());
+        }
+
+        /// Verifies relationships to missing elements are errors
+        #[test]
+        fn dangling_relationship_is_error() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+            let user = add(&mut diagram, ElementType::person("Customer", ""));
+            // add_relationship refuses dangling ends, but loaded files can still have them
+            diagram.relationships.push(Relationship::new(user, Uuid::new_v4(), "Uses"));
+            let issues = validate(&diagram);
+            assert_eq!(rules(&issues), vec!["dangling-relationship"]);
+            assert_eq!(issues[0].severity, Severity::Error);
+            assert_eq!(issues[0].message, "relationship \"Uses\" has no target element");
+            assert_eq!(issues[0].focus(&diagram), Some(user));
+        }
+
+        /// Verifies unnamed elements, containers on context diagrams and duplicates are reported, errors first
+        #[test]
+        fn element_rules() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+            let unnamed = add(&mut diagram, ElementType::system("  ", ""));
+            add(&mut diagram, ElementType::container("API", "", ContainerType::Microservice, ""));
+            add(&mut diagram, ElementType::person("Customer", ""));
+            add(&mut diagram, ElementType::system("customer ", ""));
+
+            let issues = validate(&diagram);
+            assert_eq!(rules(&issues), vec!["empty-name", "duplicate-name", "duplicate-name", "container-on-context"]);
+            assert_eq!(issues[0].element(), Some(unnamed));
+            assert!(issues[1].message.starts_with("2 elements are named"));
+
+            diagram.diagram_type = DiagramType::Container;
+            assert!(!rules(&validate(&diagram)).contains(&"container-on-context"));
+        }
+
+        /// Verifies components are only expected on component diagrams
+        #[test]
+        fn component_off_component_diagram() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::Container);
+            add(&mut diagram, ElementType::component("Checkout", "", "Spring Bean"));
+            let issues = validate(&diagram);
+            assert_eq!(rules(&issues), vec!["component-off-component"]);
+            assert_eq!(issues[0].message, "component \"Checkout\" is on a Container diagram");
+
+            diagram.diagram_type = DiagramType::Component;
+            assert!(validate(&diagram).is_empty());
+        }
+
+        /// Verifies blank and repeated notes are not reported
+        #[test]
+        fn notes_are_not_named_elements() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+            add(&mut diagram, ElementType::note(""));
+            add(&mut diagram, ElementType::note("TODO"));
+            add(&mut diagram, ElementType::note("todo"));
+            assert!(validate(&diagram).is_empty());
+        }
+    }
+
+    mod severity_tests {
+        use super::*;
+
+        /// Verifies severities parse by name and order by seriousness
+        #[test]
+        fn severity_parse_and_order() {
+            assert_eq!(Severity::parse("Warning"), Some(Severity::Warning));
+            assert_eq!(Severity::parse("fatal"), None);
+            assert!(Severity::Error > Severity::Warning && Severity::Warning > Severity::Info);
+        }
+    }
+}
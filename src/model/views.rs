@@ -0,0 +1,223 @@
+//! Views of a diagram
+//!
+//! A view shows part of the same model, such as a "security view" of the
+//! elements tagged `security`. It filters elements by tag and type;
+//! relationships are shown when both their ends are. The diagram stores its
+//! views and which one is active. The canvas and every exporter show only
+//! what the active view shows, and nothing is removed from the model.
+
+use super::{Diagram, DiagramEvent, Element, ElementId};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use uuid::Uuid;
+
+/// A named filter over a diagram's elements
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct View {
+    pub id: Uuid,
+    pub name: String,
+    /// Elements must carry one of these tags; any element if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_tags: Vec<String>,
+    /// Elements carrying any of these tags are left out, even if included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_tags: Vec<String>,
+    /// Element types shown, as [`ElementType::type_name`](super::ElementType::type_name)
+    /// gives them; every type if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+}
+
+impl View {
+    /// Type names a view can filter on
+    pub const TYPE_NAMES: [&'static str; 5] = ["Person", "Software System", "Container", "Component", "Note"];
+
+    /// A view showing everything, to be narrowed down
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            include_tags: Vec::new(),
+            exclude_tags: Vec::new(),
+            types: Vec::new(),
+        }
+    }
+
+    /// Whether the view shows an element; tags and types are compared ignoring case
+    pub fn shows(&self, element: &Element) -> bool {
+        let has_tag = |tags: &[String]| {
+            element
+                .tags
+                .iter()
+                .any(|tag| tags.iter().any(|t| t.trim().eq_ignore_ascii_case(tag.trim())))
+        };
+        (self.include_tags.is_empty() || has_tag(&self.include_tags))
+            && !has_tag(&self.exclude_tags)
+            && (self.types.is_empty()
+                || self.types.iter().any(|t| t.eq_ignore_ascii_case(element.element_type.type_name())))
+    }
+}
+
+impl Diagram {
+    /// The view the canvas and exports show, if any
+    pub fn active_view(&self) -> Option<&View> {
+        self.active_view.and_then(|id| self.views.iter().find(|v| v.id == id))
+    }
+
+    /// Show `view` from now on, or the whole diagram if None; unknown IDs show the whole diagram
+    pub fn set_active_view(&mut self, view: Option<Uuid>) {
+        let view = view.filter(|id| self.views.iter().any(|v| v.id == *id));
+        if self.active_view != view {
+            self.active_view = view;
+            self.emit(DiagramEvent::PropertiesChanged);
+        }
+    }
+
+    /// Add a view and return its ID
+    pub fn add_view(&mut self, view: View) -> Uuid {
+        let id = view.id;
+        self.views.push(view);
+        self.emit(DiagramEvent::PropertiesChanged);
+        id
+    }
+
+    /// Replace the view with the same ID, emitting an event if it changed
+    pub fn update_view(&mut self, view: View) {
+        if let Some(existing) = self.views.iter_mut().find(|v| v.id == view.id)
+            && *existing != view
+        {
+            *existing = view;
+            self.emit(DiagramEvent::PropertiesChanged);
+        }
+    }
+
+    /// Remove a view; removing the active view shows the whole diagram again
+    pub fn remove_view(&mut self, id: Uuid) {
+        let before = self.views.len();
+        self.views.retain(|v| v.id != id);
+        if self.views.len() < before {
+            if self.active_view == Some(id) {
+                self.active_view = None;
+            }
+            self.emit(DiagramEvent::PropertiesChanged);
+        }
+    }
+
+    /// The diagram as the active view shows it
+    ///
+    /// Borrowed when no view is active; otherwise a copy without the
+    /// elements the view hides (and their relationships).
+    pub fn in_active_view(&self) -> Cow<'_, Diagram> {
+        let Some(view) = self.active_view() else {
+            return Cow::Borrowed(self);
+        };
+        let hidden: Vec<ElementId> = self.elements.values().filter(|e| !view.shows(e)).map(|e| e.id).collect();
+        let mut visible = self.clone();
+        for id in hidden {
+            if let Some(element) = visible.elements.remove(&id) {
+                visible.relationships.retain(|r| r.source_id != element.id && r.target_id != element.id);
+            }
+        }
+        visible.remove_orphaned_group_members();
+        visible.active_view = None;
+        visible.take_events();
+        Cow::Owned(visible)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContainerType, DiagramType, ElementType, Position, Relationship};
+
+    /// Diagram with a customer, a tagged API and a database, and relationships between them
+    fn shop() -> (Diagram, [ElementId; 3]) {
+        let mut diagram = Diagram::new("Shop", "", DiagramType::Container);
+        let user = Element::new(ElementType::person("Customer", ""), Position::new(0.0, 0.0));
+        let mut api = Element::new(
+            ElementType::container("API", "", ContainerType::Microservice, "Rust"),
+            Position::new(0.0, 0.0),
+        );
+        api.tags = vec!["Security".to_string()];
+        let mut db = Element::new(
+            ElementType::container("DB", "", ContainerType::Database, "PostgreSQL"),
+            Position::new(0.0, 0.0),
+        );
+        db.tags = vec!["security".to_string(), "legacy".to_string()];
+        let ids = [user.id, api.id, db.id];
+        for element in [user, api, db] {
+            diagram.add_element(element);
+        }
+        diagram.add_relationship(Relationship::new(ids[0], ids[1], "Uses"));
+        diagram.add_relationship(Relationship::new(ids[1], ids[2], "Reads"));
+        diagram.take_events();
+        (diagram, ids)
+    }
+
+    mod view_tests {
+        use super::*;
+
+        /// Verifies include and exclude tags and types combine
+        #[test]
+        fn view_filters_by_tag_and_type() {
+            let (diagram, [user, api, db]) = shop();
+            let shown = |view: &View| {
+                let mut ids: Vec<ElementId> = diagram.elements.values().filter(|e| view.shows(e)).map(|e| e.id).collect();
+                ids.sort();
+                ids
+            };
+            let sorted = |mut ids: Vec<ElementId>| {
+                ids.sort();
+                ids
+            };
+
+            let mut view = View::new("Security");
+            assert_eq!(shown(&view).len(), 3);
+            view.include_tags = vec!["SECURITY".to_string()];
+            assert_eq!(shown(&view), sorted(vec![api, db]));
+            view.exclude_tags = vec!["legacy".to_string()];
+            assert_eq!(shown(&view), vec![api]);
+
+            let mut people = View::new("People");
+            people.types = vec!["person".to_string()];
+            assert_eq!(shown(&people), vec![user]);
+        }
+
+        /// Verifies the active view hides elements and their relationships in a copy
+        #[test]
+        fn in_active_view_filters_a_copy() {
+            let (mut diagram, [user, api, db]) = shop();
+            assert!(matches!(diagram.in_active_view(), Cow::Borrowed(_)));
+
+            let mut view = View::new("Security");
+            view.include_tags = vec!["security".to_string()];
+            let id = diagram.add_view(view);
+            diagram.set_active_view(Some(id));
+            assert_eq!(diagram.take_events(), vec![DiagramEvent::PropertiesChanged; 2]);
+
+            let visible = diagram.in_active_view();
+            assert!(visible.get_element(user).is_none());
+            assert_eq!(visible.elements.len(), 2);
+            assert_eq!(visible.relationships.len(), 1);
+            assert_eq!((visible.relationships[0].source_id, visible.relationships[0].target_id), (api, db));
+            assert_eq!(diagram.elements.len(), 3);
+        }
+
+        /// Verifies removing the active view shows everything and views survive a save
+        #[test]
+        fn views_roundtrip_and_removal() {
+            let (mut diagram, _) = shop();
+            diagram.set_active_view(Some(Uuid::new_v4()));
+            assert!(diagram.active_view().is_none());
+
+            let id = diagram.add_view(View::new("Data flow"));
+            diagram.set_active_view(Some(id));
+            let restored = Diagram::from_json(&diagram.to_json().unwrap()).unwrap();
+            assert_eq!(restored.active_view().map(|v| v.name.as_str()), Some("Data flow"));
+
+            diagram.remove_view(id);
+            assert!(diagram.active_view().is_none());
+            assert!(diagram.views.is_empty());
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Diagrams as YAML
+//!
+//! YAML files hold the same data as JSON ones, written as block mappings and
+//! sequences so each field sits on its own line and diffs stay small. Keys
+//! come out sorted. Strings are quoted only where a YAML reader would take
+//! them for something else, such as `"123"`, `"true"` or text with `: `.
+//!
+//! Reading goes through the YAML subset reader in [`crate::import::yaml`],
+//! which keeps every scalar as text; the file schema says which of them are
+//! numbers or booleans.
+
+use super::Diagram;
+use crate::import::yaml::{self, Yaml};
+use serde_json::Value;
+
+/// File extensions of YAML diagram files
+pub const YAML_FILE_EXTENSIONS: [&str; 2] = ["yaml", "yml"];
+
+impl Diagram {
+    /// Save the diagram as a YAML string
+    pub fn to_yaml(&self) -> crate::Result<String> {
+        let mut out = String::new();
+        // via JSON text so numbers keep their short f32 form
+        match serde_json::from_slice::<Value>(&serde_json::to_vec(self)?)? {
+            Value::Object(map) if !map.is_empty() => write_map(&mut out, &map, 0),
+            other => {
+                out.push_str(&inline(&other));
+                out.push('\n');
+            }
+        }
+        Ok(out)
+    }
+
+    /// Load a diagram from a YAML string, checked against the file schema
+    pub fn from_yaml(text: &str) -> crate::Result<Self> {
+        let document = yaml::parse(text)?.into_iter().next().unwrap_or(Yaml::Null);
+        let mut value = to_json(document);
+        super::schema::coerce(&mut value);
+        super::schema::check(&value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+fn to_json(node: Yaml) -> Value {
+    match node {
+        Yaml::Null => Value::Null,
+        Yaml::Scalar(text) => Value::String(text),
+        Yaml::List(items) => Value::Array(items.into_iter().map(to_json).collect()),
+        Yaml::Map(entries) => Value::Object(entries.into_iter().map(|(k, v)| (k, to_json(v))).collect()),
+    }
+}
+
+/// Whether a string must be quoted to read back as the same string
+fn needs_quotes(text: &str) -> bool {
+    let looks_like_number = text.parse::<f64>().is_ok()
+        || (text.starts_with(|c: char| c.is_ascii_digit())
+            && text.chars().all(|c| c.is_ascii_digit() || "._:-+".contains(c)));
+    let reserved = matches!(
+        text.to_ascii_lowercase().as_str(),
+        "~" | "null" | "true" | "false" | "yes" | "no" | "on" | "off" | "y" | "n" | ".inf" | "-.inf" | ".nan"
+    );
+    text.is_empty()
+        || text.trim() != text
+        || looks_like_number
+        || reserved
+        || text.starts_with(['-', '?', '.'])
+        || text.contains(|c: char| c.is_control() || ":#'\"[]{},&*!|>%@`\\".contains(c))
+}
+
+/// A string, quoted and escaped if it needs to be
+fn string(text: &str) -> String {
+    if !needs_quotes(text) {
+        return text.to_string();
+    }
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A scalar or an empty collection, written on one line
+fn inline(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => string(s),
+        Value::Array(_) => "[]".to_string(),
+        Value::Object(_) => "{}".to_string(),
+    }
+}
+
+/// Whether a value is written on lines of its own below its key or dash
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+        _ => false,
+    }
+}
+
+fn write_map(out: &mut String, map: &serde_json::Map<String, Value>, indent: usize) {
+    for (key, value) in map {
+        out.push_str(&" ".repeat(indent));
+        write_entry(out, key, value, indent);
+    }
+}
+
+/// `key: value` at the current position; block values go on the lines below
+fn write_entry(out: &mut String, key: &str, value: &Value, indent: usize) {
+    out.push_str(&string(key));
+    out.push(':');
+    if is_block(value) {
+        out.push('\n');
+        write_block(out, value, indent + 2);
+    } else {
+        out.push(' ');
+        out.push_str(&inline(value));
+        out.push('\n');
+    }
+}
+
+fn write_block(out: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Object(map) => write_map(out, map, indent),
+        Value::Array(items) => {
+            for item in items {
+                out.push_str(&" ".repeat(indent));
+                out.push_str("- ");
+                match item {
+                    // the first entry shares the dash line, the rest line up with it
+                    Value::Object(map) if !map.is_empty() => {
+                        let mut entries = map.iter();
+                        if let Some((key, value)) = entries.next() {
+                            write_entry(out, key, value, indent + 2);
+                        }
+                        for (key, value) in entries {
+                            out.push_str(&" ".repeat(indent + 2));
+                            write_entry(out, key, value, indent + 2);
+                        }
+                    }
+                    Value::Array(nested) if !nested.is_empty() => {
+                        out.push('\n');
+                        write_block(out, item, indent + 2);
+                    }
+                    _ => {
+                        out.push_str(&inline(item));
+                        out.push('\n');
+                    }
+                }
+            }
+        }
+        _ => {
+            out.push_str(&" ".repeat(indent));
+            out.push_str(&inline(value));
+            out.push('\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, Element, ElementType, Position, Relationship};
+
+    mod yaml_tests {
+        use super::*;
+
+        /// Verifies a diagram survives a YAML round trip, including strings that look like other types
+        #[test]
+        fn yaml_roundtrip() {
+            let mut diagram = Diagram::new("Shop: v2 # draft", "line one\nline \"two\"", DiagramType::Container);
+            diagram.metadata.version_label = "123".to_string();
+            diagram.metadata.author = "true".to_string();
+            let mut api = Element::new(ElementType::person("- null", " padded "), Position::new(10.5, -20.0));
+            api.tags = vec!["yes".to_string(), "a\\b".to_string(), String::new()];
+            api.z_index = 3;
+            let db = Element::new(ElementType::system("DB", "[primary]"), Position::new(0.0, 0.0));
+            let mut rel = Relationship::new(api.id, db.id, "Reads: {id}");
+            rel.order = Some(2);
+            rel.waypoints = vec![Position::new(1.0, 2.25)];
+            diagram.add_element(api);
+            diagram.add_element(db);
+            diagram.add_relationship(rel);
+
+            let text = diagram.to_yaml().unwrap();
+            let restored = Diagram::from_yaml(&text).unwrap();
+            let json = |d: &Diagram| serde_json::to_value(d).unwrap();
+            assert_eq!(json(&restored), json(&diagram), "{}", text);
+        }
+
+        /// Verifies the written YAML is block style with plain strings where possible
+        #[test]
+        fn yaml_is_readable() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+            diagram.metadata.version_label = "2.0".to_string();
+            let text = diagram.to_yaml().unwrap();
+            let lines: Vec<&str> = text.lines().collect();
+            for line in ["name: Shop", "description: \"\"", "  version_label: \"2.0\"", "relationships: []"] {
+                assert!(lines.contains(&line), "{} not in\n{}", line, text);
+            }
+        }
+
+        /// Verifies values of the wrong type are reported by field
+        #[test]
+        fn from_yaml_reports_schema_errors() {
+            let text = Diagram::new("Shop", "", DiagramType::Container).to_yaml().unwrap();
+            let broken = text.replace("diagram_type: Container", "diagram_type: Contaner");
+            let err = Diagram::from_yaml(&broken).unwrap_err();
+            assert!(err.to_string().contains("diagram_type"), "{}", err);
+        }
+    }
+}
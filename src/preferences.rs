@@ -0,0 +1,453 @@
+//! User preferences
+//!
+//! Preferences are stored as JSON in the platform configuration directory
+//! (`$XDG_CONFIG_HOME/c2draw`, `~/Library/Application Support/c2draw` or
+//! `%APPDATA%\c2draw`) and are independent of any diagram file.
+
+use crate::model::{ContainerType, Element, ElementType, IconPack, LineStyle, Position, Size};
+use crate::style::ElementKind;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// File name of the preferences file inside the configuration directory
+pub const PREFERENCES_FILE_NAME: &str = "preferences.json";
+
+/// Application-wide user preferences
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Java executable used to run `plantuml.jar`
+    pub java_path: String,
+    /// Path to a local `plantuml.jar` for offline rendering (empty = disabled)
+    pub plantuml_jar: String,
+    /// Path to the Mermaid CLI `mmdc` for offline rendering (empty = disabled)
+    pub mmdc_path: String,
+    /// Font sizes used on the canvas
+    pub typography: Typography,
+    /// Number of timestamped backups kept per diagram file (0 = disabled)
+    pub backup_count: usize,
+    /// Rewrite `name.puml` next to the diagram file on every save
+    pub sidecar_plantuml: bool,
+    /// Rewrite `name.mmd` next to the diagram file on every save
+    pub sidecar_mermaid: bool,
+    /// Name shown on review comments
+    pub author: String,
+    /// How relationships without their own line style are drawn
+    pub line_style: LineStyle,
+    /// Path to a `.c4style` stylesheet applied to every diagram (empty = built-in style)
+    pub stylesheet_path: String,
+    /// Overrides for what the sidebar buttons create; other kinds use the built-in template
+    pub element_templates: BTreeMap<ElementKind, ElementTemplate>,
+    /// Cloud icon packs offered in the container properties
+    pub icon_packs: Vec<IconPack>,
+    /// Pre-filled elements offered under *Templates* in the sidebar
+    pub catalog: Vec<CatalogEntry>,
+}
+
+/// Name, text and size given to a new element of one kind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElementTemplate {
+    pub name: String,
+    pub description: String,
+    /// Only used for containers
+    #[serde(default)]
+    pub technology: String,
+    pub size: Size,
+}
+
+impl ElementTemplate {
+    /// The template used when the preferences do not override it
+    pub fn builtin(kind: ElementKind) -> Self {
+        let (name, technology) = match kind {
+            ElementKind::Person => ("New Person", ""),
+            ElementKind::ExternalPerson => ("External User", ""),
+            ElementKind::System => ("New System", ""),
+            ElementKind::ExternalSystem => ("External System", ""),
+            ElementKind::Container => ("Web Application", "React/Spring Boot"),
+            ElementKind::Database => ("Database", "PostgreSQL"),
+            ElementKind::Queue => ("Message Queue", "RabbitMQ"),
+            ElementKind::Component => ("New Component", "Spring Bean"),
+            ElementKind::Note => ("Note", ""),
+        };
+        let mut template = Self {
+            name: name.to_string(),
+            description: "Description".to_string(),
+            technology: technology.to_string(),
+            size: Size::new(0.0, 0.0),
+        };
+        template.size = template.element_type(kind).default_size();
+        template
+    }
+
+    /// Element type of this kind with the template's text
+    pub fn element_type(&self, kind: ElementKind) -> ElementType {
+        let (name, description) = (self.name.as_str(), self.description.as_str());
+        let container = |container_type| ElementType::container(name, description, container_type, self.technology.as_str());
+        match kind {
+            ElementKind::Person => ElementType::person(name, description),
+            ElementKind::ExternalPerson => ElementType::external_person(name, description),
+            ElementKind::System => ElementType::system(name, description),
+            ElementKind::ExternalSystem => ElementType::external_system(name, description),
+            ElementKind::Container => container(ContainerType::WebApplication),
+            ElementKind::Database => container(ContainerType::Database),
+            ElementKind::Queue => container(ContainerType::Queue),
+            ElementKind::Component => ElementType::component(name, description, self.technology.as_str()),
+            ElementKind::Note => ElementType::note(name),
+        }
+    }
+
+    /// A new element of this kind at `position`
+    pub fn element(&self, kind: ElementKind, position: Position) -> Element {
+        let mut element = Element::new(self.element_type(kind), position);
+        element.size = self.size;
+        element
+    }
+}
+
+/// A named, pre-filled element in the template library
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    /// Shown on the sidebar button
+    pub label: String,
+    pub kind: ElementKind,
+    pub template: ElementTemplate,
+}
+
+impl CatalogEntry {
+    pub fn new(label: &str, kind: ElementKind, name: &str, description: &str, technology: &str) -> Self {
+        let mut template = ElementTemplate {
+            name: name.to_string(),
+            description: description.to_string(),
+            technology: technology.to_string(),
+            size: Size::new(0.0, 0.0),
+        };
+        template.size = template.element_type(kind).default_size();
+        Self {
+            label: label.to_string(),
+            kind,
+            template,
+        }
+    }
+
+    /// An entry recreating the kind, text and size of `element`, labelled with its name
+    pub fn from_element(element: &Element) -> Self {
+        let label = element.name().lines().next().unwrap_or_default().trim();
+        Self {
+            label: if label.is_empty() { "Untitled".to_string() } else { label.to_string() },
+            kind: ElementKind::of(element),
+            template: ElementTemplate {
+                name: element.name().to_string(),
+                description: element.description().to_string(),
+                technology: element.element_type.technology().unwrap_or_default().to_string(),
+                size: element.size,
+            },
+        }
+    }
+
+    /// A new element from this entry at `position`
+    pub fn element(&self, position: Position) -> Element {
+        self.template.element(self.kind, position)
+    }
+}
+
+/// The template library a fresh installation starts with
+pub fn builtin_catalog() -> Vec<CatalogEntry> {
+    vec![
+        CatalogEntry::new("PostgreSQL Database", ElementKind::Database, "Database", "Stores data", "PostgreSQL"),
+        CatalogEntry::new("Redis Cache", ElementKind::Database, "Cache", "Caches frequently read data", "Redis"),
+        CatalogEntry::new("React SPA", ElementKind::Container, "Web App", "Single-page application", "React"),
+        CatalogEntry::new("Kafka Topic", ElementKind::Queue, "Topic", "Carries events", "Apache Kafka"),
+        CatalogEntry::new("RabbitMQ Queue", ElementKind::Queue, "Queue", "Queues messages", "RabbitMQ"),
+    ]
+}
+
+/// Canvas font sizes in points
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Typography {
+    pub name_size: f32,
+    pub description_size: f32,
+    pub label_size: f32,
+}
+
+impl Typography {
+    /// Smallest and largest font size offered in the preferences
+    pub const SIZE_RANGE: std::ops::RangeInclusive<f32> = 6.0..=48.0;
+}
+
+impl Default for Typography {
+    fn default() -> Self {
+        Self {
+            name_size: 13.0,
+            description_size: 10.0,
+            label_size: 10.0,
+        }
+    }
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            java_path: "java".to_string(),
+            plantuml_jar: String::new(),
+            mmdc_path: String::new(),
+            typography: Typography::default(),
+            backup_count: 5,
+            sidecar_plantuml: false,
+            sidecar_mermaid: false,
+            author: default_author(),
+            line_style: LineStyle::default(),
+            stylesheet_path: String::new(),
+            element_templates: BTreeMap::new(),
+            icon_packs: Vec::new(),
+            catalog: builtin_catalog(),
+        }
+    }
+}
+
+impl Preferences {
+    /// Load preferences from the default location, falling back to defaults
+    /// if the file is missing or unreadable
+    pub fn load() -> Self {
+        preferences_path()
+            .and_then(|path| Self::load_from(&path).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save preferences to the default location
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = preferences_path().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "no configuration directory")
+        })?;
+        self.save_to(&path)
+    }
+
+    /// Load preferences from a specific file
+    pub fn load_from(path: &Path) -> std::io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(std::io::Error::other)
+    }
+
+    /// Save preferences to a specific file, creating parent directories
+    pub fn save_to(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    /// Template for new elements of `kind`
+    pub fn element_template(&self, kind: ElementKind) -> ElementTemplate {
+        self.element_templates
+            .get(&kind)
+            .cloned()
+            .unwrap_or_else(|| ElementTemplate::builtin(kind))
+    }
+
+    /// Whether a local PlantUML installation is configured
+    pub fn has_plantuml(&self) -> bool {
+        !self.plantuml_jar.trim().is_empty()
+    }
+
+    /// Whether a local Mermaid CLI is configured
+    pub fn has_mmdc(&self) -> bool {
+        !self.mmdc_path.trim().is_empty()
+    }
+}
+
+/// The login name of the current user, if known
+fn default_author() -> String {
+    ["USER", "USERNAME"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|v| !v.is_empty()))
+        .unwrap_or_default()
+}
+
+/// Directory holding C2Draw's configuration files
+pub fn config_dir() -> Option<PathBuf> {
+    let env = |key: &str| std::env::var_os(key).filter(|v| !v.is_empty()).map(PathBuf::from);
+
+    let base = if cfg!(target_os = "windows") {
+        env("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env("XDG_CONFIG_HOME").or_else(|| env("HOME").map(|home| home.join(".config")))
+    };
+
+    base.map(|dir| dir.join("c2draw"))
+}
+
+/// Full path of the preferences file
+pub fn preferences_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(PREFERENCES_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("c2draw-prefs-{}", uuid::Uuid::new_v4()))
+            .join(name)
+    }
+
+    mod defaults_tests {
+        use super::*;
+
+        /// Verifies the default preferences disable offline rendering
+        #[test]
+        fn default_preferences_disable_offline_rendering() {
+            let prefs = Preferences::default();
+            assert_eq!(prefs.java_path, "java");
+            assert!(!prefs.has_plantuml());
+            assert!(!prefs.has_mmdc());
+            assert_eq!(prefs.backup_count, 5);
+        }
+
+        /// Verifies missing fields are filled from defaults when deserializing
+        #[test]
+        fn missing_fields_use_defaults() {
+            let prefs: Preferences = serde_json::from_str(r#"{"mmdc_path": "/usr/bin/mmdc"}"#).unwrap();
+            assert_eq!(prefs.java_path, "java");
+            assert_eq!(prefs.mmdc_path, "/usr/bin/mmdc");
+            assert!(prefs.has_mmdc());
+        }
+
+        /// Verifies partially specified typography keeps the other defaults
+        #[test]
+        fn partial_typography_uses_defaults() {
+            let prefs: Preferences = serde_json::from_str(r#"{"typography": {"name_size": 20.0}}"#).unwrap();
+            assert_eq!(prefs.typography.name_size, 20.0);
+            assert_eq!(prefs.typography.description_size, Typography::default().description_size);
+        }
+    }
+
+    mod element_template_tests {
+        use super::*;
+
+        /// Verifies the built-in templates match the element type defaults
+        #[test]
+        fn builtin_templates() {
+            let prefs = Preferences::default();
+            let person = prefs.element_template(ElementKind::Person);
+            assert_eq!(person.name, "New Person");
+            assert_eq!(person.size, Size::new(120.0, 80.0));
+
+            let database = prefs.element_template(ElementKind::Database).element(ElementKind::Database, Position::new(0.0, 0.0));
+            match &database.element_type {
+                ElementType::Container(data) => {
+                    assert_eq!(data.container_type, ContainerType::Database);
+                    assert_eq!(data.technology, "PostgreSQL");
+                }
+                other => panic!("expected a container, got {:?}", other),
+            }
+        }
+
+        /// Verifies overridden templates set the text and size of new elements
+        #[test]
+        fn overridden_template_is_used() {
+            let mut prefs = Preferences::default();
+            prefs.element_templates.insert(
+                ElementKind::ExternalSystem,
+                ElementTemplate {
+                    name: "Vendor API".to_string(),
+                    description: String::new(),
+                    technology: String::new(),
+                    size: Size::new(200.0, 60.0),
+                },
+            );
+
+            let element = prefs
+                .element_template(ElementKind::ExternalSystem)
+                .element(ElementKind::ExternalSystem, Position::new(0.0, 0.0));
+            assert_eq!(element.name(), "Vendor API");
+            assert_eq!(element.size, Size::new(200.0, 60.0));
+            assert_eq!(ElementKind::of(&element), ElementKind::ExternalSystem);
+            assert_eq!(prefs.element_template(ElementKind::System), ElementTemplate::builtin(ElementKind::System));
+        }
+
+        /// Verifies overrides survive serialization keyed by kind
+        #[test]
+        fn templates_roundtrip_through_json() {
+            let mut prefs = Preferences::default();
+            prefs.element_templates.insert(ElementKind::Queue, ElementTemplate::builtin(ElementKind::Queue));
+            let json = serde_json::to_string(&prefs).unwrap();
+            assert!(json.contains("\"queue\""));
+            assert_eq!(serde_json::from_str::<Preferences>(&json).unwrap(), prefs);
+        }
+    }
+
+    mod catalog_tests {
+        use super::*;
+
+        /// Verifies catalog entries fill in new elements of their kind
+        #[test]
+        fn entry_creates_element() {
+            let prefs = Preferences::default();
+            let kafka = prefs.catalog.iter().find(|e| e.label == "Kafka Topic").unwrap();
+            let element = kafka.element(Position::new(10.0, 20.0));
+            assert_eq!(ElementKind::of(&element), ElementKind::Queue);
+            assert_eq!(element.element_type.technology(), Some("Apache Kafka"));
+            assert_eq!(element.position, Position::new(10.0, 20.0));
+            assert_eq!(element.size, element.element_type.default_size());
+        }
+
+        /// Verifies an element saved as an entry is recreated as it was
+        #[test]
+        fn entry_from_element() {
+            let mut element = Element::new(
+                ElementType::component("Auth\nFilter", "Checks tokens", "Spring Security"),
+                Position::new(0.0, 0.0),
+            );
+            element.size = Size::new(240.0, 90.0);
+            let entry = CatalogEntry::from_element(&element);
+            assert_eq!(entry.label, "Auth");
+            assert_eq!(entry.kind, ElementKind::Component);
+
+            let copy = entry.element(Position::new(0.0, 0.0));
+            assert_eq!(copy.name(), "Auth\nFilter");
+            assert_eq!(copy.description(), "Checks tokens");
+            assert_eq!(copy.element_type.technology(), Some("Spring Security"));
+            assert_eq!(copy.size, Size::new(240.0, 90.0));
+        }
+
+        /// Verifies a saved library replaces the built-in one, even when emptied
+        #[test]
+        fn saved_catalog_replaces_builtin() {
+            let prefs: Preferences = serde_json::from_str(r#"{"catalog": []}"#).unwrap();
+            assert!(prefs.catalog.is_empty());
+            let prefs: Preferences = serde_json::from_str("{}").unwrap();
+            assert_eq!(prefs.catalog, builtin_catalog());
+        }
+    }
+
+    mod persistence_tests {
+        use super::*;
+
+        /// Verifies preferences survive a save/load roundtrip
+        #[test]
+        fn save_and_load_roundtrip() {
+            let path = temp_path(PREFERENCES_FILE_NAME);
+            let prefs = Preferences {
+                plantuml_jar: "/opt/plantuml.jar".to_string(),
+                ..Default::default()
+            };
+
+            prefs.save_to(&path).expect("Failed to save");
+            let loaded = Preferences::load_from(&path).expect("Failed to load");
+            assert_eq!(loaded, prefs);
+
+            let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        }
+
+        /// Verifies loading a missing file reports an error
+        #[test]
+        fn load_from_missing_file_fails() {
+            assert!(Preferences::load_from(&temp_path("missing.json")).is_err());
+        }
+    }
+}
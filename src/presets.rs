@@ -0,0 +1,223 @@
+//! Technology-stack presets
+//!
+//! A preset is a small, pre-connected set of containers for a common
+//! architecture, such as a single-page app with a REST API and a database.
+//! Presets are turned into a [`Snippet`] and inserted like one, so every
+//! insertion gets fresh IDs.
+
+use crate::model::{ContainerType, Element, ElementType, Position, Relationship, Snippet, FILE_FORMAT_VERSION};
+
+/// Horizontal and vertical distance between preset grid cells
+const CELL: (f32, f32) = (220.0, 160.0);
+
+/// One container of a preset, placed on a grid cell
+pub struct PresetContainer {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub container_type: ContainerType,
+    pub technology: &'static str,
+    /// Grid column and row
+    pub cell: (u8, u8),
+}
+
+/// A relationship between two containers of a preset, by index
+pub struct PresetRelationship {
+    pub source: usize,
+    pub target: usize,
+    pub description: &'static str,
+    pub technology: &'static str,
+}
+
+/// A named bundle of connected containers
+pub struct StackPreset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub containers: &'static [PresetContainer],
+    pub relationships: &'static [PresetRelationship],
+}
+
+const fn container(
+    name: &'static str,
+    description: &'static str,
+    container_type: ContainerType,
+    technology: &'static str,
+    cell: (u8, u8),
+) -> PresetContainer {
+    PresetContainer {
+        name,
+        description,
+        container_type,
+        technology,
+        cell,
+    }
+}
+
+const fn rel(source: usize, target: usize, description: &'static str, technology: &'static str) -> PresetRelationship {
+    PresetRelationship {
+        source,
+        target,
+        description,
+        technology,
+    }
+}
+
+/// The presets offered in the sidebar
+pub const PRESETS: &[StackPreset] = &[
+    StackPreset {
+        name: "SPA + REST API + Postgres + Redis",
+        description: "Browser app backed by an API with a relational database and a cache",
+        containers: &[
+            container("Single-Page App", "User interface in the browser", ContainerType::WebApplication, "React", (0, 0)),
+            container("API", "Business logic over JSON/HTTPS", ContainerType::Microservice, "Node.js", (1, 0)),
+            container("Database", "Stores application data", ContainerType::Database, "PostgreSQL", (1, 1)),
+            container("Cache", "Sessions and hot data", ContainerType::Database, "Redis", (2, 0)),
+        ],
+        relationships: &[
+            rel(0, 1, "Calls", "JSON/HTTPS"),
+            rel(1, 2, "Reads from and writes to", "SQL"),
+            rel(1, 3, "Caches data in", "RESP"),
+        ],
+    },
+    StackPreset {
+        name: "Server-rendered Web App + MySQL",
+        description: "Classic web application rendering HTML on the server",
+        containers: &[
+            container("Web Application", "Renders pages and handles forms", ContainerType::WebApplication, "Django", (0, 0)),
+            container("Background Worker", "Runs scheduled and slow jobs", ContainerType::Microservice, "Celery", (1, 0)),
+            container("Database", "Stores application data", ContainerType::Database, "MySQL", (0, 1)),
+            container("Job Queue", "Jobs waiting for the worker", ContainerType::Queue, "RabbitMQ", (1, 1)),
+        ],
+        relationships: &[
+            rel(0, 2, "Reads from and writes to", "SQL"),
+            rel(0, 3, "Enqueues jobs on", "AMQP"),
+            rel(1, 3, "Takes jobs from", "AMQP"),
+            rel(1, 2, "Updates", "SQL"),
+        ],
+    },
+    StackPreset {
+        name: "Event-driven Microservices + Kafka",
+        description: "Gateway in front of services that communicate through events",
+        containers: &[
+            container("API Gateway", "Routes and authenticates requests", ContainerType::Microservice, "Kong", (1, 0)),
+            container("Order Service", "Places and tracks orders", ContainerType::Microservice, "Java, Spring Boot", (0, 1)),
+            container("Inventory Service", "Keeps stock levels", ContainerType::Microservice, "Go", (2, 1)),
+            container("Event Bus", "Domain events", ContainerType::Queue, "Apache Kafka", (1, 2)),
+            container("Order Database", "Orders", ContainerType::Database, "PostgreSQL", (0, 2)),
+            container("Inventory Database", "Stock levels", ContainerType::Database, "MongoDB", (2, 2)),
+        ],
+        relationships: &[
+            rel(0, 1, "Routes requests to", "JSON/HTTPS"),
+            rel(0, 2, "Routes requests to", "JSON/HTTPS"),
+            rel(1, 3, "Publishes order events to", "Kafka"),
+            rel(2, 3, "Subscribes to order events from", "Kafka"),
+            rel(1, 4, "Reads from and writes to", "SQL"),
+            rel(2, 5, "Reads from and writes to", "MongoDB Wire Protocol"),
+        ],
+    },
+    StackPreset {
+        name: "Mobile App + Backend API",
+        description: "Native mobile client with its own backend and push notifications",
+        containers: &[
+            container("Mobile App", "iOS and Android client", ContainerType::MobileApp, "Flutter", (0, 0)),
+            container("Backend API", "Accounts and app data", ContainerType::Microservice, "Kotlin, Ktor", (1, 0)),
+            container("Database", "Stores accounts and app data", ContainerType::Database, "PostgreSQL", (1, 1)),
+            container("Push Notifications", "Delivers notifications to devices", ContainerType::Other(String::new()), "Firebase Cloud Messaging", (2, 0)),
+        ],
+        relationships: &[
+            rel(0, 1, "Calls", "JSON/HTTPS"),
+            rel(1, 2, "Reads from and writes to", "SQL"),
+            rel(1, 3, "Sends notifications via", "HTTPS"),
+            rel(3, 0, "Pushes notifications to", ""),
+        ],
+    },
+];
+
+impl StackPreset {
+    /// The preset as a snippet laid out on its grid, top-left at the origin
+    pub fn snippet(&self) -> Snippet {
+        let elements: Vec<Element> = self
+            .containers
+            .iter()
+            .map(|c| {
+                let container_type = match &c.container_type {
+                    ContainerType::Other(name) if name.is_empty() => ContainerType::Other(c.name.to_string()),
+                    other => other.clone(),
+                };
+                let position = Position::new(c.cell.0 as f32 * CELL.0, c.cell.1 as f32 * CELL.1);
+                Element::new(
+                    ElementType::container(c.name, c.description, container_type, c.technology),
+                    position,
+                )
+            })
+            .collect();
+        let relationships = self
+            .relationships
+            .iter()
+            .map(|r| {
+                let (source, target) = (elements[r.source].id, elements[r.target].id);
+                if r.technology.is_empty() {
+                    Relationship::new(source, target, r.description)
+                } else {
+                    Relationship::with_technology(source, target, r.description, r.technology)
+                }
+            })
+            .collect();
+        Snippet {
+            version: FILE_FORMAT_VERSION.to_string(),
+            elements,
+            relationships,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Diagram, DiagramType};
+
+    mod preset_tests {
+        use super::*;
+
+        /// Verifies every preset connects only its own containers
+        #[test]
+        fn presets_are_well_formed() {
+            for preset in PRESETS {
+                assert!(!preset.containers.is_empty(), "{}", preset.name);
+                for rel in preset.relationships {
+                    assert!(rel.source < preset.containers.len(), "{}", preset.name);
+                    assert!(rel.target < preset.containers.len(), "{}", preset.name);
+                    assert_ne!(rel.source, rel.target, "{}", preset.name);
+                }
+            }
+        }
+
+        /// Verifies inserting a preset adds its connected containers
+        #[test]
+        fn insert_preset_adds_connected_containers() {
+            let preset = &PRESETS[0];
+            let mut diagram = Diagram::new("Test", "", DiagramType::Container);
+            let inserted = diagram.insert_snippet(&preset.snippet(), Position::new(100.0, 50.0));
+
+            assert_eq!(inserted.len(), 4);
+            assert_eq!(diagram.relationships.len(), 3);
+            let api = diagram.get_element(inserted[1]).unwrap();
+            assert_eq!(api.position, Position::new(320.0, 50.0));
+            match &api.element_type {
+                ElementType::Container(data) => assert_eq!(data.technology, "Node.js"),
+                other => panic!("expected a container, got {:?}", other),
+            }
+        }
+
+        /// Verifies an unnamed custom container type takes the container's name
+        #[test]
+        fn custom_type_defaults_to_name() {
+            let snippet = PRESETS[3].snippet();
+            match &snippet.elements[3].element_type {
+                ElementType::Container(data) => {
+                    assert_eq!(data.container_type, ContainerType::Other("Push Notifications".to_string()))
+                }
+                other => panic!("expected a container, got {:?}", other),
+            }
+        }
+    }
+}
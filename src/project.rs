@@ -0,0 +1,207 @@
+//! Project folders
+//!
+//! A project is a directory of diagram files. Elements link to diagrams in
+//! other files through their `url`, given as a path relative to the file the
+//! element is in (e.g. `containers/banking.c4d`), which lets a context
+//! diagram drill down into container diagrams stored separately.
+
+use crate::model::{Element, COMPRESSED_FILE_EXTENSION, FILE_EXTENSION};
+use std::path::{Component, Path, PathBuf};
+
+/// A directory opened as a project
+#[derive(Debug, Clone, PartialEq)]
+pub struct Project {
+    pub root: PathBuf,
+    /// Diagram files below `root`, relative to it and sorted
+    pub files: Vec<PathBuf>,
+}
+
+impl Project {
+    /// Scan `root` and its subdirectories for diagram files
+    ///
+    /// Hidden files and directories (starting with `.`) are skipped.
+    pub fn open(root: impl Into<PathBuf>) -> crate::Result<Self> {
+        let mut project = Self {
+            root: root.into(),
+            files: Vec::new(),
+        };
+        project.refresh()?;
+        Ok(project)
+    }
+
+    /// Re-scan the project directory
+    pub fn refresh(&mut self) -> crate::Result<()> {
+        let mut files = Vec::new();
+        collect_diagram_files(&self.root, Path::new(""), &mut files)?;
+        files.sort();
+        self.files = files;
+        Ok(())
+    }
+
+    /// Name shown for the project
+    pub fn name(&self) -> String {
+        self.root
+            .file_name()
+            .map_or_else(|| self.root.display().to_string(), |n| n.to_string_lossy().into_owned())
+    }
+
+    pub fn absolute(&self, relative: &Path) -> PathBuf {
+        self.root.join(relative)
+    }
+
+    /// Path of `path` relative to the project root, if it is inside it
+    pub fn relative(&self, path: &Path) -> Option<PathBuf> {
+        path.strip_prefix(&self.root).ok().map(Path::to_path_buf)
+    }
+}
+
+/// Whether a path names a diagram file (`.c4d` or `.c4dz`)
+pub fn is_diagram_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(FILE_EXTENSION) || ext.eq_ignore_ascii_case(COMPRESSED_FILE_EXTENSION))
+}
+
+/// The diagram file an element drills down into, resolved against the file it is in
+pub fn drill_down_path(element: &Element, from_file: &Path) -> Option<PathBuf> {
+    let url = element.url.as_deref()?.trim();
+    if url.contains("://") || !is_diagram_file(Path::new(url)) {
+        return None;
+    }
+    let base = from_file.parent().unwrap_or(Path::new(""));
+    Some(normalize(&base.join(url)))
+}
+
+/// Link from one diagram file to another, as stored in an element's `url`
+pub fn relative_link(from_file: &Path, to_file: &Path) -> String {
+    let from_dir: Vec<Component> = from_file.parent().unwrap_or(Path::new("")).components().collect();
+    let to: Vec<Component> = to_file.components().collect();
+    let common = from_dir.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from_dir.len() - common];
+    parts.extend(to[common..].iter().map(|c| c.as_os_str().to_string_lossy().into_owned()));
+    parts.join("/")
+}
+
+/// Resolve `.` and `..` components without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+fn collect_diagram_files(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> crate::Result<()> {
+    let dir = root.join(relative);
+    for entry in std::fs::read_dir(&dir).map_err(|e| crate::C2DrawError::file(&dir, e))? {
+        let entry = entry.map_err(|e| crate::C2DrawError::file(&dir, e))?;
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = relative.join(&name);
+        let file_type = entry.file_type().map_err(|e| crate::C2DrawError::file(entry.path(), e))?;
+        if file_type.is_dir() {
+            collect_diagram_files(root, &path, files)?;
+        } else if is_diagram_file(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ElementType, Position};
+
+    fn linked_element(url: &str) -> Element {
+        let mut element = Element::new(ElementType::system("Banking", ""), Position::new(0.0, 0.0));
+        element.url = Some(url.to_string());
+        element
+    }
+
+    mod project_tests {
+        use super::*;
+
+        /// Verifies diagram files are found recursively, skipping hidden and other files
+        #[test]
+        fn open_finds_diagram_files() {
+            let root = std::env::temp_dir().join(format!("c2draw-project-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(root.join("containers")).unwrap();
+            std::fs::create_dir_all(root.join(".git")).unwrap();
+            for file in ["context.c4d", "containers/banking.c4dz", "notes.md", ".git/x.c4d"] {
+                std::fs::write(root.join(file), "").unwrap();
+            }
+
+            let project = Project::open(&root).unwrap();
+            let _ = std::fs::remove_dir_all(&root);
+
+            assert_eq!(
+                project.files,
+                vec![PathBuf::from("containers/banking.c4dz"), PathBuf::from("context.c4d")]
+            );
+        }
+
+        /// Verifies a missing directory is reported
+        #[test]
+        fn open_missing_directory_fails() {
+            let root = std::env::temp_dir().join(format!("c2draw-missing-{}", uuid::Uuid::new_v4()));
+            assert!(Project::open(root).is_err());
+        }
+    }
+
+    mod link_tests {
+        use super::*;
+
+        /// Verifies links resolve relative to the linking file
+        #[test]
+        fn drill_down_path_resolves_relative_links() {
+            let from = Path::new("/work/arch/context.c4d");
+            assert_eq!(
+                drill_down_path(&linked_element("containers/banking.c4d"), from),
+                Some(PathBuf::from("/work/arch/containers/banking.c4d"))
+            );
+            assert_eq!(
+                drill_down_path(&linked_element("../shared/./auth.c4dz"), from),
+                Some(PathBuf::from("/work/shared/auth.c4dz"))
+            );
+        }
+
+        /// Verifies web links and non-diagram URLs are not drill-downs
+        #[test]
+        fn drill_down_path_ignores_other_urls() {
+            let from = Path::new("/work/context.c4d");
+            assert_eq!(drill_down_path(&linked_element("https://wiki/banking.c4d"), from), None);
+            assert_eq!(drill_down_path(&linked_element("README.md"), from), None);
+            let plain = Element::new(ElementType::system("S", ""), Position::new(0.0, 0.0));
+            assert_eq!(drill_down_path(&plain, from), None);
+        }
+
+        /// Verifies relative links walk up and down the tree
+        #[test]
+        fn relative_link_between_files() {
+            assert_eq!(
+                relative_link(Path::new("context.c4d"), Path::new("containers/banking.c4d")),
+                "containers/banking.c4d"
+            );
+            assert_eq!(
+                relative_link(Path::new("containers/banking.c4d"), Path::new("context.c4d")),
+                "../context.c4d"
+            );
+            assert_eq!(
+                relative_link(Path::new("a/x.c4d"), Path::new("b/y.c4d")),
+                "../b/y.c4d"
+            );
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! One-line relationship commands
+//!
+//! ```text
+//! User -> Web App : logs in [HTTPS]
+//! ```
+//!
+//! Source and target are element names (matched case-insensitively); the
+//! description after `:` and the technology in brackets are optional.
+
+use crate::model::{Diagram, Element, ElementId, ElementType, Relationship};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Description used when the command does not give one
+pub const DEFAULT_DESCRIPTION: &str = "uses";
+
+/// Errors from parsing or applying a quick-add command
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum QuickAddError {
+    #[error("expected 'Source -> Target : description [technology]'")]
+    MissingArrow,
+    #[error("missing {0} name")]
+    MissingName(&'static str),
+    #[error("no element named \"{0}\"")]
+    UnknownElement(String),
+    #[error("more than one element is named \"{0}\"")]
+    AmbiguousElement(String),
+}
+
+/// A parsed quick-add command
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickRelationship {
+    pub source: String,
+    pub target: String,
+    pub description: String,
+    pub technology: Option<String>,
+}
+
+/// Parse `Source -> Target : description [technology]`
+pub fn parse_quick_relationship(input: &str) -> Result<QuickRelationship, QuickAddError> {
+    let (source, rest) = input.split_once("->").ok_or(QuickAddError::MissingArrow)?;
+    let (target, label) = rest.split_once(':').unwrap_or((rest, ""));
+
+    let mut label = label.trim();
+    let mut technology = None;
+    if let Some(open) = label.rfind('[')
+        && label.ends_with(']')
+    {
+        technology = Some(label[open + 1..label.len() - 1].trim().to_string()).filter(|t| !t.is_empty());
+        label = label[..open].trim();
+    }
+
+    let source = source.trim();
+    let target = target.trim();
+    if source.is_empty() {
+        return Err(QuickAddError::MissingName("source"));
+    }
+    if target.is_empty() {
+        return Err(QuickAddError::MissingName("target"));
+    }
+    Ok(QuickRelationship {
+        source: source.to_string(),
+        target: target.to_string(),
+        description: if label.is_empty() { DEFAULT_DESCRIPTION } else { label }.to_string(),
+        technology,
+    })
+}
+
+/// Find the single element with this name, ignoring case
+pub fn find_element_by_name(diagram: &Diagram, name: &str) -> Result<Option<ElementId>, QuickAddError> {
+    let mut matches = diagram
+        .elements
+        .values()
+        .filter(|e| e.name().trim().eq_ignore_ascii_case(name));
+    match (matches.next(), matches.next()) {
+        (Some(_), Some(_)) => Err(QuickAddError::AmbiguousElement(name.to_string())),
+        (found, _) => Ok(found.map(|e| e.id)),
+    }
+}
+
+/// Add the relationship described by `command`, returning its ID
+///
+/// With `create_missing`, unknown names become new software systems;
+/// otherwise they are an error and the diagram is left unchanged.
+pub fn apply_quick_relationship(
+    diagram: &mut Diagram,
+    command: &QuickRelationship,
+    create_missing: bool,
+) -> Result<Uuid, QuickAddError> {
+    let source = find_element_by_name(diagram, &command.source)?;
+    let target = find_element_by_name(diagram, &command.target)?;
+    if !create_missing {
+        if source.is_none() {
+            return Err(QuickAddError::UnknownElement(command.source.clone()));
+        }
+        if target.is_none() {
+            return Err(QuickAddError::UnknownElement(command.target.clone()));
+        }
+    }
+
+    let source_id = source.unwrap_or_else(|| add_system(diagram, &command.source));
+    let target_id = match target {
+        Some(id) => id,
+        // `A -> A` for a missing A creates one element
+        None if command.target.eq_ignore_ascii_case(&command.source) => source_id,
+        None => add_system(diagram, &command.target),
+    };
+
+    let mut relationship = Relationship::new(source_id, target_id, command.description.clone());
+    relationship.technology = command.technology.clone();
+    let id = relationship.id;
+    diagram.add_relationship(relationship);
+    Ok(id)
+}
+
+fn add_system(diagram: &mut Diagram, name: &str) -> ElementId {
+    let position = crate::model::default_element_position(diagram.elements.len());
+    let element = Element::new(ElementType::system(name, ""), position);
+    let id = element.id;
+    diagram.add_element(element);
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, Position};
+
+    fn diagram_with(names: &[&str]) -> Diagram {
+        let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+        for name in names {
+            diagram.add_element(Element::new(ElementType::system(*name, ""), Position::new(0.0, 0.0)));
+        }
+        diagram
+    }
+
+    mod parse_tests {
+        use super::*;
+
+        /// Verifies a full command is split into its parts
+        #[test]
+        fn parse_full_command() {
+            let command = parse_quick_relationship("User -> Web App : logs in [HTTPS]").unwrap();
+            assert_eq!(
+                command,
+                QuickRelationship {
+                    source: "User".to_string(),
+                    target: "Web App".to_string(),
+                    description: "logs in".to_string(),
+                    technology: Some("HTTPS".to_string()),
+                }
+            );
+        }
+
+        /// Verifies description and technology are optional
+        #[test]
+        fn parse_minimal_command() {
+            let command = parse_quick_relationship("User->API").unwrap();
+            assert_eq!(command.target, "API");
+            assert_eq!(command.description, DEFAULT_DESCRIPTION);
+            assert_eq!(command.technology, None);
+
+            let command = parse_quick_relationship("User -> API : [gRPC]").unwrap();
+            assert_eq!(command.description, DEFAULT_DESCRIPTION);
+            assert_eq!(command.technology.as_deref(), Some("gRPC"));
+        }
+
+        /// Verifies malformed commands are rejected
+        #[test]
+        fn parse_rejects_malformed() {
+            assert_eq!(parse_quick_relationship("User API"), Err(QuickAddError::MissingArrow));
+            assert_eq!(parse_quick_relationship(" -> API"), Err(QuickAddError::MissingName("source")));
+            assert_eq!(parse_quick_relationship("User -> : x"), Err(QuickAddError::MissingName("target")));
+        }
+    }
+
+    mod apply_tests {
+        use super::*;
+
+        /// Verifies names resolve case-insensitively to existing elements
+        #[test]
+        fn apply_links_existing_elements() {
+            let mut diagram = diagram_with(&["User", "Web App"]);
+            let command = parse_quick_relationship("user -> WEB APP : logs in [HTTPS]").unwrap();
+            apply_quick_relationship(&mut diagram, &command, false).unwrap();
+
+            assert_eq!(diagram.elements.len(), 2);
+            let rel = &diagram.relationships[0];
+            assert_eq!(diagram.get_element(rel.source_id).unwrap().name(), "User");
+            assert_eq!(diagram.get_element(rel.target_id).unwrap().name(), "Web App");
+            assert_eq!(rel.technology.as_deref(), Some("HTTPS"));
+        }
+
+        /// Verifies unknown names fail without changing the diagram
+        #[test]
+        fn apply_rejects_unknown_names() {
+            let mut diagram = diagram_with(&["User"]);
+            let command = parse_quick_relationship("User -> Billing").unwrap();
+            assert_eq!(
+                apply_quick_relationship(&mut diagram, &command, false),
+                Err(QuickAddError::UnknownElement("Billing".to_string()))
+            );
+            assert_eq!(diagram.elements.len(), 1);
+            assert!(diagram.relationships.is_empty());
+        }
+
+        /// Verifies missing elements are created when requested
+        #[test]
+        fn apply_creates_missing_elements() {
+            let mut diagram = diagram_with(&["User"]);
+            let command = parse_quick_relationship("User -> Billing : pays").unwrap();
+            apply_quick_relationship(&mut diagram, &command, true).unwrap();
+
+            assert_eq!(diagram.elements.len(), 2);
+            let rel = &diagram.relationships[0];
+            assert_eq!(diagram.get_element(rel.target_id).unwrap().name(), "Billing");
+        }
+
+        /// Verifies duplicate names are reported as ambiguous
+        #[test]
+        fn apply_rejects_ambiguous_names() {
+            let mut diagram = diagram_with(&["API", "api"]);
+            let command = parse_quick_relationship("API -> API").unwrap();
+            assert_eq!(
+                apply_quick_relationship(&mut diagram, &command, true),
+                Err(QuickAddError::AmbiguousElement("API".to_string()))
+            );
+        }
+    }
+}
@@ -0,0 +1,460 @@
+//! HTTP API server mode (`c2draw serve`)
+//!
+//! Exposes a small REST API over a diagram file so scripts and external tools
+//! can read and edit it without the GUI. Every successful mutation is written
+//! back to the file immediately.
+//!
+//! | Method | Path                  | Description                          |
+//! |--------|-----------------------|--------------------------------------|
+//! | GET    | `/diagram`            | Whole diagram as JSON                |
+//! | GET    | `/elements`           | All elements                         |
+//! | GET    | `/elements/{id}`      | A single element                     |
+//! | PUT    | `/elements/{id}`      | Update name/description/position     |
+//! | GET    | `/relationships`      | All relationships                    |
+//! | POST   | `/relationships`      | Add a relationship                   |
+//! | GET    | `/export/{format}`    | `plantuml`, `mermaid` or `flowchart` |
+
+use crate::export::{DiagramExporter, MermaidExporter, MermaidFlowchartExporter, PlantUmlExporter};
+use crate::model::{Diagram, Element, ElementId, Position, Relationship};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+
+/// Default address the server binds to
+pub const DEFAULT_BIND_ADDRESS: &str = "127.0.0.1:8080";
+
+/// Largest request body the server accepts
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// Diagram being served and the file it is persisted to
+pub struct ServerState {
+    pub diagram: Diagram,
+    pub path: Option<PathBuf>,
+}
+
+impl ServerState {
+    pub fn new(diagram: Diagram, path: Option<PathBuf>) -> Self {
+        Self { diagram, path }
+    }
+
+    /// Load the diagram to serve from a file
+    pub fn open(path: PathBuf) -> crate::Result<Self> {
+        let diagram = Diagram::load_from_file(&path)?;
+        Ok(Self::new(diagram, Some(path)))
+    }
+
+    /// Write the diagram back to its file if anything changed
+    fn persist(&mut self) -> crate::Result<()> {
+        if self.diagram.take_events().is_empty() {
+            return Ok(());
+        }
+        match &self.path {
+            Some(path) => self.diagram.save_to_file(path),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An HTTP response produced by the API
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl ApiResponse {
+    fn json(status: u16, value: &impl serde::Serialize) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_string_pretty(value).unwrap_or_default(),
+        }
+    }
+
+    fn text(body: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/plain; charset=utf-8",
+            body,
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, &serde_json::json!({ "error": message.into() }))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Partial update for an element; absent fields are left unchanged
+#[derive(Debug, Deserialize)]
+struct ElementUpdate {
+    name: Option<String>,
+    description: Option<String>,
+    position: Option<Position>,
+}
+
+/// Payload for creating a relationship
+#[derive(Debug, Deserialize)]
+struct NewRelationship {
+    source_id: ElementId,
+    target_id: ElementId,
+    #[serde(default)]
+    description: String,
+    technology: Option<String>,
+}
+
+/// Route a request to its handler
+pub fn handle_request(state: &mut ServerState, method: &str, path: &str, body: &str) -> ApiResponse {
+    let path = path.split('?').next().unwrap_or_default().trim_end_matches('/');
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    match (method, segments.as_slice()) {
+        ("GET", ["diagram"]) => ApiResponse::json(200, &state.diagram),
+        ("GET", ["elements"]) => {
+            let elements: Vec<&Element> = state.diagram.elements.values().collect();
+            ApiResponse::json(200, &elements)
+        }
+        ("GET", ["elements", id]) => match find_element(state, id) {
+            Ok(id) => ApiResponse::json(200, &state.diagram.elements[&id]),
+            Err(response) => response,
+        },
+        ("PUT", ["elements", id]) => match find_element(state, id) {
+            Ok(id) => update_element(state, id, body),
+            Err(response) => response,
+        },
+        ("GET", ["relationships"]) => ApiResponse::json(200, &state.diagram.relationships),
+        ("POST", ["relationships"]) => add_relationship(state, body),
+        ("GET", ["export", format]) => export(&state.diagram, format),
+        (_, ["diagram"] | ["elements"] | ["elements", _] | ["relationships"] | ["export", _]) => {
+            ApiResponse::error(405, format!("{} not allowed on /{}", method, segments.join("/")))
+        }
+        _ => ApiResponse::error(404, format!("no such endpoint: {}", path)),
+    }
+}
+
+fn find_element(state: &ServerState, id: &str) -> Result<ElementId, ApiResponse> {
+    let id: ElementId = id
+        .parse()
+        .map_err(|_| ApiResponse::error(400, format!("invalid element id: {}", id)))?;
+    if state.diagram.elements.contains_key(&id) {
+        Ok(id)
+    } else {
+        Err(ApiResponse::error(404, format!("element not found: {}", id)))
+    }
+}
+
+fn update_element(state: &mut ServerState, id: ElementId, body: &str) -> ApiResponse {
+    let update: ElementUpdate = match serde_json::from_str(body) {
+        Ok(update) => update,
+        Err(e) => return ApiResponse::error(400, e.to_string()),
+    };
+
+    if state.diagram.get_element(id).is_none() {
+        return ApiResponse::error(404, format!("element not found: {}", id));
+    }
+    if update.name.is_some() || update.description.is_some() {
+        state.diagram.update_element(id, |element| {
+            if let Some(name) = update.name {
+                element.set_name(name);
+            }
+            if let Some(description) = update.description {
+                element.set_description(description);
+            }
+        });
+    }
+    if let Some(position) = update.position {
+        state.diagram.move_element(id, position);
+    }
+
+    if let Err(e) = state.persist() {
+        return ApiResponse::error(500, e.to_string());
+    }
+    ApiResponse::json(200, &state.diagram.elements[&id])
+}
+
+fn add_relationship(state: &mut ServerState, body: &str) -> ApiResponse {
+    let new: NewRelationship = match serde_json::from_str(body) {
+        Ok(new) => new,
+        Err(e) => return ApiResponse::error(400, e.to_string()),
+    };
+
+    for id in [new.source_id, new.target_id] {
+        if !state.diagram.elements.contains_key(&id) {
+            return ApiResponse::error(400, format!("element not found: {}", id));
+        }
+    }
+
+    let relationship = match new.technology {
+        Some(technology) => {
+            Relationship::with_technology(new.source_id, new.target_id, new.description, technology)
+        }
+        None => Relationship::new(new.source_id, new.target_id, new.description),
+    };
+    let response = ApiResponse::json(201, &relationship);
+    state.diagram.add_relationship(relationship);
+
+    if let Err(e) = state.persist() {
+        return ApiResponse::error(500, e.to_string());
+    }
+    response
+}
+
+fn export(diagram: &Diagram, format: &str) -> ApiResponse {
+    let exporter: Box<dyn DiagramExporter> = match format {
+        "plantuml" => Box::new(PlantUmlExporter::new()),
+        "mermaid" => Box::new(MermaidExporter::new()),
+        "flowchart" => Box::new(MermaidFlowchartExporter::new()),
+        _ => return ApiResponse::error(404, format!("unknown export format: {}", format)),
+    };
+    ApiResponse::text(exporter.export(diagram))
+}
+
+/// Serve the API until the process is terminated
+pub fn serve(state: &mut ServerState, address: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    eprintln!("c2draw: serving on http://{}", listener.local_addr()?);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle_connection(state, stream) {
+                    eprintln!("c2draw: connection error: {}", e);
+                }
+            }
+            Err(e) => eprintln!("c2draw: connection error: {}", e),
+        }
+    }
+    Ok(())
+}
+
+fn handle_connection(state: &mut ServerState, mut stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0usize;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+
+    let response = if content_length > MAX_BODY_SIZE {
+        ApiResponse::error(413, "request body too large")
+    } else {
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        handle_request(state, &method, &path, &String::from_utf8_lossy(&body))
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+/// Entry point for `c2draw serve [--bind ADDRESS] <file.c4d>`
+pub fn run_cli(args: &[String]) -> crate::Result<()> {
+    let mut address = DEFAULT_BIND_ADDRESS.to_string();
+    let mut file = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bind" => {
+                address = args.next().cloned().ok_or_else(|| usage_error("--bind requires an address"))?;
+            }
+            other if file.is_none() => file = Some(PathBuf::from(other)),
+            other => return Err(usage_error(&format!("unexpected argument: {}", other))),
+        }
+    }
+
+    let file = file.ok_or_else(|| usage_error("missing diagram file"))?;
+    let mut state = ServerState::open(file)?;
+    Ok(serve(&mut state, &address)?)
+}
+
+fn usage_error(message: &str) -> crate::C2DrawError {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("{}\nusage: c2draw serve [--bind ADDRESS] <file.c4d>", message),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, ElementType};
+
+    fn test_state() -> (ServerState, ElementId, ElementId) {
+        let mut diagram = Diagram::new("Served", "", DiagramType::SystemContext);
+        let user = Element::new(ElementType::person("User", "A user"), Position::new(0.0, 0.0));
+        let system = Element::new(ElementType::system("System", "A system"), Position::new(200.0, 0.0));
+        let (user_id, system_id) = (user.id, system.id);
+        diagram.add_element(user);
+        diagram.add_element(system);
+        (ServerState::new(diagram, None), user_id, system_id)
+    }
+
+    mod read_endpoint_tests {
+        use super::*;
+
+        /// Verifies GET /elements lists every element
+        #[test]
+        fn get_elements_lists_all() {
+            let (mut state, _, _) = test_state();
+            let response = handle_request(&mut state, "GET", "/elements", "");
+
+            assert_eq!(response.status, 200);
+            let elements: Vec<Element> = serde_json::from_str(&response.body).unwrap();
+            assert_eq!(elements.len(), 2);
+        }
+
+        /// Verifies GET /elements/{id} returns a single element
+        #[test]
+        fn get_element_by_id() {
+            let (mut state, user_id, _) = test_state();
+            let response = handle_request(&mut state, "GET", &format!("/elements/{}", user_id), "");
+
+            assert_eq!(response.status, 200);
+            let element: Element = serde_json::from_str(&response.body).unwrap();
+            assert_eq!(element.name(), "User");
+        }
+
+        /// Verifies unknown and malformed ids are rejected
+        #[test]
+        fn get_element_rejects_bad_ids() {
+            let (mut state, _, _) = test_state();
+            let missing = format!("/elements/{}", ElementId::new_v4());
+
+            assert_eq!(handle_request(&mut state, "GET", &missing, "").status, 404);
+            assert_eq!(handle_request(&mut state, "GET", "/elements/nope", "").status, 400);
+        }
+
+        /// Verifies GET /export/{format} returns the exporter output
+        #[test]
+        fn export_endpoint_returns_text() {
+            let (mut state, _, _) = test_state();
+
+            let plantuml = handle_request(&mut state, "GET", "/export/plantuml", "");
+            assert_eq!(plantuml.status, 200);
+            assert!(plantuml.body.starts_with("@startuml"));
+
+            let flowchart = handle_request(&mut state, "GET", "/export/flowchart", "");
+            assert!(flowchart.body.starts_with("flowchart TD"));
+
+            assert_eq!(handle_request(&mut state, "GET", "/export/visio", "").status, 404);
+        }
+
+        /// Verifies unknown paths and methods are reported
+        #[test]
+        fn unknown_routes_are_rejected() {
+            let (mut state, _, _) = test_state();
+            assert_eq!(handle_request(&mut state, "GET", "/nope", "").status, 404);
+            assert_eq!(handle_request(&mut state, "DELETE", "/elements", "").status, 405);
+        }
+    }
+
+    mod write_endpoint_tests {
+        use super::*;
+
+        /// Verifies PUT /elements/{id} applies a partial update
+        #[test]
+        fn put_element_updates_fields() {
+            let (mut state, user_id, _) = test_state();
+            let body = r#"{"name": "Customer", "position": {"x": 5.0, "y": 6.0}}"#;
+            let response = handle_request(&mut state, "PUT", &format!("/elements/{}", user_id), body);
+
+            assert_eq!(response.status, 200);
+            let element = state.diagram.get_element(user_id).unwrap();
+            assert_eq!(element.name(), "Customer");
+            assert_eq!(element.description(), "A user");
+            assert_eq!(element.position, Position::new(5.0, 6.0));
+        }
+
+        /// Verifies POST /relationships adds a relationship
+        #[test]
+        fn post_relationship_adds_relationship() {
+            let (mut state, user_id, system_id) = test_state();
+            let body = format!(
+                r#"{{"source_id": "{}", "target_id": "{}", "description": "uses", "technology": "HTTPS"}}"#,
+                user_id, system_id
+            );
+            let response = handle_request(&mut state, "POST", "/relationships", &body);
+
+            assert_eq!(response.status, 201);
+            assert_eq!(state.diagram.relationships.len(), 1);
+            assert_eq!(state.diagram.relationships[0].technology.as_deref(), Some("HTTPS"));
+        }
+
+        /// Verifies POST /relationships rejects unknown endpoints and bad JSON
+        #[test]
+        fn post_relationship_validates_input() {
+            let (mut state, user_id, _) = test_state();
+            let body = format!(
+                r#"{{"source_id": "{}", "target_id": "{}"}}"#,
+                user_id,
+                ElementId::new_v4()
+            );
+
+            assert_eq!(handle_request(&mut state, "POST", "/relationships", &body).status, 400);
+            assert_eq!(handle_request(&mut state, "POST", "/relationships", "{").status, 400);
+            assert!(state.diagram.relationships.is_empty());
+        }
+
+        /// Verifies mutations are written back to the diagram file
+        #[test]
+        fn mutations_are_persisted() {
+            let (mut state, user_id, _) = test_state();
+            let path = std::env::temp_dir().join(format!("c2draw-serve-{}.c4d", uuid::Uuid::new_v4()));
+            state.path = Some(path.clone());
+
+            handle_request(&mut state, "PUT", &format!("/elements/{}", user_id), r#"{"name": "Saved"}"#);
+
+            let saved = Diagram::from_json(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            assert_eq!(saved.get_element(user_id).unwrap().name(), "Saved");
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    mod cli_tests {
+        use super::*;
+
+        /// Verifies the CLI requires a diagram file
+        #[test]
+        fn run_cli_requires_file() {
+            let err = run_cli(&[]).unwrap_err();
+            assert!(matches!(err, crate::C2DrawError::Io(e) if e.kind() == std::io::ErrorKind::InvalidInput));
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Share links
+//!
+//! A share link carries a whole diagram in its URL fragment: the diagram is
+//! serialized to JSON, deflate-compressed and base64url-encoded. Nothing is
+//! uploaded anywhere, so links can be pasted into chat or email and opened by
+//! another C2Draw instance (or a future web build) without any server.
+
+use crate::model::Diagram;
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use std::io::{Read, Write};
+use thiserror::Error;
+
+/// Prefix used for generated share links; the payload follows the `#`
+pub const SHARE_LINK_PREFIX: &str = "c2draw://open#";
+
+/// Errors that can occur while creating or opening a share link
+#[derive(Debug, Error)]
+pub enum ShareLinkError {
+    /// Compressing or decompressing the payload failed
+    #[error("compression error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The diagram could not be serialized or the payload is not a diagram
+    #[error("invalid diagram data: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The payload contains characters outside the base64url alphabet
+    #[error("link is not a valid C2Draw share link")]
+    InvalidEncoding,
+}
+
+/// Create a share link for a diagram
+pub fn encode_share_link(diagram: &Diagram) -> Result<String, ShareLinkError> {
+    let json = serde_json::to_vec(diagram)?;
+    let compressed = deflate(&json)?;
+    Ok(format!("{}{}", SHARE_LINK_PREFIX, base64url_encode(&compressed)))
+}
+
+/// Open a diagram from a share link
+///
+/// Accepts a full link with any scheme/host (only the fragment after `#` is
+/// read) or a bare payload.
+pub fn decode_share_link(link: &str) -> Result<Diagram, ShareLinkError> {
+    let link = link.trim();
+    let payload = match link.split_once('#') {
+        Some((_, fragment)) => fragment,
+        None => link,
+    };
+    let compressed = base64url_decode(payload).ok_or(ShareLinkError::InvalidEncoding)?;
+    let json = inflate(&compressed)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// Raw deflate (no zlib header) at maximum compression
+pub(crate) fn deflate(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// Inverse of [`deflate`]
+pub(crate) fn inflate(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut decoder = DeflateDecoder::new(data);
+    let mut out = Vec::new();
+    decoder.read_to_end(&mut out)?;
+    Ok(out)
+}
+
+const BASE64URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Base64url encoding without padding (RFC 4648 §5)
+fn base64url_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        let sextets = chunk.len() + 1;
+        for i in 0..sextets {
+            let index = (n >> (18 - 6 * i)) & 0x3f;
+            out.push(BASE64URL_ALPHABET[index as usize] as char);
+        }
+    }
+    out
+}
+
+/// Decode unpadded (or padded) base64url, returning None on invalid input
+fn base64url_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in text.bytes() {
+        let value = BASE64URL_ALPHABET.iter().position(|&a| a == c)? as u32;
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, Element, ElementType, Position, Relationship};
+
+    mod base64url_tests {
+        use super::*;
+
+        /// Verifies encoding matches the RFC 4648 test vectors (minus padding)
+        #[test]
+        fn base64url_encode_rfc_vectors() {
+            assert_eq!(base64url_encode(b""), "");
+            assert_eq!(base64url_encode(b"f"), "Zg");
+            assert_eq!(base64url_encode(b"fo"), "Zm8");
+            assert_eq!(base64url_encode(b"foo"), "Zm9v");
+            assert_eq!(base64url_encode(b"foobar"), "Zm9vYmFy");
+        }
+
+        /// Verifies the URL-safe alphabet is used for the last two symbols
+        #[test]
+        fn base64url_encode_uses_url_safe_alphabet() {
+            assert_eq!(base64url_encode(&[0xfb, 0xff]), "-_8");
+        }
+
+        /// Verifies decoding reverses encoding for arbitrary bytes
+        #[test]
+        fn base64url_roundtrip() {
+            let data: Vec<u8> = (0..=255).collect();
+            let encoded = base64url_encode(&data);
+            assert_eq!(base64url_decode(&encoded), Some(data));
+        }
+
+        /// Verifies decoding rejects characters outside the alphabet
+        #[test]
+        fn base64url_decode_rejects_invalid_characters() {
+            assert_eq!(base64url_decode("abc+"), None);
+            assert_eq!(base64url_decode("a b"), None);
+        }
+    }
+
+    mod share_link_tests {
+        use super::*;
+
+        fn sample_diagram() -> Diagram {
+            let mut diagram = Diagram::new("Shared", "A shared diagram", DiagramType::SystemContext);
+            let user = Element::new(
+                ElementType::person("User", "A user"),
+                Position::new(10.0, 20.0),
+            );
+            let system = Element::new(
+                ElementType::system("System", "A system"),
+                Position::new(200.0, 20.0),
+            );
+            let (user_id, system_id) = (user.id, system.id);
+            diagram.add_element(user);
+            diagram.add_element(system);
+            diagram.add_relationship(Relationship::new(user_id, system_id, "uses"));
+            diagram
+        }
+
+        /// Verifies a share link starts with the prefix and is URL safe
+        #[test]
+        fn encode_share_link_is_url_safe() {
+            let link = encode_share_link(&sample_diagram()).expect("Failed to encode");
+            assert!(link.starts_with(SHARE_LINK_PREFIX));
+
+            let payload = &link[SHARE_LINK_PREFIX.len()..];
+            assert!(payload.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_'));
+        }
+
+        /// Verifies a diagram survives the encode/decode roundtrip
+        #[test]
+        fn share_link_roundtrip() {
+            let diagram = sample_diagram();
+            let link = encode_share_link(&diagram).expect("Failed to encode");
+            let restored = decode_share_link(&link).expect("Failed to decode");
+
+            assert_eq!(restored.name, diagram.name);
+            assert_eq!(restored.elements.len(), 2);
+            assert_eq!(restored.relationships.len(), 1);
+        }
+
+        /// Verifies decoding accepts links on other hosts and bare payloads
+        #[test]
+        fn decode_share_link_accepts_any_fragment() {
+            let link = encode_share_link(&sample_diagram()).expect("Failed to encode");
+            let payload = &link[SHARE_LINK_PREFIX.len()..];
+
+            let web = format!("https://example.com/c2draw/#{}", payload);
+            assert!(decode_share_link(&web).is_ok());
+            assert!(decode_share_link(payload).is_ok());
+        }
+
+        /// Verifies garbage input produces an error rather than a panic
+        #[test]
+        fn decode_share_link_rejects_garbage() {
+            assert!(matches!(
+                decode_share_link("c2draw://open#not valid!"),
+                Err(ShareLinkError::InvalidEncoding)
+            ));
+            assert!(decode_share_link("c2draw://open#Zm9vYmFy").is_err());
+        }
+    }
+}
@@ -0,0 +1,720 @@
+//! Shareable stylesheets
+//!
+//! A `.c4style` file is JSON describing how elements look, independent of
+//! any diagram: colours per kind of element, element shapes and borders,
+//! canvas fonts, and rules that restyle elements and relationships carrying
+//! a tag.
+//!
+//! ```json
+//! {
+//!   "name": "Acme",
+//!   "elements": {
+//!     "system": { "background": "#1168bd", "text": "#ffffff" },
+//!     "database": { "shape": "rectangle" }
+//!   },
+//!   "fonts": { "name_size": 14.0 },
+//!   "tag_rules": [
+//!     { "tag": "deprecated", "background": "#eeeeee", "line": "#cc0000" }
+//!   ]
+//! }
+//! ```
+//!
+//! Anything left out falls back to the built-in style.
+
+use crate::model::elements::ContainerData;
+use crate::model::{ContainerType, Element, ElementType, Position, Relationship, Size, SystemKind};
+use crate::preferences::Typography;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// File extension of stylesheet files
+pub const STYLESHEET_FILE_EXTENSION: &str = "c4style";
+
+/// An RGB colour, written as `#rrggbb` in stylesheet files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color(pub [u8; 3]);
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self([r, g, b])
+    }
+
+    pub const fn gray(level: u8) -> Self {
+        Self([level; 3])
+    }
+
+    /// Parse `#rrggbb` (the `#` is optional)
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim().trim_start_matches('#');
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Self([channel(0)?, channel(2)?, channel(4)?]))
+    }
+
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0[0], self.0[1], self.0[2])
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_hex(&value).ok_or_else(|| format!("invalid colour \"{}\", expected #rrggbb", value))
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.to_hex()
+    }
+}
+
+/// Outline drawn for an element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Shape {
+    Rectangle,
+    #[default]
+    Rounded,
+    Ellipse,
+    /// Upright cylinder, the usual datastore symbol
+    Cylinder,
+    /// Cylinder lying on its side, the usual message queue symbol
+    Pipe,
+}
+
+impl Shape {
+    /// Outline of the shape filling the box at `position`, as a convex
+    /// polygon whose last point repeats the first
+    pub fn outline(self, position: Position, size: Size) -> Vec<Position> {
+        let (x, y, w, h) = (position.x, position.y, size.width, size.height);
+        let mut outline = match self {
+            Shape::Rectangle | Shape::Rounded => {
+                vec![position, Position::new(x + w, y), Position::new(x + w, y + h), Position::new(x, y + h)]
+            }
+            Shape::Ellipse => {
+                const STEPS: usize = 48;
+                let center = Position::new(x + w * 0.5, y + h * 0.5);
+                (0..STEPS)
+                    .map(|i| {
+                        let angle = std::f32::consts::TAU * i as f32 / STEPS as f32;
+                        Position::new(center.x + w * 0.5 * angle.cos(), center.y + h * 0.5 * angle.sin())
+                    })
+                    .collect()
+            }
+            Shape::Cylinder | Shape::Pipe => {
+                let (near, far) = self.end_caps(position, size);
+                // each cap's outer half, from the near end round to the far one and back
+                half_ellipse(near, self.cap_radius(size), self.cap_start() + std::f32::consts::PI)
+                    .chain(half_ellipse(far, self.cap_radius(size), self.cap_start()))
+                    .collect()
+            }
+        };
+        outline.push(outline[0]);
+        outline
+    }
+
+    /// Visible edge of the near end cap of a cylinder (the top) or pipe (the
+    /// right end); empty for other shapes
+    pub fn rim(self, position: Position, size: Size) -> Vec<Position> {
+        match self {
+            Shape::Cylinder | Shape::Pipe => {
+                let (near, _) = self.end_caps(position, size);
+                half_ellipse(near, self.cap_radius(size), self.cap_start()).collect()
+            }
+            Shape::Rectangle | Shape::Rounded | Shape::Ellipse => Vec::new(),
+        }
+    }
+
+    /// Where a line from the centre of the box at `position` towards `target`
+    /// leaves the outline; None if `target` is the centre
+    pub fn exit_point(self, position: Position, size: Size, target: Position) -> Option<Position> {
+        let from = Position::new(position.x + size.width * 0.5, position.y + size.height * 0.5);
+        let (dx, dy) = (target.x - from.x, target.y - from.y);
+        let cross = |ax: f32, ay: f32, bx: f32, by: f32| ax * by - ay * bx;
+        self.outline(position, size)
+            .windows(2)
+            .filter_map(|segment| {
+                let (ex, ey) = (segment[1].x - segment[0].x, segment[1].y - segment[0].y);
+                let denominator = cross(dx, dy, ex, ey);
+                if denominator.abs() < f32::EPSILON {
+                    return None;
+                }
+                let (ox, oy) = (segment[0].x - from.x, segment[0].y - from.y);
+                let along_ray = cross(ox, oy, ex, ey) / denominator;
+                let along_edge = cross(ox, oy, dx, dy) / denominator;
+                (along_ray >= 0.0 && (0.0..=1.0).contains(&along_edge)).then_some(along_ray)
+            })
+            .max_by(f32::total_cmp)
+            .map(|t| Position::new(from.x + dx * t, from.y + dy * t))
+    }
+
+    /// Radii of a cylinder's or pipe's end caps
+    pub fn cap_radius(self, size: Size) -> Size {
+        match self {
+            Shape::Pipe => Size::new(size.width * 0.08, size.height * 0.5),
+            _ => Size::new(size.width * 0.5, size.height * 0.12),
+        }
+    }
+
+    /// Centres of the near and far end caps
+    fn end_caps(self, position: Position, size: Size) -> (Position, Position) {
+        let radius = self.cap_radius(size);
+        match self {
+            Shape::Pipe => {
+                let y = position.y + size.height * 0.5;
+                (Position::new(position.x + size.width - radius.width, y), Position::new(position.x + radius.width, y))
+            }
+            _ => {
+                let x = position.x + size.width * 0.5;
+                (Position::new(x, position.y + radius.height), Position::new(x, position.y + size.height - radius.height))
+            }
+        }
+    }
+
+    /// Angle at which the inner half of the near cap starts
+    fn cap_start(self) -> f32 {
+        if self == Shape::Pipe { std::f32::consts::FRAC_PI_2 } else { 0.0 }
+    }
+}
+
+/// Points along half an ellipse, starting at angle `from` (clockwise on screen)
+fn half_ellipse(center: Position, radius: Size, from: f32) -> impl Iterator<Item = Position> {
+    const STEPS: usize = 16;
+    (0..=STEPS).map(move |i| {
+        let angle = from + std::f32::consts::PI * i as f32 / STEPS as f32;
+        Position::new(center.x + radius.width * angle.cos(), center.y + radius.height * angle.sin())
+    })
+}
+
+/// Line an element's border is drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BorderStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl BorderStyle {
+    pub const ALL: [BorderStyle; 3] = [BorderStyle::Solid, BorderStyle::Dashed, BorderStyle::Dotted];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BorderStyle::Solid => "Solid",
+            BorderStyle::Dashed => "Dashed",
+            BorderStyle::Dotted => "Dotted",
+        }
+    }
+}
+
+/// Kinds of element a stylesheet can style separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElementKind {
+    Person,
+    ExternalPerson,
+    System,
+    ExternalSystem,
+    Container,
+    Database,
+    Queue,
+    Component,
+    Note,
+}
+
+impl ElementKind {
+    pub const ALL: [ElementKind; 9] = [
+        ElementKind::Person,
+        ElementKind::ExternalPerson,
+        ElementKind::System,
+        ElementKind::ExternalSystem,
+        ElementKind::Container,
+        ElementKind::Database,
+        ElementKind::Queue,
+        ElementKind::Component,
+        ElementKind::Note,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ElementKind::Person => "Person",
+            ElementKind::ExternalPerson => "External Person",
+            ElementKind::System => "System",
+            ElementKind::ExternalSystem => "External System",
+            ElementKind::Container => "Container",
+            ElementKind::Database => "Database",
+            ElementKind::Queue => "Queue",
+            ElementKind::Component => "Component",
+            ElementKind::Note => "Note",
+        }
+    }
+
+    pub fn of(element: &Element) -> Self {
+        Self::of_type(&element.element_type)
+    }
+
+    pub fn of_type(element_type: &ElementType) -> Self {
+        match element_type {
+            ElementType::Person(data) if data.is_external => ElementKind::ExternalPerson,
+            ElementType::Person(_) => ElementKind::Person,
+            ElementType::SoftwareSystem(data) if data.is_external => ElementKind::ExternalSystem,
+            ElementType::SoftwareSystem(_) => ElementKind::System,
+            ElementType::Container(data) => match data.container_type {
+                ContainerType::Database => ElementKind::Database,
+                ContainerType::Queue => ElementKind::Queue,
+                _ => ElementKind::Container,
+            },
+            ElementType::Component(_) => ElementKind::Component,
+            ElementType::Note(_) => ElementKind::Note,
+        }
+    }
+
+    /// `element_type` turned into this kind, keeping its name and description
+    ///
+    /// Technology is kept between containers and components, as is a custom
+    /// container type when converting to the generic container kind and the
+    /// system a container is part of while it stays a container.
+    pub fn convert(self, element_type: &ElementType) -> ElementType {
+        if ElementKind::of_type(element_type) == self {
+            return element_type.clone();
+        }
+        let (name, description, technology, container_type, icon) = match element_type {
+            ElementType::Person(data) => (&data.name, &data.description, "", None, None),
+            ElementType::SoftwareSystem(data) => (&data.name, &data.description, "", None, None),
+            ElementType::Container(data) => (
+                &data.name,
+                &data.description,
+                data.technology.as_str(),
+                Some(&data.container_type),
+                data.icon.as_ref(),
+            ),
+            ElementType::Component(data) => (&data.name, &data.description, data.technology.as_str(), None, None),
+            ElementType::Note(data) => (&data.text, &String::new(), "", None, None),
+        };
+        let (name, description) = (name.as_str(), description.as_str());
+        let system = match element_type {
+            ElementType::Container(data) => data.system,
+            _ => None,
+        };
+        let container = |container_type| {
+            ElementType::Container(ContainerData {
+                name: name.to_string(),
+                description: description.to_string(),
+                container_type,
+                technology: technology.to_string(),
+                icon: icon.cloned(),
+                system,
+            })
+        };
+        match self {
+            ElementKind::Person => ElementType::person(name, description),
+            ElementKind::ExternalPerson => ElementType::external_person(name, description),
+            ElementKind::System | ElementKind::ExternalSystem => {
+                let mut system = if self == ElementKind::System {
+                    ElementType::system(name, description)
+                } else {
+                    ElementType::external_system(name, description)
+                };
+                if let (ElementType::SoftwareSystem(new), ElementType::SoftwareSystem(old)) = (&mut system, element_type) {
+                    new.kind = old.kind;
+                }
+                system
+            }
+            ElementKind::Container => container(match container_type {
+                Some(ContainerType::Database | ContainerType::Queue) | None => ContainerType::WebApplication,
+                Some(other) => other.clone(),
+            }),
+            ElementKind::Database => container(ContainerType::Database),
+            ElementKind::Queue => container(ContainerType::Queue),
+            ElementKind::Component => ElementType::component(name, description, technology),
+            ElementKind::Note => ElementType::note(name),
+        }
+    }
+}
+
+/// Style overrides for an element; unset fields keep the underlying style
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ElementStyle {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shape: Option<Shape>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border_style: Option<BorderStyle>,
+}
+
+impl ElementStyle {
+    /// Whether nothing is overridden
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Style applied to everything carrying `tag`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagRule {
+    pub tag: String,
+    #[serde(flatten)]
+    pub element: ElementStyle,
+    /// Line colour for tagged relationships
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<Color>,
+}
+
+/// Fully resolved look of one element
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResolvedStyle {
+    pub background: Color,
+    pub border: Color,
+    pub text: Color,
+    /// Colour of the description, a lighter shade unless `text` is styled
+    pub description: Color,
+    pub shape: Shape,
+    pub border_style: BorderStyle,
+}
+
+/// A set of visual rules shared across diagrams
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stylesheet {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub elements: BTreeMap<ElementKind, ElementStyle>,
+    /// Default relationship line colour
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<Color>,
+    /// Canvas fonts, replacing the sizes from the preferences
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fonts: Option<Typography>,
+    /// Applied in order, so later rules win
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tag_rules: Vec<TagRule>,
+}
+
+/// The built-in look of each kind of element
+pub fn default_element_style(kind: ElementKind) -> ResolvedStyle {
+    let background = match kind {
+        ElementKind::Person => Color::rgb(255, 220, 180),
+        ElementKind::ExternalPerson => Color::rgb(255, 240, 220),
+        ElementKind::System => Color::rgb(200, 220, 255),
+        ElementKind::ExternalSystem => Color::gray(230),
+        ElementKind::Container => Color::rgb(220, 240, 255),
+        ElementKind::Database => Color::rgb(200, 255, 200),
+        ElementKind::Queue => Color::rgb(255, 255, 200),
+        ElementKind::Component => Color::rgb(240, 248, 255),
+        ElementKind::Note => Color::rgb(255, 245, 157),
+    };
+    ResolvedStyle {
+        background,
+        border: Color::gray(150),
+        text: Color::gray(0),
+        description: Color::gray(80),
+        shape: match kind {
+            ElementKind::Note => Shape::Rectangle,
+            // as C4-PlantUML draws ContainerDb and ContainerQueue
+            ElementKind::Database => Shape::Cylinder,
+            ElementKind::Queue => Shape::Pipe,
+            _ => Shape::Rounded,
+        },
+        border_style: BorderStyle::Solid,
+    }
+}
+
+/// Built-in relationship line colour
+pub const DEFAULT_LINE_COLOR: Color = Color::gray(100);
+
+impl ResolvedStyle {
+    fn apply(&mut self, style: &ElementStyle) {
+        self.background = style.background.unwrap_or(self.background);
+        self.border = style.border.unwrap_or(self.border);
+        if let Some(text) = style.text {
+            self.text = text;
+            self.description = text;
+        }
+        self.shape = style.shape.unwrap_or(self.shape);
+        self.border_style = style.border_style.unwrap_or(self.border_style);
+    }
+}
+
+impl Stylesheet {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn load_from_file(path: &std::path::Path) -> crate::Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| crate::C2DrawError::file(path, e))?;
+        Ok(Self::from_json(&content)?)
+    }
+
+    /// How an element is drawn: built-in style, then its kind, then matching
+    /// tag rules, then the element's own style
+    pub fn element_style(&self, element: &Element) -> ResolvedStyle {
+        let kind = ElementKind::of(element);
+        let mut style = default_element_style(kind);
+        if let ElementType::SoftwareSystem(data) = &element.element_type {
+            match data.kind {
+                SystemKind::Default => {}
+                SystemKind::Database => style.shape = Shape::Cylinder,
+                SystemKind::Queue => style.shape = Shape::Pipe,
+            }
+        }
+        if let Some(kind_style) = self.elements.get(&kind) {
+            style.apply(kind_style);
+        }
+        for rule in self.rules_for(&element.tags) {
+            style.apply(&rule.element);
+        }
+        style.apply(&element.style);
+        style
+    }
+
+    /// Line colour of a relationship
+    pub fn line_color(&self, relationship: &Relationship) -> Color {
+        self.rules_for(&relationship.tags)
+            .filter_map(|rule| rule.line)
+            .last()
+            .or(self.line)
+            .unwrap_or(DEFAULT_LINE_COLOR)
+    }
+
+    fn rules_for<'a>(&'a self, tags: &'a [String]) -> impl Iterator<Item = &'a TagRule> + 'a {
+        self.tag_rules
+            .iter()
+            .filter(|rule| tags.iter().any(|tag| tag.eq_ignore_ascii_case(&rule.tag)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CloudIcon, ElementId, IconPack, Position};
+
+    fn system(tags: &[&str]) -> Element {
+        let mut element = Element::new(ElementType::system("S", ""), Position::new(0.0, 0.0));
+        element.tags = tags.iter().map(|t| t.to_string()).collect();
+        element
+    }
+
+    mod element_kind_tests {
+        use super::*;
+
+        /// Verifies converting keeps the text and a container's technology and icon
+        #[test]
+        fn convert_keeps_text() {
+            let mut database = ElementType::container("Store", "Orders", ContainerType::Database, "PostgreSQL");
+            if let ElementType::Container(data) = &mut database {
+                data.icon = Some(CloudIcon::new(IconPack::Aws, "RDS"));
+            }
+            let queue = ElementKind::Queue.convert(&database);
+            match &queue {
+                ElementType::Container(data) => {
+                    assert_eq!(data.container_type, ContainerType::Queue);
+                    assert_eq!(data.technology, "PostgreSQL");
+                    assert_eq!(data.name, "Store");
+                    assert_eq!(data.icon, Some(CloudIcon::new(IconPack::Aws, "RDS")));
+                }
+                other => panic!("expected a container, got {:?}", other),
+            }
+
+            let person = ElementKind::ExternalPerson.convert(&queue);
+            let element = Element::new(person, Position::new(0.0, 0.0));
+            assert_eq!(ElementKind::of(&element), ElementKind::ExternalPerson);
+            assert_eq!(element.description(), "Orders");
+        }
+
+        /// Verifies a custom container type survives converting to the same kind
+        #[test]
+        fn convert_to_same_kind_is_unchanged() {
+            let custom = ElementType::container("Job", "", ContainerType::Other("Batch".to_string()), "Rust");
+            match ElementKind::Container.convert(&custom) {
+                ElementType::Container(data) => assert_eq!(data.container_type, ContainerType::Other("Batch".to_string())),
+                other => panic!("expected a container, got {:?}", other),
+            }
+        }
+    }
+
+    mod color_tests {
+        use super::*;
+
+        /// Verifies hex colours parse with or without '#'
+        #[test]
+        fn color_from_hex() {
+            assert_eq!(Color::from_hex("#1168bd"), Some(Color::rgb(0x11, 0x68, 0xbd)));
+            assert_eq!(Color::from_hex("FFFFFF"), Some(Color::gray(255)));
+            assert_eq!(Color::from_hex("#fff"), None);
+            assert_eq!(Color::from_hex("#gg0000"), None);
+        }
+
+        /// Verifies invalid colours are reported when loading
+        #[test]
+        fn invalid_color_is_rejected() {
+            let err = Stylesheet::from_json(r##"{"line": "red"}"##).unwrap_err();
+            assert!(err.to_string().contains("invalid colour \"red\""));
+        }
+    }
+
+    mod stylesheet_tests {
+        use super::*;
+
+        /// Verifies an empty stylesheet keeps the built-in look
+        #[test]
+        fn empty_stylesheet_uses_defaults() {
+            let sheet = Stylesheet::from_json("{}").unwrap();
+            assert_eq!(sheet.element_style(&system(&[])), default_element_style(ElementKind::System));
+            let rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "uses");
+            assert_eq!(sheet.line_color(&rel), DEFAULT_LINE_COLOR);
+        }
+
+        /// Verifies kind styles apply and later tag rules win
+        #[test]
+        fn tag_rules_override_kind_styles() {
+            let sheet = Stylesheet::from_json(
+                r##"{
+                    "elements": { "system": { "background": "#1168bd", "shape": "rectangle" } },
+                    "tag_rules": [
+                        { "tag": "legacy", "background": "#eeeeee", "border": "#999999" },
+                        { "tag": "deprecated", "background": "#ffcccc" }
+                    ]
+                }"##,
+            )
+            .unwrap();
+
+            let plain = sheet.element_style(&system(&[]));
+            assert_eq!(plain.background, Color::rgb(0x11, 0x68, 0xbd));
+            assert_eq!(plain.shape, Shape::Rectangle);
+
+            let tagged = sheet.element_style(&system(&["Deprecated", "legacy"]));
+            assert_eq!(tagged.background, Color::rgb(0xff, 0xcc, 0xcc));
+            assert_eq!(tagged.border, Color::gray(0x99));
+            assert_eq!(tagged.shape, Shape::Rectangle);
+        }
+
+        /// Verifies database and queue systems get their own shapes unless styled otherwise
+        #[test]
+        fn system_kind_shapes() {
+            let mut database = system(&["legacy"]);
+            if let ElementType::SoftwareSystem(data) = &mut database.element_type {
+                data.kind = SystemKind::Database;
+            }
+            let sheet = Stylesheet::default();
+            assert_eq!(sheet.element_style(&database).shape, Shape::Cylinder);
+            assert_eq!(sheet.element_style(&database).background, default_element_style(ElementKind::System).background);
+
+            let sheet = Stylesheet::from_json(r#"{ "tag_rules": [{ "tag": "legacy", "shape": "rectangle" }] }"#).unwrap();
+            assert_eq!(sheet.element_style(&database).shape, Shape::Rectangle);
+
+            match ElementKind::ExternalSystem.convert(&database.element_type) {
+                ElementType::SoftwareSystem(data) => assert_eq!(data.kind, SystemKind::Database),
+                other => panic!("expected a system, got {:?}", other),
+            }
+        }
+
+        /// Verifies database containers are cylinders unless the stylesheet says otherwise
+        #[test]
+        fn database_containers_are_cylinders() {
+            let database = Element::new(
+                ElementType::container("Store", "", ContainerType::Database, "PostgreSQL"),
+                Position::new(0.0, 0.0),
+            );
+            assert_eq!(Stylesheet::default().element_style(&database).shape, Shape::Cylinder);
+
+            let sheet = Stylesheet::from_json(r#"{ "elements": { "database": { "shape": "rectangle" } } }"#).unwrap();
+            assert_eq!(sheet.element_style(&database).shape, Shape::Rectangle);
+        }
+
+        /// Verifies queue containers are drawn as pipes
+        #[test]
+        fn queue_containers_are_pipes() {
+            let queue = Element::new(
+                ElementType::container("Orders", "", ContainerType::Queue, "Kafka"),
+                Position::new(0.0, 0.0),
+            );
+            assert_eq!(Stylesheet::default().element_style(&queue).shape, Shape::Pipe);
+        }
+
+        /// Verifies an element's own style wins over tag rules and keeps what it leaves unset
+        #[test]
+        fn element_style_overrides_rules() {
+            let sheet = Stylesheet::from_json(
+                r##"{ "tag_rules": [{ "tag": "legacy", "background": "#eeeeee", "border": "#999999" }] }"##,
+            )
+            .unwrap();
+            let mut element = system(&["legacy"]);
+            element.style = ElementStyle {
+                background: Some(Color::rgb(255, 0, 0)),
+                border_style: Some(BorderStyle::Dashed),
+                ..ElementStyle::default()
+            };
+            let style = sheet.element_style(&element);
+            assert_eq!(style.background, Color::rgb(255, 0, 0));
+            assert_eq!(style.border, Color::gray(0x99));
+            assert_eq!(style.border_style, BorderStyle::Dashed);
+            assert!(!element.style.is_empty());
+            assert!(ElementStyle::default().is_empty());
+        }
+
+        /// Verifies relationship lines take the stylesheet and tag colours
+        #[test]
+        fn line_color_from_tags() {
+            let sheet = Stylesheet::from_json(
+                r##"{ "line": "#333333", "tag_rules": [{ "tag": "async", "line": "#0000ff" }] }"##,
+            )
+            .unwrap();
+            let mut rel = Relationship::new(ElementId::new_v4(), ElementId::new_v4(), "uses");
+            assert_eq!(sheet.line_color(&rel), Color::gray(0x33));
+            rel.tags = vec!["async".to_string()];
+            assert_eq!(sheet.line_color(&rel), Color::rgb(0, 0, 255));
+        }
+
+        /// Verifies fonts fill in missing sizes from the defaults
+        #[test]
+        fn fonts_are_partial() {
+            let sheet = Stylesheet::from_json(r#"{ "fonts": { "name_size": 16.0 } }"#).unwrap();
+            let fonts = sheet.fonts.unwrap();
+            assert_eq!(fonts.name_size, 16.0);
+            assert_eq!(fonts.label_size, Typography::default().label_size);
+        }
+
+        /// Verifies a stylesheet survives a save and load
+        #[test]
+        fn stylesheet_roundtrip() {
+            let mut sheet = Stylesheet {
+                name: "Acme".to_string(),
+                line: Some(Color::gray(10)),
+                ..Stylesheet::default()
+            };
+            sheet.elements.insert(
+                ElementKind::Queue,
+                ElementStyle {
+                    shape: Some(Shape::Ellipse),
+                    ..ElementStyle::default()
+                },
+            );
+            sheet.tag_rules.push(TagRule {
+                tag: "pci".to_string(),
+                element: ElementStyle {
+                    border: Some(Color::rgb(200, 0, 0)),
+                    ..ElementStyle::default()
+                },
+                line: None,
+            });
+            assert_eq!(Stylesheet::from_json(&sheet.to_json().unwrap()).unwrap(), sheet);
+        }
+    }
+}
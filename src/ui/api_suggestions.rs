@@ -0,0 +1,127 @@
+use crate::import::openapi::{self, ApiSpec, Suggestion};
+use crate::model::{Diagram, Element, ElementId, ElementType, Position, Size};
+use egui::{Color32, Ui};
+use std::collections::{HashMap, HashSet};
+
+/// State of the OpenAPI suggestions window: the loaded spec, which container
+/// is the API, which containers consume it and which suggestions to apply
+#[derive(Default)]
+pub struct ApiSuggestions {
+    spec: Option<ApiSpec>,
+    /// Existing container standing for the API; None creates a new one
+    api: Option<ElementId>,
+    consumers: HashSet<ElementId>,
+    /// Suggestions with whether they are ticked; descriptions are editable
+    suggestions: Vec<(Suggestion, bool)>,
+}
+
+impl ApiSuggestions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show suggestions for a newly loaded spec, linking to a container
+    /// named like the API if the diagram has one
+    pub fn load(&mut self, spec: ApiSpec, diagram: &Diagram) {
+        self.api = spec.find_container(diagram);
+        self.consumers.clear();
+        self.suggestions = spec.suggestions().into_iter().map(|s| (s, true)).collect();
+        self.spec = Some(spec);
+    }
+
+    /// The chosen API container, if it still exists
+    fn api(&self, diagram: &Diagram) -> Option<ElementId> {
+        self.api.filter(|&id| diagram.get_element(id).is_some())
+    }
+
+    /// Render the window contents; returns true when "Add Relationships" is clicked
+    pub fn render(&mut self, ui: &mut Ui, diagram: &Diagram) -> bool {
+        let Some(spec) = &self.spec else {
+            ui.colored_label(Color32::from_gray(120), "Load an OpenAPI document (JSON or YAML) to get started.");
+            return false;
+        };
+        ui.strong(format!("{} {}", spec.title, spec.version));
+        ui.label(format!("{} operations", spec.operations.len()));
+        ui.separator();
+
+        let mut containers: Vec<&Element> = diagram
+            .elements
+            .values()
+            .filter(|e| matches!(e.element_type, ElementType::Container(_)))
+            .collect();
+        containers.sort_by(|a, b| a.name().cmp(b.name()));
+
+        let api = self.api(diagram);
+        let new_label = format!("New container \"{}\"", spec.title);
+        ui.horizontal(|ui| {
+            ui.label("API container:");
+            egui::ComboBox::from_id_salt("openapi_container")
+                .selected_text(api.and_then(|id| diagram.get_element(id)).map_or(new_label.as_str(), |e| e.name()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.api, None, new_label.as_str());
+                    for element in &containers {
+                        ui.selectable_value(&mut self.api, Some(element.id), element.name());
+                    }
+                });
+        });
+
+        ui.label("Consumers:");
+        egui::ScrollArea::vertical().id_salt("openapi_consumers").max_height(150.0).show(ui, |ui| {
+            for element in containers.iter().filter(|e| Some(e.id) != api) {
+                let mut consumer = self.consumers.contains(&element.id);
+                if ui.checkbox(&mut consumer, element.name()).changed() {
+                    if consumer {
+                        self.consumers.insert(element.id);
+                    } else {
+                        self.consumers.remove(&element.id);
+                    }
+                }
+            }
+        });
+
+        ui.label("Suggested relationships:");
+        egui::ScrollArea::vertical().id_salt("openapi_suggestions").max_height(200.0).show(ui, |ui| {
+            for (suggestion, ticked) in &mut self.suggestions {
+                ui.horizontal(|ui| {
+                    let tag = if suggestion.tag.is_empty() { "untagged" } else { suggestion.tag.as_str() };
+                    ui.checkbox(ticked, "")
+                        .on_hover_text(format!("{} ({} operations)", tag, suggestion.operations));
+                    ui.add(egui::TextEdit::singleline(&mut suggestion.description).desired_width(320.0));
+                });
+            }
+        });
+
+        let has_pairs = self.consumers.iter().any(|id| Some(*id) != api) && self.suggestions.iter().any(|(_, t)| *t);
+        ui.add_enabled(api.is_none() || has_pairs, egui::Button::new("Add Relationships"))
+            .on_hover_text("Create the API container if needed and connect each consumer to it")
+            .clicked()
+    }
+
+    /// Create the API container if none was chosen, placed by `place` from
+    /// the existing elements and its size, then add the ticked suggestions
+    /// from every consumer; returns the API container
+    pub fn apply(
+        &mut self,
+        diagram: &mut Diagram,
+        place: impl FnOnce(&HashMap<ElementId, Element>, Size) -> Position,
+    ) -> Option<ElementId> {
+        let spec = self.spec.as_ref()?;
+        let api = match self.api(diagram) {
+            Some(id) => id,
+            None => {
+                let mut element = Element::new(spec.container(), Position::new(0.0, 0.0));
+                element.position = place(&diagram.elements, element.size);
+                element.set_name(diagram.unique_name(element.name()));
+                let id = element.id;
+                diagram.add_element(element);
+                id
+            }
+        };
+        self.api = Some(api);
+        let mut consumers: Vec<ElementId> = self.consumers.iter().copied().collect();
+        consumers.sort();
+        let suggestions: Vec<&Suggestion> = self.suggestions.iter().filter(|(_, t)| *t).map(|(s, _)| s).collect();
+        openapi::add_suggested_relationships(diagram, api, &consumers, &suggestions, spec.technology());
+        Some(api)
+    }
+}
@@ -1,14 +1,196 @@
-use crate::model::{Element, ElementId, Position, Relationship, Size};
+use crate::model::elements::ContainerData;
+use crate::model::{
+    bezier_midpoint, draw_order, loop_curve, parallel_offsets, polyline_midpoint, snap_angle, Anchor, Change, Diagram, DiagramDiff, Element, ElementGroup, ElementId, ElementType, LineStyle, View,
+    Position, Relationship, RelationshipGraph, Side, Size, TextLabel,
+};
+use crate::preferences::Typography;
+use crate::style::{BorderStyle, Shape, Stylesheet};
+use crate::ui::text_cache::{GalleyCache, TextSlot};
+use crate::ui::keymap::{self, Command};
 use egui::{Color32, Pos2, Rect, Response, Stroke, StrokeKind, Ui, Vec2};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// Distance between grid lines (and snap positions) at 100% zoom
+pub const GRID_SPACING: f32 = 20.0;
+/// Smallest zoom factor
+pub const MIN_SCALE: f32 = 0.25;
+/// Largest zoom factor
+pub const MAX_SCALE: f32 = 4.0;
+/// Zoom factor applied by one zoom in/out step
+const ZOOM_STEP: f32 = 1.25;
+/// Screen margin kept around the diagram by zoom-to-fit
+const FIT_MARGIN: f32 = 40.0;
+/// Diagram distance the scrollbars allow scrolling past the content
+const SCROLL_MARGIN: f32 = 200.0;
+/// Thickness of the canvas scrollbars in points
+const SCROLLBAR_WIDTH: f32 = 10.0;
+/// Shortest scrollbar thumb, so it stays grabbable on large diagrams
+const MIN_THUMB_LENGTH: f32 = 20.0;
+/// Screen width of the band inside an element's border that starts a connecting drag
+const CONNECT_EDGE: f32 = 8.0;
+/// Gap between a group's members and the frame drawn around them, in diagram units
+const GROUP_PADDING: f32 = 12.0;
+/// Colour of elements and relationships on a dependency cycle
+const CYCLE_COLOR: Color32 = Color32::from_rgb(220, 30, 30);
+
+/// Either end of a relationship
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RelationshipEnd {
+    Source,
+    Target,
+}
+
+/// Where an element lies in the blast radius of the selected element
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Impact {
+    /// Reached by following relationships forward this many steps
+    Downstream(usize),
+    /// Reaches the selected element in this many steps
+    Upstream(usize),
+}
+
+impl Impact {
+    /// Everything downstream of `id`, and upstream too if asked; an element
+    /// in both directions is marked downstream
+    pub fn of(diagram: &Diagram, id: ElementId, upstream: bool) -> HashMap<ElementId, Impact> {
+        let graph = RelationshipGraph::new(diagram);
+        let mut impact: HashMap<ElementId, Impact> = HashMap::new();
+        if upstream {
+            impact.extend(graph.upstream_distances(id).into_iter().map(|(e, d)| (e, Impact::Upstream(d))));
+        }
+        impact.extend(graph.downstream_distances(id).into_iter().map(|(e, d)| (e, Impact::Downstream(d))));
+        impact
+    }
+
+    /// Red to amber downstream and dark to light blue upstream, fading over
+    /// the first four steps
+    pub fn color(&self) -> Color32 {
+        let (distance, near, far) = match *self {
+            Impact::Downstream(d) => (d, [210, 40, 30], [240, 180, 40]),
+            Impact::Upstream(d) => (d, [30, 70, 200], [110, 170, 240]),
+        };
+        let t = (distance.saturating_sub(1) as f32 / 3.0).min(1.0);
+        let mix = |i: usize| (near[i] as f32 + (far[i] as f32 - near[i] as f32) * t).round() as u8;
+        Color32::from_rgb(mix(0), mix(1), mix(2))
+    }
+
+    /// Arrow and step count shown on the element
+    pub fn label(&self) -> String {
+        match self {
+            Impact::Downstream(d) => format!("↓{}", d),
+            Impact::Upstream(d) => format!("↑{}", d),
+        }
+    }
+}
+
+/// Differences from another version of the diagram, highlighted on the canvas
+#[derive(Debug, Clone, Default)]
+pub struct Comparison {
+    pub diff: DiagramDiff,
+    /// Elements only in the other version, outlined where they were
+    pub removed: Vec<Element>,
+}
+
+impl Comparison {
+    /// What changed from `base` to `current`
+    pub fn new(base: &Diagram, current: &Diagram) -> Self {
+        let diff = base.diff(current);
+        let mut removed: Vec<Element> = diff
+            .elements_with(Change::Removed)
+            .into_iter()
+            .filter_map(|id| base.get_element(id).cloned())
+            .collect();
+        removed.sort_by(|a, b| a.name().cmp(b.name()).then(a.id.cmp(&b.id)));
+        Self { diff, removed }
+    }
+
+    /// Green for added, red for removed, amber for changed and blue for moved
+    pub fn color(change: Change) -> Color32 {
+        match change {
+            Change::Added => Color32::from_rgb(30, 160, 60),
+            Change::Removed => Color32::from_rgb(210, 40, 30),
+            Change::Changed => Color32::from_rgb(230, 150, 0),
+            Change::Moved => Color32::from_rgb(60, 120, 220),
+        }
+    }
+}
 
 /// Canvas for drawing and editing diagrams
+///
+/// Element positions are in diagram coordinates; a screen point is
+/// `position * scale + offset`.
 pub struct Canvas {
     pub offset: Vec2,
     pub scale: f32,
+    /// Font sizes for element and relationship text
+    pub typography: Typography,
+    /// Round element positions to the grid when a drag ends
+    pub snap_to_grid: bool,
+    /// Line style for relationships that do not set their own
+    pub line_style: LineStyle,
+    /// Colours, shapes and tag rules for elements and relationships
+    pub stylesheet: Stylesheet,
+    /// Draw review comment markers; right-click picks a point to comment on
+    pub show_comments: bool,
+    /// Leave external persons and systems, and their relationships, off the canvas
+    pub hide_external: bool,
+    /// The diagram's active view; elements it hides are left off the canvas
+    pub view: Option<View>,
+    /// Elements marked by impact analysis; when not empty, the rest are faded
+    pub impact: HashMap<ElementId, Impact>,
+    /// Differences from a compared file, if one is being compared
+    pub comparison: Option<Comparison>,
+    /// Elements on a dependency cycle, outlined in red
+    pub cycle_elements: HashSet<ElementId>,
+    /// Relationships that close a dependency cycle, drawn in red
+    pub cycle_relationships: HashSet<uuid::Uuid>,
+    /// Step number drawn on each relationship of a Dynamic diagram
+    pub steps: HashMap<uuid::Uuid, u32>,
+    /// Groups of elements that are dragged together
+    pub groups: Vec<ElementGroup>,
+    /// Elements selected all at once (such as every orphan), drawn as selected until the next click
+    pub marked: Vec<ElementId>,
+    /// Element and relationship text laid out in earlier frames
+    text_cache: GalleyCache,
+    /// Element responses of the current frame, kept to reuse the allocation
+    element_responses: Vec<(ElementId, Response)>,
+    /// Screen area of the canvas in the last frame
+    viewport: Rect,
+    /// Zoom to fit on the next render (the viewport is only known then)
+    fit_requested: bool,
     dragging: Option<ElementId>,
+    /// Positions of the dragged element and the rest of its group when the drag started
+    drag_origins: Vec<(ElementId, Position)>,
+    /// A finished drag as (element, original position) for each element moved, until taken by the app
+    completed_moves: Vec<(ElementId, Position)>,
+    /// Canvas point right-clicked for a new comment, until taken by the app
+    comment_request: Option<Position>,
+    /// Relationship end being dragged and the anchor it would be pinned to
+    endpoint_drag: Option<(uuid::Uuid, RelationshipEnd, Anchor)>,
+    /// A finished endpoint drag, until taken by the app
+    completed_anchor: Option<(uuid::Uuid, RelationshipEnd, Anchor)>,
+    /// Relationship whose bend is being dragged and the waypoints it would get
+    waypoint_drag: Option<(uuid::Uuid, Vec<Position>)>,
+    /// New waypoints of a relationship after a bend was moved, added or removed, until taken by the app
+    completed_waypoints: Option<(uuid::Uuid, Vec<Position>)>,
+    /// Relationship labels drawn this frame and the area each covers
+    label_areas: Vec<(uuid::Uuid, Rect)>,
+    /// Relationship whose label is being dragged and the offset it would get
+    label_drag: Option<(uuid::Uuid, Position)>,
+    /// New label offset of a relationship after its label was moved or put back, until taken by the app
+    completed_label: Option<(uuid::Uuid, Option<Position>)>,
+    /// Text label being dragged and where it would go
+    text_label_drag: Option<(uuid::Uuid, Position)>,
+    /// A text label's new position after a drag, until taken by the app
+    completed_text_label: Option<(uuid::Uuid, Position)>,
+    /// Element whose link icon was clicked, until taken by the app
+    clicked_link: Option<ElementId>,
+    /// Stacking command chosen from an element's context menu, until taken by the app
+    stacking_request: Option<(ElementId, Command)>,
     /// If Some(source_id), we're in relationship creation mode waiting for target
     pub relationship_source: Option<ElementId>,
+    /// Element a relationship is being dragged out of; it is also the relationship source
+    connect_drag: Option<ElementId>,
 }
 
 impl Default for Canvas {
@@ -16,8 +198,41 @@ impl Default for Canvas {
         Self {
             offset: Vec2::ZERO,
             scale: 1.0,
+            typography: Typography::default(),
+            snap_to_grid: false,
+            line_style: LineStyle::default(),
+            stylesheet: Stylesheet::default(),
+            show_comments: false,
+            hide_external: false,
+            view: None,
+            impact: HashMap::new(),
+            comparison: None,
+            cycle_elements: HashSet::new(),
+            cycle_relationships: HashSet::new(),
+            steps: HashMap::new(),
+            groups: Vec::new(),
+            marked: Vec::new(),
+            text_cache: GalleyCache::new(),
+            element_responses: Vec::new(),
+            viewport: Rect::ZERO,
+            fit_requested: false,
             dragging: None,
+            drag_origins: Vec::new(),
+            completed_moves: Vec::new(),
+            comment_request: None,
+            endpoint_drag: None,
+            completed_anchor: None,
+            waypoint_drag: None,
+            completed_waypoints: None,
+            label_areas: Vec::new(),
+            label_drag: None,
+            completed_label: None,
+            text_label_drag: None,
+            completed_text_label: None,
+            clicked_link: None,
+            stacking_request: None,
             relationship_source: None,
+            connect_drag: None,
         }
     }
 }
@@ -40,10 +255,335 @@ impl Canvas {
     /// Cancel relationship creation mode
     pub fn cancel_relationship(&mut self) {
         self.relationship_source = None;
+        self.connect_drag = None;
+    }
+
+    /// Screen point of a diagram position
+    pub fn to_screen(&self, position: Position) -> Pos2 {
+        Pos2::new(
+            position.x * self.scale + self.offset.x,
+            position.y * self.scale + self.offset.y,
+        )
+    }
+
+    /// Diagram position of a screen point
+    pub fn to_world(&self, pos: Pos2) -> Position {
+        Position::new(
+            (pos.x - self.offset.x) / self.scale,
+            (pos.y - self.offset.y) / self.scale,
+        )
+    }
+
+    /// Multiply the zoom by `factor`, keeping the diagram point under `anchor` in place
+    pub fn zoom_at(&mut self, anchor: Pos2, factor: f32) {
+        let world = self.to_world(anchor);
+        self.scale = (self.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+        self.offset = Vec2::new(anchor.x - world.x * self.scale, anchor.y - world.y * self.scale);
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.zoom_at(self.viewport.center(), ZOOM_STEP);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.zoom_at(self.viewport.center(), 1.0 / ZOOM_STEP);
+    }
+
+    /// Back to 100% with the diagram origin at the top-left
+    pub fn reset_zoom(&mut self) {
+        self.scale = 1.0;
+        self.offset = Vec2::ZERO;
+    }
+
+    /// Zoom so all elements fit the canvas on the next render
+    pub fn request_fit(&mut self) {
+        self.fit_requested = true;
+    }
+
+    /// Whether an element is drawn, given the external filter and the active view
+    pub fn is_shown(&self, element: &Element) -> bool {
+        !(self.hide_external && element.is_external()) && self.view.as_ref().is_none_or(|view| view.shows(element))
+    }
+
+    /// Diagram-coordinate rectangle around all shown elements
+    pub fn content_bounds(&self, elements: &HashMap<ElementId, Element>) -> Option<Rect> {
+        elements
+            .values()
+            .filter(|e| self.is_shown(e))
+            .map(|e| Rect::from_min_size(e.position.to_pos2(), e.size.to_vec2()))
+            .reduce(|a, b| a.union(b))
+    }
+
+    /// Screen rectangle of an element
+    fn screen_rect(&self, element: &Element) -> Rect {
+        Rect::from_min_size(self.to_screen(element.position), element.size.to_vec2() * self.scale)
+    }
+
+    /// The shown element under a screen point
+    pub fn element_at(&self, elements: &HashMap<ElementId, Element>, pos: Pos2) -> Option<ElementId> {
+        draw_order(elements.values())
+            .into_iter()
+            .rev()
+            .find(|e| self.is_shown(e) && self.screen_rect(e).contains(pos))
+            .map(|e| e.id)
+    }
+
+    /// The part of the diagram inside the viewport, in diagram coordinates
+    pub fn visible_rect(&self) -> Rect {
+        Rect::from_min_max(self.to_world(self.viewport.min).to_pos2(), self.to_world(self.viewport.max).to_pos2())
+    }
+
+    /// Where a new element of `size` goes: the middle of the view, stepped clear of elements already there
+    ///
+    /// None until the canvas has been rendered once.
+    pub fn drop_position(&self, elements: &HashMap<ElementId, Element>, size: Size) -> Option<Position> {
+        if self.viewport.width() <= 0.0 {
+            return None;
+        }
+        let center = self.to_world(self.viewport.center());
+        let mut position = Position::new(center.x - size.width * 0.5, center.y - size.height * 0.5);
+        if self.snap_to_grid {
+            position = Self::snap(position);
+        }
+        while elements.values().any(|e| e.position == position) {
+            position = Position::new(position.x + GRID_SPACING, position.y + GRID_SPACING);
+        }
+        Some(position)
+    }
+
+    /// Zoom and scroll so all shown elements fit inside `viewport`
+    pub fn fit_to(&mut self, elements: &HashMap<ElementId, Element>, viewport: Rect) {
+        let Some(bounds) = self.content_bounds(elements) else {
+            self.reset_zoom();
+            return;
+        };
+
+        let available = (viewport.size() - Vec2::splat(2.0 * FIT_MARGIN)).max(Vec2::splat(1.0));
+        self.scale = (available.x / bounds.width().max(1.0))
+            .min(available.y / bounds.height().max(1.0))
+            .clamp(MIN_SCALE, MAX_SCALE);
+        self.offset = viewport.center().to_vec2() - bounds.center().to_vec2() * self.scale;
+    }
+
+    /// The next (or previous) shown element in reading order, wrapping around
+    ///
+    /// Elements are ordered top to bottom, then left to right. Without a
+    /// current element the first (or last) one is returned.
+    pub fn cycle_selection(
+        &self,
+        elements: &HashMap<ElementId, Element>,
+        current: Option<ElementId>,
+        backwards: bool,
+    ) -> Option<ElementId> {
+        let mut order: Vec<&Element> = elements.values().filter(|e| self.is_shown(e)).collect();
+        order.sort_by(|a, b| {
+            (a.position.y, a.position.x)
+                .partial_cmp(&(b.position.y, b.position.x))
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.id.cmp(&b.id))
+        });
+        let len = order.len();
+        if len == 0 {
+            return None;
+        }
+        let index = match current.and_then(|id| order.iter().position(|e| e.id == id)) {
+            Some(index) if backwards => (index + len - 1) % len,
+            Some(index) => (index + 1) % len,
+            None if backwards => len - 1,
+            None => 0,
+        };
+        Some(order[index].id)
+    }
+
+    /// The nearest shown element from `current` in `direction`
+    ///
+    /// Only elements whose centres lie within 45° of the direction count;
+    /// sideways distance weighs double so the closest one in line wins.
+    pub fn neighbor(
+        &self,
+        elements: &HashMap<ElementId, Element>,
+        current: ElementId,
+        direction: Vec2,
+    ) -> Option<ElementId> {
+        let center = |e: &Element| Vec2::new(e.position.x + e.size.width * 0.5, e.position.y + e.size.height * 0.5);
+        let from = center(elements.get(&current)?);
+        let direction = direction.normalized();
+        elements
+            .values()
+            .filter(|e| e.id != current && self.is_shown(e))
+            .filter_map(|e| {
+                let offset = center(e) - from;
+                let ahead = offset.dot(direction);
+                let sideways = (offset - direction * ahead).length();
+                (ahead > 0.0 && sideways <= ahead).then_some((ahead + 2.0 * sideways, e.id))
+            })
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal).then(a.1.cmp(&b.1)))
+            .map(|(_, id)| id)
+    }
+
+    /// Scroll so an element is inside the viewport, if it is not already
+    pub fn reveal(&mut self, element: &Element) {
+        let rect = Rect::from_min_size(self.to_screen(element.position), element.size.to_vec2() * self.scale);
+        if self.viewport.width() > 0.0 && !self.viewport.contains_rect(rect) {
+            self.offset += self.viewport.center() - rect.center();
+        }
+    }
+
+    /// Nearest grid point to a position
+    pub fn snap(position: Position) -> Position {
+        Position::new(
+            (position.x / GRID_SPACING).round() * GRID_SPACING,
+            (position.y / GRID_SPACING).round() * GRID_SPACING,
+        )
+    }
+
+    /// Take the most recently finished drag as (element, original position) for each element it moved
+    pub fn take_completed_moves(&mut self) -> Vec<(ElementId, Position)> {
+        std::mem::take(&mut self.completed_moves)
+    }
+
+    /// The element and the others in its group, which move with it
+    pub fn group_members(&self, id: ElementId) -> Vec<ElementId> {
+        match self.groups.iter().find(|g| g.members.contains(&id)) {
+            Some(group) => group.members.clone(),
+            None => vec![id],
+        }
+    }
+
+    /// Take the most recently dragged relationship end and its new anchor
+    pub fn take_anchor_change(&mut self) -> Option<(uuid::Uuid, RelationshipEnd, Anchor)> {
+        self.completed_anchor.take()
+    }
+
+    /// Take the most recently rerouted relationship and its new waypoints
+    pub fn take_waypoint_change(&mut self) -> Option<(uuid::Uuid, Vec<Position>)> {
+        self.completed_waypoints.take()
+    }
+
+    /// Take the relationship whose label was most recently moved and its new offset
+    pub fn take_label_change(&mut self) -> Option<(uuid::Uuid, Option<Position>)> {
+        self.completed_label.take()
+    }
+
+    /// Take the text label most recently dragged and where it was dropped
+    pub fn take_text_label_move(&mut self) -> Option<(uuid::Uuid, Position)> {
+        self.completed_text_label.take()
+    }
+
+    /// Take the element whose link icon was most recently clicked
+    pub fn take_clicked_link(&mut self) -> Option<ElementId> {
+        self.clicked_link.take()
+    }
+
+    /// Move an element and the rest of its group by `delta`, in diagram units
+    fn move_group(&self, elements: &mut HashMap<ElementId, Element>, id: ElementId, delta: Vec2) {
+        for member in self.group_members(id) {
+            if let Some(element) = elements.get_mut(&member) {
+                element.position = Position::new(element.position.x + delta.x, element.position.y + delta.y);
+            }
+        }
+    }
+
+    /// Take the element and the bring-to-front or send-to-back command chosen from its context menu
+    pub fn take_stacking_request(&mut self) -> Option<(ElementId, Command)> {
+        self.stacking_request.take()
+    }
+
+    /// Take the canvas point most recently right-clicked for a comment
+    pub fn take_comment_request(&mut self) -> Option<Position> {
+        self.comment_request.take()
+    }
+
+    /// Draw a marker for each comment thread, returning the one clicked
+    ///
+    /// Resolved threads are only drawn when `show_resolved` is set.
+    pub fn draw_comment_markers(&self, ui: &mut Ui, diagram: &Diagram, show_resolved: bool) -> Option<uuid::Uuid> {
+        let mut clicked = None;
+        for thread in &diagram.comments {
+            if thread.resolved && !show_resolved {
+                continue;
+            }
+            let Some(position) = thread.marker_position(diagram) else {
+                continue;
+            };
+            let center = self.to_screen(position);
+            if !self.viewport.contains(center) {
+                continue;
+            }
+            let fill = if thread.resolved {
+                Color32::from_gray(170)
+            } else {
+                Color32::from_rgb(255, 190, 0)
+            };
+            let radius = 9.0;
+            ui.painter().circle(center, radius, fill, Stroke::new(1.0, Color32::from_gray(90)));
+            ui.painter().text(
+                center,
+                egui::Align2::CENTER_CENTER,
+                thread.comments.len().to_string(),
+                egui::FontId::proportional(10.0),
+                Color32::BLACK,
+            );
+            let rect = Rect::from_center_size(center, Vec2::splat(radius * 2.0));
+            let response = ui
+                .interact(rect, ui.id().with(("comment_marker", thread.id)), egui::Sense::click())
+                .on_hover_text(thread.comments.first().map_or("", |c| c.text.as_str()));
+            if response.clicked() {
+                clicked = Some(thread.id);
+            }
+        }
+        clicked
+    }
+
+    /// Draw the diagram's text labels over the elements, returning the one clicked
+    ///
+    /// Labels can be dragged; the new position is kept until taken with
+    /// [`Canvas::take_text_label_move`].
+    pub fn draw_text_labels(&mut self, ui: &mut Ui, labels: &[TextLabel], selected: Option<uuid::Uuid>) -> Option<uuid::Uuid> {
+        let mut clicked = None;
+        for label in labels {
+            let position = match self.text_label_drag {
+                Some((id, position)) if id == label.id => position,
+                _ => label.position,
+            };
+            let (text, color) = if label.text.trim().is_empty() {
+                ("(empty label)".to_string(), Color32::from_gray(150))
+            } else {
+                (label.text.clone(), Color32::from_gray(40))
+            };
+            let galley = ui.painter().layout_no_wrap(text, egui::FontId::proportional(label.font_size * self.scale), color);
+            let rect = Rect::from_min_size(self.to_screen(position), galley.size());
+            if !self.viewport.intersects(rect) {
+                continue;
+            }
+            ui.painter().galley(rect.min, galley, color);
+            let response = ui
+                .interact(rect.expand(2.0), ui.id().with(("text_label", label.id)), egui::Sense::click_and_drag())
+                .on_hover_text("Drag to move; click to edit");
+            if response.hovered() || response.dragged() || selected == Some(label.id) {
+                ui.painter().rect_stroke(rect.expand(3.0), 2.0, Stroke::new(1.0, Color32::from_rgb(0, 110, 220)), StrokeKind::Middle);
+            }
+            if response.clicked() {
+                clicked = Some(label.id);
+            } else if response.dragged() {
+                let delta = response.drag_delta() / self.scale;
+                self.text_label_drag = Some((label.id, Position::new(position.x + delta.x, position.y + delta.y)));
+            }
+            if response.drag_stopped()
+                && let Some((id, position)) = self.text_label_drag.take()
+            {
+                let position = if self.snap_to_grid { Self::snap(position) } else { position };
+                self.completed_text_label = Some((id, position));
+            }
+        }
+        clicked
     }
 
     /// Render the canvas with all elements and relationships
-    /// Returns the ID of an element clicked for relationship (if in relationship mode), or None
+    ///
+    /// Returns the target chosen for a new relationship from
+    /// `relationship_source`: an element clicked in relationship mode, or the
+    /// element a drag from another element's edge was dropped on.
     pub fn render(
         &mut self,
         ui: &mut Ui,
@@ -56,6 +596,30 @@ impl Canvas {
 
         let canvas_rect = response.rect;
         let relationship_mode = self.relationship_source.is_some();
+        self.text_cache.begin_frame();
+
+        self.viewport = canvas_rect;
+        if self.fit_requested {
+            self.fit_requested = false;
+            self.fit_to(elements, canvas_rect);
+        }
+
+        // Ctrl+scroll / pinch zooms around the pointer; dragging empty space pans
+        if let Some(hover) = response.hover_pos() {
+            let zoom = ui.input(|i| i.zoom_delta());
+            if zoom != 1.0 {
+                self.zoom_at(hover, zoom);
+            }
+        }
+        if response.dragged() && self.dragging.is_none() {
+            self.offset += response.drag_delta();
+        }
+        if self.show_comments
+            && response.secondary_clicked()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            self.comment_request = Some(self.to_world(pos));
+        }
 
         // Fill canvas background
         painter.rect_filled(canvas_rect, 0.0, Color32::from_gray(245));
@@ -66,84 +630,239 @@ impl Canvas {
         // Clip to canvas area
         let clip_rect = canvas_rect;
 
-        // Draw relationships first (so they appear behind elements)
+        // Draw relationships first (so they appear behind elements), side by side where they share a pair
+        let offsets = parallel_offsets(relationships);
+        self.label_areas.clear();
         for rel in relationships {
-            if let (Some(source), Some(target)) = (elements.get(&rel.source_id), elements.get(&rel.target_id)) {
-                self.draw_relationship(&painter, source, target, rel, clip_rect);
+            if let (Some(source), Some(target)) = (elements.get(&rel.source_id), elements.get(&rel.target_id))
+                && self.is_shown(source)
+                && self.is_shown(target)
+            {
+                let offset = offsets.get(&rel.id).copied().unwrap_or(0.0);
+                self.draw_relationship(&painter, source, target, rel, offset, clip_rect);
             }
         }
 
-        // Draw preview relationship if in relationship mode
+        // Draw preview relationship if in relationship mode; while dragging
+        // out of an element the pointer is held by it, so ask the input directly
+        let pointer = ui.input(|i| i.pointer.hover_pos()).filter(|pos| canvas_rect.contains(*pos));
         if let Some(source_id) = self.relationship_source {
             if let Some(source) = elements.get(&source_id) {
-                if let Some(mouse_pos) = response.hover_pos() {
+                if let Some(mouse_pos) = pointer {
                     self.draw_preview_relationship(&painter, source, mouse_pos);
                 }
             }
         }
 
-        // Draw elements
-        let mut element_responses: Vec<(ElementId, Response)> = Vec::new();
-
-        for element in elements.values_mut() {
+        // Draw elements back to front, so the topmost also takes the pointer;
+        // painting only reads them, the buffer is reused across frames
+        let mut element_responses = std::mem::take(&mut self.element_responses);
+        for element in draw_order(elements.values()) {
+            if !self.is_shown(element) {
+                continue;
+            }
             let element_response = self.draw_element(ui, element, clip_rect, selected_element, relationship_mode);
             element_responses.push((element.id, element_response));
         }
+        self.draw_removed_elements(&painter);
+        self.draw_groups(&painter, elements);
 
-        // Handle interactions
+        // Handle interactions; only the dragged element is changed
         let mut clicked_element_for_relationship: Option<ElementId> = None;
+        let mut moved: Option<(ElementId, Vec2)> = None;
+        let mut dropped: Option<ElementId> = None;
+
+        for (id, response) in element_responses.drain(..) {
+            response.context_menu(|ui| {
+                for (command, label) in [(Command::BringToFront, "Bring to Front"), (Command::SendToBack, "Send to Back")] {
+                    let shortcut = ui.ctx().format_shortcut(&keymap::shortcut(command));
+                    if ui.add(egui::Button::new(label).shortcut_text(shortcut)).clicked() {
+                        self.stacking_request = Some((id, command));
+                        ui.close();
+                    }
+                }
+            });
+            let rect = elements.get(&id).map(|e| self.screen_rect(e));
+            let press = ui.input(|i| i.pointer.press_origin());
+            if response.drag_started()
+                && !relationship_mode
+                && let (Some(rect), Some(press)) = (rect, press)
+                && on_connect_edge(rect, press)
+            {
+                self.connect_drag = Some(id);
+                self.relationship_source = Some(id);
+                continue;
+            }
+            if self.connect_drag == Some(id) {
+                if response.drag_stopped() {
+                    self.connect_drag = None;
+                    match pointer.and_then(|pos| self.element_at(elements, pos)) {
+                        Some(target) if target != id => clicked_element_for_relationship = Some(target),
+                        _ => self.relationship_source = None,
+                    }
+                }
+                continue;
+            }
 
-        for (id, response) in element_responses {
             if response.drag_started() {
                 self.dragging = Some(id);
+                self.drag_origins = self
+                    .group_members(id)
+                    .into_iter()
+                    .filter_map(|member| elements.get(&member).map(|e| (member, e.position)))
+                    .collect();
                 if !relationship_mode {
                     *selected_element = Some(id);
                 }
             }
 
             if response.dragged() {
-                if let Some(element) = elements.get_mut(&id) {
-                    let delta = response.drag_delta();
-                    element.position = Position::new(
-                        element.position.x + delta.x,
-                        element.position.y + delta.y,
-                    );
-                }
+                moved = Some((id, response.drag_delta() / self.scale));
             }
 
             if response.drag_stopped() {
                 self.dragging = None;
+                dropped = Some(id);
+                self.completed_moves = std::mem::take(&mut self.drag_origins);
             }
 
             if response.clicked() {
                 if relationship_mode {
                     // In relationship mode, check if this is a valid target
-                    if let Some(source_id) = self.relationship_source {
-                        if source_id != id {
-                            clicked_element_for_relationship = Some(id);
-                        }
+                    // Clicking the source again makes a relationship to itself
+                    if self.relationship_source.is_some() {
+                        clicked_element_for_relationship = Some(id);
                     }
                 } else {
                     // Normal selection mode
                     *selected_element = Some(id);
+                    self.marked.clear();
                 }
             }
         }
+        self.element_responses = element_responses;
+
+        if let Some((id, delta)) = moved {
+            self.move_group(elements, id, delta);
+        }
+        // The dragged element snaps and the rest of its group keeps its place relative to it
+        if let Some(id) = dropped
+            && self.snap_to_grid
+            && let Some(position) = elements.get(&id).map(|e| e.position)
+        {
+            let snapped = Self::snap(position);
+            self.move_group(elements, id, Vec2::new(snapped.x - position.x, snapped.y - position.y));
+        }
+
+        if !relationship_mode && let Some(selected) = *selected_element {
+            self.drag_endpoints(ui, elements, relationships, selected);
+            self.drag_waypoints(ui, elements, relationships, selected);
+            self.drag_labels(ui, relationships, selected);
+        }
+
+        self.draw_scrollbars(ui, elements);
 
         // Deselect when clicking on empty canvas (only in normal mode)
         if response.clicked() && !response.dragged() && !relationship_mode {
             *selected_element = None;
+            self.marked.clear();
         }
 
         clicked_element_for_relationship
     }
 
+    /// Scrollbars along the bottom and right edges, with a tick per element
+    ///
+    /// The scrollable extent is the diagram's content plus the current view
+    /// and a margin, so the canvas can always be scrolled a little past the
+    /// content and grows as elements are placed further out. A bar is only
+    /// shown while some content lies outside the view along its axis.
+    fn draw_scrollbars(&mut self, ui: &mut Ui, elements: &HashMap<ElementId, Element>) {
+        let Some(content) = self.content_bounds(elements) else {
+            return;
+        };
+        let view = self.visible_rect();
+        let extent = content.union(view).expand(SCROLL_MARGIN);
+        let viewport = self.viewport;
+        let track_color = Color32::from_black_alpha(20);
+        let thumb_color = Color32::from_black_alpha(70);
+        let tick_color = Color32::from_rgb(0, 110, 220);
+
+        for horizontal in [true, false] {
+            let outside = if horizontal {
+                content.min.x < view.min.x || content.max.x > view.max.x
+            } else {
+                content.min.y < view.min.y || content.max.y > view.max.y
+            };
+            if !outside {
+                continue;
+            }
+            let (track, axis) = if horizontal {
+                let track = Rect::from_min_max(
+                    Pos2::new(viewport.min.x, viewport.max.y - SCROLLBAR_WIDTH),
+                    Pos2::new(viewport.max.x - SCROLLBAR_WIDTH, viewport.max.y),
+                );
+                (track, track.x_range())
+            } else {
+                let track = Rect::from_min_max(
+                    Pos2::new(viewport.max.x - SCROLLBAR_WIDTH, viewport.min.y),
+                    Pos2::new(viewport.max.x, viewport.max.y - SCROLLBAR_WIDTH),
+                );
+                (track, track.y_range())
+            };
+            let (extent_range, view_range) = if horizontal {
+                (extent.x_range(), view.x_range())
+            } else {
+                (extent.y_range(), view.y_range())
+            };
+            let thumb_range = scroll_thumb(extent_range, view_range, axis);
+            let thumb = if horizontal {
+                Rect::from_x_y_ranges(thumb_range, track.y_range())
+            } else {
+                Rect::from_x_y_ranges(track.x_range(), thumb_range)
+            };
+
+            ui.painter().rect_filled(track, 0.0, track_color);
+            for element in elements.values().filter(|e| self.is_shown(e)) {
+                let center = if horizontal {
+                    element.position.x + element.size.width * 0.5
+                } else {
+                    element.position.y + element.size.height * 0.5
+                };
+                let at = axis.min + (center - extent_range.min) / extent_range.span() * axis.span();
+                let tick = if horizontal {
+                    Rect::from_center_size(Pos2::new(at, track.center().y), Vec2::new(2.0, SCROLLBAR_WIDTH * 0.5))
+                } else {
+                    Rect::from_center_size(Pos2::new(track.center().x, at), Vec2::new(SCROLLBAR_WIDTH * 0.5, 2.0))
+                };
+                ui.painter().rect_filled(tick, 0.0, tick_color);
+            }
+            ui.painter().rect_filled(thumb.shrink(2.0), 3.0, thumb_color);
+
+            let id = ui.id().with(("canvas_scrollbar", horizontal));
+            let response = ui.interact(track, id, egui::Sense::drag());
+            if response.dragged() {
+                // Moving the thumb by one track pixel moves the view by extent / track
+                let ratio = extent_range.span() / axis.span().max(1.0) * self.scale;
+                let delta = response.drag_delta();
+                if horizontal {
+                    self.offset.x -= delta.x * ratio;
+                } else {
+                    self.offset.y -= delta.y * ratio;
+                }
+            }
+        }
+    }
+
     fn draw_grid(&self, painter: &egui::Painter, rect: Rect) {
-        let grid_spacing = 20.0 * self.scale;
+        let grid_spacing = GRID_SPACING * self.scale;
         let grid_color = Color32::from_gray(220);
+        if grid_spacing < 4.0 {
+            return;
+        }
 
-        // Vertical lines
-        let mut x = rect.min.x + (self.offset.x % grid_spacing);
+        // Vertical lines, aligned with diagram coordinates
+        let mut x = self.offset.x + ((rect.min.x - self.offset.x) / grid_spacing).ceil() * grid_spacing;
         while x < rect.max.x {
             painter.line_segment(
                 [Pos2::new(x, rect.min.y), Pos2::new(x, rect.max.y)],
@@ -153,7 +872,7 @@ impl Canvas {
         }
 
         // Horizontal lines
-        let mut y = rect.min.y + (self.offset.y % grid_spacing);
+        let mut y = self.offset.y + ((rect.min.y - self.offset.y) / grid_spacing).ceil() * grid_spacing;
         while y < rect.max.y {
             painter.line_segment(
                 [Pos2::new(rect.min.x, y), Pos2::new(rect.max.x, y)],
@@ -164,134 +883,627 @@ impl Canvas {
     }
 
     fn draw_element(
-        &self,
+        &mut self,
         ui: &mut Ui,
         element: &Element,
         clip_rect: Rect,
         selected_element: &Option<ElementId>,
         relationship_mode_active: bool,
     ) -> Response {
-        let rect = Rect::from_min_size(
-            element.position.to_pos2(),
-            element.size.to_vec2(),
-        );
+        let scale = self.scale;
+        let rect = self.screen_rect(element);
 
         // Skip if not visible
         if !clip_rect.intersects(rect) {
             return ui.interact(rect, ui.id().with(element.id), egui::Sense::hover());
         }
 
-        let is_selected = selected_element.map_or(false, |id| id == element.id);
+        let is_selected = selected_element.map_or(false, |id| id == element.id) || self.marked.contains(&element.id);
         // Highlight if selected or if it's the relationship source
         let is_relationship_source = self.relationship_source.map_or(false, |id| id == element.id);
-        let highlight = is_selected || is_relationship_source;
-
-        let (bg_color, border_color) = crate::ui::element_colors(element, highlight);
-
-        // Draw shadow
-        let shadow_rect = rect.translate(Vec2::new(3.0, 3.0));
-        ui.painter().rect_filled(shadow_rect, 4.0, Color32::from_black_alpha(30));
-
-        // Draw element background
-        ui.painter().rect_filled(rect, 4.0, bg_color);
-
-        // Draw border (thicker if selected or in relationship mode)
-        let stroke_width = if highlight { 3.0 } else { 2.0 };
-        let final_border_color = if is_relationship_source {
-            Color32::from_rgb(0, 150, 0) // Green highlight for relationship source
+        // A possible target of the relationship being created, under the pointer
+        let is_relationship_target = self.relationship_source.is_some_and(|id| id != element.id)
+            && ui.input(|i| i.pointer.hover_pos()).is_some_and(|pos| rect.contains(pos));
+        let highlight = is_selected || is_relationship_source || is_relationship_target;
+        let impact = self.impact.get(&element.id).copied();
+        let change = self.comparison.as_ref().and_then(|c| c.diff.elements.get(&element.id).copied());
+
+        let style = self.stylesheet.element_style(element);
+        let (bg_color, border_color) = crate::ui::style_colors(&style, highlight);
+        let text_color = crate::ui::color32(style.text);
+
+        // Draw border (thicker if selected, in relationship mode or impacted)
+        let cyclic = self.cycle_elements.contains(&element.id);
+        let stroke_width = if highlight || impact.is_some() || change.is_some() || cyclic { 3.0 } else { 2.0 };
+        let final_border_color = if is_relationship_source || is_relationship_target {
+            Color32::from_rgb(0, 150, 0) // Green highlight for relationship source and target
+        } else if cyclic {
+            CYCLE_COLOR
+        } else if let Some(impact) = impact {
+            impact.color()
+        } else if let Some(change) = change {
+            Comparison::color(change)
         } else {
             border_color
         };
-        ui.painter().rect_stroke(
-            rect,
-            4.0,
-            Stroke::new(stroke_width, final_border_color),
-            StrokeKind::Middle,
-        );
+        let stroke = Stroke::new(stroke_width, final_border_color);
+        // Dashed and dotted borders are drawn over the filled shape afterwards
+        let solid = style.border_style == BorderStyle::Solid;
+        let border = if solid { stroke } else { Stroke::NONE };
+
+        // Draw shadow, background and border
+        let shadow_rect = rect.translate(Vec2::new(3.0, 3.0) * scale);
+        let shadow_color = Color32::from_black_alpha(30);
+        match style.shape {
+            Shape::Rectangle | Shape::Rounded => {
+                let rounding = if style.shape == Shape::Rounded { 4.0 } else { 0.0 };
+                ui.painter().rect_filled(shadow_rect, rounding, shadow_color);
+                ui.painter().rect_filled(rect, rounding, bg_color);
+                ui.painter().rect_stroke(rect, rounding, border, StrokeKind::Middle);
+            }
+            Shape::Ellipse => {
+                let radius = rect.size() * 0.5;
+                ui.painter().add(egui::Shape::ellipse_filled(shadow_rect.center(), radius, shadow_color));
+                ui.painter().add(egui::Shape::ellipse_filled(rect.center(), radius, bg_color));
+                ui.painter().add(egui::Shape::ellipse_stroke(rect.center(), radius, border));
+            }
+            Shape::Cylinder | Shape::Pipe => {
+                let (outline, rim) = cylinder_outline(rect, style.shape == Shape::Pipe);
+                let shadow = outline.iter().map(|p| *p + Vec2::new(3.0, 3.0) * scale).collect();
+                ui.painter().add(egui::Shape::convex_polygon(shadow, shadow_color, Stroke::NONE));
+                ui.painter().add(egui::Shape::convex_polygon(outline, bg_color, border));
+                ui.painter().extend(border_lines(&rim, stroke, style.border_style, scale));
+            }
+        }
+        if !solid {
+            ui.painter().extend(border_lines(&border_outline(rect, style.shape), stroke, style.border_style, scale));
+        }
+
+        // Notes are plain text with a folded corner instead of an icon
+        let is_note = element.is_note();
+        if is_note {
+            let fold = 14.0 * scale;
+            let corner = rect.right_top();
+            ui.painter().add(egui::Shape::convex_polygon(
+                vec![corner - Vec2::new(fold, 0.0), corner + Vec2::new(0.0, fold), corner + Vec2::new(-fold, fold)],
+                final_border_color.gamma_multiply(0.5),
+                Stroke::NONE,
+            ));
+        }
 
         // Draw icon
-        let icon = crate::ui::get_element_icon(element);
-        let icon_pos = rect.min + Vec2::new(8.0, 8.0);
-        ui.painter().text(
-            icon_pos,
-            egui::Align2::LEFT_TOP,
+        let icon = if is_note { "" } else { crate::ui::get_element_icon(element) };
+        let icon_pos = rect.min + Vec2::new(8.0, 8.0) * scale;
+        let icon_galley = self.text_cache.get(
+            ui.painter(),
+            (element.id, TextSlot::Icon),
             icon,
-            egui::FontId::proportional(20.0),
-            Color32::BLACK,
+            egui::FontId::proportional(20.0 * scale),
+            f32::INFINITY,
+            str::to_string,
         );
+        ui.painter().galley(icon_pos, icon_galley, Color32::BLACK);
+
+        // Cloud icon badge in the top-right corner
+        if let ElementType::Container(ContainerData { icon: Some(cloud), .. }) = &element.element_type {
+            let [r, g, b] = cloud.pack.color();
+            let galley = self.text_cache.get(
+                ui.painter(),
+                (element.id, TextSlot::Badge),
+                &cloud.sprite,
+                egui::FontId::proportional(9.0 * scale),
+                f32::INFINITY,
+                |_| format!("{} {}", cloud.pack.badge(), cloud.label()),
+            );
+            let padding = Vec2::new(4.0, 2.0) * scale;
+            let max = rect.right_top() + Vec2::new(-6.0, 6.0) * scale;
+            let min_x = (max.x - galley.size().x - 2.0 * padding.x).max(rect.min.x + 32.0 * scale);
+            let badge = Rect::from_min_max(
+                Pos2::new(min_x, max.y),
+                Pos2::new(max.x, max.y + galley.size().y + 2.0 * padding.y),
+            );
+            ui.painter().rect_filled(badge, 3.0 * scale, Color32::from_rgb(r, g, b));
+            ui.painter()
+                .with_clip_rect(badge.shrink(padding.x.min(badge.width() / 2.0)))
+                .galley(badge.min + padding, galley, Color32::WHITE);
+        }
 
-        // Draw name
-        let name_pos = rect.min + Vec2::new(8.0, 36.0);
-        ui.painter().text(
-            name_pos,
-            egui::Align2::LEFT_TOP,
+        // Draw name (may span several lines, wrapped to the element width)
+        let (name_top, name_size) = if is_note {
+            (8.0, self.typography.description_size)
+        } else {
+            (36.0, self.typography.name_size)
+        };
+        let name_pos = rect.min + Vec2::new(8.0, name_top) * scale;
+        let name_galley = self.text_cache.get(
+            ui.painter(),
+            (element.id, TextSlot::Name),
             element.name(),
-            egui::FontId::proportional(13.0),
-            Color32::BLACK,
+            egui::FontId::proportional(name_size * scale),
+            rect.width() - 16.0 * scale,
+            str::to_string,
         );
-
-        // Draw description (truncated)
-        let desc = truncate_text(element.description(), 25);
-        let desc_pos = rect.min + Vec2::new(8.0, 54.0);
-        ui.painter().text(
-            desc_pos,
-            egui::Align2::LEFT_TOP,
-            desc,
-            egui::FontId::proportional(10.0),
-            Color32::from_gray(80),
+        let name_height = name_galley.size().y;
+        ui.painter().galley(name_pos, name_galley, text_color);
+
+        // Draw description (truncated) below the name
+        let desc_pos = name_pos + Vec2::new(0.0, name_height + 4.0 * scale);
+        let desc_galley = self.text_cache.get(
+            ui.painter(),
+            (element.id, TextSlot::Description),
+            element.description(),
+            egui::FontId::proportional(self.typography.description_size * scale),
+            f32::INFINITY,
+            |desc| truncate_text(desc, 25),
         );
+        ui.painter().galley(desc_pos, desc_galley, crate::ui::color32(style.description));
+
+        // Impact analysis: step count in the bottom-right corner, everything unaffected faded
+        if let Some(impact) = impact {
+            ui.painter().text(
+                rect.right_bottom() - Vec2::new(6.0, 4.0) * scale,
+                egui::Align2::RIGHT_BOTTOM,
+                impact.label(),
+                egui::FontId::proportional(12.0 * scale),
+                impact.color(),
+            );
+        } else if !self.impact.is_empty() && !is_selected {
+            ui.painter().rect_filled(rect.expand(2.0), 0.0, Color32::from_white_alpha(170));
+        } else if let Some(change) = change {
+            ui.painter().text(
+                rect.right_bottom() - Vec2::new(6.0, 4.0) * scale,
+                egui::Align2::RIGHT_BOTTOM,
+                change.as_str(),
+                egui::FontId::proportional(11.0 * scale),
+                Comparison::color(change),
+            );
+        }
+
+        // Interaction; the edge band drags out a new relationship
+        let response = ui.interact(rect, ui.id().with(element.id), egui::Sense::click_and_drag());
+        if !relationship_mode_active
+            && response.hover_pos().is_some_and(|pos| on_connect_edge(rect, pos))
+        {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Crosshair);
+        }
 
-        // Interaction
-        ui.interact(rect, ui.id().with(element.id), egui::Sense::click_and_drag())
+        // Link icon in the bottom-left corner, above the element so it takes the click
+        if let Some(url) = element.url.as_deref().map(str::trim).filter(|url| !url.is_empty()) {
+            let size = 16.0 * scale;
+            let icon_rect = Rect::from_min_size(
+                rect.left_bottom() + Vec2::new(6.0 * scale, -size - 4.0 * scale),
+                Vec2::splat(size),
+            );
+            let link = ui
+                .interact(icon_rect, ui.id().with((element.id, "link")), egui::Sense::click())
+                .on_hover_text(url)
+                .on_hover_cursor(egui::CursorIcon::PointingHand);
+            ui.painter().text(
+                icon_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "🔗",
+                egui::FontId::proportional(12.0 * scale),
+                if link.hovered() { Color32::from_rgb(0, 90, 200) } else { text_color },
+            );
+            if link.clicked() {
+                self.clicked_link = Some(element.id);
+            }
+        }
+        response
+    }
+
+    /// Dashed outlines of the elements only in the compared version
+    /// A dashed frame around each group's members, with its name above
+    fn draw_groups(&self, painter: &egui::Painter, elements: &HashMap<ElementId, Element>) {
+        let color = Color32::from_rgb(120, 90, 200);
+        for group in &self.groups {
+            let Some(bounds) = group
+                .members
+                .iter()
+                .filter_map(|id| elements.get(id))
+                .filter(|e| self.is_shown(e))
+                .map(|e| self.screen_rect(e))
+                .reduce(|a, b| a.union(b))
+            else {
+                continue;
+            };
+            let frame = bounds.expand(GROUP_PADDING * self.scale);
+            let outline = border_outline(frame, Shape::Rectangle);
+            painter.extend(border_lines(&outline, Stroke::new(1.5, color), BorderStyle::Dashed, self.scale));
+            painter.text(
+                frame.left_top() + Vec2::new(0.0, -2.0),
+                egui::Align2::LEFT_BOTTOM,
+                &group.name,
+                egui::FontId::proportional(self.typography.label_size * self.scale),
+                color,
+            );
+        }
+    }
+
+    fn draw_removed_elements(&self, painter: &egui::Painter) {
+        let Some(comparison) = &self.comparison else {
+            return;
+        };
+        let color = Comparison::color(Change::Removed);
+        for element in &comparison.removed {
+            let rect = self.screen_rect(element);
+            let outline = border_outline(rect, Shape::Rectangle);
+            painter.extend(border_lines(&outline, Stroke::new(2.0, color), BorderStyle::Dashed, self.scale));
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                format!("{}\n(removed)", element.name()),
+                egui::FontId::proportional(self.typography.description_size * self.scale),
+                color,
+            );
+        }
+    }
+
+    /// Diagram positions of a relationship's source and target ends
+    ///
+    /// Pinned anchors win over the computed nearest-edge points.
+    /// Where a relationship leaves its source and meets its target
+    ///
+    /// Unpinned ends point at the nearest bend, or else the other element.
+    fn relationship_ends(&self, source: &Element, target: &Element, rel: &Relationship) -> (Position, Position) {
+        if rel.is_self_loop() && rel.waypoints.is_empty() {
+            let [start, _, _, end] = loop_curve(source.position, source.size, rel.source_anchor, rel.target_anchor);
+            return (start, end);
+        }
+        let center = |e: &Element| Pos2::new(e.position.x + e.size.width * 0.5, e.position.y + e.size.height * 0.5);
+        let source_aim = rel.waypoints.first().map_or_else(|| center(target), |p| p.to_pos2());
+        let target_aim = rel.waypoints.last().map_or_else(|| center(source), |p| p.to_pos2());
+        let source_edge = match rel.source_anchor {
+            Some(anchor) => anchor.point(source.position, source.size),
+            None => Position::from_pos2(self.element_edge_point(source, source_aim)),
+        };
+        let target_edge = match rel.target_anchor {
+            Some(anchor) => anchor.point(target.position, target.size),
+            None => Position::from_pos2(self.element_edge_point(target, target_aim)),
+        };
+        (source_edge, target_edge)
+    }
+
+    /// Handles on the ends of the selected element's relationships
+    ///
+    /// Dragging a handle pins that end to a point on the element's border.
+    /// The line from the other end snaps to multiples of 45 degrees unless
+    /// Shift is held.
+    fn drag_endpoints(
+        &mut self,
+        ui: &mut Ui,
+        elements: &HashMap<ElementId, Element>,
+        relationships: &[Relationship],
+        selected: ElementId,
+    ) {
+        let free_angle = ui.input(|i| i.modifiers.shift);
+        for rel in relationships {
+            let (Some(source), Some(target)) = (elements.get(&rel.source_id), elements.get(&rel.target_id)) else {
+                continue;
+            };
+            if !self.is_shown(source) || !self.is_shown(target) {
+                continue;
+            }
+            let (source_edge, target_edge) = self.relationship_ends(source, target, rel);
+            for end in [RelationshipEnd::Source, RelationshipEnd::Target] {
+                // The end turns about the nearest bend, or else the other end
+                let (element, point, fixed) = match end {
+                    RelationshipEnd::Source => (source, source_edge, rel.waypoints.first().copied().unwrap_or(target_edge)),
+                    RelationshipEnd::Target => (target, target_edge, rel.waypoints.last().copied().unwrap_or(source_edge)),
+                };
+                if element.id != selected {
+                    continue;
+                }
+                let handle = self.to_screen(point);
+                let rect = Rect::from_center_size(handle, Vec2::splat(12.0));
+                let response = ui
+                    .interact(rect, ui.id().with(("relationship_end", rel.id, end)), egui::Sense::drag())
+                    .on_hover_text("Drag to pin this end; hold Shift to turn off angle snapping");
+                ui.painter().circle(handle, 4.0, Color32::WHITE, Stroke::new(1.5, Color32::from_rgb(0, 110, 220)));
+
+                if response.dragged()
+                    && let Some(pointer) = response.interact_pointer_pos()
+                {
+                    let pointer = self.to_world(pointer);
+                    let aim = if free_angle { pointer } else { snap_angle(fixed, pointer) };
+                    let anchor = Anchor::on_line(fixed, aim, element.position, element.size)
+                        .unwrap_or_else(|| Anchor::nearest(element.position, element.size, aim));
+                    self.endpoint_drag = Some((rel.id, end, anchor));
+
+                    let preview = [self.to_screen(fixed), self.to_screen(anchor.point(element.position, element.size))];
+                    ui.painter().add(egui::Shape::dashed_line(
+                        &preview,
+                        Stroke::new(2.0, Color32::from_rgb(0, 110, 220)),
+                        6.0,
+                        4.0,
+                    ));
+                    ui.painter().circle_filled(preview[1], 4.0, Color32::from_rgb(0, 110, 220));
+                }
+                if response.drag_stopped() {
+                    self.completed_anchor = self.endpoint_drag.take();
+                }
+            }
+        }
+    }
+
+    /// Bend handles on the selected element's relationships
+    ///
+    /// Dragging a bend moves it and dragging the small handle halfway along a
+    /// segment adds a bend there; double-clicking a bend removes it.
+    fn drag_waypoints(
+        &mut self,
+        ui: &mut Ui,
+        elements: &HashMap<ElementId, Element>,
+        relationships: &[Relationship],
+        selected: ElementId,
+    ) {
+        let color = Color32::from_rgb(0, 110, 220);
+        for rel in relationships.iter().filter(|r| r.source_id == selected || r.target_id == selected) {
+            let (Some(source), Some(target)) = (elements.get(&rel.source_id), elements.get(&rel.target_id)) else {
+                continue;
+            };
+            if !self.is_shown(source) || !self.is_shown(target) {
+                continue;
+            }
+            let (source_edge, target_edge) = self.relationship_ends(source, target, rel);
+            let route: Vec<Position> = std::iter::once(source_edge)
+                .chain(rel.waypoints.iter().copied())
+                .chain(std::iter::once(target_edge))
+                .collect();
+
+            // (index of the bend in the new waypoints, screen point, whether it is new)
+            let bends = rel.waypoints.iter().enumerate().map(|(i, p)| (i, self.to_screen(*p), false));
+            let inserts = route.windows(2).enumerate().map(|(i, w)| (i, self.to_screen(w[0]).lerp(self.to_screen(w[1]), 0.5), true));
+            for (index, handle, insert) in bends.chain(inserts).collect::<Vec<_>>() {
+                let size = if insert { 8.0 } else { 10.0 };
+                let rect = Rect::from_center_size(handle, Vec2::splat(size + 2.0));
+                let hint = if insert { "Drag to add a bend" } else { "Drag to move this bend; double-click to remove it" };
+                let response = ui
+                    .interact(rect, ui.id().with(("waypoint", rel.id, index, insert)), egui::Sense::click_and_drag())
+                    .on_hover_text(hint);
+                let square = Rect::from_center_size(handle, Vec2::splat(size));
+                if insert {
+                    ui.painter().rect(square, 1.0, Color32::from_white_alpha(160), Stroke::new(1.0, color), StrokeKind::Middle);
+                } else {
+                    ui.painter().rect(square, 1.0, Color32::WHITE, Stroke::new(1.5, color), StrokeKind::Middle);
+                }
+
+                if response.double_clicked() && !insert {
+                    let mut waypoints = rel.waypoints.clone();
+                    waypoints.remove(index);
+                    self.completed_waypoints = Some((rel.id, waypoints));
+                }
+                if response.dragged()
+                    && let Some(pointer) = response.interact_pointer_pos()
+                {
+                    let mut point = self.to_world(pointer);
+                    if self.snap_to_grid {
+                        point = Self::snap(point);
+                    }
+                    let mut waypoints = rel.waypoints.clone();
+                    if insert {
+                        waypoints.insert(index, point);
+                    } else {
+                        waypoints[index] = point;
+                    }
+                    let preview: Vec<Pos2> = std::iter::once(source_edge)
+                        .chain(waypoints.iter().copied())
+                        .chain(std::iter::once(target_edge))
+                        .map(|p| self.to_screen(p))
+                        .collect();
+                    ui.painter().add(egui::Shape::dashed_line(&preview, Stroke::new(2.0, color), 6.0, 4.0));
+                    ui.painter().circle_filled(self.to_screen(point), 4.0, color);
+                    self.waypoint_drag = Some((rel.id, waypoints));
+                }
+                if response.drag_stopped() {
+                    self.completed_waypoints = self.waypoint_drag.take();
+                }
+            }
+        }
+    }
+
+    /// Let the labels of the selected element's relationships be dragged off the line
+    ///
+    /// Double-clicking a moved label puts it back in the middle of the line.
+    fn drag_labels(&mut self, ui: &mut Ui, relationships: &[Relationship], selected: ElementId) {
+        let areas = std::mem::take(&mut self.label_areas);
+        for rel in relationships.iter().filter(|r| r.source_id == selected || r.target_id == selected) {
+            let Some(&(_, rect)) = areas.iter().find(|(id, _)| *id == rel.id) else {
+                continue;
+            };
+            let response = ui
+                .interact(rect.expand(2.0), ui.id().with(("label", rel.id)), egui::Sense::click_and_drag())
+                .on_hover_text("Drag to move this label; double-click to put it back on the line");
+            if response.hovered() || response.dragged() {
+                ui.painter().rect_stroke(rect.expand(2.0), 2.0, Stroke::new(1.0, Color32::from_rgb(0, 110, 220)), StrokeKind::Middle);
+            }
+            if response.double_clicked() {
+                self.completed_label = Some((rel.id, None));
+            } else if response.dragged() {
+                let offset = match self.label_drag {
+                    Some((id, offset)) if id == rel.id => offset,
+                    _ => rel.label_offset.unwrap_or(Position::new(0.0, 0.0)),
+                };
+                let delta = response.drag_delta() / self.scale;
+                self.label_drag = Some((rel.id, Position::new(offset.x + delta.x, offset.y + delta.y)));
+            }
+            if response.drag_stopped() {
+                self.completed_label = self.label_drag.take().map(|(id, offset)| (id, Some(offset)));
+            }
+        }
+        self.label_areas = areas;
     }
 
     fn draw_relationship(
-        &self,
+        &mut self,
         painter: &egui::Painter,
         source: &Element,
         target: &Element,
         rel: &Relationship,
+        offset: f32,
         _clip_rect: Rect,
     ) {
         let source_pos = source.position;
         let target_pos = target.position;
         let source_size = source.size;
         let target_size = target.size;
+        let (source_edge, target_edge) = self.relationship_ends(source, target, rel);
+        let change = self.comparison.as_ref().and_then(|c| c.diff.relationships.get(&rel.id).copied());
+        let cyclic = self.cycle_relationships.contains(&rel.id);
+        let line_color = match change {
+            Some(change) => Comparison::color(change),
+            None if cyclic => CYCLE_COLOR,
+            None => crate::ui::color32(self.stylesheet.line_color(rel)),
+        };
+        let stroke = Stroke::new(if change.is_some() || cyclic { 3.0 } else { 2.0 }, line_color);
+        let bow = self.parallel_bow(source, target, offset);
+
+        // Each arrowhead points along the line's direction where it meets the element
+        let (mid_point, [source_edge, source_from], [target_edge, target_from]) =
+            match rel.line_style.unwrap_or(self.line_style) {
+            // Bends route the line in straight segments whatever the style
+            _ if !rel.waypoints.is_empty() => {
+                let route: Vec<Position> = std::iter::once(source_edge)
+                    .chain(rel.waypoints.iter().copied())
+                    .chain(std::iter::once(target_edge))
+                    .collect();
+                let points: Vec<Pos2> = route.iter().map(|p| self.to_screen(*p)).collect();
+                let mid_point = self.to_screen(polyline_midpoint(&route));
+                let ends = ([points[0], points[1]], [points[points.len() - 1], points[points.len() - 2]]);
+                painter.add(egui::Shape::line(points, stroke));
+                (mid_point, ends.0, ends.1)
+            }
+            // A relationship to its own element loops out and back whatever the style
+            _ if rel.is_self_loop() => {
+                let curve = loop_curve(source_pos, source_size, rel.source_anchor, rel.target_anchor);
+                let points = curve.map(|p| self.to_screen(p));
+                let mid_point = self.to_screen(bezier_midpoint(curve));
+                painter.add(egui::epaint::CubicBezierShape::from_points_stroke(
+                    points,
+                    false,
+                    Color32::TRANSPARENT,
+                    stroke,
+                ));
+                (mid_point, [points[0], points[1]], [points[3], points[2]])
+            }
+            LineStyle::Straight if offset == 0.0 => {
+                let source_edge = self.to_screen(source_edge);
+                let target_edge = self.to_screen(target_edge);
+                painter.line_segment([source_edge, target_edge], stroke);
+                (source_edge.lerp(target_edge, 0.5), [source_edge, target_edge], [target_edge, source_edge])
+            }
+            // Bowed out to one side, so it doesn't cover the others between the same elements
+            LineStyle::Straight => {
+                let along = |t: f32| {
+                    Position::new(
+                        source_edge.x + (target_edge.x - source_edge.x) * t + bow.x,
+                        source_edge.y + (target_edge.y - source_edge.y) * t + bow.y,
+                    )
+                };
+                let points = [source_edge, along(1.0 / 3.0), along(2.0 / 3.0), target_edge].map(|p| self.to_screen(p));
+                let curve = egui::epaint::CubicBezierShape::from_points_stroke(
+                    points,
+                    false,
+                    Color32::TRANSPARENT,
+                    stroke,
+                );
+                let mid_point = curve.sample(0.5);
+                painter.add(curve);
+                (mid_point, [points[0], points[1]], [points[3], points[2]])
+            }
+            LineStyle::Curved => {
+                let source_side = rel
+                    .source_anchor
+                    .map_or_else(|| Side::nearest(source_pos, source_size, source_edge), |a| a.side);
+                let target_side = rel
+                    .target_anchor
+                    .map_or_else(|| Side::nearest(target_pos, target_size, target_edge), |a| a.side);
+                let [c1, c2] = crate::model::curve_control_points(source_edge, source_side, target_edge, target_side)
+                    .map(|c| Position::new(c.x + bow.x, c.y + bow.y));
+                let points = [source_edge, c1, c2, target_edge].map(|p| self.to_screen(p));
+                let curve = egui::epaint::CubicBezierShape::from_points_stroke(
+                    points,
+                    false,
+                    Color32::TRANSPARENT,
+                    stroke,
+                );
+                let mid_point = curve.sample(0.5);
+                painter.add(curve);
+                // The curve leaves and arrives along the control points
+                (mid_point, [points[0], points[1]], [points[3], points[2]])
+            }
+        };
 
-        let source_center = Pos2::new(
-            source_pos.x + source_size.width * 0.5,
-            source_pos.y + source_size.height * 0.5,
-        );
-        let target_center = Pos2::new(
-            target_pos.x + target_size.width * 0.5,
-            target_pos.y + target_size.height * 0.5,
-        );
-
-        // Calculate edge points
-        let source_edge = self.calculate_edge_point(source_pos, source_size, target_center);
-        let target_edge = self.calculate_edge_point(target_pos, target_size, source_center);
-
-        // Draw line
-        painter.line_segment(
-            [source_edge, target_edge],
-            Stroke::new(2.0, Color32::from_gray(100)),
-        );
-
-        // Draw arrowhead
-        self.draw_arrowhead(painter, target_edge, source_edge);
+        if rel.direction.arrow_at_target() {
+            self.draw_arrowhead(painter, target_edge, target_from, line_color);
+        }
+        if rel.direction.arrow_at_source() {
+            self.draw_arrowhead(painter, source_edge, source_from, line_color);
+        }
 
         // Draw label
-        let mid_point = Pos2::new(
-            (source_edge.x + target_edge.x) * 0.5,
-            (source_edge.y + target_edge.y) * 0.5,
-        );
-        painter.text(
-            mid_point,
-            egui::Align2::CENTER_CENTER,
+        let label = self.text_cache.get(
+            painter,
+            (rel.id, TextSlot::Label),
             &rel.description,
-            egui::FontId::proportional(10.0),
-            Color32::from_gray(60),
+            egui::FontId::proportional(self.typography.label_size * self.scale),
+            f32::INFINITY,
+            str::to_string,
         );
+        // A label dragged off the line is tied back to its middle by a faint leader
+        let label_offset = match self.label_drag {
+            Some((id, offset)) if id == rel.id => Some(offset),
+            _ => rel.label_offset,
+        };
+        let line_middle = mid_point;
+        let mid_point = match label_offset {
+            Some(offset) => {
+                let moved = mid_point + Vec2::new(offset.x, offset.y) * self.scale;
+                painter.add(egui::Shape::dashed_line(&[line_middle, moved], Stroke::new(1.0, Color32::from_gray(170)), 3.0, 3.0));
+                moved
+            }
+            None => mid_point,
+        };
+        let label_pos = egui::Align2::CENTER_CENTER.anchor_size(mid_point, label.size()).min;
+        let label_width = label.size().x;
+        if label_width > 0.0 {
+            self.label_areas.push((rel.id, Rect::from_min_size(label_pos, label.size())));
+        }
+        painter.galley(label_pos, label, Color32::from_gray(60));
+
+        // Step number in a disc just before the label
+        if let Some(step) = self.steps.get(&rel.id) {
+            let radius = 9.0 * self.scale;
+            let center = if label_width > 0.0 {
+                Pos2::new(label_pos.x - radius - 4.0 * self.scale, mid_point.y)
+            } else {
+                mid_point
+            };
+            let color = Color32::from_rgb(17, 104, 189);
+            painter.circle_filled(center, radius, color);
+            painter.text(
+                center,
+                egui::Align2::CENTER_CENTER,
+                step.to_string(),
+                egui::FontId::proportional(11.0 * self.scale),
+                Color32::WHITE,
+            );
+        }
+    }
+
+    /// How far to push a relationship's control points for a sideways `offset` at its middle
+    ///
+    /// A cubic curve's middle moves three quarters as far as its control
+    /// points. The side is taken from the element IDs, like the offsets.
+    fn parallel_bow(&self, source: &Element, target: &Element, offset: f32) -> Position {
+        if offset == 0.0 {
+            return Position::new(0.0, 0.0);
+        }
+        let center = |e: &Element| Pos2::new(e.position.x + e.size.width * 0.5, e.position.y + e.size.height * 0.5);
+        let (from, to) = if source.id < target.id { (source, target) } else { (target, source) };
+        let direction = (center(to) - center(from)).normalized();
+        let scale = offset / 0.75;
+        Position::new(direction.y * scale, -direction.x * scale)
     }
 
     fn draw_preview_relationship(
@@ -309,7 +1521,9 @@ impl Canvas {
         );
 
         // Calculate edge point from source
-        let source_edge = self.calculate_edge_point(source_pos, source_size, mouse_pos);
+        let mouse_world = self.to_world(mouse_pos).to_pos2();
+        let source_edge = self.element_edge_point(source, mouse_world);
+        let source_edge = self.to_screen(Position::from_pos2(source_edge));
 
         // Draw dashed preview line
         let preview_color = Color32::from_rgb(0, 150, 0);
@@ -364,7 +1578,23 @@ impl Canvas {
         )
     }
 
-    fn draw_arrowhead(&self, painter: &egui::Painter, tip: Pos2, from: Pos2) {
+    /// Where a line towards `target` meets the element's drawn outline
+    ///
+    /// Boxes use their rectangle; ellipses and the curved ends of cylinders
+    /// and pipes use the outline they are drawn with, so arrows touch the
+    /// curve rather than the corner of the bounding box.
+    fn element_edge_point(&self, element: &Element, target: Pos2) -> Pos2 {
+        let shape = self.stylesheet.element_style(element).shape;
+        let on_box = self.calculate_edge_point(element.position, element.size, target);
+        if matches!(shape, Shape::Rectangle | Shape::Rounded) {
+            return on_box;
+        }
+        shape
+            .exit_point(element.position, element.size, Position::new(target.x, target.y))
+            .map_or(on_box, |p| p.to_pos2())
+    }
+
+    fn draw_arrowhead(&self, painter: &egui::Painter, tip: Pos2, from: Pos2, color: Color32) {
         let direction = (tip - from).normalized();
         let perpendicular = Vec2::new(-direction.y, direction.x);
 
@@ -376,12 +1606,54 @@ impl Canvas {
 
         painter.add(egui::Shape::convex_polygon(
             vec![tip, p1, p2],
-            Color32::from_gray(100),
-            Stroke::new(1.0, Color32::from_gray(100)),
+            color,
+            Stroke::new(1.0, color),
         ));
     }
 }
 
+/// Screen span of a scrollbar thumb showing `view` within `extent` on `track`
+fn scroll_thumb(extent: egui::Rangef, view: egui::Rangef, track: egui::Rangef) -> egui::Rangef {
+    let span = extent.span().max(f32::EPSILON);
+    let start = ((view.min - extent.min) / span).clamp(0.0, 1.0);
+    let end = ((view.max - extent.min) / span).clamp(start, 1.0);
+    let length = ((end - start) * track.span()).max(MIN_THUMB_LENGTH).min(track.span());
+    let min = (track.min + start * track.span()).min(track.max - length);
+    egui::Rangef::new(min, min + length)
+}
+
+/// Whether a screen point is in the band just inside an element's border
+fn on_connect_edge(rect: Rect, pos: Pos2) -> bool {
+    rect.contains(pos) && !rect.shrink(CONNECT_EDGE.min(rect.width().min(rect.height()) / 4.0)).contains(pos)
+}
+
+/// Outline and rim of a cylinder filling `rect`, upright or lying on its side
+///
+/// The outline is a closed convex polygon; the rim is the visible edge of the
+/// near end cap (the top one upright, the right one on its side).
+fn cylinder_outline(rect: Rect, horizontal: bool) -> (Vec<Pos2>, Vec<Pos2>) {
+    let shape = if horizontal { Shape::Pipe } else { Shape::Cylinder };
+    let mut outline = border_outline(rect, shape);
+    outline.pop();
+    let (position, size) = (Position::new(rect.min.x, rect.min.y), Size::new(rect.width(), rect.height()));
+    (outline, shape.rim(position, size).into_iter().map(|p| p.to_pos2()).collect())
+}
+
+/// Closed outline of an element's shape, for drawing a broken border along it
+fn border_outline(rect: Rect, shape: Shape) -> Vec<Pos2> {
+    let (position, size) = (Position::new(rect.min.x, rect.min.y), Size::new(rect.width(), rect.height()));
+    shape.outline(position, size).into_iter().map(|p| p.to_pos2()).collect()
+}
+
+/// A border line in the given style
+fn border_lines(points: &[Pos2], stroke: Stroke, border_style: BorderStyle, scale: f32) -> Vec<egui::Shape> {
+    match border_style {
+        BorderStyle::Solid => vec![egui::Shape::line(points.to_vec(), stroke)],
+        BorderStyle::Dashed => egui::Shape::dashed_line(points, stroke, 8.0 * scale, 4.0 * scale),
+        BorderStyle::Dotted => egui::Shape::dotted_line(points, stroke.color, 5.0 * scale, stroke.width * 0.6),
+    }
+}
+
 fn truncate_text(text: &str, max_len: usize) -> String {
     if text.chars().count() <= max_len {
         text.to_string()
@@ -457,6 +1729,153 @@ mod tests {
         }
     }
 
+    mod zoom_tests {
+        use super::*;
+
+        /// Verifies to_world inverts to_screen under zoom and scroll
+        #[test]
+        fn to_world_inverts_to_screen() {
+            let mut canvas = Canvas::new();
+            canvas.scale = 2.0;
+            canvas.offset = Vec2::new(30.0, -10.0);
+
+            let screen = canvas.to_screen(Position::new(5.0, 7.0));
+            assert_eq!(screen, Pos2::new(40.0, 4.0));
+            assert_eq!(canvas.to_world(screen), Position::new(5.0, 7.0));
+        }
+
+        /// Verifies zoom_at keeps the anchored point fixed and clamps the scale
+        #[test]
+        fn zoom_at_keeps_anchor_fixed() {
+            let mut canvas = Canvas::new();
+            let anchor = Pos2::new(100.0, 50.0);
+            let before = canvas.to_world(anchor);
+
+            canvas.zoom_at(anchor, 2.0);
+            assert_eq!(canvas.scale, 2.0);
+            assert_eq!(canvas.to_world(anchor), before);
+
+            canvas.zoom_at(anchor, 100.0);
+            assert_eq!(canvas.scale, MAX_SCALE);
+        }
+
+        /// Verifies fit_to centres the elements' bounding box in the viewport
+        #[test]
+        fn fit_to_centres_elements() {
+            let mut canvas = Canvas::new();
+            let mut elements = HashMap::new();
+            for position in [Position::new(0.0, 0.0), Position::new(1000.0, 0.0)] {
+                let element = Element::new(crate::model::ElementType::system("S", ""), position);
+                elements.insert(element.id, element);
+            }
+            let viewport = Rect::from_min_size(Pos2::ZERO, Vec2::new(600.0, 400.0));
+
+            canvas.fit_to(&elements, viewport);
+
+            assert!(canvas.scale < 1.0);
+            let right_edge = elements.values().map(|e| e.position.x + e.size.width).fold(0.0, f32::max);
+            assert!(canvas.to_screen(Position::new(right_edge, 0.0)).x <= viewport.max.x);
+            assert!(canvas.to_screen(Position::new(0.0, 0.0)).x >= viewport.min.x);
+        }
+
+        /// Verifies fit_to with no elements resets the zoom
+        #[test]
+        fn fit_to_without_elements_resets() {
+            let mut canvas = Canvas::new();
+            canvas.scale = 3.0;
+            canvas.fit_to(&HashMap::new(), Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.0)));
+            assert_eq!(canvas.scale, 1.0);
+            assert_eq!(canvas.offset, Vec2::ZERO);
+        }
+
+        fn element_at(x: f32, y: f32) -> Element {
+            Element::new(ElementType::system("S", ""), Position::new(x, y))
+        }
+
+        /// Verifies Tab order runs top to bottom, left to right, and wraps
+        #[test]
+        fn cycle_selection_follows_reading_order() {
+            let (a, b, c) = (element_at(300.0, 0.0), element_at(0.0, 0.0), element_at(0.0, 200.0));
+            let (a_id, b_id, c_id) = (a.id, b.id, c.id);
+            let elements: HashMap<ElementId, Element> = [a, b, c].into_iter().map(|e| (e.id, e)).collect();
+            let canvas = Canvas::new();
+
+            assert_eq!(canvas.cycle_selection(&elements, None, false), Some(b_id));
+            assert_eq!(canvas.cycle_selection(&elements, Some(b_id), false), Some(a_id));
+            assert_eq!(canvas.cycle_selection(&elements, Some(c_id), false), Some(b_id));
+            assert_eq!(canvas.cycle_selection(&elements, Some(b_id), true), Some(c_id));
+            assert_eq!(canvas.cycle_selection(&HashMap::new(), None, false), None);
+        }
+
+        /// Verifies arrow navigation picks the nearest element roughly in line
+        #[test]
+        fn neighbor_prefers_elements_in_line() {
+            let origin = element_at(0.0, 0.0);
+            let right = element_at(400.0, 0.0);
+            let diagonal = element_at(250.0, 200.0);
+            let below = element_at(0.0, 300.0);
+            let ids = [origin.id, right.id, diagonal.id, below.id];
+            let elements: HashMap<ElementId, Element> =
+                [origin, right, diagonal, below].into_iter().map(|e| (e.id, e)).collect();
+            let canvas = Canvas::new();
+
+            assert_eq!(canvas.neighbor(&elements, ids[0], Vec2::RIGHT), Some(ids[1]));
+            assert_eq!(canvas.neighbor(&elements, ids[0], Vec2::DOWN), Some(ids[3]));
+            assert_eq!(canvas.neighbor(&elements, ids[0], Vec2::LEFT), None);
+            assert_eq!(canvas.neighbor(&elements, ids[3], Vec2::UP), Some(ids[0]));
+        }
+
+        /// Verifies the scrollbar thumb spans the view's share of the extent
+        #[test]
+        fn scroll_thumb_matches_view() {
+            let track = egui::Rangef::new(0.0, 100.0);
+            let thumb = scroll_thumb(egui::Rangef::new(-500.0, 500.0), egui::Rangef::new(0.0, 250.0), track);
+            assert_eq!(thumb, egui::Rangef::new(50.0, 75.0));
+
+            // Tiny views keep a grabbable thumb inside the track
+            let thumb = scroll_thumb(egui::Rangef::new(0.0, 100_000.0), egui::Rangef::new(99_990.0, 100_000.0), track);
+            assert_eq!(thumb, egui::Rangef::new(80.0, 100.0));
+        }
+
+        /// Verifies new elements go to the middle of the view, clear of existing ones
+        #[test]
+        fn drop_position_centres_in_view() {
+            let mut canvas = Canvas::new();
+            let size = Size::new(100.0, 60.0);
+            assert_eq!(canvas.drop_position(&HashMap::new(), size), None);
+
+            canvas.viewport = Rect::from_min_size(Pos2::ZERO, Vec2::new(400.0, 300.0));
+            canvas.offset = Vec2::new(1000.0, 1000.0);
+            let first = canvas.drop_position(&HashMap::new(), size).unwrap();
+            assert_eq!(first, Position::new(-850.0, -880.0));
+
+            let mut occupied = element_at(first.x, first.y);
+            occupied.size = size;
+            let elements: HashMap<ElementId, Element> = [(occupied.id, occupied)].into_iter().collect();
+            assert_eq!(canvas.drop_position(&elements, size), Some(Position::new(-830.0, -860.0)));
+        }
+
+        /// Verifies snap rounds to the nearest grid point
+        #[test]
+        fn snap_rounds_to_grid() {
+            assert_eq!(Canvas::snap(Position::new(29.0, 31.0)), Position::new(20.0, 40.0));
+            assert_eq!(Canvas::snap(Position::new(-9.0, 0.0)), Position::new(0.0, 0.0));
+        }
+
+        /// Verifies hiding externals only hides external elements
+        #[test]
+        fn is_shown_follows_external_filter() {
+            let external = Element::new(ElementType::external_person("Partner", ""), Position::new(0.0, 0.0));
+            let internal = Element::new(ElementType::system("Shop", ""), Position::new(0.0, 0.0));
+            let mut canvas = Canvas::new();
+            assert!(canvas.is_shown(&external));
+
+            canvas.hide_external = true;
+            assert!(!canvas.is_shown(&external));
+            assert!(canvas.is_shown(&internal));
+        }
+    }
+
     mod calculate_edge_point_tests {
         use super::*;
 
@@ -627,4 +2046,175 @@ mod tests {
             assert_eq!(result, "...");
         }
     }
+
+    mod connect_tests {
+        use super::*;
+
+        /// Verifies only the band just inside the border starts a connection
+        #[test]
+        fn connect_edge_band() {
+            let rect = Rect::from_min_size(Pos2::new(0.0, 0.0), Vec2::new(160.0, 100.0));
+            assert!(on_connect_edge(rect, Pos2::new(3.0, 50.0)));
+            assert!(on_connect_edge(rect, Pos2::new(80.0, 97.0)));
+            assert!(!on_connect_edge(rect, Pos2::new(80.0, 50.0)));
+            assert!(!on_connect_edge(rect, Pos2::new(-3.0, 50.0)));
+        }
+
+        /// Verifies the band stays narrow on small elements so they can still be moved
+        #[test]
+        fn connect_edge_band_on_small_element() {
+            let rect = Rect::from_min_size(Pos2::new(0.0, 0.0), Vec2::new(20.0, 20.0));
+            assert!(on_connect_edge(rect, Pos2::new(2.0, 10.0)));
+            assert!(!on_connect_edge(rect, Pos2::new(7.0, 10.0)));
+        }
+
+        /// Verifies element_at finds the shown element under a screen point
+        #[test]
+        fn element_at_hit_tests_screen_rects() {
+            let mut canvas = Canvas::new();
+            canvas.scale = 2.0;
+            let element = Element::new(ElementType::system("API", ""), Position::new(10.0, 10.0));
+            let id = element.id;
+            let elements = HashMap::from([(id, element)]);
+            assert_eq!(canvas.element_at(&elements, Pos2::new(25.0, 25.0)), Some(id));
+            assert_eq!(canvas.element_at(&elements, Pos2::new(15.0, 15.0)), None);
+        }
+
+        /// Verifies overlapping elements are hit top first
+        #[test]
+        fn element_at_prefers_topmost() {
+            let canvas = Canvas::new();
+            let below = Element::new(ElementType::system("Below", ""), Position::new(0.0, 0.0));
+            let mut above = Element::new(ElementType::system("Above", ""), Position::new(50.0, 50.0));
+            above.z_index = 1;
+            let (below_id, above_id) = (below.id, above.id);
+            let elements = HashMap::from([(below_id, below), (above_id, above)]);
+            assert_eq!(canvas.element_at(&elements, Pos2::new(60.0, 60.0)), Some(above_id));
+            assert_eq!(canvas.element_at(&elements, Pos2::new(10.0, 10.0)), Some(below_id));
+        }
+    }
+
+    mod cylinder_outline_tests {
+        use super::*;
+
+        /// Verifies the upright outline fills the rect and the rim dips below the top
+        #[test]
+        fn upright_cylinder() {
+            let rect = Rect::from_min_size(Pos2::new(0.0, 0.0), Vec2::new(160.0, 100.0));
+            let (outline, rim) = cylinder_outline(rect, false);
+            assert!(outline.iter().all(|p| rect.expand(0.01).contains(*p)));
+            let top = outline.iter().map(|p| p.y).fold(f32::INFINITY, f32::min);
+            let bottom = outline.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max);
+            assert!(top.abs() < 0.01 && (bottom - 100.0).abs() < 0.01);
+            assert!((rim[rim.len() / 2] - Pos2::new(80.0, 24.0)).length() < 0.01);
+        }
+
+        /// Verifies the lying outline fills the rect and the rim curves into the right end
+        #[test]
+        fn lying_cylinder() {
+            let rect = Rect::from_min_size(Pos2::new(0.0, 0.0), Vec2::new(160.0, 100.0));
+            let (outline, rim) = cylinder_outline(rect, true);
+            assert!(outline.iter().all(|p| rect.expand(0.01).contains(*p)));
+            let left = outline.iter().map(|p| p.x).fold(f32::INFINITY, f32::min);
+            assert!(left.abs() < 0.01);
+            assert!((rim[rim.len() / 2] - Pos2::new(134.4, 50.0)).length() < 0.01);
+        }
+
+        /// Verifies lines to a queue meet its curved ends, not the corners of its box
+        #[test]
+        fn pipe_edge_points_follow_the_ends() {
+            let canvas = Canvas::new();
+            let mut queue = Element::new(
+                ElementType::container("Orders", "", crate::model::ContainerType::Queue, "Kafka"),
+                Position::new(0.0, 0.0),
+            );
+            queue.size = Size::new(160.0, 100.0);
+
+            let left = canvas.element_edge_point(&queue, Pos2::new(-100.0, 50.0));
+            assert!((left - Pos2::new(0.0, 50.0)).length() < 0.01, "{:?}", left);
+
+            // up and to the left the box edge is outside the rounded end
+            let corner = canvas.element_edge_point(&queue, Pos2::new(-100.0, -20.0));
+            let on_box = canvas.calculate_edge_point(queue.position, queue.size, Pos2::new(-100.0, -20.0));
+            assert!(corner.x > on_box.x && corner.y > on_box.y, "{:?} vs {:?}", corner, on_box);
+            let (radius, center) = (Vec2::new(12.8, 50.0), Pos2::new(12.8, 50.0));
+            let unit = Vec2::new((corner.x - center.x) / radius.x, (corner.y - center.y) / radius.y);
+            assert!((unit.length() - 1.0).abs() < 0.02, "{:?} is not on the end", corner);
+
+            let above = canvas.element_edge_point(&queue, Pos2::new(80.0, -100.0));
+            assert!((above - Pos2::new(80.0, 0.0)).length() < 0.01, "{:?}", above);
+        }
+
+        /// Verifies border outlines are closed and stay on the element
+        #[test]
+        fn border_outline_is_closed() {
+            let rect = Rect::from_min_size(Pos2::new(10.0, 20.0), Vec2::new(160.0, 100.0));
+            for shape in [Shape::Rounded, Shape::Ellipse, Shape::Pipe] {
+                let outline = border_outline(rect, shape);
+                assert_eq!(outline.first(), outline.last());
+                assert!(outline.iter().all(|p| rect.expand(0.01).contains(*p)));
+            }
+            assert_eq!(border_outline(rect, Shape::Rectangle).len(), 5);
+        }
+    }
+
+    mod comparison_tests {
+        use super::*;
+        use crate::model::DiagramType;
+
+        /// Verifies elements missing from the current diagram are kept to be outlined
+        #[test]
+        fn comparison_keeps_removed_elements() {
+            let mut base = Diagram::new("Shop", "", DiagramType::SystemContext);
+            let shop = Element::new(ElementType::system("Shop", ""), Position::new(40.0, 60.0));
+            let shop_id = shop.id;
+            base.add_element(shop);
+            let mut current = base.clone();
+            current.remove_element(shop_id);
+            current.add_element(Element::new(ElementType::person("User", ""), Position::new(0.0, 0.0)));
+
+            let comparison = Comparison::new(&base, &current);
+            assert_eq!(comparison.removed.len(), 1);
+            assert_eq!(comparison.removed[0].position, Position::new(40.0, 60.0));
+            assert_eq!(comparison.diff.elements_with(Change::Added).len(), 1);
+        }
+    }
+
+    mod impact_tests {
+        use super::*;
+        use crate::model::DiagramType;
+
+        /// Verifies impact marks downstream elements, and upstream ones only when asked
+        #[test]
+        fn impact_of_selected_element() {
+            let mut diagram = Diagram::new("Shop", "", DiagramType::SystemContext);
+            let ids: Vec<ElementId> = ["Web", "API", "DB", "User"]
+                .iter()
+                .map(|name| {
+                    let element = Element::new(ElementType::system(*name, ""), Position::new(0.0, 0.0));
+                    let id = element.id;
+                    diagram.add_element(element);
+                    id
+                })
+                .collect();
+            diagram.add_relationship(Relationship::new(ids[3], ids[0], "uses"));
+            diagram.add_relationship(Relationship::new(ids[0], ids[1], "calls"));
+            diagram.add_relationship(Relationship::new(ids[1], ids[2], "reads"));
+
+            let downstream = Impact::of(&diagram, ids[0], false);
+            assert_eq!(downstream, HashMap::from([(ids[1], Impact::Downstream(1)), (ids[2], Impact::Downstream(2))]));
+            let both = Impact::of(&diagram, ids[0], true);
+            assert_eq!(both[&ids[3]], Impact::Upstream(1));
+            assert_eq!(both[&ids[3]].label(), "↑1");
+        }
+
+        /// Verifies colours fade with distance and stop changing after four steps
+        #[test]
+        fn impact_color_fades() {
+            assert_eq!(Impact::Downstream(1).color(), Color32::from_rgb(210, 40, 30));
+            assert_ne!(Impact::Downstream(2).color(), Impact::Downstream(1).color());
+            assert_eq!(Impact::Downstream(4).color(), Impact::Downstream(9).color());
+            assert_ne!(Impact::Upstream(1).color(), Impact::Downstream(1).color());
+        }
+    }
 }
@@ -0,0 +1,209 @@
+use crate::model::{Comment, CommentAnchor, CommentThread, Diagram, ElementId, Position};
+use egui::{Color32, RichText, Ui};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Side panel listing review comment threads, with new-thread and reply boxes
+#[derive(Default)]
+pub struct CommentsPanel {
+    pub show_resolved: bool,
+    /// Canvas point picked for the next new thread (right-click on the canvas)
+    pub pending_position: Option<Position>,
+    /// Thread to expand, e.g. after its marker was clicked
+    pub focused_thread: Option<Uuid>,
+    draft: String,
+    replies: HashMap<Uuid, String>,
+}
+
+/// Short description of what a thread is attached to
+pub fn anchor_label(diagram: &Diagram, anchor: &CommentAnchor) -> String {
+    match anchor {
+        CommentAnchor::Element(id) => match diagram.get_element(*id) {
+            Some(element) => format!("On {}", element.name().lines().next().unwrap_or("")),
+            None => "On a removed element".to_string(),
+        },
+        CommentAnchor::Position(p) => format!("At ({:.0}, {:.0})", p.x, p.y),
+    }
+}
+
+impl CommentsPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indices into `diagram.comments` shown in the panel
+    pub fn visible_threads(&self, diagram: &Diagram) -> Vec<usize> {
+        diagram
+            .comments
+            .iter()
+            .enumerate()
+            .filter(|(_, thread)| self.show_resolved || !thread.resolved)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Where a new thread would be attached: the picked canvas point, else the selection
+    pub fn new_thread_anchor(&self, selected_element: Option<ElementId>) -> Option<CommentAnchor> {
+        self.pending_position
+            .map(CommentAnchor::Position)
+            .or(selected_element.map(CommentAnchor::Element))
+    }
+
+    /// Render the panel; changes are applied to the diagram directly
+    pub fn render(&mut self, ui: &mut Ui, diagram: &mut Diagram, selected_element: Option<ElementId>, author: &str) {
+        let anchor = self.new_thread_anchor(selected_element);
+        match &anchor {
+            Some(anchor) => {
+                ui.horizontal(|ui| {
+                    ui.label(anchor_label(diagram, anchor));
+                    if self.pending_position.is_some() && ui.small_button("✖").on_hover_text("Clear point").clicked() {
+                        self.pending_position = None;
+                    }
+                });
+            }
+            None => {
+                ui.colored_label(
+                    Color32::from_gray(120),
+                    "Select an element or right-click the canvas to comment",
+                );
+            }
+        }
+        ui.add(
+            egui::TextEdit::multiline(&mut self.draft)
+                .desired_rows(2)
+                .hint_text("New comment"),
+        );
+        let can_add = anchor.is_some() && !self.draft.trim().is_empty();
+        if ui.add_enabled(can_add, egui::Button::new("Add Comment")).clicked()
+            && let Some(anchor) = anchor
+        {
+            let text = std::mem::take(&mut self.draft);
+            let id = diagram.add_comment_thread(anchor, Comment::new(author, text.trim()));
+            self.pending_position = None;
+            self.focused_thread = Some(id);
+        }
+
+        ui.separator();
+        ui.checkbox(&mut self.show_resolved, "Show resolved");
+
+        let rows = self.visible_threads(diagram);
+        if rows.is_empty() {
+            ui.colored_label(Color32::from_gray(120), "No comments");
+            return;
+        }
+
+        let mut replied = None;
+        let mut resolved = None;
+        let mut deleted = None;
+        let focused = self.focused_thread.take();
+
+        egui::ScrollArea::vertical().id_salt("comments_scroll").show(ui, |ui| {
+            for index in rows {
+                let thread: &CommentThread = &diagram.comments[index];
+                let title = format!(
+                    "{}{} ({})",
+                    if thread.resolved { "✔ " } else { "" },
+                    anchor_label(diagram, &thread.anchor),
+                    thread.comments.len()
+                );
+                let header = egui::CollapsingHeader::new(title)
+                    .id_salt(("comment_thread", thread.id))
+                    .open(focused.filter(|id| *id == thread.id).map(|_| true))
+                    .show(ui, |ui| {
+                        for comment in &thread.comments {
+                            ui.label(RichText::new(&comment.author).strong());
+                            ui.label(&comment.text);
+                        }
+                        let reply = self.replies.entry(thread.id).or_default();
+                        ui.add(
+                            egui::TextEdit::multiline(reply)
+                                .id_salt(("comment_reply", thread.id))
+                                .desired_rows(1)
+                                .hint_text("Reply"),
+                        );
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(!reply.trim().is_empty(), egui::Button::new("Reply")).clicked() {
+                                replied = Some((thread.id, std::mem::take(reply)));
+                            }
+                            let label = if thread.resolved { "Reopen" } else { "Resolve" };
+                            if ui.button(label).clicked() {
+                                resolved = Some((thread.id, !thread.resolved));
+                            }
+                            if ui.small_button("🗑").on_hover_text("Delete thread").clicked() {
+                                deleted = Some(thread.id);
+                            }
+                        });
+                    });
+                if focused == Some(thread.id) {
+                    header.header_response.scroll_to_me(None);
+                }
+            }
+        });
+
+        if let Some((id, text)) = replied {
+            diagram.reply_to_comment_thread(id, Comment::new(author, text.trim()));
+        }
+        if let Some((id, value)) = resolved {
+            diagram.set_comment_thread_resolved(id, value);
+        }
+        if let Some(id) = deleted {
+            diagram.remove_comment_thread(id);
+            self.replies.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, Element, ElementType};
+
+    mod visible_threads_tests {
+        use super::*;
+
+        /// Verifies resolved threads are hidden unless requested
+        #[test]
+        fn visible_threads_hides_resolved() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let anchor = CommentAnchor::Position(Position::new(0.0, 0.0));
+            diagram.add_comment_thread(anchor, Comment::new("a", "open"));
+            let done = diagram.add_comment_thread(anchor, Comment::new("a", "done"));
+            diagram.set_comment_thread_resolved(done, true);
+
+            let mut panel = CommentsPanel::new();
+            assert_eq!(panel.visible_threads(&diagram), vec![0]);
+            panel.show_resolved = true;
+            assert_eq!(panel.visible_threads(&diagram), vec![0, 1]);
+        }
+
+        /// Verifies a picked canvas point takes precedence over the selection
+        #[test]
+        fn new_thread_anchor_prefers_pending_position() {
+            let id = ElementId::new_v4();
+            let mut panel = CommentsPanel::new();
+            assert_eq!(panel.new_thread_anchor(None), None);
+            assert_eq!(panel.new_thread_anchor(Some(id)), Some(CommentAnchor::Element(id)));
+
+            panel.pending_position = Some(Position::new(1.0, 2.0));
+            assert_eq!(
+                panel.new_thread_anchor(Some(id)),
+                Some(CommentAnchor::Position(Position::new(1.0, 2.0)))
+            );
+        }
+
+        /// Verifies anchors are labelled with the element name or point
+        #[test]
+        fn anchor_label_describes_anchor() {
+            let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+            let element = Element::new(ElementType::system("Billing\nService", ""), Position::new(0.0, 0.0));
+            let id = element.id;
+            diagram.add_element(element);
+
+            assert_eq!(anchor_label(&diagram, &CommentAnchor::Element(id)), "On Billing");
+            assert_eq!(
+                anchor_label(&diagram, &CommentAnchor::Position(Position::new(10.4, 20.6))),
+                "At (10, 21)"
+            );
+        }
+    }
+}
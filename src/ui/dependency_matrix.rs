@@ -0,0 +1,185 @@
+use crate::model::{Diagram, ElementId};
+use egui::{Color32, RichText, Ui};
+use std::collections::HashMap;
+
+/// Rows and cells of a dependency structure matrix
+///
+/// Rows are sources and columns are targets, both in the same order; a cell
+/// holds the indices into `diagram.relationships` from its row to its column.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MatrixData {
+    pub elements: Vec<ElementId>,
+    pub cells: HashMap<(ElementId, ElementId), Vec<usize>>,
+}
+
+impl MatrixData {
+    /// Build the matrix with elements sorted by name
+    ///
+    /// With `connected_only`, elements without any relationship are left out.
+    pub fn new(diagram: &Diagram, connected_only: bool) -> Self {
+        let mut cells: HashMap<(ElementId, ElementId), Vec<usize>> = HashMap::new();
+        for (index, rel) in diagram.relationships.iter().enumerate() {
+            cells.entry((rel.source_id, rel.target_id)).or_default().push(index);
+        }
+
+        let mut elements: Vec<ElementId> = diagram
+            .elements
+            .keys()
+            .copied()
+            .filter(|&id| !connected_only || cells.keys().any(|&(source, target)| source == id || target == id))
+            .collect();
+        elements.sort_by_cached_key(|id| (diagram.elements[id].name().to_lowercase(), *id));
+
+        Self { elements, cells }
+    }
+
+    /// Relationship indices from `source` to `target`
+    pub fn cell(&self, source: ElementId, target: ElementId) -> &[usize] {
+        self.cells.get(&(source, target)).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether the two elements depend on each other directly
+    pub fn is_mutual(&self, a: ElementId, b: ElementId) -> bool {
+        a != b && !self.cell(a, b).is_empty() && !self.cell(b, a).is_empty()
+    }
+}
+
+/// Elements as a matrix of sources (rows) and targets (columns)
+///
+/// Columns are numbered to keep the matrix compact; hover a number for the
+/// name. Mutual dependencies are highlighted.
+#[derive(Default)]
+pub struct DependencyMatrix {
+    pub connected_only: bool,
+}
+
+impl DependencyMatrix {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render the matrix; clicking a row header or cell selects its source element
+    pub fn render(&mut self, ui: &mut Ui, diagram: &Diagram, selected_element: &mut Option<ElementId>) {
+        ui.horizontal(|ui| {
+            ui.heading("Dependency Matrix");
+            ui.checkbox(&mut self.connected_only, "Connected elements only")
+                .on_hover_text("Hide elements without any relationship");
+        });
+        ui.label("Rows depend on columns. Highlighted cells are mutual dependencies.");
+        ui.separator();
+
+        let data = MatrixData::new(diagram, self.connected_only);
+        if data.elements.is_empty() {
+            ui.colored_label(Color32::from_gray(120), "No elements");
+            return;
+        }
+        let name = |id: &ElementId| diagram.get_element(*id).map_or("?", |e| e.name());
+
+        egui::ScrollArea::both().show(ui, |ui| {
+            egui::Grid::new("dependency_matrix_grid")
+                .striped(true)
+                .min_col_width(24.0)
+                .show(ui, |ui| {
+                    ui.label("");
+                    for (column, target) in data.elements.iter().enumerate() {
+                        ui.strong(format!("{}", column + 1)).on_hover_text(name(target));
+                    }
+                    ui.end_row();
+
+                    for (row, &source) in data.elements.iter().enumerate() {
+                        let header = format!("{}. {}", row + 1, name(&source));
+                        if ui.selectable_label(*selected_element == Some(source), header).clicked() {
+                            *selected_element = Some(source);
+                        }
+                        for &target in &data.elements {
+                            let rels = data.cell(source, target);
+                            if source == target && rels.is_empty() {
+                                ui.label(RichText::new("·").color(Color32::from_gray(160)));
+                                continue;
+                            }
+                            if rels.is_empty() {
+                                ui.label("");
+                                continue;
+                            }
+                            let mut text = RichText::new(rels.len().to_string()).strong();
+                            if data.is_mutual(source, target) {
+                                text = text.background_color(Color32::from_rgb(255, 210, 150));
+                            }
+                            let details: Vec<String> = rels
+                                .iter()
+                                .map(|&index| {
+                                    let rel = &diagram.relationships[index];
+                                    match &rel.technology {
+                                        Some(technology) => format!("{} [{}]", rel.description, technology),
+                                        None => rel.description.clone(),
+                                    }
+                                })
+                                .collect();
+                            let hover = format!("{} → {}\n{}", name(&source), name(&target), details.join("\n"));
+                            if ui.selectable_label(false, text).on_hover_text(hover).clicked() {
+                                *selected_element = Some(source);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, Element, ElementType, Position, Relationship};
+
+    fn sample_diagram() -> (Diagram, Vec<ElementId>) {
+        let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+        let ids: Vec<ElementId> = ["Catalog", "alice", "Billing", "Lonely"]
+            .iter()
+            .map(|name| {
+                let element = Element::new(ElementType::system(*name, ""), Position::new(0.0, 0.0));
+                let id = element.id;
+                diagram.add_element(element);
+                id
+            })
+            .collect();
+        diagram.add_relationship(Relationship::new(ids[1], ids[0], "browses"));
+        diagram.add_relationship(Relationship::new(ids[0], ids[2], "charges"));
+        diagram.add_relationship(Relationship::new(ids[2], ids[0], "refunds"));
+        diagram.add_relationship(Relationship::new(ids[0], ids[2], "queries"));
+        (diagram, ids)
+    }
+
+    mod matrix_data_tests {
+        use super::*;
+
+        /// Verifies elements are sorted by name, case-insensitively
+        #[test]
+        fn elements_sorted_by_name() {
+            let (diagram, ids) = sample_diagram();
+            let data = MatrixData::new(&diagram, false);
+            assert_eq!(data.elements, vec![ids[1], ids[2], ids[0], ids[3]]);
+        }
+
+        /// Verifies parallel relationships share a cell and mutual pairs are detected
+        #[test]
+        fn cells_group_relationships() {
+            let (diagram, ids) = sample_diagram();
+            let data = MatrixData::new(&diagram, false);
+            assert_eq!(data.cell(ids[0], ids[2]), &[1, 3]);
+            assert_eq!(data.cell(ids[1], ids[0]), &[0]);
+            assert!(data.cell(ids[0], ids[1]).is_empty());
+            assert!(data.is_mutual(ids[0], ids[2]));
+            assert!(!data.is_mutual(ids[1], ids[0]));
+        }
+
+        /// Verifies unconnected elements can be hidden
+        #[test]
+        fn connected_only_hides_unconnected() {
+            let (diagram, ids) = sample_diagram();
+            let data = MatrixData::new(&diagram, true);
+            assert!(!data.elements.contains(&ids[3]));
+            assert_eq!(data.elements.len(), 3);
+        }
+    }
+}
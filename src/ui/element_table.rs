@@ -0,0 +1,321 @@
+use crate::model::{Diagram, DiagramEvent, ElementId, ElementType};
+use crate::style::ElementKind;
+use egui::{Color32, Ui};
+use std::collections::HashSet;
+
+/// Spreadsheet-like list of all elements with inline editing and bulk operations
+pub struct ElementTable {
+    pub filter: String,
+    /// Rows ticked for bulk operations
+    pub selected: HashSet<ElementId>,
+    /// Kind applied by "Set Type"
+    pub bulk_kind: ElementKind,
+    /// Technology applied by "Set Technology"
+    pub bulk_technology: String,
+}
+
+impl Default for ElementTable {
+    fn default() -> Self {
+        Self {
+            filter: String::new(),
+            selected: HashSet::new(),
+            bulk_kind: ElementKind::Container,
+            bulk_technology: String::new(),
+        }
+    }
+}
+
+fn technology(element_type: &ElementType) -> &str {
+    element_type.technology().unwrap_or("")
+}
+
+impl ElementTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Elements matching the filter, sorted by name
+    ///
+    /// The filter is a case-insensitive substring match against the name,
+    /// type, technology and description.
+    pub fn visible_rows(&self, diagram: &Diagram) -> Vec<ElementId> {
+        let filter = self.filter.trim().to_lowercase();
+        let mut rows: Vec<ElementId> = diagram
+            .elements
+            .values()
+            .filter(|element| {
+                filter.is_empty()
+                    || [
+                        element.name(),
+                        ElementKind::of(element).as_str(),
+                        technology(&element.element_type),
+                        element.description(),
+                    ]
+                    .iter()
+                    .any(|field| field.to_lowercase().contains(&filter))
+            })
+            .map(|element| element.id)
+            .collect();
+        rows.sort_by_cached_key(|id| (diagram.elements[id].name().to_lowercase(), *id));
+        rows
+    }
+
+    /// Change the kind of every selected element
+    pub fn set_kind(&self, diagram: &mut Diagram, kind: ElementKind) {
+        for &id in &self.selected {
+            if diagram.get_element(id).is_some_and(|e| ElementKind::of(e) != kind) {
+                diagram.update_element(id, |e| e.element_type = kind.convert(&e.element_type));
+            }
+        }
+    }
+
+    /// Set the technology of every selected container and component
+    pub fn set_technology(&self, diagram: &mut Diagram, technology: &str) {
+        for &id in &self.selected {
+            let has_technology = diagram
+                .get_element(id)
+                .is_some_and(|e| e.element_type.technology().is_some());
+            if has_technology {
+                diagram.update_element(id, |e| {
+                    if let Some(current) = e.element_type.technology_mut() {
+                        *current = technology.to_string();
+                    }
+                });
+            }
+        }
+    }
+
+    /// Delete every selected element and its relationships
+    pub fn delete_selected(&mut self, diagram: &mut Diagram) {
+        for id in self.selected.drain() {
+            diagram.remove_element(id);
+        }
+    }
+
+    /// Render the table; edits are applied to the diagram directly
+    pub fn render(&mut self, ui: &mut Ui, diagram: &mut Diagram, selected_element: &mut Option<ElementId>) {
+        self.selected.retain(|id| diagram.elements.contains_key(id));
+
+        ui.horizontal(|ui| {
+            ui.heading("Elements");
+            ui.separator();
+            ui.label("Filter");
+            ui.text_edit_singleline(&mut self.filter);
+        });
+
+        let rows = self.visible_rows(diagram);
+        ui.horizontal_wrapped(|ui| {
+            let all_selected = !rows.is_empty() && rows.iter().all(|id| self.selected.contains(id));
+            if ui.button(if all_selected { "Select None" } else { "Select All" }).clicked() {
+                if all_selected {
+                    self.selected.clear();
+                } else {
+                    self.selected.extend(rows.iter().copied());
+                }
+            }
+            ui.label(format!("{} selected", self.selected.len()));
+            ui.separator();
+
+            let has_selection = !self.selected.is_empty();
+            egui::ComboBox::from_id_salt("element_table_kind")
+                .selected_text(self.bulk_kind.as_str())
+                .show_ui(ui, |ui| {
+                    for kind in ElementKind::ALL {
+                        ui.selectable_value(&mut self.bulk_kind, kind, kind.as_str());
+                    }
+                });
+            if ui.add_enabled(has_selection, egui::Button::new("Set Type"))
+                .on_hover_text("Change the type of the selected elements")
+                .clicked()
+            {
+                self.set_kind(diagram, self.bulk_kind);
+            }
+            ui.separator();
+            ui.add(egui::TextEdit::singleline(&mut self.bulk_technology).desired_width(100.0).hint_text("Technology"));
+            if ui.add_enabled(has_selection, egui::Button::new("Set Technology"))
+                .on_hover_text("Set the technology of the selected containers and components")
+                .clicked()
+            {
+                self.set_technology(diagram, &self.bulk_technology);
+            }
+            ui.separator();
+            if ui.add_enabled(has_selection, egui::Button::new("🗑 Delete"))
+                .on_hover_text("Delete the selected elements and their relationships")
+                .clicked()
+            {
+                self.delete_selected(diagram);
+            }
+        });
+        ui.separator();
+
+        if rows.is_empty() {
+            ui.colored_label(Color32::from_gray(120), "No elements");
+            return;
+        }
+
+        let mut updated = Vec::new();
+        let mut retyped = Vec::new();
+        egui::ScrollArea::both().show(ui, |ui| {
+            egui::Grid::new("element_table_grid")
+                .num_columns(6)
+                .striped(true)
+                .show(ui, |ui| {
+                    for header in ["", "Name", "Type", "Technology", "Description", ""] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+
+                    for &id in &rows {
+                        let Some(element) = diagram.elements.get_mut(&id) else {
+                            continue;
+                        };
+                        let mut ticked = self.selected.contains(&id);
+                        if ui.checkbox(&mut ticked, "").changed() {
+                            if ticked {
+                                self.selected.insert(id);
+                            } else {
+                                self.selected.remove(&id);
+                            }
+                        }
+
+                        let mut changed = false;
+                        let mut name = element.name().to_string();
+                        if ui.text_edit_singleline(&mut name).changed() {
+                            element.set_name(name);
+                            changed = true;
+                        }
+
+                        let kind = ElementKind::of(element);
+                        let mut new_kind = kind;
+                        egui::ComboBox::from_id_salt(("element_table_row_kind", id))
+                            .selected_text(kind.as_str())
+                            .show_ui(ui, |ui| {
+                                for kind in ElementKind::ALL {
+                                    ui.selectable_value(&mut new_kind, kind, kind.as_str());
+                                }
+                            });
+                        if new_kind != kind {
+                            retyped.push((id, new_kind));
+                        }
+
+                        match element.element_type.technology_mut() {
+                            Some(technology) => {
+                                changed |= ui.text_edit_singleline(technology).changed();
+                            }
+                            None => {
+                                ui.label("");
+                            }
+                        }
+
+                        let mut description = element.description().to_string();
+                        if ui.text_edit_singleline(&mut description).changed() {
+                            element.set_description(description);
+                            changed = true;
+                        }
+                        if changed {
+                            updated.push(id);
+                        }
+
+                        if ui.small_button("⌖").on_hover_text("Select on the canvas").clicked() {
+                            *selected_element = Some(id);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+        for id in updated {
+            diagram.emit(DiagramEvent::ElementUpdated(id));
+        }
+        for (id, kind) in retyped {
+            diagram.update_element(id, |e| e.element_type = kind.convert(&e.element_type));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContainerType, DiagramType, Element, Position};
+
+    fn sample_diagram() -> (Diagram, Vec<ElementId>) {
+        let mut diagram = Diagram::new("Test", "", DiagramType::Container);
+        let element_types = [
+            ElementType::container("Web", "Storefront", ContainerType::WebApplication, "React"),
+            ElementType::person("alice", "Customer"),
+            ElementType::container("Orders", "Order data", ContainerType::Database, "PostgreSQL"),
+        ];
+        let ids = element_types
+            .into_iter()
+            .map(|element_type| {
+                let element = Element::new(element_type, Position::new(0.0, 0.0));
+                let id = element.id;
+                diagram.add_element(element);
+                id
+            })
+            .collect();
+        diagram.take_events();
+        (diagram, ids)
+    }
+
+    mod visible_rows_tests {
+        use super::*;
+
+        /// Verifies rows are sorted by name and filtered on every column
+        #[test]
+        fn visible_rows_sorts_and_filters() {
+            let (diagram, ids) = sample_diagram();
+            let mut table = ElementTable::new();
+            assert_eq!(table.visible_rows(&diagram), vec![ids[1], ids[2], ids[0]]);
+
+            table.filter = "postgres".to_string();
+            assert_eq!(table.visible_rows(&diagram), vec![ids[2]]);
+
+            table.filter = "PERSON".to_string();
+            assert_eq!(table.visible_rows(&diagram), vec![ids[1]]);
+        }
+    }
+
+    mod bulk_tests {
+        use super::*;
+
+        /// Verifies the technology is only set on selected containers
+        #[test]
+        fn set_technology_on_containers() {
+            let (mut diagram, ids) = sample_diagram();
+            let mut table = ElementTable::new();
+            table.selected.extend([ids[0], ids[1]]);
+            table.set_technology(&mut diagram, "Vue");
+
+            assert_eq!(technology(&diagram.elements[&ids[0]].element_type), "Vue");
+            assert_eq!(technology(&diagram.elements[&ids[2]].element_type), "PostgreSQL");
+            assert_eq!(diagram.take_events(), vec![DiagramEvent::ElementUpdated(ids[0])]);
+        }
+
+        /// Verifies the kind of selected elements can be changed together
+        #[test]
+        fn set_kind_converts_selected() {
+            let (mut diagram, ids) = sample_diagram();
+            let mut table = ElementTable::new();
+            table.selected.extend([ids[0], ids[2]]);
+            table.set_kind(&mut diagram, ElementKind::Queue);
+
+            assert_eq!(ElementKind::of(&diagram.elements[&ids[0]]), ElementKind::Queue);
+            assert_eq!(ElementKind::of(&diagram.elements[&ids[2]]), ElementKind::Queue);
+            assert_eq!(diagram.elements[&ids[0]].name(), "Web");
+        }
+
+        /// Verifies deleting removes the selected elements and clears the selection
+        #[test]
+        fn delete_selected_removes_elements() {
+            let (mut diagram, ids) = sample_diagram();
+            let mut table = ElementTable::new();
+            table.selected.insert(ids[1]);
+            table.delete_selected(&mut diagram);
+
+            assert!(diagram.get_element(ids[1]).is_none());
+            assert_eq!(diagram.elements.len(), 2);
+            assert!(table.selected.is_empty());
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Keyboard bindings of the editor
+//!
+//! Every key the app reacts to is listed in [`BINDINGS`]. The handlers look
+//! their keys up here and the shortcut cheat-sheet is generated from the same
+//! table, so what the sheet says is what the keys do.
+
+use egui::{Key, KeyboardShortcut, Modifiers};
+
+/// Something a key binding does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Command {
+    Undo,
+    Redo,
+    NextElement,
+    PreviousElement,
+    SelectLeft,
+    SelectRight,
+    SelectUp,
+    SelectDown,
+    BringToFront,
+    SendToBack,
+    ShowShortcuts,
+}
+
+impl Command {
+    /// Canvas navigation keys, taken before egui can move widget focus with them
+    pub fn is_navigation(&self) -> bool {
+        matches!(
+            self,
+            Command::NextElement
+                | Command::PreviousElement
+                | Command::SelectLeft
+                | Command::SelectRight
+                | Command::SelectUp
+                | Command::SelectDown
+        )
+    }
+}
+
+/// A key combination and what it does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Binding {
+    pub command: Command,
+    pub shortcut: KeyboardShortcut,
+    /// Heading the binding is listed under in the cheat-sheet
+    pub category: &'static str,
+    pub description: &'static str,
+}
+
+const fn bind(command: Command, modifiers: Modifiers, key: Key, category: &'static str, description: &'static str) -> Binding {
+    Binding {
+        command,
+        shortcut: KeyboardShortcut::new(modifiers, key),
+        category,
+        description,
+    }
+}
+
+/// All key bindings, in cheat-sheet order
+///
+/// Shortcuts are matched in this order, so Ctrl+Shift+Z comes before
+/// Ctrl+Z, which would otherwise match it too.
+pub const BINDINGS: [Binding; 12] = [
+    bind(Command::Redo, Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z, "Edit", "Redo"),
+    bind(Command::Redo, Modifiers::COMMAND, Key::Y, "Edit", "Redo"),
+    bind(Command::Undo, Modifiers::COMMAND, Key::Z, "Edit", "Undo"),
+    bind(Command::NextElement, Modifiers::NONE, Key::Tab, "Canvas", "Select the next element"),
+    bind(Command::PreviousElement, Modifiers::SHIFT, Key::Tab, "Canvas", "Select the previous element"),
+    bind(Command::SelectLeft, Modifiers::NONE, Key::ArrowLeft, "Canvas", "Select the nearest element to the left"),
+    bind(Command::SelectRight, Modifiers::NONE, Key::ArrowRight, "Canvas", "Select the nearest element to the right"),
+    bind(Command::SelectUp, Modifiers::NONE, Key::ArrowUp, "Canvas", "Select the nearest element above"),
+    bind(Command::SelectDown, Modifiers::NONE, Key::ArrowDown, "Canvas", "Select the nearest element below"),
+    bind(Command::BringToFront, Modifiers::COMMAND, Key::CloseBracket, "Canvas", "Bring the selected element to the front"),
+    bind(Command::SendToBack, Modifiers::COMMAND, Key::OpenBracket, "Canvas", "Send the selected element to the back"),
+    bind(Command::ShowShortcuts, Modifiers::NONE, Key::Questionmark, "Help", "Show or hide this list"),
+];
+
+/// Pointer gestures on the canvas as (input, description), listed with the keys
+pub const GESTURES: [(&str, &str); 8] = [
+    ("Ctrl+Scroll or pinch", "Zoom around the pointer"),
+    ("Drag empty space", "Pan the view"),
+    ("Drag an element", "Move it"),
+    ("Shift while dragging a relationship end", "Turn off angle snapping"),
+    ("Drag the handle halfway along a relationship", "Add a bend (selected element's relationships)"),
+    ("Double-click a bend", "Remove it"),
+    ("Right-click an element", "Bring it to the front or send it to the back"),
+    ("Right-click", "Comment on a point (with comments shown)"),
+];
+
+/// The first shortcut bound to `command`, for menus and tooltips
+pub fn shortcut(command: Command) -> KeyboardShortcut {
+    BINDINGS
+        .iter()
+        .find(|b| b.command == command)
+        .map(|b| b.shortcut)
+        .expect("every command has a binding")
+}
+
+/// The navigation command for a key press; modifiers must match exactly
+pub fn navigation_for(key: Key, modifiers: Modifiers) -> Option<Command> {
+    BINDINGS
+        .iter()
+        .find(|b| b.command.is_navigation() && b.shortcut.logical_key == key && b.shortcut.modifiers == modifiers)
+        .map(|b| b.command)
+}
+
+/// One line of the cheat-sheet: a command and every key bound to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheatSheetRow {
+    pub command: Command,
+    pub shortcuts: Vec<KeyboardShortcut>,
+    pub description: &'static str,
+}
+
+/// Bindings grouped by category, in table order
+pub fn cheat_sheet() -> Vec<(&'static str, Vec<CheatSheetRow>)> {
+    let mut groups: Vec<(&'static str, Vec<CheatSheetRow>)> = Vec::new();
+    for binding in &BINDINGS {
+        let index = match groups.iter().position(|(category, _)| *category == binding.category) {
+            Some(index) => index,
+            None => {
+                groups.push((binding.category, Vec::new()));
+                groups.len() - 1
+            }
+        };
+        let rows = &mut groups[index].1;
+        match rows.iter_mut().find(|row| row.command == binding.command) {
+            Some(row) => row.shortcuts.push(binding.shortcut),
+            None => rows.push(CheatSheetRow {
+                command: binding.command,
+                shortcuts: vec![binding.shortcut],
+                description: binding.description,
+            }),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod binding_tests {
+        use super::*;
+
+        /// Verifies no two bindings share a key combination
+        #[test]
+        fn shortcuts_are_unique() {
+            for (i, a) in BINDINGS.iter().enumerate() {
+                assert!(BINDINGS[i + 1..].iter().all(|b| b.shortcut != a.shortcut), "{:?} bound twice", a.shortcut);
+            }
+        }
+
+        /// Verifies navigation keys need their exact modifiers
+        #[test]
+        fn navigation_matches_exactly() {
+            assert_eq!(navigation_for(Key::Tab, Modifiers::SHIFT), Some(Command::PreviousElement));
+            assert_eq!(navigation_for(Key::Tab, Modifiers::NONE), Some(Command::NextElement));
+            assert_eq!(navigation_for(Key::ArrowLeft, Modifiers::CTRL), None);
+            assert_eq!(navigation_for(Key::Z, Modifiers::COMMAND), None);
+        }
+
+        /// Verifies the cheat-sheet groups by category and lists a command's keys together
+        #[test]
+        fn cheat_sheet_groups_bindings() {
+            let sheet = cheat_sheet();
+            let categories: Vec<&str> = sheet.iter().map(|(category, _)| *category).collect();
+            assert_eq!(categories, vec!["Edit", "Canvas", "Help"]);
+            assert_eq!(sheet[0].1[0].shortcuts.len(), 2);
+            assert_eq!(sheet[0].1[1].description, "Undo");
+            assert_eq!(shortcut(Command::Undo), KeyboardShortcut::new(Modifiers::COMMAND, Key::Z));
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::model::{Degree, Diagram, ElementId, RelationshipGraph};
+use egui::{Color32, RichText, Ui};
+
+/// Column the metrics panel is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricsColumn {
+    Name,
+    FanIn,
+    FanOut,
+    Total,
+}
+
+impl MetricsColumn {
+    pub const ALL: [MetricsColumn; 4] = [
+        MetricsColumn::Name,
+        MetricsColumn::FanIn,
+        MetricsColumn::FanOut,
+        MetricsColumn::Total,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricsColumn::Name => "Element",
+            MetricsColumn::FanIn => "In",
+            MetricsColumn::FanOut => "Out",
+            MetricsColumn::Total => "Total",
+        }
+    }
+}
+
+/// Incoming and outgoing relationship counts per element, to spot elements
+/// everything depends on and elements nothing uses
+pub struct MetricsPanel {
+    pub sort_by: MetricsColumn,
+    pub ascending: bool,
+}
+
+impl Default for MetricsPanel {
+    fn default() -> Self {
+        Self {
+            sort_by: MetricsColumn::Total,
+            ascending: false,
+        }
+    }
+}
+
+impl MetricsPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every element with its degree, in display order
+    ///
+    /// Ties are broken by name so the order is stable.
+    pub fn rows(&self, diagram: &Diagram) -> Vec<(ElementId, Degree)> {
+        let mut rows: Vec<(ElementId, Degree)> = RelationshipGraph::new(diagram).degrees().into_iter().collect();
+        let name = |id: &ElementId| (diagram.elements[id].name().to_lowercase(), *id);
+        rows.sort_by_cached_key(|(id, _)| name(id));
+        let count = |degree: &Degree| match self.sort_by {
+            MetricsColumn::Name => 0,
+            MetricsColumn::FanIn => degree.fan_in,
+            MetricsColumn::FanOut => degree.fan_out,
+            MetricsColumn::Total => degree.fan_in + degree.fan_out,
+        };
+        if self.sort_by == MetricsColumn::Name {
+            if !self.ascending {
+                rows.reverse();
+            }
+        } else if self.ascending {
+            rows.sort_by_key(|(_, degree)| count(degree));
+        } else {
+            rows.sort_by_key(|(_, degree)| std::cmp::Reverse(count(degree)));
+        }
+        rows
+    }
+
+    /// Render the table; clicking a row selects its element
+    pub fn render(&mut self, ui: &mut Ui, diagram: &Diagram, selected_element: &mut Option<ElementId>) {
+        let rows = self.rows(diagram);
+        if rows.is_empty() {
+            ui.colored_label(Color32::from_gray(120), "No elements");
+            return;
+        }
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("metrics_grid")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    for column in MetricsColumn::ALL {
+                        let mut header = column.as_str().to_string();
+                        if column == self.sort_by {
+                            header.push_str(if self.ascending { " ⬆" } else { " ⬇" });
+                        }
+                        if ui.selectable_label(column == self.sort_by, RichText::new(header).strong())
+                            .on_hover_text("Sort by this column; click again to reverse")
+                            .clicked()
+                        {
+                            if column == self.sort_by {
+                                self.ascending = !self.ascending;
+                            } else {
+                                self.sort_by = column;
+                                self.ascending = column == MetricsColumn::Name;
+                            }
+                        }
+                    }
+                    ui.end_row();
+
+                    for (id, degree) in rows {
+                        let mut name = RichText::new(diagram.elements[&id].name());
+                        if degree == Degree::default() {
+                            name = name.color(Color32::from_gray(140));
+                        }
+                        if ui.selectable_label(*selected_element == Some(id), name).clicked() {
+                            *selected_element = Some(id);
+                        }
+                        ui.label(degree.fan_in.to_string());
+                        ui.label(degree.fan_out.to_string());
+                        ui.label((degree.fan_in + degree.fan_out).to_string());
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, Element, ElementType, Position, Relationship};
+
+    fn sample_diagram() -> (Diagram, Vec<ElementId>) {
+        let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+        let ids: Vec<ElementId> = ["Catalog", "alice", "Billing", "Lonely"]
+            .iter()
+            .map(|name| {
+                let element = Element::new(ElementType::system(*name, ""), Position::new(0.0, 0.0));
+                let id = element.id;
+                diagram.add_element(element);
+                id
+            })
+            .collect();
+        diagram.add_relationship(Relationship::new(ids[1], ids[0], "browses"));
+        diagram.add_relationship(Relationship::new(ids[2], ids[0], "updates"));
+        diagram.add_relationship(Relationship::new(ids[0], ids[2], "charges"));
+        diagram.add_relationship(Relationship::new(ids[1], ids[2], "pays"));
+        (diagram, ids)
+    }
+
+    mod rows_tests {
+        use super::*;
+
+        /// Verifies the default order puts the most connected elements first, ties by name
+        #[test]
+        fn default_sorts_by_total_descending() {
+            let (diagram, ids) = sample_diagram();
+            let rows = MetricsPanel::new().rows(&diagram);
+            let order: Vec<ElementId> = rows.iter().map(|(id, _)| *id).collect();
+            assert_eq!(order, vec![ids[2], ids[0], ids[1], ids[3]]);
+            assert_eq!(rows[1].1, Degree { fan_in: 2, fan_out: 1 });
+            assert_eq!(rows[3].1, Degree::default());
+        }
+
+        /// Verifies sorting by a single direction and by name
+        #[test]
+        fn sort_by_column() {
+            let (diagram, ids) = sample_diagram();
+            let mut panel = MetricsPanel {
+                sort_by: MetricsColumn::FanOut,
+                ascending: false,
+            };
+            assert_eq!(panel.rows(&diagram)[0].0, ids[1]);
+
+            panel.sort_by = MetricsColumn::Name;
+            panel.ascending = true;
+            let order: Vec<ElementId> = panel.rows(&diagram).iter().map(|(id, _)| *id).collect();
+            assert_eq!(order, vec![ids[1], ids[2], ids[0], ids[3]]);
+        }
+    }
+}
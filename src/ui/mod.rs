@@ -1,62 +1,368 @@
+pub mod api_suggestions;
 pub mod canvas;
-
-use crate::model::{ContainerType, Element, ElementType, Position};
+pub mod comments;
+pub mod dependency_matrix;
+pub mod element_table;
+pub mod keymap;
+pub mod metrics_panel;
+pub mod relationship_list;
+pub mod text_cache;
+
+pub use crate::model::default_element_position;
+
+use crate::export::highlight::{self, TokenKind};
+use crate::export::ExportFormat;
+use crate::model::{Anchor, CloudIcon, ContainerType, Direction, Element, ElementId, ElementType, IconPack, LineStyle, Side, SystemKind};
+use crate::project::Project;
+use crate::style::{BorderStyle, Color, ElementStyle, ResolvedStyle, Stylesheet};
 use egui::{Color32, Rect, Response, StrokeKind, Ui};
+use std::path::Path;
 
-/// Get default position for new elements
-pub fn default_element_position(index: usize) -> Position {
-    let col = index % 3;
-    let row = index / 3;
-    Position::new(50.0 + col as f32 * 200.0, 50.0 + row as f32 * 150.0)
+/// Convert a stylesheet colour for painting
+pub fn color32(color: Color) -> Color32 {
+    let [r, g, b] = color.0;
+    Color32::from_rgb(r, g, b)
 }
 
-/// Get colors for an element based on its type and selection state
-pub fn element_colors(element: &Element, is_selected: bool) -> (Color32, Color32) {
+/// Background and border colours for a resolved style and selection state
+pub fn style_colors(style: &ResolvedStyle, is_selected: bool) -> (Color32, Color32) {
     let border = if is_selected {
         Color32::from_rgb(0, 120, 215)
     } else {
-        Color32::from_gray(150)
+        color32(style.border)
     };
+    (color32(style.background), border)
+}
 
-    let bg = match &element.element_type {
-        ElementType::Person(data) => {
-            if data.is_external {
-                Color32::from_rgb(255, 240, 220)
-            } else {
-                Color32::from_rgb(255, 220, 180)
-            }
-        }
-        ElementType::SoftwareSystem(data) => {
-            if data.is_external {
-                Color32::from_rgb(230, 230, 230)
-            } else {
-                Color32::from_rgb(200, 220, 255)
-            }
-        }
-        ElementType::Container(data) => {
-            match data.container_type {
-                ContainerType::Database => Color32::from_rgb(200, 255, 200),
-                ContainerType::Queue => Color32::from_rgb(255, 255, 200),
-                _ => Color32::from_rgb(220, 240, 255),
-            }
-        }
-    };
+/// Get colors for an element based on its type, own style and selection state
+pub fn element_colors(element: &Element, is_selected: bool) -> (Color32, Color32) {
+    style_colors(&Stylesheet::default().element_style(element), is_selected)
+}
 
-    (bg, border)
+/// Colour and border overrides for one element
+///
+/// Each colour is off until ticked, starting from `base`, the look the
+/// element would otherwise have. Returns true if the style was changed.
+pub fn element_style_edit(ui: &mut Ui, id_salt: impl std::hash::Hash, style: &mut ElementStyle, base: &ResolvedStyle) -> bool {
+    let mut changed = false;
+    ui.push_id(id_salt, |ui| {
+        for (label, color, fallback) in [
+            ("Fill", &mut style.background, base.background),
+            ("Border", &mut style.border, base.border),
+            ("Text", &mut style.text, base.text),
+        ] {
+            ui.horizontal(|ui| {
+                let mut custom = color.is_some();
+                if ui.checkbox(&mut custom, label).changed() {
+                    *color = custom.then_some(fallback);
+                    changed = true;
+                }
+                if let Some(Color(rgb)) = color {
+                    changed |= ui.color_edit_button_srgb(rgb).changed();
+                }
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label("Border Line");
+            egui::ComboBox::from_id_salt("border_style")
+                .selected_text(style.border_style.map_or("Default", |s| s.as_str()))
+                .show_ui(ui, |ui| {
+                    changed |= ui.selectable_value(&mut style.border_style, None, "Default").changed();
+                    for border_style in BorderStyle::ALL {
+                        changed |= ui
+                            .selectable_value(&mut style.border_style, Some(border_style), border_style.as_str())
+                            .changed();
+                    }
+                });
+        });
+    });
+    changed
 }
 
 /// Get icon for element type
 pub fn get_element_icon(element: &Element) -> &'static str {
     match &element.element_type {
         ElementType::Person(_) => "👤",
-        ElementType::SoftwareSystem(_) => "🖥️",
+        ElementType::SoftwareSystem(data) => match data.kind {
+            SystemKind::Default => "🖥️",
+            SystemKind::Database => "🗄️",
+            SystemKind::Queue => "📨",
+        },
         ElementType::Container(data) => match data.container_type {
             ContainerType::Database => "🗄️",
             ContainerType::MobileApp => "📱",
             ContainerType::Queue => "📨",
             _ => "📦",
         },
+        ElementType::Component(_) => "🧩",
+        ElementType::Note(_) => "📝",
+    }
+}
+
+/// Side and offset picker for one end of a relationship
+///
+/// "Auto" clears the anchor so the nearest edge point is used. Returns true
+/// if the anchor was changed.
+pub fn anchor_picker(ui: &mut Ui, id_salt: impl std::hash::Hash, anchor: &mut Option<Anchor>) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt(id_salt)
+            .selected_text(anchor.map_or("Auto", |a| a.side.as_str()))
+            .show_ui(ui, |ui| {
+                changed |= ui.selectable_value(anchor, None, "Auto").changed();
+                for side in Side::ALL {
+                    let selected = anchor.is_some_and(|a| a.side == side);
+                    if ui.selectable_label(selected, side.as_str()).clicked() && !selected {
+                        *anchor = Some(Anchor::new(side));
+                        changed = true;
+                    }
+                }
+            });
+        if let Some(anchor) = anchor {
+            changed |= ui
+                .add(egui::Slider::new(&mut anchor.offset, 0.0..=1.0).show_value(false))
+                .on_hover_text("Position along the side")
+                .changed();
+        }
+    });
+    changed
+}
+
+/// Line style picker for one relationship
+///
+/// "Default" clears the override so the canvas-wide style is used. Returns
+/// true if the style was changed.
+pub fn line_style_picker(ui: &mut Ui, id_salt: impl std::hash::Hash, line_style: &mut Option<LineStyle>) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(line_style.map_or("Default line", |s| s.as_str()))
+        .show_ui(ui, |ui| {
+            changed |= ui.selectable_value(line_style, None, "Default line").changed();
+            for style in LineStyle::ALL {
+                changed |= ui.selectable_value(line_style, Some(style), style.as_str()).changed();
+            }
+        });
+    changed
+}
+
+/// Arrowhead picker for one relationship: forward, back or both ways
+///
+/// Returns true if the direction was changed.
+pub fn direction_picker(ui: &mut Ui, id_salt: impl std::hash::Hash, direction: &mut Direction) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(direction.as_str())
+        .show_ui(ui, |ui| {
+            for option in Direction::ALL {
+                changed |= ui
+                    .selectable_value(direction, option, option.as_str())
+                    .on_hover_text(format!("Exported as {}", option.macro_name()))
+                    .changed();
+            }
+        });
+    changed
+}
+
+/// Dropdown for a container's type, with a text field for custom types
+///
+/// Returns true if the type was changed.
+pub fn container_type_picker(ui: &mut Ui, id_salt: impl std::hash::Hash, container_type: &mut ContainerType) -> bool {
+    let mut changed = false;
+    let is_other = matches!(container_type, ContainerType::Other(_));
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(if is_other { "Other" } else { container_type.as_str() })
+        .show_ui(ui, |ui| {
+            for standard in ContainerType::STANDARD {
+                let label = standard.as_str().to_string();
+                changed |= ui.selectable_value(container_type, standard, label).changed();
+            }
+            if ui.selectable_label(is_other, "Other").clicked() && !is_other {
+                *container_type = ContainerType::Other(String::new());
+                changed = true;
+            }
+        });
+    if let ContainerType::Other(custom) = container_type {
+        changed |= ui
+            .add(egui::TextEdit::singleline(custom).hint_text("Custom type"))
+            .changed();
+    }
+    changed
+}
+
+/// Dropdown choosing the element another one is part of, from `(id, name)` options
+///
+/// Returns true if the choice was changed.
+pub fn parent_picker(
+    ui: &mut Ui,
+    id_salt: impl std::hash::Hash,
+    parent: &mut Option<ElementId>,
+    options: &[(ElementId, String)],
+) -> bool {
+    let mut changed = false;
+    let current = parent
+        .and_then(|owner| options.iter().find(|(id, _)| *id == owner))
+        .map_or("None", |(_, name)| name.as_str());
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(current)
+        .show_ui(ui, |ui| {
+            changed |= ui.selectable_value(parent, None, "None").changed();
+            for (id, name) in options {
+                changed |= ui.selectable_value(parent, Some(*id), name).changed();
+            }
+        });
+    changed
+}
+
+/// Dropdown choosing a container's cloud icon from the enabled packs
+///
+/// The current icon stays selectable even if its pack is not enabled.
+/// Returns true if it was changed.
+pub fn cloud_icon_picker(
+    ui: &mut Ui,
+    id_salt: impl std::hash::Hash,
+    icon: &mut Option<CloudIcon>,
+    packs: &[IconPack],
+) -> bool {
+    let mut changed = false;
+    let selected = icon
+        .as_ref()
+        .map_or_else(|| "None".to_string(), |icon| format!("{}: {}", icon.pack.as_str(), icon.label()));
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            if ui.selectable_label(icon.is_none(), "None").clicked() && icon.is_some() {
+                *icon = None;
+                changed = true;
+            }
+            for pack in IconPack::ALL {
+                if !packs.contains(&pack) && icon.as_ref().is_none_or(|icon| icon.pack != pack) {
+                    continue;
+                }
+                ui.separator();
+                ui.weak(pack.as_str());
+                for def in pack.icons() {
+                    let option = Some(CloudIcon::new(pack, def.sprite));
+                    changed |= ui.selectable_value(icon, option, def.label).changed();
+                }
+            }
+        });
+    changed
+}
+
+/// Dropdown linking an element to another diagram file in the project
+///
+/// The link is stored in `url` relative to `current_file`. Returns true if it
+/// was changed.
+pub fn diagram_link_picker(
+    ui: &mut Ui,
+    id_salt: impl std::hash::Hash,
+    url: &mut Option<String>,
+    project: &Project,
+    current_file: &Path,
+) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(url.as_deref().unwrap_or("None"))
+        .show_ui(ui, |ui| {
+            if ui.selectable_label(url.is_none(), "None").clicked() && url.is_some() {
+                *url = None;
+                changed = true;
+            }
+            for file in &project.files {
+                let path = project.absolute(file);
+                if path == current_file {
+                    continue;
+                }
+                let link = crate::project::relative_link(current_file, &path);
+                let selected = url.as_deref() == Some(link.as_str());
+                if ui.selectable_label(selected, file.display().to_string()).clicked() && !selected {
+                    *url = Some(link);
+                    changed = true;
+                }
+            }
+        });
+    changed
+}
+
+/// Text field for an optional link; clearing it removes the link
+///
+/// Returns true if the link was changed.
+pub fn url_edit(ui: &mut Ui, id_salt: impl std::hash::Hash, url: &mut Option<String>) -> bool {
+    let mut text = url.clone().unwrap_or_default();
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut text)
+            .id_salt(id_salt)
+            .hint_text("https://..."),
+    );
+    if !response.changed() {
+        return false;
+    }
+    *url = (!text.trim().is_empty()).then_some(text);
+    true
+}
+
+/// Comma-separated text field for a list of tags
+///
+/// The raw text is kept while the field has focus so separators can be
+/// typed; blank entries are dropped. Returns true if the tags were changed.
+pub fn tags_edit(ui: &mut Ui, id_salt: impl std::hash::Hash, tags: &mut Vec<String>) -> bool {
+    let id = ui.make_persistent_id(id_salt);
+    let mut text = ui
+        .data(|d| d.get_temp::<String>(id))
+        .unwrap_or_else(|| tags.join(", "));
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut text)
+            .id(id)
+            .hint_text("Tags (comma-separated)"),
+    );
+    if response.has_focus() {
+        ui.data_mut(|d| d.insert_temp(id, text.clone()));
+    } else {
+        ui.data_mut(|d| d.remove::<String>(id));
+    }
+    if !response.changed() {
+        return false;
     }
+    *tags = text
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect();
+    true
+}
+
+/// Read-only, syntax-highlighted view of exported diagram source
+///
+/// The text can still be selected and copied.
+pub fn code_view(ui: &mut Ui, format: ExportFormat, text: &str) -> Response {
+    let mut layouter = |ui: &Ui, buffer: &dyn egui::TextBuffer, wrap_width: f32| {
+        let text = buffer.as_str();
+        let font = egui::TextStyle::Monospace.resolve(ui.style());
+        let visuals = &ui.visuals();
+        let mut job = egui::text::LayoutJob::default();
+        for (range, kind) in highlight::highlight(format, text) {
+            let color = match kind {
+                TokenKind::Text | TokenKind::Punctuation => visuals.text_color(),
+                TokenKind::Comment => Color32::from_rgb(106, 153, 85),
+                TokenKind::Directive => Color32::from_rgb(197, 134, 192),
+                TokenKind::Keyword => Color32::from_rgb(86, 156, 214),
+                TokenKind::String => Color32::from_rgb(206, 145, 120),
+            };
+            job.append(&text[range], 0.0, egui::TextFormat::simple(font.clone(), color));
+        }
+        job.wrap.max_width = wrap_width;
+        ui.fonts_mut(|f| f.layout_job(job))
+    };
+    // A `&str` buffer makes the editor read-only
+    let mut text = text;
+    ui.add(
+        egui::TextEdit::multiline(&mut text)
+            .code_editor()
+            .desired_rows(20)
+            .desired_width(f32::INFINITY)
+            .layouter(&mut layouter),
+    )
 }
 
 #[cfg(test)]
@@ -231,6 +537,20 @@ mod tests {
             let (_, border) = element_colors(&element, true);
             assert_eq!(border, Color32::from_rgb(0, 120, 215)); // Blue selection
         }
+
+        /// Verifies element_colors prefers the element's own colours
+        #[test]
+        fn element_colors_style_override() {
+            let mut element = Element::new(
+                ElementType::person("User", "Description"),
+                Position::new(0.0, 0.0),
+            );
+            element.style.background = Some(Color::rgb(10, 20, 30));
+
+            let (bg, border) = element_colors(&element, false);
+            assert_eq!(bg, Color32::from_rgb(10, 20, 30));
+            assert_eq!(border, Color32::from_gray(150));
+        }
     }
 
     mod get_element_icon_tests {
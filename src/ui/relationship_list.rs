@@ -0,0 +1,255 @@
+use crate::model::{Diagram, DiagramEvent, Relationship};
+use egui::{Color32, Ui};
+use uuid::Uuid;
+
+/// Column the relationship list is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Source,
+    Target,
+    Description,
+    Technology,
+}
+
+impl SortColumn {
+    pub const ALL: [SortColumn; 4] = [
+        SortColumn::Source,
+        SortColumn::Target,
+        SortColumn::Description,
+        SortColumn::Technology,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortColumn::Source => "Source",
+            SortColumn::Target => "Target",
+            SortColumn::Description => "Description",
+            SortColumn::Technology => "Technology",
+        }
+    }
+}
+
+/// Table of all relationships with sorting, filtering, inline editing and delete
+pub struct RelationshipList {
+    pub filter: String,
+    pub sort_by: SortColumn,
+    pub ascending: bool,
+}
+
+impl Default for RelationshipList {
+    fn default() -> Self {
+        Self {
+            filter: String::new(),
+            sort_by: SortColumn::Source,
+            ascending: true,
+        }
+    }
+}
+
+/// Display name of a relationship endpoint
+fn element_name(diagram: &Diagram, id: Uuid) -> &str {
+    diagram.get_element(id).map_or("?", |e| e.name())
+}
+
+fn sort_key(diagram: &Diagram, rel: &Relationship, column: SortColumn) -> String {
+    match column {
+        SortColumn::Source => element_name(diagram, rel.source_id),
+        SortColumn::Target => element_name(diagram, rel.target_id),
+        SortColumn::Description => &rel.description,
+        SortColumn::Technology => rel.technology.as_deref().unwrap_or(""),
+    }
+    .to_lowercase()
+}
+
+impl RelationshipList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indices into `diagram.relationships` matching the filter, in display order
+    ///
+    /// The filter is a case-insensitive substring match against the source
+    /// and target names, description and technology.
+    pub fn visible_rows(&self, diagram: &Diagram) -> Vec<usize> {
+        let filter = self.filter.trim().to_lowercase();
+        let mut rows: Vec<usize> = diagram
+            .relationships
+            .iter()
+            .enumerate()
+            .filter(|(_, rel)| {
+                filter.is_empty()
+                    || SortColumn::ALL
+                        .iter()
+                        .any(|&column| sort_key(diagram, rel, column).contains(&filter))
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        rows.sort_by_cached_key(|&index| sort_key(diagram, &diagram.relationships[index], self.sort_by));
+        if !self.ascending {
+            rows.reverse();
+        }
+        rows
+    }
+
+    /// Render the list; edits and deletions are applied to the diagram directly
+    pub fn render(&mut self, ui: &mut Ui, diagram: &mut Diagram) {
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.text_edit_singleline(&mut self.filter);
+            ui.separator();
+            ui.label("Sort by");
+            egui::ComboBox::from_id_salt("relationship_sort")
+                .selected_text(self.sort_by.as_str())
+                .show_ui(ui, |ui| {
+                    for column in SortColumn::ALL {
+                        ui.selectable_value(&mut self.sort_by, column, column.as_str());
+                    }
+                });
+            let arrow = if self.ascending { "⬆" } else { "⬇" };
+            if ui.small_button(arrow).on_hover_text("Toggle sort order").clicked() {
+                self.ascending = !self.ascending;
+            }
+        });
+        ui.separator();
+
+        let rows = self.visible_rows(diagram);
+        if rows.is_empty() {
+            ui.colored_label(Color32::from_gray(120), "No relationships");
+            return;
+        }
+
+        let names: Vec<(String, String)> = rows
+            .iter()
+            .map(|&index| {
+                let rel = &diagram.relationships[index];
+                (
+                    element_name(diagram, rel.source_id).to_string(),
+                    element_name(diagram, rel.target_id).to_string(),
+                )
+            })
+            .collect();
+
+        let mut updated = Vec::new();
+        let mut reversed = None;
+        let mut deleted = None;
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("relationship_list_grid")
+                .num_columns(5)
+                .striped(true)
+                .show(ui, |ui| {
+                    for column in SortColumn::ALL {
+                        ui.strong(column.as_str());
+                    }
+                    ui.label("");
+                    ui.end_row();
+
+                    for (&index, (source, target)) in rows.iter().zip(&names) {
+                        let rel = &mut diagram.relationships[index];
+                        ui.label(source);
+                        ui.label(target);
+
+                        let mut changed = ui.text_edit_singleline(&mut rel.description).changed();
+                        let mut technology = rel.technology.clone().unwrap_or_default();
+                        if ui.text_edit_singleline(&mut technology).changed() {
+                            rel.technology = (!technology.trim().is_empty()).then_some(technology);
+                            changed = true;
+                        }
+                        if changed {
+                            updated.push(rel.id);
+                        }
+
+                        ui.horizontal(|ui| {
+                            if ui.small_button("⇄").on_hover_text("Reverse direction").clicked() {
+                                reversed = Some(rel.id);
+                            }
+                            if ui.small_button("🗑").on_hover_text("Delete relationship").clicked() {
+                                deleted = Some(rel.id);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+
+        for id in updated {
+            diagram.emit(DiagramEvent::RelationshipUpdated(id));
+        }
+        if let Some(id) = reversed {
+            diagram.reverse_relationship(id);
+        }
+        if let Some(id) = deleted {
+            diagram.remove_relationship(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiagramType, Element, ElementType, Position};
+
+    fn sample_diagram() -> Diagram {
+        let mut diagram = Diagram::new("Test", "", DiagramType::SystemContext);
+        let ids: Vec<Uuid> = ["Alice", "Billing", "Catalog"]
+            .iter()
+            .map(|name| {
+                let element = Element::new(ElementType::system(*name, ""), Position::new(0.0, 0.0));
+                let id = element.id;
+                diagram.add_element(element);
+                id
+            })
+            .collect();
+        diagram.add_relationship(Relationship::with_technology(ids[2], ids[1], "charges", "gRPC"));
+        diagram.add_relationship(Relationship::new(ids[0], ids[2], "browses"));
+        diagram.add_relationship(Relationship::with_technology(ids[1], ids[0], "emails", "SMTP"));
+        diagram
+    }
+
+    mod visible_rows_tests {
+        use super::*;
+
+        /// Verifies rows are sorted by source name in both directions
+        #[test]
+        fn visible_rows_sorts_by_column() {
+            let diagram = sample_diagram();
+            let mut list = RelationshipList::new();
+            assert_eq!(list.visible_rows(&diagram), vec![1, 2, 0]);
+
+            list.ascending = false;
+            assert_eq!(list.visible_rows(&diagram), vec![0, 2, 1]);
+
+            list.ascending = true;
+            list.sort_by = SortColumn::Description;
+            assert_eq!(list.visible_rows(&diagram), vec![1, 0, 2]);
+        }
+
+        /// Verifies missing technologies sort first
+        #[test]
+        fn visible_rows_sorts_missing_technology_first() {
+            let diagram = sample_diagram();
+            let list = RelationshipList {
+                sort_by: SortColumn::Technology,
+                ..Default::default()
+            };
+            assert_eq!(list.visible_rows(&diagram), vec![1, 0, 2]);
+        }
+
+        /// Verifies the filter matches any column case-insensitively
+        #[test]
+        fn visible_rows_filters_all_columns() {
+            let diagram = sample_diagram();
+            let mut list = RelationshipList::new();
+
+            list.filter = "smtp".to_string();
+            assert_eq!(list.visible_rows(&diagram), vec![2]);
+
+            list.filter = "CATALOG".to_string();
+            assert_eq!(list.visible_rows(&diagram), vec![1, 0]);
+
+            list.filter = "nothing".to_string();
+            assert!(list.visible_rows(&diagram).is_empty());
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use egui::{Color32, FontId, Galley, Painter};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Which piece of an element's or relationship's text a galley holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextSlot {
+    Icon,
+    Badge,
+    Name,
+    Description,
+    Label,
+}
+
+/// What a galley was laid out from; any change means it must be laid out again
+struct GalleyKey {
+    source: String,
+    font_size: u32,
+    wrap_width: u32,
+}
+
+struct CachedGalley {
+    key: GalleyKey,
+    galley: Arc<Galley>,
+    last_used: u64,
+}
+
+/// Laid-out canvas text, reused across frames
+///
+/// Entries are keyed by owner (element or relationship ID) and slot, and
+/// remember the source text, font size and wrap width they were laid out
+/// with, so editing text or zooming lays the galley out again. Galleys are
+/// laid out with [`Color32::PLACEHOLDER`], so the colour is chosen when
+/// painting and restyling does not invalidate them. Entries not drawn in the
+/// previous frame are dropped by [`GalleyCache::begin_frame`].
+#[derive(Default)]
+pub struct GalleyCache {
+    entries: HashMap<(Uuid, TextSlot), CachedGalley>,
+    frame: u64,
+}
+
+impl GalleyCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new frame, dropping entries that were not used in the last one
+    pub fn begin_frame(&mut self) {
+        let frame = self.frame;
+        self.entries.retain(|_, entry| entry.last_used == frame);
+        self.frame += 1;
+    }
+
+    /// The galley for `source` in an owner's slot, laid out only if the cached one is stale
+    ///
+    /// `display` turns the source text into the text shown, e.g. truncating
+    /// it; it only runs when the galley is laid out. A `wrap_width` of
+    /// infinity disables wrapping.
+    pub fn get(
+        &mut self,
+        painter: &Painter,
+        (owner, slot): (Uuid, TextSlot),
+        source: &str,
+        font: FontId,
+        wrap_width: f32,
+        display: impl FnOnce(&str) -> String,
+    ) -> Arc<Galley> {
+        let frame = self.frame;
+        let fresh = |entry: &CachedGalley| {
+            entry.key.source == source
+                && entry.key.font_size == font.size.to_bits()
+                && entry.key.wrap_width == wrap_width.to_bits()
+        };
+        if let Some(entry) = self.entries.get_mut(&(owner, slot))
+            && fresh(entry)
+        {
+            entry.last_used = frame;
+            return entry.galley.clone();
+        }
+
+        let key = GalleyKey {
+            source: source.to_string(),
+            font_size: font.size.to_bits(),
+            wrap_width: wrap_width.to_bits(),
+        };
+        let galley = painter.layout(display(source), font, Color32::PLACEHOLDER, wrap_width);
+        self.entries.insert(
+            (owner, slot),
+            CachedGalley {
+                key,
+                galley: galley.clone(),
+                last_used: frame,
+            },
+        );
+        galley
+    }
+
+    /// Number of cached galleys
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `f` with a painter inside a headless egui frame
+    fn with_painter(f: impl FnOnce(&Painter)) {
+        let ctx = egui::Context::default();
+        let mut f = Some(f);
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            let painter = ctx.layer_painter(egui::LayerId::background());
+            if let Some(f) = f.take() {
+                f(&painter);
+            }
+        });
+    }
+
+    mod galley_cache_tests {
+        use super::*;
+
+        /// Verifies an unchanged text reuses the same galley
+        #[test]
+        fn unchanged_text_is_reused() {
+            with_painter(|painter| {
+                let mut cache = GalleyCache::new();
+                let owner = Uuid::new_v4();
+                let font = FontId::proportional(12.0);
+                let first = cache.get(painter, (owner, TextSlot::Name), "Shop", font.clone(), 100.0, str::to_string);
+                let second = cache.get(painter, (owner, TextSlot::Name), "Shop", font, 100.0, |_| unreachable!());
+                assert!(Arc::ptr_eq(&first, &second));
+                assert_eq!(cache.len(), 1);
+            });
+        }
+
+        /// Verifies editing the text or zooming lays the galley out again
+        #[test]
+        fn changed_text_or_size_is_laid_out_again() {
+            with_painter(|painter| {
+                let mut cache = GalleyCache::new();
+                let owner = Uuid::new_v4();
+                let font = FontId::proportional(12.0);
+                let first = cache.get(painter, (owner, TextSlot::Name), "Shop", font.clone(), 100.0, str::to_string);
+                let renamed = cache.get(painter, (owner, TextSlot::Name), "Store", font, 100.0, str::to_string);
+                assert!(!Arc::ptr_eq(&first, &renamed));
+                assert_eq!(renamed.text(), "Store");
+
+                let zoomed = cache.get(painter, (owner, TextSlot::Name), "Store", FontId::proportional(24.0), 200.0, str::to_string);
+                assert!(!Arc::ptr_eq(&renamed, &zoomed));
+                assert_eq!(cache.len(), 1);
+            });
+        }
+
+        /// Verifies only the displayed text goes through `display`
+        #[test]
+        fn display_transforms_source() {
+            with_painter(|painter| {
+                let mut cache = GalleyCache::new();
+                let owner = Uuid::new_v4();
+                let galley = cache.get(
+                    painter,
+                    (owner, TextSlot::Description),
+                    "a long description",
+                    FontId::proportional(10.0),
+                    f32::INFINITY,
+                    |s| s[..6].to_string(),
+                );
+                assert_eq!(galley.text(), "a long");
+            });
+        }
+
+        /// Verifies entries not drawn in the last frame are dropped
+        #[test]
+        fn begin_frame_drops_unused_entries() {
+            with_painter(|painter| {
+                let mut cache = GalleyCache::new();
+                let (kept, dropped) = (Uuid::new_v4(), Uuid::new_v4());
+                let font = FontId::proportional(12.0);
+                cache.get(painter, (kept, TextSlot::Label), "uses", font.clone(), f32::INFINITY, str::to_string);
+                cache.get(painter, (dropped, TextSlot::Label), "calls", font.clone(), f32::INFINITY, str::to_string);
+                cache.begin_frame();
+                cache.get(painter, (kept, TextSlot::Label), "uses", font, f32::INFINITY, str::to_string);
+                cache.begin_frame();
+                assert_eq!(cache.len(), 1);
+
+                cache.begin_frame();
+                assert!(cache.is_empty());
+            });
+        }
+    }
+}
@@ -349,7 +349,7 @@ mod export_workflow_tests {
         assert!(output.starts_with("C4Context"));
         assert!(output.contains("title System Context Diagram"));
         assert!(output.contains("Person("));
-        assert!(output.contains("BiRel("));
+        assert!(output.contains("    Rel("));
     }
 
     /// Verifies Mermaid export produces valid output for container diagram
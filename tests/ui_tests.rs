@@ -6,6 +6,8 @@
 //! - Checkbox interactions
 //! - Element creation through UI
 
+#![cfg(feature = "gui")]
+
 use egui::accesskit::Toggled;
 use egui_kittest::{Harness, kittest::{Queryable, NodeT}};
 